@@ -0,0 +1,110 @@
+#!/usr/bin/env python3
+"""Pack PyTorch reference activations into golden-vector fixtures (.awmg).
+
+nojohns-training dumps one .npz per case with chain-ready tensors (already
+INT8/u16-quantized the way the on-chain kernels consume them) and the
+reference's quantized outputs. This script validates shapes and writes the
+binary fixture that `solana/programs/world-model/src/golden.rs` tests run
+against `forward_pass` / `mamba2_layer_step`.
+
+Expected .npz arrays:
+  luts               uint8  [1024]             silu | softplus | rsqrt | exp_neg
+  in_proj_{i}        int8   [2*d_inner, d_model]
+  out_proj_{i}       int8   [d_model, d_inner]
+  norm_{i}           int8   [d_model]
+  a_log_{i}          int8   [d_inner]
+  dt_bias_{i}        int8   [d_inner]
+  in_scales_{i}      uint16 [2*d_inner]        fixed-point, scale = raw / 65536
+  out_scales_{i}     uint16 [d_model]
+  input              int8   [d_model]
+  hidden             int8|int16 [n_layers, d_inner, d_state]
+  expected_output    int8   [d_model]
+  expected_hidden    same dtype/shape as hidden
+
+Usage:
+    python quantization/export_golden.py case.npz \
+        -o solana/programs/world-model/tests/golden/case.awmg --tolerance 2
+"""
+
+import argparse
+import struct
+from pathlib import Path
+
+import numpy as np
+
+MAGIC = b"AWMG"
+VERSION = 1
+LUT_TOTAL_SIZE = 1024
+HIDDEN_DTYPE_I8 = 0
+HIDDEN_DTYPE_I16 = 1
+
+
+# ── Fixture writer ───────────────────────────────────────────────────────────
+
+
+def expect(arr: np.ndarray, name: str, dtype, shape: tuple) -> np.ndarray:
+    """Check an array's dtype and shape, returning it C-contiguous."""
+    if arr.dtype != dtype or tuple(arr.shape) != shape:
+        raise ValueError(f"{name}: expected {np.dtype(dtype)}{list(shape)}, "
+                         f"got {arr.dtype}{list(arr.shape)}")
+    return np.ascontiguousarray(arr)
+
+
+def build_fixture(case: dict[str, np.ndarray], tolerance: int) -> bytes:
+    """Serialize one case into the .awmg layout documented in golden.rs."""
+    hidden = case["hidden"]
+    if hidden.ndim != 3:
+        raise ValueError(f"hidden: expected [n_layers, d_inner, d_state], got {hidden.shape}")
+    n_layers, d_inner, d_state = hidden.shape
+    d_model = case["input"].shape[0]
+    hidden_dtype = {np.dtype(np.int8): HIDDEN_DTYPE_I8,
+                    np.dtype(np.int16): HIDDEN_DTYPE_I16}[hidden.dtype]
+
+    out = bytearray()
+    out += MAGIC
+    out += struct.pack("<BBBBHHHH", VERSION, hidden_dtype, tolerance, n_layers,
+                       d_model, d_inner, d_state, 0)
+    out += expect(case["luts"], "luts", np.uint8, (LUT_TOTAL_SIZE,)).tobytes()
+
+    for i in range(n_layers):
+        out += expect(case[f"in_proj_{i}"], f"in_proj_{i}", np.int8, (2 * d_inner, d_model)).tobytes()
+        out += expect(case[f"out_proj_{i}"], f"out_proj_{i}", np.int8, (d_model, d_inner)).tobytes()
+        out += expect(case[f"norm_{i}"], f"norm_{i}", np.int8, (d_model,)).tobytes()
+        out += expect(case[f"a_log_{i}"], f"a_log_{i}", np.int8, (d_inner,)).tobytes()
+        out += expect(case[f"dt_bias_{i}"], f"dt_bias_{i}", np.int8, (d_inner,)).tobytes()
+        out += expect(case[f"in_scales_{i}"], f"in_scales_{i}", np.uint16, (2 * d_inner,)).astype("<u2").tobytes()
+        out += expect(case[f"out_scales_{i}"], f"out_scales_{i}", np.uint16, (d_model,)).astype("<u2").tobytes()
+
+    out += expect(case["input"], "input", np.int8, (d_model,)).tobytes()
+    out += hidden.astype(hidden.dtype.newbyteorder("<")).tobytes()
+    out += expect(case["expected_output"], "expected_output", np.int8, (d_model,)).tobytes()
+    out += expect(case["expected_hidden"], "expected_hidden", hidden.dtype,
+                  hidden.shape).astype(hidden.dtype.newbyteorder("<")).tobytes()
+    return bytes(out)
+
+
+# ── CLI ──────────────────────────────────────────────────────────────────────
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Export golden-vector fixtures")
+    parser.add_argument("npz", type=Path, help="Reference case from nojohns-training")
+    parser.add_argument("-o", "--output", type=Path, required=True, help="Output .awmg path")
+    parser.add_argument("--tolerance", type=int, default=0,
+                        help="Max per-element drift in INT8/INT16 units (default: exact)")
+    args = parser.parse_args()
+
+    if not 0 <= args.tolerance <= 255:
+        parser.error("--tolerance must fit in a u8")
+
+    with np.load(args.npz) as data:
+        case = {k: data[k] for k in data.files}
+    fixture = build_fixture(case, args.tolerance)
+
+    args.output.parent.mkdir(parents=True, exist_ok=True)
+    args.output.write_bytes(fixture)
+    print(f"Wrote {args.output} ({len(fixture):,} bytes, tolerance={args.tolerance})")
+
+
+if __name__ == "__main__":
+    main()
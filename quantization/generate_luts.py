@@ -6,11 +6,15 @@ Produces 256-entry LUTs for:
   - softplus(x) = ln(1 + exp(x)) — dt computation in selective scan
   - rsqrt(x) = 1/sqrt(x)       — RMSNorm
   - exp_neg(x) = exp(-x)        — A_bar in selective scan
+  - GELU(x) = x * Phi(x)        — gate activation (v2)
+  - tanh(x)                     — output squashing (v2)
 
 Each LUT maps an INT8 input (-128..127) to an INT8 output, using configurable
 input/output scales. The LUTs are stored as 256-byte arrays.
 
-Output: luts.bin (binary, 4 × 256 = 1024 bytes) + luts.json (metadata)
+Output: luts.bin (binary, 6 × 256 = 1536 bytes) + luts.json (metadata).
+This is a v2 block; its first 1024 bytes are the v1 block (the first four
+LUTs), which is what init_manifest takes. set_extended_luts takes the rest.
 """
 
 import argparse
@@ -88,6 +92,29 @@ def compute_exp_neg_lut(input_scale: float, output_scale: float) -> np.ndarray:
     return lut
 
 
+def compute_gelu_lut(input_scale: float, output_scale: float) -> np.ndarray:
+    """GELU(x), tanh approximation: 0.5x(1 + tanh(sqrt(2/pi)(x + 0.044715x^3)))"""
+    lut = np.zeros(256, dtype=np.int8)
+    for i in range(256):
+        int8_val = np.int8(i if i < 128 else i - 256)
+        x = float(int8_val) * input_scale
+        gelu = 0.5 * x * (1.0 + np.tanh(0.7978845608 * (x + 0.044715 * x * x * x)))
+        quantized = int(np.round(gelu / output_scale))
+        lut[i] = np.clip(quantized, -128, 127).astype(np.int8)
+    return lut
+
+
+def compute_tanh_lut(input_scale: float, output_scale: float) -> np.ndarray:
+    """tanh(x) for output squashing"""
+    lut = np.zeros(256, dtype=np.int8)
+    for i in range(256):
+        int8_val = np.int8(i if i < 128 else i - 256)
+        x = float(int8_val) * input_scale
+        quantized = int(np.round(np.tanh(x) / output_scale))
+        lut[i] = np.clip(quantized, -128, 127).astype(np.int8)
+    return lut
+
+
 def main():
     parser = argparse.ArgumentParser(description="Generate INT8 activation LUTs for Mamba2")
     parser.add_argument(
@@ -128,6 +155,22 @@ def main():
         "--exp-output-scale", type=float, default=0.00392157,
         help="Output scale for exp(-x) (maps 0..1 to 0..255, ~1/255)",
     )
+    parser.add_argument(
+        "--gelu-input-scale", type=float, default=0.0625,
+        help="Input dequantization scale for GELU (default: 1/16)",
+    )
+    parser.add_argument(
+        "--gelu-output-scale", type=float, default=0.0625,
+        help="Output quantization scale for GELU",
+    )
+    parser.add_argument(
+        "--tanh-input-scale", type=float, default=0.03125,
+        help="Input dequantization scale for tanh (maps -128..127 to -4..4)",
+    )
+    parser.add_argument(
+        "--tanh-output-scale", type=float, default=0.0078125,
+        help="Output quantization scale for tanh (maps -1..1 to -128..127, 1/128)",
+    )
     args = parser.parse_args()
 
     args.output_dir.mkdir(parents=True, exist_ok=True)
@@ -139,6 +182,8 @@ def main():
     softplus_lut = compute_softplus_lut(args.softplus_input_scale, args.softplus_output_scale)
     rsqrt_lut = compute_rsqrt_lut(args.rsqrt_input_scale, args.rsqrt_output_scale)
     exp_neg_lut = compute_exp_neg_lut(args.exp_input_scale, args.exp_output_scale)
+    gelu_lut = compute_gelu_lut(args.gelu_input_scale, args.gelu_output_scale)
+    tanh_lut = compute_tanh_lut(args.tanh_input_scale, args.tanh_output_scale)
 
     # Print sample values for verification
     print("\nSiLU LUT samples (index → output):")
@@ -156,22 +201,24 @@ def main():
         out = np.int8(softplus_lut[idx] if softplus_lut[idx] < 128 else softplus_lut[idx] - 256)
         print(f"  [{idx:3d}] int8={int8_in:+4d} → x={x:+7.3f} → softplus={sp:+7.3f} → int8={out:+4d}")
 
-    # Write binary file: 4 LUTs × 256 bytes = 1024 bytes
+    # Write binary file: 6 LUTs × 256 bytes = 1536 bytes
     bin_path = args.output_dir / "luts.bin"
     with open(bin_path, "wb") as f:
         f.write(silu_lut.tobytes())
         f.write(softplus_lut.tobytes())
         f.write(rsqrt_lut.view(np.uint8).tobytes())
         f.write(exp_neg_lut.view(np.uint8).tobytes())
+        f.write(gelu_lut.tobytes())
+        f.write(tanh_lut.tobytes())
 
     print(f"\nBinary LUTs written to {bin_path} ({bin_path.stat().st_size} bytes)")
 
     # Write metadata JSON
     metadata = {
-        "format": "int8_luts_v1",
-        "num_luts": 4,
+        "format": "int8_luts_v2",
+        "num_luts": 6,
         "lut_size": 256,
-        "total_bytes": 1024,
+        "total_bytes": 1536,
         "luts": [
             {
                 "name": "silu",
@@ -213,6 +260,26 @@ def main():
                 "output_scale": args.exp_output_scale,
                 "description": "exp(-x), A_bar decay in selective scan",
             },
+            {
+                "name": "gelu",
+                "offset": 1024,
+                "size": 256,
+                "input_signed": True,
+                "output_signed": True,
+                "input_scale": args.gelu_input_scale,
+                "output_scale": args.gelu_output_scale,
+                "description": "GELU(x) = x * Phi(x), tanh approximation, gate activation",
+            },
+            {
+                "name": "tanh",
+                "offset": 1280,
+                "size": 256,
+                "input_signed": True,
+                "output_signed": True,
+                "input_scale": args.tanh_input_scale,
+                "output_scale": args.tanh_output_scale,
+                "description": "tanh(x), output squashing",
+            },
         ],
     }
 
@@ -228,6 +295,8 @@ def main():
     print(f"  Softplus range: [{softplus_lut.min()}, {softplus_lut.max()}]")
     print(f"  rsqrt range: [{rsqrt_lut.min()}, {rsqrt_lut.max()}]")
     print(f"  exp_neg range: [{exp_neg_lut.min()}, {exp_neg_lut.max()}]")
+    print(f"  GELU range: [{gelu_lut.min()}, {gelu_lut.max()}]")
+    print(f"  tanh range: [{tanh_lut.min()}, {tanh_lut.max()}]")
 
     # Check softplus is monotonically non-decreasing (signed interpretation)
     sp_signed = softplus_lut.view(np.int8)
@@ -0,0 +1,588 @@
+//! Convert a quantize.py export to the on-chain shard layout.
+//!
+//! quantize.py packs every tensor of the PyTorch checkpoint in key order.
+//! The program's forward pass only reads six tensors per layer, in the
+//! kernel's shapes, so conversion re-packs just those:
+//!   - in_proj:  `layers.{i}.mamba.in_proj.weight` up to its dt rows (the
+//!     z and x projections, then B and C for each of the ngroups head
+//!     groups; ngroups is inferred from the row count)
+//!   - out_proj: `layers.{i}.mamba.out_proj.weight` as is
+//!   - norm:     `layers.{i}.norm.weight` (the pre-norm over d_model)
+//!   - A_log, dt_bias: per-head values repeated across each head's channels
+//!   - dt_proj:  the last nheads rows of in_proj (one dt per head)
+//!
+//! in_proj and out_proj are followed by row sparsity masks (and marked
+//! TENSOR_DTYPE_I8_MASKED) when structured pruning has zeroed at least a
+//! quarter of their 4-byte column groups.
+//!
+//! An exported `action_embed.weight` ([classes, width]) is stored
+//! transposed as the action embedding, one column per class, and the
+//! action-state logit head is tied to it rather than stored again.
+//!
+//! Tensors are packed greedily into shards of at most `max_shard_size`
+//! bytes, never straddling a shard boundary. Each shard opens with a
+//! named-tensor directory (`layers.{i}.in_proj`, ...) that the forward pass
+//! resolves tensors through, and the same locations go to layout.json as
+//! the tensor table for `set_tensor_table`.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::solana_program::hash::hash;
+use anyhow::{bail, ensure, Context, Result};
+use awm_client::state::*;
+use awm_kernel::{matmul, tensor_dir};
+use serde::{Deserialize, Serialize};
+
+/// Default shard size, matching quantize.py's ~4 MB target.
+pub const DEFAULT_MAX_SHARD_SIZE: usize = 4 * 1024 * 1024;
+
+/// Export key of the action-state embedding table.
+const ACTION_EMBED_KEY: &str = "action_embed.weight";
+
+/// A projection with at least 1 in this many 4-byte column groups zeroed
+/// (structured pruning) is stored with row sparsity masks.
+const MASK_MIN_PRUNED_FRACTION: usize = 4;
+
+// ── quantize.py manifest.json ────────────────────────────────────────────────
+
+#[derive(Deserialize, Debug)]
+pub struct ExportManifest {
+    pub format: String,
+    pub architecture: Architecture,
+    pub weights: ExportWeights,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Architecture {
+    pub model_type: String,
+    pub d_model: usize,
+    pub d_inner: usize,
+    pub d_state: usize,
+    pub n_layers: usize,
+    pub nheads: usize,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExportWeights {
+    pub layer_weights: BTreeMap<String, WeightEntry>,
+}
+
+/// One tensor's position in weights_int8.bin.
+#[derive(Deserialize, Debug)]
+pub struct WeightEntry {
+    pub offset: usize,
+    pub size: usize,
+    pub shape: Vec<usize>,
+}
+
+// ── layout.json ──────────────────────────────────────────────────────────────
+
+/// Converted model: architecture, shard sizes and hashes, tensor table.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ShardLayout {
+    pub d_model: u16,
+    pub d_inner: u16,
+    pub d_state: u16,
+    pub num_layers: u8,
+    pub num_heads: u8,
+    /// B/C head groups (absent from layouts converted before grouped B/C)
+    #[serde(default)]
+    pub ngroups: u8,
+    pub total_weight_bytes: u32,
+    pub shards: Vec<ShardInfo>,
+    /// tensor_table[layer][TENSOR_*]
+    pub tensor_table: Vec<Vec<TensorEntry>>,
+    /// Action-state embedding, [width × classes], the logit head tied to it
+    /// (absent when the export has no embedding)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_embedding: Option<TensorEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ShardInfo {
+    pub index: u8,
+    pub size: u32,
+    /// Hex SHA-256 of the shard data, as finalize_weights checks it
+    pub sha256: String,
+}
+
+/// Serde mirror of `TensorRef`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TensorEntry {
+    pub shard: u8,
+    pub offset: u32,
+    pub rows: u16,
+    pub cols: u16,
+    /// TENSOR_DTYPE_* (absent from layouts converted before sparsity masks)
+    #[serde(default)]
+    pub dtype: u8,
+}
+
+impl TensorEntry {
+    pub fn to_tensor_ref(self) -> TensorRef {
+        TensorRef {
+            shard: self.shard,
+            dtype: self.dtype,
+            offset: self.offset,
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+impl ShardLayout {
+    /// One layer's table entries, ready for set_tensor_table.
+    pub fn layer_tensors(&self, layer: usize) -> Result<[TensorRef; TENSORS_PER_LAYER]> {
+        let entries = self
+            .tensor_table
+            .get(layer)
+            .with_context(|| format!("layout has no layer {}", layer))?;
+        // Layouts converted before dt_proj end at DT_BIAS; their dt_proj
+        // slot stays unset
+        ensure!(
+            entries.len() == TENSORS_PER_LAYER || entries.len() == TENSOR_DT_PROJ,
+            "layer {} has {} tensors, expected {}",
+            layer,
+            entries.len(),
+            TENSORS_PER_LAYER
+        );
+        Ok(core::array::from_fn(|slot| {
+            entries.get(slot).map_or_else(TensorRef::default, |e| e.to_tensor_ref())
+        }))
+    }
+
+    /// The action embedding and its tied logit head, ready for
+    /// set_action_tensors.
+    pub fn action_tensors(&self) -> Option<(TensorRef, TensorRef)> {
+        let embedding = self.action_embedding?.to_tensor_ref();
+        let head = TensorRef {
+            dtype: TENSOR_DTYPE_TIED,
+            rows: embedding.cols,
+            cols: embedding.rows,
+            ..TensorRef::default()
+        };
+        Some((embedding, head))
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ── Conversion ───────────────────────────────────────────────────────────────
+
+/// Packs tensors into shards without splitting any tensor, behind a
+/// named-tensor directory (awm_kernel::tensor_dir) at the head of each.
+struct ShardPacker {
+    max_shard_size: usize,
+    shards: Vec<Vec<u8>>,
+    /// Directory entries of each shard, offsets relative to its tensor bytes
+    names: Vec<Vec<tensor_dir::TensorEntry>>,
+}
+
+impl ShardPacker {
+    fn push(&mut self, name: u64, data: &[u8], rows: usize, cols: usize) -> Result<TensorEntry> {
+        self.push_typed(name, data, rows, cols, TENSOR_DTYPE_I8)
+    }
+
+    /// Push a projection, followed by its row sparsity masks if enough of
+    /// it is pruned for the masked matmul to pay off.
+    fn push_projection(
+        &mut self,
+        name: u64,
+        weights: &[u8],
+        rows: usize,
+        cols: usize,
+    ) -> Result<TensorEntry> {
+        let mut masks = vec![0u8; rows * matmul::mask_stride(cols)];
+        matmul::build_row_masks(weights, &mut masks, rows, cols);
+        let groups = rows * cols.div_ceil(4);
+        let kept: usize = masks.iter().map(|m| m.count_ones() as usize).sum();
+        if (groups - kept) * MASK_MIN_PRUNED_FRACTION < groups {
+            return self.push(name, weights, rows, cols);
+        }
+        let data = [weights, &masks].concat();
+        self.push_typed(name, &data, rows, cols, TENSOR_DTYPE_I8_MASKED)
+    }
+
+    fn push_typed(
+        &mut self,
+        name: u64,
+        data: &[u8],
+        rows: usize,
+        cols: usize,
+        dtype: u8,
+    ) -> Result<TensorEntry> {
+        ensure!(
+            tensor_dir::directory_size(1) + data.len() <= self.max_shard_size,
+            "{}-byte tensor exceeds the {}-byte shard size",
+            data.len(),
+            self.max_shard_size
+        );
+        let full = self.shards.last().zip(self.names.last()).map_or(true, |(s, names)| {
+            tensor_dir::directory_size(names.len() + 1) + s.len() + data.len() > self.max_shard_size
+        });
+        if full {
+            ensure!(self.shards.len() < MAX_SHARDS, "model needs more than {} shards", MAX_SHARDS);
+            self.shards.push(Vec::new());
+            self.names.push(Vec::new());
+        }
+        let shard = self.shards.last_mut().unwrap();
+        let entry = TensorEntry {
+            shard: (self.shards.len() - 1) as u8,
+            offset: shard.len() as u32,
+            rows: u16::try_from(rows).context("tensor rows exceed u16")?,
+            cols: u16::try_from(cols).context("tensor cols exceed u16")?,
+            dtype,
+        };
+        self.names.last_mut().unwrap().push(tensor_dir::TensorEntry {
+            name_hash: name,
+            offset: entry.offset,
+            rows: entry.rows,
+            cols: entry.cols,
+            dtype,
+        });
+        shard.extend_from_slice(data);
+        Ok(entry)
+    }
+
+    /// Prefix each shard with its directory. Returns the shards and how far
+    /// each one's tensors moved.
+    fn finish(self) -> (Vec<Vec<u8>>, Vec<u32>) {
+        self.shards
+            .into_iter()
+            .zip(self.names)
+            .map(|(data, mut names)| {
+                let shift = tensor_dir::directory_size(names.len());
+                for e in &mut names {
+                    e.offset += shift as u32;
+                }
+                let mut shard = vec![0u8; shift];
+                tensor_dir::write_directory(&mut names, &mut shard)
+                    .expect("buffer sized for the directory");
+                shard.extend_from_slice(&data);
+                (shard, shift as u32)
+            })
+            .unzip()
+    }
+}
+
+/// B/C head groups of an export, from layer 0's in_proj rows:
+/// 2*d_inner (z, x) + 2 * ngroups * d_state (B, C) + nheads (dt).
+fn bc_groups(export: &ExportManifest) -> Result<usize> {
+    let arch = &export.architecture;
+    let rows = export
+        .weights
+        .layer_weights
+        .get("layers.0.mamba.in_proj.weight")
+        .and_then(|e| e.shape.first().copied())
+        .context("export is missing layers.0.mamba.in_proj.weight")?;
+    let bc_rows = rows
+        .checked_sub(2 * arch.d_inner + arch.nheads)
+        .context("in_proj has fewer rows than z, x and dt need")?;
+    ensure!(
+        arch.d_state > 0 && bc_rows % (2 * arch.d_state) == 0,
+        "in_proj's {} B/C rows aren't a whole number of d_state={} groups",
+        bc_rows,
+        arch.d_state
+    );
+    let ngroups = bc_rows / (2 * arch.d_state);
+    ensure!(
+        ngroups == 0 || arch.nheads % ngroups == 0,
+        "{} heads don't split into {} groups",
+        arch.nheads,
+        ngroups
+    );
+    Ok(ngroups)
+}
+
+/// Re-pack the per-layer tensors of a quantize.py export. Returns the layout
+/// and the shard data in index order.
+pub fn convert(
+    export: &ExportManifest,
+    weights: &[u8],
+    max_shard_size: usize,
+) -> Result<(ShardLayout, Vec<Vec<u8>>)> {
+    let arch = &export.architecture;
+    ensure!(
+        arch.model_type == "mamba2",
+        "only mamba2 exports can run on chain (got {})",
+        arch.model_type
+    );
+    ensure!(arch.n_layers <= MAX_LAYERS, "{} layers exceeds MAX_LAYERS", arch.n_layers);
+    ensure!(
+        arch.nheads > 0 && arch.d_inner % arch.nheads == 0,
+        "d_inner {} doesn't split into {} heads",
+        arch.d_inner,
+        arch.nheads
+    );
+    let (d_model, d_inner) = (arch.d_model, arch.d_inner);
+    let headdim = d_inner / arch.nheads;
+    let ngroups = bc_groups(export)?;
+    let in_rows = 2 * d_inner + 2 * ngroups * arch.d_state;
+
+    let tensor = |key: &str, shape: &[usize]| -> Result<&[u8]> {
+        let entry = export
+            .weights
+            .layer_weights
+            .get(key)
+            .with_context(|| format!("export is missing {}", key))?;
+        ensure!(
+            entry.shape.len() == shape.len()
+                && entry.shape.iter().zip(shape).all(|(&got, &min)| got >= min),
+            "{} has shape {:?}, expected at least {:?}",
+            key,
+            entry.shape,
+            shape
+        );
+        weights
+            .get(entry.offset..entry.offset + entry.size)
+            .with_context(|| format!("{} lies outside weights_int8.bin", key))
+    };
+    let per_head = |key: &str| -> Result<Vec<u8>> {
+        let heads = tensor(key, &[arch.nheads])?;
+        Ok(heads.iter().flat_map(|&v| std::iter::repeat(v).take(headdim)).collect())
+    };
+
+    let mut packer = ShardPacker { max_shard_size, shards: Vec::new(), names: Vec::new() };
+    let mut tensor_table = Vec::with_capacity(arch.n_layers);
+    for layer in 0..arch.n_layers {
+        let prefix = format!("layers.{}", layer);
+
+        let in_proj = tensor(
+            &format!("{}.mamba.in_proj.weight", prefix),
+            &[in_rows + arch.nheads, d_model],
+        )?;
+        let dt_proj = &in_proj[in_proj.len() - arch.nheads * d_model..];
+        let out_proj = tensor(&format!("{}.mamba.out_proj.weight", prefix), &[d_model, d_inner])?;
+        let norm = tensor(&format!("{}.norm.weight", prefix), &[d_model])?;
+        let a_log = per_head(&format!("{}.mamba.A_log", prefix))?;
+        let dt_bias = per_head(&format!("{}.mamba.dt_bias", prefix))?;
+
+        // Slot order is TENSOR_IN_PROJ, OUT_PROJ, NORM, A_LOG, DT_BIAS, DT_PROJ
+        let name = |slot: usize| tensor_dir::layer_tensor_hash(layer, TENSOR_NAMES[slot]);
+        tensor_table.push(vec![
+            packer.push_projection(
+                name(TENSOR_IN_PROJ),
+                &in_proj[..in_rows * d_model],
+                in_rows,
+                d_model,
+            )?,
+            packer.push_projection(
+                name(TENSOR_OUT_PROJ),
+                &out_proj[..d_model * d_inner],
+                d_model,
+                d_inner,
+            )?,
+            packer.push(name(TENSOR_NORM), &norm[..d_model], d_model, 1)?,
+            packer.push(name(TENSOR_A_LOG), &a_log, d_inner, 1)?,
+            packer.push(name(TENSOR_DT_BIAS), &dt_bias, d_inner, 1)?,
+            packer.push(name(TENSOR_DT_PROJ), dt_proj, arch.nheads, d_model)?,
+        ]);
+    }
+
+    // One column per class, so the tied head reads it back transposed
+    let mut action_embedding = match export.weights.layer_weights.get(ACTION_EMBED_KEY) {
+        Some(entry) => {
+            let &[classes, width] = &entry.shape[..] else {
+                bail!(
+                    "{} has shape {:?}, expected [classes, width]",
+                    ACTION_EMBED_KEY,
+                    entry.shape
+                );
+            };
+            let table = tensor(ACTION_EMBED_KEY, &[classes, width])?;
+            let columns: Vec<u8> = (0..width * classes)
+                .map(|k| table[(k % classes) * width + k / classes])
+                .collect();
+            let name = tensor_dir::name_hash(b"action_embedding");
+            Some(packer.push(name, &columns, width, classes)?)
+        }
+        None => None,
+    };
+
+    // The table points at the same bytes as the directories
+    let (shards, shifts) = packer.finish();
+    for entry in tensor_table.iter_mut().flatten().chain(&mut action_embedding) {
+        entry.offset += shifts[entry.shard as usize];
+    }
+    let total: usize = shards.iter().map(|s| s.len()).sum();
+    let layout = ShardLayout {
+        d_model: u16::try_from(d_model)?,
+        d_inner: u16::try_from(d_inner)?,
+        d_state: u16::try_from(arch.d_state)?,
+        num_layers: arch.n_layers as u8,
+        num_heads: u8::try_from(arch.nheads)?,
+        ngroups: u8::try_from(ngroups)?,
+        total_weight_bytes: u32::try_from(total)?,
+        shards: shards
+            .iter()
+            .enumerate()
+            .map(|(i, s)| ShardInfo {
+                index: i as u8,
+                size: s.len() as u32,
+                sha256: hex(&hash(s).to_bytes()),
+            })
+            .collect(),
+        tensor_table,
+        action_embedding,
+    };
+    if layout.shards.is_empty() {
+        bail!("export has no layers");
+    }
+    Ok((layout, shards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const D_MODEL: usize = 4;
+    const D_INNER: usize = 8;
+    const NHEADS: usize = 2;
+
+    /// A one-layer export laid out like quantize.py's (sorted keys, with
+    /// B/C/dt rows in in_proj and a tensor the chain doesn't use).
+    fn export() -> (ExportManifest, Vec<u8>) {
+        let d_in_proj = 2 * D_INNER + 2 * 2 + NHEADS;
+        let tensors: [(&str, Vec<usize>); 6] = [
+            ("layers.0.mamba.A_log", vec![NHEADS]),
+            ("layers.0.mamba.D", vec![NHEADS]),
+            ("layers.0.mamba.dt_bias", vec![NHEADS]),
+            ("layers.0.mamba.in_proj.weight", vec![d_in_proj, D_MODEL]),
+            ("layers.0.mamba.out_proj.weight", vec![D_MODEL, D_INNER]),
+            ("layers.0.norm.weight", vec![D_MODEL]),
+        ];
+        let mut weights = Vec::new();
+        let mut layer_weights = BTreeMap::new();
+        for (i, (key, shape)) in tensors.into_iter().enumerate() {
+            let size: usize = shape.iter().product();
+            let offset = weights.len();
+            weights.extend((0..size).map(|j| (i * 40 + j) as u8));
+            layer_weights.insert(key.to_string(), WeightEntry { offset, size, shape });
+        }
+        let manifest = ExportManifest {
+            format: "mamba2_int8_v1".into(),
+            architecture: Architecture {
+                model_type: "mamba2".into(),
+                d_model: D_MODEL,
+                d_inner: D_INNER,
+                d_state: 2,
+                n_layers: 1,
+                nheads: NHEADS,
+            },
+            weights: ExportWeights { layer_weights },
+        };
+        (manifest, weights)
+    }
+
+    #[test]
+    fn test_convert_slices_and_broadcasts() {
+        let (manifest, weights) = export();
+        let (layout, shards) = convert(&manifest, &weights, DEFAULT_MAX_SHARD_SIZE).unwrap();
+        assert_eq!(shards.len(), 1);
+        let shard_refs: Vec<&[u8]> = shards.iter().map(|s| s.as_slice()).collect();
+        let tensors = layout.layer_tensors(0).unwrap();
+
+        // in_proj keeps the z, x and B/C rows of its one head group
+        assert_eq!(layout.ngroups, 1);
+        let in_proj = tensors[TENSOR_IN_PROJ].resolve(&shard_refs).unwrap();
+        assert_eq!(tensors[TENSOR_IN_PROJ].rows as usize, 2 * D_INNER + 2 * 2);
+        assert_eq!(in_proj.len(), (2 * D_INNER + 2 * 2) * D_MODEL);
+        assert_eq!(in_proj[0], 3 * 40);
+
+        // A_log's two heads each cover four channels
+        let a_log = tensors[TENSOR_A_LOG].resolve(&shard_refs).unwrap();
+        assert_eq!(a_log, &[0, 0, 0, 0, 1, 1, 1, 1]);
+        let dt_bias = tensors[TENSOR_DT_BIAS].resolve(&shard_refs).unwrap();
+        assert_eq!(dt_bias, &[80, 80, 80, 80, 81, 81, 81, 81]);
+
+        // dt_proj is in_proj's trailing dt rows, one per head
+        let dt_proj = tensors[TENSOR_DT_PROJ].resolve(&shard_refs).unwrap();
+        assert_eq!((tensors[TENSOR_DT_PROJ].rows, tensors[TENSOR_DT_PROJ].cols), (2, 4));
+        assert_eq!(dt_proj, &(200..208).collect::<Vec<u8>>()[..]);
+
+        assert_eq!(tensors[TENSOR_NORM].resolve(&shard_refs).unwrap(), &[200, 201, 202, 203]);
+        assert_eq!(layout.total_weight_bytes as usize, shards[0].len());
+        assert_eq!(layout.shards[0].sha256, hex(&hash(&shards[0]).to_bytes()));
+    }
+
+    #[test]
+    fn test_tensors_never_straddle_shards() {
+        let (manifest, weights) = export();
+        // Directories take 8 + 20 bytes per tensor: in_proj (80) needs 108
+        // bytes alone, and out_proj (32) can't join it in a 112-byte shard
+        let (layout, shards) = convert(&manifest, &weights, 112).unwrap();
+        assert_eq!(shards.len(), 3);
+        assert!(shards.iter().all(|s| s.len() <= 112));
+        let slots: Vec<(u8, u32)> = layout.tensor_table[0].iter().map(|t| (t.shard, t.offset)).collect();
+        assert_eq!(slots, vec![(0, 28), (1, 68), (1, 100), (1, 104), (2, 48), (2, 56)]);
+        assert!(convert(&manifest, &weights, 107).is_err());
+    }
+
+    #[test]
+    fn test_pruned_projections_carry_row_masks() {
+        let (manifest, mut weights) = export();
+        // Zero every other 4-byte group of out_proj (8 columns, 2 groups a row)
+        let out = &manifest.weights.layer_weights["layers.0.mamba.out_proj.weight"];
+        for row in weights[out.offset..out.offset + out.size].chunks_mut(D_INNER) {
+            row[4..].fill(0);
+        }
+        let (layout, shards) = convert(&manifest, &weights, DEFAULT_MAX_SHARD_SIZE).unwrap();
+        let shard_refs: Vec<&[u8]> = shards.iter().map(|s| s.as_slice()).collect();
+        let tensors = layout.layer_tensors(0).unwrap();
+
+        assert_eq!(tensors[TENSOR_IN_PROJ].dtype, TENSOR_DTYPE_I8);
+        assert_eq!(tensors[TENSOR_OUT_PROJ].dtype, TENSOR_DTYPE_I8_MASKED);
+        let mask = tensors[TENSOR_OUT_PROJ].resolve_mask(&shard_refs).unwrap();
+        assert_eq!(mask, &[0b01; D_MODEL]);
+        // The next tensor starts after the masks
+        assert_eq!(
+            tensors[TENSOR_NORM].offset,
+            tensors[TENSOR_OUT_PROJ].offset + (D_MODEL * D_INNER + D_MODEL) as u32
+        );
+    }
+
+    #[test]
+    fn test_shard_directories_name_every_tensor() {
+        let (manifest, weights) = export();
+        let (layout, shards) = convert(&manifest, &weights, 112).unwrap();
+        let shard_refs: Vec<&[u8]> = shards.iter().map(|s| s.as_slice()).collect();
+        let tensors = layout.layer_tensors(0).unwrap();
+        for (slot, t) in tensors.iter().enumerate() {
+            let dir = tensor_dir::TensorDirectory::parse(&shards[t.shard as usize])
+                .unwrap()
+                .expect("every shard has a directory");
+            let named = dir.resolve(tensor_dir::layer_tensor_hash(0, TENSOR_NAMES[slot]));
+            assert_eq!(named, t.resolve(&shard_refs), "slot {}", slot);
+        }
+    }
+
+    #[test]
+    fn test_action_embedding_is_stored_once_for_a_tied_head() {
+        let (mut manifest, mut weights) = export();
+        let (plain, plain_shards) = convert(&manifest, &weights, DEFAULT_MAX_SHARD_SIZE).unwrap();
+        assert_eq!(plain.action_tensors(), None);
+
+        // Three classes, two slots each: class c's embedding is [10c, 10c + 1]
+        let (classes, width) = (3, 2);
+        let offset = weights.len();
+        weights.extend((0..classes).flat_map(|c| [10 * c as u8, 10 * c as u8 + 1]));
+        manifest.weights.layer_weights.insert(
+            ACTION_EMBED_KEY.to_string(),
+            WeightEntry { offset, size: classes * width, shape: vec![classes, width] },
+        );
+        let (layout, shards) = convert(&manifest, &weights, DEFAULT_MAX_SHARD_SIZE).unwrap();
+        let shard_refs: Vec<&[u8]> = shards.iter().map(|s| s.as_slice()).collect();
+
+        // Stored one column per class, and only once: the head is an alias
+        let (embedding, head) = layout.action_tensors().unwrap();
+        assert_eq!((embedding.rows, embedding.cols), (2, 3));
+        assert_eq!(embedding.resolve(&shard_refs).unwrap(), &[0, 10, 20, 1, 11, 21]);
+        assert_eq!((head.dtype, head.rows, head.cols), (TENSOR_DTYPE_TIED, 3, 2));
+        assert_eq!(
+            shards[0].len(),
+            plain_shards[0].len() + tensor_dir::ENTRY_SIZE + classes * width
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Human-readable dumps of manifest, session, weight and calibration
+//! accounts.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use awm_client::decode;
+use awm_client::state::*;
+use awm_kernel::lut::validate_luts;
+
+use crate::convert::hex;
+
+/// Zero-padded byte string → text.
+fn padded_str(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn optional_key(key: &Pubkey) -> String {
+    if *key == Pubkey::default() {
+        "-".to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+pub fn manifest(data: &[u8]) -> Result<()> {
+    let m: ModelManifestAccount = decode::account(data)?;
+
+    println!("Manifest \"{}\" v{}", padded_str(&m.name), m.version);
+    println!("  authority:        {}", m.authority);
+    println!("  pending_authority {}", optional_key(&m.pending_authority));
+    println!(
+        "  architecture:     d_model={} d_inner={} d_state={} layers={} heads={} groups={} hidden={}",
+        m.d_model,
+        m.d_inner,
+        m.d_state,
+        m.num_layers,
+        m.num_heads,
+        m.ngroups,
+        if m.hidden_dtype == HIDDEN_DTYPE_I16 { "i16" } else { "i8" }
+    );
+    println!(
+        "  shards:           {}/{} registered, {}/{} bytes, directory {}",
+        m.num_shards,
+        m.expected_shards,
+        m.registered_weight_bytes,
+        m.total_weight_bytes,
+        m.shard_directory
+    );
+    if m.num_experts > 0 {
+        println!("  experts:          {} (table {})", m.num_experts, m.expert_table);
+    }
+    if m.attention_layers != 0 {
+        println!(
+            "  attention:        layers {:#06x}, window {}",
+            m.attention_layers, m.attn_window
+        );
+    }
+    if m.requant_mode == REQUANT_STOCHASTIC {
+        println!("  requantization:   stochastic");
+    }
+    if m.head_stride != 0 {
+        let heads: Vec<String> = m
+            .head_offsets
+            .iter()
+            .enumerate()
+            .filter(|(_, &o)| o != 0)
+            .map(|(c, o)| format!("{}@{}", c, o))
+            .collect();
+        println!(
+            "  character heads:  stride {}, offsets [{}]",
+            m.head_stride,
+            heads.join(" ")
+        );
+    }
+    if m.output_smoothing.iter().any(|&w| w != 0) {
+        let weights: Vec<String> = m
+            .output_smoothing
+            .iter()
+            .enumerate()
+            .filter(|(_, &w)| w != 0)
+            .map(|(f, w)| format!("{}:{}/256", f, w))
+            .collect();
+        println!("  output smoothing: fields [{}]", weights.join(" "));
+    }
+    println!(
+        "  ready={} deprecated={} superseded_by={} forked_from={}",
+        m.ready,
+        m.deprecated,
+        optional_key(&m.superseded_by),
+        optional_key(&m.forked_from)
+    );
+    println!(
+        "  session_fee={} fee_mint={} cartridge_mint={} gate_mint={} (min {})",
+        m.session_fee,
+        optional_key(&m.fee_mint),
+        optional_key(&m.cartridge_mint),
+        optional_key(&m.gate_mint),
+        m.gate_min_amount
+    );
+    println!("  settlement_verifier {}", optional_key(&m.settlement_verifier));
+    println!(
+        "  encoder: input_size={} (continuous={} binary={}) player_fields={} global_fields={}",
+        m.input_size,
+        m.num_continuous,
+        m.num_binary,
+        m.encoder_spec.num_player_fields,
+        m.encoder_spec.num_global_fields
+    );
+    match validate_luts(m.lut_block()) {
+        Ok(()) => println!("  luts: v{} valid", m.lut_version),
+        Err(e) => println!("  luts: v{} INVALID ({:?})", m.lut_version, e),
+    }
+    let failures = m.consistency_failures();
+    if failures == 0 {
+        println!("  consistency: ok");
+    } else {
+        println!("  consistency: failed checks {:#06b}", failures);
+    }
+
+    println!("  tensor table (shard:offset rows×cols):");
+    for layer in 0..(m.num_layers as usize).min(MAX_LAYERS) {
+        let row: Vec<String> = m.tensor_table[layer]
+            .iter()
+            .map(|t| format!("{}:{} {}×{}", t.shard, t.offset, t.rows, t.cols))
+            .collect();
+        println!(
+            "    layer {:2} [{}] scales in={} out={}",
+            layer,
+            row.join(", "),
+            m.layer_input_scales[layer],
+            m.layer_output_scales[layer]
+        );
+    }
+    let (e, head) = (m.action_embedding, m.action_head);
+    if e.rows != 0 {
+        let tied = if head.dtype == TENSOR_DTYPE_TIED { " (head tied)" } else { "" };
+        println!("  action embedding: {}:{} {}×{}{}", e.shard, e.offset, e.rows, e.cols, tied);
+    }
+
+    println!("  metadata_uri: {}", padded_str(&m.metadata_uri));
+    println!("  dataset_hash: {}", hex(&m.dataset_hash));
+    println!("  training_commit: {}", hex(&m.training_commit));
+    Ok(())
+}
+
+pub fn session(data: &[u8]) -> Result<()> {
+    let s = decode::session_state(data)?;
+    let status = match s.status {
+        STATUS_WAITING_PLAYERS => "waiting for players",
+        STATUS_ACTIVE => "active",
+        STATUS_ENDED => "ended",
+        _ => "unknown",
+    };
+
+    println!("Session ({}), stage {}", status, s.stage);
+    println!("  frame:        {}/{}", s.frame, s.max_frames);
+    println!("  created_at:   {}  last_update: {}", s.created_at, s.last_update);
+    println!("  player1:      {}", s.player1);
+    println!("  player2:      {}", optional_key(&s.player2));
+    println!("  model:        {}", s.model);
+    println!("  hidden_state: {}", s.hidden_state);
+    println!("  input_buffer: {}", s.input_buffer);
+    println!("  seed={} fees_collected={}", s.seed, s.fees_collected);
+    match s.crank_mode {
+        CRANK_MODE_DESIGNATED => println!("  crank mode:   designated ({})", s.crank_authority),
+        CRANK_MODE_PLAYERS => println!("  crank mode:   players only"),
+        _ => println!("  crank mode:   permissionless"),
+    }
+    if s.forfeited_by != 0 {
+        println!("  forfeited by: p{}", s.forfeited_by);
+    }
+    println!("  state_commitment:  {}", hex(&s.state_commitment));
+    println!("  hidden_state_hash: {}", hex(&s.hidden_state_hash));
+    for (slot, p) in s.players.iter().enumerate() {
+        println!(
+            "  p{}: char={} x={:.2} y={:.2} {}% stocks={} action={} age={} facing={} ground={}",
+            slot + 1,
+            p.character,
+            p.x as f64 / 256.0,
+            p.y as f64 / 256.0,
+            p.percent,
+            p.stocks,
+            p.action_state,
+            p.state_age,
+            p.facing,
+            p.on_ground
+        );
+    }
+    for c in s.crankers.iter().filter(|c| c.frames > 0) {
+        println!("  cranker {}: {} frames", c.cranker, c.frames);
+    }
+    Ok(())
+}
+
+pub fn weights(data: &[u8]) -> Result<()> {
+    let w: WeightAccount = decode::account(data)?;
+    println!("Weight shard {}", w.shard_index);
+    println!("  authority:     {}", w.authority);
+    println!("  data_size:     {}", w.data_size);
+    println!("  bytes_written: {}", w.bytes_written);
+    println!("  finalized:     {}", w.finalized);
+    println!("  data_hash:     {}", hex(&w.data_hash));
+    Ok(())
+}
+
+/// Observed accumulator ranges per layer and the scales that fit them, for
+/// set_layer_scales on a fork.
+pub fn calibration(data: &[u8]) -> Result<()> {
+    let c: CalibrationAccount = decode::account(data)?;
+    println!("Calibration of {}", c.manifest);
+    println!("  frames: {}  last_slot: {}", c.frames, c.last_slot);
+    let fitted = |scale: Option<u16>| scale.map_or("-".to_string(), |s| s.to_string());
+    for layer in 0..MAX_LAYERS {
+        let range = c.range(layer);
+        if range.in_min > range.in_max {
+            continue;
+        }
+        println!(
+            "    layer {:2} in [{}, {}] scale={}  out [{}, {}] scale={}",
+            layer,
+            range.in_min,
+            range.in_max,
+            fitted(range.in_scale()),
+            range.out_min,
+            range.out_max,
+            fitted(range.out_scale())
+        );
+    }
+    Ok(())
+}
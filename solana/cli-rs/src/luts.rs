@@ -0,0 +1,177 @@
+//! Activation LUT generation, ported from quantization/generate_luts.py.
+//!
+//! Same formulas, default scales, clipping and round-half-to-even as the
+//! Python script, so both write the same luts.bin (a v2 block):
+//!   [silu(256)] [softplus(256)] [rsqrt(256)] [exp_neg(256)] [gelu(256)] [tanh(256)]
+//!
+//! init_manifest takes the first LUT_V1_SIZE bytes; set_extended_luts the rest.
+
+use awm_kernel::lut::{
+    EXP_NEG_OFFSET, GELU_OFFSET, LUT_SIZE, LUT_TOTAL_SIZE, NUM_LUTS, RSQRT_OFFSET, SILU_OFFSET,
+    SOFTPLUS_OFFSET, TANH_OFFSET,
+};
+use serde_json::{json, Value};
+
+/// (input_scale, output_scale) per LUT.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LutScales {
+    pub silu: (f64, f64),
+    pub softplus: (f64, f64),
+    pub rsqrt: (f64, f64),
+    pub exp_neg: (f64, f64),
+    pub gelu: (f64, f64),
+    pub tanh: (f64, f64),
+}
+
+impl Default for LutScales {
+    fn default() -> Self {
+        LutScales {
+            silu: (0.0625, 0.0625),
+            softplus: (0.0625, 0.03125),
+            rsqrt: (0.01, 0.05),
+            exp_neg: (0.03125, 0.00392157),
+            gelu: (0.0625, 0.0625),
+            tanh: (0.03125, 0.0078125),
+        }
+    }
+}
+
+/// Table index → signed INT8 input (-128 → 0, ..., 127 → 255 as stored).
+fn signed_input(i: usize) -> f64 {
+    (i as u8 as i8) as f64
+}
+
+fn quantize(v: f64, scale: f64, lo: i64, hi: i64) -> i64 {
+    ((v / scale).round_ties_even() as i64).clamp(lo, hi)
+}
+
+fn signed_lut(out: &mut [u8], (in_scale, out_scale): (f64, f64), f: impl Fn(f64) -> f64) {
+    for (i, v) in out.iter_mut().enumerate() {
+        let x = signed_input(i) * in_scale;
+        *v = quantize(f(x), out_scale, -128, 127) as i8 as u8;
+    }
+}
+
+fn unsigned_lut(out: &mut [u8], (in_scale, out_scale): (f64, f64), f: impl Fn(f64) -> f64) {
+    for (i, v) in out.iter_mut().enumerate() {
+        let x = i as f64 * in_scale;
+        *v = quantize(f(x), out_scale, 0, 255) as u8;
+    }
+}
+
+/// SiLU(x) = x * sigmoid(x)
+fn silu(x: f64) -> f64 {
+    x / (1.0 + (-x.clamp(-20.0, 20.0)).exp())
+}
+
+/// softplus(x) = ln(1 + exp(x)), linear/zero past ±20
+fn softplus(x: f64) -> f64 {
+    if x > 20.0 {
+        x
+    } else if x < -20.0 {
+        0.0
+    } else {
+        (1.0 + x.exp()).ln()
+    }
+}
+
+/// 1/sqrt(x); index 0 maps to the largest output
+fn rsqrt(x: f64) -> f64 {
+    1.0 / x.max(1e-6).sqrt()
+}
+
+fn exp_neg(x: f64) -> f64 {
+    (-x).exp()
+}
+
+/// GELU(x), tanh approximation: 0.5x(1 + tanh(√(2/π)(x + 0.044715x³)))
+fn gelu(x: f64) -> f64 {
+    0.5 * x * (1.0 + (0.7978845608 * (x + 0.044715 * x * x * x)).tanh())
+}
+
+/// The packed 1536-byte LUT block stored in the manifest.
+pub fn generate(scales: &LutScales) -> [u8; LUT_TOTAL_SIZE] {
+    let mut luts = [0u8; LUT_TOTAL_SIZE];
+    signed_lut(&mut luts[SILU_OFFSET..SOFTPLUS_OFFSET], scales.silu, silu);
+    signed_lut(&mut luts[SOFTPLUS_OFFSET..RSQRT_OFFSET], scales.softplus, softplus);
+    unsigned_lut(&mut luts[RSQRT_OFFSET..EXP_NEG_OFFSET], scales.rsqrt, rsqrt);
+    unsigned_lut(&mut luts[EXP_NEG_OFFSET..GELU_OFFSET], scales.exp_neg, exp_neg);
+    signed_lut(&mut luts[GELU_OFFSET..TANH_OFFSET], scales.gelu, gelu);
+    signed_lut(&mut luts[TANH_OFFSET..], scales.tanh, f64::tanh);
+    luts
+}
+
+/// luts.json, in generate_luts.py's format.
+pub fn metadata(scales: &LutScales) -> Value {
+    let entry = |name: &str, offset: usize, signed: bool, (i, o): (f64, f64), desc: &str| {
+        json!({
+            "name": name,
+            "offset": offset,
+            "size": 256,
+            "input_signed": signed,
+            "output_signed": signed,
+            "input_scale": i,
+            "output_scale": o,
+            "description": desc,
+        })
+    };
+    json!({
+        "format": "int8_luts_v2",
+        "num_luts": NUM_LUTS,
+        "lut_size": LUT_SIZE,
+        "total_bytes": LUT_TOTAL_SIZE,
+        "luts": [
+            entry("silu", SILU_OFFSET, true, scales.silu,
+                  "SiLU(x) = x * sigmoid(x), gate activation"),
+            entry("softplus", SOFTPLUS_OFFSET, true, scales.softplus,
+                  "softplus(x) = ln(1 + exp(x)), dt computation"),
+            entry("rsqrt", RSQRT_OFFSET, false, scales.rsqrt,
+                  "1/sqrt(x), RMSNorm normalization"),
+            entry("exp_neg", EXP_NEG_OFFSET, false, scales.exp_neg,
+                  "exp(-x), A_bar decay in selective scan"),
+            entry("gelu", GELU_OFFSET, true, scales.gelu,
+                  "GELU(x) = x * Phi(x), tanh approximation, gate activation"),
+            entry("tanh", TANH_OFFSET, true, scales.tanh,
+                  "tanh(x), output squashing"),
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use awm_kernel::lut::{
+        exp_neg_lut, gelu_lut, rsqrt_lut, silu_lut, softplus_lut, tanh_lut, validate_luts,
+        LUT_V1_SIZE,
+    };
+
+    #[test]
+    fn test_default_luts_pass_program_validation() {
+        let luts = generate(&LutScales::default());
+        assert_eq!(validate_luts(&luts), Ok(()));
+        assert_eq!(validate_luts(&luts[..LUT_V1_SIZE]), Ok(()));
+    }
+
+    #[test]
+    fn test_lut_values_match_generator() {
+        let luts = generate(&LutScales::default());
+        // SiLU(2.0) / 0.0625 = 1.7616 / 0.0625 = 28.19
+        assert_eq!(silu_lut(&luts, 32), 28);
+        assert_eq!(silu_lut(&luts, 0), 0);
+        // softplus(0) = ln 2 = 0.693 → 22.18
+        assert_eq!(softplus_lut(&luts, 0), 22);
+        assert_eq!(softplus_lut(&luts, 127), 127);
+        assert_eq!(rsqrt_lut(&luts, 0), 255);
+        // 1/sqrt(1.0) / 0.05 = 20
+        assert_eq!(rsqrt_lut(&luts, 100), 20);
+        assert_eq!(exp_neg_lut(&luts, 0), 255);
+        assert_eq!(exp_neg_lut(&luts, 255), 0);
+        // GELU(2.0) = 1.9546 → 31.27
+        assert_eq!(gelu_lut(&luts, 32), 31);
+        assert_eq!(gelu_lut(&luts, 0), 0);
+        // tanh(1.0) = 0.7616 / 0.0078125 = 97.48
+        assert_eq!(tanh_lut(&luts, 32), 97);
+        assert_eq!(tanh_lut(&luts, -32), -97);
+        assert_eq!(tanh_lut(&luts, 127), 127);
+    }
+}
@@ -0,0 +1,235 @@
+//! `awm-cli` — the model pipeline from quantized export to a ready manifest.
+//!
+//!   awm-cli luts -o out/luts
+//!   awm-cli convert --export quantization/output/world-model -o out/shards
+//!   awm-cli upload --shard out/shards/shard_0.bin --account <WEIGHT>
+//!   awm-cli finalize --shard out/shards/shard_0.bin --account <WEIGHT> --manifest <MANIFEST>
+//!   awm-cli tensor-table --layout out/shards/layout.json --manifest <MANIFEST>
+//!   awm-cli inspect manifest <PUBKEY | FILE>
+//!
+//! `convert` reads quantize.py's weights_int8.bin + manifest.json; `luts`
+//! replaces generate_luts.py. Upload commands sign with `--keypair`, which
+//! must be the manifest and weight account authority.
+
+mod convert;
+mod inspect;
+mod luts;
+mod rpc;
+mod upload;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::convert::{ExportManifest, ShardLayout};
+use crate::rpc::Rpc;
+
+#[derive(Parser)]
+#[command(name = "awm-cli", about = "Autonomous World Model pipeline CLI")]
+struct Cli {
+    /// Cluster (devnet, mainnet-beta, localnet) or RPC URL
+    #[arg(long, short = 'u', global = true, default_value = "devnet")]
+    url: String,
+    /// Signing keypair
+    #[arg(long, global = true, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the six activation LUTs (luts.bin + luts.json)
+    Luts {
+        #[arg(long, short = 'o', default_value = "quantization/output")]
+        output_dir: PathBuf,
+        #[arg(long, default_value_t = 0.0625)]
+        silu_input_scale: f64,
+        #[arg(long, default_value_t = 0.0625)]
+        silu_output_scale: f64,
+        #[arg(long, default_value_t = 0.0625)]
+        softplus_input_scale: f64,
+        #[arg(long, default_value_t = 0.03125)]
+        softplus_output_scale: f64,
+        #[arg(long, default_value_t = 0.01)]
+        rsqrt_input_scale: f64,
+        #[arg(long, default_value_t = 0.05)]
+        rsqrt_output_scale: f64,
+        #[arg(long, default_value_t = 0.03125)]
+        exp_input_scale: f64,
+        #[arg(long, default_value_t = 0.00392157)]
+        exp_output_scale: f64,
+        #[arg(long, default_value_t = 0.0625)]
+        gelu_input_scale: f64,
+        #[arg(long, default_value_t = 0.0625)]
+        gelu_output_scale: f64,
+        #[arg(long, default_value_t = 0.03125)]
+        tanh_input_scale: f64,
+        #[arg(long, default_value_t = 0.0078125)]
+        tanh_output_scale: f64,
+    },
+    /// Convert a quantize.py export to shard files and a tensor table
+    Convert {
+        /// Directory holding weights_int8.bin and manifest.json
+        #[arg(long)]
+        export: PathBuf,
+        #[arg(long, short = 'o')]
+        output_dir: PathBuf,
+        #[arg(long, default_value_t = convert::DEFAULT_MAX_SHARD_SIZE)]
+        max_shard_size: usize,
+    },
+    /// Upload a shard file into its weight account, resuming where it left off
+    Upload {
+        #[arg(long)]
+        shard: PathBuf,
+        /// WeightAccount to write
+        #[arg(long)]
+        account: Pubkey,
+    },
+    /// Finalize a fully uploaded shard with its hash and register it
+    Finalize {
+        #[arg(long)]
+        shard: PathBuf,
+        #[arg(long)]
+        account: Pubkey,
+        #[arg(long)]
+        manifest: Pubkey,
+    },
+    /// Write a converted layout's tensor table into the manifest
+    TensorTable {
+        #[arg(long)]
+        layout: PathBuf,
+        #[arg(long)]
+        manifest: Pubkey,
+    },
+    /// Dump an account fetched by pubkey or read from a file
+    Inspect {
+        kind: AccountKind,
+        /// Account pubkey, or a file of raw account data
+        source: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AccountKind {
+    Manifest,
+    Session,
+    Weights,
+    Calibration,
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("reading {}", path.display()))
+}
+
+fn write(path: &Path, data: impl AsRef<[u8]>) -> Result<()> {
+    fs::write(path, data).with_context(|| format!("writing {}", path.display()))
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let connect = || Rpc::connect(&cli.url, &expand_home(&cli.keypair));
+
+    match cli.command {
+        Command::Luts {
+            output_dir,
+            silu_input_scale,
+            silu_output_scale,
+            softplus_input_scale,
+            softplus_output_scale,
+            rsqrt_input_scale,
+            rsqrt_output_scale,
+            exp_input_scale,
+            exp_output_scale,
+            gelu_input_scale,
+            gelu_output_scale,
+            tanh_input_scale,
+            tanh_output_scale,
+        } => {
+            let scales = luts::LutScales {
+                silu: (silu_input_scale, silu_output_scale),
+                softplus: (softplus_input_scale, softplus_output_scale),
+                rsqrt: (rsqrt_input_scale, rsqrt_output_scale),
+                exp_neg: (exp_input_scale, exp_output_scale),
+                gelu: (gelu_input_scale, gelu_output_scale),
+                tanh: (tanh_input_scale, tanh_output_scale),
+            };
+            let table = luts::generate(&scales);
+            if let Err(e) = awm_kernel::lut::validate_luts(&table) {
+                println!("Warning: the program will reject these LUTs ({:?})", e);
+            }
+            fs::create_dir_all(&output_dir)?;
+            write(&output_dir.join("luts.bin"), table)?;
+            write(
+                &output_dir.join("luts.json"),
+                serde_json::to_string_pretty(&luts::metadata(&scales))?,
+            )?;
+            println!("LUTs written to {}", output_dir.display());
+        }
+
+        Command::Convert { export, output_dir, max_shard_size } => {
+            let manifest: ExportManifest =
+                serde_json::from_slice(&read(&export.join("manifest.json"))?)
+                    .context("parsing manifest.json")?;
+            let weights = read(&export.join("weights_int8.bin"))?;
+            let (layout, shards) = convert::convert(&manifest, &weights, max_shard_size)?;
+
+            fs::create_dir_all(&output_dir)?;
+            for (info, data) in layout.shards.iter().zip(&shards) {
+                write(&output_dir.join(format!("shard_{}.bin", info.index)), data)?;
+                println!("  shard {}: {} bytes, sha256 {}", info.index, info.size, info.sha256);
+            }
+            write(&output_dir.join("layout.json"), serde_json::to_string_pretty(&layout)?)?;
+            println!(
+                "{} ({} layers) → {} shards, {} of {} bytes on chain",
+                manifest.format,
+                layout.num_layers,
+                shards.len(),
+                layout.total_weight_bytes,
+                weights.len()
+            );
+        }
+
+        Command::Upload { shard, account } => {
+            upload::upload(&connect()?, &account, &read(&shard)?)?;
+        }
+
+        Command::Finalize { shard, account, manifest } => {
+            upload::finalize(&connect()?, &manifest, &account, &read(&shard)?)?;
+        }
+
+        Command::TensorTable { layout, manifest } => {
+            let layout: ShardLayout =
+                serde_json::from_slice(&read(&layout)?).context("parsing layout.json")?;
+            upload::set_tensor_table(&connect()?, &manifest, &layout)?;
+        }
+
+        Command::Inspect { kind, source } => {
+            let data = match Pubkey::from_str(&source) {
+                Ok(key) if !Path::new(&source).exists() => {
+                    rpc::fetch_account(&rpc::client(&cli.url), &key)?
+                        .with_context(|| format!("account {} does not exist", key))?
+                }
+                _ => read(Path::new(&source))?,
+            };
+            match kind {
+                AccountKind::Manifest => inspect::manifest(&data)?,
+                AccountKind::Session => inspect::session(&data)?,
+                AccountKind::Weights => inspect::weights(&data)?,
+                AccountKind::Calibration => inspect::calibration(&data)?,
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Cluster connection and transaction sending.
+
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anyhow::{anyhow, Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+/// Sends per transaction before giving up (blockhash expiry, dropped txs).
+const MAX_RETRIES: usize = 3;
+
+/// Cluster name or RPC URL → RPC URL.
+pub fn resolve_cluster(cluster: &str) -> &str {
+    match cluster {
+        "devnet" => "https://api.devnet.solana.com",
+        "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        "localnet" | "localhost" => "http://localhost:8899",
+        url => url,
+    }
+}
+
+pub fn client(cluster: &str) -> RpcClient {
+    RpcClient::new_with_commitment(resolve_cluster(cluster), CommitmentConfig::confirmed())
+}
+
+/// Raw account data, or None if the account doesn't exist.
+pub fn fetch_account(client: &RpcClient, key: &Pubkey) -> Result<Option<Vec<u8>>> {
+    let response = client
+        .get_account_with_commitment(key, client.commitment())
+        .with_context(|| format!("fetching {}", key))?;
+    Ok(response.value.map(|account| account.data))
+}
+
+/// A client plus the keypair that signs and pays.
+pub struct Rpc {
+    pub client: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Rpc {
+    pub fn connect(cluster: &str, keypair: &Path) -> Result<Self> {
+        let payer = read_keypair_file(keypair)
+            .map_err(|e| anyhow!("reading keypair {}: {}", keypair.display(), e))?;
+        Ok(Rpc { client: client(cluster), payer })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub fn account_data(&self, key: &Pubkey) -> Result<Option<Vec<u8>>> {
+        fetch_account(&self.client, key)
+    }
+
+    /// Send instructions in one transaction signed by the payer, retrying
+    /// with a fresh blockhash.
+    pub fn send(&self, ixs: &[Instruction]) -> Result<Signature> {
+        let mut last_err = None;
+        for _ in 0..MAX_RETRIES {
+            let blockhash = self.client.get_latest_blockhash()?;
+            let tx = Transaction::new_signed_with_payer(
+                ixs,
+                Some(&self.payer.pubkey()),
+                &[&self.payer],
+                blockhash,
+            );
+            match self.client.send_and_confirm_transaction(&tx) {
+                Ok(sig) => return Ok(sig),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap().into())
+    }
+}
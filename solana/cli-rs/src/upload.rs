@@ -0,0 +1,209 @@
+//! Chunked shard upload, finalization and tensor table registration.
+//!
+//! The WeightAccount must already exist with its header set (shard index,
+//! data size, authority); this drives everything after that. Uploads
+//! resume by comparing the account's data region with the local shard and
+//! re-sending only the chunks that differ, so an interrupted or partially
+//! failed run is fixed by running it again.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use anyhow::{bail, ensure, Context, Result};
+use awm_client::state::*;
+use awm_client::{accounts, decode, instruction, instructions, pda};
+
+use crate::convert::{hex, ShardLayout};
+use crate::rpc::Rpc;
+
+/// Offsets of the MAX_CHUNK_SIZE chunks whose on-chain bytes differ from
+/// `local`. `onchain` is the account's data region (past the header).
+pub fn pending_chunks(onchain: &[u8], local: &[u8]) -> Vec<usize> {
+    (0..local.len())
+        .step_by(MAX_CHUNK_SIZE)
+        .filter(|&offset| {
+            let end = (offset + MAX_CHUNK_SIZE).min(local.len());
+            onchain.get(offset..end) != Some(&local[offset..end])
+        })
+        .collect()
+}
+
+/// Fetch a weight account, returning its header and data region.
+fn fetch_weight(rpc: &Rpc, key: &Pubkey) -> Result<(WeightAccount, Vec<u8>)> {
+    let data = rpc
+        .account_data(key)?
+        .with_context(|| format!("weight account {} does not exist", key))?;
+    let header: WeightAccount =
+        decode::account(&data).with_context(|| format!("decoding weight account {}", key))?;
+    let region = data.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[]).to_vec();
+    Ok((header, region))
+}
+
+fn check_header(header: &WeightAccount, shard: &[u8], authority: &Pubkey) -> Result<()> {
+    ensure!(
+        header.data_size as usize == shard.len(),
+        "weight account holds {} bytes, shard file is {}",
+        header.data_size,
+        shard.len()
+    );
+    ensure!(
+        header.authority == *authority,
+        "weight account authority is {}, not {}",
+        header.authority,
+        authority
+    );
+    Ok(())
+}
+
+/// Upload `shard` into `weight`, skipping chunks already on chain.
+pub fn upload(rpc: &Rpc, weight: &Pubkey, shard: &[u8]) -> Result<()> {
+    let (header, region) = fetch_weight(rpc, weight)?;
+    check_header(&header, shard, &rpc.payer())?;
+    if header.finalized {
+        println!("Shard {} already finalized", header.shard_index);
+        return Ok(());
+    }
+
+    let pending = pending_chunks(&region, shard);
+    let total = shard.len().div_ceil(MAX_CHUNK_SIZE);
+    println!(
+        "Shard {}: {} of {} chunks to upload ({} bytes written on chain)",
+        header.shard_index,
+        pending.len(),
+        total,
+        header.bytes_written
+    );
+
+    for (n, &offset) in pending.iter().enumerate() {
+        let end = (offset + MAX_CHUNK_SIZE).min(shard.len());
+        let ix = instructions::upload_weights(
+            weight,
+            &rpc.payer(),
+            offset as u32,
+            shard[offset..end].to_vec(),
+        );
+        rpc.send(&[ix])
+            .with_context(|| format!("chunk at offset {} (re-run to resume)", offset))?;
+        if (n + 1) % 100 == 0 || n + 1 == pending.len() {
+            println!("  {}/{} chunks", n + 1, pending.len());
+        }
+    }
+    Ok(())
+}
+
+/// finalize_weights with the shard's SHA-256, then register_shard in the
+/// manifest's shard directory. Already-done steps are skipped.
+pub fn finalize(rpc: &Rpc, manifest: &Pubkey, weight: &Pubkey, shard: &[u8]) -> Result<()> {
+    let (header, region) = fetch_weight(rpc, weight)?;
+    check_header(&header, shard, &rpc.payer())?;
+    let expected_hash = hash(shard).to_bytes();
+
+    if header.finalized {
+        ensure!(
+            header.data_hash == expected_hash,
+            "shard {} was finalized with a different hash ({})",
+            header.shard_index,
+            hex(&header.data_hash)
+        );
+    } else {
+        let pending = pending_chunks(&region, shard);
+        if !pending.is_empty() {
+            bail!(
+                "{} chunks differ from the shard file; run upload first",
+                pending.len()
+            );
+        }
+        rpc.send(&[instructions::finalize_weights(weight, &rpc.payer(), expected_hash)])?;
+        println!("Shard {} finalized, sha256 {}", header.shard_index, hex(&expected_hash));
+    }
+
+    let directory_key = pda::shard_directory(manifest);
+    let directory: ShardDirectoryAccount = decode::account(
+        &rpc.account_data(&directory_key)?
+            .context("shard directory missing; run init_shard_directory first")?,
+    )?;
+    let slot = directory
+        .entries
+        .get(header.shard_index as usize)
+        .context("shard index outside the manifest's shard directory")?;
+    if slot.key == *weight {
+        println!("Shard {} already registered", header.shard_index);
+        return Ok(());
+    }
+    ensure!(
+        slot.key == Pubkey::default(),
+        "shard slot {} already holds {}",
+        header.shard_index,
+        slot.key
+    );
+    rpc.send(&[instructions::register_shard(manifest, weight, &rpc.payer(), expected_hash)])?;
+    println!("Shard {} registered", header.shard_index);
+    Ok(())
+}
+
+/// set_head_groups for a grouped-B/C layout, then set_tensor_table for
+/// every layer in it and set_action_tensors for its action embedding.
+pub fn set_tensor_table(rpc: &Rpc, manifest: &Pubkey, layout: &ShardLayout) -> Result<()> {
+    if layout.ngroups > 0 {
+        let ix = instructions::build(
+            accounts::SetHeadGroups {
+                manifest: *manifest,
+                authority: rpc.payer(),
+            },
+            instruction::SetHeadGroups {
+                ngroups: layout.ngroups,
+            },
+        );
+        rpc.send(&[ix])?;
+        println!("Head groups set: {}", layout.ngroups);
+    }
+    for layer in 0..layout.tensor_table.len() {
+        let ix = instructions::build(
+            accounts::SetTensorTable {
+                manifest: *manifest,
+                authority: rpc.payer(),
+            },
+            instruction::SetTensorTable {
+                layer: layer as u8,
+                tensors: layout.layer_tensors(layer)?,
+            },
+        );
+        rpc.send(&[ix])?;
+        println!("Layer {} tensor table set", layer);
+    }
+    if let Some((embedding, head)) = layout.action_tensors() {
+        let ix = instructions::build(
+            accounts::SetActionTensors {
+                manifest: *manifest,
+                authority: rpc.payer(),
+            },
+            instruction::SetActionTensors { embedding, head },
+        );
+        rpc.send(&[ix])?;
+        println!("Action embedding set: {} classes, head tied", embedding.cols);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_chunks_resume() {
+        let local: Vec<u8> = (0..3 * MAX_CHUNK_SIZE + 10).map(|i| (i % 251) as u8 + 1).collect();
+
+        // Fresh account: every chunk is pending
+        let empty = vec![0u8; local.len()];
+        assert_eq!(pending_chunks(&empty, &local), vec![0, 1000, 2000, 3000]);
+
+        // First and last chunk landed; a gap in the middle did not
+        let mut partial = empty.clone();
+        partial[..MAX_CHUNK_SIZE].copy_from_slice(&local[..MAX_CHUNK_SIZE]);
+        partial[3 * MAX_CHUNK_SIZE..].copy_from_slice(&local[3 * MAX_CHUNK_SIZE..]);
+        assert_eq!(pending_chunks(&partial, &local), vec![1000, 2000]);
+
+        assert!(pending_chunks(&local, &local).is_empty());
+        // Short data region: the tail can never match
+        assert_eq!(pending_chunks(&local[..2500], &local), vec![2000, 3000]);
+    }
+}
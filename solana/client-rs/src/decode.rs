@@ -0,0 +1,186 @@
+//! Account decoders.
+//!
+//! Zero-copy accounts (session, input buffer) are copied out of the raw
+//! data, so RPC buffers with any alignment work. Raw-layout accounts (hidden
+//! state, checkpoints) reuse the program's own header readers.
+
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hash;
+use anchor_lang::{AccountDeserialize, Discriminator, ZeroCopy};
+use world_model::state::*;
+
+/// Why account data couldn't be decoded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecodeError {
+    /// Data is shorter than the account's layout
+    TooShort,
+    /// The leading 8 bytes aren't this account type's discriminator
+    WrongDiscriminator,
+    /// Borsh body doesn't deserialize
+    InvalidData,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            DecodeError::TooShort => "account data too short",
+            DecodeError::WrongDiscriminator => "account discriminator mismatch",
+            DecodeError::InvalidData => "account data failed to deserialize",
+        })
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn check_discriminator(
+    data: &[u8],
+    discriminator: &[u8],
+) -> core::result::Result<(), DecodeError> {
+    match data.get(..discriminator.len()) {
+        None => Err(DecodeError::TooShort),
+        Some(d) if d != discriminator => Err(DecodeError::WrongDiscriminator),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Decode any `#[account(zero_copy)]` account.
+pub fn zero_copy<T: ZeroCopy + Discriminator>(
+    data: &[u8],
+) -> core::result::Result<T, DecodeError> {
+    check_discriminator(data, T::DISCRIMINATOR)?;
+    let body = data
+        .get(T::DISCRIMINATOR.len()..T::DISCRIMINATOR.len() + core::mem::size_of::<T>())
+        .ok_or(DecodeError::TooShort)?;
+    Ok(bytemuck::pod_read_unaligned(body))
+}
+
+/// Decode any Borsh `#[account]` (manifest, weight header, escrow, ...).
+pub fn account<T: AccountDeserialize + Discriminator>(
+    data: &[u8],
+) -> core::result::Result<T, DecodeError> {
+    check_discriminator(data, T::DISCRIMINATOR)?;
+    T::try_deserialize(&mut &data[..]).map_err(|_| DecodeError::InvalidData)
+}
+
+pub fn session_state(data: &[u8]) -> core::result::Result<SessionStateAccount, DecodeError> {
+    zero_copy(data)
+}
+
+pub fn input_buffer(data: &[u8]) -> core::result::Result<InputBufferAccount, DecodeError> {
+    zero_copy(data)
+}
+
+/// Header of a raw hidden state account (no discriminator).
+pub fn hidden_header(data: &[u8]) -> core::result::Result<HiddenHeader, DecodeError> {
+    if data.len() < HIDDEN_HEADER_SIZE {
+        return Err(DecodeError::TooShort);
+    }
+    Ok(read_hidden_header(data))
+}
+
+/// Header of a raw checkpoint account (no discriminator).
+pub fn checkpoint_header(data: &[u8]) -> core::result::Result<CheckpointHeader, DecodeError> {
+    if data.len() < CHECKPOINT_HEADER_SIZE {
+        return Err(DecodeError::TooShort);
+    }
+    Ok(read_checkpoint_header(data))
+}
+
+/// Header of a raw matmul accumulator account (no discriminator).
+pub fn accumulator_header(data: &[u8]) -> core::result::Result<AccumulatorHeader, DecodeError> {
+    if data.len() < ACCUM_HEADER_SIZE {
+        return Err(DecodeError::TooShort);
+    }
+    Ok(read_accumulator_header(data))
+}
+
+// ── ECS FrameLog ─────────────────────────────────────────────────────────────
+
+/// Header of the BOLT ECS `FrameLog` component
+/// (programs-ecs/components/frame-log).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameLogHeader {
+    /// Next ring buffer slot (wraps at FRAME_LOG_RING_SIZE)
+    pub write_index: u16,
+    /// Frames written, may exceed FRAME_LOG_RING_SIZE
+    pub total_frames: u32,
+    pub session: Pubkey,
+}
+
+/// Ring buffer capacity of the FrameLog component
+pub const FRAME_LOG_RING_SIZE: usize = 256;
+/// BOLT prepends component metadata (the authority pubkey) to every component
+const BOLT_METADATA_SIZE: usize = 32;
+
+/// Decode a FrameLog component header:
+///   discriminator (8) | bolt_metadata (32) | write_index u16 | total_frames u32 | session (32)
+pub fn frame_log_header(data: &[u8]) -> core::result::Result<FrameLogHeader, DecodeError> {
+    check_discriminator(data, &hash(b"account:FrameLog").to_bytes()[..8])?;
+    let body = data
+        .get(8 + BOLT_METADATA_SIZE..8 + BOLT_METADATA_SIZE + 38)
+        .ok_or(DecodeError::TooShort)?;
+    Ok(FrameLogHeader {
+        write_index: u16::from_le_bytes([body[0], body[1]]),
+        total_frames: u32::from_le_bytes([body[2], body[3], body[4], body[5]]),
+        session: Pubkey::new_from_array(body[6..38].try_into().unwrap()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_discriminator(discriminator: &[u8], body: &[u8]) -> Vec<u8> {
+        [discriminator, body].concat()
+    }
+
+    #[test]
+    fn test_session_state_roundtrip() {
+        let mut state: SessionStateAccount = bytemuck::Zeroable::zeroed();
+        state.status = STATUS_ACTIVE;
+        state.frame = 1234;
+        state.player1 = Pubkey::new_unique();
+        state.players[1].x = -30 * 256;
+        state.crankers[0].frames = 7;
+
+        let data = with_discriminator(SessionStateAccount::DISCRIMINATOR, bytemuck::bytes_of(&state));
+        let decoded = session_state(&data).unwrap();
+        assert_eq!(bytemuck::bytes_of(&decoded), bytemuck::bytes_of(&state));
+        assert_eq!(decoded.frame, 1234);
+        assert_eq!(decoded.players[1].x, -30 * 256);
+
+        assert_eq!(session_state(&data[..data.len() - 1]).err(), Some(DecodeError::TooShort));
+        assert_eq!(input_buffer(&data).err(), Some(DecodeError::WrongDiscriminator));
+    }
+
+    #[test]
+    fn test_hidden_header() {
+        let header = HiddenHeader {
+            num_layers: 2,
+            d_inner: 128,
+            d_state: 4,
+            data_size: 1024,
+            frame: 9,
+            initialized: true,
+            dtype: HIDDEN_DTYPE_I8,
+        };
+        let mut data = vec![0u8; HIDDEN_HEADER_SIZE];
+        write_hidden_header(&mut data, &header);
+        assert_eq!(hidden_header(&data), Ok(header));
+        assert_eq!(hidden_header(&data[..HIDDEN_HEADER_SIZE - 1]), Err(DecodeError::TooShort));
+    }
+
+    #[test]
+    fn test_frame_log_header() {
+        let session = Pubkey::new_unique();
+        let mut body = vec![0xAA; BOLT_METADATA_SIZE];
+        body.extend_from_slice(&5u16.to_le_bytes());
+        body.extend_from_slice(&261u32.to_le_bytes());
+        body.extend_from_slice(session.as_ref());
+        let data = with_discriminator(&hash(b"account:FrameLog").to_bytes()[..8], &body);
+
+        let header = frame_log_header(&data).unwrap();
+        assert_eq!(header, FrameLogHeader { write_index: 5, total_frames: 261, session });
+        assert_eq!(frame_log_header(&data[..data.len() - 1]), Err(DecodeError::TooShort));
+    }
+}
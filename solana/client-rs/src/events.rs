@@ -0,0 +1,105 @@
+//! Program events, decoded from transaction logs.
+//!
+//! Anchor logs each `emit!` as `Program data: <base64>` — an 8-byte event
+//! discriminator followed by the Borsh body. The schema is `awm-events`,
+//! the same crate the programs emit from.
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+pub use awm_events::*;
+
+const LOG_PREFIX: &str = "Program data: ";
+
+/// Any event emitted by world-model or fraud-proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    SessionCreated(SessionCreated),
+    SessionJoined(SessionJoined),
+    SessionEnded(SessionEnded),
+    SessionReaped(SessionReaped),
+    FrameAdvanced(FrameAdvanced),
+    PlayerKo(PlayerKo),
+    ExpertsRouted(ExpertsRouted),
+    OutputSmoothed(OutputSmoothed),
+    FrameSkipped(FrameSkipped),
+    SessionResult(SessionResult),
+    SessionSettled(SessionSettled),
+    ManifestValidated(ManifestValidated),
+    KernelsVerified(KernelsVerified),
+    ReplayArchived(ReplayArchived),
+    CrankRegistered(CrankRegistered),
+    CrankUnregistered(CrankUnregistered),
+    ChallengeOpened(ChallengeOpened),
+    FraudProven(FraudProven),
+}
+
+fn body<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    let rest = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::try_from_slice(rest).ok()
+}
+
+/// Decode one event from its raw bytes (discriminator + Borsh). None for
+/// other programs' events or malformed data.
+pub fn decode_event(data: &[u8]) -> Option<Event> {
+    macro_rules! try_events {
+        ($($name:ident),*) => {
+            $(if let Some(e) = body::<$name>(data) {
+                return Some(Event::$name(e));
+            })*
+        };
+    }
+    try_events!(
+        SessionCreated, SessionJoined, SessionEnded, SessionReaped, FrameAdvanced, PlayerKo,
+        ExpertsRouted, OutputSmoothed, FrameSkipped, SessionResult, SessionSettled,
+        ManifestValidated, KernelsVerified, ReplayArchived, CrankRegistered, CrankUnregistered,
+        ChallengeOpened, FraudProven
+    );
+    None
+}
+
+/// Every event in a transaction's log messages, in emission order.
+pub fn parse_logs<'a>(logs: impl IntoIterator<Item = &'a str>) -> Vec<Event> {
+    logs.into_iter()
+        .filter_map(|line| line.strip_prefix(LOG_PREFIX))
+        .filter_map(|b64| STANDARD.decode(b64).ok())
+        .filter_map(|data| decode_event(&data))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Event as _;
+
+    #[test]
+    fn test_parse_logs_round_trip() {
+        let session = Pubkey::new_unique();
+        let ko = PlayerKo { session, frame: 90, player: 1, stocks_left: 3 };
+        let frame = FrameAdvanced {
+            session,
+            frame: 90,
+            state_commitment: [7; 32],
+            cranker: Pubkey::new_unique(),
+        };
+        let logs = [
+            "Program BC9hw928r1rvxedMwTSDSvRxFSHMgY4maWnYa9TV71nZ invoke [1]".to_string(),
+            format!("{}{}", LOG_PREFIX, STANDARD.encode(ko.data())),
+            format!("{}{}", LOG_PREFIX, STANDARD.encode(frame.data())),
+            format!("{}{}", LOG_PREFIX, STANDARD.encode([0u8; 16])),
+        ];
+
+        let events = parse_logs(logs.iter().map(|l| l.as_str()));
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            Event::PlayerKo(e) => assert_eq!((e.player, e.stocks_left), (1, 3)),
+            other => panic!("expected PlayerKo, got {:?}", other),
+        }
+        match &events[1] {
+            Event::FrameAdvanced(e) => assert_eq!(e.state_commitment, [7; 32]),
+            other => panic!("expected FrameAdvanced, got {:?}", other),
+        }
+    }
+}
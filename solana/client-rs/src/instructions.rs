@@ -0,0 +1,724 @@
+//! Instruction builders.
+//!
+//! The session loop and the weight upload pipeline get dedicated builders
+//! that derive PDAs and fill in program accounts. Anything else goes
+//! through `build` with the program's generated `accounts::*` and
+//! `instruction::*` structs, which already carry every account and argument.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData};
+use world_model::delegation::{
+    delegate_buffer_pda_from_delegated_account_and_owner_program,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    DELEGATION_PROGRAM_ID, MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID,
+};
+use world_model::state::*;
+use world_model::{accounts, archive, instruction};
+
+use crate::pda;
+
+/// Space for a session account (discriminator + zero-copy state)
+pub const SESSION_ACCOUNT_SIZE: usize = 8 + core::mem::size_of::<SessionStateAccount>();
+/// Space for an input buffer account (discriminator + zero-copy state)
+/// without a frame log; allocate `input_buffer_size(max_frames)` for a
+/// session whose replay can be verified
+pub const INPUT_BUFFER_ACCOUNT_SIZE: usize = 8 + core::mem::size_of::<InputBufferAccount>();
+
+/// Space for a hidden state account of the manifest's model.
+pub fn hidden_state_account_size(manifest: &ModelManifestAccount) -> usize {
+    HIDDEN_HEADER_SIZE
+        + hidden_data_size(
+            manifest.num_layers,
+            manifest.d_inner,
+            manifest.d_state,
+            manifest.hidden_dtype,
+        ) as usize
+}
+
+/// Build any world-model instruction from its generated account and
+/// argument structs.
+pub fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    build_with_remaining(accounts, args, Vec::new())
+}
+
+/// `build`, appending remaining accounts (shards, verifier accounts).
+pub fn build_with_remaining(
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+    remaining: Vec<AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining);
+    Instruction {
+        program_id: world_model::ID,
+        accounts: metas,
+        data: args.data(),
+    }
+}
+
+// ── Sessions ─────────────────────────────────────────────────────────────────
+
+/// The accounts that make up one session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionKeys {
+    pub session: Pubkey,
+    pub hidden_state: Pubkey,
+    pub input_buffer: Pubkey,
+    pub manifest: Pubkey,
+}
+
+impl SessionKeys {
+    /// Keys recorded in a decoded session account.
+    pub fn from_state(session: Pubkey, state: &SessionStateAccount) -> Self {
+        SessionKeys {
+            session,
+            hidden_state: state.hidden_state,
+            input_buffer: state.input_buffer,
+            manifest: state.model,
+        }
+    }
+}
+
+/// Token accounts paying a royalty charged in the manifest's fee_mint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeTokenAccounts {
+    pub mint: Pubkey,
+    /// Player 1's token account for the mint
+    pub payer_token: Pubkey,
+    /// A token account for the mint owned by the manifest's treasury PDA
+    pub treasury_token: Pubkey,
+    /// SPL Token or Token-2022, whichever owns the mint
+    pub token_program: Pubkey,
+}
+
+/// create_session. The session, hidden state and input buffer accounts must
+/// already be allocated (owned by the program, sized with the constants
+/// above) earlier in the same transaction. The token accounts are only
+/// needed when the manifest sets a cartridge mint, token gate or fee mint.
+pub fn create_session(
+    keys: &SessionKeys,
+    player1: &Pubkey,
+    args: instruction::CreateSession,
+    cartridge_token: Option<Pubkey>,
+    gate_token: Option<Pubkey>,
+    fee_tokens: Option<FeeTokenAccounts>,
+) -> Instruction {
+    build(
+        accounts::CreateSession {
+            session: keys.session,
+            hidden_state: keys.hidden_state,
+            input_buffer: keys.input_buffer,
+            manifest: keys.manifest,
+            player1: *player1,
+            treasury: pda::treasury(&keys.manifest),
+            fee_schedule: pda::fee_schedule(),
+            meter: pda::meter(&keys.session),
+            system_program: system_program::ID,
+            cartridge_token,
+            gate_token,
+            fee_mint: fee_tokens.map(|f| f.mint),
+            fee_token: fee_tokens.map(|f| f.payer_token),
+            treasury_fee_token: fee_tokens.map(|f| f.treasury_token),
+            token_program: fee_tokens.map(|f| f.token_program),
+        },
+        args,
+    )
+}
+
+/// set_crank_mode, before player 2 joins. `cranker` only matters under
+/// CRANK_MODE_DESIGNATED.
+pub fn set_crank_mode(
+    session: &Pubkey,
+    player1: &Pubkey,
+    mode: u8,
+    cranker: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::SetCrankMode {
+            session: *session,
+            player1: *player1,
+        },
+        instruction::SetCrankMode {
+            mode,
+            cranker: *cranker,
+        },
+    )
+}
+
+pub fn join_session(session: &Pubkey, player2: &Pubkey, character: u8) -> Instruction {
+    build(
+        accounts::JoinSession {
+            session: *session,
+            player2: *player2,
+        },
+        instruction::JoinSession { character },
+    )
+}
+
+pub fn close_session(session: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseSession {
+            session: *session,
+            player: *player,
+        },
+        instruction::CloseSession {},
+    )
+}
+
+/// reap_session for an idle session, decoded as `state`. Passes the
+/// session's top cranker for its fee share; when nobody cranked any
+/// otherwise unused account will do, so the session's meter stands in.
+/// `protocol_treasury` is the fee schedule's.
+pub fn reap_session(
+    session: &Pubkey,
+    state: &SessionStateAccount,
+    protocol_treasury: &Pubkey,
+    reaper: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ReapSession {
+            session: *session,
+            model: state.model,
+            fee_schedule: pda::fee_schedule(),
+            author_treasury: pda::treasury(&state.model),
+            cranker: state
+                .top_cranker()
+                .unwrap_or_else(|| pda::meter(session)),
+            protocol_treasury: *protocol_treasury,
+            reaper: *reaper,
+        },
+        instruction::ReapSession {},
+    )
+}
+
+pub fn submit_input(keys: &SessionKeys, player: &Pubkey, input: &ControllerInput) -> Instruction {
+    build(
+        accounts::SubmitInput {
+            session: keys.session,
+            input_buffer: keys.input_buffer,
+            player: *player,
+        },
+        instruction::SubmitInput {
+            stick_x: input.stick_x,
+            stick_y: input.stick_y,
+            c_stick_x: input.c_stick_x,
+            c_stick_y: input.c_stick_y,
+            trigger_l: input.trigger_l,
+            trigger_r: input.trigger_r,
+            buttons: input.buttons,
+            buttons_ext: input.buttons_ext,
+        },
+    )
+}
+
+/// run_inference, advancing the session to `frame` (its current frame + 1;
+/// a retry after the frame ran fails instead of advancing again).
+/// `cranker` signs and is tallied for the cranker fee share.
+pub fn run_inference(
+    keys: &SessionKeys,
+    weights: &Pubkey,
+    cranker: &Pubkey,
+    frame: u32,
+) -> Instruction {
+    build_run_inference(keys, weights, cranker, frame, None)
+}
+
+/// run_inference that also records the frame's activation ranges in the
+/// model's calibration account (created by init_calibration).
+pub fn run_inference_calibrating(
+    keys: &SessionKeys,
+    weights: &Pubkey,
+    cranker: &Pubkey,
+    frame: u32,
+) -> Instruction {
+    build_run_inference(keys, weights, cranker, frame, Some(pda::calibration(&keys.manifest)))
+}
+
+fn build_run_inference(
+    keys: &SessionKeys,
+    weights: &Pubkey,
+    cranker: &Pubkey,
+    frame: u32,
+    calibration: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::RunInference {
+            session: keys.session,
+            hidden_state: keys.hidden_state,
+            input_buffer: keys.input_buffer,
+            manifest: keys.manifest,
+            weights: *weights,
+            cranker: *cranker,
+            meter: pda::meter(&keys.session),
+            calibration,
+        },
+        instruction::RunInference { frame },
+    )
+}
+
+/// run_inference for a program built with `mamba2-inference`, which reads
+/// the shard directory, a mixture-of-experts model's expert table, and then
+/// the shards (in directory order) as remaining accounts.
+pub fn run_inference_with_shards(
+    keys: &SessionKeys,
+    weights: &Pubkey,
+    cranker: &Pubkey,
+    frame: u32,
+    shard_directory: &Pubkey,
+    expert_table: Option<&Pubkey>,
+    shards: &[Pubkey],
+) -> Instruction {
+    let mut ix = run_inference(keys, weights, cranker, frame);
+    ix.accounts.push(AccountMeta::new_readonly(*shard_directory, false));
+    ix.accounts.extend(expert_table.map(|t| AccountMeta::new_readonly(*t, false)));
+    ix.accounts
+        .extend(shards.iter().map(|s| AccountMeta::new_readonly(*s, false)));
+    ix
+}
+
+/// frame_advance over many sessions of one model, for an automated
+/// cranker. Each session contributes its bundle (session, hidden state,
+/// input buffer, meter); `shards` is the shard directory, expert table (if
+/// any) and shards for a `mamba2-inference` program, empty otherwise.
+/// Sessions not ready this frame are skipped on-chain, not failed.
+pub fn frame_advance(
+    manifest: &Pubkey,
+    weights: &Pubkey,
+    cranker: &Pubkey,
+    sessions: &[SessionKeys],
+    shards: &[Pubkey],
+) -> Instruction {
+    let mut remaining: Vec<AccountMeta> = shards
+        .iter()
+        .map(|s| AccountMeta::new_readonly(*s, false))
+        .collect();
+    for keys in sessions {
+        remaining.extend([
+            AccountMeta::new(keys.session, false),
+            AccountMeta::new(keys.hidden_state, false),
+            AccountMeta::new(keys.input_buffer, false),
+            AccountMeta::new(pda::meter(&keys.session), false),
+        ]);
+    }
+    build_with_remaining(
+        accounts::FrameAdvance {
+            manifest: *manifest,
+            weights: *weights,
+            cranker: *cranker,
+        },
+        instruction::FrameAdvance {},
+        remaining,
+    )
+}
+
+// ── Ephemeral rollup ─────────────────────────────────────────────────────────
+//
+// configure_delegation → delegate_session (base layer) → play and
+// commit_session on the rollup → undelegate_session once the session ends.
+
+/// configure_delegation, signed by player 1. `commit_mask` is a set of
+/// ER_ACCOUNT_* bits; DEFAULT_COMMIT_MASK leaves out the hidden state.
+pub fn configure_delegation(
+    session: &Pubkey,
+    player1: &Pubkey,
+    validator: Option<Pubkey>,
+    commit_frequency_ms: u32,
+    commit_mask: u8,
+) -> Instruction {
+    build(
+        accounts::ConfigureDelegation {
+            session: *session,
+            config: pda::delegation_config(session),
+            summary: pda::settlement_summary(session),
+            player1: *player1,
+            system_program: system_program::ID,
+        },
+        instruction::ConfigureDelegation {
+            validator: validator.unwrap_or_default(),
+            commit_frequency_ms,
+            commit_mask,
+        },
+    )
+}
+
+/// delegate_session_account for each of the session's bundle accounts, in
+/// bundle order, then its settlement summary. The session, hidden state
+/// and input buffer keypairs must sign alongside `authority` (player 1).
+pub fn delegate_session(keys: &SessionKeys, authority: &Pubkey) -> Vec<Instruction> {
+    let bundle = [
+        (ER_ACCOUNT_SESSION, keys.session),
+        (ER_ACCOUNT_HIDDEN_STATE, keys.hidden_state),
+        (ER_ACCOUNT_INPUT_BUFFER, keys.input_buffer),
+        (ER_ACCOUNT_METER, pda::meter(&keys.session)),
+        (ER_ACCOUNT_SUMMARY, pda::settlement_summary(&keys.session)),
+    ];
+    bundle
+        .into_iter()
+        .map(|(account, target)| {
+            let mut ix = build(
+                accounts::DelegateSessionAccount {
+                    config: pda::delegation_config(&keys.session),
+                    authority: *authority,
+                    target,
+                    buffer: delegate_buffer_pda_from_delegated_account_and_owner_program(
+                        &target,
+                        &world_model::ID,
+                    ),
+                    delegation_record: delegation_record_pda_from_delegated_account(&target),
+                    delegation_metadata: delegation_metadata_pda_from_delegated_account(&target),
+                    owner_program: world_model::ID,
+                    delegation_program: DELEGATION_PROGRAM_ID,
+                    system_program: system_program::ID,
+                },
+                instruction::DelegateSessionAccount { account },
+            );
+            if !matches!(account, ER_ACCOUNT_METER | ER_ACCOUNT_SUMMARY) {
+                for meta in ix.accounts.iter_mut().filter(|m| m.pubkey == target) {
+                    meta.is_signer = true;
+                }
+            }
+            ix
+        })
+        .collect()
+}
+
+fn commit_accounts(keys: &SessionKeys, payer: &Pubkey) -> accounts::CommitSession {
+    accounts::CommitSession {
+        payer: *payer,
+        config: pda::delegation_config(&keys.session),
+        session: keys.session,
+        hidden_state: keys.hidden_state,
+        input_buffer: keys.input_buffer,
+        meter: pda::meter(&keys.session),
+        magic_context: MAGIC_CONTEXT_ID,
+        magic_program: MAGIC_PROGRAM_ID,
+    }
+}
+
+/// commit_session, sent to the rollup: writes the config's masked accounts
+/// back to the base layer.
+pub fn commit_session(keys: &SessionKeys, payer: &Pubkey) -> Instruction {
+    build(commit_accounts(keys, payer), instruction::CommitSession {})
+}
+
+/// undelegate_session, sent to the rollup once the session has ended.
+/// `replay` is a checkpoint of the final hidden state for the settlement
+/// summary to point at; checkpoint before this, since the hidden state
+/// comes back cut to its header.
+pub fn undelegate_session(
+    keys: &SessionKeys,
+    payer: &Pubkey,
+    replay: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::UndelegateSession {
+            payer: *payer,
+            config: pda::delegation_config(&keys.session),
+            session: keys.session,
+            hidden_state: keys.hidden_state,
+            input_buffer: keys.input_buffer,
+            meter: pda::meter(&keys.session),
+            summary: pda::settlement_summary(&keys.session),
+            replay,
+            magic_context: MAGIC_CONTEXT_ID,
+            magic_program: MAGIC_PROGRAM_ID,
+        },
+        instruction::UndelegateSession {},
+    )
+}
+
+// ── Automation ───────────────────────────────────────────────────────────────
+//
+// register_crank, sent to the rollup once the session is delegated and
+// active, keeps it ticking until unregister_crank or its iterations run out.
+
+/// register_crank: `payer` funds and signs a frame_advance of this session
+/// every `interval_ms` (at least the session's tick_interval_ms, e.g.
+/// CRANK_INTERVAL_MS at 60fps). `shards` is the shard
+/// directory and its shards for a `mamba2-inference` program, empty
+/// otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn register_crank(
+    keys: &SessionKeys,
+    player1: &Pubkey,
+    manifest: &Pubkey,
+    weights: &Pubkey,
+    payer: &Pubkey,
+    shards: &[Pubkey],
+    interval_ms: u32,
+    iterations: u32,
+) -> Instruction {
+    build_with_remaining(
+        accounts::RegisterCrank {
+            session: keys.session,
+            player1: *player1,
+            hidden_state: keys.hidden_state,
+            input_buffer: keys.input_buffer,
+            meter: pda::meter(&keys.session),
+            manifest: *manifest,
+            weights: *weights,
+            payer: *payer,
+            magic_program: MAGIC_PROGRAM_ID,
+        },
+        instruction::RegisterCrank {
+            interval_ms,
+            iterations,
+        },
+        shards
+            .iter()
+            .map(|s| AccountMeta::new_readonly(*s, false))
+            .collect(),
+    )
+}
+
+/// unregister_crank, signed by the payer that registered it.
+pub fn unregister_crank(session: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::UnregisterCrank {
+            session: *session,
+            payer: *payer,
+            magic_program: MAGIC_PROGRAM_ID,
+        },
+        instruction::UnregisterCrank {},
+    )
+}
+
+// ── Replay archive ───────────────────────────────────────────────────────────
+//
+// create_archive_tree once per tree (after allocating it for the compression
+// program), then archive_replay per ended session instead of mint_replay_nft.
+
+/// create_archive_tree for a `merkle_tree` already allocated at this depth
+/// and buffer size.
+pub fn create_archive_tree(
+    merkle_tree: &Pubkey,
+    payer: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    build(
+        accounts::CreateArchiveTree {
+            tree_config: archive::tree_config(merkle_tree),
+            merkle_tree: *merkle_tree,
+            payer: *payer,
+            archive_authority: pda::archive_authority(),
+            log_wrapper: archive::NOOP_PROGRAM_ID,
+            compression_program: archive::COMPRESSION_PROGRAM_ID,
+            bubblegum_program: archive::BUBBLEGUM_PROGRAM_ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateArchiveTree {
+            max_depth,
+            max_buffer_size,
+        },
+    )
+}
+
+/// archive_replay, signed by the participant who receives the leaf and the
+/// replay checkpoint's rent.
+pub fn archive_replay(
+    session: &Pubkey,
+    replay: &Pubkey,
+    merkle_tree: &Pubkey,
+    minter: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ArchiveReplay {
+            session: *session,
+            replay: *replay,
+            record: pda::replay(session),
+            minter: *minter,
+            archive_authority: pda::archive_authority(),
+            tree_config: archive::tree_config(merkle_tree),
+            merkle_tree: *merkle_tree,
+            log_wrapper: archive::NOOP_PROGRAM_ID,
+            compression_program: archive::COMPRESSION_PROGRAM_ID,
+            bubblegum_program: archive::BUBBLEGUM_PROGRAM_ID,
+            system_program: system_program::ID,
+        },
+        instruction::ArchiveReplay {},
+    )
+}
+
+// ── Weight upload pipeline ───────────────────────────────────────────────────
+//
+// init_manifest → init_shard_directory → per shard: upload_weights chunks,
+// finalize_weights, register_shard. The manifest turns ready once every
+// expected shard is registered.
+
+/// init_manifest. `manifest` is a fresh keypair account and must sign.
+pub fn init_manifest(
+    manifest: &Pubkey,
+    authority: &Pubkey,
+    args: instruction::InitManifest,
+) -> Instruction {
+    build(
+        accounts::InitManifest {
+            manifest: *manifest,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+pub fn init_shard_directory(manifest: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        accounts::InitShardDirectory {
+            manifest: *manifest,
+            shard_directory: pda::shard_directory(manifest),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitShardDirectory {},
+    )
+}
+
+/// One upload_weights chunk at `offset` within the shard's data region.
+pub fn upload_weights(
+    weight: &Pubkey,
+    authority: &Pubkey,
+    offset: u32,
+    data: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::UploadWeights {
+            weight_account: *weight,
+            weight_data: *weight,
+            authority: *authority,
+        },
+        instruction::UploadWeights { offset, data },
+    )
+}
+
+/// upload_weights instructions covering all of `bytes`, MAX_CHUNK_SIZE at a
+/// time. Each fits in its own transaction.
+pub fn upload_weight_chunks(weight: &Pubkey, authority: &Pubkey, bytes: &[u8]) -> Vec<Instruction> {
+    bytes
+        .chunks(MAX_CHUNK_SIZE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            upload_weights(weight, authority, (i * MAX_CHUNK_SIZE) as u32, chunk.to_vec())
+        })
+        .collect()
+}
+
+/// finalize_weights. `expected_hash` is the SHA-256 of the shard's data.
+pub fn finalize_weights(
+    weight: &Pubkey,
+    authority: &Pubkey,
+    expected_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::FinalizeWeights {
+            weight_account: *weight,
+            weight_data: *weight,
+            authority: *authority,
+        },
+        instruction::FinalizeWeights { expected_hash },
+    )
+}
+
+pub fn register_shard(
+    manifest: &Pubkey,
+    weight: &Pubkey,
+    authority: &Pubkey,
+    expected_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::RegisterShard {
+            manifest: *manifest,
+            shard_directory: pda::shard_directory(manifest),
+            weight_account: *weight,
+            authority: *authority,
+        },
+        instruction::RegisterShard { expected_hash },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn test_upload_chunks_cover_shard() {
+        let (weight, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let bytes: Vec<u8> = (0..2 * MAX_CHUNK_SIZE + 17).map(|i| i as u8).collect();
+        let ixs = upload_weight_chunks(&weight, &authority, &bytes);
+        assert_eq!(ixs.len(), 3);
+
+        let mut rebuilt = Vec::new();
+        for (i, ix) in ixs.iter().enumerate() {
+            assert_eq!(&ix.data[..8], instruction::UploadWeights::DISCRIMINATOR);
+            let args = instruction::UploadWeights::try_from_slice(&ix.data[8..]).unwrap();
+            assert_eq!(args.offset as usize, i * MAX_CHUNK_SIZE);
+            rebuilt.extend_from_slice(&args.data);
+            // weight_account and weight_data are the same account
+            assert_eq!(ix.accounts[0].pubkey, weight);
+            assert_eq!(ix.accounts[1].pubkey, weight);
+        }
+        assert_eq!(rebuilt, bytes);
+    }
+
+    #[test]
+    fn test_run_inference_accounts() {
+        let keys = SessionKeys {
+            session: Pubkey::new_unique(),
+            hidden_state: Pubkey::new_unique(),
+            input_buffer: Pubkey::new_unique(),
+            manifest: Pubkey::new_unique(),
+        };
+        let (weights, cranker) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = run_inference(&keys, &weights, &cranker, 7);
+
+        let expected = [
+            (keys.session, true, false),
+            (keys.hidden_state, true, false),
+            (keys.input_buffer, true, false),
+            (keys.manifest, false, false),
+            (weights, false, false),
+            (cranker, false, true),
+            (pda::meter(&keys.session), true, false),
+            // No calibration account: the program id stands in
+            (world_model::ID, false, false),
+        ];
+        assert_eq!(ix.program_id, world_model::ID);
+        assert_eq!(ix.accounts.len(), expected.len());
+        for (meta, (key, writable, signer)) in ix.accounts.iter().zip(expected) {
+            assert_eq!((meta.pubkey, meta.is_writable, meta.is_signer), (key, writable, signer));
+        }
+        assert_eq!(ix.data, instruction::RunInference { frame: 7 }.data());
+    }
+
+    #[test]
+    fn test_frame_advance_bundles() {
+        let sessions: Vec<SessionKeys> = (0..3)
+            .map(|_| SessionKeys {
+                session: Pubkey::new_unique(),
+                hidden_state: Pubkey::new_unique(),
+                input_buffer: Pubkey::new_unique(),
+                manifest: Pubkey::new_unique(),
+            })
+            .collect();
+        let (manifest, weights, cranker) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = frame_advance(&manifest, &weights, &cranker, &sessions, &[]);
+
+        assert_eq!(ix.accounts.len(), 3 + sessions.len() * SESSION_BUNDLE_LEN);
+        assert!(ix.accounts[2].is_signer);
+        for (keys, bundle) in sessions.iter().zip(ix.accounts[3..].chunks(SESSION_BUNDLE_LEN)) {
+            let expected = [
+                keys.session,
+                keys.hidden_state,
+                keys.input_buffer,
+                pda::meter(&keys.session),
+            ];
+            for (meta, key) in bundle.iter().zip(expected) {
+                assert_eq!((meta.pubkey, meta.is_writable), (key, true));
+            }
+        }
+        assert_eq!(ix.data, instruction::FrameAdvance::DISCRIMINATOR);
+    }
+}
@@ -0,0 +1,28 @@
+//! Rust client SDK for `programs/world-model`.
+//!
+//! Bots, crankers and indexers build instructions and decode accounts here
+//! instead of hand-rolling discriminators and byte offsets:
+//!   - `instructions` — builders for the session loop and the weight upload
+//!     pipeline, with PDAs filled in; `instructions::build` covers every
+//!     other instruction via the program's generated `accounts` /
+//!     `instruction` types
+//!   - `pda` — program-derived addresses
+//!   - `events` — program events parsed from transaction logs
+//!   - `decode` — session and input buffer accounts, hidden state and
+//!     checkpoint headers, Borsh accounts, and the ECS FrameLog header
+//!   - `proof` — bank-hash inclusion proofs of session results and replay
+//!     records, for bridges that shouldn't trust an indexer
+//!   - `simulator` — steps a session natively with the program's inference
+//!     code, for local model development
+//!
+//! The TypeScript SDK in `solana/client` targets the BOLT ECS programs; this
+//! crate targets the standalone Anchor program.
+
+pub mod decode;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+pub mod proof;
+pub mod simulator;
+
+pub use world_model::{accounts, instruction, state, ID};
@@ -0,0 +1,109 @@
+//! Program-derived addresses of the world-model program. Seeds match the
+//! `*_SEED` constants in `world_model::state`.
+
+use anchor_lang::prelude::Pubkey;
+use world_model::state::*;
+
+fn find(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &world_model::ID).0
+}
+
+/// Usage meter created with a session: ["meter", session]
+pub fn meter(session: &Pubkey) -> Pubkey {
+    find(&[METER_SEED, session.as_ref()])
+}
+
+/// Ephemeral rollup config of a session: ["delegation", session]
+pub fn delegation_config(session: &Pubkey) -> Pubkey {
+    find(&[DELEGATION_SEED, session.as_ref()])
+}
+
+/// Settlement summary of a session: ["settlement", session]
+pub fn settlement_summary(session: &Pubkey) -> Pubkey {
+    find(&[SETTLEMENT_SEED, session.as_ref()])
+}
+
+/// Royalty treasury of a manifest: ["treasury", manifest]
+pub fn treasury(manifest: &Pubkey) -> Pubkey {
+    find(&[TREASURY_SEED, manifest.as_ref()])
+}
+
+/// Singleton protocol fee schedule: ["fee_schedule"]
+pub fn fee_schedule() -> Pubkey {
+    find(&[FEE_SCHEDULE_SEED])
+}
+
+/// Shard directory of a manifest: ["shards", manifest]
+pub fn shard_directory(manifest: &Pubkey) -> Pubkey {
+    find(&[SHARD_DIRECTORY_SEED, manifest.as_ref()])
+}
+
+/// Expert table of a mixture-of-experts manifest: ["experts", manifest]
+pub fn expert_table(manifest: &Pubkey) -> Pubkey {
+    find(&[EXPERT_TABLE_SEED, manifest.as_ref()])
+}
+
+/// Activation calibration of a manifest: ["calibration", manifest]
+pub fn calibration(manifest: &Pubkey) -> Pubkey {
+    find(&[CALIBRATION_SEED, manifest.as_ref()])
+}
+
+/// Wager escrow of a session: ["wager", session]
+pub fn wager(session: &Pubkey) -> Pubkey {
+    find(&[WAGER_SEED, session.as_ref()])
+}
+
+/// Spectator stats / tip jar of a session: ["stats", session]
+pub fn session_stats(session: &Pubkey) -> Pubkey {
+    find(&[SESSION_STATS_SEED, session.as_ref()])
+}
+
+/// SPL tip jar of a session for `mint`: ["tip_jar", session, mint]
+pub fn tip_jar(session: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find(&[TIP_JAR_SEED, session.as_ref(), mint.as_ref()])
+}
+
+/// Singleton session rent pool: ["rent_pool"]
+pub fn rent_pool() -> Pubkey {
+    find(&[RENT_POOL_SEED])
+}
+
+/// A funder's rent pool position: ["rent_funder", funder]
+pub fn rent_funder(funder: &Pubkey) -> Pubkey {
+    find(&[RENT_FUNDER_SEED, funder.as_ref()])
+}
+
+/// Rent loan of a session: ["rent_loan", session]
+pub fn rent_loan(session: &Pubkey) -> Pubkey {
+    find(&[RENT_LOAN_SEED, session.as_ref()])
+}
+
+/// Tournament `id` of an organizer: ["tournament", organizer, id u64 LE]
+pub fn tournament(organizer: &Pubkey, id: u64) -> Pubkey {
+    find(&[TOURNAMENT_SEED, organizer.as_ref(), &id.to_le_bytes()])
+}
+
+/// Hidden-state checkpoint of a session: ["checkpoint", session]
+pub fn checkpoint(session: &Pubkey) -> Pubkey {
+    find(&[CHECKPOINT_SEED, session.as_ref()])
+}
+
+/// Replay record of a session: ["replay", session]
+pub fn replay(session: &Pubkey) -> Pubkey {
+    find(&[REPLAY_SEED, session.as_ref()])
+}
+
+/// Replay NFT mint of a session: ["replay_mint", session]
+pub fn replay_mint(session: &Pubkey) -> Pubkey {
+    find(&[REPLAY_MINT_SEED, session.as_ref()])
+}
+
+/// A verifier's replay verification run: ["replay_verify", record, verifier]
+pub fn replay_verification(record: &Pubkey, verifier: &Pubkey) -> Pubkey {
+    find(&[REPLAY_VERIFY_SEED, record.as_ref(), verifier.as_ref()])
+}
+
+/// Creator of replay archive trees: ["archive_authority"]
+pub fn archive_authority() -> Pubkey {
+    find(&[ARCHIVE_AUTHORITY_SEED])
+}
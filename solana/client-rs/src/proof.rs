@@ -0,0 +1,363 @@
+//! Light-client proofs of session results and replay records.
+//!
+//! A bank hash commits to every account written in its slot:
+//!
+//! ```text
+//! bank_hash     = sha256(parent_bank_hash || accounts_delta_hash
+//!                        || signature_count (u64 LE) || last_blockhash)
+//! account_hash  = blake3(lamports || rent_epoch || data || executable
+//!                        || owner || pubkey)
+//! ```
+//!
+//! where `accounts_delta_hash` is the root of a 16-ary SHA-256 Merkle tree
+//! over the slot's account hashes, sorted by pubkey. An [`AccountProof`]
+//! carries one account, its Merkle path and the other bank hash inputs, so
+//! a bridge or L2 that already trusts a bank hash (from validator votes)
+//! can check a session's final standings or a replay record without
+//! trusting an indexer.
+//!
+//! Proofs are only possible for a slot that wrote the account — for a
+//! session, the close_session / reap_session slot. [`prove`] builds one
+//! from that slot's full (pubkey, account hash) list, which a Geyser
+//! plugin or the validator's accounts-db provides.
+
+use anchor_lang::prelude::*;
+use awm_events::{SessionResult, OUTCOME_DRAW, OUTCOME_UNPLAYED, OUTCOME_WINNER};
+use solana_hash::Hash;
+use solana_sha256_hasher::hashv;
+use world_model::state::*;
+
+use crate::decode::{self, DecodeError};
+use crate::pda;
+
+/// Children per node of the accounts-delta Merkle tree
+pub const MERKLE_FANOUT: usize = 16;
+
+/// Why a proof was rejected.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProofError {
+    /// A Merkle level doesn't contain the hash below it at its index
+    BrokenPath,
+    /// The recomputed bank hash isn't the trusted one
+    BankHashMismatch,
+    /// The account isn't owned by world-model
+    WrongOwner,
+    /// The account isn't at the expected address
+    WrongAccount,
+    /// The session hasn't ended, so it has no result yet
+    NotEnded,
+    /// The proven data doesn't decode
+    Decode(DecodeError),
+}
+
+impl core::fmt::Display for ProofError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProofError::BrokenPath => f.write_str("merkle path does not connect"),
+            ProofError::BankHashMismatch => f.write_str("bank hash mismatch"),
+            ProofError::WrongOwner => f.write_str("account not owned by world-model"),
+            ProofError::WrongAccount => f.write_str("account address mismatch"),
+            ProofError::NotEnded => f.write_str("session has not ended"),
+            ProofError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+impl From<DecodeError> for ProofError {
+    fn from(e: DecodeError) -> Self {
+        ProofError::Decode(e)
+    }
+}
+
+/// An account as stored at the end of a slot.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProvenAccount {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data: Vec<u8>,
+}
+
+impl ProvenAccount {
+    /// The account's leaf hash. Closed (zero-lamport) accounts hash to
+    /// the default hash.
+    pub fn hash(&self) -> Hash {
+        if self.lamports == 0 {
+            return Hash::default();
+        }
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.lamports.to_le_bytes());
+        hasher.update(&self.rent_epoch.to_le_bytes());
+        hasher.update(&self.data);
+        hasher.update(&[self.executable as u8]);
+        hasher.update(self.owner.as_ref());
+        hasher.update(self.pubkey.as_ref());
+        Hash::new_from_array(*hasher.finalize().as_bytes())
+    }
+}
+
+/// Bank hash inputs other than the accounts-delta hash.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BankHashInputs {
+    pub parent_bank_hash: Hash,
+    pub signature_count: u64,
+    pub last_blockhash: Hash,
+}
+
+impl BankHashInputs {
+    pub fn bank_hash(&self, accounts_delta_hash: &Hash) -> Hash {
+        hashv(&[
+            self.parent_bank_hash.as_ref(),
+            accounts_delta_hash.as_ref(),
+            &self.signature_count.to_le_bytes(),
+            self.last_blockhash.as_ref(),
+        ])
+    }
+}
+
+/// One Merkle level: the group of sibling hashes hashed together into the
+/// next level's node, with the proven node at `index`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MerkleLevel {
+    pub hashes: Vec<Hash>,
+    pub index: u8,
+}
+
+/// Inclusion proof of one account in a slot's bank hash.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AccountProof {
+    pub account: ProvenAccount,
+    /// Leaf level first
+    pub path: Vec<MerkleLevel>,
+    pub bank: BankHashInputs,
+}
+
+impl AccountProof {
+    /// The accounts-delta hash the path leads to.
+    pub fn accounts_delta_hash(&self) -> core::result::Result<Hash, ProofError> {
+        let mut node = self.account.hash();
+        for level in &self.path {
+            if level.hashes.get(level.index as usize) != Some(&node) {
+                return Err(ProofError::BrokenPath);
+            }
+            node = hash_group(&level.hashes);
+        }
+        if self.path.is_empty() {
+            return Err(ProofError::BrokenPath);
+        }
+        Ok(node)
+    }
+
+    /// Check the account is in the slot whose bank hash is `bank_hash`.
+    pub fn verify(&self, bank_hash: &Hash) -> core::result::Result<(), ProofError> {
+        let root = self.accounts_delta_hash()?;
+        if self.bank.bank_hash(&root) != *bank_hash {
+            return Err(ProofError::BankHashMismatch);
+        }
+        Ok(())
+    }
+
+    fn world_model_data(&self, bank_hash: &Hash) -> core::result::Result<&[u8], ProofError> {
+        self.verify(bank_hash)?;
+        if self.account.owner != world_model::ID {
+            return Err(ProofError::WrongOwner);
+        }
+        Ok(&self.account.data)
+    }
+}
+
+fn hash_group(hashes: &[Hash]) -> Hash {
+    let parts: Vec<&[u8]> = hashes.iter().map(|h| h.as_ref()).collect();
+    hashv(&parts)
+}
+
+fn sorted_hashes(leaves: &[(Pubkey, Hash)]) -> Vec<(Pubkey, Hash)> {
+    let mut sorted = leaves.to_vec();
+    sorted.sort_unstable_by_key(|(k, _)| *k);
+    sorted
+}
+
+/// Root of the Merkle tree over a slot's (pubkey, account hash) pairs, in
+/// any order.
+pub fn accounts_delta_hash(leaves: &[(Pubkey, Hash)]) -> Hash {
+    let mut level: Vec<Hash> = sorted_hashes(leaves).into_iter().map(|(_, h)| h).collect();
+    loop {
+        level = level.chunks(MERKLE_FANOUT).map(hash_group).collect();
+        match level.len() {
+            0 => return hashv(&[]),
+            1 => return level[0],
+            _ => {}
+        }
+    }
+}
+
+/// Prove `account` against a slot's full (pubkey, account hash) list.
+/// `None` if the list doesn't hold the account with these contents.
+pub fn prove(
+    account: ProvenAccount,
+    leaves: &[(Pubkey, Hash)],
+    bank: BankHashInputs,
+) -> Option<AccountProof> {
+    let sorted = sorted_hashes(leaves);
+    let mut pos = sorted.binary_search_by_key(&account.pubkey, |(k, _)| *k).ok()?;
+    if sorted[pos].1 != account.hash() {
+        return None;
+    }
+
+    let mut level: Vec<Hash> = sorted.into_iter().map(|(_, h)| h).collect();
+    let mut path = Vec::new();
+    loop {
+        let start = pos - pos % MERKLE_FANOUT;
+        let end = (start + MERKLE_FANOUT).min(level.len());
+        path.push(MerkleLevel {
+            hashes: level[start..end].to_vec(),
+            index: (pos - start) as u8,
+        });
+        level = level.chunks(MERKLE_FANOUT).map(hash_group).collect();
+        pos /= MERKLE_FANOUT;
+        if level.len() == 1 {
+            break;
+        }
+    }
+    Some(AccountProof { account, path, bank })
+}
+
+/// A session's final standings, proven against `bank_hash`.
+pub fn verify_session_result(
+    proof: &AccountProof,
+    bank_hash: &Hash,
+) -> core::result::Result<SessionResult, ProofError> {
+    let session = decode::session_state(proof.world_model_data(bank_hash)?)?;
+    if session.status != STATUS_ENDED {
+        return Err(ProofError::NotEnded);
+    }
+    let (outcome, winner) = match session.outcome() {
+        SessionOutcome::Winner(0) => (OUTCOME_WINNER, session.player1),
+        SessionOutcome::Winner(_) => (OUTCOME_WINNER, session.player2),
+        SessionOutcome::Draw => (OUTCOME_DRAW, Pubkey::default()),
+        SessionOutcome::Unplayed => (OUTCOME_UNPLAYED, Pubkey::default()),
+    };
+    Ok(SessionResult {
+        session: proof.account.pubkey,
+        frame: session.frame,
+        outcome,
+        winner,
+        stocks: [session.players[0].stocks, session.players[1].stocks],
+        percents: [session.players[0].percent, session.players[1].percent],
+    })
+}
+
+/// `session`'s replay record (final frame and state commitment, verified
+/// flag), proven against `bank_hash`.
+pub fn verify_replay_record(
+    proof: &AccountProof,
+    bank_hash: &Hash,
+    session: &Pubkey,
+) -> core::result::Result<ReplayRecordAccount, ProofError> {
+    let data = proof.world_model_data(bank_hash)?;
+    if proof.account.pubkey != pda::replay(session) {
+        return Err(ProofError::WrongAccount);
+    }
+    let record: ReplayRecordAccount = decode::account(data)?;
+    if record.session != *session {
+        return Err(ProofError::WrongAccount);
+    }
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn bank() -> BankHashInputs {
+        BankHashInputs {
+            parent_bank_hash: Hash::new_from_array([1; 32]),
+            signature_count: 42,
+            last_blockhash: Hash::new_from_array([2; 32]),
+        }
+    }
+
+    fn ended_session() -> ProvenAccount {
+        let mut state: SessionStateAccount = bytemuck::Zeroable::zeroed();
+        state.status = STATUS_ENDED;
+        state.frame = 3600;
+        state.player1 = Pubkey::new_unique();
+        state.player2 = Pubkey::new_unique();
+        state.players[0].stocks = 1;
+        state.players[1].stocks = 2;
+        ProvenAccount {
+            pubkey: Pubkey::new_unique(),
+            lamports: 1_000_000,
+            owner: world_model::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+            data: [SessionStateAccount::DISCRIMINATOR, bytemuck::bytes_of(&state)].concat(),
+        }
+    }
+
+    /// The account among `n - 1` others, as a slot's leaf list.
+    fn slot_with(account: &ProvenAccount, n: usize) -> Vec<(Pubkey, Hash)> {
+        let mut leaves: Vec<(Pubkey, Hash)> = (1..n)
+            .map(|i| (Pubkey::new_unique(), hashv(&[&i.to_le_bytes()[..]])))
+            .collect();
+        leaves.push((account.pubkey, account.hash()));
+        leaves
+    }
+
+    #[test]
+    fn test_single_leaf_root_is_hashed_once() {
+        let leaf = (Pubkey::new_unique(), Hash::new_from_array([9; 32]));
+        assert_eq!(accounts_delta_hash(&[leaf]), hashv(&[leaf.1.as_ref()]));
+    }
+
+    #[test]
+    fn test_proof_reaches_root_at_every_size() {
+        for n in [1, 2, 16, 17, 300] {
+            let account = ended_session();
+            let leaves = slot_with(&account, n);
+            let root = accounts_delta_hash(&leaves);
+            let proof = prove(account, &leaves, bank()).unwrap();
+            assert_eq!(proof.accounts_delta_hash(), Ok(root), "n = {}", n);
+            assert_eq!(proof.verify(&bank().bank_hash(&root)), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_session_result_from_proof() {
+        let account = ended_session();
+        let leaves = slot_with(&account, 40);
+        let bank_hash = bank().bank_hash(&accounts_delta_hash(&leaves));
+        let proof = prove(account.clone(), &leaves, bank()).unwrap();
+
+        let result = verify_session_result(&proof, &bank_hash).unwrap();
+        assert_eq!(result.session, account.pubkey);
+        assert_eq!(result.outcome, OUTCOME_WINNER);
+        assert_eq!(result.stocks, [1, 2]);
+
+        let mut tampered = proof.clone();
+        tampered.account.data[8 + 1] ^= 1;
+        assert_eq!(verify_session_result(&tampered, &bank_hash), Err(ProofError::BrokenPath));
+
+        let other_bank = Hash::new_from_array([3; 32]);
+        assert_eq!(
+            verify_session_result(&proof, &other_bank),
+            Err(ProofError::BankHashMismatch)
+        );
+    }
+
+    #[test]
+    fn test_prove_rejects_stale_contents() {
+        let account = ended_session();
+        let leaves = slot_with(&account, 20);
+        let mut stale = account.clone();
+        stale.lamports += 1;
+        assert!(prove(stale, &leaves, bank()).is_none());
+        let absent = ProvenAccount { pubkey: Pubkey::new_unique(), ..account };
+        assert!(prove(absent, &leaves, bank()).is_none());
+    }
+}
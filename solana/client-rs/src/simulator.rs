@@ -0,0 +1,433 @@
+//! Native off-chain simulator.
+//!
+//! Steps a session in memory with the program's own code, so model authors
+//! can run frames, diff against training rollouts and debug quantization
+//! without a validator:
+//!   - `Dynamics::Stub` runs `inference::step_players`, exactly what
+//!     run_inference does today
+//!   - `Dynamics::Model` runs encode_input → forward_pass → decode_output
+//!     over the manifest's tensor table, LUTs and encoder spec (and a
+//!     mixture-of-experts model's expert table, see `with_expert_table`),
+//!     then the manifest's output smoothing and the stage's blast zones
+//!
+//! Either way the frame counter and rolling commitment advance as they do
+//! on-chain, so `session.state_commitment` can be checked against a live
+//! session or checkpoint.
+
+use world_model::inference::{
+    action_tensors_from_manifest, decode_output, encode_input, dt_shift_for, forward_pass,
+    enforce_blast_zones, manifest_layer_kinds, smooth_players, step_players, ActionTensors,
+    DecodedPlayerState, HiddenStateMut, Mamba2Config,
+};
+use world_model::rng::frame_seed;
+use world_model::stages::stage_or_default;
+use world_model::state::*;
+
+/// How a frame's next player state is computed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dynamics {
+    Stub,
+    Model,
+}
+
+/// Why a frame couldn't be simulated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SimError {
+    /// The session isn't ACTIVE
+    SessionNotActive,
+    /// A tensor or expert table entry points outside the shards or has the
+    /// wrong shape, or a mixture-of-experts model has no expert table
+    InvalidWeights,
+}
+
+/// Hidden state in its stored element type.
+enum Hidden {
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+}
+
+pub struct Simulator {
+    pub manifest: ModelManifestAccount,
+    pub session: SessionStateAccount,
+    /// Weight data regions in shard index order
+    shards: Vec<Vec<u8>>,
+    /// Routers and experts of a mixture-of-experts model
+    expert_table: Option<ExpertTableAccount>,
+    hidden: Hidden,
+    /// Raw model output of the last `Dynamics::Model` frame, before decoding
+    pub last_output: Option<Vec<i8>>,
+    /// Expert each layer routed to on the last `Dynamics::Model` frame
+    pub last_routes: Vec<u8>,
+    /// Decoded values output smoothing damped on the last `Dynamics::Model`
+    /// frame, before smoothing (run_inference's OutputSmoothed event)
+    pub last_raw: Vec<i32>,
+}
+
+impl Simulator {
+    /// A session as create_session + join_session leave it: both players
+    /// spawned, ACTIVE at frame 0, hidden state zeroed. `shards` are weight
+    /// data regions in shard index order (see `shard_data`).
+    pub fn new(
+        manifest: ModelManifestAccount,
+        shards: Vec<Vec<u8>>,
+        characters: [u8; NUM_PLAYERS],
+        stage: u8,
+    ) -> Self {
+        let mut session: SessionStateAccount = bytemuck::Zeroable::zeroed();
+        session.status = STATUS_ACTIVE;
+        session.stage = stage;
+        session.players = PlayerState::spawns(characters, session.seed, stage);
+
+        let elems = manifest.num_layers as usize
+            * manifest.d_inner as usize
+            * manifest.d_state as usize;
+        let hidden = if manifest.hidden_dtype == HIDDEN_DTYPE_I16 {
+            Hidden::I16(vec![0; elems])
+        } else {
+            Hidden::I8(vec![0; elems])
+        };
+
+        Simulator {
+            manifest,
+            session,
+            shards,
+            expert_table: None,
+            hidden,
+            last_output: None,
+            last_routes: Vec::new(),
+            last_raw: Vec::new(),
+        }
+    }
+
+    /// Route a mixture-of-experts manifest's layers through its expert table.
+    pub fn with_expert_table(mut self, table: ExpertTableAccount) -> Self {
+        self.expert_table = Some(table);
+        self
+    }
+
+    /// Weight data region of a raw WeightAccount (everything past its header).
+    pub fn shard_data(account_data: &[u8]) -> &[u8] {
+        account_data.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[])
+    }
+
+    /// Kernel config for the next frame, at the session's tick rate (set
+    /// `session.tick_rate` to simulate a 30 or 120fps session). Stochastic
+    /// requantization draws from the frame's stream, as run_inference does.
+    pub fn config(&self) -> Mamba2Config {
+        Mamba2Config {
+            d_model: self.manifest.d_model as usize,
+            d_inner: self.manifest.d_inner as usize,
+            d_state: self.manifest.d_state as usize,
+            num_layers: self.manifest.num_layers as usize,
+            num_heads: self.manifest.num_heads as usize,
+            ngroups: self.manifest.ngroups as usize,
+            dt_shift: dt_shift_for(self.session.tick_rate()),
+            rounding: self
+                .manifest
+                .rounding(frame_seed(self.session.seed, self.session.frame + 1)),
+        }
+    }
+
+    /// Advance one frame with both players' inputs. On error the session is
+    /// left untouched.
+    pub fn step(
+        &mut self,
+        inputs: [ControllerInput; NUM_PLAYERS],
+        dynamics: Dynamics,
+    ) -> Result<(), SimError> {
+        if self.session.status != STATUS_ACTIVE {
+            return Err(SimError::SessionNotActive);
+        }
+
+        match dynamics {
+            Dynamics::Stub => {
+                step_players(&mut self.session.players, &inputs, self.session.stage)
+            }
+            Dynamics::Model => {
+                let output = self.forward(&inputs)?;
+                let players = &self.session.players;
+                let offsets =
+                    self.manifest.output_offsets([players[0].character, players[1].character]);
+                let decoded = decode_output(
+                    &self.manifest.encoder_spec,
+                    self.action_tensors()?.as_ref(),
+                    &self.manifest.luts,
+                    &output,
+                    offsets,
+                );
+                let previous = self.session.players;
+                for (p, d) in self.session.players.iter_mut().zip(decoded.iter()) {
+                    *p = player_from_decoded(d);
+                }
+                self.last_raw = smooth_players(
+                    &self.manifest.encoder_spec,
+                    &self.manifest.output_smoothing,
+                    &previous,
+                    &mut self.session.players,
+                );
+                enforce_blast_zones(
+                    &mut self.session.players,
+                    previous.map(|p| p.stocks),
+                    stage_or_default(self.session.stage),
+                );
+                self.last_output = Some(output);
+            }
+        }
+
+        self.session.frame += 1;
+        self.session
+            .commit_frame(&inputs)
+            .expect("frame commitment serializes into a Vec");
+        Ok(())
+    }
+
+    /// `step` over a rollout, returning the players after each frame.
+    pub fn run(
+        &mut self,
+        inputs: &[[ControllerInput; NUM_PLAYERS]],
+        dynamics: Dynamics,
+    ) -> Result<Vec<[PlayerState; NUM_PLAYERS]>, SimError> {
+        inputs
+            .iter()
+            .map(|frame_inputs| {
+                self.step(*frame_inputs, dynamics)?;
+                Ok(self.session.players)
+            })
+            .collect()
+    }
+
+    /// The manifest's action embedding and logit head in the loaded shards
+    /// (None for a one-hot model).
+    fn action_tensors(&self) -> Result<Option<ActionTensors<'_>>, SimError> {
+        let shards: Vec<&[u8]> = self.shards.iter().map(|s| s.as_slice()).collect();
+        action_tensors_from_manifest(&self.manifest, &shards).ok_or(SimError::InvalidWeights)
+    }
+
+    /// Encode the current state, run every layer and return the raw model
+    /// output. The manifest's per-layer scales apply to every channel.
+    fn forward(&mut self, inputs: &[ControllerInput; NUM_PLAYERS]) -> Result<Vec<i8>, SimError> {
+        let config = self.config();
+        let manifest = &self.manifest;
+        if manifest.num_experts > 0 && self.expert_table.is_none() {
+            return Err(SimError::InvalidWeights);
+        }
+
+        let mut input = vec![0i8; config.d_model];
+        encode_input(
+            &manifest.encoder_spec,
+            self.action_tensors()?.as_ref(),
+            &self.session.players,
+            inputs,
+            self.session.stage,
+            &mut input,
+            config.d_model,
+        );
+
+        let num_layers = config.num_layers.min(MAX_LAYERS);
+        let in_scales: Vec<Vec<u16>> = manifest.layer_input_scales[..num_layers]
+            .iter()
+            .map(|&s| vec![s; 3 * config.d_inner])
+            .collect();
+        let out_scales: Vec<Vec<u16>> = manifest.layer_output_scales[..num_layers]
+            .iter()
+            .map(|&s| vec![s; config.d_model])
+            .collect();
+        let in_refs: Vec<&[u16]> = in_scales.iter().map(|s| s.as_slice()).collect();
+        let out_refs: Vec<&[u16]> = out_scales.iter().map(|s| s.as_slice()).collect();
+        let shards: Vec<&[u8]> = self.shards.iter().map(|s| s.as_slice()).collect();
+
+        // Scan on a copy so a failed layer leaves the hidden state as it was
+        let mut hidden = match &self.hidden {
+            Hidden::I8(h) => Hidden::I8(h.clone()),
+            Hidden::I16(h) => Hidden::I16(h.clone()),
+        };
+        let view = match &mut hidden {
+            Hidden::I8(h) => HiddenStateMut::I8(h),
+            Hidden::I16(h) => HiddenStateMut::I16(h),
+        };
+        let mut routes = vec![0u8; num_layers];
+        let output = forward_pass(
+            &input,
+            view,
+            &shards,
+            &manifest.tensor_table[..num_layers],
+            self.expert_table.as_ref(),
+            &manifest_layer_kinds(manifest),
+            &manifest.luts,
+            &config,
+            &in_refs,
+            &out_refs,
+            &mut routes,
+            &mut [],
+        )
+        .ok_or(SimError::InvalidWeights)?;
+
+        self.hidden = hidden;
+        self.last_routes = routes;
+        Ok(output)
+    }
+
+    /// The hidden state as raw account data (header + little-endian
+    /// elements), comparable byte for byte with the on-chain account.
+    pub fn hidden_account_data(&self) -> Vec<u8> {
+        let m = &self.manifest;
+        let header = HiddenHeader {
+            num_layers: m.num_layers,
+            d_inner: m.d_inner,
+            d_state: m.d_state,
+            data_size: hidden_data_size(m.num_layers, m.d_inner, m.d_state, m.hidden_dtype),
+            frame: self.session.frame,
+            initialized: self.session.frame > 0,
+            dtype: m.hidden_dtype,
+        };
+        let mut data = vec![0u8; HIDDEN_HEADER_SIZE];
+        write_hidden_header(&mut data, &header);
+        match &self.hidden {
+            Hidden::I8(h) => data.extend(h.iter().map(|&v| v as u8)),
+            Hidden::I16(h) => data.extend(h.iter().flat_map(|v| v.to_le_bytes())),
+        }
+        data
+    }
+}
+
+fn player_from_decoded(d: &DecodedPlayerState) -> PlayerState {
+    PlayerState {
+        x: d.x,
+        y: d.y,
+        percent: d.percent,
+        shield_strength: d.shield_strength,
+        speed_air_x: d.speed_air_x,
+        speed_y: d.speed_y,
+        speed_ground_x: d.speed_ground_x,
+        speed_attack_x: d.speed_attack_x,
+        speed_attack_y: d.speed_attack_y,
+        state_age: d.state_age,
+        hitlag: d.hitlag,
+        stocks: d.stocks,
+        facing: d.facing,
+        on_ground: d.on_ground,
+        action_state: d.action_state,
+        jumps_left: d.jumps_left,
+        character: d.character,
+    }
+}
+
+/// Fields where two player states differ, as (field, expected, actual) —
+/// for diffing simulated frames against a training rollout.
+pub fn diff_players(
+    expected: &PlayerState,
+    actual: &PlayerState,
+) -> Vec<(&'static str, i64, i64)> {
+    macro_rules! fields {
+        ($($f:ident),*) => {
+            vec![$((stringify!($f), expected.$f as i64, actual.$f as i64)),*]
+        };
+    }
+    fields!(
+        x, y, percent, shield_strength, speed_air_x, speed_y, speed_ground_x, speed_attack_x,
+        speed_attack_y, state_age, hitlag, stocks, facing, on_ground, action_state, jumps_left,
+        character
+    )
+    .into_iter()
+    .filter(|(_, e, a)| e != a)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountDeserialize;
+    use world_model::fixture::SyntheticModel;
+
+    /// All-zero manifest (stub dynamics ignore the model).
+    fn zero_manifest() -> ModelManifestAccount {
+        ModelManifestAccount::try_deserialize_unchecked(&mut &[0u8; 8192][..]).unwrap()
+    }
+
+    fn input(stick_x: i8, buttons: u8) -> ControllerInput {
+        ControllerInput { stick_x, buttons, ..Default::default() }
+    }
+
+    #[test]
+    fn test_stub_matches_program() {
+        let mut sim = Simulator::new(zero_manifest(), Vec::new(), [2, 20], 31);
+        let rollout = [
+            [input(80, 0), input(-80, 1)],
+            [input(0, 1), input(-80, 0)],
+            [input(-20, 0), input(0, 0)],
+        ];
+        let frames = sim.run(&rollout, Dynamics::Stub).unwrap();
+
+        let mut players = PlayerState::spawns([2, 20], 0, sim.session.stage);
+        let mut commitment = [0u8; 32];
+        for (i, inputs) in rollout.iter().enumerate() {
+            step_players(&mut players, inputs, sim.session.stage);
+            commitment = frame_commitment(&commitment, i as u32 + 1, &players, inputs).unwrap();
+            for slot in 0..NUM_PLAYERS {
+                assert!(diff_players(&players[slot], &frames[i][slot]).is_empty());
+            }
+        }
+        assert_eq!(sim.session.frame, 3);
+        assert_eq!(sim.session.state_commitment, commitment);
+    }
+
+    #[test]
+    fn test_model_without_weights_fails_cleanly() {
+        let mut manifest = zero_manifest();
+        manifest.d_model = 8;
+        manifest.d_inner = 4;
+        manifest.d_state = 2;
+        manifest.num_layers = 1;
+        let mut sim = Simulator::new(manifest, Vec::new(), [2, 20], 31);
+        let before = sim.session.players;
+
+        let result = sim.step([input(10, 0), input(0, 0)], Dynamics::Model);
+        assert_eq!(result, Err(SimError::InvalidWeights));
+        assert_eq!(sim.session.frame, 0);
+        assert!(diff_players(&before[0], &sim.session.players[0]).is_empty());
+        assert_eq!(sim.hidden_account_data().len(), HIDDEN_HEADER_SIZE + 8);
+    }
+
+    #[test]
+    fn test_model_runs_synthetic_fixture() {
+        let fixture = SyntheticModel::tiny();
+        let rollout = [
+            [input(80, 0), input(-80, 1)],
+            [input(0, 1), input(-80, 0)],
+            [input(-20, 0), input(0, 0)],
+        ];
+        let simulate = || {
+            let mut sim = Simulator::new(fixture.manifest(), fixture.shards.clone(), [2, 20], 31);
+            let frames = sim.run(&rollout, Dynamics::Model).unwrap();
+            (sim, frames)
+        };
+
+        let (sim, frames) = simulate();
+        assert_eq!(sim.session.frame, 3);
+        assert_eq!(sim.last_output.as_ref().map(Vec::len), Some(fixture.config.d_model));
+        let hidden = sim.hidden_account_data();
+        assert_eq!(hidden.len(), fixture.hidden_account_data().len());
+        assert!(hidden[HIDDEN_HEADER_SIZE..].iter().any(|&b| b != 0));
+
+        // Same model, same inputs: same frames and commitment
+        let (again, again_frames) = simulate();
+        assert_eq!(again.session.state_commitment, sim.session.state_commitment);
+        for (a, b) in frames.iter().zip(&again_frames) {
+            for slot in 0..NUM_PLAYERS {
+                assert!(diff_players(&a[slot], &b[slot]).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_players_lists_changed_fields() {
+        let a = PlayerState::spawns([2, 20], 0, 31)[0];
+        let mut b = a;
+        b.percent = 12;
+        b.x -= 256;
+        assert_eq!(
+            diff_players(&a, &b),
+            vec![("x", a.x as i64, b.x as i64), ("percent", 0, 12)]
+        );
+    }
+}
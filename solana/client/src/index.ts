@@ -36,9 +36,12 @@
 export {
   type PlayerState,
   type SessionState,
+  type RenderPlayer,
+  type RenderState,
   type VizPlayerFrame,
   type VizFrame,
   SessionStatus,
+  EndReason,
   playerStateToViz,
   sessionToVizFrame,
   validateVizFrame,
@@ -55,6 +58,8 @@ export {
   sendInput,
   endSession,
   listModels,
+  deserializeRenderState,
+  spectate,
 } from "./session";
 
 // Input handling
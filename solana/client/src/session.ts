@@ -22,7 +22,14 @@ import {
   InitializeComponent,
   ApplySystem,
 } from "@magicblock-labs/bolt-sdk";
-import { SessionState, SessionStatus, VizFrame, sessionToVizFrame } from "./state";
+import {
+  RenderState,
+  SessionState,
+  SessionKind,
+  SessionStatus,
+  VizFrame,
+  sessionToVizFrame,
+} from "./state";
 import { ControllerInput, defaultInput } from "./input";
 
 // ── Program IDs (must match declare_id! in Rust) ─────────────────────────────
@@ -42,6 +49,56 @@ export const RUN_INFERENCE_PROGRAM_ID = new PublicKey(
   "3tHPJJSNhKwbp7K5vSYCUdYVX9bGxRCmpddwaJWRKPyb"
 );
 
+/** Settle session system program ID */
+export const SETTLE_SESSION_PROGRAM_ID = new PublicKey(
+  "8kw2QHBzSQZAJPHSCioJoReq97LekkRNLXLciqGefyER"
+);
+
+/** Resolve damage system program ID (runs after run_inference) */
+export const RESOLVE_DAMAGE_PROGRAM_ID = new PublicKey(
+  "HJoxZQJWCZAxapCvnoJqfQmd2w4EX7usejjtEkfLiUy"
+);
+
+/** Stage collision system program ID (runs after run_inference) */
+export const STAGE_COLLISION_PROGRAM_ID = new PublicKey(
+  "Ecvh29rvVX5woEdV3QD61JnQx5hvnH8Y1auSjQebethy"
+);
+
+/** Aggregate stats system program ID (runs after settle_session) */
+export const AGGREGATE_STATS_PROGRAM_ID = new PublicKey(
+  "HxaNZGsEnGwNqXJPpVEFJcuXnDmtNZHibYsvnZdTYFwc"
+);
+
+/** Anti-stall system program ID (runs after run_inference) */
+export const ANTI_STALL_PROGRAM_ID = new PublicKey(
+  "B6weNxhYJEuiHR7AZcvGWYqWB9abHscdxKRdzfydw72r"
+);
+
+/** Sync render system program ID (runs last each frame) */
+export const SYNC_RENDER_PROGRAM_ID = new PublicKey(
+  "GzpHb4cogzaXYaTr5kjJLMuXFwQRF2JgN8e1VLgaqErD"
+);
+
+/** Spawn items system program ID (runs after run_inference when items are on) */
+export const SPAWN_ITEMS_PROGRAM_ID = new PublicKey(
+  "5WN21KyfuPo7jZLWet2a5W594q7kQWo4J66T6ChgfU1J"
+);
+
+/** Ledge correction system program ID (runs after stage_collision) */
+export const LEDGE_CORRECTION_PROGRAM_ID = new PublicKey(
+  "AoyARRdZ6Anyd5ZAL7fCApC7iGoYZw6zPjGga8L7mrpY"
+);
+
+/** Combo tracker system program ID (runs after resolve_damage) */
+export const COMBO_TRACKER_PROGRAM_ID = new PublicKey(
+  "CN6qNVQGxn6iMMVbRvgBD5Z4c2VqQYhTKz9rSX6cBmgK"
+);
+
+/** Export training system program ID (runs last each frame when export is on) */
+export const EXPORT_TRAINING_PROGRAM_ID = new PublicKey(
+  "7p5kNMA4mTgcrmmSa8Ru6xLFTUpFa5jhAKTWUWpCv24"
+);
+
 /** Component program IDs */
 export const SESSION_STATE_PROGRAM_ID = new PublicKey(
   "FJwbNTbGHSpq4a72ro1aza53kvs7YMNT7J5U34kaosFj"
@@ -55,12 +112,44 @@ export const INPUT_BUFFER_PROGRAM_ID = new PublicKey(
 export const FRAME_LOG_PROGRAM_ID = new PublicKey(
   "3mWTNv5jhzLnpG4Xt9XqM1b2nbNpizoGEJxepUhhoaNK"
 );
+export const SESSION_RESULT_PROGRAM_ID = new PublicKey(
+  "97Yh4tUphcQHcLsLZ16cQUF7irmF6NkEYfVjFPn5wG3V"
+);
+export const PLAYER_PROFILE_PROGRAM_ID = new PublicKey(
+  "2H17JVS3vhTmzHF3jYhFrziG2nsH1uQgiWm3sE4svamr"
+);
+export const COMBAT_STATE_PROGRAM_ID = new PublicKey(
+  "AxtvkTx4q4szRmhDtE614gRscqP2KStkDC2QD8uzFkoQ"
+);
+export const STAGE_GEOMETRY_PROGRAM_ID = new PublicKey(
+  "HFQnLTeVuMxPGVU1QDc86xtesaheQFYxhm2QZcmqck5r"
+);
+export const SESSION_STATS_PROGRAM_ID = new PublicKey(
+  "Ae9VHs4Ayui2tF9pxxCy7coqGoAySfhJS6JmbAfLPiT6"
+);
+export const PLAYER_STATS_PROGRAM_ID = new PublicKey(
+  "4hC15QFy1pNRNj6DWwQ5ukZBfmBDXKrYuVh7HeZ3W9hD"
+);
+export const STALL_MONITOR_PROGRAM_ID = new PublicKey(
+  "Dj2DAP6C26k9XEDRRnsN56tynPjrcHLytFFGwrSP34uP"
+);
+export const RENDER_STATE_PROGRAM_ID = new PublicKey(
+  "Dv3czUCp1L3fWpYifVQ4vsyBrs2aRVoAUu3JLKE7KAL4"
+);
+export const ITEM_STATE_PROGRAM_ID = new PublicKey(
+  "c4wr2cifFVyLUSPu6Q8haMku7RbU5vJyAqQLqTiXxwU"
+);
+export const TRAINING_EXPORT_PROGRAM_ID = new PublicKey(
+  "ChAHZMdVwTQFogf87m9QMKucAQwMTRPmsAhzssL6jHC7"
+);
 
 // ── Lifecycle action codes ──────────────────────────────────────────────────
 
 const ACTION_CREATE = 0;
 const ACTION_JOIN = 1;
 const ACTION_END = 2;
+const ACTION_CLAIM_SLOT = 3;
+const ACTION_RELEASE_SLOT = 4;
 
 // ── Session configuration ───────────────────────────────────────────────────
 
@@ -81,6 +170,15 @@ export interface SessionConfig {
   dInner?: number;
   dState?: number;
   numLayers?: number;
+  /** Push every frame as a SpectatorFrame event (costs CU and log space) */
+  emitFrames?: boolean;
+  /** Frames per second: 30, 60 or 120 (default 60) */
+  tickRate?: number;
+  /**
+   * Create a persistent world instead of a match: it never ends, and
+   * players claimSlot/releaseSlot on the fly (maxFrames is ignored)
+   */
+  persistent?: boolean;
 }
 
 // ── BOLT session accounts (PDAs, not keypairs) ─────────────────────────────
@@ -255,6 +353,10 @@ export class SessionClient {
         d_inner: this.config.dInner ?? 768,
         d_state: this.config.dState ?? 64,
         num_layers: this.config.numLayers ?? 4,
+        emit_frames: this.config.emitFrames ?? false,
+        tick_rate: this.config.tickRate ?? 60,
+        kind: this.config.persistent ? SessionKind.Persistent : SessionKind.Match,
+        archived_frames: 0,
       },
     });
     await sendAndConfirmTransaction(
@@ -264,7 +366,7 @@ export class SessionClient {
     );
 
     this.emitStatus(`Session created: entity=${entityPda.toBase58().slice(0, 8)}...`);
-    this.emitStatus("Waiting for player 2...");
+    this.emitStatus(this.config.persistent ? "World running, slots open." : "Waiting for player 2...");
 
     return entityPda;
   }
@@ -301,6 +403,10 @@ export class SessionClient {
         d_inner: 0,
         d_state: 0,
         num_layers: 0,
+        emit_frames: false,
+        tick_rate: 0,
+        kind: 0,
+        archived_frames: 0,
       },
     });
     await sendAndConfirmTransaction(
@@ -312,6 +418,70 @@ export class SessionClient {
     this.emitStatus("Joined! Session active.");
   }
 
+  /**
+   * Plug into a persistent world: take its first open controller slot.
+   */
+  async claimSlot(accounts: BoltSessionAccounts): Promise<void> {
+    this.accounts = accounts;
+    this.emitStatus("Claiming a slot...");
+    await this.applySlotAction(ACTION_CLAIM_SLOT);
+    const session = await this.fetchSessionState();
+    this.playerNumber = session.player1 === this.player.publicKey.toBase58() ? 1 : 2;
+    this.emitStatus(`Claimed slot ${this.playerNumber}.`);
+  }
+
+  /**
+   * Unplug from a persistent world, leaving it running for everyone else.
+   */
+  async releaseSlot(): Promise<void> {
+    this.stopPlaying();
+    if (!this.accounts) return;
+
+    this.emitStatus("Releasing slot...");
+    await this.applySlotAction(ACTION_RELEASE_SLOT);
+    this.emitStatus("Slot released.");
+    this.accounts = undefined;
+  }
+
+  private async applySlotAction(action: number): Promise<void> {
+    if (!this.accounts) throw new Error("No active session");
+    const result = await ApplySystem({
+      authority: this.player.publicKey,
+      systemId: SESSION_LIFECYCLE_PROGRAM_ID,
+      world: this.accounts.worldPda,
+      entities: [{
+        entity: this.accounts.entityPda,
+        components: [
+          { componentId: SESSION_STATE_PROGRAM_ID },
+          { componentId: HIDDEN_STATE_PROGRAM_ID },
+          { componentId: INPUT_BUFFER_PROGRAM_ID },
+          { componentId: FRAME_LOG_PROGRAM_ID },
+        ],
+      }],
+      args: {
+        action,
+        player: this.player.publicKey.toBase58(),
+        character: this.config.character,
+        stage: 0,
+        model: PublicKey.default.toBase58(),
+        max_frames: 0,
+        seed: 0,
+        d_inner: 0,
+        d_state: 0,
+        num_layers: 0,
+        emit_frames: false,
+        tick_rate: 0,
+        kind: 0,
+        archived_frames: 0,
+      },
+    });
+    await sendAndConfirmTransaction(
+      this.connection,
+      result.transaction,
+      [this.player],
+    );
+  }
+
   /**
    * Send a single frame's controller input via submit_input system.
    */
@@ -430,6 +600,10 @@ export class SessionClient {
         d_inner: 0,
         d_state: 0,
         num_layers: 0,
+        emit_frames: false,
+        tick_rate: 0,
+        kind: 0,
+        archived_frames: 0,
       },
     });
     await sendAndConfirmTransaction(
@@ -477,7 +651,7 @@ export function deserializeSessionState(data: Buffer): SessionState {
   let offset = 8;
 
   const status = data.readUInt8(offset);
-  if (status > 3) {
+  if (status > SessionStatus.Settled) {
     // Probably hit bolt_metadata — skip 32 more bytes
     offset = 8 + 32;
   }
@@ -519,6 +693,11 @@ function parseSessionStateAt(data: Buffer, offset: number): SessionState {
   const seedLow = data.readUInt32LE(offset);
   const seedHigh = data.readUInt32LE(offset + 4);
   const seed = seedLow + seedHigh * 0x100000000;
+  offset += 8;
+
+  const endReason = data.readUInt8(offset); offset += 1;
+  const tickRate = data.readUInt8(offset) || 60; offset += 1;
+  const kind = data.readUInt8(offset);
 
   return {
     status,
@@ -532,6 +711,9 @@ function parseSessionStateAt(data: Buffer, offset: number): SessionState {
     createdAt,
     lastUpdate,
     seed,
+    endReason,
+    tickRate,
+    kind,
   };
 }
 
@@ -557,6 +739,61 @@ function deserializePlayerState(data: Buffer, offset: number): import("./state")
   };
 }
 
+// ── Spectating ──────────────────────────────────────────────────────────────
+
+const RENDER_PLAYER_SIZE = 13; // 4+4+2+2+1 = 13 bytes
+
+/**
+ * Deserialize raw RenderState account data (fields follow the 8-byte
+ * discriminator).
+ */
+export function deserializeRenderState(data: Buffer): RenderState {
+  const offset = 8;
+  const frame = data.readUInt32LE(offset);
+  const players: [import("./state").RenderPlayer, import("./state").RenderPlayer] = [
+    deserializeRenderPlayer(data, offset + 4),
+    deserializeRenderPlayer(data, offset + 4 + RENDER_PLAYER_SIZE),
+  ];
+  return { frame, players };
+}
+
+function deserializeRenderPlayer(data: Buffer, offset: number): import("./state").RenderPlayer {
+  return {
+    x: data.readInt32LE(offset),
+    y: data.readInt32LE(offset + 4),
+    percent: data.readUInt16LE(offset + 8),
+    actionState: data.readUInt16LE(offset + 10),
+    facing: data.readUInt8(offset + 12),
+  };
+}
+
+/**
+ * Watch a session as a spectator. Subscribes to the RenderState component
+ * (~40 bytes per update) instead of the full SessionState.
+ *
+ * Returns a function that removes the subscription.
+ */
+export function spectate(
+  connection: Connection,
+  renderStatePda: PublicKey,
+  onRender: (state: RenderState) => void
+): () => void {
+  const id = connection.onAccountChange(
+    renderStatePda,
+    (accountInfo) => {
+      try {
+        onRender(deserializeRenderState(accountInfo.data));
+      } catch (e) {
+        console.warn("Failed to deserialize render state:", e);
+      }
+    },
+    "processed"
+  );
+  return () => {
+    connection.removeAccountChangeListener(id);
+  };
+}
+
 // ── Model browser ───────────────────────────────────────────────────────────
 
 export interface ModelInfo {
@@ -39,6 +39,26 @@ export interface SessionState {
   createdAt: number;
   lastUpdate: number;
   seed: number;
+  /** EndReason value (why an Ended session ended) */
+  endReason: number;
+  /** Frames per second */
+  tickRate: number;
+  /** SessionKind value (a match, or a persistent world that never ends) */
+  kind: number;
+}
+
+/** The per-frame fields spectators draw (RenderState component) */
+export interface RenderPlayer {
+  x: number; // Fixed-point: actual = x / 256.0
+  y: number;
+  percent: number;
+  actionState: number;
+  facing: number;
+}
+
+export interface RenderState {
+  frame: number;
+  players: [RenderPlayer, RenderPlayer];
 }
 
 export const SessionStatus = {
@@ -46,6 +66,17 @@ export const SessionStatus = {
   WaitingPlayers: 1,
   Active: 2,
   Ended: 3,
+  Settled: 4,
+} as const;
+
+export const EndReason = {
+  None: 0,
+  Stall: 1,
+} as const;
+
+export const SessionKind = {
+  Match: 0,
+  Persistent: 1,
 } as const;
 
 // ── Visualizer JSON format ──────────────────────────────────────────────────
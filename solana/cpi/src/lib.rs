@@ -0,0 +1,205 @@
+//! CPI interface to `programs/world-model`'s view instructions.
+//!
+//! `get_session_summary`, `get_result` and `get_frame` read one session
+//! account and return a Borsh value through `set_return_data`; they write
+//! nothing. Another program (a betting market, a quest system) invokes one
+//! with the builders in [`instruction`] and decodes the answer with the
+//! top-level helpers, which check the return data came from world-model.
+//! Off-chain callers simulate the same instructions.
+//!
+//! world-model returns these exact types, so the schema lives in one
+//! place. Fields are only ever appended.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{get_return_data, invoke};
+
+/// The world-model program (its `declare_id!`; world-model's tests check
+/// they match)
+pub const WORLD_MODEL_ID: Pubkey = pubkey!("BC9hw928r1rvxedMwTSDSvRxFSHMgY4maWnYa9TV71nZ");
+
+/// `status` values of a session (world-model's STATUS_*)
+pub const STATUS_CREATED: u8 = 0;
+pub const STATUS_WAITING_PLAYERS: u8 = 1;
+pub const STATUS_ACTIVE: u8 = 2;
+pub const STATUS_ENDED: u8 = 3;
+
+/// `outcome` values of a [`MatchResult`] (awm-events' OUTCOME_*)
+pub const OUTCOME_UNPLAYED: u8 = 0;
+pub const OUTCOME_WINNER: u8 = 1;
+pub const OUTCOME_DRAW: u8 = 2;
+
+// ── Return types ─────────────────────────────────────────────────────────────
+
+/// get_session_summary: who is playing what, and how far along it is.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SessionSummary {
+    /// STATUS_*
+    pub status: u8,
+    pub stage: u8,
+    pub model: Pubkey,
+    pub player1: Pubkey,
+    /// Default until player 2 joins
+    pub player2: Pubkey,
+    pub frame: u32,
+    /// 0 = unlimited
+    pub max_frames: u32,
+    pub created_at: i64,
+    pub last_update: i64,
+    pub stocks: [u8; 2],
+    pub percents: [u16; 2],
+    /// Frames per second
+    pub tick_rate: u8,
+}
+
+/// get_result: the standings (most stocks wins, then lowest percent). Only
+/// final once `ended` is set; before that it's the standings so far.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    pub ended: bool,
+    /// OUTCOME_*
+    pub outcome: u8,
+    /// Default unless `outcome` is OUTCOME_WINNER
+    pub winner: Pubkey,
+    pub frame: u32,
+    pub stocks: [u8; 2],
+    pub percents: [u16; 2],
+    /// SHA-256 of the frame number and both players' state (what replay
+    /// NFTs and archives record)
+    pub final_state_hash: [u8; 32],
+}
+
+/// One player as of a frame.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlayerView {
+    /// Fixed-point position (× 256)
+    pub x: i32,
+    pub y: i32,
+    pub percent: u16,
+    pub stocks: u8,
+    pub facing: u8,
+    pub on_ground: u8,
+    pub action_state: u16,
+    pub character: u8,
+}
+
+/// get_frame: the session's latest frame.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameView {
+    pub frame: u32,
+    /// Rolling per-frame commitment through `frame`
+    pub state_commitment: [u8; 32],
+    pub players: [PlayerView; 2],
+}
+
+// ── Instructions ─────────────────────────────────────────────────────────────
+
+/// Builders for the view instructions. Each takes the session account,
+/// read-only, and no arguments.
+pub mod instruction {
+    use super::*;
+    use anchor_lang::solana_program::instruction::Instruction;
+
+    /// Anchor discriminators of the view instructions: the first 8 bytes
+    /// of sha256("global:<name>")
+    pub const GET_SESSION_SUMMARY: [u8; 8] = [88, 72, 96, 61, 166, 52, 44, 177];
+    pub const GET_RESULT: [u8; 8] = [57, 144, 166, 101, 148, 52, 100, 135];
+    pub const GET_FRAME: [u8; 8] = [93, 207, 134, 63, 102, 78, 170, 42];
+
+    fn view(discriminator: [u8; 8], session: Pubkey) -> Instruction {
+        Instruction {
+            program_id: WORLD_MODEL_ID,
+            accounts: vec![AccountMeta::new_readonly(session, false)],
+            data: discriminator.to_vec(),
+        }
+    }
+
+    pub fn get_session_summary(session: Pubkey) -> Instruction {
+        view(GET_SESSION_SUMMARY, session)
+    }
+
+    pub fn get_result(session: Pubkey) -> Instruction {
+        view(GET_RESULT, session)
+    }
+
+    pub fn get_frame(session: Pubkey) -> Instruction {
+        view(GET_FRAME, session)
+    }
+}
+
+// ── CPI helpers ──────────────────────────────────────────────────────────────
+
+/// Decode world-model's return data as `T`. Fails if the last return data
+/// came from another program or doesn't decode.
+pub fn read_return<T: AnchorDeserialize>() -> Result<T> {
+    let (program_id, data) = get_return_data().ok_or(ProgramError::InvalidAccountData)?;
+    require_keys_eq!(program_id, WORLD_MODEL_ID, ErrorCode::InvalidProgramId);
+    T::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData.into())
+}
+
+fn call<'info, T: AnchorDeserialize>(
+    ix: anchor_lang::solana_program::instruction::Instruction,
+    world_model: &AccountInfo<'info>,
+    session: &AccountInfo<'info>,
+) -> Result<T> {
+    require_keys_eq!(world_model.key(), WORLD_MODEL_ID, ErrorCode::InvalidProgramId);
+    invoke(&ix, &[session.clone(), world_model.clone()])?;
+    read_return()
+}
+
+/// CPI get_session_summary on `session`.
+pub fn get_session_summary<'info>(
+    world_model: &AccountInfo<'info>,
+    session: &AccountInfo<'info>,
+) -> Result<SessionSummary> {
+    call(instruction::get_session_summary(session.key()), world_model, session)
+}
+
+/// CPI get_result on `session`.
+pub fn get_result<'info>(
+    world_model: &AccountInfo<'info>,
+    session: &AccountInfo<'info>,
+) -> Result<MatchResult> {
+    call(instruction::get_result(session.key()), world_model, session)
+}
+
+/// CPI get_frame on `session`.
+pub fn get_frame<'info>(
+    world_model: &AccountInfo<'info>,
+    session: &AccountInfo<'info>,
+) -> Result<FrameView> {
+    call(instruction::get_frame(session.key()), world_model, session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_instructions_take_session_read_only() {
+        let session = Pubkey::new_unique();
+        for (ix, discriminator) in [
+            (instruction::get_session_summary(session), instruction::GET_SESSION_SUMMARY),
+            (instruction::get_result(session), instruction::GET_RESULT),
+            (instruction::get_frame(session), instruction::GET_FRAME),
+        ] {
+            assert_eq!(ix.program_id, WORLD_MODEL_ID);
+            assert_eq!(ix.data, discriminator);
+            assert_eq!(ix.accounts, vec![AccountMeta::new_readonly(session, false)]);
+        }
+    }
+
+    #[test]
+    fn test_return_types_round_trip() {
+        let frame = FrameView {
+            frame: 90,
+            state_commitment: [7; 32],
+            players: [
+                PlayerView { x: -256, stocks: 4, ..Default::default() },
+                PlayerView { x: 256, percent: 12, stocks: 3, ..Default::default() },
+            ],
+        };
+        let mut bytes = Vec::new();
+        frame.serialize(&mut bytes).unwrap();
+        assert_eq!(FrameView::try_from_slice(&bytes).unwrap(), frame);
+    }
+}
@@ -0,0 +1,284 @@
+//! Events emitted by `programs/world-model` and `programs/fraud-proof`.
+//!
+//! Each struct is an Anchor `#[event]`: its discriminator is
+//! `sha256("event:<Name>")[..8]` and its body is Borsh, logged as
+//! `Program data: <base64>`. Renaming a struct or reordering its fields
+//! breaks indexers, so fields are only ever appended. awm-client decodes
+//! them from transaction logs (`awm_client::events`).
+
+use anchor_lang::prelude::*;
+
+// ── Session lifecycle ────────────────────────────────────────────────────────
+
+/// create_session: player 1 opened a session, waiting for player 2.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionCreated {
+    pub session: Pubkey,
+    pub model: Pubkey,
+    pub player1: Pubkey,
+    pub stage: u8,
+    pub character: u8,
+    pub max_frames: u32,
+    /// Frames per second
+    pub tick_rate: u8,
+}
+
+/// join_session: player 2 joined and the session went ACTIVE.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionJoined {
+    pub session: Pubkey,
+    pub player2: Pubkey,
+    pub character: u8,
+}
+
+/// close_session / reap_session: the session ENDED. Followed by
+/// `SessionResult`.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionEnded {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// The player who closed it, or the reaper
+    pub closed_by: Pubkey,
+}
+
+/// reap_session: an idle session was ended and its fee settled.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionReaped {
+    pub session: Pubkey,
+    pub reaper: Pubkey,
+    /// Seconds since the session's last activity
+    pub idle_secs: i64,
+    /// Lamports paid to the reaper from the session's fee
+    pub bounty: u64,
+}
+
+// ── Frames ───────────────────────────────────────────────────────────────────
+
+/// run_inference: the session advanced one frame.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameAdvanced {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// Rolling commitment after this frame
+    pub state_commitment: [u8; 32],
+    pub cranker: Pubkey,
+}
+
+/// run_inference: a player lost a stock this frame.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerKo {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// Player slot (0 = player 1)
+    pub player: u8,
+    pub stocks_left: u8,
+}
+
+/// run_inference: the experts a mixture-of-experts model routed to this
+/// frame. Telemetry for expert load and specialization.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpertsRouted {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// Selected expert per layer (0 for dense layers)
+    pub experts: Vec<u8>,
+}
+
+/// run_inference: the model's raw decoded values of the fields its output
+/// smoothing damped this frame, before smoothing — the session holds the
+/// smoothed ones. Training data for models that smooth.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputSmoothed {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// Player 1's smoothed fields then player 2's, in encoder spec order
+    pub raw: Vec<i32>,
+}
+
+/// `FrameSkipped::reason` values, also frame_advance's per-session return
+/// codes (where ADVANCE_ADVANCED marks a session that advanced)
+pub const ADVANCE_ADVANCED: u8 = 0;
+pub const ADVANCE_NOT_ACTIVE: u8 = 1;
+pub const ADVANCE_INPUTS_NOT_READY: u8 = 2;
+pub const ADVANCE_INVALID_BUNDLE: u8 = 3;
+pub const ADVANCE_NOT_PERMITTED: u8 = 4;
+
+/// frame_advance: a session in the batch wasn't advanced.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameSkipped {
+    pub session: Pubkey,
+    /// The session's current frame (0 if its account couldn't be read)
+    pub frame: u32,
+    /// ADVANCE_*
+    pub reason: u8,
+}
+
+// ── Results ──────────────────────────────────────────────────────────────────
+
+/// `SessionResult::outcome` values
+pub const OUTCOME_UNPLAYED: u8 = 0;
+pub const OUTCOME_WINNER: u8 = 1;
+pub const OUTCOME_DRAW: u8 = 2;
+
+/// close_session / reap_session: final standings (most stocks wins, then lowest percent).
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionResult {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// OUTCOME_*
+    pub outcome: u8,
+    /// Winning player (default unless outcome is OUTCOME_WINNER)
+    pub winner: Pubkey,
+    pub stocks: [u8; 2],
+    pub percents: [u16; 2],
+}
+
+/// undelegate_session: an ended session's settlement summary went back to
+/// mainnet in place of its full hidden state.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionSettled {
+    pub session: Pubkey,
+    pub final_frame: u32,
+    /// OUTCOME_*
+    pub outcome: u8,
+    pub winner: Pubkey,
+    pub final_state_hash: [u8; 32],
+    pub state_commitment: [u8; 32],
+    /// SHA-256 of the hidden state's data before it was cut to its header
+    pub hidden_state_hash: [u8; 32],
+    /// Checkpoint of the final hidden state (default = none)
+    pub replay: Pubkey,
+}
+
+// ── Models ───────────────────────────────────────────────────────────────────
+
+/// Emitted by validate_manifest for frontends.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestValidated {
+    pub manifest: Pubkey,
+    pub passed: bool,
+    /// CHECK_* bitmask of failed checks
+    pub failures: u8,
+}
+
+/// Emitted by verify_kernels for each checked row range.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KernelsVerified {
+    pub manifest: Pubkey,
+    pub layer: u8,
+    /// TENSOR_IN_PROJ or TENSOR_OUT_PROJ
+    pub tensor: u8,
+    pub row_start: u16,
+    pub row_count: u16,
+    /// Whether the syscall and BPF outputs were byte-identical
+    pub matches: bool,
+}
+
+// ── Replays ──────────────────────────────────────────────────────────────────
+
+/// archive_replay: a session's replay became a compressed NFT leaf and its
+/// checkpoint was closed. The leaf URI carries `summary_hash`.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayArchived {
+    pub session: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// Leaf owner: the participant who archived it
+    pub owner: Pubkey,
+    /// SHA-256 of the Borsh-encoded ReplaySummary
+    pub summary_hash: [u8; 32],
+    pub final_frame: u32,
+    pub final_state_hash: [u8; 32],
+    /// SHA-256 of the closed replay checkpoint's data
+    pub replay_hash: [u8; 32],
+}
+
+// ── Automation ───────────────────────────────────────────────────────────────
+
+/// register_crank: the session's frame_advance was scheduled on the rollup.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrankRegistered {
+    pub session: Pubkey,
+    /// Pays for and signs every tick; credited as the cranker
+    pub payer: Pubkey,
+    pub task_id: i64,
+    pub interval_ms: u32,
+    pub iterations: u32,
+}
+
+/// unregister_crank: the session's scheduled task was cancelled.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrankUnregistered {
+    pub session: Pubkey,
+    pub payer: Pubkey,
+    pub task_id: i64,
+}
+
+// ── Fraud proofs ─────────────────────────────────────────────────────────────
+
+/// open_challenge: `challenger` disputed `frame` of a rollup session.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChallengeOpened {
+    pub session: Pubkey,
+    pub challenger: Pubkey,
+    pub frame: u32,
+}
+
+/// resolve_challenge: the dispute held and the operator bond was slashed.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FraudProven {
+    pub session: Pubkey,
+    pub challenger: Pubkey,
+    pub frame: u32,
+    /// Lamports slashed to the challenger
+    pub slashed: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sha256_hasher::hash;
+    use anchor_lang::Discriminator;
+
+    /// Indexers key on these bytes — they must stay sha256("event:<Name>")[..8].
+    #[test]
+    fn test_discriminators_are_stable() {
+        fn check<T: Discriminator>(name: &str) {
+            let expected = hash(format!("event:{}", name).as_bytes()).to_bytes();
+            assert_eq!(T::DISCRIMINATOR, &expected[..8], "{}", name);
+        }
+        check::<SessionCreated>("SessionCreated");
+        check::<SessionJoined>("SessionJoined");
+        check::<SessionEnded>("SessionEnded");
+        check::<SessionReaped>("SessionReaped");
+        check::<FrameAdvanced>("FrameAdvanced");
+        check::<PlayerKo>("PlayerKo");
+        check::<FrameSkipped>("FrameSkipped");
+        check::<SessionResult>("SessionResult");
+        check::<SessionSettled>("SessionSettled");
+        check::<ManifestValidated>("ManifestValidated");
+        check::<KernelsVerified>("KernelsVerified");
+        check::<ReplayArchived>("ReplayArchived");
+        check::<CrankRegistered>("CrankRegistered");
+        check::<CrankUnregistered>("CrankUnregistered");
+        check::<ChallengeOpened>("ChallengeOpened");
+        check::<FraudProven>("FraudProven");
+    }
+}
@@ -0,0 +1,220 @@
+//! Sliding-window INT8 attention for hybrid Mamba2/attention models.
+//!
+//! An attention layer swaps the selective scan for single-step attention
+//! over the last `window` frames. Its keys and values are kept in a short
+//! KV buffer stored in the layer's hidden-state slot, which an attention
+//! layer has no SSM state to put in — so reset, checkpoint and state-hash
+//! paths cover it unchanged. Slot layout (hidden-state elements):
+//!
+//!   [len] [k: window × d_inner] [v: window × d_inner]
+//!
+//! `len` counts the filled rows (0 after a reset). Rows are oldest first;
+//! once the buffer is full every frame drops the oldest row. There is no
+//! positional encoding: the window is short and the SSM layers carry order.
+//!
+//! Per head, per frame:
+//!   scores[t] = q · k[t]                      (INT8 dot product, INT32)
+//!   p = softmax(scores)                       (exp_neg LUT, Q15)
+//!   out = Σ_t p[t] · v[t]                     (Q15 MAC, rounded to INT8)
+
+use crate::lut;
+
+/// Longest window an attention layer may use (bounds the stack score
+/// buffers).
+pub const MAX_ATTN_WINDOW: usize = 32;
+
+/// Base softmax shift for attention scores (see `lut::softmax_i32`); each
+/// layer adds log2(head_dim) / 2, the 1/√head_dim of real attention.
+pub const SCORE_SHIFT: u32 = 6;
+
+/// Hidden-state elements an attention layer's KV buffer takes.
+pub const fn kv_len(window: usize, d_inner: usize) -> usize {
+    1 + 2 * window * d_inner
+}
+
+/// Longest window whose KV buffer fits in one layer's (d_inner × d_state)
+/// hidden-state slot, capped at MAX_ATTN_WINDOW.
+pub fn max_window(d_inner: usize, d_state: usize) -> usize {
+    if d_inner == 0 {
+        return 0;
+    }
+    ((d_inner * d_state).saturating_sub(1) / (2 * d_inner)).min(MAX_ATTN_WINDOW)
+}
+
+/// Hidden-state element types a KV buffer can be stored in. Entries are
+/// always INT8 values; INT16 hidden state holds them widened.
+pub trait KvElem: Copy {
+    fn from_i8(v: i8) -> Self;
+    fn to_i32(self) -> i32;
+}
+
+impl KvElem for i8 {
+    #[inline(always)]
+    fn from_i8(v: i8) -> Self {
+        v
+    }
+
+    #[inline(always)]
+    fn to_i32(self) -> i32 {
+        self as i32
+    }
+}
+
+impl KvElem for i16 {
+    #[inline(always)]
+    fn from_i8(v: i8) -> Self {
+        v as i16
+    }
+
+    /// Clamped to INT8, so a corrupted buffer can't overflow the MACs.
+    #[inline(always)]
+    fn to_i32(self) -> i32 {
+        (self as i32).clamp(-128, 127)
+    }
+}
+
+/// One attention step: append this frame's `k` and `v` (d_inner each) to
+/// the KV buffer `kv`, then attend `q` over every buffered frame, writing
+/// d_inner outputs to `out`. Channels split evenly across `num_heads`;
+/// any remainder past the last head is zeroed.
+#[allow(clippy::too_many_arguments)]
+pub fn attention_step<T: KvElem>(
+    lut_data: &[u8],
+    q: &[i8],
+    k: &[i8],
+    v: &[i8],
+    kv: &mut [T],
+    window: usize,
+    num_heads: usize,
+    out: &mut [i8],
+) {
+    let d = q.len();
+    let window = window.clamp(1, MAX_ATTN_WINDOW);
+    let heads = num_heads.max(1);
+    let head_dim = d / heads;
+    assert!(k.len() == d && v.len() == d && out.len() >= d);
+    assert!(kv.len() >= kv_len(window, d));
+
+    let (len_slot, rows) = kv.split_at_mut(1);
+    let (keys, rest) = rows.split_at_mut(window * d);
+    let values = &mut rest[..window * d];
+
+    // Append this frame, dropping the oldest once the buffer is full
+    let mut len = len_slot[0].to_i32().clamp(0, window as i32) as usize;
+    if len == window {
+        keys.copy_within(d.., 0);
+        values.copy_within(d.., 0);
+        len -= 1;
+    }
+    for j in 0..d {
+        keys[len * d + j] = T::from_i8(k[j]);
+        values[len * d + j] = T::from_i8(v[j]);
+    }
+    len += 1;
+    len_slot[0] = T::from_i8(len as i8);
+
+    let shift = SCORE_SHIFT + head_dim.max(1).ilog2() / 2;
+    let mut scores = [0i32; MAX_ATTN_WINDOW];
+    let mut probs = [0u16; MAX_ATTN_WINDOW];
+    out[..d].fill(0);
+
+    for h in 0..heads {
+        let cols = h * head_dim..(h + 1) * head_dim;
+        for (t, score) in scores[..len].iter_mut().enumerate() {
+            let key = &keys[t * d..(t + 1) * d];
+            *score = cols.clone().map(|j| q[j] as i32 * key[j].to_i32()).sum();
+        }
+        lut::softmax_i32(lut_data, &scores[..len], shift, &mut probs[..len]);
+
+        for j in cols {
+            let acc: i32 = (0..len)
+                .map(|t| probs[t] as i32 * values[t * d + j].to_i32())
+                .sum();
+            // Q15 probabilities sum to one, so this stays in INT8 range
+            out[j] = ((acc + (1 << 14)) >> 15).clamp(-128, 127) as i8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const D: usize = 4;
+
+    /// exp_neg falls linearly from 255 to 0
+    fn luts() -> Vec<u8> {
+        (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn test_max_window_fits_slot() {
+        assert_eq!(max_window(8, 2), 0);
+        assert_eq!(max_window(8, 4), 1);
+        assert!(kv_len(max_window(768, 64), 768) <= 768 * 64);
+        assert_eq!(max_window(1024, 128), MAX_ATTN_WINDOW);
+        assert_eq!(max_window(0, 64), 0);
+    }
+
+    /// The buffer fills, then slides: after three frames a window of two
+    /// holds frames 2 and 3.
+    #[test]
+    fn test_kv_buffer_slides() {
+        let luts = luts();
+        let mut kv = [0i8; kv_len(2, D)];
+        let mut out = [0i8; D];
+        for f in 1..=3i8 {
+            let row = [f; D];
+            attention_step(&luts, &[0; D], &row, &[-f; D], &mut kv, 2, 1, &mut out);
+        }
+        assert_eq!(kv[0], 2);
+        assert_eq!(&kv[1..1 + 2 * D], &[2, 2, 2, 2, 3, 3, 3, 3]);
+        assert_eq!(&kv[1 + 2 * D..], &[-2, -2, -2, -2, -3, -3, -3, -3]);
+        // Equal scores average the two values, rounding -2.5 up
+        assert_eq!(out, [-2; D]);
+    }
+
+    #[test]
+    fn test_single_frame_returns_its_value() {
+        let luts = luts();
+        let v = [20i8, -5, 0, 127];
+        let mut kv = [0i16; kv_len(4, D)];
+        let mut out = [0i8; D];
+        attention_step(&luts, &[1; D], &[3; D], &v, &mut kv, 4, 2, &mut out);
+        assert_eq!(out, v);
+        assert_eq!(kv[0], 1);
+    }
+
+    /// Each head attends to the frame whose key lines up with its half of
+    /// the query.
+    #[test]
+    fn test_heads_pick_matching_keys() {
+        let luts = luts();
+        let mut kv = [0i8; kv_len(2, D)];
+        let mut out = [0i8; D];
+        attention_step(
+            &luts,
+            &[0; D],
+            &[100, 100, -100, -100],
+            &[10; D],
+            &mut kv,
+            2,
+            2,
+            &mut out,
+        );
+        attention_step(
+            &luts,
+            &[-100, -100, -100, -100],
+            &[-100, -100, 100, 100],
+            &[-10; D],
+            &mut kv,
+            2,
+            2,
+            &mut out,
+        );
+        // Head 0 matches frame 2's key, head 1 frame 1's
+        assert_eq!(out, [-10, -10, 10, 10]);
+    }
+}
@@ -0,0 +1,25 @@
+//! INT8 Mamba2 kernels shared by `programs/world-model` and the ECS
+//! `run-inference` system.
+//!
+//! No Solana or Anchor dependencies — everything works on plain slices, so
+//! the kernels test on the host and the syscall crate can check its native
+//! matmul against them. Encoding game state into the model input vector
+//! stays with each program, since it depends on their account types.
+//!
+//! `no_std`: the kernels only need core. The default `alloc` feature adds
+//! the Vec-returning conveniences (`mamba2::forward_layers`,
+//! `mamba2::ScratchArena`); without it, callers hand `ScratchBuffers` their
+//! own storage and run `forward_layers_in_place`. Tests build with std.
+
+#![cfg_attr(not(test), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod attention;
+pub mod lut;
+pub mod mamba2;
+pub mod matmul;
+pub mod rng;
+pub mod ssm;
+pub mod tensor_dir;
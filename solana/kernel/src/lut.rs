@@ -0,0 +1,650 @@
+//! LUT-based activation functions for INT8 Mamba2 inference.
+//!
+//! Each LUT is a 256-entry table mapping an INT8 input (-128..127) to an INT8 output.
+//! For unsigned activations (rsqrt, exp_neg), the input/output are unsigned (0..255).
+//!
+//! LUTs are stored in the ModelManifest account, packed as:
+//!   v1: [silu_lut(256)] [softplus_lut(256)] [rsqrt_lut(256)] [exp_neg_lut(256)]
+//!   v2: v1 ++ [gelu_lut(256)] [tanh_lut(256)]
+//!
+//! Each version only appends tables, so a v1 block is a prefix of a v2
+//! block and existing offsets never move. The block's length identifies
+//! its version.
+//!
+//! Total: 1536 bytes. Negligible compared to weight storage.
+//! Lookup cost: 1 memory access (~1-2 CU) vs hundreds of CU for software float.
+
+/// Entries per LUT
+pub const LUT_SIZE: usize = 256;
+/// Tables in the current (v2) block: SiLU, softplus, rsqrt, exp_neg, GELU, tanh
+pub const NUM_LUTS: usize = 6;
+
+/// LUT offsets within the packed LUT data
+pub const SILU_OFFSET: usize = 0;
+pub const SOFTPLUS_OFFSET: usize = 256;
+pub const RSQRT_OFFSET: usize = 512;
+pub const EXP_NEG_OFFSET: usize = 768;
+pub const GELU_OFFSET: usize = 1024;
+pub const TANH_OFFSET: usize = 1280;
+pub const LUT_TOTAL_SIZE: usize = LUT_SIZE * NUM_LUTS;
+
+/// Block versions
+pub const LUT_VERSION_1: u8 = 1;
+pub const LUT_VERSION_2: u8 = 2;
+pub const LUT_VERSION_CURRENT: u8 = LUT_VERSION_2;
+/// Size of a v1 block (SiLU, softplus, rsqrt, exp_neg)
+pub const LUT_V1_SIZE: usize = 1024;
+
+/// Packed size of a LUT block version.
+pub fn lut_block_size(version: u8) -> Option<usize> {
+    match version {
+        LUT_VERSION_1 => Some(LUT_V1_SIZE),
+        LUT_VERSION_2 => Some(LUT_TOTAL_SIZE),
+        _ => None,
+    }
+}
+
+/// Version of a packed LUT block, from its length.
+pub fn lut_block_version(len: usize) -> Option<u8> {
+    match len {
+        LUT_V1_SIZE => Some(LUT_VERSION_1),
+        LUT_TOTAL_SIZE => Some(LUT_VERSION_2),
+        _ => None,
+    }
+}
+
+/// SiLU activation via lookup table.
+/// SiLU(x) = x * sigmoid(x) — used for gating in Mamba2.
+///
+/// Input: signed INT8 value
+/// Output: signed INT8 value
+#[inline(always)]
+pub fn silu_lut(lut_data: &[u8], x: i8) -> i8 {
+    // Convert signed i8 to unsigned index: -128 → 0, -127 → 1, ..., 127 → 255
+    let idx = (x as u8) as usize;
+    lut_data[SILU_OFFSET + idx] as i8
+}
+
+/// Softplus activation via lookup table.
+/// softplus(x) = ln(1 + exp(x)) — used for dt computation in selective scan.
+///
+/// Input: signed INT8 value
+/// Output: signed INT8 value (always non-negative in float, but quantized range)
+#[inline(always)]
+pub fn softplus_lut(lut_data: &[u8], x: i8) -> i8 {
+    let idx = (x as u8) as usize;
+    lut_data[SOFTPLUS_OFFSET + idx] as i8
+}
+
+/// Reciprocal square root via lookup table.
+/// rsqrt(x) = 1/sqrt(x) — used for RMSNorm.
+///
+/// Input: unsigned value (mean-squared, always positive)
+/// Output: unsigned value (always positive)
+#[inline(always)]
+pub fn rsqrt_lut(lut_data: &[u8], x: u8) -> u8 {
+    lut_data[RSQRT_OFFSET + x as usize]
+}
+
+/// Negative exponential via lookup table.
+/// exp(-x) — used for A_bar computation in selective scan: A_bar = exp(-dt * A).
+///
+/// Input: unsigned value (dt * A product, always non-negative)
+/// Output: unsigned value (decay factor, 0..1 mapped to 0..255)
+#[inline(always)]
+pub fn exp_neg_lut(lut_data: &[u8], x: u8) -> u8 {
+    lut_data[EXP_NEG_OFFSET + x as usize]
+}
+
+/// GELU activation via lookup table (v2 blocks).
+/// GELU(x) = x * Φ(x) — gate activation in some exported architectures.
+///
+/// Input: signed INT8 value
+/// Output: signed INT8 value
+#[inline(always)]
+pub fn gelu_lut(lut_data: &[u8], x: i8) -> i8 {
+    let idx = (x as u8) as usize;
+    lut_data[GELU_OFFSET + idx] as i8
+}
+
+/// tanh via lookup table (v2 blocks) — output squashing.
+///
+/// Input: signed INT8 value
+/// Output: signed INT8 value
+#[inline(always)]
+pub fn tanh_lut(lut_data: &[u8], x: i8) -> i8 {
+    let idx = (x as u8) as usize;
+    lut_data[TANH_OFFSET + idx] as i8
+}
+
+/// Apply SiLU activation to a slice in-place.
+/// Used for: gate = SiLU(z) in Mamba2 gating.
+#[inline]
+pub fn silu_slice(lut_data: &[u8], data: &mut [i8]) {
+    for v in data.iter_mut() {
+        *v = silu_lut(lut_data, *v);
+    }
+}
+
+/// Apply softplus activation to a slice in-place.
+/// Used for: dt = softplus(dt_raw) in selective scan.
+#[inline]
+pub fn softplus_slice(lut_data: &[u8], data: &mut [i8]) {
+    for v in data.iter_mut() {
+        *v = softplus_lut(lut_data, *v);
+    }
+}
+
+/// Apply GELU activation to a slice in-place.
+#[inline]
+pub fn gelu_slice(lut_data: &[u8], data: &mut [i8]) {
+    for v in data.iter_mut() {
+        *v = gelu_lut(lut_data, *v);
+    }
+}
+
+/// Apply tanh to a slice in-place.
+#[inline]
+pub fn tanh_slice(lut_data: &[u8], data: &mut [i8]) {
+    for v in data.iter_mut() {
+        *v = tanh_lut(lut_data, *v);
+    }
+}
+
+/// Softmax probabilities are Q15 fixed point: PROB_ONE = 1.0.
+pub const PROB_ONE: u16 = 1 << 15;
+
+/// Index of the largest logit, first on ties (0 for an empty slice).
+pub fn argmax_i32(logits: &[i32]) -> usize {
+    let mut best = 0;
+    for (i, &l) in logits.iter().enumerate() {
+        if l > logits[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Softmax over INT32 logits via the exp_neg LUT.
+/// Used for: categorical decode (class confidence) and policy sampling.
+///
+/// Subtracts the max logit first, so every term is exp(-d) with d ≥ 0 and
+/// the largest is exp(0) — no overflow for any logit range. `shift` maps
+/// logit units to exp_neg LUT steps (index = d >> shift), i.e. it sets the
+/// temperature; gaps past the table's end saturate at its last entry.
+///
+/// Writes Q15 probabilities to `probs` (same length as `logits`). They sum
+/// to exactly PROB_ONE: the flooring remainder goes to the argmax, as does
+/// all the mass if the table rounds every term to zero.
+pub fn softmax_i32(lut_data: &[u8], logits: &[i32], shift: u32, probs: &mut [u16]) {
+    assert_eq!(logits.len(), probs.len());
+    if logits.is_empty() {
+        return;
+    }
+    let top = argmax_i32(logits);
+    let max = logits[top] as i64;
+
+    // Unnormalized terms, 0..255 each; at most 255 * len in total
+    let mut sum: u64 = 0;
+    for (p, &l) in probs.iter_mut().zip(logits.iter()) {
+        let d = (max - l as i64).checked_shr(shift).unwrap_or(0).min(255) as u8;
+        *p = exp_neg_lut(lut_data, d) as u16;
+        sum += *p as u64;
+    }
+
+    let mut total: u32 = 0;
+    for p in probs.iter_mut() {
+        *p = (*p as u64 * PROB_ONE as u64).checked_div(sum).unwrap_or(0) as u16;
+        total += *p as u32;
+    }
+    probs[top] += (PROB_ONE as u32 - total) as u16;
+}
+
+/// Draw a class from Q15 probabilities (as written by softmax_i32) with a
+/// uniform draw `r` in 0..PROB_ONE. Deterministic given `r`, so onchain
+/// replays can re-derive the same choice from a recorded seed.
+pub fn sample_categorical(probs: &[u16], r: u16) -> usize {
+    let mut cum: u32 = 0;
+    for (i, &p) in probs.iter().enumerate() {
+        cum += p as u32;
+        if (r as u32) < cum {
+            return i;
+        }
+    }
+    probs.len().saturating_sub(1)
+}
+
+/// RMSNorm using LUT for rsqrt.
+///
+/// Computes: y[i] = x[i] * weight[i] / rms(x)
+/// Where rms(x) = sqrt(mean(x^2))
+///
+/// In INT8:
+///   1. Compute mean of squared values (INT32 accumulator)
+///   2. Map to LUT index for rsqrt
+///   3. Element-wise multiply x * weight * rsqrt_factor
+///
+/// Returns: normalized values as INT8, plus the scale factor for downstream use.
+pub fn rmsnorm_int8(
+    lut_data: &[u8],
+    x: &[i8],
+    weight: &[i8],
+    output: &mut [i8],
+    weight_scale: i32,
+) {
+    let n = x.len();
+    assert_eq!(n, weight.len());
+    assert_eq!(n, output.len());
+
+    // Compute mean squared value
+    let mut sum_sq: i64 = 0;
+    for &val in x.iter() {
+        let v = val as i64;
+        sum_sq += v * v;
+    }
+
+    // Map to rsqrt LUT index
+    // mean_sq = sum_sq / n, scaled to fit in u8
+    let mean_sq = (sum_sq / n as i64) as u32;
+    // Scale to 0..255 range for LUT lookup
+    let lut_idx = (mean_sq.min(255 * 64) / 64) as u8;
+    let rsqrt_val = rsqrt_lut(lut_data, lut_idx) as i32;
+
+    // Apply normalization: output[i] = x[i] * weight[i] * rsqrt_val
+    for i in 0..n {
+        let val = x[i] as i32 * weight[i] as i32 * rsqrt_val;
+        // Rescale: adjust for weight_scale and rsqrt output scale
+        let rescaled = (val * weight_scale) >> 16;
+        output[i] = rescaled.clamp(-128, 127) as i8;
+    }
+}
+
+/// Reason a packed LUT set failed validation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LutError {
+    /// SiLU(0) is not within ±1 of zero
+    SiluNotZeroAtOrigin,
+    /// softplus decreases somewhere, or is flat end to end
+    SoftplusNotMonotone,
+    /// rsqrt increases somewhere, or is flat end to end
+    RsqrtNotDecreasing,
+    /// exp(-x) increases somewhere, or is flat end to end
+    ExpNegNotMonotone,
+    /// GELU(0) is not within ±1 of zero
+    GeluNotZeroAtOrigin,
+    /// tanh decreases somewhere, or is flat end to end
+    TanhNotMonotone,
+    /// Block length matches no LUT_VERSION_*
+    UnknownVersion,
+}
+
+/// Sanity-check a packed LUT set before it is committed to a manifest.
+///
+/// Catches obviously corrupted tables (wrong order, truncated upload,
+/// all-zero), not subtle quantization error:
+///   - SiLU(0) ≈ 0
+///   - softplus non-decreasing over -128..127 and not constant
+///   - rsqrt, exp_neg non-increasing over 0..255 and not constant
+///   - v2: GELU(0) ≈ 0; tanh non-decreasing and not constant
+///
+/// The block's length picks its version (`lut_block_version`).
+pub fn validate_luts(lut_data: &[u8]) -> Result<(), LutError> {
+    let version = lut_block_version(lut_data.len()).ok_or(LutError::UnknownVersion)?;
+
+    if silu_lut(lut_data, 0).unsigned_abs() > 1 {
+        return Err(LutError::SiluNotZeroAtOrigin);
+    }
+    if !is_signed_increasing(lut_data, softplus_lut) {
+        return Err(LutError::SoftplusNotMonotone);
+    }
+
+    if !is_decreasing(&lut_data[RSQRT_OFFSET..RSQRT_OFFSET + 256]) {
+        return Err(LutError::RsqrtNotDecreasing);
+    }
+    if !is_decreasing(&lut_data[EXP_NEG_OFFSET..EXP_NEG_OFFSET + 256]) {
+        return Err(LutError::ExpNegNotMonotone);
+    }
+    if version < LUT_VERSION_2 {
+        return Ok(());
+    }
+
+    if gelu_lut(lut_data, 0).unsigned_abs() > 1 {
+        return Err(LutError::GeluNotZeroAtOrigin);
+    }
+    if !is_signed_increasing(lut_data, tanh_lut) {
+        return Err(LutError::TanhNotMonotone);
+    }
+
+    Ok(())
+}
+
+/// A signed table is non-decreasing over -128..127 and not constant.
+fn is_signed_increasing(lut_data: &[u8], lookup: fn(&[u8], i8) -> i8) -> bool {
+    let mut prev = lookup(lut_data, -128);
+    for x in -127i8..=127 {
+        let curr = lookup(lut_data, x);
+        if curr < prev {
+            return false;
+        }
+        prev = curr;
+    }
+    lookup(lut_data, 127) != lookup(lut_data, -128)
+}
+
+/// Non-increasing and not constant.
+fn is_decreasing(lut: &[u8]) -> bool {
+    lut.windows(2).all(|w| w[1] <= w[0]) && lut[0] > lut[lut.len() - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    fn make_test_luts() -> Vec<u8> {
+        let mut luts = vec![0u8; LUT_TOTAL_SIZE];
+
+        // SiLU LUT: approximate SiLU(x) for x in [-8, 8]
+        for i in 0u16..256 {
+            let x = (i as i8) as f64 / 16.0;
+            let silu = x / (1.0 + (-x).exp());
+            luts[SILU_OFFSET + i as usize] = (silu * 16.0 + 0.5)
+                .clamp(-128.0, 127.0) as i8 as u8;
+        }
+
+        // Softplus LUT
+        for i in 0u16..256 {
+            let x = (i as i8) as f64 / 16.0;
+            let sp = (1.0 + x.exp()).ln();
+            luts[SOFTPLUS_OFFSET + i as usize] = (sp * 32.0)
+                .clamp(-128.0, 127.0) as i8 as u8;
+        }
+
+        // rsqrt LUT
+        for i in 0u16..256 {
+            let x = (i.max(1) as f64) / 32.0;
+            let rsqrt = 1.0 / x.sqrt();
+            luts[RSQRT_OFFSET + i as usize] = (rsqrt * 32.0).min(255.0) as u8;
+        }
+
+        // exp_neg LUT
+        for i in 0u16..256 {
+            let x = i as f64 / 32.0;
+            let exp_neg = (-x).exp();
+            luts[EXP_NEG_OFFSET + i as usize] = (exp_neg * 255.0) as u8;
+        }
+
+        // GELU LUT (tanh approximation)
+        for i in 0u16..256 {
+            let x = (i as i8) as f64 / 16.0;
+            let gelu = 0.5 * x * (1.0 + (0.797_884_6 * (x + 0.044715 * x.powi(3))).tanh());
+            luts[GELU_OFFSET + i as usize] = (gelu * 16.0).round()
+                .clamp(-128.0, 127.0) as i8 as u8;
+        }
+
+        // tanh LUT
+        for i in 0u16..256 {
+            let x = (i as i8) as f64 / 32.0;
+            luts[TANH_OFFSET + i as usize] = (x.tanh() * 127.0).round() as i8 as u8;
+        }
+
+        luts
+    }
+
+    #[test]
+    fn test_silu_properties() {
+        let luts = make_test_luts();
+
+        // SiLU(0) ≈ 0
+        let val = silu_lut(&luts, 0);
+        assert!(val.abs() <= 1, "SiLU(0) should be near 0, got {}", val);
+
+        // SiLU is monotonically increasing for large positive values
+        let neg = silu_lut(&luts, -64);
+        let pos = silu_lut(&luts, 64);
+        assert!(pos > neg, "SiLU should be increasing: {} > {}", pos, neg);
+    }
+
+    #[test]
+    fn test_softplus_positive() {
+        let luts = make_test_luts();
+
+        // softplus is monotonically non-decreasing
+        let mut prev = softplus_lut(&luts, -128);
+        for i in -127i8..=127 {
+            let curr = softplus_lut(&luts, i);
+            assert!(curr >= prev, "softplus should be non-decreasing at {}", i);
+            prev = curr;
+        }
+    }
+
+    #[test]
+    fn test_exp_neg_decreasing() {
+        let luts = make_test_luts();
+
+        // exp(-x) is monotonically non-increasing
+        let mut prev = exp_neg_lut(&luts, 0);
+        for i in 1u8..=255 {
+            let curr = exp_neg_lut(&luts, i);
+            assert!(curr <= prev, "exp(-x) should be non-increasing at {}", i);
+            prev = curr;
+        }
+
+        // exp(0) ≈ 1.0 → 255 in unsigned repr
+        assert!(exp_neg_lut(&luts, 0) > 200, "exp(0) should be near max");
+    }
+
+    #[test]
+    fn test_gelu_tanh_properties() {
+        let luts = make_test_luts();
+
+        // GELU(0) = 0, ≈ identity for large x, ≈ 0 for very negative x
+        assert_eq!(gelu_lut(&luts, 0), 0);
+        assert_eq!(gelu_lut(&luts, 64), 64);
+        assert!(gelu_lut(&luts, -128).abs() <= 1);
+
+        // tanh is odd and saturates
+        assert_eq!(tanh_lut(&luts, 0), 0);
+        assert_eq!(tanh_lut(&luts, 32), -tanh_lut(&luts, -32));
+        assert_eq!(tanh_lut(&luts, 127), 127);
+    }
+
+    #[test]
+    fn test_softmax_sums_to_one() {
+        let luts = make_test_luts();
+        let logits = [10, -3, 10, 40, 0];
+        let mut probs = [0u16; 5];
+        softmax_i32(&luts, &logits, 0, &mut probs);
+
+        assert_eq!(probs.iter().map(|&p| p as u32).sum::<u32>(), PROB_ONE as u32);
+        assert_eq!(argmax_i32(&logits), 3);
+        // Ordered like the logits, equal logits get equal mass
+        assert!(probs[3] > probs[0] && probs[0] > probs[4] && probs[4] > probs[1]);
+        assert_eq!(probs[0], probs[2]);
+        // exp(-30/32) / (1 + 2·exp(-30/32) + exp(-40/32) + exp(-43/32)) ≈ 0.168
+        assert!((5200..5800).contains(&probs[0]), "got {}", probs[0]);
+    }
+
+    #[test]
+    fn test_softmax_extreme_logits() {
+        let luts = make_test_luts();
+
+        // Max-subtraction: huge logits don't overflow, far gaps saturate
+        let mut probs = [0u16; 3];
+        softmax_i32(&luts, &[i32::MAX, i32::MIN, i32::MAX - 1], 0, &mut probs);
+        assert_eq!(probs[1], 0);
+        assert!(probs[0] >= probs[2]);
+        assert_eq!(probs.iter().map(|&p| p as u32).sum::<u32>(), PROB_ONE as u32);
+
+        // A larger shift flattens the distribution (higher temperature)
+        let logits = [0, 64];
+        let (mut sharp, mut flat) = ([0u16; 2], [0u16; 2]);
+        softmax_i32(&luts, &logits, 0, &mut sharp);
+        softmax_i32(&luts, &logits, 4, &mut flat);
+        assert!(sharp[1] > flat[1] && flat[1] > PROB_ONE / 2);
+
+        // An all-zero exp table puts everything on the argmax
+        let mut probs = [0u16; 2];
+        softmax_i32(&[0u8; LUT_TOTAL_SIZE], &[1, 2], 0, &mut probs);
+        assert_eq!(probs, [0, PROB_ONE]);
+    }
+
+    #[test]
+    fn test_sample_categorical() {
+        let probs = [PROB_ONE / 4, 0, PROB_ONE / 2, PROB_ONE / 4];
+        assert_eq!(sample_categorical(&probs, 0), 0);
+        assert_eq!(sample_categorical(&probs, PROB_ONE / 4 - 1), 0);
+        assert_eq!(sample_categorical(&probs, PROB_ONE / 4), 2);
+        assert_eq!(sample_categorical(&probs, PROB_ONE - 1), 3);
+        assert_eq!(sample_categorical(&[], 0), 0);
+    }
+
+    #[test]
+    fn test_validate_luts_accepts_reference() {
+        let luts = make_test_luts();
+        assert_eq!(validate_luts(&luts), Ok(()));
+        // A v1 block is the v2 block's prefix
+        assert_eq!(validate_luts(&luts[..LUT_V1_SIZE]), Ok(()));
+    }
+
+    #[test]
+    fn test_block_versions() {
+        for version in [LUT_VERSION_1, LUT_VERSION_2] {
+            let size = lut_block_size(version).unwrap();
+            assert_eq!(lut_block_version(size), Some(version));
+        }
+        assert_eq!(lut_block_size(LUT_VERSION_CURRENT), Some(LUT_TOTAL_SIZE));
+        assert_eq!(lut_block_size(0), None);
+        assert_eq!(lut_block_version(LUT_V1_SIZE + 1), None);
+        assert_eq!(
+            validate_luts(&make_test_luts()[..LUT_V1_SIZE - 1]),
+            Err(LutError::UnknownVersion)
+        );
+    }
+
+    #[test]
+    fn test_validate_luts_rejects_broken() {
+        // All-zero upload: SiLU(0) passes, softplus is flat
+        assert_eq!(
+            validate_luts(&[0u8; LUT_TOTAL_SIZE]),
+            Err(LutError::SoftplusNotMonotone)
+        );
+
+        let mut luts = make_test_luts();
+        luts[SILU_OFFSET] = 20;
+        assert_eq!(validate_luts(&luts), Err(LutError::SiluNotZeroAtOrigin));
+
+        let mut luts = make_test_luts();
+        luts[RSQRT_OFFSET + 100] = 255;
+        assert_eq!(validate_luts(&luts), Err(LutError::RsqrtNotDecreasing));
+
+        // Reversed table (increasing)
+        let mut luts = make_test_luts();
+        luts[EXP_NEG_OFFSET..GELU_OFFSET].reverse();
+        assert_eq!(validate_luts(&luts), Err(LutError::ExpNegNotMonotone));
+
+        // v2 tables are only checked in v2 blocks
+        let mut luts = make_test_luts();
+        luts[GELU_OFFSET] = 20;
+        assert_eq!(validate_luts(&luts), Err(LutError::GeluNotZeroAtOrigin));
+        assert_eq!(validate_luts(&luts[..LUT_V1_SIZE]), Ok(()));
+
+        let mut luts = make_test_luts();
+        luts[TANH_OFFSET..].fill(0);
+        assert_eq!(validate_luts(&luts), Err(LutError::TanhNotMonotone));
+    }
+
+    /// Random tables shaped to pass validation: SiLU(0) = GELU(0) = 0,
+    /// softplus and tanh sorted ascending, rsqrt and exp_neg sorted
+    /// descending, none of them flat
+    fn valid_luts() -> impl Strategy<Value = Vec<u8>> {
+        vec(any::<u8>(), LUT_TOTAL_SIZE).prop_map(|mut luts| {
+            luts[SILU_OFFSET] = 0;
+            luts[GELU_OFFSET] = 0;
+
+            for offset in [SOFTPLUS_OFFSET, TANH_OFFSET] {
+                let mut sorted: Vec<i8> =
+                    luts[offset..offset + 256].iter().map(|&v| v as i8).collect();
+                sorted.sort();
+                sorted[0] = -128;
+                sorted[255] = 127;
+                // Index 0 is x = 0; -128 lives at index 128 (x as u8)
+                for (x, v) in (-128i8..=127).zip(sorted) {
+                    luts[offset + (x as u8) as usize] = v as u8;
+                }
+            }
+
+            for offset in [RSQRT_OFFSET, EXP_NEG_OFFSET] {
+                let lut = &mut luts[offset..offset + 256];
+                lut.sort_unstable();
+                lut.reverse();
+                lut[0] = 255;
+                lut[255] = 0;
+            }
+            luts
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_slices_match_scalar_lookups(
+            luts in vec(any::<u8>(), LUT_TOTAL_SIZE),
+            data in vec(any::<i8>(), 0..64),
+        ) {
+            let mut silu = data.clone();
+            silu_slice(&luts, &mut silu);
+            let mut softplus = data.clone();
+            softplus_slice(&luts, &mut softplus);
+            let mut gelu = data.clone();
+            gelu_slice(&luts, &mut gelu);
+            let mut tanh = data.clone();
+            tanh_slice(&luts, &mut tanh);
+
+            for (i, &x) in data.iter().enumerate() {
+                prop_assert_eq!(silu[i], silu_lut(&luts, x));
+                prop_assert_eq!(softplus[i], softplus_lut(&luts, x));
+                prop_assert_eq!(gelu[i], gelu_lut(&luts, x));
+                prop_assert_eq!(tanh[i], tanh_lut(&luts, x));
+            }
+        }
+
+        #[test]
+        fn prop_rmsnorm_preserves_sign(
+            luts in vec(any::<u8>(), LUT_TOTAL_SIZE),
+            (x, weight) in (1usize..64).prop_flat_map(|n| (vec(any::<i8>(), n), vec(any::<i8>(), n))),
+            weight_scale in 0i32..=256,
+        ) {
+            let mut output = vec![0i8; x.len()];
+            rmsnorm_int8(&luts, &x, &weight, &mut output, weight_scale);
+
+            for i in 0..x.len() {
+                let product = x[i] as i32 * weight[i] as i32;
+                if product == 0 {
+                    prop_assert_eq!(output[i], 0);
+                } else if product > 0 {
+                    prop_assert!(output[i] >= 0, "x={} w={} gave {}", x[i], weight[i], output[i]);
+                } else {
+                    prop_assert!(output[i] <= 0, "x={} w={} gave {}", x[i], weight[i], output[i]);
+                }
+            }
+        }
+
+        #[test]
+        fn prop_validate_luts_accepts_well_formed(luts in valid_luts()) {
+            prop_assert_eq!(validate_luts(&luts), Ok(()));
+        }
+
+        #[test]
+        fn prop_validate_luts_rejects_rising_exp_neg(luts in valid_luts(), idx in 0usize..255) {
+            // Any uptick in exp(-x) is caught, wherever it lands
+            let mut luts = luts;
+            let lut = &mut luts[EXP_NEG_OFFSET..EXP_NEG_OFFSET + 256];
+            if lut[idx] == u8::MAX {
+                lut[idx] -= 1;
+            }
+            lut[idx + 1] = lut[idx] + 1;
+            prop_assert_eq!(validate_luts(&luts), Err(LutError::ExpNegNotMonotone));
+        }
+    }
+}
@@ -0,0 +1,1256 @@
+//! Mamba2 INT8 inference kernel for onchain execution.
+//!
+//! Implements a single-step (autoregressive) Mamba2 forward pass:
+//!   (input_state, controller_inputs, hidden_state) → (output_state, new_hidden_state)
+//!
+//! Architecture (per layer):
+//!   1. RMSNorm(x)
+//!   2. in_proj: x → [z, x_ssm, B, C]       (INT8 matmul; B and C are
+//!      (ngroups, d_state), shared across each group's heads)
+//!      dt_proj: x → dt (one per head)         (INT8 matmul)
+//!   3. Selective scan step:
+//!      dt = softplus(dt + dt_bias)             (LUT)
+//!      A_bar = exp(-dt * A)                    (LUT)
+//!      h_new = A_bar * h + dt * B * x_ssm     (INT8/INT32 MAC)
+//!      y = C * h_new                           (INT8 dot product)
+//!   4. Gate: y = y * SiLU(z)                  (LUT + multiply)
+//!   5. out_proj: y → residual                 (INT8 matmul)
+//!   6. Residual add                           (INT32 add, requantize)
+//!
+//! Mixture-of-experts layers add a router matmul after RMSNorm that picks
+//! one expert (top-1) whose in_proj/out_proj replace the dense ones in 2
+//! and 5. The router costs num_experts × d_model MACs, noise next to the
+//! projections.
+//!
+//! Hybrid checkpoints mark some layers as attention (`LayerKind`): in_proj
+//! maps to [q, k, v] instead, and steps 3-4 become sliding-window attention
+//! over a KV buffer kept in the layer's hidden-state slot (see `attention`).
+//!
+//! Per-layer CU estimate (d_model=512, d_inner=1024, d_state=16):
+//!   in_proj:  ~3.1M CU
+//!   SSM step: ~147K CU
+//!   gate:     ~5K CU
+//!   out_proj: ~1.6M CU
+//!   total:    ~4.9M CU per layer, ~59M CU for 12 layers
+//!   RMSNorm and requantization are not in these figures; cu-benchmark's
+//!   bench_rmsnorm and bench_requantize measure them
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use crate::attention;
+use crate::lut;
+use crate::matmul;
+use crate::matmul::Rounding;
+use crate::ssm;
+
+/// Tick rate models are trained at; the scan's dt is relative to one frame
+/// at this rate.
+pub const NATIVE_TICK_RATE: u32 = 60;
+
+/// Configuration for a Mamba2 model, matching ModelManifest fields.
+#[derive(Clone, Copy)]
+pub struct Mamba2Config {
+    pub d_model: usize,
+    pub d_inner: usize,
+    pub d_state: usize,
+    pub num_layers: usize,
+    pub num_heads: usize,
+    /// B/C head groups; 0 for models exported without B and C, which the
+    /// scan then derives from x_ssm
+    pub ngroups: usize,
+    /// log2 of native frames per tick: 0 at NATIVE_TICK_RATE, 1 at half of
+    /// it (dt doubled), -1 at twice it (dt halved). See `dt_shift_for`.
+    pub dt_shift: i8,
+    /// Requantization rounding; a stochastic mode carries this frame's
+    /// stream seed (`rng::frame_seed`), split per layer and per projection
+    pub rounding: Rounding,
+}
+
+/// `Mamba2Config::dt_shift` for a session ticking `tick_rate` times a
+/// second, rounded to a power-of-two ratio (0 for 0 or the native rate).
+pub fn dt_shift_for(tick_rate: u32) -> i8 {
+    match tick_rate {
+        0 => 0,
+        r if r >= NATIVE_TICK_RATE => -((r / NATIVE_TICK_RATE).ilog2() as i8),
+        r => (NATIVE_TICK_RATE / r).ilog2() as i8,
+    }
+}
+
+/// Scale a post-softplus dt by 2^shift, saturating at the INT8 range.
+#[inline(always)]
+pub fn scale_dt(dt: i8, shift: i8) -> i8 {
+    if shift >= 0 {
+        ((dt as i32) << shift.min(7)).clamp(-128, 127) as i8
+    } else {
+        dt >> (-shift).min(7)
+    }
+}
+
+/// Approximate BPF cost of one INT8 multiply-accumulate in the matmul kernel.
+pub const CU_PER_MAC: u64 = 3;
+/// Approximate BPF cost of one (d_inner × d_state) element of the scan step.
+pub const CU_PER_SCAN_ELEM: u64 = 9;
+
+impl Mamba2Config {
+    /// Estimated compute units for one full forward pass, from the per-layer
+    /// breakdown above: in_proj + out_proj (3 × d_model × d_inner MACs) plus
+    /// the selective scan (d_inner × d_state). Used for metering, not budgeting.
+    pub fn estimate_frame_cu(&self) -> u64 {
+        let macs = 3 * self.d_model as u64 * self.d_inner as u64;
+        let scan = self.d_inner as u64 * self.d_state as u64;
+        self.num_layers as u64 * (macs * CU_PER_MAC + scan * CU_PER_SCAN_ELEM)
+    }
+
+    /// Rows of a layer's dt_proj: one dt per head, at least one and at most
+    /// one per inner channel.
+    pub fn dt_heads(&self) -> usize {
+        self.num_heads.clamp(1, self.d_inner.max(1))
+    }
+
+    /// Rows of an SSM layer's in_proj: [z, x_ssm] plus the grouped [B, C].
+    pub fn in_proj_rows(&self) -> usize {
+        2 * self.d_inner + 2 * self.ngroups * self.d_state
+    }
+
+    /// Whether in_proj's output and the per-head dt fit the layer step's
+    /// 3*d_inner projection buffers, and the groups split the heads evenly.
+    pub fn groups_fit(&self) -> bool {
+        self.in_proj_rows() + self.dt_heads() <= 3 * self.d_inner
+            && (self.ngroups == 0 || self.num_heads.is_multiple_of(self.ngroups))
+    }
+}
+
+/// What a layer mixes its normed input with.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum LayerKind {
+    /// Mamba2 selective scan
+    #[default]
+    Ssm,
+    /// Sliding-window attention over the last `window` frames (at most
+    /// `attention::max_window` for the config)
+    Attention { window: usize },
+}
+
+/// One layer's weights, borrowed zero-copy from weight shard account data.
+/// Programs build these from their manifest's named-tensor table.
+pub struct LayerWeights<'a> {
+    /// in_proj weight: (in_proj_rows, d_model) — maps input to [z, x_ssm]
+    /// and grouped [B, C]; (3*d_inner, d_model) mapping to [q, k, v] for an
+    /// attention layer
+    pub in_proj: &'a [u8],
+    /// out_proj weight: (d_model, d_inner) — maps gated output back to residual
+    pub out_proj: &'a [u8],
+    /// Row sparsity masks of a structured-pruned in_proj / out_proj
+    /// (`matmul::mask_stride(cols)` bytes per row); empty for dense weights.
+    /// Not applied to expert projections.
+    pub in_proj_mask: &'a [u8],
+    pub out_proj_mask: &'a [u8],
+    /// RMSNorm weight: (d_model,)
+    pub norm: &'a [u8],
+    /// A_log diagonal: (d_inner,) — log of SSM decay matrix (SSM layers)
+    pub a_log: &'a [u8],
+    /// dt bias: (d_inner,) — timestep bias (SSM layers)
+    pub dt_bias: &'a [u8],
+    /// dt projection: (dt_heads, d_model) — maps normed input to one dt
+    /// per head (SSM layers). Empty for models exported without one, whose
+    /// dt is derived from x_ssm instead.
+    pub dt_proj: &'a [u8],
+    /// Per-head requantization scales for dt_proj output
+    pub dt_proj_scales: &'a [u16],
+    /// Per-channel requantization scales for in_proj output (one per
+    /// in_proj row)
+    pub in_proj_scales: &'a [u16],
+    /// Per-channel requantization scales for out_proj output
+    pub out_proj_scales: &'a [u16],
+    /// Expert routing; None for a dense layer. When set, the selected
+    /// expert's projections are used instead of in_proj/out_proj.
+    /// Attention layers ignore it.
+    pub moe: Option<MoeWeights<'a>>,
+    pub kind: LayerKind,
+}
+
+/// Expert count limit for mixture-of-experts layers.
+pub const MAX_EXPERTS: usize = 8;
+
+/// One expert's projections in a mixture-of-experts layer, shaped like the
+/// dense LayerWeights ones.
+#[derive(Clone, Copy, Default)]
+pub struct ExpertWeights<'a> {
+    /// in_proj weight: (in_proj_rows, d_model)
+    pub in_proj: &'a [u8],
+    /// out_proj weight: (d_model, d_inner)
+    pub out_proj: &'a [u8],
+}
+
+/// Mixture-of-experts routing for one layer. Experts may come from any
+/// shard; only the selected one is read.
+pub struct MoeWeights<'a> {
+    /// Router weight: (num_experts, d_model) — scores the normed input
+    pub router: &'a [u8],
+    /// experts[..num_experts]
+    pub experts: [ExpertWeights<'a>; MAX_EXPERTS],
+    pub num_experts: usize,
+}
+
+/// Top-1 routing: the expert whose router row scores highest against the
+/// normed input, first on ties.
+pub fn route_top1(router: &[u8], x_norm: &[i8], num_experts: usize, d_model: usize) -> usize {
+    let n = num_experts.min(MAX_EXPERTS);
+    let mut scores = [0i32; MAX_EXPERTS];
+    matmul::matmul_i8(router, x_norm, &mut scores[..n], n, d_model);
+    lut::argmax_i32(&scores[..n])
+}
+
+/// Extremes of one layer's INT32 accumulators before requantization: its
+/// in_proj output (in_proj_rows, or 3*d_inner for attention; not dt_proj)
+/// and its out_proj output. Gathered over live frames, they are what a
+/// layer's quantization scales have to map into i8.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ActivationRange {
+    pub in_min: i32,
+    pub in_max: i32,
+    pub out_min: i32,
+    pub out_max: i32,
+}
+
+impl Default for ActivationRange {
+    /// Empty: min above max, so the first observation sets both.
+    fn default() -> Self {
+        ActivationRange {
+            in_min: i32::MAX,
+            in_max: i32::MIN,
+            out_min: i32::MAX,
+            out_max: i32::MIN,
+        }
+    }
+}
+
+impl ActivationRange {
+    /// Widen the range to cover one frame's accumulators.
+    pub fn observe(&mut self, in_acc: &[i32], out_acc: &[i32]) {
+        for &v in in_acc {
+            self.in_min = self.in_min.min(v);
+            self.in_max = self.in_max.max(v);
+        }
+        for &v in out_acc {
+            self.out_min = self.out_min.min(v);
+            self.out_max = self.out_max.max(v);
+        }
+    }
+
+    /// Widen the range to cover another one.
+    pub fn merge(&mut self, other: &ActivationRange) {
+        self.in_min = self.in_min.min(other.in_min);
+        self.in_max = self.in_max.max(other.in_max);
+        self.out_min = self.out_min.min(other.out_min);
+        self.out_max = self.out_max.max(other.out_max);
+    }
+
+    /// in_proj requantization scale that maps the widest observed value to
+    /// ±127, or None before anything was observed.
+    pub fn in_scale(&self) -> Option<u16> {
+        fit_scale(self.in_min, self.in_max)
+    }
+
+    /// out_proj requantization scale, as `in_scale`.
+    pub fn out_scale(&self) -> Option<u16> {
+        fit_scale(self.out_min, self.out_max)
+    }
+}
+
+/// Largest scale (`requantize_per_channel`'s `(acc * scale) >> 16`) that
+/// keeps every value in min..=max within i8. An all-zero range gets the
+/// largest scale there is.
+fn fit_scale(min: i32, max: i32) -> Option<u16> {
+    if min > max {
+        return None;
+    }
+    let peak = (min as i64).abs().max((max as i64).abs()).max(1);
+    Some(((127i64 << 16) / peak).min(u16::MAX as i64) as u16)
+}
+
+/// Mutable view of hidden state in its stored element type (INT8 or INT16).
+pub enum HiddenStateMut<'a> {
+    I8(&'a mut [i8]),
+    I16(&'a mut [i16]),
+}
+
+impl<'a> HiddenStateMut<'a> {
+    /// Borrow the `len` elements starting at `offset` (one layer's h matrix).
+    pub fn slice(&mut self, offset: usize, len: usize) -> HiddenStateMut<'_> {
+        match self {
+            HiddenStateMut::I8(h) => HiddenStateMut::I8(&mut h[offset..offset + len]),
+            HiddenStateMut::I16(h) => HiddenStateMut::I16(&mut h[offset..offset + len]),
+        }
+    }
+}
+
+/// Scratch buffers for intermediate computations within a layer.
+/// Borrowed from caller-provided storage (see `from_slices`, or
+/// `ScratchArena` with the `alloc` feature) and reused across layers, so
+/// the layer step itself never allocates.
+pub struct ScratchBuffers<'a> {
+    /// Normalized input, then the residual sum: (d_model,)
+    pub x_norm: &'a mut [i8],
+    /// in_proj output before split: (3*d_inner,) as INT32 — [z, x_ssm, B, C]
+    /// uses the first in_proj_rows (and dt_proj's per-head output follows),
+    /// attention's [q, k, v] all of it
+    pub proj_i32: &'a mut [i32],
+    /// in_proj output requantized: (3*d_inner,)
+    pub proj_i8: &'a mut [i8],
+    /// z (gate input): (d_inner,)
+    pub z: &'a mut [i8],
+    /// x_ssm (SSM input): (d_inner,)
+    pub x_ssm: &'a mut [i8],
+    /// dt after softplus: (d_inner,)
+    pub dt: &'a mut [i8],
+    /// SSM output: (d_inner,)
+    pub y_ssm: &'a mut [i8],
+    /// Gate output (SiLU(z)): (d_inner,)
+    pub gate: &'a mut [i8],
+    /// Gated output (attention output in attention layers): (d_inner,)
+    pub y_gated: &'a mut [i8],
+    /// out_proj output as INT32: (d_model,)
+    pub out_i32: &'a mut [i32],
+    /// Layer output: (d_model,)
+    pub y_out: &'a mut [i8],
+}
+
+impl<'a> ScratchBuffers<'a> {
+    /// i8 elements `from_slices` needs for a config.
+    pub const fn i8_len(d_model: usize, d_inner: usize) -> usize {
+        2 * d_model + 9 * d_inner
+    }
+
+    /// i32 elements `from_slices` needs for a config.
+    pub const fn i32_len(d_model: usize, d_inner: usize) -> usize {
+        d_model + 3 * d_inner
+    }
+
+    /// Carve the buffers out of caller storage (a stack array, a static, an
+    /// account region). None if either slice is shorter than `i8_len` /
+    /// `i32_len`; extra elements are ignored.
+    pub fn from_slices(
+        i8s: &'a mut [i8],
+        i32s: &'a mut [i32],
+        d_model: usize,
+        d_inner: usize,
+    ) -> Option<Self> {
+        if i8s.len() < Self::i8_len(d_model, d_inner)
+            || i32s.len() < Self::i32_len(d_model, d_inner)
+        {
+            return None;
+        }
+        let (x_norm, rest) = i8s.split_at_mut(d_model);
+        let (proj_i8, rest) = rest.split_at_mut(3 * d_inner);
+        let (z, rest) = rest.split_at_mut(d_inner);
+        let (x_ssm, rest) = rest.split_at_mut(d_inner);
+        let (dt, rest) = rest.split_at_mut(d_inner);
+        let (y_ssm, rest) = rest.split_at_mut(d_inner);
+        let (gate, rest) = rest.split_at_mut(d_inner);
+        let (y_gated, rest) = rest.split_at_mut(d_inner);
+        let (y_out, _) = rest.split_at_mut(d_model);
+        let (proj_i32, rest) = i32s.split_at_mut(3 * d_inner);
+        let (out_i32, _) = rest.split_at_mut(d_model);
+        Some(Self {
+            x_norm,
+            proj_i32,
+            proj_i8,
+            z,
+            x_ssm,
+            dt,
+            y_ssm,
+            gate,
+            y_gated,
+            out_i32,
+            y_out,
+        })
+    }
+}
+
+/// Heap storage for `ScratchBuffers`, sized for one config.
+#[cfg(feature = "alloc")]
+pub struct ScratchArena {
+    d_model: usize,
+    d_inner: usize,
+    i8s: Vec<i8>,
+    i32s: Vec<i32>,
+}
+
+#[cfg(feature = "alloc")]
+impl ScratchArena {
+    pub fn new(d_model: usize, d_inner: usize) -> Self {
+        Self {
+            d_model,
+            d_inner,
+            i8s: vec![0i8; ScratchBuffers::i8_len(d_model, d_inner)],
+            i32s: vec![0i32; ScratchBuffers::i32_len(d_model, d_inner)],
+        }
+    }
+
+    pub fn buffers(&mut self) -> ScratchBuffers<'_> {
+        ScratchBuffers::from_slices(&mut self.i8s, &mut self.i32s, self.d_model, self.d_inner)
+            .expect("arena is sized for its config")
+    }
+}
+
+/// Execute one Mamba2 layer (single timestep, single layer).
+///
+/// This is the core inner loop called num_layers times per frame. Returns
+/// the expert a mixture-of-experts layer routed to (0 for a dense or
+/// attention layer).
+pub fn mamba2_layer_step(
+    x: &mut [i8],
+    h: HiddenStateMut,
+    weights: &LayerWeights,
+    lut_data: &[u8],
+    config: &Mamba2Config,
+    scratch: &mut ScratchBuffers,
+) -> u8 {
+    let d_model = config.d_model;
+    let d_inner = config.d_inner;
+
+    // ── Step 1: RMSNorm ─────────────────────────────────────────────────
+    lut::rmsnorm_int8(
+        lut_data,
+        x,
+        // Reinterpret norm weights as i8
+        unsafe { core::slice::from_raw_parts(weights.norm.as_ptr() as *const i8, d_model) },
+        scratch.x_norm,
+        256, // weight_scale
+    );
+
+    // ── Steps 1b-4: mix (SSM or attention) into y_gated ─────────────────
+    let (expert, out_proj, out_mask) = match weights.kind {
+        LayerKind::Ssm => ssm_mix(h, weights, lut_data, config, scratch),
+        LayerKind::Attention { window } => {
+            attention_mix(h, weights, window, lut_data, config, scratch);
+            (0, weights.out_proj, weights.out_proj_mask)
+        }
+    };
+
+    // ── Step 5: out_proj matmul ─────────────────────────────────────────
+    matmul::matmul_i8_sparse(
+        out_proj,
+        out_mask,
+        scratch.y_gated,
+        scratch.out_i32,
+        d_model,
+        d_inner,
+    );
+
+    matmul::requantize_per_channel_rounded(
+        scratch.out_i32,
+        weights.out_proj_scales,
+        scratch.y_out,
+        d_model,
+        config.rounding.substream(1),
+    );
+
+    // ── Step 6: Residual add ────────────────────────────────────────────
+    // add_i8 can't alias its input and output; x_norm is free again by now,
+    // so stage the sum there
+    matmul::add_i8(x, scratch.y_out, scratch.x_norm, d_model);
+    x.copy_from_slice(scratch.x_norm);
+
+    expert as u8
+}
+
+/// Steps 1b-4 of an SSM layer: route, in_proj, selective scan and gate.
+/// Returns the selected expert and the out_proj (and its mask) to finish
+/// with.
+fn ssm_mix<'w>(
+    h: HiddenStateMut,
+    weights: &LayerWeights<'w>,
+    lut_data: &[u8],
+    config: &Mamba2Config,
+    scratch: &mut ScratchBuffers,
+) -> (usize, &'w [u8], &'w [u8]) {
+    let d_model = config.d_model;
+    let d_inner = config.d_inner;
+
+    // ── Step 1b: MoE routing ────────────────────────────────────────────
+    let (expert, in_proj, in_mask, out_proj, out_mask) = match &weights.moe {
+        Some(moe) => {
+            let e = route_top1(moe.router, scratch.x_norm, moe.num_experts, d_model);
+            (e, moe.experts[e].in_proj, &[][..], moe.experts[e].out_proj, &[][..])
+        }
+        None => (
+            0,
+            weights.in_proj,
+            weights.in_proj_mask,
+            weights.out_proj,
+            weights.out_proj_mask,
+        ),
+    };
+
+    // ── Step 2: in_proj matmul ──────────────────────────────────────────
+    let in_rows = config.in_proj_rows();
+    matmul::matmul_i8_sparse(
+        in_proj,
+        in_mask,
+        scratch.x_norm,
+        scratch.proj_i32,
+        in_rows,
+        d_model,
+    );
+
+    // Requantize and split into z, x_ssm and (read in place) B and C
+    matmul::requantize_per_channel_rounded(
+        scratch.proj_i32,
+        weights.in_proj_scales,
+        scratch.proj_i8,
+        in_rows,
+        config.rounding.substream(0),
+    );
+
+    scratch.z.copy_from_slice(&scratch.proj_i8[..d_inner]);
+    scratch
+        .x_ssm
+        .copy_from_slice(&scratch.proj_i8[d_inner..2 * d_inner]);
+
+    // ── Step 2b: dt_proj matmul ─────────────────────────────────────────
+    // One dt per head, staged in the projection buffers past in_proj's rows
+    let heads = config.dt_heads();
+    let has_dt_proj = !weights.dt_proj.is_empty();
+    if has_dt_proj {
+        let dt_head = in_rows..in_rows + heads;
+        matmul::matmul_i8(
+            weights.dt_proj,
+            scratch.x_norm,
+            &mut scratch.proj_i32[dt_head.clone()],
+            heads,
+            d_model,
+        );
+        matmul::requantize_per_channel_rounded(
+            &scratch.proj_i32[dt_head.clone()],
+            weights.dt_proj_scales,
+            &mut scratch.proj_i8[dt_head],
+            heads,
+            config.rounding.substream(2),
+        );
+    }
+
+    // ── Step 3: Selective scan step ─────────────────────────────────────
+    // dt = softplus(dt_proj(x) + dt_bias), each channel taking its head's
+    // dt — or softplus(x_ssm + dt_bias) without a dt_proj — stretched to
+    // the session's tick
+    let head_dim = (d_inner / heads).max(1);
+    for i in 0..d_inner {
+        let dt_in = if has_dt_proj {
+            scratch.proj_i8[in_rows + (i / head_dim).min(heads - 1)]
+        } else {
+            scratch.x_ssm[i]
+        };
+        let dt_raw = (dt_in as i16 + weights.dt_bias[i] as i8 as i16).clamp(-128, 127) as i8;
+        scratch.dt[i] = scale_dt(lut::softplus_lut(lut_data, dt_raw), config.dt_shift);
+    }
+
+    let group_len = config.ngroups * config.d_state;
+    let bc = (config.ngroups > 0).then(|| ssm::GroupedBc {
+        b: &scratch.proj_i8[2 * d_inner..2 * d_inner + group_len],
+        c: &scratch.proj_i8[2 * d_inner + group_len..in_rows],
+        ngroups: config.ngroups,
+    });
+    match h {
+        HiddenStateMut::I8(h) => ssm::selective_scan_step(
+            scratch.x_ssm,
+            scratch.dt,
+            h,
+            weights.a_log,
+            bc.as_ref(),
+            lut_data,
+            scratch.y_ssm,
+            config.d_inner,
+            config.d_state,
+        ),
+        HiddenStateMut::I16(h) => ssm::selective_scan_step_i16(
+            scratch.x_ssm,
+            scratch.dt,
+            h,
+            weights.a_log,
+            bc.as_ref(),
+            lut_data,
+            scratch.y_ssm,
+            config.d_inner,
+            config.d_state,
+        ),
+    }
+
+    // ── Step 4: Gate ────────────────────────────────────────────────────
+    scratch.gate.copy_from_slice(scratch.z);
+    lut::silu_slice(lut_data, scratch.gate);
+
+    matmul::elementwise_mul_i8(
+        scratch.y_ssm,
+        scratch.gate,
+        scratch.y_gated,
+        d_inner,
+        7, // shift: INT8 * INT8 has ~14 bits, shift 7 to center
+    );
+
+    (expert, out_proj, out_mask)
+}
+
+/// Steps 2-4 of an attention layer: in_proj to [q, k, v], then attention
+/// over the layer's KV buffer into y_gated.
+fn attention_mix(
+    h: HiddenStateMut,
+    weights: &LayerWeights,
+    window: usize,
+    lut_data: &[u8],
+    config: &Mamba2Config,
+    scratch: &mut ScratchBuffers,
+) {
+    let d_model = config.d_model;
+    let d_inner = config.d_inner;
+
+    matmul::matmul_i8_sparse(
+        weights.in_proj,
+        weights.in_proj_mask,
+        scratch.x_norm,
+        scratch.proj_i32,
+        3 * d_inner,
+        d_model,
+    );
+    matmul::requantize_per_channel_rounded(
+        scratch.proj_i32,
+        weights.in_proj_scales,
+        scratch.proj_i8,
+        3 * d_inner,
+        config.rounding.substream(0),
+    );
+
+    let (q, kv_new) = scratch.proj_i8.split_at(d_inner);
+    let (k, v) = kv_new.split_at(d_inner);
+    match h {
+        HiddenStateMut::I8(kv) => attention::attention_step(
+            lut_data,
+            q,
+            k,
+            v,
+            kv,
+            window,
+            config.num_heads,
+            scratch.y_gated,
+        ),
+        HiddenStateMut::I16(kv) => attention::attention_step(
+            lut_data,
+            q,
+            k,
+            v,
+            kv,
+            window,
+            config.num_heads,
+            scratch.y_gated,
+        ),
+    }
+}
+
+/// Run all layers over `x` in place, pulling each layer's weights from
+/// `layer_weights(layer_idx)`. Returns None if any layer's weights are
+/// unavailable; layers before it have already run. Each layer's selected
+/// expert is written to `routes[layer_idx]` while it fits (pass `&mut []`
+/// to ignore them), and `ranges[layer_idx]` likewise widens to the layer's
+/// accumulators for calibration. Encoding the input and decoding the output
+/// are left to the calling program, which owns the game-state types.
+#[allow(clippy::too_many_arguments)]
+pub fn forward_layers_in_place<'a>(
+    x: &mut [i8],
+    mut hidden_state: HiddenStateMut,
+    lut_data: &[u8],
+    config: &Mamba2Config,
+    scratch: &mut ScratchBuffers,
+    routes: &mut [u8],
+    ranges: &mut [ActivationRange],
+    mut layer_weights: impl FnMut(usize) -> Option<LayerWeights<'a>>,
+) -> Option<()> {
+    let h_per_layer = config.d_inner * config.d_state;
+
+    for layer_idx in 0..config.num_layers {
+        let h_slice = hidden_state.slice(layer_idx * h_per_layer, h_per_layer);
+        let weights = layer_weights(layer_idx)?;
+        let layer_config = Mamba2Config {
+            rounding: config.rounding.substream(layer_idx as u64),
+            ..*config
+        };
+
+        let expert = mamba2_layer_step(x, h_slice, &weights, lut_data, &layer_config, scratch);
+        if let Some(route) = routes.get_mut(layer_idx) {
+            *route = expert;
+        }
+        if let Some(range) = ranges.get_mut(layer_idx) {
+            let in_rows = match weights.kind {
+                LayerKind::Ssm => config.in_proj_rows(),
+                LayerKind::Attention { .. } => 3 * config.d_inner,
+            };
+            range.observe(&scratch.proj_i32[..in_rows], scratch.out_i32);
+        }
+    }
+
+    Some(())
+}
+
+/// `forward_layers_in_place` on a copy of `input`, with heap scratch.
+#[cfg(feature = "alloc")]
+pub fn forward_layers<'a>(
+    input: &[i8],
+    hidden_state: HiddenStateMut,
+    lut_data: &[u8],
+    config: &Mamba2Config,
+    routes: &mut [u8],
+    ranges: &mut [ActivationRange],
+    layer_weights: impl FnMut(usize) -> Option<LayerWeights<'a>>,
+) -> Option<Vec<i8>> {
+    let mut x = input.to_vec();
+    let mut arena = ScratchArena::new(config.d_model, config.d_inner);
+    forward_layers_in_place(
+        &mut x,
+        hidden_state,
+        lut_data,
+        config,
+        &mut arena.buffers(),
+        routes,
+        ranges,
+        layer_weights,
+    )?;
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const D_MODEL: usize = 4;
+    const D_INNER: usize = 8;
+    const D_STATE: usize = 2;
+
+    fn config() -> Mamba2Config {
+        Mamba2Config {
+            d_model: D_MODEL,
+            d_inner: D_INNER,
+            d_state: D_STATE,
+            num_layers: 2,
+            num_heads: 2,
+            ngroups: 0,
+            dt_shift: 0,
+            rounding: Rounding::Truncate,
+        }
+    }
+
+    #[test]
+    fn test_dt_shift_for_tick_rates() {
+        assert_eq!(dt_shift_for(30), 1);
+        assert_eq!(dt_shift_for(60), 0);
+        assert_eq!(dt_shift_for(120), -1);
+        assert_eq!(dt_shift_for(0), 0);
+        assert_eq!(scale_dt(40, 1), 80);
+        assert_eq!(scale_dt(100, 1), 127);
+        assert_eq!(scale_dt(41, -1), 20);
+        assert_eq!(scale_dt(41, 0), 41);
+    }
+
+    #[test]
+    fn test_scratch_from_slices_checks_length() {
+        let mut i8s = [0i8; ScratchBuffers::i8_len(D_MODEL, D_INNER)];
+        let mut i32s = [0i32; ScratchBuffers::i32_len(D_MODEL, D_INNER)];
+        assert!(ScratchBuffers::from_slices(&mut i8s, &mut i32s, D_MODEL, D_INNER).is_some());
+        assert!(ScratchBuffers::from_slices(&mut i8s[1..], &mut i32s, D_MODEL, D_INNER).is_none());
+        assert!(ScratchBuffers::from_slices(&mut i8s, &mut i32s[1..], D_MODEL, D_INNER).is_none());
+    }
+
+    /// Caller-provided stack storage gives the same result as the heap path.
+    #[test]
+    fn test_in_place_matches_alloc() {
+        let in_proj: Vec<u8> = (0..2 * D_INNER * D_MODEL).map(|i| (i % 7) as u8).collect();
+        let out_proj: Vec<u8> = (0..D_MODEL * D_INNER).map(|i| (i % 5) as u8).collect();
+        let norm = [64u8; D_MODEL];
+        let a_log = [16u8; D_INNER];
+        let dt_bias = [2u8; D_INNER];
+        let in_scales = [16384u16; 2 * D_INNER];
+        let out_scales = [16384u16; D_MODEL];
+        let luts: Vec<u8> = (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect();
+        let weights = |_| {
+            Some(LayerWeights {
+                in_proj: &in_proj,
+                out_proj: &out_proj,
+                in_proj_mask: &[],
+                out_proj_mask: &[],
+                norm: &norm,
+                a_log: &a_log,
+                dt_bias: &dt_bias,
+                dt_proj: &[],
+                dt_proj_scales: &[],
+                in_proj_scales: &in_scales,
+                out_proj_scales: &out_scales,
+                moe: None,
+                kind: LayerKind::Ssm,
+            })
+        };
+        let input = [20i8, -10, 5, 40];
+        let config = config();
+
+        let mut h_heap = [3i8; 2 * D_INNER * D_STATE];
+        let heap = forward_layers(
+            &input,
+            HiddenStateMut::I8(&mut h_heap),
+            &luts,
+            &config,
+            &mut [],
+            &mut [],
+            weights,
+        )
+        .unwrap();
+
+        let mut i8s = [0i8; ScratchBuffers::i8_len(D_MODEL, D_INNER)];
+        let mut i32s = [0i32; ScratchBuffers::i32_len(D_MODEL, D_INNER)];
+        let mut scratch =
+            ScratchBuffers::from_slices(&mut i8s, &mut i32s, D_MODEL, D_INNER).unwrap();
+        let mut x = input;
+        let mut h_stack = [3i8; 2 * D_INNER * D_STATE];
+        forward_layers_in_place(
+            &mut x,
+            HiddenStateMut::I8(&mut h_stack),
+            &luts,
+            &config,
+            &mut scratch,
+            &mut [],
+            &mut [],
+            weights,
+        )
+        .unwrap();
+
+        assert_eq!(&x[..], &heap[..]);
+        assert_eq!(h_stack, h_heap);
+    }
+
+    /// Each layer's range covers exactly the accumulators its step left in
+    /// scratch, and the fitted scale maps the widest of them to ±127.
+    #[test]
+    fn test_activation_ranges_cover_the_accumulators() {
+        let in_proj: Vec<u8> = (0..2 * D_INNER * D_MODEL).map(|i| (i % 7) as u8).collect();
+        let out_proj: Vec<u8> = (0..D_MODEL * D_INNER).map(|i| (i % 5) as u8).collect();
+        let norm = [64u8; D_MODEL];
+        let a_log = [16u8; D_INNER];
+        let dt_bias = [2u8; D_INNER];
+        let in_scales = [16384u16; 2 * D_INNER];
+        let out_scales = [16384u16; D_MODEL];
+        let luts: Vec<u8> = (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect();
+        let weights = |_| {
+            Some(LayerWeights {
+                in_proj: &in_proj,
+                out_proj: &out_proj,
+                in_proj_mask: &[],
+                out_proj_mask: &[],
+                norm: &norm,
+                a_log: &a_log,
+                dt_bias: &dt_bias,
+                dt_proj: &[],
+                dt_proj_scales: &[],
+                in_proj_scales: &in_scales,
+                out_proj_scales: &out_scales,
+                moe: None,
+                kind: LayerKind::Ssm,
+            })
+        };
+        let config = Mamba2Config {
+            num_layers: 1,
+            ..config()
+        };
+
+        let mut i8s = [0i8; ScratchBuffers::i8_len(D_MODEL, D_INNER)];
+        let mut i32s = [0i32; ScratchBuffers::i32_len(D_MODEL, D_INNER)];
+        let mut scratch =
+            ScratchBuffers::from_slices(&mut i8s, &mut i32s, D_MODEL, D_INNER).unwrap();
+        let mut x = [20i8, -10, 5, 40];
+        let mut h = [3i8; D_INNER * D_STATE];
+        let mut ranges = [ActivationRange::default(); 1];
+        forward_layers_in_place(
+            &mut x,
+            HiddenStateMut::I8(&mut h),
+            &luts,
+            &config,
+            &mut scratch,
+            &mut [],
+            &mut ranges,
+            weights,
+        )
+        .unwrap();
+
+        let mut expected = ActivationRange::default();
+        expected.observe(&scratch.proj_i32[..config.in_proj_rows()], scratch.out_i32);
+        assert_eq!(ranges[0], expected);
+        assert!(ranges[0].in_min <= ranges[0].in_max);
+
+        let peak = ranges[0].in_min.abs().max(ranges[0].in_max.abs());
+        let scale = ranges[0].in_scale().unwrap();
+        let mut out = [0i8; 1];
+        matmul::requantize_per_channel(&[peak], &[scale], &mut out, 1);
+        assert!(out[0] >= 126, "peak {} requantized to {}", peak, out[0]);
+    }
+
+    #[test]
+    fn test_activation_range_scales() {
+        let mut range = ActivationRange::default();
+        assert_eq!(range.in_scale(), None);
+        range.observe(&[-254, 100], &[0]);
+        assert_eq!(range.in_scale(), Some(32768));
+        assert_eq!(range.out_scale(), Some(u16::MAX));
+
+        let mut wider = ActivationRange::default();
+        wider.observe(&[300], &[-5]);
+        range.merge(&wider);
+        assert_eq!((range.in_min, range.in_max), (-254, 300));
+        assert_eq!((range.out_min, range.out_max), (-5, 0));
+    }
+
+    /// With a dt_proj, every channel of a head shares that head's projected
+    /// dt, whatever its x_ssm.
+    #[test]
+    fn test_dt_proj_sets_one_dt_per_head() {
+        let in_proj: Vec<u8> = (0..2 * D_INNER * D_MODEL).map(|i| (i % 7) as u8).collect();
+        let out_proj = [1u8; D_MODEL * D_INNER];
+        let dt_proj: Vec<u8> = [[1i8; D_MODEL], [-1; D_MODEL]]
+            .iter()
+            .flatten()
+            .map(|&w| w as u8)
+            .collect();
+        let norm = [64u8; D_MODEL];
+        let a_log = [16u8; D_INNER];
+        let dt_bias = [2u8; D_INNER];
+        let in_scales = [1024u16; 2 * D_INNER];
+        let dt_scales = [16384u16; 2];
+        let out_scales = [16384u16; D_MODEL];
+        let luts: Vec<u8> = (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect();
+        let config = config();
+        let weights = LayerWeights {
+            in_proj: &in_proj,
+            out_proj: &out_proj,
+            in_proj_mask: &[],
+            out_proj_mask: &[],
+            norm: &norm,
+            a_log: &a_log,
+            dt_bias: &dt_bias,
+            dt_proj: &dt_proj,
+            dt_proj_scales: &dt_scales,
+            in_proj_scales: &in_scales,
+            out_proj_scales: &out_scales,
+            moe: None,
+            kind: LayerKind::Ssm,
+        };
+
+        let mut arena = ScratchArena::new(D_MODEL, D_INNER);
+        let mut scratch = arena.buffers();
+        let mut x = [20i8, 30, 5, 40];
+        let mut h = [0i8; D_INNER * D_STATE];
+        mamba2_layer_step(
+            &mut x,
+            HiddenStateMut::I8(&mut h),
+            &weights,
+            &luts,
+            &config,
+            &mut scratch,
+        );
+
+        let (head0, head1) = scratch.dt.split_at(D_INNER / 2);
+        assert!(head0.iter().all(|&d| d == head0[0]));
+        assert!(head1.iter().all(|&d| d == head1[0]));
+        assert_ne!(head0[0], head1[0]);
+        assert_ne!(scratch.x_ssm[0], scratch.x_ssm[1]);
+    }
+
+    /// Row sparsity masks built from pruned weights change nothing but the
+    /// groups visited.
+    #[test]
+    fn test_masked_layer_matches_dense() {
+        // Structured pruning: whole 4-byte groups of some rows are zero
+        let prune = |rows: usize, cols: usize, seed: usize| -> Vec<u8> {
+            (0..rows * cols)
+                .map(|i| {
+                    let (row, group) = (i / cols, i % cols / 4);
+                    if (row + group) % 3 == 0 {
+                        0
+                    } else {
+                        ((i * seed) % 11) as u8
+                    }
+                })
+                .collect()
+        };
+        let in_proj = prune(2 * D_INNER, D_MODEL, 5);
+        let out_proj = prune(D_MODEL, D_INNER, 3);
+        let mut in_mask = vec![0u8; 2 * D_INNER * matmul::mask_stride(D_MODEL)];
+        let mut out_mask = vec![0u8; D_MODEL * matmul::mask_stride(D_INNER)];
+        matmul::build_row_masks(&in_proj, &mut in_mask, 2 * D_INNER, D_MODEL);
+        matmul::build_row_masks(&out_proj, &mut out_mask, D_MODEL, D_INNER);
+        assert!(in_mask.contains(&0), "some in_proj rows are fully pruned");
+
+        let norm = [64u8; D_MODEL];
+        let a_log = [16u8; D_INNER];
+        let dt_bias = [2u8; D_INNER];
+        let in_scales = [4096u16; 2 * D_INNER];
+        let out_scales = [8192u16; D_MODEL];
+        let luts: Vec<u8> = (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect();
+        let config = config();
+        let run = |in_proj_mask, out_proj_mask| {
+            let weights = LayerWeights {
+                in_proj: &in_proj,
+                out_proj: &out_proj,
+                in_proj_mask,
+                out_proj_mask,
+                norm: &norm,
+                a_log: &a_log,
+                dt_bias: &dt_bias,
+                dt_proj: &[],
+                dt_proj_scales: &[],
+                in_proj_scales: &in_scales,
+                out_proj_scales: &out_scales,
+                moe: None,
+                kind: LayerKind::Ssm,
+            };
+            let mut arena = ScratchArena::new(D_MODEL, D_INNER);
+            let mut x = [20i8, -30, 5, 40];
+            let mut h = [0i8; D_INNER * D_STATE];
+            mamba2_layer_step(
+                &mut x,
+                HiddenStateMut::I8(&mut h),
+                &weights,
+                &luts,
+                &config,
+                &mut arena.buffers(),
+            );
+            (x, h)
+        };
+
+        assert_eq!(run(&in_mask, &out_mask), run(&[], &[]));
+    }
+
+    #[test]
+    fn test_route_top1() {
+        // Row 2 lines up with the input, row 1 opposes it
+        let router: Vec<u8> = [[0i8, 0, 0, 0], [-1, -1, -1, -1], [1, 2, 3, 4]]
+            .iter()
+            .flatten()
+            .map(|&w| w as u8)
+            .collect();
+        assert_eq!(route_top1(&router, &[10, 10, 10, 10], 3, D_MODEL), 2);
+        assert_eq!(route_top1(&router, &[-10, -10, -10, -10], 3, D_MODEL), 1);
+        // Ties go to the first expert
+        assert_eq!(route_top1(&router, &[0; D_MODEL], 3, D_MODEL), 0);
+    }
+
+    /// A MoE layer computes exactly the dense layer built from the expert
+    /// it reports.
+    #[test]
+    fn test_moe_matches_selected_dense_expert() {
+        let expert_in: Vec<Vec<u8>> = (0..2)
+            .map(|e| (0..2 * D_INNER * D_MODEL).map(|i| ((i + e) % (7 + 4 * e)) as u8).collect())
+            .collect();
+        let expert_out: Vec<Vec<u8>> = (0..2)
+            .map(|e| (0..D_MODEL * D_INNER).map(|i| ((i * (e + 1)) % 5) as u8).collect())
+            .collect();
+        // Expert 1 scores the sum of the normed input, expert 0 zero
+        let router: Vec<u8> = [[0u8; D_MODEL], [1u8; D_MODEL]].concat();
+        let norm = [64u8; D_MODEL];
+        let a_log = [16u8; D_INNER];
+        let dt_bias = [2u8; D_INNER];
+        let in_scales = [16384u16; 2 * D_INNER];
+        let out_scales = [16384u16; D_MODEL];
+        let luts: Vec<u8> = (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect();
+        let config = config();
+        let mut seen = [false; 2];
+
+        for input in [[20i8, -10, 5, 40], [-30, -5, -60, 2]] {
+            let mut experts = [ExpertWeights::default(); MAX_EXPERTS];
+            for (e, slot) in experts.iter_mut().take(2).enumerate() {
+                *slot = ExpertWeights { in_proj: &expert_in[e], out_proj: &expert_out[e] };
+            }
+            let mut routes = [0xffu8; 2];
+            let mut h_moe = [3i8; 2 * D_INNER * D_STATE];
+            let moe = forward_layers(
+                &input,
+                HiddenStateMut::I8(&mut h_moe),
+                &luts,
+                &config,
+                &mut routes,
+                &mut [],
+                |_| {
+                    Some(LayerWeights {
+                        in_proj: &[],
+                        out_proj: &[],
+                        in_proj_mask: &[],
+                        out_proj_mask: &[],
+                        norm: &norm,
+                        a_log: &a_log,
+                        dt_bias: &dt_bias,
+                        dt_proj: &[],
+                        dt_proj_scales: &[],
+                        in_proj_scales: &in_scales,
+                        out_proj_scales: &out_scales,
+                        moe: Some(MoeWeights { router: &router, experts, num_experts: 2 }),
+                        kind: LayerKind::Ssm,
+                    })
+                },
+            )
+            .unwrap();
+            for &r in routes.iter() {
+                seen[r as usize] = true;
+            }
+
+            let mut h_dense = [3i8; 2 * D_INNER * D_STATE];
+            let dense = forward_layers(
+                &input,
+                HiddenStateMut::I8(&mut h_dense),
+                &luts,
+                &config,
+                &mut [],
+                &mut [],
+                |l| {
+                    let e = routes[l] as usize;
+                    Some(LayerWeights {
+                        in_proj: &expert_in[e],
+                        out_proj: &expert_out[e],
+                        in_proj_mask: &[],
+                        out_proj_mask: &[],
+                        norm: &norm,
+                        a_log: &a_log,
+                        dt_bias: &dt_bias,
+                        dt_proj: &[],
+                        dt_proj_scales: &[],
+                        in_proj_scales: &in_scales,
+                        out_proj_scales: &out_scales,
+                        moe: None,
+                        kind: LayerKind::Ssm,
+                    })
+                },
+            )
+            .unwrap();
+
+            assert_eq!(moe, dense);
+            assert_eq!(h_moe, h_dense);
+        }
+        assert_eq!(seen, [true, true], "both experts should be exercised");
+    }
+
+    /// An attention layer keeps its KV buffer in its own hidden slot and
+    /// leaves the SSM layer before it as in a pure Mamba2 model.
+    #[test]
+    fn test_attention_layer_uses_hidden_slot() {
+        const D_STATE_KV: usize = 8;
+        const SLOT: usize = D_INNER * D_STATE_KV;
+        let config = Mamba2Config { d_state: D_STATE_KV, ..config() };
+        let window = 2;
+        assert!(window <= attention::max_window(D_INNER, D_STATE_KV));
+
+        let in_proj: Vec<u8> = (0..3 * D_INNER * D_MODEL).map(|i| (i % 7) as u8).collect();
+        let out_proj: Vec<u8> = (0..D_MODEL * D_INNER).map(|i| (i % 5) as u8).collect();
+        let norm = [64u8; D_MODEL];
+        let a_log = [16u8; D_INNER];
+        let dt_bias = [2u8; D_INNER];
+        let in_scales = [16384u16; 3 * D_INNER];
+        let out_scales = [16384u16; D_MODEL];
+        let luts: Vec<u8> = (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect();
+        let weights = |kind| LayerWeights {
+            in_proj: &in_proj,
+            out_proj: &out_proj,
+            in_proj_mask: &[],
+            out_proj_mask: &[],
+            norm: &norm,
+            a_log: &a_log,
+            dt_bias: &dt_bias,
+            dt_proj: &[],
+            dt_proj_scales: &[],
+            in_proj_scales: &in_scales,
+            out_proj_scales: &out_scales,
+            moe: None,
+            kind,
+        };
+
+        let mut h_hybrid = [0i16; 2 * SLOT];
+        let mut h_pure = [0i16; 2 * SLOT];
+        for frame in 1..=3i16 {
+            let input = [20i8, -10, 5, 40];
+            let hybrid = |l| match l {
+                1 => Some(weights(LayerKind::Attention { window })),
+                _ => Some(weights(LayerKind::Ssm)),
+            };
+            let pure = |_| Some(weights(LayerKind::Ssm));
+            let h = HiddenStateMut::I16(&mut h_hybrid);
+            forward_layers(&input, h, &luts, &config, &mut [], &mut [], hybrid).unwrap();
+            let h = HiddenStateMut::I16(&mut h_pure);
+            forward_layers(&input, h, &luts, &config, &mut [], &mut [], pure).unwrap();
+
+            assert_eq!(h_hybrid[..SLOT], h_pure[..SLOT]);
+            assert_eq!(h_hybrid[SLOT], frame.min(window as i16));
+        }
+        // Nothing past the KV buffer is touched
+        let kv_end = SLOT + attention::kv_len(window, D_INNER);
+        assert!(h_hybrid[kv_end..].iter().all(|&e| e == 0));
+    }
+
+    /// Stochastic rounding replays exactly from its seed, and the seed
+    /// changes the result.
+    #[test]
+    fn test_stochastic_rounding_replays_from_seed() {
+        let in_proj: Vec<u8> = (0..2 * D_INNER * D_MODEL).map(|i| (i % 7) as u8).collect();
+        let out_proj: Vec<u8> = (0..D_MODEL * D_INNER).map(|i| (i % 5) as u8).collect();
+        let norm = [64u8; D_MODEL];
+        let a_log = [16u8; D_INNER];
+        let dt_bias = [2u8; D_INNER];
+        // Small scales leave most of each value in the dropped bits
+        let in_scales = [6000u16; 2 * D_INNER];
+        let out_scales = [6000u16; D_MODEL];
+        let luts: Vec<u8> = (0..lut::LUT_TOTAL_SIZE)
+            .map(|i| (255 - i % 256) as u8)
+            .collect();
+        let run = |rounding| {
+            let config = Mamba2Config { rounding, ..config() };
+            let mut h = [3i8; 2 * D_INNER * D_STATE];
+            let out = forward_layers(
+                &[90, -70, 50, 110],
+                HiddenStateMut::I8(&mut h),
+                &luts,
+                &config,
+                &mut [],
+                &mut [],
+                |_| {
+                    Some(LayerWeights {
+                        in_proj: &in_proj,
+                        out_proj: &out_proj,
+                        in_proj_mask: &[],
+                        out_proj_mask: &[],
+                        norm: &norm,
+                        a_log: &a_log,
+                        dt_bias: &dt_bias,
+                        dt_proj: &[],
+                        dt_proj_scales: &[],
+                        in_proj_scales: &in_scales,
+                        out_proj_scales: &out_scales,
+                        moe: None,
+                        kind: LayerKind::Ssm,
+                    })
+                },
+            )
+            .unwrap();
+            (out, h)
+        };
+
+        let seeded = |frame| Rounding::Stochastic { seed: crate::rng::frame_seed(5, frame) };
+        assert_eq!(run(seeded(1)), run(seeded(1)));
+        let runs: Vec<_> = (1..=4).map(|f| run(seeded(f))).collect();
+        assert!(runs.iter().any(|r| *r != runs[0]), "seeds should change the rounding");
+        assert!(runs.iter().any(|r| *r != run(Rounding::Truncate)));
+    }
+}
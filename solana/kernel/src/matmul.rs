@@ -0,0 +1,773 @@
+//! INT8 matrix-vector multiplication optimized for Solana BPF.
+//!
+//! Core operation for Mamba2 inference:
+//!   y = W * x
+//!   W: (rows, cols) INT8 matrix (weights, zero-copy from account)
+//!   x: (cols,) INT8 vector (activations)
+//!   y: (rows,) INT32 accumulator → requantized to INT8
+//!
+//! Uses packed u32 loads for ~16 CU/MAC (proven in cu-benchmark).
+
+/// Add into an INT32 accumulator.
+///
+/// With the `checked-math` feature, overflow panics instead of silently
+/// wrapping as it would in a BPF release build.
+#[inline(always)]
+pub(crate) fn acc_add(acc: i32, x: i32) -> i32 {
+    #[cfg(feature = "checked-math")]
+    {
+        acc.checked_add(x).expect("INT32 accumulator overflow")
+    }
+    #[cfg(not(feature = "checked-math"))]
+    {
+        acc + x
+    }
+}
+
+/// Narrow a widened intermediate back to INT32. With `checked-math`, a
+/// lossy cast panics instead of truncating.
+#[inline(always)]
+pub(crate) fn narrow_i32(x: i64) -> i32 {
+    #[cfg(feature = "checked-math")]
+    {
+        i32::try_from(x).expect("INT32 narrowing overflow")
+    }
+    #[cfg(not(feature = "checked-math"))]
+    {
+        x as i32
+    }
+}
+
+/// Arithmetic right shift. With `checked-math`, a shift of 32 or more
+/// panics instead of being masked.
+#[inline(always)]
+pub(crate) fn shr_i32(x: i32, shift: u32) -> i32 {
+    #[cfg(feature = "checked-math")]
+    {
+        x.checked_shr(shift).expect("shift exceeds INT32 width")
+    }
+    #[cfg(not(feature = "checked-math"))]
+    {
+        x >> shift
+    }
+}
+
+/// Matrix-vector multiply: y = W * x with INT32 accumulation.
+///
+/// Inner loop uses packed u32 `read_unaligned` to load 4 bytes at once,
+/// reducing memory load count by 4x vs individual byte loads.
+///
+/// Arguments:
+///   weights: Row-major INT8 weight matrix, shape (rows, cols), stored as &[u8]
+///   input:   INT8 input vector, shape (cols,), stored as &[i8]
+///   output:  INT32 output vector, shape (rows,) — caller requantizes
+///   rows:    Number of output elements
+///   cols:    Number of input elements (dot product length)
+pub fn matmul_i8(
+    weights: &[u8],
+    input: &[i8],
+    output: &mut [i32],
+    rows: usize,
+    cols: usize,
+) {
+    assert!(weights.len() >= rows * cols);
+    assert!(input.len() >= cols);
+    assert!(output.len() >= rows);
+
+    let chunks = cols / 4;
+    let remainder = cols % 4;
+
+    // SAFETY: bounds checked above via asserts. Packed loads read 4 bytes
+    // at a time from within the validated slice range.
+    unsafe {
+        let w_ptr = weights.as_ptr();
+        let x_ptr = input.as_ptr() as *const u8;
+
+        for (i, out) in output[..rows].iter_mut().enumerate() {
+            let mut acc: i32 = 0;
+            let row_offset = i * cols;
+
+            // Packed 4-byte loads — the key optimization (~16 CU/MAC)
+            for j in 0..chunks {
+                let w_base = row_offset + j * 4;
+                let x_base = j * 4;
+
+                // Load 4 weight bytes via pointer cast
+                let w4 = (w_ptr.add(w_base) as *const u32).read_unaligned();
+                // Load 4 input bytes
+                let x4 = (x_ptr.add(x_base) as *const u32).read_unaligned();
+
+                // Extract individual bytes as signed i8 -> i32
+                let w0 = (w4 as u8) as i8 as i32;
+                let w1 = ((w4 >> 8) as u8) as i8 as i32;
+                let w2 = ((w4 >> 16) as u8) as i8 as i32;
+                let w3 = ((w4 >> 24) as u8) as i8 as i32;
+
+                let x0 = (x4 as u8) as i8 as i32;
+                let x1 = ((x4 >> 8) as u8) as i8 as i32;
+                let x2 = ((x4 >> 16) as u8) as i8 as i32;
+                let x3 = ((x4 >> 24) as u8) as i8 as i32;
+
+                acc = acc_add(acc, w0 * x0 + w1 * x1 + w2 * x2 + w3 * x3);
+            }
+
+            // Handle remainder (cols not divisible by 4)
+            for j in 0..remainder {
+                let idx = chunks * 4 + j;
+                let w = *weights.get_unchecked(row_offset + idx) as i8 as i32;
+                let x = *input.get_unchecked(idx) as i32;
+                acc = acc_add(acc, w * x);
+            }
+
+            *out = acc;
+        }
+    }
+}
+
+/// Bytes of a row sparsity mask: one bit per 4-byte column group.
+pub const fn mask_stride(cols: usize) -> usize {
+    cols.div_ceil(4).div_ceil(8)
+}
+
+/// Build the row sparsity masks of `weights`: bit g of row i (byte
+/// `i * mask_stride(cols) + g / 8`, LSB first) is set when any weight in
+/// columns 4g..4g+4 of the row is non-zero. A trailing partial group
+/// counts as a group.
+pub fn build_row_masks(weights: &[u8], mask: &mut [u8], rows: usize, cols: usize) {
+    let stride = mask_stride(cols);
+    assert!(weights.len() >= rows * cols);
+    assert!(mask.len() >= rows * stride);
+
+    mask[..rows * stride].fill(0);
+    for i in 0..rows {
+        let row = &weights[i * cols..(i + 1) * cols];
+        for (g, group) in row.chunks(4).enumerate() {
+            if group.iter().any(|&w| w != 0) {
+                mask[i * stride + g / 8] |= 1 << (g % 8);
+            }
+        }
+    }
+}
+
+/// `matmul_i8` that only visits the 4-byte column groups each row's mask
+/// marks non-zero (see `build_row_masks`). Same result as the dense kernel
+/// whenever the mask covers every non-zero weight; a structured-pruned
+/// layer pays for its surviving groups only.
+pub fn matmul_i8_masked(
+    weights: &[u8],
+    mask: &[u8],
+    input: &[i8],
+    output: &mut [i32],
+    rows: usize,
+    cols: usize,
+) {
+    let stride = mask_stride(cols);
+    assert!(weights.len() >= rows * cols);
+    assert!(mask.len() >= rows * stride);
+    assert!(input.len() >= cols);
+    assert!(output.len() >= rows);
+
+    let chunks = cols / 4;
+
+    // SAFETY: bounds checked above. A set bit g < chunks reads the 4 bytes
+    // at 4g inside the row; the partial group g == chunks reads bytes
+    // one at a time up to cols. Bits past the last group are ignored.
+    unsafe {
+        let w_ptr = weights.as_ptr();
+        let x_ptr = input.as_ptr() as *const u8;
+
+        for (i, out) in output[..rows].iter_mut().enumerate() {
+            let mut acc: i32 = 0;
+            let row_offset = i * cols;
+
+            for byte in 0..stride {
+                let mut bits = *mask.get_unchecked(i * stride + byte);
+                while bits != 0 {
+                    let g = byte * 8 + bits.trailing_zeros() as usize;
+                    bits &= bits - 1;
+
+                    if g < chunks {
+                        let w4 = (w_ptr.add(row_offset + g * 4) as *const u32).read_unaligned();
+                        let x4 = (x_ptr.add(g * 4) as *const u32).read_unaligned();
+
+                        let w0 = (w4 as u8) as i8 as i32;
+                        let w1 = ((w4 >> 8) as u8) as i8 as i32;
+                        let w2 = ((w4 >> 16) as u8) as i8 as i32;
+                        let w3 = ((w4 >> 24) as u8) as i8 as i32;
+
+                        let x0 = (x4 as u8) as i8 as i32;
+                        let x1 = ((x4 >> 8) as u8) as i8 as i32;
+                        let x2 = ((x4 >> 16) as u8) as i8 as i32;
+                        let x3 = ((x4 >> 24) as u8) as i8 as i32;
+
+                        acc = acc_add(acc, w0 * x0 + w1 * x1 + w2 * x2 + w3 * x3);
+                    } else {
+                        for idx in g * 4..cols {
+                            let w = *weights.get_unchecked(row_offset + idx) as i8 as i32;
+                            let x = *input.get_unchecked(idx) as i32;
+                            acc = acc_add(acc, w * x);
+                        }
+                    }
+                }
+            }
+
+            *out = acc;
+        }
+    }
+}
+
+/// Dense `matmul_i8`, or `matmul_i8_masked` when `mask` is non-empty.
+pub fn matmul_i8_sparse(
+    weights: &[u8],
+    mask: &[u8],
+    input: &[i8],
+    output: &mut [i32],
+    rows: usize,
+    cols: usize,
+) {
+    if mask.is_empty() {
+        matmul_i8(weights, input, output, rows, cols);
+    } else {
+        matmul_i8_masked(weights, mask, input, output, rows, cols);
+    }
+}
+
+/// Transposed product: y = Wᵀ * x, for a weight matrix stored (rows, cols)
+/// and read as (cols, rows) — how a tied logit head reuses its embedding.
+///
+/// Walks W row by row, scattering `x[i] * W[i, ..]` into the outputs, so
+/// loads stay sequential (packed 4 bytes at a time) and zero inputs skip
+/// their row entirely.
+///
+/// Arguments:
+///   weights: Row-major INT8 weight matrix, shape (rows, cols), stored as &[u8]
+///   input:   INT8 input vector, shape (rows,)
+///   output:  INT32 output vector, shape (cols,) — caller requantizes
+pub fn matmul_i8_transposed(
+    weights: &[u8],
+    input: &[i8],
+    output: &mut [i32],
+    rows: usize,
+    cols: usize,
+) {
+    assert!(weights.len() >= rows * cols);
+    assert!(input.len() >= rows);
+    assert!(output.len() >= cols);
+
+    let output = &mut output[..cols];
+    output.fill(0);
+    let chunks = cols / 4;
+
+    // SAFETY: bounds checked above. Packed loads read 4 bytes at 4j within
+    // row i for j < chunks; the remainder is read byte by byte up to cols.
+    unsafe {
+        let w_ptr = weights.as_ptr();
+
+        for (i, &x) in input[..rows].iter().enumerate() {
+            if x == 0 {
+                continue;
+            }
+            let x = x as i32;
+            let row_offset = i * cols;
+
+            for j in 0..chunks {
+                let w4 = (w_ptr.add(row_offset + j * 4) as *const u32).read_unaligned();
+                let out = output.get_unchecked_mut(j * 4..j * 4 + 4);
+                out[0] = acc_add(out[0], (w4 as u8) as i8 as i32 * x);
+                out[1] = acc_add(out[1], ((w4 >> 8) as u8) as i8 as i32 * x);
+                out[2] = acc_add(out[2], ((w4 >> 16) as u8) as i8 as i32 * x);
+                out[3] = acc_add(out[3], ((w4 >> 24) as u8) as i8 as i32 * x);
+            }
+
+            for j in chunks * 4..cols {
+                let w = *weights.get_unchecked(row_offset + j) as i8 as i32;
+                let out = output.get_unchecked_mut(j);
+                *out = acc_add(*out, w * x);
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "er-syscall", target_os = "solana"))]
+extern "C" {
+    fn sol_matmul_i8(
+        weights: *const i8,
+        input: *const i8,
+        output: *mut i32,
+        rows: u64,
+        cols: u64,
+    ) -> u64;
+}
+
+/// Same product through the `sol_matmul_i8` syscall that MagicBlock ER
+/// validators register (awm_syscall::matmul::matmul_i8 on the host).
+///
+/// Only built with the `er-syscall` feature — mainnet validators reject
+/// programs that reference unknown syscalls. Returns false when the syscall
+/// is unavailable or fails.
+pub fn matmul_i8_native(
+    weights: &[u8],
+    input: &[i8],
+    output: &mut [i32],
+    rows: usize,
+    cols: usize,
+) -> bool {
+    assert!(weights.len() >= rows * cols);
+    assert!(input.len() >= cols);
+    assert!(output.len() >= rows);
+
+    #[cfg(all(feature = "er-syscall", target_os = "solana"))]
+    {
+        // SAFETY: bounds checked above; the syscall maps each region
+        // through the VM memory map before touching it.
+        let ret = unsafe {
+            sol_matmul_i8(
+                weights.as_ptr() as *const i8,
+                input.as_ptr(),
+                output.as_mut_ptr(),
+                rows as u64,
+                cols as u64,
+            )
+        };
+        ret == 0
+    }
+
+    #[cfg(not(all(feature = "er-syscall", target_os = "solana")))]
+    {
+        false
+    }
+}
+
+/// Requantize INT32 accumulator values to INT8 using per-channel scale factors.
+///
+/// For each output element:
+///   output_i8[i] = clamp(round(output_i32[i] * scale[i] / 65536), -128, 127)
+///
+/// Scale factors are stored as u16 fixed-point values in the manifest:
+///   actual_scale = raw_u16 / 65536.0
+pub fn requantize_per_channel(
+    input: &[i32],
+    scales: &[u16],
+    output: &mut [i8],
+    n: usize,
+) {
+    assert!(input.len() >= n);
+    assert!(scales.len() >= n);
+    assert!(output.len() >= n);
+
+    for i in 0..n {
+        let scaled = narrow_i32((input[i] as i64 * scales[i] as i64) >> 16);
+        output[i] = scaled.clamp(-128, 127) as i8;
+    }
+}
+
+/// How requantization rounds the scaled accumulator.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    /// Floor (arithmetic shift). The error is the same for the same
+    /// values, so it correlates across frames.
+    #[default]
+    Truncate,
+    /// Round up with probability equal to the dropped fraction, drawing
+    /// from the `rng` stream `seed` (unbiased in expectation, and still
+    /// replayable from the seed)
+    Stochastic { seed: u64 },
+}
+
+impl Rounding {
+    /// The same mode on sub-stream `tag`, so separate requantize calls in a
+    /// frame draw independent noise.
+    pub fn substream(self, tag: u64) -> Self {
+        match self {
+            Rounding::Truncate => Rounding::Truncate,
+            Rounding::Stochastic { seed } => Rounding::Stochastic {
+                seed: crate::rng::substream(seed, tag),
+            },
+        }
+    }
+}
+
+/// `requantize_per_channel` with the given rounding. Stochastic rounding
+/// adds a uniform draw in 0..65536 before the shift, so each channel's
+/// value rounds up with probability (dropped bits / 65536).
+pub fn requantize_per_channel_rounded(
+    input: &[i32],
+    scales: &[u16],
+    output: &mut [i8],
+    n: usize,
+    rounding: Rounding,
+) {
+    let seed = match rounding {
+        Rounding::Truncate => return requantize_per_channel(input, scales, output, n),
+        Rounding::Stochastic { seed } => seed,
+    };
+    assert!(input.len() >= n);
+    assert!(scales.len() >= n);
+    assert!(output.len() >= n);
+
+    for i in 0..n {
+        let noise = crate::rng::draw_u16(seed, i) as i64;
+        let scaled = narrow_i32((input[i] as i64 * scales[i] as i64 + noise) >> 16);
+        output[i] = scaled.clamp(-128, 127) as i8;
+    }
+}
+
+/// Requantize with a single per-tensor scale factor.
+pub fn requantize_per_tensor(
+    input: &[i32],
+    scale: u16,
+    output: &mut [i8],
+    n: usize,
+) {
+    assert!(input.len() >= n);
+    assert!(output.len() >= n);
+
+    let scale_i64 = scale as i64;
+    for i in 0..n {
+        let scaled = narrow_i32((input[i] as i64 * scale_i64) >> 16);
+        output[i] = scaled.clamp(-128, 127) as i8;
+    }
+}
+
+/// Element-wise multiply two INT8 vectors with INT8 output.
+///
+/// Used for: y = y_ssm * SiLU(z) (gating step)
+///
+/// Computes: output[i] = (a[i] * b[i]) >> shift
+pub fn elementwise_mul_i8(
+    a: &[i8],
+    b: &[i8],
+    output: &mut [i8],
+    n: usize,
+    shift: u32,
+) {
+    assert!(a.len() >= n);
+    assert!(b.len() >= n);
+    assert!(output.len() >= n);
+
+    for i in 0..n {
+        let product = (a[i] as i32) * (b[i] as i32);
+        let shifted = shr_i32(product, shift);
+        output[i] = shifted.clamp(-128, 127) as i8;
+    }
+}
+
+/// Add two INT8 vectors (residual connection).
+///
+/// Computes: output[i] = clamp(a[i] + b[i], -128, 127)
+pub fn add_i8(a: &[i8], b: &[i8], output: &mut [i8], n: usize) {
+    assert!(a.len() >= n);
+    assert!(b.len() >= n);
+    assert!(output.len() >= n);
+
+    for i in 0..n {
+        let sum = (a[i] as i16) + (b[i] as i16);
+        output[i] = sum.clamp(-128, 127) as i8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    fn matmul_naive(weights: &[u8], input: &[i8], rows: usize, cols: usize) -> Vec<i32> {
+        (0..rows)
+            .map(|i| {
+                (0..cols)
+                    .map(|j| weights[i * cols + j] as i8 as i32 * input[j] as i32)
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matmul_identity() {
+        // 2x2 identity matrix times [1, 2] should give [1, 2]
+        let weights: &[u8] = &[1, 0, 0, 1]; // [[1,0],[0,1]] as u8
+        let input: &[i8] = &[10, 20];
+        let mut output = [0i32; 2];
+
+        matmul_i8(weights, input, &mut output, 2, 2);
+
+        assert_eq!(output[0], 10);
+        assert_eq!(output[1], 20);
+    }
+
+    #[test]
+    fn test_matmul_simple() {
+        // [[1, 2], [3, 4]] * [5, 6] = [17, 39]
+        let weights: &[u8] = &[1, 2, 3, 4];
+        let input: &[i8] = &[5, 6];
+        let mut output = [0i32; 2];
+
+        matmul_i8(weights, input, &mut output, 2, 2);
+
+        assert_eq!(output[0], 5 + 2 * 6); // 17
+        assert_eq!(output[1], 3 * 5 + 4 * 6); // 39
+    }
+
+    #[test]
+    fn test_matmul_negative() {
+        // Test with negative INT8 values
+        let weights: &[u8] = &[(-1i8) as u8, 2, 3, (-4i8) as u8];
+        let input: &[i8] = &[-5, 6];
+        let mut output = [0i32; 2];
+
+        matmul_i8(weights, input, &mut output, 2, 2);
+
+        assert_eq!(output[0], (-1) * (-5) + 2 * 6); // 17
+        assert_eq!(output[1], 3 * (-5) + (-4) * 6);  // -39
+    }
+
+    #[test]
+    fn test_matmul_unrolling() {
+        // Test with cols=8 to exercise packed loop
+        let cols = 8;
+        let rows = 2;
+        let weights: Vec<u8> = (0..16).map(|i| (i as i8 + 1) as u8).collect();
+        let input: Vec<i8> = (0..8).map(|i| i as i8 + 1).collect();
+        let mut output = vec![0i32; rows];
+
+        matmul_i8(&weights, &input, &mut output, rows, cols);
+
+        // Row 0: 1*1 + 2*2 + 3*3 + 4*4 + 5*5 + 6*6 + 7*7 + 8*8 = 204
+        let expected0: i32 = (1..=8).map(|i: i32| i * i).sum();
+        assert_eq!(output[0], expected0);
+    }
+
+    #[test]
+    fn test_matmul_large_packed() {
+        // Test with a larger matrix that fully exercises the packed loop
+        let rows = 4;
+        let cols = 16;
+        let weights: Vec<u8> = (0..64).map(|i| ((i % 5) as i8 - 2) as u8).collect();
+        let input: Vec<i8> = (0..16).map(|i| i as i8 - 8).collect();
+        let mut output = vec![0i32; rows];
+
+        matmul_i8(&weights, &input, &mut output, rows, cols);
+
+        // Verify against naive computation
+        for i in 0..rows {
+            let expected: i32 = (0..cols).map(|j| {
+                let w = weights[i * cols + j] as i8 as i32;
+                let x = input[j] as i32;
+                w * x
+            }).sum();
+            assert_eq!(output[i], expected, "row {} mismatch", i);
+        }
+    }
+
+    #[test]
+    fn test_masked_matmul_skips_cleared_groups() {
+        // cols = 10: groups [0..4), [4..8) and the partial [8..10)
+        let (rows, cols) = (2, 10);
+        let weights = vec![1u8; rows * cols];
+        let input = vec![1i8; cols];
+        assert_eq!(mask_stride(cols), 1);
+
+        let mut mask = vec![0u8; 2];
+        build_row_masks(&weights, &mut mask, rows, cols);
+        assert_eq!(mask, vec![0b111, 0b111]);
+
+        // Row 0 keeps only the partial group, row 1 only the middle one
+        let mask = [0b100, 0b010];
+        let mut output = vec![0i32; rows];
+        matmul_i8_masked(&weights, &mask, &input, &mut output, rows, cols);
+        assert_eq!(output, vec![2, 4]);
+
+        // An empty mask falls back to the dense kernel
+        matmul_i8_sparse(&weights, &[], &input, &mut output, rows, cols);
+        assert_eq!(output, vec![10, 10]);
+    }
+
+    #[test]
+    fn test_requantize() {
+        let input = [1000i32, -2000, 500, -100];
+        let scales = [32768u16, 16384, 65535, 8192];
+        let mut output = [0i8; 4];
+
+        requantize_per_channel(&input, &scales, &mut output, 4);
+
+        assert_eq!(output[0], 127);  // 1000 * 32768 / 65536 = 500 → clamped
+        assert_eq!(output[1], -128); // -2000 * 16384 / 65536 = -500 → clamped
+    }
+
+    /// Stochastic rounding lands on the floor or the next value, and on
+    /// average matches the exact quotient where truncation is biased low.
+    #[test]
+    fn test_stochastic_requantize_is_unbiased() {
+        // 3 * 16384 / 65536 = 0.75
+        let input = [3i32; 1024];
+        let scales = [16384u16; 1024];
+        let mut truncated = [0i8; 1024];
+        let mut rounded = [0i8; 1024];
+        let stochastic = Rounding::Stochastic { seed: crate::rng::frame_seed(9, 1) };
+
+        requantize_per_channel_rounded(&input, &scales, &mut truncated, 1024, Rounding::Truncate);
+        requantize_per_channel_rounded(&input, &scales, &mut rounded, 1024, stochastic);
+        assert!(truncated.iter().all(|&v| v == 0));
+        assert!(rounded.iter().all(|&v| v == 0 || v == 1));
+        let ones = rounded.iter().filter(|&&v| v == 1).count();
+        assert!((700..830).contains(&ones), "{ones} of 1024 rounded up");
+
+        // Same seed, same result; exact values never move
+        let mut again = [0i8; 1024];
+        requantize_per_channel_rounded(&input, &scales, &mut again, 1024, stochastic);
+        assert_eq!(rounded, again);
+        let exact = [4i32; 4];
+        let mut out = [0i8; 4];
+        requantize_per_channel_rounded(&exact, &[16384; 4], &mut out, 4, stochastic);
+        assert_eq!(out, [1; 4]);
+    }
+
+    #[test]
+    fn test_elementwise_mul() {
+        let a: &[i8] = &[10, -20, 30, -40];
+        let b: &[i8] = &[5, -5, 2, -3];
+        let mut output = [0i8; 4];
+
+        elementwise_mul_i8(a, b, &mut output, 4, 4);
+
+        assert_eq!(output[0], 3);  // 10 * 5 = 50, >> 4 = 3
+        assert_eq!(output[1], 6);  // (-20) * (-5) = 100, >> 4 = 6
+    }
+
+    #[test]
+    fn test_add_saturation() {
+        let a: &[i8] = &[100, -100, 50, -50];
+        let b: &[i8] = &[100, -100, -60, 60];
+        let mut output = [0i8; 4];
+
+        add_i8(a, b, &mut output, 4);
+
+        assert_eq!(output[0], 127);  // 200 → clamped
+        assert_eq!(output[1], -128); // -200 → clamped
+        assert_eq!(output[2], -10);
+        assert_eq!(output[3], 10);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-math")]
+    #[should_panic(expected = "INT32 accumulator overflow")]
+    fn test_matmul_checked_overflow() {
+        // 128 * 128 per MAC overflows i32 past 2^17 columns
+        let cols = (1 << 17) + 4;
+        let weights = vec![(-128i8) as u8; cols];
+        let input = vec![-128i8; cols];
+        let mut output = [0i32; 1];
+
+        matmul_i8(&weights, &input, &mut output, 1, cols);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-math")]
+    #[should_panic(expected = "shift exceeds INT32 width")]
+    fn test_elementwise_mul_checked_shift() {
+        let mut output = [0i8; 1];
+        elementwise_mul_i8(&[1], &[1], &mut output, 1, 32);
+    }
+
+    #[test]
+    fn test_matmul_native_unavailable_off_er() {
+        // Host builds have no sol_matmul_i8; the caller must fall back
+        let weights: &[u8] = &[1, 2, 3, 4];
+        let input: &[i8] = &[5, 6];
+        let mut output = [0i32; 2];
+
+        assert!(!matmul_i8_native(weights, input, &mut output, 2, 2));
+    }
+
+    /// Random shape plus weights/input with a few bytes of slack past the
+    /// end, so the packed loads see every cols % 4 remainder
+    fn matmul_case() -> impl Strategy<Value = (usize, usize, Vec<u8>, Vec<i8>)> {
+        (0usize..9, 0usize..41).prop_flat_map(|(rows, cols)| {
+            (
+                Just(rows),
+                Just(cols),
+                vec(any::<u8>(), rows * cols..rows * cols + 8),
+                vec(any::<i8>(), cols..cols + 4),
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matmul_matches_naive(
+            (rows, cols, weights, input) in matmul_case(),
+            offset in 0usize..4,
+        ) {
+            // Shift both slices off the allocation's alignment so every
+            // read_unaligned in the packed loop is genuinely unaligned
+            let mut w_buf = vec![0u8; offset];
+            w_buf.extend_from_slice(&weights);
+            let mut x_buf = vec![0i8; offset];
+            x_buf.extend_from_slice(&input);
+
+            let mut output = vec![i32::MIN; rows];
+            matmul_i8(&w_buf[offset..], &x_buf[offset..], &mut output, rows, cols);
+
+            prop_assert_eq!(output, matmul_naive(&weights, &input, rows, cols));
+        }
+
+        #[test]
+        fn prop_matmul_native_matches_naive((rows, cols, weights, input) in matmul_case()) {
+            // Off-ER the syscall is unavailable and output must be untouched
+            let mut output = vec![i32::MIN; rows];
+            if matmul_i8_native(&weights, &input, &mut output, rows, cols) {
+                prop_assert_eq!(output, matmul_naive(&weights, &input, rows, cols));
+            } else {
+                prop_assert!(output.iter().all(|&v| v == i32::MIN));
+            }
+        }
+
+        #[test]
+        fn prop_matmul_ignores_trailing_bytes((rows, cols, weights, input) in matmul_case()) {
+            // Bytes past rows * cols (the next tensor in a shard) never leak in
+            let mut output = vec![0i32; rows];
+            matmul_i8(&weights, &input, &mut output, rows, cols);
+
+            let mut trimmed = vec![0i32; rows];
+            matmul_i8(&weights[..rows * cols], &input[..cols], &mut trimmed, rows, cols);
+
+            prop_assert_eq!(output, trimmed);
+        }
+
+        #[test]
+        fn prop_masked_matmul_matches_naive(
+            (rows, cols, mut weights, input) in matmul_case(),
+            prune in any::<u64>(),
+        ) {
+            // Zero whole 4-byte groups, as structured pruning does
+            for i in 0..rows {
+                for g in 0..cols.div_ceil(4) {
+                    if prune >> ((i * 7 + g) % 64) & 1 == 1 {
+                        let end = (i * cols + 4 * g + 4).min((i + 1) * cols);
+                        weights[i * cols + 4 * g..end].fill(0);
+                    }
+                }
+            }
+            let mut mask = vec![0u8; rows * mask_stride(cols)];
+            build_row_masks(&weights, &mut mask, rows, cols);
+
+            let mut output = vec![i32::MIN; rows];
+            matmul_i8_masked(&weights, &mask, &input, &mut output, rows, cols);
+            prop_assert_eq!(output, matmul_naive(&weights, &input, rows, cols));
+        }
+
+        #[test]
+        fn prop_transposed_matmul_matches_naive(
+            (rows, cols, weights, _) in matmul_case(),
+            input in vec(any::<i8>(), 8),
+        ) {
+            // Wᵀx equals W'x for W' the explicit (cols, rows) transpose
+            let transposed: Vec<u8> =
+                (0..cols * rows).map(|k| weights[(k % rows) * cols + k / rows]).collect();
+
+            let mut output = vec![i32::MIN; cols];
+            matmul_i8_transposed(&weights, &input[..rows], &mut output, rows, cols);
+            prop_assert_eq!(output, matmul_naive(&transposed, &input, cols, rows));
+        }
+    }
+}
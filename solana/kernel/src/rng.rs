@@ -0,0 +1,73 @@
+//! Counter-based random streams for seeded, replayable kernel randomness.
+//!
+//! Every draw is a pure function of (stream seed, index) — the splitmix64
+//! sequence — so a frame can be re-executed from its session seed and frame
+//! number alone, in any order, with no generator state to checkpoint.
+//! Sub-streams (per layer, per call site) are derived the same way, so
+//! different consumers of one frame's stream never share draws.
+
+/// splitmix64's increment: 2^64 / φ
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// splitmix64 output mix: a well-distributed 64-bit hash of `x`.
+#[inline(always)]
+pub const fn mix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Stream seed of frame `frame` in a session created with `seed`.
+pub const fn frame_seed(seed: u64, frame: u32) -> u64 {
+    mix64(seed ^ mix64(frame as u64))
+}
+
+/// Seed of sub-stream `tag` of the stream `seed`.
+pub const fn substream(seed: u64, tag: u64) -> u64 {
+    mix64(seed.wrapping_add(mix64(!tag)))
+}
+
+/// The `index`th draw of the stream `seed`, uniform over 0..=u16::MAX.
+#[inline(always)]
+pub fn draw_u16(seed: u64, index: usize) -> u16 {
+    (mix64(seed.wrapping_add((index as u64).wrapping_mul(GOLDEN_GAMMA))) >> 48) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streams_are_reproducible_and_distinct() {
+        let a = frame_seed(42, 7);
+        assert_eq!(a, frame_seed(42, 7));
+        assert_ne!(a, frame_seed(42, 8));
+        assert_ne!(a, frame_seed(43, 7));
+        assert_ne!(substream(a, 0), substream(a, 1));
+        assert_ne!(substream(a, 0), a);
+
+        let draws: Vec<u16> = (0..64).map(|i| draw_u16(a, i)).collect();
+        assert_eq!(draws, (0..64).map(|i| draw_u16(a, i)).collect::<Vec<_>>());
+        assert_ne!(
+            draws,
+            (0..64)
+                .map(|i| draw_u16(substream(a, 0), i))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// Draws cover the range evenly enough for rounding: each quarter gets
+    /// roughly a quarter of them.
+    #[test]
+    fn test_draws_are_roughly_uniform() {
+        let seed = frame_seed(1, 1);
+        let mut quarters = [0usize; 4];
+        for i in 0..4096 {
+            quarters[(draw_u16(seed, i) >> 14) as usize] += 1;
+        }
+        for q in quarters {
+            assert!((900..1150).contains(&q), "{quarters:?}");
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! Selective scan step — the core SSM recurrence for Mamba2.
+//!
+//! For each (i, j) in d_inner × d_state:
+//!   A_bar = exp(-dt[i] * A[i])                   (LUT)
+//!   h_new[i,j] = A_bar * h[i,j] + dt[i] * B[i,j] * x_ssm[i]   (INT8/INT32 MAC)
+//!   y[i] += C[i,j] * h_new[i,j]                 (INT8 dot product)
+//!
+//! As in Mamba2, B and C come from in_proj and are shared by every channel
+//! of a head group (`GroupedBc`). Models exported without them pass None
+//! and get B and C derived from x_ssm and the state index.
+//!
+//! CU estimate for d_inner=1024, d_state=16: ~147K CU
+//!
+//! Two hidden state storage modes are supported (selected by the manifest):
+//!   - INT8:  h stored at activation scale, 1 byte per element
+//!   - INT16: h stored with HIDDEN_I16_FRAC_BITS extra fractional bits,
+//!     2 bytes per element — slower decay of small values
+
+use crate::lut;
+use crate::matmul::acc_add;
+
+/// Extra fractional bits carried by INT16 hidden state relative to INT8.
+/// An INT16 element h16 represents h16 / 2^HIDDEN_I16_FRAC_BITS in INT8 units.
+pub const HIDDEN_I16_FRAC_BITS: u32 = 8;
+
+/// One step's input-dependent B and C, each (ngroups, d_state). Channel i
+/// reads group i / (d_inner / ngroups).
+#[derive(Clone, Copy)]
+pub struct GroupedBc<'a> {
+    pub b: &'a [i8],
+    pub c: &'a [i8],
+    pub ngroups: usize,
+}
+
+impl GroupedBc<'_> {
+    /// Offset of channel `i`'s group row in `b` and `c`.
+    #[inline(always)]
+    fn row(&self, i: usize, d_inner: usize, d_state: usize) -> usize {
+        let ngroups = self.ngroups.max(1);
+        let group_len = (d_inner / ngroups).max(1);
+        (i / group_len).min(ngroups - 1) * d_state
+    }
+}
+
+/// B·x and C for element (i, j): from the channel's group row if `bc` is
+/// set, else derived from x_ssm and j.
+#[inline(always)]
+fn b_c(bc: Option<&GroupedBc>, row: usize, x_val: i32, j: usize, d_state: usize) -> (i32, i32) {
+    match bc {
+        Some(bc) => (
+            ((bc.b[row + j] as i32 * x_val) >> 7).clamp(-128, 127),
+            bc.c[row + j] as i32,
+        ),
+        None => (
+            ((x_val * (j as i32 + 1)) >> 4).clamp(-128, 127),
+            ((x_val * (d_state as i32 - j as i32)) >> 4).clamp(-128, 127),
+        ),
+    }
+}
+
+/// Execute one selective scan step.
+///
+/// Arguments:
+///   x_ssm:    SSM input vector, shape (d_inner,)
+///   dt:       Timestep after softplus, shape (d_inner,)
+///   h:        Hidden state, shape (d_inner * d_state,) — modified in place
+///   a_log:    Log diagonal of SSM decay matrix, shape (d_inner,)
+///   bc:       Grouped B and C, or None to derive them from x_ssm
+///   lut_data: Packed activation LUTs (1024 bytes)
+///   y_ssm:    Output vector, shape (d_inner,) — written
+///   d_inner:  Inner dimension
+///   d_state:  State dimension
+#[allow(clippy::too_many_arguments)]
+pub fn selective_scan_step(
+    x_ssm: &[i8],
+    dt: &[i8],
+    h: &mut [i8],
+    a_log: &[u8],
+    bc: Option<&GroupedBc>,
+    lut_data: &[u8],
+    y_ssm: &mut [i8],
+    d_inner: usize,
+    d_state: usize,
+) {
+    for i in 0..d_inner {
+        let dt_val = dt[i] as i32;
+        let a_val = a_log[i] as i8 as i32;
+        let x_val = x_ssm[i] as i32;
+
+        // A_bar = exp(-dt * A) via LUT
+        let dt_a = ((dt_val.abs() * a_val.abs()) >> 4).min(255) as u8;
+        let a_bar = lut::exp_neg_lut(lut_data, dt_a) as i32;
+        let row = bc.map_or(0, |bc| bc.row(i, d_inner, d_state));
+
+        let mut y_acc: i32 = 0;
+
+        for j in 0..d_state {
+            let h_idx = i * d_state + j;
+
+            // Current hidden state
+            let h_val = h[h_idx] as i32;
+
+            let (b_val, c_val) = b_c(bc, row, x_val, j, d_state);
+
+            // h_new = A_bar * h + dt * B * x_ssm
+            let h_new = (a_bar * h_val + dt_val * b_val) >> 8;
+            h[h_idx] = h_new.clamp(-128, 127) as i8;
+
+            // y += C * h_new
+            y_acc = acc_add(y_acc, c_val * h_new);
+        }
+
+        // Requantize SSM output
+        y_ssm[i] = (y_acc >> 8).clamp(-128, 127) as i8;
+    }
+}
+
+/// Execute one selective scan step with INT16 hidden state.
+///
+/// Same recurrence as `selective_scan_step`, but h keeps HIDDEN_I16_FRAC_BITS
+/// of extra precision so small state values survive repeated decay instead
+/// of truncating to zero. The output y_ssm is requantized back to INT8.
+#[allow(clippy::too_many_arguments)]
+pub fn selective_scan_step_i16(
+    x_ssm: &[i8],
+    dt: &[i8],
+    h: &mut [i16],
+    a_log: &[u8],
+    bc: Option<&GroupedBc>,
+    lut_data: &[u8],
+    y_ssm: &mut [i8],
+    d_inner: usize,
+    d_state: usize,
+) {
+    for i in 0..d_inner {
+        let dt_val = dt[i] as i32;
+        let a_val = a_log[i] as i8 as i32;
+        let x_val = x_ssm[i] as i32;
+
+        // A_bar = exp(-dt * A) via LUT
+        let dt_a = ((dt_val.abs() * a_val.abs()) >> 4).min(255) as u8;
+        let a_bar = lut::exp_neg_lut(lut_data, dt_a) as i32;
+        let row = bc.map_or(0, |bc| bc.row(i, d_inner, d_state));
+
+        let mut y_acc: i32 = 0;
+
+        for j in 0..d_state {
+            let h_idx = i * d_state + j;
+
+            // Current hidden state (INT8 units << HIDDEN_I16_FRAC_BITS)
+            let h_val = h[h_idx] as i32;
+
+            let (b_val, c_val) = b_c(bc, row, x_val, j, d_state);
+
+            // h_new = A_bar * h + dt * B * x_ssm, kept at the widened scale:
+            // the INT8 kernel's final >> 8 is absorbed by the extra fraction bits
+            let h_new = ((a_bar * h_val) >> 8) + dt_val * b_val;
+            let h_new = h_new.clamp(i16::MIN as i32, i16::MAX as i32);
+            h[h_idx] = h_new as i16;
+
+            // y += C * h_new
+            y_acc = acc_add(y_acc, c_val * h_new);
+        }
+
+        // Requantize SSM output (drop the extra fraction bits too)
+        y_ssm[i] = (y_acc >> (8 + HIDDEN_I16_FRAC_BITS)).clamp(-128, 127) as i8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    fn make_test_luts() -> Vec<u8> {
+        let mut luts = vec![0u8; lut::LUT_TOTAL_SIZE];
+
+        // exp_neg LUT
+        for i in 0u16..256 {
+            let x = i as f64 / 32.0;
+            let exp_neg = (-x).exp();
+            luts[lut::EXP_NEG_OFFSET + i as usize] = (exp_neg * 255.0) as u8;
+        }
+
+        luts
+    }
+
+    #[test]
+    fn test_ssm_step_zero_input() {
+        let luts = make_test_luts();
+        let d_inner = 4;
+        let d_state = 2;
+
+        let x_ssm = vec![0i8; d_inner];
+        let dt = vec![10i8; d_inner];
+        let mut h = vec![10i8; d_inner * d_state];
+        let a_log = vec![16u8; d_inner];
+        let mut y_ssm = vec![0i8; d_inner];
+
+        selective_scan_step(&x_ssm, &dt, &mut h, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+
+        // With zero input, hidden state should decay toward zero
+        // and output should be near zero (since C depends on x_val=0)
+        for &y in &y_ssm {
+            assert_eq!(y, 0, "zero input should produce zero output");
+        }
+    }
+
+    #[test]
+    fn test_ssm_step_nonzero() {
+        let luts = make_test_luts();
+        let d_inner = 4;
+        // Both B·x·dt and C·h are requantized by >> 8: a small state or
+        // input rounds the whole step to zero
+        let d_state = 16;
+
+        let x_ssm = vec![64i8; d_inner];
+        let dt = vec![64i8; d_inner];
+        let mut h = vec![0i8; d_inner * d_state];
+        let a_log = vec![8u8; d_inner];
+        let mut y_ssm = vec![0i8; d_inner];
+
+        selective_scan_step(&x_ssm, &dt, &mut h, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+
+        // With nonzero input and zero initial hidden state, we should get nonzero output
+        let any_nonzero = y_ssm.iter().any(|&y| y != 0);
+        assert!(any_nonzero, "nonzero input should produce nonzero output");
+    }
+
+    #[test]
+    fn test_ssm_step_i16_zero_input() {
+        let luts = make_test_luts();
+        let d_inner = 4;
+        let d_state = 2;
+
+        let x_ssm = vec![0i8; d_inner];
+        let dt = vec![10i8; d_inner];
+        let mut h = vec![10i16 << HIDDEN_I16_FRAC_BITS; d_inner * d_state];
+        let a_log = vec![16u8; d_inner];
+        let mut y_ssm = vec![0i8; d_inner];
+
+        selective_scan_step_i16(&x_ssm, &dt, &mut h, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+
+        for &y in &y_ssm {
+            assert_eq!(y, 0, "zero input should produce zero output");
+        }
+    }
+
+    #[test]
+    fn test_ssm_step_i16_retains_small_state() {
+        let luts = make_test_luts();
+        let d_inner = 1;
+        let d_state = 1;
+
+        let x_ssm = vec![0i8; d_inner];
+        let dt = vec![4i8; d_inner];
+        let a_log = vec![4u8; d_inner];
+        let mut y_ssm = vec![0i8; d_inner];
+
+        // A state of 1 in INT8 units truncates to zero after one decay step...
+        let mut h8 = vec![1i8; d_inner * d_state];
+        selective_scan_step(&x_ssm, &dt, &mut h8, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+        assert_eq!(h8[0], 0);
+
+        // ...but survives in INT16 storage
+        let mut h16 = vec![1i16 << HIDDEN_I16_FRAC_BITS; d_inner * d_state];
+        selective_scan_step_i16(&x_ssm, &dt, &mut h16, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+        assert!(h16[0] > 0, "INT16 state should retain sub-unit values, got {}", h16[0]);
+    }
+
+    /// Channels in one group see the same B and C; the other group's
+    /// opposite B drives its state the other way.
+    #[test]
+    fn test_ssm_grouped_bc_shared_within_group() {
+        let luts = make_test_luts();
+        let (d_inner, d_state) = (4, 2);
+        let bc = GroupedBc {
+            b: &[64, 32, -64, -32],
+            c: &[16, 16, 16, 16],
+            ngroups: 2,
+        };
+
+        let x_ssm = vec![100i8; d_inner];
+        let dt = vec![16i8; d_inner];
+        let a_log = vec![16u8; d_inner];
+        let mut h = vec![0i8; d_inner * d_state];
+        let mut y_ssm = vec![0i8; d_inner];
+        selective_scan_step(&x_ssm, &dt, &mut h, &a_log, Some(&bc), &luts, &mut y_ssm, d_inner, d_state);
+
+        assert_eq!(h[0..2], h[2..4]);
+        assert_eq!(h[4..6], h[6..8]);
+        assert!(h[0] > h[1] && h[1] > 0, "{h:?}");
+        assert!(h[4] < 0 && h[5] < 0, "{h:?}");
+        assert_eq!(y_ssm[0], y_ssm[1]);
+        assert!(y_ssm[0] > y_ssm[2]);
+    }
+
+    /// Random (d_inner, d_state) with matching dt, h and a_log, plus an
+    /// arbitrary LUT upload — the invariants below hold for any exp_neg table
+    fn scan_case() -> impl Strategy<Value = (usize, usize, Vec<i8>, Vec<i8>, Vec<u8>, Vec<u8>)> {
+        (1usize..9, 1usize..9).prop_flat_map(|(d_inner, d_state)| {
+            (
+                Just(d_inner),
+                Just(d_state),
+                vec(any::<i8>(), d_inner),
+                vec(any::<i8>(), d_inner * d_state),
+                vec(any::<u8>(), d_inner),
+                vec(any::<u8>(), lut::LUT_TOTAL_SIZE),
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ssm_zero_input_decays((d_inner, d_state, dt, h, a_log, luts) in scan_case()) {
+            let x_ssm = vec![0i8; d_inner];
+            let mut y_ssm = vec![1i8; d_inner];
+            let mut h_new = h.clone();
+
+            selective_scan_step(&x_ssm, &dt, &mut h_new, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+
+            prop_assert!(y_ssm.iter().all(|&y| y == 0), "zero input gave {:?}", y_ssm);
+            for (before, after) in h.iter().zip(h_new.iter()) {
+                prop_assert!(
+                    after.unsigned_abs() <= before.unsigned_abs(),
+                    "state grew from {} to {}", before, after
+                );
+            }
+        }
+
+        #[test]
+        fn prop_ssm_i16_zero_input_decays((d_inner, d_state, dt, h, a_log, luts) in scan_case()) {
+            let x_ssm = vec![0i8; d_inner];
+            let mut y_ssm = vec![1i8; d_inner];
+            let h: Vec<i16> = h.iter().map(|&v| (v as i16) << HIDDEN_I16_FRAC_BITS).collect();
+            let mut h_new = h.clone();
+
+            selective_scan_step_i16(&x_ssm, &dt, &mut h_new, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+
+            prop_assert!(y_ssm.iter().all(|&y| y == 0), "zero input gave {:?}", y_ssm);
+            for (before, after) in h.iter().zip(h_new.iter()) {
+                prop_assert!(
+                    after.unsigned_abs() <= before.unsigned_abs(),
+                    "state grew from {} to {}", before, after
+                );
+            }
+        }
+
+        #[test]
+        fn prop_ssm_i16_matches_i8_from_zero_state(
+            (d_inner, d_state, dt, _h, a_log, luts) in scan_case(),
+            x in any::<i8>(),
+        ) {
+            // From h = 0 the decay term vanishes, so the INT16 state is the
+            // INT8 state with its fraction bits kept
+            let x_ssm = vec![x; d_inner];
+            let mut y_ssm = vec![0i8; d_inner];
+            let mut h8 = vec![0i8; d_inner * d_state];
+            let mut h16 = vec![0i16; d_inner * d_state];
+
+            selective_scan_step(&x_ssm, &dt, &mut h8, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+            selective_scan_step_i16(&x_ssm, &dt, &mut h16, &a_log, None, &luts, &mut y_ssm, d_inner, d_state);
+
+            let widened: Vec<i8> = h16.iter().map(|&v| (v >> HIDDEN_I16_FRAC_BITS) as i8).collect();
+            prop_assert_eq!(widened, h8);
+        }
+    }
+}
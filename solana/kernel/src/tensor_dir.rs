@@ -0,0 +1,280 @@
+//! Named-tensor directory at the head of a weight shard.
+//!
+//! A shard may open with a compact directory — safetensors-style, but
+//! fixed-width so it parses without allocation — naming every tensor it
+//! holds by a 64-bit hash. Readers then find tensors by name instead of
+//! trusting offsets recorded elsewhere, and a shard is self-describing
+//! once finalized. Shards without the magic are plain tensor bytes.
+//!
+//! Layout (all integers little-endian):
+//!   - magic: `AWMT`           (offset 0)
+//!   - count: u16              (offset 4)
+//!   - reserved: u16           (offset 6)
+//!   - entries: count × 20 bytes, strictly ascending by name hash
+//!
+//! Each entry is `name_hash u64 | offset u32 | rows u16 | cols u16 |
+//! dtype u8 | pad [u8; 3]`.
+//!
+//! Entry offsets are from the start of the shard and point past the
+//! directory, so tensor bytes never overlap it.
+
+/// First bytes of a shard that carries a directory
+pub const MAGIC: [u8; 4] = *b"AWMT";
+
+/// Magic, entry count and reserved bytes
+pub const HEADER_SIZE: usize = 8;
+
+/// Bytes per directory entry
+pub const ENTRY_SIZE: usize = 20;
+
+/// One named tensor of a shard.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TensorEntry {
+    pub name_hash: u64,
+    /// Byte offset from the start of the shard
+    pub offset: u32,
+    pub rows: u16,
+    pub cols: u16,
+    pub dtype: u8,
+}
+
+impl TensorEntry {
+    /// Size of the tensor in bytes (one byte per element).
+    pub fn byte_len(&self) -> usize {
+        self.rows as usize * self.cols as usize
+    }
+}
+
+/// Why a shard's directory was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirectoryError {
+    /// The entry table runs past the end of the shard
+    Truncated,
+    /// Name hashes are not strictly ascending (unsorted or duplicated)
+    Unsorted,
+    /// A tensor overlaps the directory or runs past the end of the shard
+    EntryOutOfBounds,
+}
+
+/// Bytes a directory of `count` entries occupies.
+pub const fn directory_size(count: usize) -> usize {
+    HEADER_SIZE + count * ENTRY_SIZE
+}
+
+/// FNV-1a, 64-bit: the name hash of directory entries.
+#[derive(Clone, Copy)]
+struct Fnv(u64);
+
+impl Fnv {
+    const fn new() -> Self {
+        Fnv(0xCBF2_9CE4_8422_2325)
+    }
+
+    const fn write(mut self, bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            self.0 ^= bytes[i] as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01B3);
+            i += 1;
+        }
+        self
+    }
+}
+
+/// Name hash of `name`.
+pub const fn name_hash(name: &[u8]) -> u64 {
+    Fnv::new().write(name).0
+}
+
+/// Name hash of `layers.{layer}.{name}` — a layer tensor's directory name —
+/// without formatting the string.
+pub fn layer_tensor_hash(layer: usize, name: &[u8]) -> u64 {
+    let mut digits = [0u8; 20];
+    let mut start = digits.len();
+    let mut n = layer;
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    Fnv::new()
+        .write(b"layers.")
+        .write(&digits[start..])
+        .write(b".")
+        .write(name)
+        .0
+}
+
+/// A parsed, validated shard directory borrowing the shard bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct TensorDirectory<'a> {
+    shard: &'a [u8],
+    count: usize,
+}
+
+impl<'a> TensorDirectory<'a> {
+    /// Parse the directory at the head of `shard`. Ok(None) if the shard
+    /// has no directory; an error if it has one that can't be trusted.
+    pub fn parse(shard: &'a [u8]) -> Result<Option<Self>, DirectoryError> {
+        if shard.len() < HEADER_SIZE || shard[..4] != MAGIC {
+            return Ok(None);
+        }
+        let count = u16::from_le_bytes([shard[4], shard[5]]) as usize;
+        let dir = TensorDirectory { shard, count };
+        if shard.len() < dir.data_start() {
+            return Err(DirectoryError::Truncated);
+        }
+
+        let mut prev = None;
+        for i in 0..count {
+            let e = dir.entry(i);
+            if prev.is_some_and(|p| e.name_hash <= p) {
+                return Err(DirectoryError::Unsorted);
+            }
+            prev = Some(e.name_hash);
+            let start = e.offset as usize;
+            if start < dir.data_start() || start + e.byte_len() > shard.len() {
+                return Err(DirectoryError::EntryOutOfBounds);
+            }
+        }
+        Ok(Some(dir))
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// First byte past the directory.
+    pub fn data_start(&self) -> usize {
+        directory_size(self.count)
+    }
+
+    /// The `i`th entry, in name hash order. Panics past `len()`.
+    pub fn entry(&self, i: usize) -> TensorEntry {
+        let b = &self.shard[HEADER_SIZE + i * ENTRY_SIZE..][..ENTRY_SIZE];
+        TensorEntry {
+            name_hash: u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]),
+            offset: u32::from_le_bytes([b[8], b[9], b[10], b[11]]),
+            rows: u16::from_le_bytes([b[12], b[13]]),
+            cols: u16::from_le_bytes([b[14], b[15]]),
+            dtype: b[16],
+        }
+    }
+
+    /// The entry named by `name_hash` (binary search).
+    pub fn find(&self, name_hash: u64) -> Option<TensorEntry> {
+        let (mut lo, mut hi) = (0, self.count);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let e = self.entry(mid);
+            match e.name_hash.cmp(&name_hash) {
+                core::cmp::Ordering::Equal => return Some(e),
+                core::cmp::Ordering::Less => lo = mid + 1,
+                core::cmp::Ordering::Greater => hi = mid,
+            }
+        }
+        None
+    }
+
+    /// Bytes of the tensor named by `name_hash`.
+    pub fn resolve(&self, name_hash: u64) -> Option<&'a [u8]> {
+        let e = self.find(name_hash)?;
+        self.shard.get(e.offset as usize..e.offset as usize + e.byte_len())
+    }
+}
+
+/// Write a directory of `entries` to the head of `out`, sorting them by
+/// name hash first. Returns the bytes written, or None if `out` is too
+/// short or there are more than u16::MAX entries.
+pub fn write_directory(entries: &mut [TensorEntry], out: &mut [u8]) -> Option<usize> {
+    let count = u16::try_from(entries.len()).ok()?;
+    let size = directory_size(entries.len());
+    let out = out.get_mut(..size)?;
+    entries.sort_unstable_by_key(|e| e.name_hash);
+
+    out[..4].copy_from_slice(&MAGIC);
+    out[4..6].copy_from_slice(&count.to_le_bytes());
+    out[6..8].fill(0);
+    for (e, b) in entries.iter().zip(out[HEADER_SIZE..].chunks_exact_mut(ENTRY_SIZE)) {
+        b[0..8].copy_from_slice(&e.name_hash.to_le_bytes());
+        b[8..12].copy_from_slice(&e.offset.to_le_bytes());
+        b[12..14].copy_from_slice(&e.rows.to_le_bytes());
+        b[14..16].copy_from_slice(&e.cols.to_le_bytes());
+        b[16] = e.dtype;
+        b[17..20].fill(0);
+    }
+    Some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard_with(entries: &mut [TensorEntry], data_len: usize) -> Vec<u8> {
+        let mut shard = vec![0u8; directory_size(entries.len()) + data_len];
+        write_directory(entries, &mut shard).unwrap();
+        shard
+    }
+
+    #[test]
+    fn test_layer_tensor_hash_matches_formatted_name() {
+        assert_eq!(layer_tensor_hash(0, b"in_proj"), name_hash(b"layers.0.in_proj"));
+        assert_eq!(layer_tensor_hash(17, b"norm"), name_hash(b"layers.17.norm"));
+        assert_ne!(layer_tensor_hash(1, b"norm"), layer_tensor_hash(11, b"norm"));
+    }
+
+    #[test]
+    fn test_directory_round_trips_and_resolves_by_name() {
+        let start = directory_size(2) as u32;
+        let mut entries = [
+            TensorEntry { name_hash: name_hash(b"b"), offset: start + 4, rows: 2, cols: 1, dtype: 0 },
+            TensorEntry { name_hash: name_hash(b"a"), offset: start, rows: 2, cols: 2, dtype: 0 },
+        ];
+        let mut shard = shard_with(&mut entries, 6);
+        let data_start = start as usize;
+        shard[data_start..].copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+
+        let dir = TensorDirectory::parse(&shard).unwrap().unwrap();
+        assert_eq!(dir.len(), 2);
+        assert_eq!(dir.data_start(), data_start);
+        assert_eq!(dir.resolve(name_hash(b"a")), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(dir.resolve(name_hash(b"b")), Some(&[5, 6][..]));
+        assert_eq!(dir.resolve(name_hash(b"c")), None);
+    }
+
+    #[test]
+    fn test_plain_shards_have_no_directory() {
+        assert!(TensorDirectory::parse(&[1, 2, 3]).unwrap().is_none());
+        assert!(TensorDirectory::parse(&[0; 64]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bad_directories_are_rejected() {
+        let start = directory_size(1) as u32;
+        let entry = TensorEntry { name_hash: 1, offset: start, rows: 4, cols: 1, dtype: 0 };
+
+        // Tensor runs past the shard, or overlaps the directory
+        let shard = shard_with(&mut [entry], 3);
+        assert_eq!(TensorDirectory::parse(&shard).unwrap_err(), DirectoryError::EntryOutOfBounds);
+        let shard = shard_with(&mut [TensorEntry { offset: 4, ..entry }], 4);
+        assert_eq!(TensorDirectory::parse(&shard).unwrap_err(), DirectoryError::EntryOutOfBounds);
+
+        // Entry table longer than the shard
+        let mut shard = shard_with(&mut [entry], 4);
+        shard[4] = 9;
+        assert_eq!(TensorDirectory::parse(&shard).unwrap_err(), DirectoryError::Truncated);
+
+        // Duplicate names
+        let start = directory_size(2) as u32;
+        let e = TensorEntry { offset: start, ..entry };
+        let shard = shard_with(&mut [e, e], 4);
+        assert_eq!(TensorDirectory::parse(&shard).unwrap_err(), DirectoryError::Unsorted);
+    }
+}
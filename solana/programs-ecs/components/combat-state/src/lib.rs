@@ -0,0 +1,27 @@
+use bolt_lang::*;
+
+declare_id!("AxtvkTx4q4szRmhDtE614gRscqP2KStkDC2QD8uzFkoQ");
+
+/// Combat state — what resolve-damage settled on the last frame it ran.
+///
+/// The model only emits the next frame's state; hit events are read off
+/// the difference between it and these resolved values (a percent rise is
+/// a hit, a stock drop resets percent).
+///
+/// Lifecycle: Per-session, written every frame by resolve-damage after
+/// run_inference.
+#[component]
+#[derive(Default)]
+pub struct CombatState {
+    /// Session frame last resolved
+    pub frame: u32,
+
+    /// Resolved damage percent per player
+    pub percents: [u16; 2],
+
+    /// Stocks per player at the last resolved frame
+    pub stocks: [u8; 2],
+
+    /// Hits taken per player this session
+    pub hits: [u32; 2],
+}
@@ -61,6 +61,14 @@ pub struct CompressedFrame {
 /// Also serves as the replay data committed to mainnet when the session ends —
 /// the permanent record of what happened in this world.
 ///
+/// Sessions that opt in (`emit_frames`) also get each frame pushed as a
+/// SpectatorFrame event, for spectators whose RPC can't stream account
+/// diffs. It costs CU and log space every frame, so it's off by default.
+///
+/// A persistent world never ends, so there's no final replay to commit;
+/// instead its history leaves the ring in archived chunks of up to
+/// RING_BUFFER_SIZE frames (`archived_frames`).
+///
 /// Lifecycle: Per-session, written every frame by run_inference.
 #[component]
 #[derive(Default)]
@@ -74,9 +82,37 @@ pub struct FrameLog {
     /// Session ID reference
     pub session: Pubkey,
 
+    /// run-inference emits each frame as a SpectatorFrame event (set on CREATE)
+    pub emit_frames: bool,
+
+    /// Frame 0 as JOIN set it up: seed-derived spawn positions, facing and
+    /// port sides on the session's stage. Replays start from here rather
+    /// than assuming a fixed layout. A persistent world's is CREATE's empty
+    /// frame 0; players arrive later, via claims.
+    pub keyframe: CompressedFrame,
+
+    /// Frames an archiver has copied out of the ring (persistent worlds,
+    /// via session-lifecycle's ARCHIVE). A persistent world's ring would
+    /// otherwise overwrite its history unseen, so run-inference stalls
+    /// rather than get more than RING_BUFFER_SIZE frames ahead of this.
+    pub archived_frames: u32,
+
     // The actual ring buffer data is stored in the account's remaining space:
     //   frames: [CompressedFrame; RING_BUFFER_SIZE]
     //
     // At ~66 bytes per frame × 256 frames = ~16,896 bytes
     // Accessed via zero-copy by index: data[header_size + (index % 256) * frame_size]
 }
+
+impl FrameLog {
+    /// Frames written since the archiver last caught up.
+    pub fn unarchived(&self) -> u32 {
+        self.total_frames.saturating_sub(self.archived_frames)
+    }
+
+    /// Whether the next frame can be written without overwriting one the
+    /// archiver hasn't copied yet.
+    pub fn has_room(&self) -> bool {
+        self.unarchived() < RING_BUFFER_SIZE as u32
+    }
+}
@@ -0,0 +1,87 @@
+use bolt_lang::*;
+
+declare_id!("c4wr2cifFVyLUSPu6Q8haMku7RbU5vJyAqQLqTiXxwU");
+
+/// Most items live at once (K)
+pub const MAX_ITEMS: usize = 4;
+
+/// Item kinds (ITEM_NONE marks a free slot)
+pub const ITEM_NONE: u8 = 0;
+pub const ITEM_CAPSULE: u8 = 1;
+pub const ITEM_HEART: u8 = 2;
+pub const ITEM_BOMB: u8 = 3;
+pub const ITEM_BAT: u8 = 4;
+pub const NUM_ITEM_KINDS: u8 = 4;
+
+/// `Item::owner` of an item nobody holds
+pub const OWNER_NONE: u8 = u8::MAX;
+
+/// Used until the session configures its own
+pub const DEFAULT_SPAWN_INTERVAL: u16 = 600;
+
+/// One live item (14 bytes).
+#[component_deserialize]
+#[derive(Default)]
+pub struct Item {
+    /// ITEM_*
+    pub kind: u8,
+    /// Player slot holding it, or OWNER_NONE
+    pub owner: u8,
+    /// Fixed-point: actual = x / 256.0
+    pub x: i32,
+    /// Fixed-point: actual = y / 256.0
+    pub y: i32,
+    /// Session frame it spawned on
+    pub spawned_at: u32,
+}
+
+/// Item state — a session's item rules and the items currently in play.
+///
+/// Items are off unless the session enables them; a zeroed component
+/// spawns nothing. When enabled, spawn-items drops one item every
+/// `spawn_interval` frames into a free slot, at a kind and position drawn
+/// from the session seed and frame, so replays spawn the same items.
+///
+/// Lifecycle: Per-session; configured before the match starts, then
+/// updated every frame by spawn-items after run_inference.
+#[component]
+#[derive(Default)]
+pub struct ItemState {
+    /// Whether the session set the rules below
+    pub configured: bool,
+
+    /// Items are in play (and encoded into the model input)
+    pub enabled: bool,
+
+    /// Frames between spawns (0 = DEFAULT_SPAWN_INTERVAL)
+    pub spawn_interval: u16,
+
+    /// Session frame last stepped
+    pub frame: u32,
+
+    /// Item slots; free slots have kind ITEM_NONE
+    pub items: [Item; MAX_ITEMS],
+
+    /// Items spawned this session
+    pub spawned: u32,
+}
+
+impl ItemState {
+    /// Frames between spawns, falling back to the default.
+    pub fn interval_frames(&self) -> u32 {
+        match self.spawn_interval {
+            0 => DEFAULT_SPAWN_INTERVAL as u32,
+            n => n as u32,
+        }
+    }
+
+    /// Number of items in play.
+    pub fn live(&self) -> usize {
+        self.items.iter().filter(|i| i.kind != ITEM_NONE).count()
+    }
+
+    /// First free item slot, if any.
+    pub fn free_slot(&self) -> Option<usize> {
+        self.items.iter().position(|i| i.kind == ITEM_NONE)
+    }
+}
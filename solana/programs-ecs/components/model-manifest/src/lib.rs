@@ -11,8 +11,194 @@ pub const MAX_SHARDS: usize = 4;
 /// LUT size: 256 entries per activation function
 pub const LUT_SIZE: usize = 256;
 
-/// Number of activation LUTs: SiLU, softplus, rsqrt, exp_neg
-pub const NUM_LUTS: usize = 4;
+/// Number of activation LUTs: SiLU, softplus, rsqrt, exp_neg (v1), then
+/// GELU, tanh (v2)
+pub const NUM_LUTS: usize = 6;
+
+/// LUT block versions (see awm_kernel::lut). v2 appends tables to v1, so
+/// v1 tables keep their offsets and the v2 tables of a v1 block are zero.
+pub const LUT_VERSION_1: u8 = 1;
+pub const LUT_VERSION_2: u8 = 2;
+
+/// Per-layer tensor slots in the tensor table
+pub const TENSOR_IN_PROJ: usize = 0;
+pub const TENSOR_OUT_PROJ: usize = 1;
+pub const TENSOR_NORM: usize = 2;
+pub const TENSOR_A_LOG: usize = 3;
+pub const TENSOR_DT_BIAS: usize = 4;
+/// Optional (rows = 0 when unset): models without one derive dt from x_ssm
+pub const TENSOR_DT_PROJ: usize = 5;
+pub const TENSORS_PER_LAYER: usize = 6;
+
+/// Tensor element types
+pub const TENSOR_DTYPE_I8: u8 = 0;
+
+/// Encoder spec capacity
+pub const MAX_PLAYER_FIELDS: usize = 32;
+pub const MAX_GLOBAL_FIELDS: usize = 8;
+
+/// Encoder field sources — per-player state
+pub const FIELD_X: u8 = 0;
+pub const FIELD_Y: u8 = 1;
+pub const FIELD_PERCENT: u8 = 2;
+pub const FIELD_SHIELD: u8 = 3;
+pub const FIELD_SPEED_AIR_X: u8 = 4;
+pub const FIELD_SPEED_Y: u8 = 5;
+pub const FIELD_SPEED_GROUND_X: u8 = 6;
+pub const FIELD_SPEED_ATTACK_X: u8 = 7;
+pub const FIELD_SPEED_ATTACK_Y: u8 = 8;
+pub const FIELD_STATE_AGE: u8 = 9;
+pub const FIELD_HITLAG: u8 = 10;
+pub const FIELD_STOCKS: u8 = 11;
+pub const FIELD_FACING: u8 = 12;
+pub const FIELD_ON_GROUND: u8 = 13;
+pub const FIELD_ACTION_STATE: u8 = 14;
+pub const FIELD_JUMPS_LEFT: u8 = 15;
+pub const FIELD_CHARACTER: u8 = 16;
+
+/// Encoder field sources — per-player controller (input only, skipped on decode)
+pub const FIELD_STICK_X: u8 = 32;
+pub const FIELD_STICK_Y: u8 = 33;
+pub const FIELD_C_STICK_X: u8 = 34;
+pub const FIELD_C_STICK_Y: u8 = 35;
+pub const FIELD_TRIGGER_L: u8 = 36;
+pub const FIELD_TRIGGER_R: u8 = 37;
+pub const FIELD_BUTTONS: u8 = 38;
+
+/// Encoder field sources — global (input only, skipped on decode)
+pub const FIELD_STAGE: u8 = 64;
+
+/// Encoder field sources — items (input only, skipped on decode). Item
+/// fields sit among the global fields; slot k of the field's width holds
+/// item slot k. They encode as zero unless the session enables items.
+pub const FIELD_ITEM_KIND: u8 = 65;
+pub const FIELD_ITEM_X: u8 = 66;
+pub const FIELD_ITEM_Y: u8 = 67;
+pub const FIELD_ITEM_OWNER: u8 = 68;
+
+/// Encoder field kinds
+/// LINEAR: encode clamp(v / divisor), decode raw * divisor
+pub const FIELD_KIND_LINEAR: u8 = 0;
+/// BINARY: encode ±64, decode raw > 0
+pub const FIELD_KIND_BINARY: u8 = 1;
+/// CATEGORICAL: one slot per class (width = class count); encode one-hot
+/// ±64, decode argmax of the softmax over the slots
+pub const FIELD_KIND_CATEGORICAL: u8 = 2;
+
+/// Location and shape of one weight tensor inside the shard accounts.
+///
+/// Exported alongside the weights so inference never has to guess offsets
+/// from tensor sizes. Vectors (norm, a_log, dt_bias) use cols = 1.
+#[component_deserialize]
+#[derive(Default)]
+pub struct TensorRef {
+    /// Index into shard_keys
+    pub shard: u8,
+    /// Element type (TENSOR_DTYPE_*)
+    pub dtype: u8,
+    /// Byte offset within the shard's weight data
+    pub offset: u32,
+    /// Row-major shape
+    pub rows: u16,
+    pub cols: u16,
+}
+
+impl TensorRef {
+    /// Size of the tensor in bytes.
+    pub fn byte_len(&self) -> usize {
+        self.rows as usize * self.cols as usize
+    }
+
+    /// Resolve to the tensor's bytes within the given shard data regions.
+    pub fn resolve<'a>(&self, shards: &[&'a [u8]]) -> Option<&'a [u8]> {
+        let shard = shards.get(self.shard as usize)?;
+        let start = self.offset as usize;
+        shard.get(start..start.checked_add(self.byte_len())?)
+    }
+}
+
+/// One slot group in the model input/output vector (5 bytes).
+#[component_deserialize]
+#[derive(Default)]
+pub struct FieldSpec {
+    /// Where the value comes from (FIELD_*)
+    pub source: u8,
+    /// How it is quantized (FIELD_KIND_*)
+    pub kind: u8,
+    /// Number of vector slots the field occupies
+    pub width: u8,
+    /// LINEAR quantization step (encoded = value / divisor)
+    pub divisor: u16,
+}
+
+impl FieldSpec {
+    pub const fn linear(source: u8, divisor: u16) -> Self {
+        Self { source, kind: FIELD_KIND_LINEAR, width: 1, divisor }
+    }
+
+    pub const fn binary(source: u8) -> Self {
+        Self { source, kind: FIELD_KIND_BINARY, width: 1, divisor: 1 }
+    }
+
+    pub const fn categorical(source: u8, classes: u8) -> Self {
+        Self { source, kind: FIELD_KIND_CATEGORICAL, width: classes, divisor: 1 }
+    }
+}
+
+/// Ordered field layout of the model's input/output vector.
+///
+/// The vector is: player_fields for player 1, player_fields for player 2,
+/// then global_fields. Decoding walks the same layout and skips input-only
+/// sources (controller, stage).
+#[component_deserialize]
+#[derive(Default)]
+pub struct EncoderSpec {
+    pub num_player_fields: u8,
+    pub player_fields: [FieldSpec; MAX_PLAYER_FIELDS],
+    pub num_global_fields: u8,
+    pub global_fields: [FieldSpec; MAX_GLOBAL_FIELDS],
+}
+
+impl EncoderSpec {
+    /// The Melee v2 encoding from nojohns-training (24 slots per player + stage).
+    pub fn melee_v2() -> Self {
+        let fields = [
+            FieldSpec::linear(FIELD_X, 256),
+            FieldSpec::linear(FIELD_Y, 256),
+            FieldSpec::linear(FIELD_PERCENT, 4),
+            FieldSpec::linear(FIELD_SHIELD, 1),
+            FieldSpec::linear(FIELD_SPEED_AIR_X, 2),
+            FieldSpec::linear(FIELD_SPEED_Y, 2),
+            FieldSpec::linear(FIELD_SPEED_GROUND_X, 2),
+            FieldSpec::linear(FIELD_SPEED_ATTACK_X, 2),
+            FieldSpec::linear(FIELD_SPEED_ATTACK_Y, 2),
+            FieldSpec::linear(FIELD_STATE_AGE, 1),
+            FieldSpec::linear(FIELD_HITLAG, 1),
+            FieldSpec::linear(FIELD_STOCKS, 1),
+            FieldSpec::binary(FIELD_FACING),
+            FieldSpec::binary(FIELD_ON_GROUND),
+            FieldSpec::linear(FIELD_ACTION_STATE, 1),
+            FieldSpec::linear(FIELD_JUMPS_LEFT, 1),
+            FieldSpec::linear(FIELD_CHARACTER, 1),
+            FieldSpec::linear(FIELD_STICK_X, 1),
+            FieldSpec::linear(FIELD_STICK_Y, 1),
+            FieldSpec::linear(FIELD_C_STICK_X, 1),
+            FieldSpec::linear(FIELD_C_STICK_Y, 1),
+            FieldSpec::linear(FIELD_TRIGGER_L, 1),
+            FieldSpec::linear(FIELD_TRIGGER_R, 1),
+            FieldSpec::linear(FIELD_BUTTONS, 1),
+        ];
+
+        let mut spec = Self::default();
+        spec.num_player_fields = fields.len() as u8;
+        for (slot, field) in spec.player_fields.iter_mut().zip(fields) {
+            *slot = field;
+        }
+        spec.num_global_fields = 1;
+        spec.global_fields[0] = FieldSpec::linear(FIELD_STAGE, 1);
+        spec
+    }
+}
 
 /// Model manifest — the "cartridge label" of the autonomous world.
 ///
@@ -21,10 +207,10 @@ pub const NUM_LUTS: usize = 4;
 ///   - References to weight shard accounts
 ///   - Per-layer quantization parameters (scale, zero-point)
 ///   - Activation function lookup tables
-///   - Input/output encoding parameters
+///   - Input/output encoder spec (field layout + quantization)
 ///
 /// Lifecycle: Permanent on mainnet. Created once per model version.
-/// Size: ~2KB (well within single account limits).
+/// Size: ~2.4KB (well within single account limits).
 #[component]
 pub struct ModelManifest {
     /// Human-readable model name (e.g., "melee-mamba2-v1")
@@ -61,6 +247,12 @@ pub struct ModelManifest {
     /// Size of each shard in bytes
     pub shard_sizes: [u32; MAX_SHARDS],
 
+    // ── Named-tensor offset table ───────────────────────────────────────
+    // tensor_table[layer][TENSOR_*] — 16 layers × 6 tensors × 10 bytes = 960 bytes
+
+    /// Where each layer's weight tensors live
+    pub tensor_table: [[TensorRef; TENSORS_PER_LAYER]; MAX_LAYERS],
+
     // ── Per-layer quantization parameters ───────────────────────────────
     // Each layer needs scale/zero-point for requantization between layers.
     // Stored as fixed-point: actual_scale = raw_value / 65536.0
@@ -72,14 +264,20 @@ pub struct ModelManifest {
     pub layer_output_scales: [u16; MAX_LAYERS],
 
     // ── Activation LUTs ─────────────────────────────────────────────────
-    // 4 LUTs × 256 bytes = 1024 bytes total
-    // Order: SiLU, softplus, rsqrt, exp_neg
+    // 6 LUTs × 256 bytes = 1536 bytes total
+    // Order: SiLU, softplus, rsqrt, exp_neg, GELU, tanh
 
     /// Packed activation lookup tables
     pub luts: [u8; LUT_SIZE * NUM_LUTS],
 
+    /// Which tables luts carries (LUT_VERSION_*)
+    pub lut_version: u8,
+
     // ── Input/Output encoding ───────────────────────────────────────────
 
+    /// Field layout of the model's input/output vector (~200 bytes)
+    pub encoder_spec: EncoderSpec,
+
     /// Number of continuous output fields per player
     pub num_continuous: u8,
 
@@ -120,9 +318,12 @@ impl Default for ModelManifest {
             num_shards: 0,
             shard_keys: [Pubkey::default(); MAX_SHARDS],
             shard_sizes: [0u32; MAX_SHARDS],
+            tensor_table: Default::default(),
             layer_input_scales: [0u16; MAX_LAYERS],
             layer_output_scales: [0u16; MAX_LAYERS],
             luts: [0u8; LUT_SIZE * NUM_LUTS],
+            lut_version: LUT_VERSION_1,
+            encoder_spec: EncoderSpec::melee_v2(),
             num_continuous: 0,
             num_action_states: 0,
             num_binary: 0,
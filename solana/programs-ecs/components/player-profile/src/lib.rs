@@ -0,0 +1,31 @@
+use bolt_lang::*;
+
+declare_id!("2H17JVS3vhTmzHF3jYhFrziG2nsH1uQgiWm3sE4svamr");
+
+/// Player profile — one wallet's match record, carried across sessions.
+///
+/// Lives on its own entity per wallet and is updated by settle-session when
+/// a session the wallet played in settles. A zeroed profile is claimed by
+/// the first session it settles.
+///
+/// Lifecycle: Permanent on mainnet, delegated to the ephemeral rollup
+/// alongside a session for settlement.
+#[component]
+#[derive(Default)]
+pub struct PlayerProfile {
+    /// Wallet this profile belongs to
+    pub player: Pubkey,
+
+    /// Sessions settled, including draws and unplayed sessions
+    pub matches: u32,
+
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+
+    /// Last session settled into this profile
+    pub last_session: Pubkey,
+
+    /// Timestamp of the last settlement (Unix seconds)
+    pub updated_at: i64,
+}
@@ -0,0 +1,104 @@
+use bolt_lang::*;
+
+declare_id!("4hC15QFy1pNRNj6DWwQ5ukZBfmBDXKrYuVh7HeZ3W9hD");
+
+/// Character pairings tracked per wallet
+pub const MAX_MATCHUPS: usize = 8;
+
+/// Games and wins with one character against one opponent character.
+#[component_deserialize]
+#[derive(Default)]
+pub struct Matchup {
+    /// Character this wallet played
+    pub character: u8,
+    /// Opponent's character
+    pub opponent: u8,
+    pub games: u32,
+    pub wins: u32,
+}
+
+/// Player stats — one wallet's lifetime aggregates across every session it
+/// played, alongside the win/loss record in PlayerProfile.
+///
+/// Lives on its own entity per wallet and is updated by aggregate-stats
+/// from each settled session's SessionStats. A zeroed account is claimed by
+/// the first session folded into it. Unplayed sessions aren't counted.
+///
+/// Lifecycle: Permanent on mainnet, delegated to the ephemeral rollup
+/// alongside a session for settlement.
+#[component]
+#[derive(Default)]
+pub struct PlayerStats {
+    /// Wallet these stats belong to
+    pub player: Pubkey,
+
+    /// Played sessions folded in
+    pub games: u32,
+
+    /// Frames played across all games
+    pub frames: u64,
+
+    /// Damage percent dealt and taken
+    pub damage_dealt: u64,
+    pub damage_taken: u64,
+
+    /// Hits landed
+    pub hits_landed: u32,
+
+    /// Stocks taken and lost
+    pub kos: u32,
+    pub falls: u32,
+
+    /// Most-played character pairings; the first `num_matchups` are used
+    pub matchups: [Matchup; MAX_MATCHUPS],
+    pub num_matchups: u8,
+
+    /// Last session folded in
+    pub last_session: Pubkey,
+
+    /// Timestamp of the last update (Unix seconds)
+    pub updated_at: i64,
+}
+
+impl PlayerStats {
+    /// Count one game of `character` against `opponent`. A new pairing
+    /// replaces the least-played one once the table is full.
+    pub fn record_matchup(&mut self, character: u8, opponent: u8, won: bool) {
+        let used = self.num_matchups as usize;
+        let slot = match self.matchups[..used]
+            .iter()
+            .position(|m| m.character == character && m.opponent == opponent)
+        {
+            Some(slot) => slot,
+            None => {
+                let slot = if used < MAX_MATCHUPS {
+                    self.num_matchups += 1;
+                    used
+                } else {
+                    (0..MAX_MATCHUPS)
+                        .min_by_key(|&i| self.matchups[i].games)
+                        .unwrap_or(0)
+                };
+                self.matchups[slot] = Matchup {
+                    character,
+                    opponent,
+                    games: 0,
+                    wins: 0,
+                };
+                slot
+            }
+        };
+        self.matchups[slot].games += 1;
+        if won {
+            self.matchups[slot].wins += 1;
+        }
+    }
+
+    /// The most-played pairing (the earliest recorded on a tie).
+    pub fn favorite_matchup(&self) -> Option<&Matchup> {
+        self.matchups[..self.num_matchups as usize]
+            .iter()
+            .rev()
+            .max_by_key(|m| m.games)
+    }
+}
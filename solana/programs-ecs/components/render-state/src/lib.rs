@@ -0,0 +1,35 @@
+use bolt_lang::*;
+
+declare_id!("Dv3czUCp1L3fWpYifVQ4vsyBrs2aRVoAUu3JLKE7KAL4");
+
+/// One player as drawn: position, damage, animation and direction (13 bytes).
+#[component_deserialize]
+#[derive(Default)]
+pub struct RenderPlayer {
+    /// Fixed-point: actual = x / 256.0
+    pub x: i32,
+    /// Fixed-point: actual = y / 256.0
+    pub y: i32,
+    pub percent: u16,
+    pub action_state: u16,
+    /// 1 = facing right, 0 = left
+    pub facing: u8,
+}
+
+/// Render state — the slice of SessionState a renderer needs each frame.
+///
+/// SessionState carries every model output field; spectators subscribed to
+/// it over WebSocket receive all of it at 60Hz. This component holds just
+/// what gets drawn (30 bytes of fields), so spectator subscriptions move a
+/// fraction of the bytes.
+///
+/// Lifecycle: Per-session, copied from SessionState every frame by
+/// sync-render after run_inference.
+#[component]
+#[derive(Default)]
+pub struct RenderState {
+    /// Session frame these positions are from
+    pub frame: u32,
+
+    pub players: [RenderPlayer; 2],
+}
@@ -0,0 +1,48 @@
+use bolt_lang::*;
+
+declare_id!("97Yh4tUphcQHcLsLZ16cQUF7irmF6NkEYfVjFPn5wG3V");
+
+/// Outcome values (match world-model's SessionResult event)
+pub const OUTCOME_UNPLAYED: u8 = 0;
+pub const OUTCOME_WINNER: u8 = 1;
+pub const OUTCOME_DRAW: u8 = 2;
+
+/// Session result — the final standings of an ended session, written once
+/// by settle-session.
+///
+/// Together with the FrameLog it is the permanent record of the match:
+/// `replay_frames` pins how many frames the committed log covers, so a
+/// replay reader knows where the match stopped.
+///
+/// Lifecycle: Per-session, written on settle, committed to mainnet with the
+/// session's other components.
+#[component]
+#[derive(Default)]
+pub struct SessionResult {
+    /// SessionState this result settles
+    pub session: Pubkey,
+
+    /// Final frame number
+    pub frame: u32,
+
+    /// OUTCOME_*
+    pub outcome: u8,
+
+    /// Winning wallet (default unless outcome is OUTCOME_WINNER)
+    pub winner: Pubkey,
+
+    /// Stocks left per player
+    pub stocks: [u8; 2],
+
+    /// Final damage percent per player
+    pub percents: [u16; 2],
+
+    /// FrameLog committed as the replay
+    pub frame_log: Pubkey,
+
+    /// Frames written to the committed FrameLog
+    pub replay_frames: u32,
+
+    /// Timestamp of settlement (Unix seconds)
+    pub settled_at: i64,
+}
@@ -15,6 +15,32 @@ pub const STATUS_CREATED: u8 = 0;
 pub const STATUS_WAITING_PLAYERS: u8 = 1;
 pub const STATUS_ACTIVE: u8 = 2;
 pub const STATUS_ENDED: u8 = 3;
+pub const STATUS_SETTLED: u8 = 4;
+
+/// Why an Ended session ended
+pub const END_REASON_NONE: u8 = 0;
+/// anti-stall ended it; settle-session records a draw
+pub const END_REASON_STALL: u8 = 1;
+
+/// Session kinds
+/// A 1v1 match: two players, ends, settles
+pub const SESSION_KIND_MATCH: u8 = 0;
+/// A world that never ends: players claim and release the two controller
+/// slots on the fly, and stocks count lives instead of running out
+pub const SESSION_KIND_PERSISTENT: u8 = 1;
+
+/// Session tick rates (frames per second) session-lifecycle accepts
+pub const TICK_RATE_30: u8 = 30;
+pub const TICK_RATE_60: u8 = 60;
+pub const TICK_RATE_120: u8 = 120;
+/// Tick rate of a session that stores 0
+pub const DEFAULT_TICK_RATE: u8 = TICK_RATE_60;
+
+/// Melee action states the correction systems set
+pub const ACTION_FALL: u16 = 29;
+pub const ACTION_PASS: u16 = 244;
+pub const ACTION_CLIFF_CATCH: u16 = 252;
+pub const ACTION_CLIFF_WAIT: u16 = 253;
 
 /// Per-player state output from the world model.
 ///
@@ -46,7 +72,9 @@ pub struct PlayerState {
     pub state_age: u16,
     /// Hitlag frames remaining
     pub hitlag: u8,
-    /// Remaining stocks (0-4)
+    /// Remaining stocks (0-4). In a persistent world, the life the player
+    /// is on instead: 1 on claiming the slot, +1 per respawn, 0 while the
+    /// slot is open.
     pub stocks: u8,
 
     // ── Binary (classification) ─────────────────────────────────────────
@@ -70,22 +98,25 @@ pub struct PlayerState {
 /// via WebSocket to receive real-time state updates for rendering.
 ///
 /// Lifecycle: Created per session in ephemeral rollup, committed to mainnet on end.
+/// A persistent world (SESSION_KIND_PERSISTENT) stays Active for good.
 #[component]
 #[derive(Default)]
 pub struct SessionState {
-    /// Session status (Created → WaitingPlayers → Active → Ended)
+    /// Session status (Created → WaitingPlayers → Active → Ended → Settled)
     pub status: u8,
 
     /// Current frame number (monotonically increasing)
     pub frame: u32,
 
     /// Maximum frames before auto-end (0 = unlimited, 28800 = 8 minutes at 60fps)
+    /// — frames, so a different tick_rate changes the wall-clock length.
+    /// Always 0 in a persistent world.
     pub max_frames: u32,
 
-    /// Player 1 wallet public key
+    /// Player 1 wallet public key (default = open slot in a persistent world)
     pub player1: Pubkey,
 
-    /// Player 2 wallet public key
+    /// Player 2 wallet public key (default = open slot in a persistent world)
     pub player2: Pubkey,
 
     /// Stage ID (0-32, matches Melee internal stage IDs)
@@ -105,4 +136,48 @@ pub struct SessionState {
 
     /// Session seed (for deterministic initialization)
     pub seed: u64,
+
+    /// END_REASON_* (NONE unless a system ended the session)
+    pub end_reason: u8,
+
+    /// TICK_RATE_* (0 = DEFAULT_TICK_RATE); see tick_rate()
+    pub tick_rate: u8,
+
+    /// SESSION_KIND_* (set on CREATE)
+    pub kind: u8,
+}
+
+impl SessionState {
+    /// Frames per second the session runs at.
+    pub fn tick_rate(&self) -> u32 {
+        match self.tick_rate {
+            0 => DEFAULT_TICK_RATE as u32,
+            r => r as u32,
+        }
+    }
+
+    /// Whether this is a persistent world rather than a match.
+    pub fn is_persistent(&self) -> bool {
+        self.kind == SESSION_KIND_PERSISTENT
+    }
+
+    /// Wallet holding each controller slot.
+    pub fn slot_owners(&self) -> [Pubkey; NUM_PLAYERS] {
+        [self.player1, self.player2]
+    }
+
+    /// Slot `player` holds, if any.
+    pub fn slot_of(&self, player: &Pubkey) -> Option<usize> {
+        if *player == Pubkey::default() {
+            return None;
+        }
+        self.slot_owners().iter().position(|owner| owner == player)
+    }
+
+    /// Whether a persistent world's slot is open: nobody's controller is
+    /// plugged in, so there's no input to wait for and no player to step.
+    /// Always false in a match.
+    pub fn slot_open(&self, slot: usize) -> bool {
+        self.is_persistent() && self.slot_owners()[slot] == Pubkey::default()
+    }
 }
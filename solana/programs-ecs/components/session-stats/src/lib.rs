@@ -0,0 +1,59 @@
+use bolt_lang::*;
+
+declare_id!("Ae9VHs4Ayui2tF9pxxCy7coqGoAySfhJS6JmbAfLPiT6");
+
+/// Hits a run needs to count as a combo
+pub const MIN_COMBO_HITS: u16 = 2;
+
+/// Session stats — what each player did this session, per player slot.
+///
+/// Damage and hits are the values resolve-damage settled, not the model's
+/// raw percents. A KO is credited to the opponent of the player who lost
+/// the stock.
+///
+/// Combo fields are indexed by the attacker and kept by combo-tracker: a
+/// combo is the run of hits landed while the victim never got back to an
+/// actionable state.
+///
+/// Lifecycle: Per-session, written every frame by resolve-damage and
+/// combo-tracker, folded into both players' PlayerStats by aggregate-stats
+/// after settlement.
+#[component]
+#[derive(Default)]
+pub struct SessionStats {
+    /// Damage percent dealt per player
+    pub damage_dealt: [u32; 2],
+
+    /// Hits landed per player
+    pub hits_landed: [u32; 2],
+
+    /// Stocks taken per player
+    pub kos: [u8; 2],
+
+    /// Whether aggregate-stats has folded this session into PlayerStats
+    pub folded: bool,
+
+    /// Session frame combo-tracker last checked
+    pub combo_frame: u32,
+
+    /// Each player's percent as combo-tracker last saw it
+    pub combo_percents: [u16; 2],
+
+    /// Hits in each player's running combo (0 when none is running)
+    pub combo_hits: [u16; 2],
+
+    /// Damage dealt in each player's running combo
+    pub combo_damage: [u16; 2],
+
+    /// Frame each player's running combo started on
+    pub combo_started: [u32; 2],
+
+    /// Combos of at least MIN_COMBO_HITS hits landed per player
+    pub combos: [u32; 2],
+
+    /// Most hits in one combo per player
+    pub longest_combo: [u16; 2],
+
+    /// Most damage in one combo per player
+    pub best_combo_damage: [u16; 2],
+}
@@ -0,0 +1,161 @@
+use bolt_lang::*;
+
+declare_id!("HFQnLTeVuMxPGVU1QDc86xtesaheQFYxhm2QZcmqck5r");
+
+/// Most pass-through platforms a stage can have
+pub const MAX_PLATFORMS: usize = 3;
+
+/// Where a player hangs relative to the ledge they hold
+pub const LEDGE_HANG_X: i32 = 3 * 256;
+pub const LEDGE_HANG_Y: i32 = 10 * 256;
+
+/// Legal stage IDs (matching the stage vocabulary the model trains on)
+pub const STAGE_FOUNTAIN_OF_DREAMS: u8 = 2;
+pub const STAGE_POKEMON_STADIUM: u8 = 3;
+pub const STAGE_YOSHIS_STORY: u8 = 8;
+pub const STAGE_DREAM_LAND: u8 = 28;
+pub const STAGE_BATTLEFIELD: u8 = 31;
+pub const STAGE_FINAL_DESTINATION: u8 = 32;
+
+/// A pass-through platform: players land on it from above and drop or
+/// jump through it.
+#[component_deserialize]
+#[derive(Default)]
+pub struct Platform {
+    /// Left edge, fixed-point game units × 256
+    pub left: i32,
+    /// Right edge, fixed-point game units × 256
+    pub right: i32,
+    /// Surface height, fixed-point game units × 256
+    pub y: i32,
+}
+
+/// Stage geometry — the surfaces the collision system lands players on.
+///
+/// All coordinates share PlayerState's fixed-point (game units × 256).
+/// The main platform is solid, and its two corners are the ledges.
+/// Crossing a blast zone costs a stock.
+///
+/// Lifecycle: Per-session, loaded from the built-in legal stage table for
+/// the session's stage on the first collision pass.
+#[component]
+#[derive(Default)]
+pub struct StageGeometry {
+    /// Stage ID this geometry describes
+    pub stage: u8,
+
+    /// Whether the fields below hold a stage (a zeroed account doesn't)
+    pub loaded: bool,
+
+    /// Main platform surface height
+    pub ground_y: i32,
+
+    /// Main platform edges (and ledge positions)
+    pub ground_left: i32,
+    pub ground_right: i32,
+
+    /// Side and top platforms; the first `num_platforms` are used
+    pub platforms: [Platform; MAX_PLATFORMS],
+    pub num_platforms: u8,
+
+    /// Blast zones
+    pub blast_left: i32,
+    pub blast_right: i32,
+    pub blast_top: i32,
+    pub blast_bottom: i32,
+}
+
+/// Game units in hundredths → fixed-point × 256
+const fn fx(centi: i32) -> i32 {
+    centi * 256 / 100
+}
+
+/// One legal stage, in hundredths of a game unit: main platform half
+/// width, platforms (left, right, y), and blast zones (left, right, top,
+/// bottom). Ground is at y = 0 on every legal stage.
+struct Layout {
+    stage: u8,
+    half_width: i32,
+    platforms: &'static [(i32, i32, i32)],
+    blast: (i32, i32, i32, i32),
+}
+
+const LEGAL_STAGES: [Layout; 6] = [
+    Layout {
+        stage: STAGE_FINAL_DESTINATION,
+        half_width: 8557,
+        platforms: &[],
+        blast: (-24600, 24600, 18800, -14000),
+    },
+    Layout {
+        stage: STAGE_BATTLEFIELD,
+        half_width: 6840,
+        platforms: &[(-5760, -2000, 2720), (2000, 5760, 2720), (-1880, 1880, 5440)],
+        blast: (-22400, 22400, 20000, -10880),
+    },
+    Layout {
+        stage: STAGE_POKEMON_STADIUM,
+        half_width: 8775,
+        platforms: &[(-5500, -2500, 2500), (2500, 5500, 2500)],
+        blast: (-23000, 23000, 20000, -11100),
+    },
+    Layout {
+        stage: STAGE_YOSHIS_STORY,
+        half_width: 5600,
+        platforms: &[(-6000, -2800, 2345), (2800, 6000, 2345), (-1575, 1575, 4200)],
+        blast: (-17570, 17360, 16800, -9100),
+    },
+    Layout {
+        stage: STAGE_DREAM_LAND,
+        half_width: 7727,
+        platforms: &[(-6139, -3173, 3014), (3173, 6303, 3014), (-1902, 1902, 5143)],
+        blast: (-25500, 25500, 25000, -12300),
+    },
+    Layout {
+        stage: STAGE_FOUNTAIN_OF_DREAMS,
+        half_width: 6335,
+        platforms: &[(-5050, -2050, 2720), (2050, 5050, 2720), (-1500, 1500, 4275)],
+        blast: (-19875, 19875, 20250, -14625),
+    },
+];
+
+impl StageGeometry {
+    /// Fill in `stage` from the legal stage table. Returns false (and
+    /// leaves the geometry untouched) for stages not in the table.
+    pub fn load_legal(&mut self, stage: u8) -> bool {
+        let Some(layout) = LEGAL_STAGES.iter().find(|l| l.stage == stage) else {
+            return false;
+        };
+        self.stage = stage;
+        self.ground_y = 0;
+        self.ground_left = fx(-layout.half_width);
+        self.ground_right = fx(layout.half_width);
+        self.platforms = Default::default();
+        for (slot, &(left, right, y)) in layout.platforms.iter().enumerate() {
+            self.platforms[slot] = Platform {
+                left: fx(left),
+                right: fx(right),
+                y: fx(y),
+            };
+        }
+        self.num_platforms = layout.platforms.len() as u8;
+        let (left, right, top, bottom) = layout.blast;
+        self.blast_left = fx(left);
+        self.blast_right = fx(right);
+        self.blast_top = fx(top);
+        self.blast_bottom = fx(bottom);
+        self.loaded = true;
+        true
+    }
+
+    /// Hang position and facing (1 = right) at the left or right ledge.
+    /// A player hangs just outside the corner, facing the stage.
+    pub fn ledge_hang(&self, right: bool) -> (i32, i32, u8) {
+        let y = self.ground_y - LEDGE_HANG_Y;
+        if right {
+            (self.ground_right + LEDGE_HANG_X, y, 0)
+        } else {
+            (self.ground_left - LEDGE_HANG_X, y, 1)
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use bolt_lang::*;
+
+declare_id!("Dj2DAP6C26k9XEDRRnsN56tynPjrcHLytFFGwrSP34uP");
+
+/// What anti-stall does once players have stalled for `stall_seconds`
+pub const CONSEQUENCE_WARN: u8 = 0;
+pub const CONSEQUENCE_SUDDEN_DEATH: u8 = 1;
+pub const CONSEQUENCE_END_DRAW: u8 = 2;
+
+/// Used until the session configures its own
+pub const DEFAULT_STALL_SECONDS: u16 = 30;
+pub const DEFAULT_MIN_DISTANCE: u16 = 80;
+
+/// Stall monitor — a session's anti-stall rules and how long the players
+/// have currently been stalling.
+///
+/// A zeroed monitor runs with the defaults above and CONSEQUENCE_WARN.
+///
+/// Lifecycle: Per-session; configured before the match starts, then
+/// updated every frame by anti-stall after run_inference.
+#[component]
+#[derive(Default)]
+pub struct StallMonitor {
+    /// Whether the fields below were set (otherwise defaults apply)
+    pub configured: bool,
+
+    /// Seconds of continuous stalling before the consequence
+    pub stall_seconds: u16,
+
+    /// Distance (game units) at which players count as camping apart
+    pub min_distance: u16,
+
+    /// CONSEQUENCE_*
+    pub consequence: u8,
+
+    /// Session frame last checked
+    pub frame: u32,
+
+    /// Frames the current stall has lasted
+    pub stall_frames: u32,
+
+    /// Percents at the last check (a change means someone got hit)
+    pub percents: [u16; 2],
+
+    /// Warnings issued this session
+    pub warnings: u16,
+
+    /// Set once stalling triggers sudden death; stays set for the session
+    pub sudden_death: bool,
+}
+
+impl StallMonitor {
+    /// Stall limit in frames at `tick_rate` frames per second, falling back
+    /// to the default when unconfigured.
+    pub fn limit_frames(&self, tick_rate: u32) -> u32 {
+        let seconds = if self.configured {
+            self.stall_seconds
+        } else {
+            DEFAULT_STALL_SECONDS
+        };
+        seconds as u32 * tick_rate
+    }
+
+    /// Camping distance in fixed-point (game units × 256).
+    pub fn min_distance_fixed(&self) -> i32 {
+        let units = if self.configured {
+            self.min_distance
+        } else {
+            DEFAULT_MIN_DISTANCE
+        };
+        units as i32 * 256
+    }
+}
@@ -0,0 +1,46 @@
+use bolt_lang::*;
+
+declare_id!("ChAHZMdVwTQFogf87m9QMKucAQwMTRPmsAhzssL6jHC7");
+
+/// Encoding tag of exported tuples: the v2 wire format the crank and
+/// nojohns-training read (PlayerState 32 bytes, ControllerInput 8 bytes,
+/// little-endian, field order as in the structs)
+pub const ENCODING_V2: u8 = 2;
+
+/// Packed PlayerState size in the v2 encoding
+pub const PLAYER_STATE_SIZE: usize = 32;
+
+/// Packed ControllerInput size in the v2 encoding
+pub const CONTROLLER_INPUT_SIZE: usize = 8;
+
+/// Training export — a session's opt-in to training-data export and the
+/// last frame it exported.
+///
+/// Export is off unless the session enables it. When enabled,
+/// export-training emits one (state, input, next state) tuple per frame;
+/// `state` holds the packed players of the last exported frame, which
+/// become the next tuple's starting state.
+///
+/// Lifecycle: Per-session; configured before the match starts, then
+/// updated every frame by export-training after the frame's other systems.
+#[component]
+#[derive(Default)]
+pub struct TrainingExport {
+    /// Whether the session set the opt-in below
+    pub configured: bool,
+
+    /// Tuples are exported for this session
+    pub enabled: bool,
+
+    /// Whether `state` holds a frame yet
+    pub has_state: bool,
+
+    /// Session frame `state` was packed at
+    pub frame: u32,
+
+    /// Both players at `frame`, packed in the v2 encoding
+    pub state: [[u8; PLAYER_STATE_SIZE]; 2],
+
+    /// Tuples exported this session
+    pub exported: u32,
+}
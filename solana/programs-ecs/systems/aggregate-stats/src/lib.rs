@@ -0,0 +1,114 @@
+use bolt_lang::*;
+use player_stats::PlayerStats;
+use session_result::{SessionResult, OUTCOME_UNPLAYED, OUTCOME_WINNER};
+use session_state::{SessionState, STATUS_SETTLED};
+use session_stats::SessionStats;
+
+declare_id!("HxaNZGsEnGwNqXJPpVEFJcuXnDmtNZHibYsvnZdTYFwc");
+
+#[error_code]
+pub enum StatsError {
+    #[msg("Session has not been settled")]
+    SessionNotSettled,
+    #[msg("Session stats are already folded in")]
+    AlreadyFolded,
+    #[msg("Player stats belong to a different wallet")]
+    StatsMismatch,
+}
+
+/// Aggregate stats system — runs once per session, after settle-session.
+///
+/// Folds the session's SessionStats into each player's lifetime
+/// PlayerStats: games and frames played, damage dealt and taken, hits,
+/// KOs and falls, and the character matchup table (won per the settled
+/// SessionResult). SessionStats is marked folded so a session counts once.
+/// Unplayed sessions are marked folded without touching PlayerStats.
+///
+/// Like settle-session's profiles, both PlayerStats are delegated with the
+/// session and committed back by the client.
+///
+/// Accounts read/written:
+///   - SessionState: players, characters, final frame (read)
+///   - SessionResult: outcome and winner (read)
+///   - SessionStats: per-session tallies; marked folded
+///   - PlayerStats × 2: lifetime aggregates per wallet
+#[system]
+pub mod aggregate_stats {
+
+    pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session_key = ctx.accounts.session_state.key();
+        let session = &ctx.accounts.session_state;
+        let result = &ctx.accounts.session_result;
+        let stats = &mut ctx.accounts.session_stats;
+
+        require!(
+            session.status == STATUS_SETTLED,
+            StatsError::SessionNotSettled
+        );
+        require!(!stats.folded, StatsError::AlreadyFolded);
+
+        if result.outcome != OUTCOME_UNPLAYED {
+            let now = Clock::get()?.unix_timestamp;
+            let players = [session.player1, session.player2];
+            let lifetime = [
+                &mut ctx.accounts.player1_stats,
+                &mut ctx.accounts.player2_stats,
+            ];
+            for (slot, player_stats) in lifetime.into_iter().enumerate() {
+                if player_stats.player == Pubkey::default() {
+                    player_stats.player = players[slot];
+                }
+                require!(
+                    player_stats.player == players[slot],
+                    StatsError::StatsMismatch
+                );
+                let won = result.outcome == OUTCOME_WINNER && result.winner == players[slot];
+                fold_session(player_stats, session, stats, slot, won);
+                player_stats.last_session = session_key;
+                player_stats.updated_at = now;
+            }
+        }
+        stats.folded = true;
+
+        msg!(
+            "Stats folded: damage {}/{}, KOs {}/{}",
+            stats.damage_dealt[0],
+            stats.damage_dealt[1],
+            stats.kos[0],
+            stats.kos[1]
+        );
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub session_result: SessionResult,
+        pub session_stats: SessionStats,
+        pub player1_stats: PlayerStats,
+        pub player2_stats: PlayerStats,
+    }
+}
+
+/// Add player `slot`'s side of one played session to their lifetime stats.
+pub fn fold_session(
+    lifetime: &mut PlayerStats,
+    session: &SessionState,
+    stats: &SessionStats,
+    slot: usize,
+    won: bool,
+) {
+    let opponent = 1 - slot;
+    lifetime.games += 1;
+    lifetime.frames += session.frame as u64;
+    lifetime.damage_dealt += stats.damage_dealt[slot] as u64;
+    lifetime.damage_taken += stats.damage_dealt[opponent] as u64;
+    lifetime.hits_landed += stats.hits_landed[slot];
+    lifetime.kos += stats.kos[slot] as u32;
+    lifetime.falls += stats.kos[opponent] as u32;
+    lifetime.record_matchup(
+        session.players[slot].character,
+        session.players[opponent].character,
+        won,
+    );
+}
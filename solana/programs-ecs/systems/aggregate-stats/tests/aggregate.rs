@@ -0,0 +1,78 @@
+//! Folding session stats into lifetime PlayerStats, and the matchup table.
+
+use aggregate_stats::fold_session;
+use player_stats::{PlayerStats, MAX_MATCHUPS};
+use session_state::SessionState;
+use session_stats::SessionStats;
+
+fn session(frame: u32, characters: [u8; 2]) -> SessionState {
+    let mut session = SessionState {
+        frame,
+        ..Default::default()
+    };
+    session.players[0].character = characters[0];
+    session.players[1].character = characters[1];
+    session
+}
+
+#[test]
+fn test_fold_session_sides() {
+    // Fox (1) vs Marth (18): player 1 dealt 180% and took two stocks
+    let session = session(3600, [1, 18]);
+    let stats = SessionStats {
+        damage_dealt: [180, 95],
+        hits_landed: [40, 21],
+        kos: [2, 1],
+        ..Default::default()
+    };
+    let (mut p1, mut p2) = (PlayerStats::default(), PlayerStats::default());
+    fold_session(&mut p1, &session, &stats, 0, true);
+    fold_session(&mut p2, &session, &stats, 1, false);
+
+    assert_eq!((p1.games, p1.frames), (1, 3600));
+    assert_eq!((p1.damage_dealt, p1.damage_taken), (180, 95));
+    assert_eq!((p2.damage_dealt, p2.damage_taken), (95, 180));
+    assert_eq!((p1.kos, p1.falls, p1.hits_landed), (2, 1, 40));
+    assert_eq!((p2.kos, p2.falls, p2.hits_landed), (1, 2, 21));
+
+    let m = p1.favorite_matchup().unwrap();
+    assert_eq!((m.character, m.opponent, m.games, m.wins), (1, 18, 1, 1));
+    let m = p2.favorite_matchup().unwrap();
+    assert_eq!((m.character, m.opponent, m.games, m.wins), (18, 1, 1, 0));
+
+    // A second game accumulates
+    fold_session(&mut p1, &session, &stats, 0, false);
+    assert_eq!((p1.games, p1.damage_dealt), (2, 360));
+    assert_eq!(p1.num_matchups, 1);
+    assert_eq!((p1.matchups[0].games, p1.matchups[0].wins), (2, 1));
+}
+
+#[test]
+fn test_matchup_table() {
+    let mut stats = PlayerStats::default();
+    assert!(stats.favorite_matchup().is_none());
+
+    // Fill the table; opponent 3 gets played most
+    for opponent in 0..MAX_MATCHUPS as u8 {
+        stats.record_matchup(1, opponent, false);
+    }
+    stats.record_matchup(1, 3, true);
+    stats.record_matchup(1, 3, true);
+    let m = stats.favorite_matchup().unwrap();
+    assert_eq!((m.opponent, m.games, m.wins), (3, 3, 2));
+
+    // A new pairing replaces a least-played one, never the favorite
+    stats.record_matchup(2, 20, false);
+    assert_eq!(stats.num_matchups as usize, MAX_MATCHUPS);
+    assert!(stats
+        .matchups
+        .iter()
+        .any(|m| m.character == 2 && m.opponent == 20 && m.games == 1));
+    assert_eq!(stats.favorite_matchup().unwrap().opponent, 3);
+
+    // Ties go to the earliest recorded pairing
+    let mut stats = PlayerStats::default();
+    stats.record_matchup(5, 6, false);
+    stats.record_matchup(5, 7, false);
+    assert_eq!(stats.favorite_matchup().unwrap().opponent, 6);
+}
@@ -0,0 +1,187 @@
+use bolt_lang::*;
+use session_state::{
+    PlayerState, SessionState, END_REASON_STALL, NUM_PLAYERS, STATUS_ACTIVE, STATUS_CREATED,
+    STATUS_ENDED, STATUS_WAITING_PLAYERS,
+};
+use stall_monitor::{
+    StallMonitor, CONSEQUENCE_END_DRAW, CONSEQUENCE_SUDDEN_DEATH, CONSEQUENCE_WARN,
+};
+
+declare_id!("B6weNxhYJEuiHR7AZcvGWYqWB9abHscdxKRdzfydw72r");
+
+/// Fixed-point speed (game units × 256 per frame) at or under which a
+/// player counts as standing still
+pub const IDLE_SPEED: i16 = 64;
+
+#[error_code]
+pub enum StallError {
+    #[msg("Session is not active")]
+    SessionNotActive,
+    #[msg("This frame was already checked")]
+    AlreadyChecked,
+    #[msg("Anti-stall rules are fixed once the session starts")]
+    ConfigLocked,
+    #[msg("Stall time must be nonzero and the consequence known")]
+    InvalidConfig,
+}
+
+/// Emitted when players have stalled for the session's stall limit.
+#[event]
+pub struct StallDetected {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// Frames the stall lasted
+    pub stall_frames: u32,
+    /// CONSEQUENCE_* applied
+    pub consequence: u8,
+}
+
+/// Anti-stall system — runs after run_inference each frame.
+///
+/// A frame is stalling when neither player's percent changed and the
+/// players are either camping at least `min_distance` apart or both
+/// standing still. Once that lasts `stall_seconds`, the session's
+/// consequence applies and the count starts over:
+///   - CONSEQUENCE_WARN: a StallDetected event, counted in `warnings`
+///   - CONSEQUENCE_SUDDEN_DEATH: sets the monitor's `sudden_death` flag
+///   - CONSEQUENCE_END_DRAW: ends the session; settle-session records a draw
+///     (a persistent world never ends, so there it only counts a warning)
+///
+/// Called with `configure` set (before the session goes Active), it sets
+/// the rules instead.
+///
+/// Accounts read/written:
+///   - SessionState: players (read); status and end reason on END_DRAW
+///   - StallMonitor: rules, running stall count
+#[system]
+pub mod anti_stall {
+
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        let session_key = ctx.accounts.session_state.key();
+        let session = &mut ctx.accounts.session_state;
+        let monitor = &mut ctx.accounts.stall_monitor;
+
+        if args.configure {
+            configure(session, monitor, &args)?;
+        } else {
+            check(session_key, session, monitor)?;
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub stall_monitor: StallMonitor,
+    }
+
+    #[arguments]
+    pub struct Args {
+        /// Set the rules below instead of checking a frame
+        pub configure: bool,
+        /// Seconds of stalling before the consequence — only used to configure
+        pub stall_seconds: u16,
+        /// Camping distance in game units — only used to configure
+        pub min_distance: u16,
+        /// CONSEQUENCE_* — only used to configure
+        pub consequence: u8,
+    }
+}
+
+fn configure(
+    session: &SessionState,
+    monitor: &mut StallMonitor,
+    args: &anti_stall::Args,
+) -> Result<()> {
+    require!(
+        session.status == STATUS_CREATED || session.status == STATUS_WAITING_PLAYERS,
+        StallError::ConfigLocked
+    );
+    require!(
+        args.stall_seconds > 0 && args.consequence <= CONSEQUENCE_END_DRAW,
+        StallError::InvalidConfig
+    );
+
+    monitor.configured = true;
+    monitor.stall_seconds = args.stall_seconds;
+    monitor.min_distance = args.min_distance;
+    monitor.consequence = args.consequence;
+    msg!(
+        "Anti-stall: {}s, {} units apart, consequence {}",
+        args.stall_seconds,
+        args.min_distance,
+        args.consequence
+    );
+    Ok(())
+}
+
+fn check(
+    session_key: Pubkey,
+    session: &mut SessionState,
+    monitor: &mut StallMonitor,
+) -> Result<()> {
+    require!(
+        session.status == STATUS_ACTIVE,
+        StallError::SessionNotActive
+    );
+    require!(
+        session.frame > monitor.frame,
+        StallError::AlreadyChecked
+    );
+
+    // The first check only records a baseline
+    let stalling = monitor.frame != 0
+        && is_stalling(&session.players, monitor.percents, monitor.min_distance_fixed());
+    monitor.stall_frames = if stalling {
+        monitor.stall_frames + (session.frame - monitor.frame)
+    } else {
+        0
+    };
+    monitor.frame = session.frame;
+    monitor.percents = [session.players[0].percent, session.players[1].percent];
+
+    if monitor.stall_frames < monitor.limit_frames(session.tick_rate()) {
+        return Ok(());
+    }
+
+    emit!(StallDetected {
+        session: session_key,
+        frame: session.frame,
+        stall_frames: monitor.stall_frames,
+        consequence: monitor.consequence,
+    });
+    monitor.stall_frames = 0;
+    match monitor.consequence {
+        CONSEQUENCE_WARN => monitor.warnings += 1,
+        CONSEQUENCE_SUDDEN_DEATH => monitor.sudden_death = true,
+        // A persistent world can't end, so the most it gets is a warning
+        CONSEQUENCE_END_DRAW if session.is_persistent() => monitor.warnings += 1,
+        CONSEQUENCE_END_DRAW => {
+            session.status = STATUS_ENDED;
+            session.end_reason = END_REASON_STALL;
+            msg!("Session ended for stalling at frame {}", session.frame);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Whether this frame counts toward a stall: no percent changed since
+/// `percents`, and the players are at least `min_distance` apart or both
+/// standing still.
+pub fn is_stalling(
+    players: &[PlayerState; NUM_PLAYERS],
+    percents: [u16; NUM_PLAYERS],
+    min_distance: i32,
+) -> bool {
+    let unhurt = players.iter().zip(percents).all(|(p, prev)| p.percent == prev);
+    let apart = (players[0].x - players[1].x).abs() >= min_distance;
+    unhurt && (apart || players.iter().all(is_idle))
+}
+
+fn is_idle(p: &PlayerState) -> bool {
+    [p.speed_air_x, p.speed_ground_x, p.speed_y]
+        .iter()
+        .all(|v| v.unsigned_abs() <= IDLE_SPEED as u16)
+}
@@ -0,0 +1,66 @@
+//! Which frames count toward a stall, and the monitor's limits.
+
+use anti_stall::{is_stalling, IDLE_SPEED};
+use session_state::{PlayerState, SessionState, TICK_RATE_120, TICK_RATE_60};
+use stall_monitor::{StallMonitor, DEFAULT_MIN_DISTANCE, DEFAULT_STALL_SECONDS};
+
+fn players(x: [i32; 2], percents: [u16; 2]) -> [PlayerState; 2] {
+    [0, 1].map(|i| PlayerState {
+        x: x[i],
+        percent: percents[i],
+        stocks: 4,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_camping_apart() {
+    let min_distance = 80 * 256;
+    let mut p = players([-60 * 256, 60 * 256], [20, 35]);
+    p[0].speed_ground_x = 400;
+    assert!(is_stalling(&p, [20, 35], min_distance));
+
+    // A hit breaks it
+    assert!(!is_stalling(&p, [20, 30], min_distance));
+}
+
+#[test]
+fn test_idle_up_close() {
+    let min_distance = 80 * 256;
+    let mut p = players([-10 * 256, 10 * 256], [0, 0]);
+    p[1].speed_y = -IDLE_SPEED;
+    assert!(is_stalling(&p, [0, 0], min_distance));
+
+    // Close together and moving is neutral play
+    p[1].speed_ground_x = 300;
+    assert!(!is_stalling(&p, [0, 0], min_distance));
+}
+
+#[test]
+fn test_monitor_defaults() {
+    let mut monitor = StallMonitor::default();
+    assert_eq!(monitor.limit_frames(60), DEFAULT_STALL_SECONDS as u32 * 60);
+    assert_eq!(monitor.min_distance_fixed(), DEFAULT_MIN_DISTANCE as i32 * 256);
+
+    monitor.configured = true;
+    monitor.stall_seconds = 10;
+    monitor.min_distance = 50;
+    assert_eq!(monitor.limit_frames(60), 600);
+    assert_eq!(monitor.min_distance_fixed(), 50 * 256);
+}
+
+#[test]
+fn test_limit_follows_tick_rate() {
+    let monitor = StallMonitor {
+        configured: true,
+        stall_seconds: 10,
+        ..Default::default()
+    };
+    let session = |tick_rate| SessionState {
+        tick_rate,
+        ..Default::default()
+    };
+    assert_eq!(monitor.limit_frames(session(0).tick_rate()), 600);
+    assert_eq!(monitor.limit_frames(session(TICK_RATE_60).tick_rate()), 600);
+    assert_eq!(monitor.limit_frames(session(TICK_RATE_120).tick_rate()), 1200);
+}
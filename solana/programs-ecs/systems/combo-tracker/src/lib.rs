@@ -0,0 +1,178 @@
+use bolt_lang::*;
+use session_state::{PlayerState, SessionState, NUM_PLAYERS, STATUS_ACTIVE};
+use session_stats::{SessionStats, MIN_COMBO_HITS};
+
+declare_id!("CN6qNVQGxn6iMMVbRvgBD5Z4c2VqQYhTKz9rSX6cBmgK");
+
+/// Melee action states a hit victim can't act out of: tumble, the
+/// DamageHi1..DamageFlyRoll hitstun block, knockdowns and being held in a
+/// grab
+pub const ACTION_DAMAGE_FALL: u16 = 38;
+pub const ACTION_DAMAGE_START: u16 = 75;
+pub const ACTION_DAMAGE_END: u16 = 91;
+pub const ACTION_DOWN_START: u16 = 183;
+pub const ACTION_DOWN_END: u16 = 198;
+pub const ACTION_CAPTURE_START: u16 = 223;
+pub const ACTION_CAPTURE_END: u16 = 232;
+
+#[error_code]
+pub enum ComboError {
+    #[msg("Session is not active")]
+    SessionNotActive,
+    #[msg("This frame was already tracked")]
+    AlreadyTracked,
+}
+
+/// Emitted when a combo of at least MIN_COMBO_HITS hits ends.
+#[event]
+pub struct ComboEnded {
+    pub session: Pubkey,
+    pub frame: u32,
+    /// Player slot that landed the combo
+    pub attacker: u8,
+    /// Hits in the combo
+    pub hits: u16,
+    /// Damage percent the combo dealt
+    pub damage: u16,
+    /// Frame the combo's first hit landed on
+    pub started: u32,
+    /// Whether the combo ended in a KO
+    pub ko: bool,
+}
+
+/// A finished combo, as `track` reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Combo {
+    pub hits: u16,
+    pub damage: u16,
+    pub started: u32,
+    pub ko: bool,
+}
+
+/// Combo tracker system — runs after resolve-damage each frame.
+///
+/// Reads hits off resolve-damage's settled percents: a rise is a hit that
+/// extends the attacker's running combo. The combo ends when the victim
+/// is actionable again (out of hitlag and hitstun) or loses the stock.
+/// Runs of MIN_COMBO_HITS or more count as combos: they're tallied in
+/// SessionStats and a ComboEnded event marks them for highlights and
+/// spectator overlays.
+///
+/// Accounts read/written:
+///   - SessionState: players' percent, hitlag and action state (read)
+///   - SessionStats: running and best combos per player
+#[system]
+pub mod combo_tracker {
+
+    pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session_key = ctx.accounts.session_state.key();
+        let session = &ctx.accounts.session_state;
+        let stats = &mut ctx.accounts.session_stats;
+
+        require!(
+            session.status == STATUS_ACTIVE,
+            ComboError::SessionNotActive
+        );
+        require!(
+            session.frame > stats.combo_frame,
+            ComboError::AlreadyTracked
+        );
+
+        let ended = track(stats, &session.players, session.frame);
+        for (attacker, combo) in ended.iter().enumerate() {
+            let Some(combo) = combo else { continue };
+            emit!(ComboEnded {
+                session: session_key,
+                frame: session.frame,
+                attacker: attacker as u8,
+                hits: combo.hits,
+                damage: combo.damage,
+                started: combo.started,
+                ko: combo.ko,
+            });
+            msg!(
+                "Combo: player {} lands {} hits for {}%",
+                attacker + 1,
+                combo.hits,
+                combo.damage
+            );
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub session_stats: SessionStats,
+    }
+}
+
+/// Advance the running combos to `frame`, returning the combos that ended
+/// on it by attacker slot. The first call only records a baseline.
+///
+/// A percent drop is a lost stock: resolve-damage resets percent only
+/// then.
+pub fn track(
+    stats: &mut SessionStats,
+    players: &[PlayerState; NUM_PLAYERS],
+    frame: u32,
+) -> [Option<Combo>; NUM_PLAYERS] {
+    let mut ended = [None; NUM_PLAYERS];
+    let baseline = stats.combo_frame == 0;
+    stats.combo_frame = frame;
+
+    for (victim, p) in players.iter().enumerate() {
+        let attacker = 1 - victim;
+        let prev = stats.combo_percents[victim];
+        stats.combo_percents[victim] = p.percent;
+        if baseline {
+            continue;
+        }
+
+        if p.percent > prev {
+            if stats.combo_hits[attacker] == 0 {
+                stats.combo_started[attacker] = frame;
+            }
+            stats.combo_hits[attacker] += 1;
+            stats.combo_damage[attacker] =
+                stats.combo_damage[attacker].saturating_add(p.percent - prev);
+        } else if p.percent < prev {
+            ended[attacker] = end_combo(stats, attacker, true);
+        } else if is_actionable(p) {
+            ended[attacker] = end_combo(stats, attacker, false);
+        }
+    }
+    ended
+}
+
+/// Whether a player is free to act: out of hitlag and not in a hitstun,
+/// knockdown or grabbed state.
+pub fn is_actionable(p: &PlayerState) -> bool {
+    let stunned = p.action_state == ACTION_DAMAGE_FALL
+        || (ACTION_DAMAGE_START..=ACTION_DAMAGE_END).contains(&p.action_state)
+        || (ACTION_DOWN_START..=ACTION_DOWN_END).contains(&p.action_state)
+        || (ACTION_CAPTURE_START..=ACTION_CAPTURE_END).contains(&p.action_state);
+    p.hitlag == 0 && !stunned
+}
+
+/// Close the attacker's running combo, counting it if it was long enough.
+fn end_combo(stats: &mut SessionStats, attacker: usize, ko: bool) -> Option<Combo> {
+    let combo = Combo {
+        hits: stats.combo_hits[attacker],
+        damage: stats.combo_damage[attacker],
+        started: stats.combo_started[attacker],
+        ko,
+    };
+    stats.combo_hits[attacker] = 0;
+    stats.combo_damage[attacker] = 0;
+    stats.combo_started[attacker] = 0;
+    if combo.hits < MIN_COMBO_HITS {
+        return None;
+    }
+
+    stats.combos[attacker] += 1;
+    stats.longest_combo[attacker] = stats.longest_combo[attacker].max(combo.hits);
+    stats.best_combo_damage[attacker] = stats.best_combo_damage[attacker].max(combo.damage);
+    Some(combo)
+}
@@ -0,0 +1,79 @@
+//! Which hit runs count as combos, and what they tally.
+
+use combo_tracker::{is_actionable, track, Combo, ACTION_DAMAGE_START};
+use session_state::PlayerState;
+use session_stats::SessionStats;
+
+/// Player 2 at `percent`, in hitstun unless `free`
+fn players(percent: u16, free: bool) -> [PlayerState; 2] {
+    let mut p = [PlayerState::default(), PlayerState::default()];
+    p[1].percent = percent;
+    if !free {
+        p[1].action_state = ACTION_DAMAGE_START;
+    }
+    p
+}
+
+#[test]
+fn test_actionable() {
+    let mut p = PlayerState::default();
+    assert!(is_actionable(&p));
+    p.hitlag = 3;
+    assert!(!is_actionable(&p));
+    p.hitlag = 0;
+    p.action_state = ACTION_DAMAGE_START + 4;
+    assert!(!is_actionable(&p));
+}
+
+#[test]
+fn test_combo_ends_when_victim_acts() {
+    let mut stats = SessionStats::default();
+    assert_eq!(track(&mut stats, &players(0, true), 1), [None, None]);
+
+    // Three hits with no window to act between them
+    track(&mut stats, &players(12, false), 2);
+    track(&mut stats, &players(12, false), 3);
+    track(&mut stats, &players(20, false), 4);
+    track(&mut stats, &players(33, false), 5);
+    assert_eq!(stats.combo_hits[0], 3);
+    assert_eq!(stats.combo_damage[0], 33);
+
+    let ended = track(&mut stats, &players(33, true), 6);
+    assert_eq!(
+        ended[0],
+        Some(Combo {
+            hits: 3,
+            damage: 33,
+            started: 2,
+            ko: false
+        })
+    );
+    assert_eq!(ended[1], None);
+    assert_eq!(stats.combo_hits[0], 0);
+    assert_eq!(stats.combos, [1, 0]);
+    assert_eq!(stats.longest_combo[0], 3);
+    assert_eq!(stats.best_combo_damage[0], 33);
+}
+
+#[test]
+fn test_single_hit_is_no_combo() {
+    let mut stats = SessionStats::default();
+    track(&mut stats, &players(0, true), 1);
+    track(&mut stats, &players(9, false), 2);
+    assert_eq!(track(&mut stats, &players(9, true), 3), [None, None]);
+    assert_eq!(stats.combos, [0, 0]);
+    assert_eq!(stats.longest_combo, [0, 0]);
+}
+
+#[test]
+fn test_combo_ending_in_ko() {
+    let mut stats = SessionStats::default();
+    track(&mut stats, &players(90, true), 1);
+    track(&mut stats, &players(104, false), 2);
+    track(&mut stats, &players(121, false), 3);
+
+    // Stock lost: resolve-damage reset the percent
+    let ended = track(&mut stats, &players(0, false), 4);
+    assert_eq!(ended[0].map(|c| (c.hits, c.damage, c.ko)), Some((2, 31, true)));
+    assert_eq!(stats.combo_percents[1], 0);
+}
@@ -0,0 +1,195 @@
+use bolt_lang::*;
+use input_buffer::{ControllerInput, InputBuffer};
+use session_state::{
+    PlayerState, SessionState, NUM_PLAYERS, STATUS_ACTIVE, STATUS_CREATED,
+    STATUS_WAITING_PLAYERS,
+};
+use training_export::{TrainingExport, CONTROLLER_INPUT_SIZE, ENCODING_V2, PLAYER_STATE_SIZE};
+
+declare_id!("7p5kNMA4mTgcrmmSa8Ru6xLFTUpFa5jhAKTWUWpCv24");
+
+#[error_code]
+pub enum ExportError {
+    #[msg("Session is not active")]
+    SessionNotActive,
+    #[msg("Training export is not enabled for this session")]
+    ExportDisabled,
+    #[msg("This frame was already exported")]
+    AlreadyExported,
+    #[msg("Export opt-in is fixed once the session starts")]
+    ConfigLocked,
+}
+
+/// One frame transition for training, in the encoding `encoding` names.
+#[event]
+pub struct TrainingTuple {
+    pub session: Pubkey,
+    /// Frame `next_state` is at; `state` is the frame before
+    pub frame: u32,
+    /// ENCODING_* of the packed fields
+    pub encoding: u8,
+    pub stage: u8,
+    /// Both players before the frame
+    pub state: [[u8; PLAYER_STATE_SIZE]; 2],
+    /// Both players' controller inputs for the frame
+    pub inputs: [[u8; CONTROLLER_INPUT_SIZE]; 2],
+    /// Both players after the frame
+    pub next_state: [[u8; PLAYER_STATE_SIZE]; 2],
+}
+
+/// A packed (state, input, next state) transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub state: [[u8; PLAYER_STATE_SIZE]; 2],
+    pub inputs: [[u8; CONTROLLER_INPUT_SIZE]; 2],
+    pub next_state: [[u8; PLAYER_STATE_SIZE]; 2],
+}
+
+/// Export training system — runs last each frame when the session has
+/// training export enabled.
+///
+/// The world produces exactly the (state, input, next state) triples the
+/// model trains on. Each frame this packs both players in the v2 encoding
+/// and, when the previous frame was exported too, emits a TrainingTuple
+/// event pairing it with this frame's inputs. Indexers collect the events
+/// into training shards. A skipped frame just starts a new run: the state
+/// is kept, but no tuple spans the gap.
+///
+/// Called with `configure` set (before the session goes Active), it sets
+/// the opt-in instead.
+///
+/// Accounts read/written:
+///   - SessionState: players and stage (read)
+///   - InputBuffer: the frame's controller inputs (read)
+///   - TrainingExport: opt-in, last exported frame
+#[system]
+pub mod export_training {
+
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        let session_key = ctx.accounts.session_state.key();
+        let session = &ctx.accounts.session_state;
+        let input_buf = &ctx.accounts.input_buffer;
+        let export = &mut ctx.accounts.training_export;
+
+        if args.configure {
+            require!(
+                session.status == STATUS_CREATED || session.status == STATUS_WAITING_PLAYERS,
+                ExportError::ConfigLocked
+            );
+            export.configured = true;
+            export.enabled = args.enabled;
+            msg!("Training export: enabled={}", export.enabled);
+            return Ok(ctx.accounts);
+        }
+
+        require!(
+            session.status == STATUS_ACTIVE,
+            ExportError::SessionNotActive
+        );
+        require!(export.enabled, ExportError::ExportDisabled);
+        require!(
+            !export.has_state || session.frame > export.frame,
+            ExportError::AlreadyExported
+        );
+
+        if let Some(t) = step_export(export, &session.players, input_buf, session.frame) {
+            emit!(TrainingTuple {
+                session: session_key,
+                frame: session.frame,
+                encoding: ENCODING_V2,
+                stage: session.stage,
+                state: t.state,
+                inputs: t.inputs,
+                next_state: t.next_state,
+            });
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub input_buffer: InputBuffer,
+        pub training_export: TrainingExport,
+    }
+
+    #[arguments]
+    pub struct Args {
+        /// Set the opt-in below instead of exporting a frame
+        pub configure: bool,
+        /// Whether tuples are exported — only used to configure
+        pub enabled: bool,
+    }
+}
+
+/// Pack the players at `frame` as the export's state, returning the
+/// transition from the previous state when it was the frame before and
+/// the input buffer holds this frame's inputs.
+pub fn step_export(
+    export: &mut TrainingExport,
+    players: &[PlayerState; NUM_PLAYERS],
+    input_buf: &InputBuffer,
+    frame: u32,
+) -> Option<Transition> {
+    let next_state = [pack_player(&players[0]), pack_player(&players[1])];
+    let follows = export.has_state && export.frame + 1 == frame && input_buf.frame == frame;
+    let transition = follows.then(|| Transition {
+        state: export.state,
+        inputs: [pack_input(&input_buf.player1), pack_input(&input_buf.player2)],
+        next_state,
+    });
+
+    export.has_state = true;
+    export.frame = frame;
+    export.state = next_state;
+    if transition.is_some() {
+        export.exported += 1;
+    }
+    transition
+}
+
+/// Pack a player in the v2 encoding (crank/solana_bridge.py's
+/// serialize_player_state).
+pub fn pack_player(p: &PlayerState) -> [u8; PLAYER_STATE_SIZE] {
+    let mut out = [0u8; PLAYER_STATE_SIZE];
+    let fields: [&[u8]; 17] = [
+        &p.x.to_le_bytes(),
+        &p.y.to_le_bytes(),
+        &p.percent.to_le_bytes(),
+        &p.shield_strength.to_le_bytes(),
+        &p.speed_air_x.to_le_bytes(),
+        &p.speed_y.to_le_bytes(),
+        &p.speed_ground_x.to_le_bytes(),
+        &p.speed_attack_x.to_le_bytes(),
+        &p.speed_attack_y.to_le_bytes(),
+        &p.state_age.to_le_bytes(),
+        &[p.hitlag],
+        &[p.stocks],
+        &[p.facing],
+        &[p.on_ground],
+        &p.action_state.to_le_bytes(),
+        &[p.jumps_left],
+        &[p.character],
+    ];
+    let mut offset = 0;
+    for bytes in fields {
+        out[offset..offset + bytes.len()].copy_from_slice(bytes);
+        offset += bytes.len();
+    }
+    out
+}
+
+/// Pack a controller input in the v2 encoding.
+pub fn pack_input(c: &ControllerInput) -> [u8; CONTROLLER_INPUT_SIZE] {
+    [
+        c.stick_x as u8,
+        c.stick_y as u8,
+        c.c_stick_x as u8,
+        c.c_stick_y as u8,
+        c.trigger_l,
+        c.trigger_r,
+        c.buttons,
+        c.buttons_ext,
+    ]
+}
@@ -0,0 +1,86 @@
+//! Packing in the v2 encoding, and which frames make a tuple.
+
+use export_training::{pack_input, pack_player, step_export};
+use input_buffer::{ControllerInput, InputBuffer};
+use session_state::PlayerState;
+use training_export::TrainingExport;
+
+fn players(x: i32) -> [PlayerState; 2] {
+    [0, 1].map(|i| PlayerState {
+        x: if i == 0 { -x } else { x },
+        stocks: 4,
+        ..Default::default()
+    })
+}
+
+fn inputs(frame: u32, stick_x: i8) -> InputBuffer {
+    InputBuffer {
+        frame,
+        player1: ControllerInput {
+            stick_x,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_pack_player_layout() {
+    let p = PlayerState {
+        x: -256,
+        percent: 42,
+        stocks: 3,
+        action_state: 0x0158,
+        character: 18,
+        ..Default::default()
+    };
+    let bytes = pack_player(&p);
+    assert_eq!(&bytes[0..4], &(-256i32).to_le_bytes());
+    assert_eq!(&bytes[8..10], &42u16.to_le_bytes());
+    assert_eq!(bytes[25], 3);
+    assert_eq!(&bytes[28..30], &[0x58, 0x01]);
+    assert_eq!(bytes[31], 18);
+}
+
+#[test]
+fn test_pack_input_layout() {
+    let c = ControllerInput {
+        stick_x: -1,
+        trigger_r: 200,
+        buttons: 0x11,
+        ..Default::default()
+    };
+    assert_eq!(pack_input(&c), [0xff, 0, 0, 0, 0, 200, 0x11, 0]);
+}
+
+#[test]
+fn test_consecutive_frames_make_tuples() {
+    let mut export = TrainingExport::default();
+
+    // The first frame only records the state
+    assert_eq!(step_export(&mut export, &players(10), &inputs(0, 0), 0), None);
+
+    let t = step_export(&mut export, &players(12), &inputs(1, 64), 1).unwrap();
+    assert_eq!(t.state[1], pack_player(&players(10)[1]));
+    assert_eq!(t.next_state[1], pack_player(&players(12)[1]));
+    assert_eq!(t.inputs[0][0], 64);
+    assert_eq!(export.exported, 1);
+
+    assert!(step_export(&mut export, &players(14), &inputs(2, 0), 2).is_some());
+    assert_eq!((export.frame, export.exported), (2, 2));
+}
+
+#[test]
+fn test_gap_starts_a_new_run() {
+    let mut export = TrainingExport::default();
+    step_export(&mut export, &players(10), &inputs(4, 0), 4);
+
+    // Frame 5 was never exported: no tuple spans it
+    assert_eq!(step_export(&mut export, &players(12), &inputs(6, 0), 6), None);
+    assert_eq!(export.frame, 6);
+
+    // Stale inputs don't make a tuple either
+    assert_eq!(step_export(&mut export, &players(14), &inputs(6, 0), 7), None);
+    assert!(step_export(&mut export, &players(16), &inputs(8, 0), 8).is_some());
+    assert_eq!(export.exported, 1);
+}
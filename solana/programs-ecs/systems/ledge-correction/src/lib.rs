@@ -0,0 +1,195 @@
+use bolt_lang::*;
+use input_buffer::{ControllerInput, InputBuffer};
+use session_state::{
+    PlayerState, SessionState, ACTION_CLIFF_CATCH, ACTION_CLIFF_WAIT, ACTION_FALL, ACTION_PASS,
+    NUM_PLAYERS, STATUS_ACTIVE,
+};
+use stage_geometry::StageGeometry;
+
+declare_id!("AoyARRdZ6Anyd5ZAL7fCApC7iGoYZw6zPjGga8L7mrpY");
+
+/// How far (fixed-point, per axis) from the hang position a player in a
+/// ledge state may be and still hold the ledge
+pub const LEDGE_SNAP: i32 = 6 * 256;
+
+/// Half width of a player's environment collision box (fixed-point).
+/// Narrower than LEDGE_HANG_X, so a hanging player clears the wall.
+pub const ECB_HALF_WIDTH: i32 = 2 * 256;
+
+/// Main stick Y at or below which a grounded player drops through a
+/// platform (about 70% down)
+pub const PASS_STICK_Y: i8 = -88;
+
+/// How far below a platform a drop-through puts the player — past
+/// stage-collision's SNAP_DEPTH, so the platform doesn't catch them again
+pub const PASS_DEPTH: i32 = 10 * 256;
+
+/// Speed (fixed-point per frame) a trumped player is popped up and away
+/// from the ledge at
+pub const TRUMP_POP_SPEED: i16 = 256;
+
+#[error_code]
+pub enum CorrectionError {
+    #[msg("Session is not active")]
+    SessionNotActive,
+    #[msg("No geometry for this stage")]
+    UnknownStage,
+}
+
+/// What the correction pass did to a player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Correction {
+    None,
+    /// Holding a ledge off its hang position: moved onto it
+    LedgeSnapped,
+    /// In a ledge state nowhere near a ledge: falls instead
+    LedgeReleased,
+    /// Dropped through a pass-through platform
+    DroppedThrough,
+    /// Inside the main platform: pushed out through its nearest face
+    PushedOut,
+}
+
+/// Ledge correction system — runs after stage-collision each frame.
+///
+/// The model often decodes players into states the stage rules out:
+/// hanging on a ledge from mid-air, or overlapping the stage body next to
+/// a ledge. This pass fixes them up:
+///   - Ledge states (CliffCatch, CliffWait) hold only at a ledge: near
+///     one, snap to its hang position; otherwise fall
+///   - One player per ledge: a new grab trumps the player already there
+///   - Holding down on a pass-through platform drops through it
+///   - A player inside the main platform is pushed out through its
+///     nearest face, like Melee's ECB
+///
+/// Accounts read/written:
+///   - SessionState: players' positions, velocities, action states
+///   - StageGeometry: loaded for the session's stage on first use
+///   - InputBuffer: this frame's sticks (read)
+#[system]
+pub mod ledge_correction {
+
+    pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session = &mut ctx.accounts.session_state;
+        let geometry = &mut ctx.accounts.stage_geometry;
+        let input_buf = &ctx.accounts.input_buffer;
+
+        require!(
+            session.status == STATUS_ACTIVE,
+            CorrectionError::SessionNotActive
+        );
+        if !geometry.loaded || geometry.stage != session.stage {
+            require!(
+                geometry.load_legal(session.stage),
+                CorrectionError::UnknownStage
+            );
+        }
+
+        let inputs = [&input_buf.player1, &input_buf.player2];
+        for (slot, p) in session.players.iter_mut().enumerate() {
+            let correction = correct(p, inputs[slot], geometry);
+            if correction != Correction::None {
+                msg!("Player {}: {:?}", slot + 1, correction);
+            }
+        }
+        if let Some(slot) = trump_ledge(&mut session.players) {
+            msg!("Player {} was trumped off the ledge", slot + 1);
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub stage_geometry: StageGeometry,
+        pub input_buffer: InputBuffer,
+    }
+}
+
+/// Correct one player against the stage.
+pub fn correct(p: &mut PlayerState, input: &ControllerInput, stage: &StageGeometry) -> Correction {
+    if p.stocks == 0 {
+        return Correction::None;
+    }
+
+    if on_ledge(p) {
+        let right = p.x > (stage.ground_left + stage.ground_right) / 2;
+        let (x, y, facing) = stage.ledge_hang(right);
+        if (p.x - x).abs() > LEDGE_SNAP || (p.y - y).abs() > LEDGE_SNAP {
+            fall(p);
+            return Correction::LedgeReleased;
+        }
+        if (p.x, p.y, p.facing, p.on_ground) == (x, y, facing, 0) {
+            return Correction::None;
+        }
+        p.x = x;
+        p.y = y;
+        p.facing = facing;
+        p.on_ground = 0;
+        p.speed_air_x = 0;
+        p.speed_y = 0;
+        p.speed_ground_x = 0;
+        return Correction::LedgeSnapped;
+    }
+
+    if p.on_ground != 0 && input.stick_y <= PASS_STICK_Y {
+        let standing_on = stage.platforms[..stage.num_platforms as usize]
+            .iter()
+            .find(|pl| p.y == pl.y && p.x >= pl.left && p.x <= pl.right);
+        if let Some(platform) = standing_on {
+            p.y = platform.y - PASS_DEPTH;
+            p.speed_y = 0;
+            p.on_ground = 0;
+            p.action_state = ACTION_PASS;
+            p.state_age = 0;
+            return Correction::DroppedThrough;
+        }
+    }
+
+    let left = stage.ground_left - ECB_HALF_WIDTH;
+    let right = stage.ground_right + ECB_HALF_WIDTH;
+    if p.y < stage.ground_y && p.x > left && p.x < right {
+        let (up, to_left, to_right) = (stage.ground_y - p.y, p.x - left, right - p.x);
+        if up <= to_left.min(to_right) {
+            p.y = stage.ground_y;
+            p.speed_y = 0;
+            p.on_ground = 1;
+        } else {
+            p.x = if to_left < to_right { left } else { right };
+            p.speed_air_x = 0;
+            p.speed_ground_x = 0;
+        }
+        return Correction::PushedOut;
+    }
+
+    Correction::None
+}
+
+/// Only one player holds a ledge. When both hang from the same one, the
+/// later grab (lower state_age; ties to player 1) keeps it and the other
+/// pops up and away. Returns the slot that lost the ledge. Expects
+/// `correct` to have snapped both to the hang position first.
+pub fn trump_ledge(players: &mut [PlayerState; NUM_PLAYERS]) -> Option<usize> {
+    let [a, b] = players;
+    let shared = on_ledge(a) && on_ledge(b) && (a.x, a.y) == (b.x, b.y);
+    if !shared || a.stocks == 0 || b.stocks == 0 {
+        return None;
+    }
+    let (slot, loser) = if a.state_age <= b.state_age { (1, b) } else { (0, a) };
+    fall(loser);
+    loser.speed_y = TRUMP_POP_SPEED;
+    // Facing right means hanging from the left ledge: away is left
+    loser.speed_air_x = if loser.facing != 0 { -TRUMP_POP_SPEED } else { TRUMP_POP_SPEED };
+    Some(slot)
+}
+
+fn on_ledge(p: &PlayerState) -> bool {
+    p.action_state == ACTION_CLIFF_CATCH || p.action_state == ACTION_CLIFF_WAIT
+}
+
+fn fall(p: &mut PlayerState) {
+    p.action_state = ACTION_FALL;
+    p.state_age = 0;
+    p.on_ground = 0;
+}
@@ -0,0 +1,105 @@
+//! Ledge eligibility, ledge trumps, platform drop-through and ECB push-out
+//! on Battlefield.
+
+use input_buffer::ControllerInput;
+use ledge_correction::*;
+use session_state::{PlayerState, ACTION_CLIFF_WAIT, ACTION_FALL, ACTION_PASS};
+use stage_geometry::{StageGeometry, LEDGE_HANG_X, LEDGE_HANG_Y, STAGE_BATTLEFIELD};
+
+fn battlefield() -> StageGeometry {
+    let mut stage = StageGeometry::default();
+    assert!(stage.load_legal(STAGE_BATTLEFIELD));
+    stage
+}
+
+fn player(x: i32, y: i32, on_ground: u8, action_state: u16) -> PlayerState {
+    PlayerState {
+        x,
+        y,
+        on_ground,
+        action_state,
+        stocks: 4,
+        ..Default::default()
+    }
+}
+
+fn stick_y(stick_y: i8) -> ControllerInput {
+    ControllerInput { stick_y, ..Default::default() }
+}
+
+#[test]
+fn test_ledge_state_snaps_or_falls() {
+    let stage = battlefield();
+    let neutral = stick_y(0);
+
+    // Hovering a few units off the right ledge's hang position
+    let (hx, hy, _) = stage.ledge_hang(true);
+    let mut p = player(hx + 4 * 256, hy - 3 * 256, 0, ACTION_CLIFF_WAIT);
+    assert_eq!(correct(&mut p, &neutral, &stage), Correction::LedgeSnapped);
+    assert_eq!((p.x, p.y, p.facing), (stage.ground_right + LEDGE_HANG_X, -LEDGE_HANG_Y, 0));
+    assert_eq!(correct(&mut p, &neutral, &stage), Correction::None);
+
+    // A ledge state in mid-air
+    let mut p = player(0, 40 * 256, 0, ACTION_CLIFF_WAIT);
+    assert_eq!(correct(&mut p, &neutral, &stage), Correction::LedgeReleased);
+    assert_eq!(p.action_state, ACTION_FALL);
+}
+
+#[test]
+fn test_ledge_trump() {
+    let stage = battlefield();
+    let (hx, hy, facing) = stage.ledge_hang(false);
+    let mut players = [
+        player(hx, hy, 0, ACTION_CLIFF_WAIT),
+        player(hx, hy, 0, ACTION_CLIFF_WAIT),
+    ];
+    players[0].facing = facing;
+    players[1].facing = facing;
+    players[0].state_age = 40;
+    players[1].state_age = 2;
+
+    // Player 2 grabbed last and keeps it; player 1 pops up and away (left)
+    assert_eq!(trump_ledge(&mut players), Some(0));
+    assert_eq!(players[0].action_state, ACTION_FALL);
+    assert!(players[0].speed_y > 0 && players[0].speed_air_x < 0);
+    assert_eq!(players[1].action_state, ACTION_CLIFF_WAIT);
+    assert_eq!(trump_ledge(&mut players), None);
+}
+
+#[test]
+fn test_drop_through_platform() {
+    let stage = battlefield();
+    let side = &stage.platforms[0];
+    let x = (side.left + side.right) / 2;
+
+    let mut p = player(x, side.y, 1, 0);
+    assert_eq!(correct(&mut p, &stick_y(-40), &stage), Correction::None);
+    assert_eq!(correct(&mut p, &stick_y(-128), &stage), Correction::DroppedThrough);
+    assert_eq!((p.y, p.on_ground, p.action_state), (side.y - PASS_DEPTH, 0, ACTION_PASS));
+
+    // The main platform is solid
+    let mut p = player(x, stage.ground_y, 1, 0);
+    assert_eq!(correct(&mut p, &stick_y(-128), &stage), Correction::None);
+}
+
+#[test]
+fn test_push_out_of_stage_body() {
+    let stage = battlefield();
+    let neutral = stick_y(0);
+
+    // Just under the surface: up onto it
+    let mut p = player(0, -2 * 256, 0, 0);
+    assert_eq!(correct(&mut p, &neutral, &stage), Correction::PushedOut);
+    assert_eq!((p.y, p.on_ground), (stage.ground_y, 1));
+
+    // Deep beside the left wall: out to the side
+    let mut p = player(stage.ground_left + 256, -30 * 256, 0, 0);
+    p.speed_air_x = 200;
+    assert_eq!(correct(&mut p, &neutral, &stage), Correction::PushedOut);
+    assert_eq!((p.x, p.y, p.speed_air_x), (stage.ground_left - ECB_HALF_WIDTH, -30 * 256, 0));
+
+    // Hanging from the ledge without a ledge state clears the wall
+    let (hx, hy, _) = stage.ledge_hang(false);
+    let mut p = player(hx, hy, 0, ACTION_FALL);
+    assert_eq!(correct(&mut p, &neutral, &stage), Correction::None);
+}
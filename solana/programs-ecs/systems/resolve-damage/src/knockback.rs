@@ -0,0 +1,82 @@
+//! Melee's knockback formula, in integer fixed-point so every validator
+//! resolves a hit to the same numbers:
+//!
+//!   KB = ((p/10 + p·d/20) · 200/(w+100) · 1.4 + 18) · s/100 + b
+//!
+//! p = defender percent after the hit, d = damage, w = defender weight,
+//! s = knockback growth, b = base knockback. A hit launches at KB · 0.03
+//! game units per frame and freezes both players for ⌊d/3⌋ + 3 frames
+//! of hitlag.
+
+/// Knockback growth and base applied to every hit. The model doesn't say
+/// which move connected, so hits resolve as one representative
+/// mid-strength move.
+pub const KB_GROWTH: i64 = 100;
+pub const KB_BASE: i64 = 10;
+
+/// Most damage one hit can deal; bigger single-frame jumps are drift.
+pub const MAX_HIT_DAMAGE: u16 = 40;
+
+/// Melee's percent display cap
+pub const MAX_PERCENT: u16 = 999;
+
+/// Melee's hitlag cap in frames
+pub const MAX_HITLAG: u8 = 20;
+
+/// Weights by internal character ID (Mario 0x00 … Roy 0x1A).
+const WEIGHTS: [i64; 27] = [
+    100, 75, 104, 114, 70, 117, 104, 90, 94, 90, 88, 88, 80, 110, 108, 60, 85, 100, 87, 90, 85,
+    100, 80, 55, 60, 109, 85,
+];
+
+/// Character weight; 100 for IDs outside the playable cast.
+pub fn character_weight(character: u8) -> i64 {
+    WEIGHTS.get(character as usize).copied().unwrap_or(100)
+}
+
+/// Knockback in thousandths for a hit of `damage` that leaves the
+/// defender at `percent`.
+pub fn knockback_milli(percent: u16, damage: u16, weight: i64) -> i64 {
+    let (p, d) = (percent as i64, damage as i64);
+    // p/10 + p·d/20, in thousandths
+    let base = p * 100 + p * d * 50;
+    let weighted = base * 200 / (weight + 100) * 14 / 10;
+    (weighted + 18_000) * KB_GROWTH / 100 + KB_BASE * 1000
+}
+
+/// Launch speed for `kb_milli` in PlayerState fixed-point (game units per
+/// frame × 256).
+pub fn launch_speed(kb_milli: i64) -> i64 {
+    // KB · 0.03 · 256, with KB in thousandths
+    kb_milli * 3 * 256 / 100_000
+}
+
+/// Hitlag frames for a hit of `damage`.
+pub fn hitlag_frames(damage: u16) -> u8 {
+    (damage / 3 + 3).min(MAX_HITLAG as u16) as u8
+}
+
+/// Split `speed` along the direction (dx, dy). A zero direction yields a
+/// zero vector.
+pub fn launch_vector(speed: i64, dx: i64, dy: i64) -> (i16, i16) {
+    let len = isqrt(dx * dx + dy * dy);
+    if len == 0 {
+        return (0, 0);
+    }
+    let clamp = |v: i64| v.clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+    (clamp(speed * dx / len), clamp(speed * dy / len))
+}
+
+/// Integer square root (floor).
+fn isqrt(n: i64) -> i64 {
+    if n < 2 {
+        return n.max(0);
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
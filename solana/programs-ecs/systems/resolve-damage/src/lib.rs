@@ -0,0 +1,142 @@
+use bolt_lang::*;
+use combat_state::CombatState;
+use session_state::{PlayerState, SessionState, STATUS_ACTIVE};
+use session_stats::SessionStats;
+
+pub mod knockback;
+
+use knockback::{
+    character_weight, hitlag_frames, knockback_milli, launch_speed, launch_vector,
+    MAX_HIT_DAMAGE, MAX_PERCENT,
+};
+
+declare_id!("HJoxZQJWCZAxapCvnoJqfQmd2w4EX7usejjtEkfLiUy");
+
+#[error_code]
+pub enum DamageError {
+    #[msg("Session is not active")]
+    SessionNotActive,
+    #[msg("This frame's damage is already resolved")]
+    AlreadyResolved,
+}
+
+/// Resolve damage system — runs after run_inference each frame.
+///
+/// The model's regression heads drift: percent can creep, heal, or jump
+/// by implausible amounts, and knockback speeds needn't match the damage.
+/// This system treats the model's output as hit events and re-derives the
+/// numbers deterministically:
+///   - A percent rise is a hit: damage is the rise (capped at
+///     MAX_HIT_DAMAGE), knockback follows Melee's formula for the
+///     defender's weight, launched along the model's own knockback
+///     direction (away from the attacker if it emitted none), and both
+///     players get the hit's hitlag
+///   - A stock loss resets percent to 0 (in a persistent world, where
+///     stocks count lives, so does starting the next life)
+///   - A persistent-world slot that's open or was just claimed only sets
+///     the baseline: a player coming or going is neither a hit nor a KO
+///   - Otherwise percent holds at its resolved value; it never heals
+///
+/// Resolved hits and KOs are tallied to the attacker in SessionStats.
+///
+/// Accounts read/written:
+///   - SessionState: players' percent, knockback speeds, hitlag
+///   - CombatState: last resolved percents and stocks
+///   - SessionStats: damage dealt, hits landed and KOs per player
+#[system]
+pub mod resolve_damage {
+
+    pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session = &mut ctx.accounts.session_state;
+        let combat = &mut ctx.accounts.combat_state;
+        let stats = &mut ctx.accounts.session_stats;
+
+        require!(
+            session.status == STATUS_ACTIVE,
+            DamageError::SessionNotActive
+        );
+        require!(
+            session.frame > combat.frame,
+            DamageError::AlreadyResolved
+        );
+
+        let mut hitlag = [0u8; 2];
+        let persistent = session.is_persistent();
+        for defender in 0..2 {
+            if persistent && (session.slot_open(defender) || combat.stocks[defender] == 0) {
+                continue;
+            }
+            let attacker_x = session.players[1 - defender].x;
+            let p = &mut session.players[defender];
+            let prev = combat.percents[defender];
+
+            let lost = kos_taken(persistent, combat.stocks[defender], p.stocks);
+            if lost > 0 {
+                p.percent = 0;
+                stats.kos[1 - defender] = stats.kos[1 - defender].saturating_add(lost);
+            } else if p.percent > prev {
+                let damage = (p.percent - prev).min(MAX_HIT_DAMAGE);
+                p.percent = (prev + damage).min(MAX_PERCENT);
+                resolve_hit(p, damage, attacker_x);
+                hitlag[defender] = hitlag[defender].max(hitlag_frames(damage));
+                hitlag[1 - defender] = hitlag[1 - defender].max(hitlag_frames(damage));
+                combat.hits[defender] += 1;
+                stats.damage_dealt[1 - defender] += damage as u32;
+                stats.hits_landed[1 - defender] += 1;
+                msg!(
+                    "Hit: player {} takes {}% → {}%",
+                    defender + 1,
+                    damage,
+                    p.percent
+                );
+            } else {
+                p.percent = prev;
+            }
+        }
+
+        for (slot, p) in session.players.iter_mut().enumerate() {
+            if hitlag[slot] > 0 {
+                p.hitlag = hitlag[slot];
+            }
+            combat.percents[slot] = p.percent;
+            combat.stocks[slot] = p.stocks;
+        }
+        combat.frame = session.frame;
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub combat_state: CombatState,
+        pub session_stats: SessionStats,
+    }
+}
+
+/// KOs a player took between two frames' stocks: stocks lost in a match,
+/// lives started in a persistent world.
+pub fn kos_taken(persistent: bool, before: u8, after: u8) -> u8 {
+    if persistent {
+        after.saturating_sub(before)
+    } else {
+        before.saturating_sub(after)
+    }
+}
+
+/// Replace the defender's knockback speeds with the formula's launch,
+/// keeping the model's launch direction.
+fn resolve_hit(p: &mut PlayerState, damage: u16, attacker_x: i32) {
+    let kb = knockback_milli(p.percent, damage, character_weight(p.character));
+    let speed = launch_speed(kb);
+
+    let (mut dx, mut dy) = (p.speed_attack_x as i64, p.speed_attack_y as i64);
+    if dx == 0 && dy == 0 {
+        // No direction from the model: 45° up and away from the attacker
+        dx = if p.x >= attacker_x { 1 } else { -1 };
+        dy = 1;
+    }
+    let (vx, vy) = launch_vector(speed, dx, dy);
+    p.speed_attack_x = vx;
+    p.speed_attack_y = vy;
+}
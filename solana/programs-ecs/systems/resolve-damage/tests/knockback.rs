@@ -0,0 +1,50 @@
+//! Knockback formula against hand-computed Melee values, and KO counting.
+
+use resolve_damage::knockback::*;
+use resolve_damage::kos_taken;
+
+#[test]
+fn test_knockback_formula() {
+    // Fox (75) at 100% after a 10% hit:
+    // (10 + 50) · 200/175 · 1.4 + 18 = 114, + base 10 = 124
+    let kb = knockback_milli(100, 10, character_weight(1));
+    assert!((kb - 124_000).abs() <= 5, "kb = {kb}");
+
+    // Heavier characters take less knockback from the same hit
+    let bowser = knockback_milli(100, 10, character_weight(5));
+    let jigglypuff = knockback_milli(100, 10, character_weight(15));
+    assert!(bowser < kb && kb < jigglypuff);
+
+    // At 0% only the constant terms remain: 18 + 10
+    assert_eq!(knockback_milli(0, 10, 100), 28_000);
+}
+
+#[test]
+fn test_launch() {
+    // KB 100 → 3 units/frame → 768 fixed-point
+    assert_eq!(launch_speed(100_000), 768);
+
+    let (vx, vy) = launch_vector(768, 3, 4);
+    assert_eq!((vx, vy), (460, 614));
+    assert_eq!(launch_vector(768, 0, 0), (0, 0));
+    let (vx, vy) = launch_vector(1000, -1, 0);
+    assert_eq!((vx, vy), (-1000, 0));
+}
+
+#[test]
+fn test_hitlag_and_weights() {
+    assert_eq!(hitlag_frames(0), 3);
+    assert_eq!(hitlag_frames(12), 7);
+    assert_eq!(hitlag_frames(200), MAX_HITLAG);
+
+    assert_eq!(character_weight(18), 87); // Marth
+    assert_eq!(character_weight(0x1e), 100); // Sandbag: default
+}
+
+#[test]
+fn test_kos_count_lost_stocks_or_started_lives() {
+    assert_eq!(kos_taken(false, 4, 3), 1);
+    assert_eq!(kos_taken(false, 3, 3), 0);
+    assert_eq!(kos_taken(true, 1, 2), 1);
+    assert_eq!(kos_taken(true, 2, 2), 0);
+}
@@ -4,10 +4,20 @@ use hidden_state::HiddenState;
 use input_buffer::InputBuffer;
 use session_state::{PlayerState, SessionState, STATUS_ACTIVE};
 
-pub mod lut;
-pub mod matmul;
 pub mod mamba2;
 
+pub use awm_kernel::{lut, matmul};
+
+// The forward pass needs the ModelManifest and WeightShard components in
+// the system input, which Phase 4 adds; until then only the stub builds.
+#[cfg(feature = "mamba2-inference")]
+compile_error!(
+    "run-inference: mamba2-inference needs the Phase 4 manifest and shard components; \
+     use world-model's mamba2-inference meanwhile"
+);
+#[cfg(not(feature = "stub-inference"))]
+compile_error!("run-inference: enable `stub-inference`");
+
 declare_id!("3tHPJJSNhKwbp7K5vSYCUdYVX9bGxRCmpddwaJWRKPyb");
 
 #[error_code]
@@ -16,6 +26,16 @@ pub enum InferenceError {
     SessionNotActive,
     #[msg("Both players must submit inputs before inference")]
     InputsNotReady,
+    #[msg("FrameLog is a full ring ahead of its archive")]
+    ArchiveBehind,
+}
+
+/// One frame, pushed for spectators when the session's FrameLog has
+/// `emit_frames` set.
+#[event]
+pub struct SpectatorFrame {
+    pub session: Pubkey,
+    pub frame: CompressedFrame,
 }
 
 /// Run inference system — the heart of the autonomous world.
@@ -23,10 +43,12 @@ pub enum InferenceError {
 /// Executes one Mamba2 forward pass per call:
 ///   (controller_inputs, current_state, hidden_state) → (next_state, new_hidden_state)
 ///
-/// Called by a cranker/scheduler at 60fps cadence (every 16.67ms).
+/// Called by a cranker/scheduler at the session's tick rate (every 16.67ms
+/// at the default 60fps).
 ///
-/// Phase 3 implementation: STUB. Copies inputs through with default state changes.
-/// Phase 4 will replace this with the real INT8 Mamba2 inference kernel.
+/// Phase 3 implementation: STUB (`stub-inference`, on by default). Copies inputs
+/// through with default state changes. Phase 4 will add the real INT8 Mamba2
+/// kernel behind `mamba2-inference`.
 ///
 /// Accounts read:
 ///   - InputBuffer: controller inputs for current frame
@@ -36,11 +58,17 @@ pub enum InferenceError {
 /// Accounts written:
 ///   - SessionState: updated with new frame state
 ///   - HiddenState: updated recurrent state
-///   - FrameLog: compressed frame appended to ring buffer
+///   - FrameLog: compressed frame appended to ring buffer (and emitted as
+///     SpectatorFrame when the session opted in)
+///
+/// In a persistent world, open slots have no input to wait for and no
+/// player to step, and the frame waits until the FrameLog's archiver has
+/// room in the ring for it.
 #[system]
 pub mod run_inference {
 
     pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session_key = ctx.accounts.session_state.key();
         let session = &mut ctx.accounts.session_state;
         let hidden = &mut ctx.accounts.hidden_state;
         let input_buf = &ctx.accounts.input_buffer;
@@ -52,82 +80,33 @@ pub mod run_inference {
             InferenceError::SessionNotActive
         );
 
-        // Validate inputs are ready
+        // Validate inputs are ready (an open slot has none coming)
+        let seated = [!session.slot_open(0), !session.slot_open(1)];
         require!(
-            input_buf.p1_ready && input_buf.p2_ready,
+            (input_buf.p1_ready || !seated[0]) && (input_buf.p2_ready || !seated[1]),
             InferenceError::InputsNotReady
         );
+        require!(
+            !session.is_persistent() || frame_log.has_room(),
+            InferenceError::ArchiveBehind
+        );
 
-        // ── STUB INFERENCE (Phase 3) ────────────────────────────────────
-        // In Phase 4, this will be replaced with:
+        let frame = session.frame + 1;
+
+        // ── STUB INFERENCE (stub-inference) ─────────────────────────────
+        // Simple physics-like rules that demonstrate the pipeline. Phase 4
+        // (mamba2-inference) replaces this with:
         //   1. Encode inputs (controller + current state → model input vector)
         //   2. For each layer: RMSNorm → in_proj → SSM step → gate → out_proj
         //   3. Decode output (model output → next PlayerState per player)
-        //
-        // For now: apply simple physics-like rules to demonstrate the pipeline.
-
-        let frame = session.frame + 1;
-
-        // Simple stub: apply controller inputs as velocity
-        for player_idx in 0..2 {
-            let input = if player_idx == 0 {
-                &input_buf.player1
-            } else {
-                &input_buf.player2
-            };
-
-            let p = &mut session.players[player_idx];
-
-            // Apply stick input as velocity (simplified physics)
-            let stick_x = input.stick_x as i32;
-            let stick_y = input.stick_y as i32;
-
-            // Move based on stick (scale by 2 for reasonable speed)
-            p.x += stick_x * 2;
-            p.y += stick_y * 2;
-
-            // Apply gravity if airborne
-            if p.on_ground == 0 {
-                p.speed_y -= 4; // Gravity (fixed-point)
-                p.y += p.speed_y as i32;
-
-                // Ground collision at y=0
-                if p.y <= 0 {
-                    p.y = 0;
-                    p.speed_y = 0;
-                    p.on_ground = 1;
-                }
-            }
-
-            // Jump (button A)
-            if input.buttons & 0x01 != 0 && p.jumps_left > 0 {
-                p.speed_y = 40; // Jump velocity (fixed-point)
-                p.on_ground = 0;
-                p.jumps_left = p.jumps_left.saturating_sub(1);
-            }
-
-            // Update facing based on stick direction
-            if stick_x > 10 {
-                p.facing = 1;
-            } else if stick_x < -10 {
-                p.facing = 0;
-            }
-
-            // Update ground speed
-            p.speed_ground_x = (stick_x * 2).clamp(-32767, 32767) as i16;
-
-            // Increment state age
-            p.state_age = p.state_age.saturating_add(1);
-        }
-
-        // ── END STUB ────────────────────────────────────────────────────
+        step_players_stub(&mut session.players, input_buf, seated);
 
         // Update frame counter
         session.frame = frame;
         hidden.frame = frame;
 
         // Write to frame log ring buffer
-        let _log_entry = compress_frame(frame, &session.players, session.stage, input_buf);
+        let log_entry = compress_frame(frame, &session.players, session.stage, input_buf);
         let write_idx = (frame_log.write_index as usize) % RING_BUFFER_SIZE;
         // In production, write directly to account data via zero-copy:
         //   let offset = HEADER_SIZE + write_idx * COMPRESSED_FRAME_SIZE;
@@ -136,6 +115,13 @@ pub mod run_inference {
         frame_log.write_index = ((write_idx + 1) % RING_BUFFER_SIZE) as u16;
         frame_log.total_frames = frame;
 
+        if frame_log.emit_frames {
+            emit!(SpectatorFrame {
+                session: session_key,
+                frame: log_entry,
+            });
+        }
+
         Ok(ctx.accounts)
     }
 
@@ -152,6 +138,55 @@ pub mod run_inference {
     // pub weight_shard_1: WeightShard,
 }
 
+/// Stub dynamics (stub-inference): apply controller inputs as velocity to
+/// the seated players.
+fn step_players_stub(players: &mut [PlayerState; 2], input_buf: &InputBuffer, seated: [bool; 2]) {
+    for player_idx in (0..2).filter(|&i| seated[i]) {
+        let input = if player_idx == 0 {
+            &input_buf.player1
+        } else {
+            &input_buf.player2
+        };
+
+        let p = &mut players[player_idx];
+
+        // Apply stick input as velocity (simplified physics)
+        let stick_x = input.stick_x as i32;
+        let stick_y = input.stick_y as i32;
+
+        // Move based on stick (scale by 2 for reasonable speed)
+        p.x += stick_x * 2;
+        p.y += stick_y * 2;
+
+        // Apply gravity if airborne
+        if p.on_ground == 0 {
+            p.speed_y -= 4; // Gravity (fixed-point)
+            p.y += p.speed_y as i32;
+            // Landing is left to the stage-collision system
+        }
+
+        // Jump (button A)
+        if input.buttons & 0x01 != 0 && p.jumps_left > 0 {
+            p.speed_y = 40; // Jump velocity (fixed-point)
+            p.on_ground = 0;
+            p.jumps_left = p.jumps_left.saturating_sub(1);
+        }
+
+        // Update facing based on stick direction
+        if stick_x > 10 {
+            p.facing = 1;
+        } else if stick_x < -10 {
+            p.facing = 0;
+        }
+
+        // Update ground speed
+        p.speed_ground_x = (stick_x * 2).clamp(-32767, 32767) as i16;
+
+        // Increment state age
+        p.state_age = p.state_age.saturating_add(1);
+    }
+}
+
 /// Compress a full frame state into the compact ring buffer format.
 fn compress_frame(
     frame: u32,
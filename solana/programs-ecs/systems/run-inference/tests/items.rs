@@ -0,0 +1,45 @@
+//! Item slots in the model input vector.
+
+use input_buffer::ControllerInput;
+use item_state::{Item, ITEM_BOMB, ITEM_HEART, MAX_ITEMS, OWNER_NONE};
+use model_manifest::{
+    EncoderSpec, FieldSpec, FIELD_ITEM_KIND, FIELD_ITEM_OWNER, FIELD_ITEM_X, FIELD_KIND_LINEAR,
+};
+use run_inference::mamba2::{encode_input, encode_items};
+use session_state::PlayerState;
+
+fn item_field(source: u8, divisor: u16) -> FieldSpec {
+    FieldSpec { source, kind: FIELD_KIND_LINEAR, width: MAX_ITEMS as u8, divisor }
+}
+
+#[test]
+fn test_items_fill_their_slots() {
+    // melee_v2 (24 slots per player, then stage) plus three item fields
+    let mut spec = EncoderSpec::melee_v2();
+    spec.global_fields[1] = item_field(FIELD_ITEM_KIND, 1);
+    spec.global_fields[2] = item_field(FIELD_ITEM_X, 256);
+    spec.global_fields[3] = item_field(FIELD_ITEM_OWNER, 1);
+    spec.num_global_fields = 4;
+
+    let items = [
+        Item { kind: ITEM_HEART, owner: OWNER_NONE, x: 10 * 256, ..Default::default() },
+        Item::default(),
+        Item { kind: ITEM_BOMB, owner: 1, x: -20 * 256, ..Default::default() },
+        Item::default(),
+    ];
+    let players = [PlayerState::default(), PlayerState::default()];
+    let inputs = [ControllerInput::default(), ControllerInput::default()];
+    let d_model = 64;
+    let mut out = vec![0i8; d_model];
+    encode_input(&spec, &players, &inputs, 31, &mut out, d_model);
+    let without_items = out.clone();
+    encode_items(&spec, &items, &mut out, d_model);
+
+    assert_eq!(out[48], 31);
+    assert_eq!(out[49..53], [ITEM_HEART as i8, 0, ITEM_BOMB as i8, 0]);
+    assert_eq!(out[53..57], [10, 0, -20, 0]);
+    assert_eq!(out[57..61], [-1, 0, 1, 0]);
+    // Everything else is encode_input's
+    assert_eq!(out[..49], without_items[..49]);
+    assert_eq!(out[61..], without_items[61..]);
+}
@@ -0,0 +1,373 @@
+//! Parity between the monolithic (programs/world-model) and ECS
+//! (run-inference) inference paths.
+//!
+//! Both run the layer math through awm-kernel, but each keeps its own glue
+//! over its own account types: tensor-table lookup, state encode/decode,
+//! and forward_pass. These tests push identical inputs through both and
+//! require identical outputs, so the two paths can't drift apart silently.
+
+use awm_kernel::mamba2::{HiddenStateMut, Mamba2Config};
+use awm_kernel::matmul::Rounding;
+use run_inference::mamba2 as ecs;
+use world_model::inference as wm;
+use world_model::state as wm_state;
+
+/// Deterministic xorshift byte stream, so any failure reproduces.
+struct Bytes(u64);
+
+impl Bytes {
+    fn next(&mut self) -> u8 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 24) as u8
+    }
+
+    fn u8s(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next()).collect()
+    }
+
+    fn i8s(&mut self, n: usize) -> Vec<i8> {
+        (0..n).map(|_| self.next() as i8).collect()
+    }
+
+    fn i16(&mut self) -> i16 {
+        i16::from_le_bytes([self.next(), self.next()])
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes([self.next(), self.next()])
+    }
+
+    fn i32(&mut self) -> i32 {
+        i32::from_le_bytes([self.next(), self.next(), self.next(), self.next()])
+    }
+}
+
+fn random_player(rng: &mut Bytes) -> wm_state::PlayerState {
+    wm_state::PlayerState {
+        x: rng.i32() >> 8,
+        y: rng.i32() >> 8,
+        percent: rng.u16() % 1000,
+        shield_strength: rng.u16(),
+        speed_air_x: rng.i16(),
+        speed_y: rng.i16(),
+        speed_ground_x: rng.i16(),
+        speed_attack_x: rng.i16(),
+        speed_attack_y: rng.i16(),
+        state_age: rng.u16(),
+        hitlag: rng.next(),
+        stocks: rng.next() % 5,
+        facing: rng.next() & 1,
+        on_ground: rng.next() & 1,
+        action_state: rng.u16() % 400,
+        jumps_left: rng.next() % 8,
+        character: rng.next() % 33,
+    }
+}
+
+fn random_input(rng: &mut Bytes) -> wm_state::ControllerInput {
+    wm_state::ControllerInput {
+        stick_x: rng.next() as i8,
+        stick_y: rng.next() as i8,
+        c_stick_x: rng.next() as i8,
+        c_stick_y: rng.next() as i8,
+        trigger_l: rng.next(),
+        trigger_r: rng.next(),
+        buttons: rng.next(),
+        buttons_ext: rng.next(),
+    }
+}
+
+fn to_ecs_player(p: &wm_state::PlayerState) -> session_state::PlayerState {
+    session_state::PlayerState {
+        x: p.x,
+        y: p.y,
+        percent: p.percent,
+        shield_strength: p.shield_strength,
+        speed_air_x: p.speed_air_x,
+        speed_y: p.speed_y,
+        speed_ground_x: p.speed_ground_x,
+        speed_attack_x: p.speed_attack_x,
+        speed_attack_y: p.speed_attack_y,
+        state_age: p.state_age,
+        hitlag: p.hitlag,
+        stocks: p.stocks,
+        facing: p.facing,
+        on_ground: p.on_ground,
+        action_state: p.action_state,
+        jumps_left: p.jumps_left,
+        character: p.character,
+    }
+}
+
+fn to_ecs_input(c: &wm_state::ControllerInput) -> input_buffer::ControllerInput {
+    input_buffer::ControllerInput {
+        stick_x: c.stick_x,
+        stick_y: c.stick_y,
+        c_stick_x: c.c_stick_x,
+        c_stick_y: c.c_stick_y,
+        trigger_l: c.trigger_l,
+        trigger_r: c.trigger_r,
+        buttons: c.buttons,
+        buttons_ext: c.buttons_ext,
+    }
+}
+
+/// (slot, shard, offset, rows, cols) for every tensor of every layer.
+type Layout = Vec<[(usize, u8, u32, u16, u16); wm_state::TENSORS_PER_LAYER]>;
+
+/// Random weights spread over two shards (alternating by slot), so the
+/// shard index in each table entry is exercised too.
+fn random_model(rng: &mut Bytes, config: &Mamba2Config) -> (Vec<Vec<u8>>, Layout) {
+    let (d_model, d_inner) = (config.d_model as u16, config.d_inner as u16);
+    let mut shards = vec![Vec::new(), Vec::new()];
+    let mut layout = Vec::new();
+
+    for _ in 0..config.num_layers {
+        let shapes = [
+            (wm_state::TENSOR_IN_PROJ, 2 * d_inner, d_model),
+            (wm_state::TENSOR_OUT_PROJ, d_model, d_inner),
+            (wm_state::TENSOR_NORM, d_model, 1),
+            (wm_state::TENSOR_A_LOG, d_inner, 1),
+            (wm_state::TENSOR_DT_BIAS, d_inner, 1),
+            (wm_state::TENSOR_DT_PROJ, config.dt_heads() as u16, d_model),
+        ];
+        let mut layer = [(0, 0, 0, 0, 0); wm_state::TENSORS_PER_LAYER];
+        for (i, (slot, rows, cols)) in shapes.into_iter().enumerate() {
+            let shard = (slot % 2) as u8;
+            let data = &mut shards[shard as usize];
+            layer[i] = (slot, shard, data.len() as u32, rows, cols);
+            data.extend(rng.u8s(rows as usize * cols as usize));
+        }
+        layout.push(layer);
+    }
+
+    (shards, layout)
+}
+
+fn wm_table(layout: &Layout) -> Vec<[wm_state::TensorRef; wm_state::TENSORS_PER_LAYER]> {
+    layout
+        .iter()
+        .map(|layer| {
+            let mut refs = [wm_state::TensorRef::default(); wm_state::TENSORS_PER_LAYER];
+            for &(slot, shard, offset, rows, cols) in layer {
+                refs[slot] = wm_state::TensorRef {
+                    shard,
+                    dtype: wm_state::TENSOR_DTYPE_I8,
+                    offset,
+                    rows,
+                    cols,
+                };
+            }
+            refs
+        })
+        .collect()
+}
+
+fn ecs_table(layout: &Layout) -> Vec<[model_manifest::TensorRef; model_manifest::TENSORS_PER_LAYER]> {
+    layout
+        .iter()
+        .map(|layer| {
+            let mut refs: [model_manifest::TensorRef; model_manifest::TENSORS_PER_LAYER] =
+                Default::default();
+            for &(slot, shard, offset, rows, cols) in layer {
+                refs[slot] = model_manifest::TensorRef {
+                    shard,
+                    dtype: model_manifest::TENSOR_DTYPE_I8,
+                    offset,
+                    rows,
+                    cols,
+                };
+            }
+            refs
+        })
+        .collect()
+}
+
+macro_rules! assert_fields_eq {
+    ($a:expr, $b:expr, $($field:ident),*) => {
+        $( assert_eq!($a.$field, $b.$field, stringify!($field)); )*
+    };
+}
+
+#[test]
+fn encode_input_matches() {
+    let wm_spec = wm_state::EncoderSpec::melee_v2();
+    let ecs_spec = model_manifest::EncoderSpec::melee_v2();
+    let d_model = 64;
+    let mut rng = Bytes(0x5eed_0001);
+
+    for _ in 0..256 {
+        let players = [random_player(&mut rng), random_player(&mut rng)];
+        let inputs = [random_input(&mut rng), random_input(&mut rng)];
+        let stage = rng.next();
+
+        let mut wm_out = vec![0i8; d_model];
+        wm::encode_input(&wm_spec, None, &players, &inputs, stage, &mut wm_out, d_model);
+
+        let ecs_players = [to_ecs_player(&players[0]), to_ecs_player(&players[1])];
+        let ecs_inputs = [to_ecs_input(&inputs[0]), to_ecs_input(&inputs[1])];
+        let mut ecs_out = vec![0i8; d_model];
+        ecs::encode_input(&ecs_spec, &ecs_players, &ecs_inputs, stage, &mut ecs_out, d_model);
+
+        assert_eq!(wm_out, ecs_out);
+    }
+}
+
+#[test]
+fn decode_output_matches() {
+    let mut wm_spec = wm_state::EncoderSpec::melee_v2();
+    let mut ecs_spec = model_manifest::EncoderSpec::melee_v2();
+    let mut rng = Bytes(0x5eed_0002);
+
+    for i in 0..256 {
+        // Alternate a categorical action_state head (8 classes) with melee_v2's
+        let classes = if i % 2 == 0 { 8 } else { 0 };
+        wm_spec.player_fields[14] = if classes > 0 {
+            wm_state::FieldSpec::categorical(wm_state::FIELD_ACTION_STATE, classes)
+        } else {
+            wm_state::FieldSpec::linear(wm_state::FIELD_ACTION_STATE, 1)
+        };
+        ecs_spec.player_fields[14] = if classes > 0 {
+            model_manifest::FieldSpec::categorical(model_manifest::FIELD_ACTION_STATE, classes)
+        } else {
+            model_manifest::FieldSpec::linear(model_manifest::FIELD_ACTION_STATE, 1)
+        };
+
+        let output = rng.i8s(64);
+        let luts = rng.u8s(awm_kernel::lut::LUT_TOTAL_SIZE);
+        // Single-headed: player 2's fields follow player 1's
+        let offsets = [0, wm_spec.player_width()];
+        let wm_players = wm::decode_output(&wm_spec, None, &luts, &output, offsets);
+        let ecs_players = ecs::decode_output(&ecs_spec, &luts, &output);
+
+        for (a, b) in wm_players.iter().zip(ecs_players.iter()) {
+            assert_fields_eq!(
+                a, b,
+                x, y, percent, shield_strength, speed_air_x, speed_y, speed_ground_x,
+                speed_attack_x, speed_attack_y, state_age, hitlag, stocks, facing, on_ground,
+                action_state, action_state_prob, jumps_left, character
+            );
+        }
+    }
+}
+
+#[test]
+fn forward_pass_matches() {
+    let config = Mamba2Config {
+        d_model: 16,
+        d_inner: 32,
+        d_state: 4,
+        num_layers: 3,
+        num_heads: 1,
+        ngroups: 0,
+        dt_shift: 0,
+        rounding: Rounding::Truncate,
+    };
+    let mut rng = Bytes(0x5eed_0003);
+
+    for _ in 0..32 {
+        let (shards, layout) = random_model(&mut rng, &config);
+        let shard_refs: Vec<&[u8]> = shards.iter().map(|s| &s[..]).collect();
+        let luts = rng.u8s(awm_kernel::lut::LUT_TOTAL_SIZE);
+        let scale = |rng: &mut Bytes, n: usize| -> Vec<u16> {
+            (0..n).map(|_| 128 + rng.next() as u16).collect()
+        };
+        let in_scales: Vec<Vec<u16>> =
+            (0..config.num_layers).map(|_| scale(&mut rng, 2 * config.d_inner)).collect();
+        let out_scales: Vec<Vec<u16>> =
+            (0..config.num_layers).map(|_| scale(&mut rng, config.d_model)).collect();
+        let in_refs: Vec<&[u16]> = in_scales.iter().map(|s| &s[..]).collect();
+        let out_refs: Vec<&[u16]> = out_scales.iter().map(|s| &s[..]).collect();
+
+        let input = rng.i8s(config.d_model);
+        let hidden = rng.i8s(config.num_layers * config.d_inner * config.d_state);
+
+        let mut wm_hidden = hidden.clone();
+        let wm_out = wm::forward_pass(
+            &input,
+            HiddenStateMut::I8(&mut wm_hidden),
+            &shard_refs,
+            &wm_table(&layout),
+            None,
+            &[],
+            &luts,
+            &config,
+            &in_refs,
+            &out_refs,
+            &mut [],
+            &mut [],
+        );
+
+        let mut ecs_hidden = hidden.clone();
+        let ecs_out = ecs::forward_pass(
+            &input,
+            &mut ecs_hidden,
+            &shard_refs,
+            &ecs_table(&layout),
+            &luts,
+            &config,
+            &in_refs,
+            &out_refs,
+        );
+
+        assert!(wm_out.is_some());
+        assert_eq!(wm_out, ecs_out);
+        assert_eq!(wm_hidden, ecs_hidden);
+    }
+}
+
+#[test]
+fn forward_pass_rejects_same_bad_tables() {
+    // A wrong shape in either table must fail both paths the same way
+    let config = Mamba2Config {
+        d_model: 8,
+        d_inner: 16,
+        d_state: 2,
+        num_layers: 1,
+        num_heads: 1,
+        ngroups: 0,
+        dt_shift: 0,
+        rounding: Rounding::Truncate,
+    };
+    let mut rng = Bytes(0x5eed_0004);
+    let (shards, mut layout) = random_model(&mut rng, &config);
+    let shard_refs: Vec<&[u8]> = shards.iter().map(|s| &s[..]).collect();
+    layout[0][1].3 += 1;
+
+    let luts = rng.u8s(awm_kernel::lut::LUT_TOTAL_SIZE);
+    let in_scales = vec![256u16; 2 * config.d_inner];
+    let out_scales = vec![256u16; config.d_model];
+    let input = rng.i8s(config.d_model);
+    let mut wm_hidden = vec![0i8; config.d_inner * config.d_state];
+    let mut ecs_hidden = wm_hidden.clone();
+
+    let wm_out = wm::forward_pass(
+        &input,
+        HiddenStateMut::I8(&mut wm_hidden),
+        &shard_refs,
+        &wm_table(&layout),
+        None,
+        &[],
+        &luts,
+        &config,
+        &[&in_scales],
+        &[&out_scales],
+        &mut [],
+        &mut [],
+    );
+    let ecs_out = ecs::forward_pass(
+        &input,
+        &mut ecs_hidden,
+        &shard_refs,
+        &ecs_table(&layout),
+        &luts,
+        &config,
+        &[&in_scales],
+        &[&out_scales],
+    );
+
+    assert!(wm_out.is_none());
+    assert!(ecs_out.is_none());
+}
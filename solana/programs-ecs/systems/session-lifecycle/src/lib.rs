@@ -1,11 +1,14 @@
+use awm_kernel::rng::substream;
 use bolt_lang::*;
-use frame_log::FrameLog;
+use frame_log::{CompressedFrame, FrameLog};
 use hidden_state::HiddenState;
-use input_buffer::InputBuffer;
+use input_buffer::{ControllerInput, InputBuffer};
 use session_state::{
-    PlayerState, SessionState, STATUS_ACTIVE,
-    STATUS_CREATED, STATUS_ENDED, STATUS_WAITING_PLAYERS,
+    PlayerState, SessionState, END_REASON_NONE, NUM_PLAYERS, SESSION_KIND_MATCH,
+    SESSION_KIND_PERSISTENT, STATUS_ACTIVE, STATUS_CREATED, STATUS_ENDED, STATUS_WAITING_PLAYERS,
+    TICK_RATE_120, TICK_RATE_30, TICK_RATE_60,
 };
+use stage_geometry::{StageGeometry, STAGE_FINAL_DESTINATION};
 
 declare_id!("4ozheJvvMhG7yMrp1UR2kq1fhRvjXoY5Pn3NJ4nvAcyE");
 
@@ -13,6 +16,17 @@ declare_id!("4ozheJvvMhG7yMrp1UR2kq1fhRvjXoY5Pn3NJ4nvAcyE");
 pub const ACTION_CREATE: u8 = 0;
 pub const ACTION_JOIN: u8 = 1;
 pub const ACTION_END: u8 = 2;
+pub const ACTION_CLAIM_SLOT: u8 = 3;
+pub const ACTION_RELEASE_SLOT: u8 = 4;
+pub const ACTION_ARCHIVE: u8 = 5;
+
+/// Sub-stream of the session seed that spawn layouts are drawn from
+pub const SPAWN_STREAM: u64 = 0x5350_4157_4E00_0000;
+
+/// Spawn distance from stage center, as a share of the main platform's
+/// half width in 1/256 (FD's old fixed ±30 units is ~90)
+pub const SPAWN_SPREAD_MIN: i32 = 64;
+pub const SPAWN_SPREAD_MAX: i32 = 160;
 
 #[error_code]
 pub enum LifecycleError {
@@ -22,6 +36,24 @@ pub enum LifecycleError {
     InvalidStateTransition,
     #[msg("Cannot join your own session")]
     CannotJoinOwnSession,
+    #[msg("Tick rate must be 30, 60 or 120 fps")]
+    InvalidTickRate,
+    #[msg("Unknown session kind")]
+    InvalidSessionKind,
+    #[msg("Only persistent sessions have claimable slots and an archive")]
+    NotPersistent,
+    #[msg("Persistent sessions never end")]
+    PersistentSessionNeverEnds,
+    #[msg("Both controller slots are taken")]
+    NoOpenSlot,
+    #[msg("The default pubkey can't hold a slot")]
+    InvalidPlayer,
+    #[msg("Player already holds a slot in this session")]
+    AlreadyHoldsSlot,
+    #[msg("Player doesn't hold a slot in this session")]
+    NotSlotHolder,
+    #[msg("Archive frame is behind the last archive or ahead of the log")]
+    InvalidArchiveFrame,
 }
 
 /// Session lifecycle system — manages session creation, joining, and ending.
@@ -30,6 +62,9 @@ pub enum LifecycleError {
 ///   CREATE = insert cartridge (select model, allocate accounts)
 ///   JOIN   = plug in controller (player 2 connects)
 ///   END    = power off (commit state to mainnet, reclaim rent)
+///   CLAIM_SLOT / RELEASE_SLOT = plug a controller into, or pull it out
+///            of, a console left running (persistent worlds)
+///   ARCHIVE = record that the FrameLog ring was copied out
 ///
 /// Session flow:
 ///   1. Player 1 calls CREATE with model reference and character selection
@@ -41,24 +76,47 @@ pub enum LifecycleError {
 ///
 ///   2. Player 2 calls JOIN with session ID and character selection
 ///      → SessionState: WaitingPlayers → Active
-///      → Players' initial state set (start positions, 4 stocks, etc.)
+///      → Players' initial state set (4 stocks, etc.), with spawn positions
+///        and port sides drawn from the seed and stage
+///      → FrameLog: keyframe holds that frame 0
 ///
 ///   3. Either player calls END (or auto-end after max_frames)
 ///      → SessionState: Active → Ended
-///      → Accounts undelegated back to mainnet
+///
+///   4. settle-session builds the SessionResult and updates PlayerProfiles
+///      → SessionState: Ended → Settled
+///      → Accounts committed and undelegated back to mainnet
 ///      → Session accounts closeable for rent reclaim
+///
+/// Persistent worlds (kind = SESSION_KIND_PERSISTENT) skip the match flow:
+///   1. CREATE starts the world Active with both slots open; it never has
+///      a max_frames and END refuses it, so it never settles
+///   2. CLAIM_SLOT seats a player in the first open slot, spawned at that
+///      port's seed-derived spawn on life 1; RELEASE_SLOT empties it again.
+///      Open slots aren't waited on or stepped, and the hidden state keeps
+///      accumulating across players coming and going
+///   3. KOs respawn rather than eliminate: stocks count lives
+///   4. An archiver copies the FrameLog ring out and calls ARCHIVE with
+///      how far it got; run-inference stalls rather than overwrite frames
+///      it hasn't archived
 #[system]
 pub mod session_lifecycle {
 
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
         let session = &mut ctx.accounts.session_state;
         let hidden = &mut ctx.accounts.hidden_state;
+        let input = &mut ctx.accounts.input_buffer;
         let frame_log = &mut ctx.accounts.frame_log;
 
         match args.action {
             ACTION_CREATE => create_session(session, hidden, frame_log, &args),
-            ACTION_JOIN => join_session(session, &args),
+            ACTION_JOIN => join_session(session, frame_log, &args),
             ACTION_END => end_session(session),
+            ACTION_CLAIM_SLOT => {
+                claim_slot(session, input, args.player, args.character).map(|_| ())
+            }
+            ACTION_RELEASE_SLOT => release_slot(session, input, args.player).map(|_| ()),
+            ACTION_ARCHIVE => archive_frames(session, frame_log, args.archived_frames),
             _ => return Err(LifecycleError::InvalidAction.into()),
         }?;
 
@@ -75,11 +133,12 @@ pub mod session_lifecycle {
 
     #[arguments]
     pub struct Args {
-        /// Action: 0=create, 1=join, 2=end
+        /// Action: 0=create, 1=join, 2=end, 3=claim slot, 4=release slot,
+        /// 5=archive
         pub action: u8,
         /// Player public key
         pub player: Pubkey,
-        /// Character ID (0-32) for the joining player
+        /// Character ID (0-32) for the joining or claiming player
         pub character: u8,
         /// Stage ID (0-32) — only used on CREATE
         pub stage: u8,
@@ -95,6 +154,14 @@ pub mod session_lifecycle {
         pub d_state: u16,
         /// Model num_layers — used to configure hidden state on CREATE
         pub num_layers: u8,
+        /// Push every frame as a SpectatorFrame event — only used on CREATE
+        pub emit_frames: bool,
+        /// Frames per second (30, 60 or 120) — only used on CREATE
+        pub tick_rate: u8,
+        /// SESSION_KIND_* — only used on CREATE
+        pub kind: u8,
+        /// Frames copied out of the FrameLog so far — only used on ARCHIVE
+        pub archived_frames: u32,
     }
 }
 
@@ -109,6 +176,14 @@ fn create_session(
         session.status == STATUS_CREATED || session.status == 0,
         LifecycleError::InvalidStateTransition
     );
+    require!(
+        matches!(args.tick_rate, TICK_RATE_30 | TICK_RATE_60 | TICK_RATE_120),
+        LifecycleError::InvalidTickRate
+    );
+    require!(
+        matches!(args.kind, SESSION_KIND_MATCH | SESSION_KIND_PERSISTENT),
+        LifecycleError::InvalidSessionKind
+    );
 
     // Initialize session
     session.status = STATUS_WAITING_PLAYERS;
@@ -119,6 +194,9 @@ fn create_session(
     session.stage = args.stage;
     session.model = args.model;
     session.seed = args.seed;
+    session.end_reason = END_REASON_NONE;
+    session.tick_rate = args.tick_rate;
+    session.kind = args.kind;
 
     // Set player 1's character
     session.players[0] = PlayerState::default();
@@ -136,6 +214,20 @@ fn create_session(
     // Initialize frame log
     frame_log.write_index = 0;
     frame_log.total_frames = 0;
+    frame_log.emit_frames = args.emit_frames;
+    frame_log.archived_frames = 0;
+
+    if session.is_persistent() {
+        // Nobody is seated yet and the world never ends: open both slots
+        // and start running
+        session.max_frames = 0;
+        session.player1 = Pubkey::default();
+        session.players = Default::default();
+        session.status = STATUS_ACTIVE;
+        frame_log.keyframe = spawn_keyframe(&session.players, session.stage);
+        msg!("Persistent world created: stage={}, model={}", args.stage, args.model);
+        return Ok(());
+    }
 
     // Clock timestamp would be set here in production:
     // session.created_at = Clock::get()?.unix_timestamp;
@@ -147,6 +239,7 @@ fn create_session(
 
 fn join_session(
     session: &mut Account<SessionState>,
+    frame_log: &mut Account<FrameLog>,
     args: &session_lifecycle::Args,
 ) -> Result<()> {
     require!(
@@ -165,22 +258,13 @@ fn join_session(
     session.players[1].character = args.character;
     session.players[1].stocks = 4;
 
-    // Set initial positions (stage-dependent, using FD defaults)
-    // Player 1: left side, Player 2: right side
-    // Fixed-point: multiply by 256
-    session.players[0].x = -30 * 256;  // -30.0 game units
-    session.players[0].y = 0;
-    session.players[0].facing = 1;     // Facing right
-    session.players[0].on_ground = 1;
-    session.players[0].jumps_left = 2;
-    session.players[0].shield_strength = 60 * 256;
-
-    session.players[1].x = 30 * 256;   // 30.0 game units
-    session.players[1].y = 0;
-    session.players[1].facing = 0;     // Facing left
-    session.players[1].on_ground = 1;
-    session.players[1].jumps_left = 2;
-    session.players[1].shield_strength = 60 * 256;
+    // Initial positions, facing and sides come from the seed and stage, so
+    // a replay re-derives the same frame 0
+    let spawns = spawn_positions(session.seed, session.stage);
+    for (p, &spawn) in session.players.iter_mut().zip(spawns.iter()) {
+        place(p, spawn);
+    }
+    frame_log.keyframe = spawn_keyframe(&session.players, session.stage);
 
     // Activate session
     session.status = STATUS_ACTIVE;
@@ -191,7 +275,66 @@ fn join_session(
     Ok(())
 }
 
+/// Spawn (x, y, facing) per port for a session seed on `stage`: both
+/// players on the main platform, mirrored about its center at a
+/// seed-drawn spread of its half width, facing each other. A seed bit
+/// picks which port starts on the left. Stages outside the legal table
+/// spawn on Final Destination's layout.
+pub fn spawn_positions(seed: u64, stage: u8) -> [(i32, i32, u8); 2] {
+    let mut geometry = StageGeometry::default();
+    if !geometry.load_legal(stage) {
+        geometry.load_legal(STAGE_FINAL_DESTINATION);
+    }
+    let roll = substream(seed, SPAWN_STREAM ^ stage as u64);
+    let range = (SPAWN_SPREAD_MAX - SPAWN_SPREAD_MIN + 1) as u64;
+    let spread = SPAWN_SPREAD_MIN + (roll % range) as i32;
+    let center = (geometry.ground_left + geometry.ground_right) / 2;
+    let offset = (geometry.ground_right - center) * spread / 256;
+
+    let left = (center - offset, geometry.ground_y, 1);
+    let right = (center + offset, geometry.ground_y, 0);
+    if (roll >> 32) & 1 == 0 {
+        [left, right]
+    } else {
+        [right, left]
+    }
+}
+
+/// Stand a player at a spawn, grounded with full jumps and shield.
+fn place(p: &mut PlayerState, (x, y, facing): (i32, i32, u8)) {
+    p.x = x;
+    p.y = y;
+    p.facing = facing;
+    p.on_ground = 1;
+    p.jumps_left = 2;
+    p.shield_strength = 60 * 256;
+}
+
+/// Frame 0 in the FrameLog's compressed format (no inputs yet).
+fn spawn_keyframe(players: &[PlayerState; 2], stage: u8) -> CompressedFrame {
+    let (p1, p2) = (&players[0], &players[1]);
+    CompressedFrame {
+        frame: 0,
+        p1_x: (p1.x / 256) as i16,
+        p1_y: (p1.y / 256) as i16,
+        p1_stocks: p1.stocks,
+        p1_facing: p1.facing,
+        p1_on_ground: p1.on_ground,
+        p2_x: (p2.x / 256) as i16,
+        p2_y: (p2.y / 256) as i16,
+        p2_stocks: p2.stocks,
+        p2_facing: p2.facing,
+        p2_on_ground: p2.on_ground,
+        stage,
+        ..Default::default()
+    }
+}
+
 fn end_session(session: &mut Account<SessionState>) -> Result<()> {
+    require!(
+        !session.is_persistent(),
+        LifecycleError::PersistentSessionNeverEnds
+    );
     require!(
         session.status == STATUS_ACTIVE || session.status == STATUS_WAITING_PLAYERS,
         LifecycleError::InvalidStateTransition
@@ -200,10 +343,119 @@ fn end_session(session: &mut Account<SessionState>) -> Result<()> {
     session.status = STATUS_ENDED;
     msg!("Session ended at frame {}", session.frame);
 
-    // In production:
-    // - Undelegate all session accounts back to mainnet
-    // - Mark accounts as closeable for rent reclaim
-    // - Emit final state as event for indexers
+    // settle-session takes it from here: result, replay, profiles, and
+    // the Settled status the client commits and undelegates on
 
     Ok(())
 }
+
+/// Seat `player` in a persistent world's first open slot, returning it.
+/// They spawn at that port's seed-derived spawn, on their first life, and
+/// the slot's input starts neutral and unsubmitted.
+pub fn claim_slot(
+    session: &mut SessionState,
+    input: &mut InputBuffer,
+    player: Pubkey,
+    character: u8,
+) -> Result<usize> {
+    require!(session.is_persistent(), LifecycleError::NotPersistent);
+    require!(
+        session.status == STATUS_ACTIVE,
+        LifecycleError::InvalidStateTransition
+    );
+    require!(player != Pubkey::default(), LifecycleError::InvalidPlayer);
+    require!(
+        session.slot_of(&player).is_none(),
+        LifecycleError::AlreadyHoldsSlot
+    );
+    let slot = (0..NUM_PLAYERS)
+        .find(|&slot| session.slot_open(slot))
+        .ok_or(LifecycleError::NoOpenSlot)?;
+
+    if slot == 0 {
+        session.player1 = player;
+    } else {
+        session.player2 = player;
+    }
+    let p = &mut session.players[slot];
+    *p = PlayerState::default();
+    p.character = character;
+    p.stocks = 1;
+    place(p, spawn_positions(session.seed, session.stage)[slot]);
+    unplug(input, slot);
+
+    msg!(
+        "Player {} claimed slot {} at frame {}",
+        player,
+        slot + 1,
+        session.frame
+    );
+    Ok(slot)
+}
+
+/// Empty the slot `player` holds in a persistent world, returning it. The
+/// world runs on without them.
+pub fn release_slot(
+    session: &mut SessionState,
+    input: &mut InputBuffer,
+    player: Pubkey,
+) -> Result<usize> {
+    require!(session.is_persistent(), LifecycleError::NotPersistent);
+    require!(
+        session.status == STATUS_ACTIVE,
+        LifecycleError::InvalidStateTransition
+    );
+    let slot = session
+        .slot_of(&player)
+        .ok_or(LifecycleError::NotSlotHolder)?;
+
+    if slot == 0 {
+        session.player1 = Pubkey::default();
+    } else {
+        session.player2 = Pubkey::default();
+    }
+    session.players[slot] = PlayerState::default();
+    unplug(input, slot);
+
+    msg!(
+        "Player {} released slot {} at frame {}",
+        player,
+        slot + 1,
+        session.frame
+    );
+    Ok(slot)
+}
+
+/// Record that an archiver has copied a persistent world's FrameLog out
+/// through `archived_frames`, freeing that much of the ring.
+pub fn archive_frames(
+    session: &SessionState,
+    frame_log: &mut FrameLog,
+    archived_frames: u32,
+) -> Result<()> {
+    require!(session.is_persistent(), LifecycleError::NotPersistent);
+    require!(
+        archived_frames >= frame_log.archived_frames
+            && archived_frames <= frame_log.total_frames,
+        LifecycleError::InvalidArchiveFrame
+    );
+
+    frame_log.archived_frames = archived_frames;
+    msg!(
+        "FrameLog archived through frame {} of {}",
+        archived_frames,
+        frame_log.total_frames
+    );
+    Ok(())
+}
+
+/// Clear a slot's controller so a stale input never drives its next owner.
+fn unplug(input: &mut InputBuffer, slot: usize) {
+    if slot == 0 {
+        input.player1 = ControllerInput::default();
+        input.p1_ready = false;
+    } else {
+        input.player2 = ControllerInput::default();
+        input.p2_ready = false;
+    }
+}
@@ -0,0 +1,88 @@
+//! Claiming and releasing slots, and archiving, in a persistent world.
+
+use bolt_lang::Pubkey;
+use frame_log::{FrameLog, RING_BUFFER_SIZE};
+use input_buffer::InputBuffer;
+use session_lifecycle::{archive_frames, claim_slot, release_slot, spawn_positions};
+use session_state::{SessionState, SESSION_KIND_PERSISTENT, STATUS_ACTIVE};
+use stage_geometry::STAGE_BATTLEFIELD;
+
+fn world() -> SessionState {
+    SessionState {
+        status: STATUS_ACTIVE,
+        kind: SESSION_KIND_PERSISTENT,
+        stage: STAGE_BATTLEFIELD,
+        seed: 7,
+        frame: 5_000,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_claims_fill_open_slots_and_releases_free_them() {
+    let mut session = world();
+    let mut input = InputBuffer::default();
+    let (alice, bob, carol) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+    assert_eq!(claim_slot(&mut session, &mut input, alice, 2).unwrap(), 0);
+    assert!(claim_slot(&mut session, &mut input, alice, 2).is_err(), "double claim");
+    assert_eq!(claim_slot(&mut session, &mut input, bob, 9).unwrap(), 1);
+    assert!(claim_slot(&mut session, &mut input, carol, 1).is_err(), "no open slot");
+
+    // Claimed players spawn at their port's spawn, on their first life
+    let spawns = spawn_positions(session.seed, session.stage);
+    for (slot, character) in [(0, 2), (1, 9)] {
+        let p = &session.players[slot];
+        assert_eq!((p.x, p.y, p.facing), spawns[slot]);
+        assert_eq!((p.character, p.stocks, p.on_ground), (character, 1, 1));
+    }
+
+    // Releasing empties the slot and its input; the world runs on
+    input.p1_ready = true;
+    input.player1.stick_x = 90;
+    assert!(release_slot(&mut session, &mut input, carol).is_err(), "not seated");
+    assert_eq!(release_slot(&mut session, &mut input, alice).unwrap(), 0);
+    assert!(session.slot_open(0) && !session.slot_open(1));
+    assert_eq!(session.players[0].stocks, 0);
+    assert!(!input.p1_ready && input.player1.stick_x == 0);
+    assert_eq!((session.status, session.frame), (STATUS_ACTIVE, 5_000));
+
+    // The freed slot goes to whoever claims next
+    assert_eq!(claim_slot(&mut session, &mut input, carol, 1).unwrap(), 0);
+    assert_eq!(session.slot_owners(), [carol, bob]);
+}
+
+#[test]
+fn test_slots_are_persistent_only() {
+    let mut session = SessionState {
+        status: STATUS_ACTIVE,
+        ..Default::default()
+    };
+    let mut input = InputBuffer::default();
+    let player = Pubkey::new_unique();
+    assert!(claim_slot(&mut session, &mut input, player, 2).is_err());
+    assert!(release_slot(&mut session, &mut input, player).is_err());
+    assert!(!session.slot_open(0), "a match's slots are never open");
+
+    // The default key never holds a slot
+    let mut session = world();
+    assert!(claim_slot(&mut session, &mut input, Pubkey::default(), 2).is_err());
+}
+
+#[test]
+fn test_archive_frees_the_ring() {
+    let session = world();
+    let mut log = FrameLog {
+        total_frames: 1_000,
+        archived_frames: 1_000 - RING_BUFFER_SIZE as u32,
+        ..Default::default()
+    };
+    assert!(!log.has_room(), "a full ring ahead of the archive");
+
+    assert!(archive_frames(&session, &mut log, 1_001).is_err(), "ahead of the log");
+    assert!(archive_frames(&session, &mut log, 10).is_err(), "behind the archive");
+    archive_frames(&session, &mut log, 900).unwrap();
+    assert_eq!((log.unarchived(), log.has_room()), (100, true));
+
+    assert!(archive_frames(&SessionState::default(), &mut log, 1_000).is_err(), "a match");
+}
@@ -0,0 +1,51 @@
+//! Seed-derived spawn layouts.
+
+use session_lifecycle::{spawn_positions, SPAWN_SPREAD_MAX, SPAWN_SPREAD_MIN};
+use stage_geometry::{StageGeometry, STAGE_BATTLEFIELD, STAGE_FINAL_DESTINATION};
+
+fn geometry(stage: u8) -> StageGeometry {
+    let mut g = StageGeometry::default();
+    assert!(g.load_legal(stage));
+    g
+}
+
+#[test]
+fn test_spawns_are_reproducible_from_seed_and_stage() {
+    for seed in [0, 1, 0xDEAD_BEEF, u64::MAX] {
+        assert_eq!(
+            spawn_positions(seed, STAGE_BATTLEFIELD),
+            spawn_positions(seed, STAGE_BATTLEFIELD)
+        );
+    }
+    let layouts: Vec<_> = (0..32).map(|seed| spawn_positions(seed, STAGE_BATTLEFIELD)).collect();
+    assert!(layouts.iter().any(|l| *l != layouts[0]), "seeds never vary the spawn");
+}
+
+#[test]
+fn test_spawns_mirror_on_the_main_platform_facing_each_other() {
+    for stage in [STAGE_FINAL_DESTINATION, STAGE_BATTLEFIELD] {
+        let g = geometry(stage);
+        let mut sides = [false; 2];
+        for seed in 0..64 {
+            let [a, b] = spawn_positions(seed, stage);
+            assert_eq!(a.0, -b.0);
+            assert_eq!((a.1, b.1), (g.ground_y, g.ground_y));
+            let offset = a.0.abs();
+            assert!(offset >= g.ground_right * SPAWN_SPREAD_MIN / 256 - 1);
+            assert!(offset <= g.ground_right * SPAWN_SPREAD_MAX / 256);
+            // The player on the left faces right, and vice versa
+            assert_eq!(a.2, (a.0 < 0) as u8);
+            assert_eq!(b.2, (b.0 < 0) as u8);
+            sides[(a.0 > 0) as usize] = true;
+        }
+        assert_eq!(sides, [true, true], "port 1 should start on either side");
+    }
+}
+
+#[test]
+fn test_unknown_stages_spawn_on_final_destination() {
+    let fd = geometry(STAGE_FINAL_DESTINATION);
+    let [a, _] = spawn_positions(7, 0);
+    assert!(a.0.abs() <= fd.ground_right * SPAWN_SPREAD_MAX / 256);
+    assert_eq!(a.1, fd.ground_y);
+}
@@ -0,0 +1,140 @@
+use bolt_lang::*;
+use frame_log::FrameLog;
+use player_profile::PlayerProfile;
+use session_result::{SessionResult, OUTCOME_DRAW, OUTCOME_UNPLAYED, OUTCOME_WINNER};
+use session_state::{SessionState, END_REASON_STALL, STATUS_ENDED, STATUS_SETTLED};
+
+declare_id!("8kw2QHBzSQZAJPHSCioJoReq97LekkRNLXLciqGefyER");
+
+#[error_code]
+pub enum SettleError {
+    #[msg("Session has not ended")]
+    SessionNotEnded,
+    #[msg("Player profile belongs to a different wallet")]
+    ProfileMismatch,
+}
+
+/// Settle session system — the "power off and save" half of END.
+///
+/// Runs once on an Ended session, inside the ephemeral rollup:
+///   1. Builds the SessionResult from the final SessionState
+///      (most stocks wins, then lowest percent; a session anti-stall
+///      ended is a draw)
+///   2. Pins the FrameLog as the match replay (SessionResult.replay_frames)
+///   3. Records the outcome in both players' PlayerProfiles
+///   4. Marks the session Settled
+///
+/// Settled is the signal to commit: systems can't CPI into the ER's
+/// delegation program, so the client follows up with commit_and_undelegate
+/// for the session entity's components and both profiles, which lands the
+/// result, replay and profiles on mainnet together.
+///
+/// An unplayed session (no player 2, or ended at frame 0) settles with
+/// OUTCOME_UNPLAYED and leaves the profiles untouched.
+#[system]
+pub mod settle_session {
+
+    pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session_key = ctx.accounts.session_state.key();
+        let frame_log_key = ctx.accounts.frame_log.key();
+        let session = &mut ctx.accounts.session_state;
+        let frame_log = &ctx.accounts.frame_log;
+        let result = &mut ctx.accounts.session_result;
+
+        require!(session.status == STATUS_ENDED, SettleError::SessionNotEnded);
+
+        let now = Clock::get()?.unix_timestamp;
+        let winner = decide_winner(session);
+
+        result.session = session_key;
+        result.frame = session.frame;
+        result.outcome = match winner {
+            None => OUTCOME_UNPLAYED,
+            Some(None) => OUTCOME_DRAW,
+            Some(Some(_)) => OUTCOME_WINNER,
+        };
+        result.winner = match winner {
+            Some(Some(0)) => session.player1,
+            Some(Some(_)) => session.player2,
+            _ => Pubkey::default(),
+        };
+        result.stocks = [session.players[0].stocks, session.players[1].stocks];
+        result.percents = [session.players[0].percent, session.players[1].percent];
+        result.frame_log = frame_log_key;
+        result.replay_frames = frame_log.total_frames;
+        result.settled_at = now;
+
+        if let Some(winner) = winner {
+            let players = [session.player1, session.player2];
+            let profiles = [
+                &mut ctx.accounts.player1_profile,
+                &mut ctx.accounts.player2_profile,
+            ];
+            for (slot, profile) in profiles.into_iter().enumerate() {
+                record_match(profile, players[slot], slot, winner, session_key, now)?;
+            }
+        }
+
+        session.status = STATUS_SETTLED;
+        session.last_update = now;
+
+        msg!(
+            "Session settled: outcome={}, winner={}, replay_frames={}",
+            result.outcome,
+            result.winner,
+            result.replay_frames
+        );
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub frame_log: FrameLog,
+        pub session_result: SessionResult,
+        pub player1_profile: PlayerProfile,
+        pub player2_profile: PlayerProfile,
+    }
+}
+
+/// None if the session was never played, otherwise the winning player slot
+/// (None for a draw).
+fn decide_winner(session: &SessionState) -> Option<Option<usize>> {
+    if session.player2 == Pubkey::default() || session.frame == 0 {
+        return None;
+    }
+    if session.end_reason == END_REASON_STALL {
+        return Some(None);
+    }
+    let (p1, p2) = (&session.players[0], &session.players[1]);
+    match p1.stocks.cmp(&p2.stocks).then(p2.percent.cmp(&p1.percent)) {
+        core::cmp::Ordering::Greater => Some(Some(0)),
+        core::cmp::Ordering::Less => Some(Some(1)),
+        core::cmp::Ordering::Equal => Some(None),
+    }
+}
+
+/// Add one settled match to `profile`, claiming it for `player` if unused.
+fn record_match(
+    profile: &mut Account<PlayerProfile>,
+    player: Pubkey,
+    slot: usize,
+    winner: Option<usize>,
+    session: Pubkey,
+    now: i64,
+) -> Result<()> {
+    if profile.player == Pubkey::default() {
+        profile.player = player;
+    }
+    require!(profile.player == player, SettleError::ProfileMismatch);
+
+    profile.matches += 1;
+    match winner {
+        None => profile.draws += 1,
+        Some(w) if w == slot => profile.wins += 1,
+        Some(_) => profile.losses += 1,
+    }
+    profile.last_session = session;
+    profile.updated_at = now;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use bolt_lang::*;
+use item_state::{Item, ItemState, ITEM_NONE, MAX_ITEMS, NUM_ITEM_KINDS, OWNER_NONE};
+use session_state::{
+    PlayerState, SessionState, NUM_PLAYERS, STATUS_ACTIVE, STATUS_CREATED,
+    STATUS_WAITING_PLAYERS,
+};
+use stage_geometry::StageGeometry;
+
+declare_id!("5WN21KyfuPo7jZLWet2a5W594q7kQWo4J66T6ChgfU1J");
+
+/// How close (fixed-point, per axis) a grounded player must be to pick an
+/// item up
+pub const PICKUP_RANGE: i32 = 10 * 256;
+
+/// Frames an item stays in play before it despawns
+pub const ITEM_LIFETIME: u32 = 1200;
+
+#[error_code]
+pub enum ItemError {
+    #[msg("Session is not active")]
+    SessionNotActive,
+    #[msg("Items are not enabled for this session")]
+    ItemsDisabled,
+    #[msg("This frame was already stepped")]
+    AlreadyStepped,
+    #[msg("Item rules are fixed once the session starts")]
+    ConfigLocked,
+    #[msg("Unknown stage")]
+    UnknownStage,
+}
+
+/// Spawn items system — runs after run_inference each frame when the
+/// session has items enabled.
+///
+/// Each frame it despawns items older than ITEM_LIFETIME, hands unowned
+/// items to a grounded player standing on them, carries held items with
+/// their owner, and every `spawn_interval` frames drops a new item into a
+/// free slot. Spawn kind and position come from `spawn_roll(seed, frame)`,
+/// so anyone replaying the session sees the same items.
+///
+/// Called with `configure` set (before the session goes Active), it sets
+/// the rules instead.
+///
+/// Accounts read/written:
+///   - SessionState: players, seed (read)
+///   - StageGeometry: loaded for the session's stage on first use
+///   - ItemState: rules, live items
+#[system]
+pub mod spawn_items {
+
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        let session = &ctx.accounts.session_state;
+        let geometry = &mut ctx.accounts.stage_geometry;
+        let items = &mut ctx.accounts.item_state;
+
+        if args.configure {
+            require!(
+                session.status == STATUS_CREATED || session.status == STATUS_WAITING_PLAYERS,
+                ItemError::ConfigLocked
+            );
+            items.configured = true;
+            items.enabled = args.enabled;
+            items.spawn_interval = args.spawn_interval;
+            msg!(
+                "Items: enabled={}, every {} frames",
+                items.enabled,
+                items.interval_frames()
+            );
+            return Ok(ctx.accounts);
+        }
+
+        require!(
+            session.status == STATUS_ACTIVE,
+            ItemError::SessionNotActive
+        );
+        require!(items.enabled, ItemError::ItemsDisabled);
+        require!(session.frame > items.frame, ItemError::AlreadyStepped);
+        if !geometry.loaded || geometry.stage != session.stage {
+            require!(
+                geometry.load_legal(session.stage),
+                ItemError::UnknownStage
+            );
+        }
+
+        let spawned = step_items(items, &session.players, geometry, session.seed, session.frame);
+        if let Some(slot) = spawned {
+            msg!("Item {} spawned in slot {}", items.items[slot].kind, slot);
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub stage_geometry: StageGeometry,
+        pub item_state: ItemState,
+    }
+
+    #[arguments]
+    pub struct Args {
+        /// Set the rules below instead of stepping a frame
+        pub configure: bool,
+        /// Whether items are in play — only used to configure
+        pub enabled: bool,
+        /// Frames between spawns (0 = default) — only used to configure
+        pub spawn_interval: u16,
+    }
+}
+
+/// Step the items to `frame`: despawn, pick up, carry, then spawn if due.
+/// Returns the slot of a newly spawned item.
+pub fn step_items(
+    state: &mut ItemState,
+    players: &[PlayerState; NUM_PLAYERS],
+    stage: &StageGeometry,
+    seed: u64,
+    frame: u32,
+) -> Option<usize> {
+    state.frame = frame;
+
+    for i in 0..MAX_ITEMS {
+        let item = &mut state.items[i];
+        if item.kind == ITEM_NONE {
+            continue;
+        }
+        let owner = item.owner as usize;
+        let holder_out = owner < NUM_PLAYERS && players[owner].stocks == 0;
+        if frame.saturating_sub(item.spawned_at) >= ITEM_LIFETIME || holder_out {
+            *item = Item::default();
+            continue;
+        }
+        if item.owner == OWNER_NONE {
+            if let Some(slot) = (0..NUM_PLAYERS).find(|&s| can_pick_up(&players[s], item)) {
+                item.owner = slot as u8;
+            }
+        }
+        if (item.owner as usize) < NUM_PLAYERS {
+            let holder = &players[item.owner as usize];
+            item.x = holder.x;
+            item.y = holder.y;
+        }
+    }
+
+    if frame % state.interval_frames() != 0 {
+        return None;
+    }
+    let slot = state.free_slot()?;
+    state.items[slot] = spawn_item(stage, seed, frame);
+    state.spawned += 1;
+    Some(slot)
+}
+
+/// The item spawned at `frame`: a kind and a spot on the main platform
+/// drawn from the session seed.
+pub fn spawn_item(stage: &StageGeometry, seed: u64, frame: u32) -> Item {
+    let roll = spawn_roll(seed, frame);
+    let width = (stage.ground_right - stage.ground_left).max(0) as u64;
+    let x = stage.ground_left + ((roll >> 8) % (width + 1)) as i32;
+    Item {
+        kind: 1 + (roll % NUM_ITEM_KINDS as u64) as u8,
+        owner: OWNER_NONE,
+        x,
+        y: stage.ground_y,
+        spawned_at: frame,
+    }
+}
+
+/// Deterministic 64-bit roll for a session frame (SplitMix64 over the
+/// seed and frame).
+pub fn spawn_roll(seed: u64, frame: u32) -> u64 {
+    let mut z = seed.wrapping_add((frame as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn can_pick_up(p: &PlayerState, item: &Item) -> bool {
+    p.stocks > 0
+        && p.on_ground != 0
+        && (p.x - item.x).abs() <= PICKUP_RANGE
+        && (p.y - item.y).abs() <= PICKUP_RANGE
+}
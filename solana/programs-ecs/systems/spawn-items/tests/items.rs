@@ -0,0 +1,104 @@
+//! Seeded spawns, pickups, carrying and despawns.
+
+use item_state::{ItemState, ITEM_NONE, MAX_ITEMS, NUM_ITEM_KINDS, OWNER_NONE};
+use session_state::PlayerState;
+use spawn_items::{spawn_item, spawn_roll, step_items, ITEM_LIFETIME, PICKUP_RANGE};
+use stage_geometry::{StageGeometry, STAGE_BATTLEFIELD};
+
+fn stage() -> StageGeometry {
+    let mut s = StageGeometry::default();
+    assert!(s.load_legal(STAGE_BATTLEFIELD));
+    s
+}
+
+/// Both players airborne far off stage, so nobody picks anything up.
+fn players() -> [PlayerState; 2] {
+    [0, 1].map(|i| PlayerState {
+        x: (i * 2 - 1) * 150 * 256,
+        y: 80 * 256,
+        stocks: 4,
+        ..Default::default()
+    })
+}
+
+fn enabled(spawn_interval: u16) -> ItemState {
+    ItemState {
+        configured: true,
+        enabled: true,
+        spawn_interval,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_spawns_are_seeded() {
+    let stage = stage();
+    let (a, b) = (spawn_item(&stage, 7, 600), spawn_item(&stage, 7, 600));
+    assert_eq!((a.kind, a.x, a.y), (b.kind, b.x, b.y));
+    assert_ne!(spawn_roll(7, 600), spawn_roll(8, 600));
+    assert_ne!(spawn_roll(7, 600), spawn_roll(7, 1200));
+
+    for frame in 0..500 {
+        let item = spawn_item(&stage, 42, frame);
+        assert!((1..=NUM_ITEM_KINDS).contains(&item.kind));
+        assert!(item.x >= stage.ground_left && item.x <= stage.ground_right);
+        assert_eq!(item.y, stage.ground_y);
+        assert_eq!(item.owner, OWNER_NONE);
+    }
+}
+
+#[test]
+fn test_spawn_interval_and_capacity() {
+    let (stage, players) = (stage(), players());
+    let mut state = enabled(10);
+
+    assert_eq!(step_items(&mut state, &players, &stage, 1, 9), None);
+    for (n, frame) in (10..).step_by(10).take(MAX_ITEMS).enumerate() {
+        assert_eq!(step_items(&mut state, &players, &stage, 1, frame), Some(n));
+    }
+    // Every slot is full until one frees up
+    let frame = 10 * (MAX_ITEMS as u32 + 1);
+    assert_eq!(step_items(&mut state, &players, &stage, 1, frame), None);
+    assert_eq!(state.live(), MAX_ITEMS);
+    assert_eq!(state.spawned, MAX_ITEMS as u32);
+}
+
+#[test]
+fn test_pickup_and_carry() {
+    let stage = stage();
+    let mut players = players();
+    let mut state = enabled(10);
+    step_items(&mut state, &players, &stage, 3, 10);
+    let (x, y) = (state.items[0].x, state.items[0].y);
+
+    // Standing on it, grounded
+    players[1].x = x + PICKUP_RANGE;
+    players[1].y = y;
+    players[1].on_ground = 1;
+    step_items(&mut state, &players, &stage, 3, 11);
+    assert_eq!(state.items[0].owner, 1);
+
+    // Held items follow their owner
+    players[1].x += 30 * 256;
+    players[1].y += 20 * 256;
+    step_items(&mut state, &players, &stage, 3, 12);
+    assert_eq!((state.items[0].x, state.items[0].y), (players[1].x, players[1].y));
+}
+
+#[test]
+fn test_despawn() {
+    let (stage, mut players) = (stage(), players());
+    let mut state = enabled(1000);
+    step_items(&mut state, &players, &stage, 5, 1000);
+    step_items(&mut state, &players, &stage, 5, 999 + ITEM_LIFETIME);
+    assert_eq!(state.live(), 1);
+    step_items(&mut state, &players, &stage, 5, 1000 + ITEM_LIFETIME);
+    assert_eq!(state.items[0].kind, ITEM_NONE);
+
+    // A held item goes when its owner is out of stocks
+    step_items(&mut state, &players, &stage, 5, 3000);
+    state.items[0].owner = 0;
+    players[0].stocks = 0;
+    step_items(&mut state, &players, &stage, 5, 3001);
+    assert_eq!(state.live(), 0);
+}
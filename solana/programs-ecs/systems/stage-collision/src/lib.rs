@@ -0,0 +1,195 @@
+use bolt_lang::*;
+use session_state::{PlayerState, SessionState, STATUS_ACTIVE};
+use stage_geometry::StageGeometry;
+
+pub use session_state::ACTION_CLIFF_CATCH;
+pub use stage_geometry::{LEDGE_HANG_X, LEDGE_HANG_Y};
+
+declare_id!("Ecvh29rvVX5woEdV3QD61JnQx5hvnH8Y1auSjQebethy");
+
+/// How far below a surface (fixed-point × 256) a falling player may be and
+/// still land on it — one frame of fast-fall plus model noise.
+pub const SNAP_DEPTH: i32 = 8 * 256;
+
+/// Ledge grab box: how far out from the ledge, and how far below the
+/// stage surface, a falling player catches it.
+pub const LEDGE_GRAB_X: i32 = 12 * 256;
+pub const LEDGE_GRAB_DEPTH: i32 = 24 * 256;
+
+/// Respawn point after a KO (above the stage centre, airborne)
+pub const RESPAWN_Y: i32 = 50 * 256;
+
+#[error_code]
+pub enum CollisionError {
+    #[msg("Session is not active")]
+    SessionNotActive,
+    #[msg("No geometry for this stage")]
+    UnknownStage,
+}
+
+/// What the collision pass did to a player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Contact {
+    None,
+    Landed,
+    LeftGround,
+    LedgeGrab,
+    Ko,
+}
+
+/// Stage collision system — runs after run_inference each frame.
+///
+/// The model predicts positions but knows nothing of the stage's solid
+/// surfaces, so this pass holds players to the geometry:
+///   - Past a blast zone: lose a stock (in a persistent world, start the
+///     next life), respawn above the stage
+///   - Falling onto the main platform or a side platform: snap to its
+///     surface and ground the player
+///   - Grounded but no longer over a surface: walk off, airborne
+///   - Falling past a ledge corner: catch the ledge
+///
+/// Accounts read/written:
+///   - SessionState: players' positions, velocities, grounded state, stocks
+///   - StageGeometry: loaded for the session's stage on first use
+#[system]
+pub mod stage_collision {
+
+    pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session = &mut ctx.accounts.session_state;
+        let geometry = &mut ctx.accounts.stage_geometry;
+
+        require!(
+            session.status == STATUS_ACTIVE,
+            CollisionError::SessionNotActive
+        );
+        if !geometry.loaded || geometry.stage != session.stage {
+            require!(
+                geometry.load_legal(session.stage),
+                CollisionError::UnknownStage
+            );
+        }
+
+        let persistent = session.is_persistent();
+        for (slot, p) in session.players.iter_mut().enumerate() {
+            match collide(p, geometry, persistent) {
+                Contact::Ko if persistent => {
+                    msg!("KO: player {} respawns on life {}", slot + 1, p.stocks)
+                }
+                Contact::Ko => msg!("KO: player {} has {} stocks left", slot + 1, p.stocks),
+                Contact::LedgeGrab => msg!("Player {} caught the ledge", slot + 1),
+                _ => {}
+            }
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub stage_geometry: StageGeometry,
+    }
+}
+
+/// Resolve one player against the stage. In a `persistent` world stocks
+/// count lives, so a KO adds one; an open slot (0) is skipped either way.
+pub fn collide(p: &mut PlayerState, stage: &StageGeometry, persistent: bool) -> Contact {
+    if p.stocks == 0 {
+        return Contact::None;
+    }
+
+    if p.x < stage.blast_left
+        || p.x > stage.blast_right
+        || p.y > stage.blast_top
+        || p.y < stage.blast_bottom
+    {
+        p.stocks = if persistent {
+            p.stocks.saturating_add(1)
+        } else {
+            p.stocks - 1
+        };
+        p.x = 0;
+        p.y = RESPAWN_Y;
+        p.percent = 0;
+        stop(p);
+        p.on_ground = 0;
+        p.jumps_left = full_jumps(p.character);
+        return Contact::Ko;
+    }
+
+    if p.on_ground != 0 || p.speed_y <= 0 {
+        if let Some(surface) = landing_surface(p, stage) {
+            let landed = p.on_ground == 0;
+            p.y = surface;
+            p.speed_y = 0;
+            p.on_ground = 1;
+            p.jumps_left = full_jumps(p.character);
+            if landed {
+                p.speed_air_x = 0;
+                return Contact::Landed;
+            }
+            return Contact::None;
+        }
+    }
+
+    if p.on_ground != 0 {
+        p.on_ground = 0;
+        return Contact::LeftGround;
+    }
+
+    if p.speed_y <= 0 {
+        let below = p.y <= stage.ground_y && p.y >= stage.ground_y - LEDGE_GRAB_DEPTH;
+        let ledge = if p.x < stage.ground_left && stage.ground_left - p.x <= LEDGE_GRAB_X {
+            Some(stage.ledge_hang(false))
+        } else if p.x > stage.ground_right && p.x - stage.ground_right <= LEDGE_GRAB_X {
+            Some(stage.ledge_hang(true))
+        } else {
+            None
+        };
+        if let (true, Some((x, y, facing))) = (below, ledge) {
+            p.x = x;
+            p.y = y;
+            p.facing = facing;
+            stop(p);
+            p.jumps_left = full_jumps(p.character);
+            p.action_state = ACTION_CLIFF_CATCH;
+            p.state_age = 0;
+            return Contact::LedgeGrab;
+        }
+    }
+
+    Contact::None
+}
+
+/// The highest surface under the player within reach: SNAP_DEPTH below
+/// for a falling player, SNAP_DEPTH either side for a grounded one.
+fn landing_surface(p: &PlayerState, stage: &StageGeometry) -> Option<i32> {
+    let reach_above = if p.on_ground != 0 { SNAP_DEPTH } else { 0 };
+    let platforms = stage.platforms[..stage.num_platforms as usize]
+        .iter()
+        .map(|pl| (pl.left, pl.right, pl.y));
+    core::iter::once((stage.ground_left, stage.ground_right, stage.ground_y))
+        .chain(platforms)
+        .filter(|&(left, right, y)| {
+            p.x >= left && p.x <= right && p.y <= y + reach_above && p.y >= y - SNAP_DEPTH
+        })
+        .map(|(_, _, y)| y)
+        .max()
+}
+
+fn stop(p: &mut PlayerState) {
+    p.speed_air_x = 0;
+    p.speed_y = 0;
+    p.speed_ground_x = 0;
+    p.speed_attack_x = 0;
+    p.speed_attack_y = 0;
+    p.hitlag = 0;
+}
+
+/// Jumps a character has on the ground: 2, or 6 for Kirby and Jigglypuff.
+fn full_jumps(character: u8) -> u8 {
+    match character {
+        4 | 15 => 6,
+        _ => 2,
+    }
+}
@@ -0,0 +1,115 @@
+//! Landing, walking off, ledge grabs and blast zones on Battlefield.
+
+use session_state::PlayerState;
+use stage_collision::*;
+use stage_geometry::{StageGeometry, STAGE_BATTLEFIELD};
+
+fn battlefield() -> StageGeometry {
+    let mut stage = StageGeometry::default();
+    assert!(stage.load_legal(STAGE_BATTLEFIELD));
+    stage
+}
+
+fn airborne(x: i32, y: i32, speed_y: i16) -> PlayerState {
+    PlayerState {
+        x,
+        y,
+        speed_y,
+        stocks: 4,
+        character: 18,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_unknown_stage_not_loaded() {
+    let mut stage = StageGeometry::default();
+    assert!(!stage.load_legal(0));
+    assert!(!stage.loaded);
+}
+
+#[test]
+fn test_lands_on_main_platform() {
+    let stage = battlefield();
+    let mut p = airborne(0, -3 * 256, -400);
+    assert_eq!(collide(&mut p, &stage, false), Contact::Landed);
+    assert_eq!((p.y, p.speed_y, p.on_ground, p.jumps_left), (0, 0, 1, 2));
+
+    // Standing still stays grounded without a new landing
+    assert_eq!(collide(&mut p, &stage, false), Contact::None);
+    assert_eq!(p.on_ground, 1);
+}
+
+#[test]
+fn test_lands_on_side_platform_only_when_falling() {
+    let stage = battlefield();
+    let platform_y = stage.platforms[0].y;
+    let x = (stage.platforms[0].left + stage.platforms[0].right) / 2;
+
+    // Rising through the platform passes it
+    let mut p = airborne(x, platform_y - 256, 300);
+    assert_eq!(collide(&mut p, &stage, false), Contact::None);
+    assert_eq!(p.on_ground, 0);
+
+    let mut p = airborne(x, platform_y - 256, -300);
+    assert_eq!(collide(&mut p, &stage, false), Contact::Landed);
+    assert_eq!(p.y, platform_y);
+}
+
+#[test]
+fn test_walks_off_edge() {
+    let stage = battlefield();
+    let mut p = airborne(stage.ground_right + 256, 0, 0);
+    p.on_ground = 1;
+    assert_eq!(collide(&mut p, &stage, false), Contact::LeftGround);
+    assert_eq!(p.on_ground, 0);
+}
+
+#[test]
+fn test_ledge_grab() {
+    let stage = battlefield();
+    let mut p = airborne(stage.ground_left - 6 * 256, -10 * 256, -500);
+    p.facing = 0;
+    p.jumps_left = 0;
+    assert_eq!(collide(&mut p, &stage, false), Contact::LedgeGrab);
+    assert_eq!(p.x, stage.ground_left - LEDGE_HANG_X);
+    assert_eq!(p.action_state, ACTION_CLIFF_CATCH);
+    assert_eq!((p.facing, p.speed_y, p.jumps_left), (1, 0, 2));
+
+    // Rising past the ledge doesn't catch it
+    let mut p = airborne(stage.ground_right + 6 * 256, -10 * 256, 500);
+    assert_eq!(collide(&mut p, &stage, false), Contact::None);
+
+    // Too far out doesn't either
+    let mut p = airborne(stage.ground_right + 30 * 256, -10 * 256, -500);
+    assert_eq!(collide(&mut p, &stage, false), Contact::None);
+}
+
+#[test]
+fn test_blast_zone_ko() {
+    let stage = battlefield();
+    let mut p = airborne(stage.blast_right + 1, 40 * 256, 200);
+    p.percent = 140;
+    assert_eq!(collide(&mut p, &stage, false), Contact::Ko);
+    assert_eq!((p.stocks, p.percent, p.x, p.y), (3, 0, 0, RESPAWN_Y));
+
+    // An eliminated player is left alone
+    let mut p = airborne(stage.blast_right + 1, 0, 0);
+    p.stocks = 0;
+    assert_eq!(collide(&mut p, &stage, false), Contact::None);
+}
+
+#[test]
+fn test_persistent_ko_starts_the_next_life() {
+    let stage = battlefield();
+    let mut p = airborne(0, stage.blast_bottom - 1, -200);
+    p.stocks = 1;
+    p.percent = 90;
+    assert_eq!(collide(&mut p, &stage, true), Contact::Ko);
+    assert_eq!((p.stocks, p.percent, p.x, p.y), (2, 0, 0, RESPAWN_Y));
+
+    // An open slot is left alone
+    let mut p = airborne(0, stage.blast_bottom - 1, -200);
+    p.stocks = 0;
+    assert_eq!(collide(&mut p, &stage, true), Contact::None);
+}
@@ -38,13 +38,14 @@ pub mod submit_input {
             InputError::SessionNotActive
         );
 
-        // Determine which player is submitting
+        // Determine which player is submitting (an open persistent-world
+        // slot holds the default key, which nobody signs for)
         let player = args.player;
         let is_p1 = player == session.player1;
         let is_p2 = player == session.player2;
 
         require!(
-            is_p1 || is_p2,
+            (is_p1 || is_p2) && player != Pubkey::default(),
             InputError::UnauthorizedPlayer
         );
 
@@ -0,0 +1,56 @@
+use bolt_lang::*;
+use render_state::{RenderPlayer, RenderState};
+use session_state::SessionState;
+
+declare_id!("GzpHb4cogzaXYaTr5kjJLMuXFwQRF2JgN8e1VLgaqErD");
+
+#[error_code]
+pub enum RenderError {
+    #[msg("Render state is already at this frame")]
+    AlreadySynced,
+}
+
+/// Sync render system — runs last each frame, after run_inference and the
+/// systems that correct its output.
+///
+/// Copies positions, percents, action states and facing from SessionState
+/// into RenderState. Spectators subscribe to RenderState instead of
+/// SessionState; players still read SessionState.
+///
+/// Accounts read/written:
+///   - SessionState: players (read)
+///   - RenderState: overwritten with the current frame
+#[system]
+pub mod sync_render {
+
+    pub fn execute(ctx: Context<Components>, _args: Vec<u8>) -> Result<Components> {
+        let session = &ctx.accounts.session_state;
+        let render = &mut ctx.accounts.render_state;
+
+        // Only write when the frame moved, so subscribers see one update
+        // per frame
+        require!(
+            render.frame != session.frame || session.frame == 0,
+            RenderError::AlreadySynced
+        );
+
+        render.frame = session.frame;
+        for (slot, p) in session.players.iter().enumerate() {
+            render.players[slot] = RenderPlayer {
+                x: p.x,
+                y: p.y,
+                percent: p.percent,
+                action_state: p.action_state,
+                facing: p.facing,
+            };
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub session_state: SessionState,
+        pub render_state: RenderState,
+    }
+}
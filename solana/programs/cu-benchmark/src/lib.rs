@@ -1,15 +1,147 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::compute_units::sol_remaining_compute_units;
+use awm_kernel::lut::{self, LUT_V1_SIZE};
+use awm_kernel::{matmul, ssm};
+use world_model::inference::{hidden_state_mut, step_players_mamba2};
+use world_model::stages::STAGE_FINAL_DESTINATION;
+use world_model::state::{
+    hidden_data_size, ControllerInput, ModelManifestAccount, PlayerState, DEFAULT_TICK_RATE,
+    WEIGHT_HEADER_SIZE,
+};
 
 declare_id!("2ugkUeQwNdfFpQXKHja4LiFxFgvn1VNn7w1YLp6XeNEJ");
 
+// Operation ids recorded in BenchResults.op
+pub const OP_MATMUL: u8 = 0;
+pub const OP_MATMUL_TILED: u8 = 1;
+pub const OP_LUT_ACTIVATION: u8 = 2;
+pub const OP_SSM_STEP: u8 = 3;
+pub const OP_MATMUL_UNSAFE: u8 = 4;
+pub const OP_MATMUL_PACKED: u8 = 5;
+pub const OP_FULL_LAYER: u8 = 6;
+pub const OP_FORWARD_PASS: u8 = 7;
+pub const OP_MATMUL_SYSCALL: u8 = 8;
+pub const OP_SWEEP_MATMUL: u8 = 9;
+pub const OP_SWEEP_SSM: u8 = 10;
+pub const OP_RMSNORM: u8 = 11;
+pub const OP_REQUANTIZE: u8 = 12;
+pub const OP_CONV1D: u8 = 13;
+pub const OP_MEMORY_ACCESS: u8 = 14;
+
+// Access patterns for bench_memory_access
+pub const ACCESS_SEQUENTIAL: u8 = 0;
+pub const ACCESS_STRIDED: u8 = 1;
+pub const ACCESS_PACKED: u8 = 2;
+
+/// Rows in a SweepResults table
+pub const MAX_SWEEP_ENTRIES: usize = 32;
+
 /// CU benchmark program for INT8 matmul and LUT-based activations.
 ///
 /// Results determine whether we pursue single-tx (~60M CU) or multi-tx pipeline.
-
+///
+/// Every bench takes an optional `results` account (created by init_results)
+/// as its last account and records the operation, its dimensions, the CU
+/// spent in the kernel loop and an output checksum there, so harnesses read
+/// structured results instead of parsing logs.
 #[program]
 pub mod cu_benchmark {
     use super::*;
 
+    /// Create a results account for the benches to write into.
+    pub fn init_results(ctx: Context<InitResults>) -> Result<()> {
+        ctx.accounts.results.authority = ctx.accounts.payer.key();
+        Ok(())
+    }
+
+    /// Create an empty sweep table for bench_sweep to append to.
+    pub fn init_sweep(ctx: Context<InitSweep>) -> Result<()> {
+        ctx.accounts.sweep.authority = ctx.accounts.payer.key();
+        Ok(())
+    }
+
+    /// Benchmark one awm-kernel op over a list of shapes and append a row
+    /// per shape to the sweep table, so a whole CU table comes out of a few
+    /// transactions instead of one run per shape.
+    ///
+    /// OP_SWEEP_MATMUL: shapes are (rows, cols); `data` holds weights then
+    /// input, as for bench_matmul. OP_SWEEP_SSM: shapes are (d_inner,
+    /// d_state); `data` holds the v1 LUTs, then x, dt, a_log (d_inner each)
+    /// and the initial hidden state. Scratch is sized once for the largest
+    /// shape, since the BPF heap never frees.
+    pub fn bench_sweep(ctx: Context<BenchSweep>, op: u8, shapes: Vec<[u32; 2]>) -> Result<()> {
+        let data = ctx.accounts.data.try_borrow_data()?;
+        let sweep = &mut ctx.accounts.sweep;
+        require!(
+            sweep.len as usize + shapes.len() <= MAX_SWEEP_ENTRIES,
+            BenchError::SweepFull
+        );
+
+        let shapes: Vec<[usize; 2]> = shapes
+            .iter()
+            .map(|&[a, b]| [a as usize, b as usize])
+            .collect();
+        let max_a = shapes.iter().map(|s| s[0]).max().unwrap_or(0);
+        let max_ab = shapes.iter().map(|s| s[0] * s[1]).max().unwrap_or(0);
+
+        match op {
+            OP_SWEEP_MATMUL => {
+                let mut output = vec![0i32; max_a];
+                for [rows, cols] in shapes {
+                    let weight_size = rows * cols;
+                    require!(data.len() >= weight_size + cols, BenchError::InsufficientData);
+                    let weights = &data[..weight_size];
+                    let input = as_i8(&data[weight_size..weight_size + cols]);
+                    let output = &mut output[..rows];
+
+                    let start = sol_remaining_compute_units();
+                    matmul::matmul_i8(weights, input, output, rows, cols);
+                    let cu = start - sol_remaining_compute_units();
+
+                    let checksum = output.iter().map(|&v| v as i64).sum();
+                    msg!("sweep matmul {}x{}: {} CU", rows, cols, cu);
+                    sweep.push(op, [rows, cols], cu, checksum);
+                }
+            }
+            OP_SWEEP_SSM => {
+                let mut h = vec![0i8; max_ab];
+                let mut y = vec![0i8; max_a];
+                for [d_inner, d_state] in shapes {
+                    let x_offset = LUT_V1_SIZE;
+                    let dt_offset = x_offset + d_inner;
+                    let a_offset = dt_offset + d_inner;
+                    let h_offset = a_offset + d_inner;
+                    let h_size = d_inner * d_state;
+                    require!(data.len() >= h_offset + h_size, BenchError::InsufficientData);
+
+                    let h = &mut h[..h_size];
+                    let y = &mut y[..d_inner];
+                    h.copy_from_slice(as_i8(&data[h_offset..h_offset + h_size]));
+
+                    let start = sol_remaining_compute_units();
+                    ssm::selective_scan_step(
+                        as_i8(&data[x_offset..dt_offset]),
+                        as_i8(&data[dt_offset..a_offset]),
+                        h,
+                        &data[a_offset..h_offset],
+                        None,
+                        &data[..LUT_V1_SIZE],
+                        y,
+                        d_inner,
+                        d_state,
+                    );
+                    let cu = start - sol_remaining_compute_units();
+
+                    let checksum = y.iter().chain(h.iter()).map(|&v| v as i64).sum();
+                    msg!("sweep ssm {}x{}: {} CU", d_inner, d_state, cu);
+                    sweep.push(op, [d_inner, d_state], cu, checksum);
+                }
+            }
+            _ => return err!(BenchError::UnknownOp),
+        }
+        Ok(())
+    }
+
     /// Benchmark INT8 matrix-vector multiply.
     /// y[i] = sum_j(W[i][j] * x[j]), accumulated in i32, requantized to i8.
     pub fn bench_matmul(ctx: Context<BenchMatmul>, rows: u32, cols: u32) -> Result<()> {
@@ -27,7 +159,9 @@ pub mod cu_benchmark {
         let scale: i32 = 128;
 
         msg!("matmul start: {}x{}", rows, cols);
+        let start = sol_remaining_compute_units();
 
+        let mut checksum: i64 = 0;
         for i in 0..rows {
             let mut acc: i32 = 0;
             let row_offset = i * cols;
@@ -37,11 +171,12 @@ pub mod cu_benchmark {
                 acc += w * x;
             }
             let scaled = (acc * scale) >> 8;
-            let _output = scaled.clamp(-128, 127) as i8;
+            checksum += scaled.clamp(-128, 127) as i8 as i64;
         }
 
+        let cu = start - sol_remaining_compute_units();
         msg!("matmul done: {}x{}", rows, cols);
-        Ok(())
+        record(&mut ctx.accounts.results, OP_MATMUL, [rows, cols, 0], cu, checksum)
     }
 
     /// Benchmark INT8 matmul with 4x unrolled inner loop.
@@ -60,7 +195,9 @@ pub mod cu_benchmark {
         let scale: i32 = 128;
 
         msg!("matmul_tiled start: {}x{}", rows, cols);
+        let start = sol_remaining_compute_units();
 
+        let mut checksum: i64 = 0;
         for i in 0..rows {
             let mut acc0: i32 = 0;
             let mut acc1: i32 = 0;
@@ -87,11 +224,12 @@ pub mod cu_benchmark {
 
             let acc = acc0 + acc1 + acc2 + acc3 + acc_rem;
             let scaled = (acc * scale) >> 8;
-            let _output = scaled.clamp(-128, 127) as i8;
+            checksum += scaled.clamp(-128, 127) as i8 as i64;
         }
 
+        let cu = start - sol_remaining_compute_units();
         msg!("matmul_tiled done: {}x{}", rows, cols);
-        Ok(())
+        record(&mut ctx.accounts.results, OP_MATMUL_TILED, [rows, cols, 0], cu, checksum)
     }
 
     /// Benchmark LUT-based activation (SiLU=0, softplus=1, rsqrt=2).
@@ -114,6 +252,7 @@ pub mod cu_benchmark {
             0 => "SiLU", 1 => "softplus", 2 => "rsqrt", _ => "unknown",
         };
         msg!("lut_{} start: {} elements", name, num_elements);
+        let start = sol_remaining_compute_units();
 
         let mut checksum: u32 = 0;
         for i in 0..num_elements {
@@ -121,8 +260,15 @@ pub mod cu_benchmark {
             checksum = checksum.wrapping_add(lut[idx] as u32);
         }
 
+        let cu = start - sol_remaining_compute_units();
         msg!("lut_{} done: checksum={}", name, checksum);
-        Ok(())
+        record(
+            &mut ctx.accounts.results,
+            OP_LUT_ACTIVATION,
+            [num_elements, activation_type as usize, 0],
+            cu,
+            checksum as i64,
+        )
     }
 
     /// Benchmark Mamba2 selective scan step.
@@ -146,7 +292,9 @@ pub mod cu_benchmark {
         let exp_lut = &data[256..512];
 
         msg!("ssm_step start: d_inner={}, d_state={}", d_inner, d_state);
+        let start = sol_remaining_compute_units();
 
+        let mut checksum: i64 = 0;
         for i in 0..d_inner {
             let dt_raw_idx = data[dt_raw_offset + i] as usize;
             let dt = softplus_lut[dt_raw_idx] as i32;
@@ -160,14 +308,16 @@ pub mod cu_benchmark {
                 let h_val = data[h_offset + h_idx] as i8 as i32;
                 let b_val = data[b_offset + h_idx] as i8 as i32;
                 let h_new = (a_bar * h_val + dt * b_val * x_val) >> 8;
-                let _h_new_q = h_new.clamp(-128, 127) as i8;
+                let h_new_q = h_new.clamp(-128, 127) as i8;
                 let c_val = data[c_offset + h_idx] as i8 as i32;
-                let _y = c_val * h_new;
+                let y = c_val * h_new;
+                checksum += h_new_q as i64 + y as i64;
             }
         }
 
+        let cu = start - sol_remaining_compute_units();
         msg!("ssm_step done: {}x{}", d_inner, d_state);
-        Ok(())
+        record(&mut ctx.accounts.results, OP_SSM_STEP, [d_inner, d_state, 0], cu, checksum)
     }
 
     /// Benchmark INT8 matmul with unsafe indexing (no bounds checks).
@@ -185,7 +335,9 @@ pub mod cu_benchmark {
         let input = &data[weight_size..weight_size + cols];
 
         msg!("matmul_unsafe start: {}x{}", rows, cols);
+        let start = sol_remaining_compute_units();
 
+        let mut checksum: i64 = 0;
         // SAFETY: bounds checked above via require!
         unsafe {
             for i in 0..rows {
@@ -196,12 +348,13 @@ pub mod cu_benchmark {
                     let x = *input.get_unchecked(j) as i8 as i32;
                     acc += w * x;
                 }
-                let _output = ((acc * 128) >> 8).clamp(-128, 127) as i8;
+                checksum += ((acc * 128) >> 8).clamp(-128, 127) as i8 as i64;
             }
         }
 
+        let cu = start - sol_remaining_compute_units();
         msg!("matmul_unsafe done: {}x{}", rows, cols);
-        Ok(())
+        record(&mut ctx.accounts.results, OP_MATMUL_UNSAFE, [rows, cols, 0], cu, checksum)
     }
 
     /// Benchmark INT8 matmul with unsafe indexing + packed u32 loads.
@@ -222,8 +375,10 @@ pub mod cu_benchmark {
 
         msg!("matmul_packed start: {}x{}", rows, cols);
 
+        let start = sol_remaining_compute_units();
         let chunks = cols / 4;
 
+        let mut checksum: i64 = 0;
         // SAFETY: bounds checked above, cols divisible by 4
         unsafe {
             for i in 0..rows {
@@ -254,12 +409,13 @@ pub mod cu_benchmark {
 
                     acc += w0 * x0 + w1 * x1 + w2 * x2 + w3 * x3;
                 }
-                let _output = ((acc * 128) >> 8).clamp(-128, 127) as i8;
+                checksum += ((acc * 128) >> 8).clamp(-128, 127) as i8 as i64;
             }
         }
 
+        let cu = start - sol_remaining_compute_units();
         msg!("matmul_packed done: {}x{}", rows, cols);
-        Ok(())
+        record(&mut ctx.accounts.results, OP_MATMUL_PACKED, [rows, cols, 0], cu, checksum)
     }
 
     /// Benchmark full Mamba2 layer (in_proj + SSM + gate + out_proj).
@@ -279,6 +435,7 @@ pub mod cu_benchmark {
         let s_len = s_data.len();
 
         msg!("full_layer start: d_model={}, d_inner={}, d_state={}", d_model, d_inner, d_state);
+        let start = sol_remaining_compute_units();
 
         // Step 1: RMSNorm
         let mut norm_sum: i64 = 0;
@@ -328,27 +485,500 @@ pub mod cu_benchmark {
             out_checksum += acc as i64;
         }
 
+        let cu = start - sol_remaining_compute_units();
         msg!("full_layer done: norm={} proj={} ssm={} out={}", norm_sum, proj_checksum, ssm_checksum, out_checksum);
+        let checksum = norm_sum
+            .wrapping_add(proj_checksum)
+            .wrapping_add(ssm_checksum)
+            .wrapping_add(out_checksum);
+        record(
+            &mut ctx.accounts.results,
+            OP_FULL_LAYER,
+            [d_model, d_inner, d_state],
+            cu,
+            checksum,
+        )
+    }
+
+    /// Benchmark the kernel's RMSNorm (lut::rmsnorm_int8) over `n` elements.
+    /// `data` holds the v1 LUTs, then x and the norm weight (n bytes each).
+    pub fn bench_rmsnorm(ctx: Context<BenchKernel>, n: u32, weight_scale: u32) -> Result<()> {
+        let data = ctx.accounts.data.try_borrow_data()?;
+
+        let n = n as usize;
+        let x_offset = LUT_V1_SIZE;
+        let w_offset = x_offset + n;
+        require!(n > 0 && data.len() >= w_offset + n, BenchError::InsufficientData);
+
+        let luts = &data[..LUT_V1_SIZE];
+        let x = as_i8(&data[x_offset..w_offset]);
+        let weight = as_i8(&data[w_offset..w_offset + n]);
+        let mut output = vec![0i8; n];
+
+        msg!("rmsnorm start: n={}", n);
+        let start = sol_remaining_compute_units();
+
+        lut::rmsnorm_int8(luts, x, weight, &mut output, weight_scale as i32);
+
+        let cu = start - sol_remaining_compute_units();
+        msg!("rmsnorm done: n={}", n);
+        let checksum = output.iter().map(|&v| v as i64).sum();
+        record(
+            &mut ctx.accounts.results,
+            OP_RMSNORM,
+            [n, weight_scale as usize, 0],
+            cu,
+            checksum,
+        )
+    }
+
+    /// Benchmark the kernel's requantization of `n` i32 accumulators to
+    /// INT8, per channel (matmul::requantize_per_channel, as after in_proj
+    /// and out_proj) or per tensor. `data` holds the accumulators (n × i32
+    /// LE), then the scales (n × u16 LE; per tensor uses the first).
+    pub fn bench_requantize(ctx: Context<BenchKernel>, n: u32, per_channel: bool) -> Result<()> {
+        let data = ctx.accounts.data.try_borrow_data()?;
+
+        let n = n as usize;
+        let scales_offset = n * 4;
+        require!(data.len() >= scales_offset + n * 2, BenchError::InsufficientData);
+
+        // Account data has no alignment guarantee for i32/u16; decode
+        // outside the timed region
+        let input: Vec<i32> = data[..scales_offset]
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let scales: Vec<u16> = data[scales_offset..scales_offset + n * 2]
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        let mut output = vec![0i8; n];
+
+        msg!("requantize start: n={}, per_channel={}", n, per_channel);
+        let start = sol_remaining_compute_units();
+
+        if per_channel {
+            matmul::requantize_per_channel(&input, &scales, &mut output, n);
+        } else {
+            let scale = scales.first().copied().unwrap_or(0);
+            matmul::requantize_per_tensor(&input, scale, &mut output, n);
+        }
+
+        let cu = start - sol_remaining_compute_units();
+        msg!("requantize done: n={}", n);
+        let checksum = output.iter().map(|&v| v as i64).sum();
+        record(
+            &mut ctx.accounts.results,
+            OP_REQUANTIZE,
+            [n, per_channel as usize, 0],
+            cu,
+            checksum,
+        )
+    }
+
+    /// Benchmark one step of Mamba2's depthwise causal conv1d over `d_inner`
+    /// channels: each output is the dot product of a channel's
+    /// `kernel_size` taps with its last `kernel_size - 1` inputs and the
+    /// new one, after which the channel's window shifts. `data` holds the
+    /// taps (d_inner × kernel_size), the window (d_inner × (kernel_size - 1),
+    /// oldest first) and x (d_inner).
+    pub fn bench_conv1d(ctx: Context<BenchKernel>, d_inner: u32, kernel_size: u8) -> Result<()> {
+        let data = ctx.accounts.data.try_borrow_data()?;
+
+        let d_inner = d_inner as usize;
+        let k = kernel_size as usize;
+        require!((2..=4).contains(&k), BenchError::InvalidKernelSize);
+        let window_offset = d_inner * k;
+        let x_offset = window_offset + d_inner * (k - 1);
+        require!(data.len() >= x_offset + d_inner, BenchError::InsufficientData);
+
+        let weights = as_i8(&data[..window_offset]);
+        let x = as_i8(&data[x_offset..x_offset + d_inner]);
+        let mut window = as_i8(&data[window_offset..x_offset]).to_vec();
+        let mut output = vec![0i8; d_inner];
+
+        msg!("conv1d start: d_inner={}, kernel_size={}", d_inner, k);
+        let start = sol_remaining_compute_units();
+
+        for c in 0..d_inner {
+            let w = &weights[c * k..(c + 1) * k];
+            let s = &mut window[c * (k - 1)..(c + 1) * (k - 1)];
+            let mut acc = w[k - 1] as i32 * x[c] as i32;
+            for i in 0..k - 1 {
+                acc += w[i] as i32 * s[i] as i32;
+            }
+            output[c] = (acc >> 7).clamp(-128, 127) as i8;
+            // Drop the oldest input, append the new one
+            s.copy_within(1.., 0);
+            s[k - 2] = x[c];
+        }
+
+        let cu = start - sol_remaining_compute_units();
+        msg!("conv1d done: d_inner={}, kernel_size={}", d_inner, k);
+        let checksum = output
+            .iter()
+            .chain(window.iter())
+            .map(|&v| v as i64)
+            .sum();
+        record(&mut ctx.accounts.results, OP_CONV1D, [d_inner, k, 0], cu, checksum)
+    }
+
+    /// Benchmark reading `len` bytes of account data in one access pattern,
+    /// for the weight-layout decision: byte loads in order
+    /// (ACCESS_SEQUENTIAL), byte loads down the columns of a row-major
+    /// matrix `stride` bytes wide (ACCESS_STRIDED, what a transposed
+    /// weight layout costs the kernel), or u32 loads in order
+    /// (ACCESS_PACKED). All skip bounds checks, so only the pattern differs.
+    pub fn bench_memory_access(
+        ctx: Context<BenchKernel>,
+        len: u32,
+        pattern: u8,
+        stride: u32,
+    ) -> Result<()> {
+        let data = ctx.accounts.data.try_borrow_data()?;
+
+        let len = len as usize;
+        let stride = stride as usize;
+        require!(data.len() >= len, BenchError::InsufficientData);
+        require!(
+            pattern != ACCESS_STRIDED || (stride > 0 && len % stride == 0),
+            BenchError::InsufficientData
+        );
+        require!(
+            pattern != ACCESS_PACKED || len % 4 == 0,
+            BenchError::InsufficientData
+        );
+
+        msg!("memory_access start: len={}, pattern={}, stride={}", len, pattern, stride);
+        let start = sol_remaining_compute_units();
+
+        let mut checksum: u32 = 0;
+        // SAFETY: every index is below len, checked above
+        unsafe {
+            match pattern {
+                ACCESS_SEQUENTIAL => {
+                    for i in 0..len {
+                        checksum = checksum.wrapping_add(*data.get_unchecked(i) as u32);
+                    }
+                }
+                ACCESS_STRIDED => {
+                    let rows = len / stride;
+                    for j in 0..stride {
+                        for i in 0..rows {
+                            let b = *data.get_unchecked(i * stride + j);
+                            checksum = checksum.wrapping_add(b as u32);
+                        }
+                    }
+                }
+                ACCESS_PACKED => {
+                    for i in 0..len / 4 {
+                        let w4 = (data.as_ptr().add(i * 4) as *const u32).read_unaligned();
+                        checksum = checksum
+                            .wrapping_add(w4 & 0xff)
+                            .wrapping_add((w4 >> 8) & 0xff)
+                            .wrapping_add((w4 >> 16) & 0xff)
+                            .wrapping_add(w4 >> 24);
+                    }
+                }
+                _ => return err!(BenchError::UnknownPattern),
+            }
+        }
+
+        let cu = start - sol_remaining_compute_units();
+        msg!("memory_access done: checksum={}", checksum);
+        record(
+            &mut ctx.accounts.results,
+            OP_MEMORY_ACCESS,
+            [len, pattern as usize, stride],
+            cu,
+            checksum as i64,
+        )
+    }
+
+    /// Benchmark the same matmul through the BPF kernel and the
+    /// `sol_matmul_i8` syscall back to back. Records the kernel's CU in
+    /// `cu` and the syscall's in `cu_syscall`; fails if the outputs differ.
+    /// Needs the er-syscall build on a MagicBlock ER validator.
+    pub fn bench_matmul_syscall(ctx: Context<BenchMatmul>, rows: u32, cols: u32) -> Result<()> {
+        let data = ctx.accounts.benchmark.try_borrow_data()?;
+
+        let rows = rows as usize;
+        let cols = cols as usize;
+        let weight_size = rows * cols;
+        let total_needed = weight_size + cols + rows;
+
+        require!(data.len() >= total_needed, BenchError::InsufficientData);
+
+        let weights = &data[..weight_size];
+        let input: Vec<i8> = data[weight_size..weight_size + cols]
+            .iter()
+            .map(|&b| b as i8)
+            .collect();
+        let mut bpf_out = vec![0i32; rows];
+        let mut native_out = vec![0i32; rows];
+
+        msg!("matmul_syscall start: {}x{}", rows, cols);
+
+        let start = sol_remaining_compute_units();
+        matmul::matmul_i8(weights, &input, &mut bpf_out, rows, cols);
+        let bpf_cu = start - sol_remaining_compute_units();
+
+        let start = sol_remaining_compute_units();
+        let ok = matmul::matmul_i8_native(weights, &input, &mut native_out, rows, cols);
+        let syscall_cu = start - sol_remaining_compute_units();
+
+        require!(ok, BenchError::SyscallUnavailable);
+        require!(bpf_out == native_out, BenchError::KernelMismatch);
+
+        msg!(
+            "matmul_syscall done: {}x{} bpf={} CU syscall={} CU",
+            rows,
+            cols,
+            bpf_cu,
+            syscall_cu
+        );
+        let checksum = bpf_out.iter().map(|&v| v as i64).sum();
+        record(&mut ctx.accounts.results, OP_MATMUL_SYSCALL, [rows, cols, 0], bpf_cu, checksum)?;
+        if let Some(results) = &mut ctx.accounts.results {
+            results.cu_syscall = syscall_cu;
+        }
         Ok(())
     }
+
+    /// Benchmark one whole frame of a real uploaded model: encode both
+    /// players, run the forward pass over the first `max_layers` layers
+    /// (0 = all) and decode — exactly what run_inference does under
+    /// mamba2-inference, with the manifest's own dims, tables and LUTs.
+    ///
+    /// Remaining accounts: the model's WeightAccounts in shard index order.
+    /// Layers run their dense tensor table entries, without expert routing.
+    /// `hidden` is a scratch account owned by this program, at least the
+    /// model's hidden data size; it starts from whatever it holds. Larger
+    /// layer counts need a heap frame above the default 32KB.
+    pub fn bench_forward_pass(ctx: Context<BenchForwardPass>, max_layers: u8) -> Result<()> {
+        let mut manifest = ctx.accounts.manifest.clone().into_inner();
+        if max_layers > 0 {
+            manifest.num_layers = manifest.num_layers.min(max_layers);
+        }
+        require!(
+            ctx.remaining_accounts.len() == manifest.expected_shards as usize,
+            BenchError::InsufficientData
+        );
+
+        let shard_data = ctx
+            .remaining_accounts
+            .iter()
+            .map(|a| a.try_borrow_data())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let shards = shard_data
+            .iter()
+            .map(|d| d.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[]))
+            .collect::<Vec<&[u8]>>();
+
+        let hidden_size = hidden_data_size(
+            manifest.num_layers,
+            manifest.d_inner,
+            manifest.d_state,
+            manifest.hidden_dtype,
+        ) as usize;
+        let mut h_data = ctx.accounts.hidden.try_borrow_mut_data()?;
+        require!(h_data.len() >= hidden_size, BenchError::InsufficientData);
+        let hidden = hidden_state_mut(manifest.hidden_dtype, &mut h_data[..hidden_size])
+            .ok_or(BenchError::InsufficientData)?;
+
+        let mut players = PlayerState::spawns([2, 20], 0, STAGE_FINAL_DESTINATION);
+        let inputs = [
+            ControllerInput { stick_x: 64, buttons: 0x01, ..Default::default() },
+            ControllerInput { stick_x: -64, ..Default::default() },
+        ];
+
+        msg!(
+            "forward_pass start: d_model={}, d_inner={}, d_state={}, layers={}",
+            manifest.d_model,
+            manifest.d_inner,
+            manifest.d_state,
+            manifest.num_layers
+        );
+        let start = sol_remaining_compute_units();
+
+        let tick_rate = DEFAULT_TICK_RATE as u32;
+        step_players_mamba2(
+            &manifest,
+            &mut players,
+            &inputs,
+            31,
+            tick_rate,
+            hidden,
+            &shards,
+            None,
+            0,
+            &mut [],
+        )
+        .ok_or(BenchError::InsufficientData)?;
+
+        let cu = start - sol_remaining_compute_units();
+        let checksum = players.iter().fold(0i64, |acc, p| {
+            acc + p.x as i64 + p.y as i64 + p.percent as i64 + p.action_state as i64
+        });
+        msg!("forward_pass done: {} CU, checksum={}", cu, checksum);
+        record(
+            &mut ctx.accounts.results,
+            OP_FORWARD_PASS,
+            [
+                manifest.d_model as usize,
+                manifest.d_inner as usize,
+                manifest.num_layers as usize,
+            ],
+            cu,
+            checksum,
+        )
+    }
+}
+
+/// Write one bench's result into the results account, if one was passed.
+fn record(
+    results: &mut Option<Account<BenchResults>>,
+    op: u8,
+    dims: [usize; 3],
+    cu: u64,
+    checksum: i64,
+) -> Result<()> {
+    if let Some(results) = results {
+        results.op = op;
+        results.dims = dims.map(|d| d as u32);
+        results.cu = cu;
+        results.cu_syscall = 0;
+        results.checksum = checksum;
+        results.slot = Clock::get()?.slot;
+        results.runs += 1;
+    }
+    Ok(())
+}
+
+/// Reinterpret account bytes as the kernels' i8 activations.
+fn as_i8(bytes: &[u8]) -> &[i8] {
+    // SAFETY: i8 and u8 share size and alignment
+    unsafe { core::slice::from_raw_parts(bytes.as_ptr() as *const i8, bytes.len()) }
+}
+
+/// Structured result of the last bench run against this account.
+#[account]
+pub struct BenchResults {
+    /// Who created the account
+    pub authority: Pubkey,
+    /// Which bench wrote the record (OP_*)
+    pub op: u8,
+    /// The bench's dimension arguments, unused entries 0
+    /// (LUT: num_elements, activation_type; forward pass: d_model, d_inner,
+    /// layers run; rmsnorm: n, weight_scale; requantize: n, per_channel;
+    /// conv1d: d_inner, kernel_size; memory access: len, pattern, stride)
+    pub dims: [u32; 3],
+    /// CU spent in the kernel loop, from sol_remaining_compute_units
+    /// before and after (includes one syscall, excludes logging)
+    pub cu: u64,
+    /// The sol_matmul_i8 side of bench_matmul_syscall, 0 for other benches
+    pub cu_syscall: u64,
+    /// Fold of the kernel's outputs; also keeps the loop from being
+    /// optimized away
+    pub checksum: i64,
+    /// Slot of the run
+    pub slot: u64,
+    /// Records written so far
+    pub runs: u32,
+}
+
+/// One row of a sweep table.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SweepEntry {
+    /// OP_SWEEP_*
+    pub op: u8,
+    /// The shape: (rows, cols) or (d_inner, d_state)
+    pub dims: [u32; 2],
+    /// CU spent in the kernel call
+    pub cu: u64,
+    /// Fold of the kernel's outputs
+    pub checksum: i64,
+}
+
+/// CU table filled by bench_sweep, one row per benchmarked shape.
+#[account]
+pub struct SweepResults {
+    /// Who created the account
+    pub authority: Pubkey,
+    /// Rows filled so far
+    pub len: u8,
+    pub entries: [SweepEntry; MAX_SWEEP_ENTRIES],
+}
+
+impl SweepResults {
+    fn push(&mut self, op: u8, dims: [usize; 2], cu: u64, checksum: i64) {
+        self.entries[self.len as usize] = SweepEntry {
+            op,
+            dims: dims.map(|d| d as u32),
+            cu,
+            checksum,
+        };
+        self.len += 1;
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitResults<'info> {
+    #[account(init, payer = payer, space = 8 + std::mem::size_of::<BenchResults>())]
+    pub results: Account<'info, BenchResults>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitSweep<'info> {
+    #[account(init, payer = payer, space = 8 + std::mem::size_of::<SweepResults>())]
+    pub sweep: Account<'info, SweepResults>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BenchSweep<'info> {
+    /// CHECK: Benchmark data account, sized for the largest shape.
+    pub data: AccountInfo<'info>,
+    #[account(mut)]
+    pub sweep: Account<'info, SweepResults>,
 }
 
 #[derive(Accounts)]
 pub struct BenchMatmul<'info> {
     /// CHECK: Benchmark data account — no ownership checks needed.
     pub benchmark: AccountInfo<'info>,
+    #[account(mut)]
+    pub results: Option<Account<'info, BenchResults>>,
 }
 
 #[derive(Accounts)]
 pub struct BenchLut<'info> {
     /// CHECK: LUT data account.
     pub lut: AccountInfo<'info>,
+    #[account(mut)]
+    pub results: Option<Account<'info, BenchResults>>,
 }
 
 #[derive(Accounts)]
 pub struct BenchSsm<'info> {
     /// CHECK: SSM data account.
     pub ssm_data: AccountInfo<'info>,
+    #[account(mut)]
+    pub results: Option<Account<'info, BenchResults>>,
+}
+
+#[derive(Accounts)]
+pub struct BenchKernel<'info> {
+    /// CHECK: Kernel input data account.
+    pub data: AccountInfo<'info>,
+    #[account(mut)]
+    pub results: Option<Account<'info, BenchResults>>,
 }
 
 #[derive(Accounts)]
@@ -357,10 +987,35 @@ pub struct BenchFullLayer<'info> {
     pub weights: AccountInfo<'info>,
     /// CHECK: State data account.
     pub state: AccountInfo<'info>,
+    #[account(mut)]
+    pub results: Option<Account<'info, BenchResults>>,
+}
+
+#[derive(Accounts)]
+pub struct BenchForwardPass<'info> {
+    /// A world-model manifest (its tensor table locates the weights)
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// CHECK: Hidden state scratch, raw elements without a header.
+    #[account(mut)]
+    pub hidden: AccountInfo<'info>,
+    #[account(mut)]
+    pub results: Option<Account<'info, BenchResults>>,
 }
 
 #[error_code]
 pub enum BenchError {
     #[msg("Account data too small for specified dimensions")]
     InsufficientData,
+    #[msg("sol_matmul_i8 syscall unavailable (build with er-syscall on an ER validator)")]
+    SyscallUnavailable,
+    #[msg("Syscall matmul output differs from the BPF kernel")]
+    KernelMismatch,
+    #[msg("Sweep table has no room for these shapes")]
+    SweepFull,
+    #[msg("Op cannot be swept")]
+    UnknownOp,
+    #[msg("Conv1d kernel size must be 2 to 4")]
+    InvalidKernelSize,
+    #[msg("Unknown memory access pattern")]
+    UnknownPattern,
 }
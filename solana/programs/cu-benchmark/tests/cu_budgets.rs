@@ -0,0 +1,364 @@
+/// CU regression suite — runs each bench instruction under Mollusk and fails
+/// when it spends more than its budget, so a kernel change that gives back
+/// CU (e.g. a refactor that loses the packed u32 loads) fails here instead
+/// of turning up in the next devnet run.
+///
+/// Budgets are whole-instruction CU. Where docs/cu-benchmark-findings.md or
+/// docs/packed-matmul-explainer.md has a measured figure the budget is that
+/// plus ~10%; the rest are estimates, marked as such, to tighten once
+/// measured.
+///
+/// Prerequisites: `cargo build-sbf --manifest-path programs/cu-benchmark/Cargo.toml`
+/// (the .so must exist at programs/cu-benchmark/target/deploy/cu_benchmark.so)
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use cu_benchmark::{accounts, instruction, SweepEntry, SweepResults, MAX_SWEEP_ENTRIES};
+use cu_benchmark::{OP_SWEEP_MATMUL, OP_SWEEP_SSM};
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use world_model::fixture::SyntheticModel;
+use world_model::state::WEIGHT_HEADER_SIZE;
+
+fn svm_key(key: &Pubkey) -> solana_pubkey::Pubkey {
+    solana_pubkey::Pubkey::new_from_array(key.to_bytes())
+}
+
+/// The program uses Anchor's Solana types; Mollusk uses its own.
+fn svm_instruction(ix: &Instruction) -> solana_instruction::Instruction {
+    solana_instruction::Instruction {
+        program_id: svm_key(&ix.program_id),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|m| solana_instruction::AccountMeta {
+                pubkey: svm_key(&m.pubkey),
+                is_signer: m.is_signer,
+                is_writable: m.is_writable,
+            })
+            .collect(),
+        data: ix.data.clone(),
+    }
+}
+
+fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: cu_benchmark::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn mollusk() -> Mollusk {
+    // cu-benchmark is excluded from the workspace, so its .so lives in its own target dir.
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    std::env::set_var(
+        "SBF_OUT_DIR",
+        std::path::Path::new(manifest_dir).join("target/deploy"),
+    );
+    let mut mollusk = Mollusk::default();
+    mollusk.add_program_with_loader(
+        &svm_key(&cu_benchmark::ID),
+        "cu_benchmark",
+        &mollusk_svm::program::loader_keys::LOADER_V3,
+    );
+    mollusk
+}
+
+/// A zeroed data account, as bench-direct.mjs creates them.
+fn data_account(len: usize, owner: &Pubkey) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data: vec![0; len],
+        owner: svm_key(owner),
+        ..Account::default()
+    }
+}
+
+/// Run `ix` with `accounts`, assert it succeeds within `budget` CU, and
+/// return the resulting accounts.
+fn within_budget(
+    mollusk: &Mollusk,
+    label: &str,
+    ix: &Instruction,
+    accounts: Vec<(Pubkey, Account)>,
+    budget: u64,
+) -> Vec<(solana_pubkey::Pubkey, Account)> {
+    let mut accounts: Vec<_> = accounts
+        .into_iter()
+        .map(|(k, a)| (svm_key(&k), a))
+        .collect();
+    // Placeholder for an omitted optional results account
+    if ix.accounts.iter().any(|m| m.pubkey == cu_benchmark::ID) {
+        accounts.push((
+            svm_key(&cu_benchmark::ID),
+            mollusk_svm::program::create_program_account_loader_v3(&svm_key(&cu_benchmark::ID)),
+        ));
+    }
+
+    let result = mollusk.process_instruction(&svm_instruction(ix), &accounts);
+    assert!(
+        result.raw_result.is_ok(),
+        "{label} failed: {:?}",
+        result.raw_result
+    );
+    assert!(
+        result.compute_units_consumed <= budget,
+        "{label}: {} CU exceeds budget of {budget}",
+        result.compute_units_consumed
+    );
+    result.resulting_accounts
+}
+
+/// bench_matmul-style instruction over a fresh weights + input account.
+fn matmul(mollusk: &Mollusk, label: &str, args: impl InstructionData, dims: usize, budget: u64) {
+    let benchmark = Pubkey::new_unique();
+    let ix = build(
+        accounts::BenchMatmul {
+            benchmark,
+            results: None,
+        },
+        args,
+    );
+    let account = data_account(dims * dims + 2 * dims, &Pubkey::default());
+    within_budget(mollusk, label, &ix, vec![(benchmark, account)], budget);
+}
+
+#[test]
+fn matmul_budgets() {
+    let m = mollusk();
+    let (rows, cols) = (64, 64);
+    matmul(
+        &m,
+        "matmul 64",
+        instruction::BenchMatmul { rows, cols },
+        64,
+        116_000,
+    );
+    let (rows, cols) = (128, 128);
+    matmul(
+        &m,
+        "matmul 128",
+        instruction::BenchMatmul { rows, cols },
+        128,
+        447_000,
+    );
+    matmul(
+        &m,
+        "matmul_unsafe 128",
+        instruction::BenchMatmulUnsafe { rows, cols },
+        128,
+        374_000,
+    );
+    matmul(
+        &m,
+        "matmul_packed 128",
+        instruction::BenchMatmulPacked { rows, cols },
+        128,
+        293_000,
+    );
+    let (rows, cols) = (256, 256);
+    matmul(
+        &m,
+        "matmul_packed 256",
+        instruction::BenchMatmulPacked { rows, cols },
+        256,
+        1_143_000,
+    );
+}
+
+#[test]
+fn lut_budgets() {
+    let m = mollusk();
+    for activation_type in 0..3 {
+        let lut = Pubkey::new_unique();
+        let ix = build(
+            accounts::BenchLut { lut, results: None },
+            instruction::BenchLutActivation {
+                num_elements: 1024,
+                activation_type,
+            },
+        );
+        let account = data_account(768 + 1024, &Pubkey::default());
+        let label = format!("lut {activation_type} 1024");
+        within_budget(&m, &label, &ix, vec![(lut, account)], 14_600);
+    }
+}
+
+#[test]
+fn ssm_step_budgets() {
+    let m = mollusk();
+    for (d_inner, d_state, budget) in [(256, 16, 175_000), (512, 16, 348_000)] {
+        let ssm_data = Pubkey::new_unique();
+        let ix = build(
+            accounts::BenchSsm {
+                ssm_data,
+                results: None,
+            },
+            instruction::BenchSsmStep { d_inner, d_state },
+        );
+        let h_size = (d_inner * d_state) as usize;
+        let account = data_account(512 + 3 * d_inner as usize + 3 * h_size, &Pubkey::default());
+        let label = format!("ssm_step {d_inner}x{d_state}");
+        within_budget(&m, &label, &ix, vec![(ssm_data, account)], budget);
+    }
+}
+
+#[test]
+fn norm_and_requantize_budgets() {
+    let m = mollusk();
+    // Estimated: ~30 CU/element for RMSNorm, ~20 for requantize, plus
+    // decoding the requantize inputs
+    let n = 512;
+    let data = Pubkey::new_unique();
+    let ix = build(
+        accounts::BenchKernel {
+            data,
+            results: None,
+        },
+        instruction::BenchRmsnorm {
+            n,
+            weight_scale: 256,
+        },
+    );
+    let account = data_account(1024 + 2 * n as usize, &Pubkey::default());
+    within_budget(&m, "rmsnorm 512", &ix, vec![(data, account)], 25_000);
+
+    for per_channel in [true, false] {
+        let ix = build(
+            accounts::BenchKernel {
+                data,
+                results: None,
+            },
+            instruction::BenchRequantize { n, per_channel },
+        );
+        let account = data_account(6 * n as usize, &Pubkey::default());
+        let label = format!("requantize 512 per_channel={per_channel}");
+        within_budget(&m, &label, &ix, vec![(data, account)], 40_000);
+    }
+}
+
+#[test]
+fn conv1d_budget() {
+    let m = mollusk();
+    // Estimated: ~20 CU per tap plus ~40 per channel
+    let (d_inner, kernel_size) = (2048, 4);
+    let data = Pubkey::new_unique();
+    let ix = build(
+        accounts::BenchKernel {
+            data,
+            results: None,
+        },
+        instruction::BenchConv1d {
+            d_inner,
+            kernel_size,
+        },
+    );
+    let account = data_account(8 * d_inner as usize, &Pubkey::default());
+    within_budget(&m, "conv1d 2048 k4", &ix, vec![(data, account)], 250_000);
+}
+
+/// The awm-kernel implementations, through bench_sweep's table, so the
+/// budget covers the kernel call alone.
+#[test]
+fn kernel_sweep_budgets() {
+    let m = mollusk();
+    let sweep_table = SweepResults {
+        authority: Pubkey::default(),
+        len: 0,
+        entries: [SweepEntry::default(); MAX_SWEEP_ENTRIES],
+    };
+    let mut sweep_data = Vec::new();
+    sweep_table.try_serialize(&mut sweep_data).unwrap();
+    sweep_data.resize(8 + core::mem::size_of::<SweepResults>(), 0);
+
+    // op, shapes, data account size, per-row budget. The kernel matmul has
+    // the packed loads, so it should cost what bench_matmul_packed does;
+    // the SSM budget is an estimate.
+    let cases = [
+        (OP_SWEEP_MATMUL, [128, 128], 128 * 128 + 128, 293_000),
+        (OP_SWEEP_SSM, [256, 16], 1024 + 3 * 256 + 256 * 16, 175_000),
+    ];
+    for (op, shape, len, budget) in cases {
+        let (data, sweep) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = build(
+            accounts::BenchSweep { data, sweep },
+            instruction::BenchSweep {
+                op,
+                shapes: vec![shape],
+            },
+        );
+        let mut sweep_account = data_account(0, &cu_benchmark::ID);
+        sweep_account.data = sweep_data.clone();
+        let label = format!("sweep op {op} {shape:?}");
+        let resulting = within_budget(
+            &m,
+            &label,
+            &ix,
+            vec![
+                (data, data_account(len, &Pubkey::default())),
+                (sweep, sweep_account),
+            ],
+            budget + 20_000,
+        );
+
+        // Row layout: op(1) dims(2×u32) cu(u64) checksum(i64), after
+        // disc(8) authority(32) len(1)
+        let table = &resulting[1].1.data;
+        assert_eq!(table[40], 1);
+        let cu = u64::from_le_bytes(table[50..58].try_into().unwrap());
+        assert!(
+            cu <= budget,
+            "{label}: kernel {cu} CU exceeds budget of {budget}"
+        );
+    }
+}
+
+/// A whole frame of the synthetic fixture model through the real
+/// mamba2 inference path. Estimated budget.
+#[test]
+fn forward_pass_budget() {
+    let m = mollusk();
+    let fixture = SyntheticModel::tiny();
+
+    let mut manifest_data = Vec::new();
+    fixture
+        .manifest()
+        .try_serialize(&mut manifest_data)
+        .unwrap();
+    let mut manifest_account = data_account(0, &world_model::ID);
+    manifest_account.data = manifest_data;
+
+    let (manifest, hidden) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let shards: Vec<(Pubkey, Account)> = (0..fixture.shards.len())
+        .map(|i| {
+            let mut account = data_account(0, &world_model::ID);
+            account.data = fixture.weight_account_data(i, Pubkey::default(), true);
+            assert!(account.data.len() > WEIGHT_HEADER_SIZE);
+            (Pubkey::new_unique(), account)
+        })
+        .collect();
+
+    let mut ix = build(
+        accounts::BenchForwardPass {
+            manifest,
+            hidden,
+            results: None,
+        },
+        instruction::BenchForwardPass { max_layers: 0 },
+    );
+    // The remaining accounts follow the placeholder for `results`
+    ix.accounts.extend(shards.iter().map(|(k, _)| {
+        anchor_lang::solana_program::instruction::AccountMeta::new_readonly(*k, false)
+    }));
+
+    let mut accounts = vec![
+        (manifest, manifest_account),
+        (
+            hidden,
+            data_account(fixture.hidden_data_size() as usize, &cu_benchmark::ID),
+        ),
+    ];
+    accounts.extend(shards);
+    within_budget(&m, "forward_pass tiny", &ix, accounts, 500_000);
+}
@@ -0,0 +1,353 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use awm_events::{ChallengeOpened, FraudProven};
+use world_model::inference::step_players;
+use world_model::state::{
+    frame_commitment, ControllerInput, PlayerState, SessionStateAccount, CRANK_MODE_DESIGNATED,
+    NUM_PLAYERS, STATUS_ENDED,
+};
+
+declare_id!("FrauDProof111111111111111111111111111111111");
+
+/// Seed prefix for an operator bond: ["bond", session]
+pub const BOND_SEED: &[u8] = b"bond";
+/// Seed prefix for a challenge: ["challenge", session, challenger]
+pub const CHALLENGE_SEED: &[u8] = b"challenge";
+/// How long after a session ends its bond stays slashable (24 hours)
+pub const CHALLENGE_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+/// Fraud proofs for rollup-executed sessions.
+///
+/// An operator runs a session off mainnet (ephemeral rollup) and posts a
+/// bond against it. The operator is the session's designated cranker
+/// (set_crank_mode), the only account that can produce its frames. When
+/// the session comes back, its SessionStateAccount carries the rolling
+/// state commitment
+///
+///   c_n = SHA-256(c_{n-1} || n || players_n || inputs_n)
+///
+/// so every frame the operator produced is bound to the final c_N. Anyone
+/// can dispute frame k by posting
+///
+///   c_{k-2}, players_{k-1}, inputs_{k-1}   → pre-state, bound into c_{k-1}
+///   inputs_k, claimed players_k            → the operator's post-state
+///
+/// The program re-executes frame k on mainnet and requires the result to
+/// differ from the claim. The challenger then extends the chain with the
+/// logged (players, inputs) for frames k+1..N, split across as many
+/// transactions as needed. If the rolled commitment reaches the session's
+/// c_N, the claimed post-state really is what the operator committed, and
+/// the bond goes to the challenger.
+///
+/// Frame 1 can't be disputed: its pre-state (the session's initial players)
+/// isn't part of the chain.
+#[program]
+pub mod fraud_proof {
+    use super::*;
+
+    /// Post an operator bond against a session that hasn't ended. Signer
+    /// must be the session's designated cranker. The bond is slashable
+    /// until CHALLENGE_WINDOW_SECS after the session ends.
+    pub fn post_bond(ctx: Context<PostBond>, amount: u64) -> Result<()> {
+        require!(amount > 0, FraudProofError::InvalidAmount);
+        {
+            let session = &ctx.accounts.session.load()?;
+            require!(session.status != STATUS_ENDED, FraudProofError::SessionEnded);
+            require!(
+                session.crank_mode == CRANK_MODE_DESIGNATED
+                    && session.crank_authority == ctx.accounts.operator.key(),
+                FraudProofError::NotSessionOperator
+            );
+        }
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.operator.to_account_info(),
+                    to: ctx.accounts.bond.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let bond = &mut ctx.accounts.bond;
+        bond.session = ctx.accounts.session.key();
+        bond.operator = ctx.accounts.operator.key();
+        bond.amount = amount;
+        bond.bump = ctx.bumps.bond;
+
+        msg!("Bond posted: session={}, amount={}", bond.session, amount);
+        Ok(())
+    }
+
+    /// Reclaim the bond once the session has ended and the challenge window
+    /// has passed.
+    pub fn withdraw_bond(ctx: Context<WithdrawBond>) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        require!(
+            session.status == STATUS_ENDED,
+            FraudProofError::ChallengeWindowOpen
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= session.last_update + CHALLENGE_WINDOW_SECS,
+            FraudProofError::ChallengeWindowOpen
+        );
+
+        msg!("Bond withdrawn: session={}", ctx.accounts.session.key());
+        Ok(())
+    }
+
+    /// Dispute frame `frame`: re-execute it from the posted pre-state and
+    /// start rolling the commitment chain from the claimed post-state.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_challenge(
+        ctx: Context<OpenChallenge>,
+        frame: u32,
+        anchor: [u8; 32],
+        pre_players: [PlayerState; NUM_PLAYERS],
+        pre_inputs: [ControllerInput; NUM_PLAYERS],
+        inputs: [ControllerInput; NUM_PLAYERS],
+        claimed_players: [PlayerState; NUM_PLAYERS],
+    ) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        require!(
+            (2..=session.frame).contains(&frame),
+            FraudProofError::FrameOutOfRange
+        );
+
+        // c_{k-1}: binds the pre-state once the chain reaches c_N
+        let pre_commitment = frame_commitment(&anchor, frame - 1, &pre_players, &pre_inputs)?;
+        let claimed = frame_commitment(&pre_commitment, frame, &claimed_players, &inputs)?;
+
+        // Re-execute the disputed frame
+        let mut expected_players = pre_players;
+        step_players(&mut expected_players, &inputs, session.stage);
+        let expected = frame_commitment(&pre_commitment, frame, &expected_players, &inputs)?;
+        require!(expected != claimed, FraudProofError::NotFraudulent);
+
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.session = ctx.accounts.session.key();
+        challenge.challenger = ctx.accounts.challenger.key();
+        challenge.disputed_frame = frame;
+        challenge.frame = frame;
+        challenge.commitment = claimed;
+        challenge.bump = ctx.bumps.challenge;
+
+        emit!(ChallengeOpened {
+            session: challenge.session,
+            challenger: challenge.challenger,
+            frame,
+        });
+        msg!("Challenge opened: session={}, frame={}", ctx.accounts.session.key(), frame);
+        Ok(())
+    }
+
+    /// Roll the challenge's commitment forward over logged frames.
+    pub fn extend_challenge(ctx: Context<ExtendChallenge>, links: Vec<FrameLink>) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let challenge = &mut ctx.accounts.challenge;
+        require!(
+            challenge.frame as u64 + links.len() as u64 <= session.frame as u64,
+            FraudProofError::FrameOutOfRange
+        );
+
+        for link in links.iter() {
+            challenge.frame += 1;
+            challenge.commitment = frame_commitment(
+                &challenge.commitment,
+                challenge.frame,
+                &link.players,
+                &link.inputs,
+            )?;
+        }
+
+        msg!("Challenge extended to frame {} / {}", challenge.frame, session.frame);
+        Ok(())
+    }
+
+    /// Check the rolled chain against the session's commitment and slash
+    /// the bond to the challenger.
+    pub fn resolve_challenge(ctx: Context<ResolveChallenge>) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let challenge = &ctx.accounts.challenge;
+        require!(
+            challenge.frame == session.frame,
+            FraudProofError::ChainIncomplete
+        );
+        require!(
+            challenge.commitment == session.state_commitment,
+            FraudProofError::CommitmentMismatch
+        );
+
+        emit!(FraudProven {
+            session: challenge.session,
+            challenger: challenge.challenger,
+            frame: challenge.disputed_frame,
+            slashed: ctx.accounts.bond.amount,
+        });
+        msg!("Fraud proven: session={}, frame={}, bond {} lamports slashed",
+             ctx.accounts.session.key(), challenge.disputed_frame, ctx.accounts.bond.amount);
+        Ok(())
+    }
+
+    /// Abandon a challenge and reclaim its rent.
+    pub fn close_challenge(_ctx: Context<CloseChallenge>) -> Result<()> {
+        msg!("Challenge closed");
+        Ok(())
+    }
+}
+
+// ── Account structures ──────────────────────────────────────────────────────
+
+#[account]
+#[derive(Default)]
+pub struct OperatorBond {
+    pub session: Pubkey,
+    pub operator: Pubkey,
+    /// Lamports posted on top of rent
+    pub amount: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(Default)]
+pub struct ChallengeAccount {
+    pub session: Pubkey,
+    pub challenger: Pubkey,
+    /// Frame whose post-state is disputed
+    pub disputed_frame: u32,
+    /// Last frame folded into `commitment`
+    pub frame: u32,
+    /// Rolling commitment through `frame`
+    pub commitment: [u8; 32],
+    pub bump: u8,
+}
+
+/// One logged frame: post-state players and the inputs that produced it.
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct FrameLink {
+    pub players: [PlayerState; NUM_PLAYERS],
+    pub inputs: [ControllerInput; NUM_PLAYERS],
+}
+
+// ── Account contexts ────────────────────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct PostBond<'info> {
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + std::mem::size_of::<OperatorBond>(),
+        seeds = [BOND_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub bond: Account<'info, OperatorBond>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(mut)]
+    pub operator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBond<'info> {
+    #[account(
+        mut,
+        seeds = [BOND_SEED, session.key().as_ref()],
+        bump = bond.bump,
+        has_one = operator,
+        close = operator,
+    )]
+    pub bond: Account<'info, OperatorBond>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(mut)]
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenChallenge<'info> {
+    #[account(
+        init,
+        payer = challenger,
+        space = 8 + std::mem::size_of::<ChallengeAccount>(),
+        seeds = [CHALLENGE_SEED, session.key().as_ref(), challenger.key().as_ref()],
+        bump,
+    )]
+    pub challenge: Account<'info, ChallengeAccount>,
+    /// Only bonded sessions can be challenged
+    #[account(seeds = [BOND_SEED, session.key().as_ref()], bump = bond.bump)]
+    pub bond: Account<'info, OperatorBond>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendChallenge<'info> {
+    #[account(
+        mut,
+        seeds = [CHALLENGE_SEED, session.key().as_ref(), challenger.key().as_ref()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, ChallengeAccount>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub challenger: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveChallenge<'info> {
+    #[account(
+        mut,
+        seeds = [CHALLENGE_SEED, session.key().as_ref(), challenger.key().as_ref()],
+        bump = challenge.bump,
+        close = challenger,
+    )]
+    pub challenge: Account<'info, ChallengeAccount>,
+    #[account(
+        mut,
+        seeds = [BOND_SEED, session.key().as_ref()],
+        bump = bond.bump,
+        close = challenger,
+    )]
+    pub bond: Account<'info, OperatorBond>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChallenge<'info> {
+    #[account(
+        mut,
+        seeds = [CHALLENGE_SEED, challenge.session.as_ref(), challenger.key().as_ref()],
+        bump = challenge.bump,
+        close = challenger,
+    )]
+    pub challenge: Account<'info, ChallengeAccount>,
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+}
+
+// ── Errors ──────────────────────────────────────────────────────────────────
+
+#[error_code]
+pub enum FraudProofError {
+    #[msg("Bond amount must be non-zero")]
+    InvalidAmount,
+    #[msg("Session has already ended")]
+    SessionEnded,
+    #[msg("Signer is not the session's designated cranker")]
+    NotSessionOperator,
+    #[msg("Bond is still challengeable")]
+    ChallengeWindowOpen,
+    #[msg("Frame is outside the session's committed range")]
+    FrameOutOfRange,
+    #[msg("Claimed post-state matches re-execution")]
+    NotFraudulent,
+    #[msg("Challenge chain has not reached the session's latest frame")]
+    ChainIncomplete,
+    #[msg("Rolled commitment does not match the session's commitment")]
+    CommitmentMismatch,
+}
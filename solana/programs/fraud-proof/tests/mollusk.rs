@@ -0,0 +1,313 @@
+//! Mollusk tests for the fraud-proof program: bonding a session, then
+//! disputing one of its frames. Sessions are written directly in the
+//! world-model account layout, so world_model.so isn't needed.
+//!
+//! Prerequisites: `cargo build-sbf --manifest-path programs/fraud-proof/Cargo.toml`
+//! (the .so must exist at programs/fraud-proof/target/deploy/fraud_proof.so)
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, Discriminator, InstructionData, ToAccountMetas};
+use fraud_proof::{accounts, instruction, FraudProofError, BOND_SEED, CHALLENGE_SEED};
+use mollusk_svm::result::InstructionResult;
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use solana_instruction::error::InstructionError;
+use world_model::inference::step_players;
+use world_model::state::*;
+
+const LAMPORTS: u64 = 10_000_000_000;
+const BOND: u64 = 1_000_000_000;
+
+fn svm_key(key: &Pubkey) -> solana_pubkey::Pubkey {
+    solana_pubkey::Pubkey::new_from_array(key.to_bytes())
+}
+
+/// The program uses Anchor's Solana types; Mollusk uses its own.
+fn svm_instruction(ix: &Instruction) -> solana_instruction::Instruction {
+    solana_instruction::Instruction {
+        program_id: svm_key(&ix.program_id),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|m| solana_instruction::AccountMeta {
+                pubkey: svm_key(&m.pubkey),
+                is_signer: m.is_signer,
+                is_writable: m.is_writable,
+            })
+            .collect(),
+        data: ix.data.clone(),
+    }
+}
+
+fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: fraud_proof::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &fraud_proof::ID).0
+}
+
+/// Mollusk plus an account store that successful instructions write back to.
+struct Harness {
+    mollusk: Mollusk,
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        // fraud-proof is excluded from the workspace, so its .so lives in its own target dir.
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        std::env::set_var(
+            "SBF_OUT_DIR",
+            std::path::Path::new(manifest_dir).join("target/deploy"),
+        );
+
+        let mollusk = Mollusk::new(&svm_key(&fraud_proof::ID), "fraud_proof");
+        let mut accounts = HashMap::new();
+        let (_, system) = mollusk_svm::program::keyed_account_for_system_program();
+        accounts.insert(system_program::ID, system);
+        Harness { mollusk, accounts }
+    }
+
+    fn wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.accounts.insert(
+            key,
+            Account {
+                lamports: LAMPORTS,
+                owner: svm_key(&system_program::ID),
+                ..Account::default()
+            },
+        );
+        key
+    }
+
+    /// A world-model session account holding `session`.
+    fn session(&mut self, session: &SessionStateAccount) -> Pubkey {
+        let mut data = SessionStateAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(session));
+        let key = Pubkey::new_unique();
+        self.accounts.insert(
+            key,
+            Account {
+                lamports: self.mollusk.sysvars.rent.minimum_balance(data.len()),
+                data,
+                owner: svm_key(&world_model::ID),
+                ..Account::default()
+            },
+        );
+        key
+    }
+
+    fn process(&mut self, ix: &Instruction) -> InstructionResult {
+        let mut keys: Vec<Pubkey> = Vec::new();
+        for meta in &ix.accounts {
+            if !keys.contains(&meta.pubkey) {
+                keys.push(meta.pubkey);
+            }
+        }
+        let accounts: Vec<_> = keys
+            .iter()
+            .map(|k| (svm_key(k), self.accounts.get(k).cloned().unwrap_or_default()))
+            .collect();
+
+        let result = self
+            .mollusk
+            .process_instruction(&svm_instruction(ix), &accounts);
+        if result.raw_result.is_ok() {
+            for (key, account) in keys.iter().zip(&result.resulting_accounts) {
+                self.accounts.insert(*key, account.1.clone());
+            }
+        }
+        result
+    }
+
+    fn ok(&mut self, ix: &Instruction) {
+        let result = self.process(ix);
+        assert!(
+            result.raw_result.is_ok(),
+            "instruction failed: {:?}",
+            result.raw_result
+        );
+    }
+
+    fn fails_with(&mut self, ix: &Instruction, err: FraudProofError) {
+        let result = self.process(ix);
+        assert_eq!(result.raw_result, Err(InstructionError::Custom(err.into())));
+    }
+}
+
+/// A session the operator cranks under CRANK_MODE_DESIGNATED.
+fn operated_session(operator: Pubkey) -> SessionStateAccount {
+    let mut s: SessionStateAccount = bytemuck::Zeroable::zeroed();
+    s.status = STATUS_ACTIVE;
+    s.stage = 31;
+    s.player1 = Pubkey::new_unique();
+    s.player2 = Pubkey::new_unique();
+    s.crank_mode = CRANK_MODE_DESIGNATED;
+    s.crank_authority = operator;
+    s
+}
+
+fn post_bond_ix(session: Pubkey, operator: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::PostBond {
+            bond: pda(&[BOND_SEED, session.as_ref()]),
+            session,
+            operator,
+            system_program: system_program::ID,
+        },
+        instruction::PostBond { amount },
+    )
+}
+
+/// Frames 1 and 2 of a match, and the commitment chain over them with
+/// `reported` as frame 2's post-state.
+struct Match {
+    anchor: [u8; 32],
+    pre_players: [PlayerState; NUM_PLAYERS],
+    pre_inputs: [ControllerInput; NUM_PLAYERS],
+    inputs: [ControllerInput; NUM_PLAYERS],
+    honest: [PlayerState; NUM_PLAYERS],
+}
+
+impl Match {
+    fn new() -> Self {
+        let pre_players = PlayerState::spawns([1, 2], 0, 31);
+        let pre_inputs = [ControllerInput::default(); NUM_PLAYERS];
+        let mut inputs = [ControllerInput::default(); NUM_PLAYERS];
+        inputs[0].stick_x = 127;
+        inputs[1].stick_x = -127;
+        let mut honest = pre_players;
+        step_players(&mut honest, &inputs, 31);
+        Match {
+            anchor: [7; 32],
+            pre_players,
+            pre_inputs,
+            inputs,
+            honest,
+        }
+    }
+
+    fn commitment(&self, reported: &[PlayerState; NUM_PLAYERS]) -> [u8; 32] {
+        let c1 = frame_commitment(&self.anchor, 1, &self.pre_players, &self.pre_inputs).unwrap();
+        frame_commitment(&c1, 2, reported, &self.inputs).unwrap()
+    }
+
+    fn open_ix(
+        &self,
+        session: Pubkey,
+        challenger: Pubkey,
+        claimed: [PlayerState; NUM_PLAYERS],
+    ) -> Instruction {
+        build(
+            accounts::OpenChallenge {
+                challenge: pda(&[CHALLENGE_SEED, session.as_ref(), challenger.as_ref()]),
+                bond: pda(&[BOND_SEED, session.as_ref()]),
+                session,
+                challenger,
+                system_program: system_program::ID,
+            },
+            instruction::OpenChallenge {
+                frame: 2,
+                anchor: self.anchor,
+                pre_players: self.pre_players,
+                pre_inputs: self.pre_inputs,
+                inputs: self.inputs,
+                claimed_players: claimed,
+            },
+        )
+    }
+}
+
+/// An ended two-frame session whose operator reported `reported` for frame 2.
+fn ended_session(h: &mut Harness, m: &Match, reported: &[PlayerState; NUM_PLAYERS]) -> (Pubkey, Pubkey) {
+    let operator = h.wallet();
+    let mut state = operated_session(operator);
+    let session = h.session(&state);
+    h.ok(&post_bond_ix(session, operator, BOND));
+
+    state.status = STATUS_ENDED;
+    state.frame = 2;
+    state.state_commitment = m.commitment(reported);
+    let mut data = SessionStateAccount::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&state));
+    h.accounts.get_mut(&session).unwrap().data = data;
+    (session, operator)
+}
+
+fn resolve_ix(session: Pubkey, challenger: Pubkey) -> Instruction {
+    build(
+        accounts::ResolveChallenge {
+            challenge: pda(&[CHALLENGE_SEED, session.as_ref(), challenger.as_ref()]),
+            bond: pda(&[BOND_SEED, session.as_ref()]),
+            session,
+            challenger,
+        },
+        instruction::ResolveChallenge {},
+    )
+}
+
+#[test]
+fn only_the_sessions_operator_bonds_it_before_it_ends() {
+    let mut h = Harness::new();
+    let operator = h.wallet();
+    let stranger = h.wallet();
+
+    let session = h.session(&operated_session(operator));
+    h.fails_with(&post_bond_ix(session, operator, 0), FraudProofError::InvalidAmount);
+    h.fails_with(&post_bond_ix(session, stranger, BOND), FraudProofError::NotSessionOperator);
+    h.ok(&post_bond_ix(session, operator, BOND));
+    let bond = pda(&[BOND_SEED, session.as_ref()]);
+    assert!(h.accounts[&bond].lamports > BOND);
+
+    // Without a designated cranker, nobody is the operator
+    let mut open = operated_session(operator);
+    open.crank_mode = CRANK_MODE_PERMISSIONLESS;
+    let session = h.session(&open);
+    h.fails_with(&post_bond_ix(session, operator, BOND), FraudProofError::NotSessionOperator);
+
+    let mut ended = operated_session(operator);
+    ended.status = STATUS_ENDED;
+    let session = h.session(&ended);
+    h.fails_with(&post_bond_ix(session, operator, BOND), FraudProofError::SessionEnded);
+}
+
+#[test]
+fn fraudulent_frame_slashes_the_bond() {
+    let mut h = Harness::new();
+    let m = Match::new();
+    let mut forged = m.honest;
+    forged[1].percent += 40;
+    let (session, _) = ended_session(&mut h, &m, &forged);
+
+    let challenger = h.wallet();
+    h.ok(&m.open_ix(session, challenger, forged));
+    let bond = h.accounts[&pda(&[BOND_SEED, session.as_ref()])].lamports;
+    let before = h.accounts[&challenger].lamports;
+    h.ok(&resolve_ix(session, challenger));
+    assert!(h.accounts[&challenger].lamports >= before + bond);
+}
+
+#[test]
+fn honest_frame_cannot_be_challenged() {
+    let mut h = Harness::new();
+    let m = Match::new();
+    let (session, _) = ended_session(&mut h, &m, &m.honest);
+
+    let challenger = h.wallet();
+    h.fails_with(&m.open_ix(session, challenger, m.honest), FraudProofError::NotFraudulent);
+
+    // A made-up claim re-executes differently but isn't what the operator
+    // committed, so its chain never reaches the session's commitment
+    let mut made_up = m.honest;
+    made_up[0].stocks = 0;
+    h.ok(&m.open_ix(session, challenger, made_up));
+    h.fails_with(&resolve_ix(session, challenger), FraudProofError::CommitmentMismatch);
+}
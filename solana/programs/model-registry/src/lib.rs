@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+use world_model::state::ModelManifestAccount;
+
+declare_id!("8wY2kLqq8ekf4646Lsu89a6vP8UHaFrV8ynCERTBT481");
+
+/// Seed for the singleton registry account
+pub const REGISTRY_SEED: &[u8] = b"registry";
+/// Seed prefix for entry PDAs: ["entry", index u32 LE]
+pub const ENTRY_SEED: &[u8] = b"entry";
+/// Seed prefix for listing markers: ["listing", manifest]
+pub const LISTING_SEED: &[u8] = b"listing";
+
+/// Model registry — the "cartridge library" of deployed world models.
+///
+/// Manifests live wherever their authority created them, so there's no way
+/// to enumerate them onchain. The registry gives each listed manifest a
+/// sequentially indexed entry PDA:
+///
+///   registry            ["registry"]                 → num_entries
+///   entry i             ["entry", i as u32 LE]       → name, version, manifest, ...
+///   listing marker      ["listing", manifest]        → i (prevents double-listing)
+///
+/// Clients page through the library by deriving entry PDAs for
+/// [page * page_size, (page + 1) * page_size) and fetching them with a
+/// single getMultipleAccounts call — no getProgramAccounts scan needed.
+///
+/// Entry fields are copied from the manifest (never taken from instruction
+/// args), so a listing can't misrepresent the model it points to.
+#[program]
+pub mod model_registry {
+    use super::*;
+
+    /// Create the singleton registry account.
+    pub fn init_registry(ctx: Context<InitRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.num_entries = 0;
+        registry.bump = ctx.bumps.registry;
+
+        msg!("Model registry initialized");
+        Ok(())
+    }
+
+    /// List a manifest in the registry. Only the manifest authority can list.
+    pub fn list_model(ctx: Context<ListModel>) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            RegistryError::Unauthorized
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        let index = registry.num_entries;
+
+        let entry = &mut ctx.accounts.entry;
+        entry.index = index;
+        entry.manifest = manifest.key();
+        entry.listed_by = ctx.accounts.authority.key();
+        entry.listed_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.entry;
+        entry.sync_from(manifest);
+
+        let listing = &mut ctx.accounts.listing;
+        listing.index = index;
+        listing.bump = ctx.bumps.listing;
+
+        registry.num_entries = index
+            .checked_add(1)
+            .ok_or(RegistryError::RegistryFull)?;
+
+        msg!("Model listed: index={}, manifest={}", index, manifest.key());
+        Ok(())
+    }
+
+    /// Re-sync an entry from its manifest (e.g. after the model becomes ready).
+    /// Permissionless — the data comes from the manifest account itself.
+    pub fn refresh_listing(ctx: Context<RefreshListing>) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        let entry = &mut ctx.accounts.entry;
+        entry.sync_from(manifest);
+
+        msg!("Listing {} refreshed: ready={}", entry.index, entry.ready);
+        Ok(())
+    }
+}
+
+// ── Account structures ──────────────────────────────────────────────────────
+
+#[account]
+#[derive(Default)]
+pub struct RegistryAccount {
+    /// Number of entries listed (next entry index)
+    pub num_entries: u32,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(Default)]
+pub struct RegistryEntry {
+    /// Position in the registry (entry PDA seed)
+    pub index: u32,
+    /// ModelManifestAccount this entry points to
+    pub manifest: Pubkey,
+    /// Manifest authority at listing time
+    pub listed_by: Pubkey,
+    /// Human-readable model name (copied from the manifest)
+    pub name: [u8; 32],
+    pub version: u16,
+    pub total_params: u32,
+    /// Whether the model is ready for inference (as of last sync)
+    pub ready: bool,
+    /// Listing timestamp (Unix seconds)
+    pub listed_at: i64,
+    pub bump: u8,
+}
+
+impl RegistryEntry {
+    /// Copy the display fields from the manifest.
+    fn sync_from(&mut self, manifest: &ModelManifestAccount) {
+        self.name = manifest.name;
+        self.version = manifest.version;
+        self.total_params = manifest.total_params;
+        self.ready = manifest.ready;
+    }
+}
+
+/// Marker PDA proving a manifest is already listed, and where.
+#[account]
+#[derive(Default)]
+pub struct ManifestListing {
+    pub index: u32,
+    pub bump: u8,
+}
+
+// ── Account contexts ────────────────────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<RegistryAccount>(),
+        seeds = [REGISTRY_SEED],
+        bump,
+    )]
+    pub registry: Account<'info, RegistryAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ListModel<'info> {
+    #[account(mut, seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Account<'info, RegistryAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RegistryEntry>(),
+        seeds = [ENTRY_SEED, &registry.num_entries.to_le_bytes()],
+        bump,
+    )]
+    pub entry: Account<'info, RegistryEntry>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ManifestListing>(),
+        seeds = [LISTING_SEED, manifest.key().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, ManifestListing>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshListing<'info> {
+    #[account(
+        mut,
+        seeds = [ENTRY_SEED, &entry.index.to_le_bytes()],
+        bump = entry.bump,
+        has_one = manifest,
+    )]
+    pub entry: Account<'info, RegistryEntry>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+}
+
+// ── Errors ──────────────────────────────────────────────────────────────────
+
+#[error_code]
+pub enum RegistryError {
+    #[msg("Only the manifest authority can list this model")]
+    Unauthorized,
+    #[msg("Registry has reached its maximum number of entries")]
+    RegistryFull,
+}
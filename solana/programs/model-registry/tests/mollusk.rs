@@ -0,0 +1,258 @@
+//! Mollusk tests for the model registry: listing manifests, paging entries
+//! by index, and refreshing a listing from its manifest. Manifests are the
+//! world-model fixture's, written directly, so world_model.so isn't needed.
+//!
+//! Prerequisites: `cargo build-sbf --manifest-path programs/model-registry/Cargo.toml`
+//! (the .so must exist at programs/model-registry/target/deploy/model_registry.so)
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{
+    system_program, AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas,
+};
+use model_registry::{
+    accounts, instruction, ManifestListing, RegistryAccount, RegistryEntry, RegistryError,
+    ENTRY_SEED, LISTING_SEED, REGISTRY_SEED,
+};
+use mollusk_svm::result::InstructionResult;
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use solana_instruction::error::InstructionError;
+use world_model::fixture::SyntheticModel;
+use world_model::state::ModelManifestAccount;
+
+const LAMPORTS: u64 = 10_000_000_000;
+
+fn svm_key(key: &Pubkey) -> solana_pubkey::Pubkey {
+    solana_pubkey::Pubkey::new_from_array(key.to_bytes())
+}
+
+/// The program uses Anchor's Solana types; Mollusk uses its own.
+fn svm_instruction(ix: &Instruction) -> solana_instruction::Instruction {
+    solana_instruction::Instruction {
+        program_id: svm_key(&ix.program_id),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|m| solana_instruction::AccountMeta {
+                pubkey: svm_key(&m.pubkey),
+                is_signer: m.is_signer,
+                is_writable: m.is_writable,
+            })
+            .collect(),
+        data: ix.data.clone(),
+    }
+}
+
+fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: model_registry::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &model_registry::ID).0
+}
+
+fn entry_pda(index: u32) -> Pubkey {
+    pda(&[ENTRY_SEED, &index.to_le_bytes()])
+}
+
+/// Mollusk plus an account store that successful instructions write back to.
+struct Harness {
+    mollusk: Mollusk,
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        // model-registry is excluded from the workspace, so its .so lives in its own target dir.
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        std::env::set_var(
+            "SBF_OUT_DIR",
+            std::path::Path::new(manifest_dir).join("target/deploy"),
+        );
+
+        let mollusk = Mollusk::new(&svm_key(&model_registry::ID), "model_registry");
+        let mut accounts = HashMap::new();
+        let (_, system) = mollusk_svm::program::keyed_account_for_system_program();
+        accounts.insert(system_program::ID, system);
+        Harness { mollusk, accounts }
+    }
+
+    fn wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.accounts.insert(
+            key,
+            Account {
+                lamports: LAMPORTS,
+                owner: svm_key(&system_program::ID),
+                ..Account::default()
+            },
+        );
+        key
+    }
+
+    /// Write `manifest` as a world-model manifest account at `key`.
+    fn put_manifest(&mut self, key: Pubkey, manifest: &ModelManifestAccount) {
+        let mut data = Vec::new();
+        manifest.try_serialize(&mut data).unwrap();
+        data.resize(8 + core::mem::size_of::<ModelManifestAccount>(), 0);
+        self.accounts.insert(
+            key,
+            Account {
+                lamports: self.mollusk.sysvars.rent.minimum_balance(data.len()),
+                data,
+                owner: svm_key(&world_model::ID),
+                ..Account::default()
+            },
+        );
+    }
+
+    fn process(&mut self, ix: &Instruction) -> InstructionResult {
+        let mut keys: Vec<Pubkey> = Vec::new();
+        for meta in &ix.accounts {
+            if !keys.contains(&meta.pubkey) {
+                keys.push(meta.pubkey);
+            }
+        }
+        let accounts: Vec<_> = keys
+            .iter()
+            .map(|k| (svm_key(k), self.accounts.get(k).cloned().unwrap_or_default()))
+            .collect();
+
+        let result = self
+            .mollusk
+            .process_instruction(&svm_instruction(ix), &accounts);
+        if result.raw_result.is_ok() {
+            for (key, account) in keys.iter().zip(&result.resulting_accounts) {
+                self.accounts.insert(*key, account.1.clone());
+            }
+        }
+        result
+    }
+
+    fn ok(&mut self, ix: &Instruction) {
+        let result = self.process(ix);
+        assert!(
+            result.raw_result.is_ok(),
+            "instruction failed: {:?}",
+            result.raw_result
+        );
+    }
+
+    fn fails_with(&mut self, ix: &Instruction, err: RegistryError) {
+        let result = self.process(ix);
+        assert_eq!(result.raw_result, Err(InstructionError::Custom(err.into())));
+    }
+
+    fn anchor<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        T::try_deserialize(&mut &self.accounts[key].data[..]).unwrap()
+    }
+}
+
+fn init_registry(h: &mut Harness) {
+    let payer = h.wallet();
+    h.ok(&build(
+        accounts::InitRegistry {
+            registry: pda(&[REGISTRY_SEED]),
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitRegistry {},
+    ));
+}
+
+/// A manifest owned by `authority`, named `name`.
+fn manifest(h: &mut Harness, authority: Pubkey, name: &[u8], ready: bool) -> Pubkey {
+    let mut m = SyntheticModel::tiny().manifest();
+    m.authority = authority;
+    m.name = [0; 32];
+    m.name[..name.len()].copy_from_slice(name);
+    m.ready = ready;
+    let key = Pubkey::new_unique();
+    h.put_manifest(key, &m);
+    key
+}
+
+/// list_model for the registry's next entry index.
+fn list_ix(h: &Harness, manifest: Pubkey, authority: Pubkey) -> Instruction {
+    let registry: RegistryAccount = h.anchor(&pda(&[REGISTRY_SEED]));
+    build(
+        accounts::ListModel {
+            registry: pda(&[REGISTRY_SEED]),
+            entry: entry_pda(registry.num_entries),
+            listing: pda(&[LISTING_SEED, manifest.as_ref()]),
+            manifest,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::ListModel {},
+    )
+}
+
+fn refresh_ix(index: u32, manifest: Pubkey) -> Instruction {
+    build(
+        accounts::RefreshListing {
+            entry: entry_pda(index),
+            manifest,
+        },
+        instruction::RefreshListing {},
+    )
+}
+
+#[test]
+fn listing_indexes_manifests_in_order() {
+    let mut h = Harness::new();
+    init_registry(&mut h);
+    let authority = h.wallet();
+    let first = manifest(&mut h, authority, b"melee-a", true);
+    let second = manifest(&mut h, authority, b"melee-b", false);
+
+    let stranger = h.wallet();
+    h.fails_with(&list_ix(&h, first, stranger), RegistryError::Unauthorized);
+    h.ok(&list_ix(&h, first, authority));
+    h.ok(&list_ix(&h, second, authority));
+
+    let registry: RegistryAccount = h.anchor(&pda(&[REGISTRY_SEED]));
+    assert_eq!(registry.num_entries, 2);
+    for (index, key, name) in [(0u32, first, &b"melee-a"[..]), (1, second, &b"melee-b"[..])] {
+        let entry: RegistryEntry = h.anchor(&entry_pda(index));
+        assert_eq!((entry.index, entry.manifest, entry.listed_by), (index, key, authority));
+        assert_eq!(&entry.name[..name.len()], name);
+        let listing: ManifestListing = h.anchor(&pda(&[LISTING_SEED, key.as_ref()]));
+        assert_eq!(listing.index, index);
+    }
+    assert!(h.anchor::<RegistryEntry>(&entry_pda(0)).ready);
+    assert!(!h.anchor::<RegistryEntry>(&entry_pda(1)).ready);
+
+    // The listing marker already exists, so a manifest lists once
+    assert!(h.process(&list_ix(&h, first, authority)).raw_result.is_err());
+    assert_eq!(h.anchor::<RegistryAccount>(&pda(&[REGISTRY_SEED])).num_entries, 2);
+}
+
+#[test]
+fn refresh_copies_the_manifest_it_lists() {
+    let mut h = Harness::new();
+    init_registry(&mut h);
+    let authority = h.wallet();
+    let listed = manifest(&mut h, authority, b"melee", false);
+    h.ok(&list_ix(&h, listed, authority));
+
+    // Permissionless: the entry only ever copies its own manifest
+    let mut m: ModelManifestAccount = h.anchor(&listed);
+    m.ready = true;
+    m.version = 2;
+    h.put_manifest(listed, &m);
+    h.ok(&refresh_ix(0, listed));
+    let entry: RegistryEntry = h.anchor(&entry_pda(0));
+    assert!(entry.ready);
+    assert_eq!(entry.version, 2);
+
+    let other = manifest(&mut h, authority, b"other", false);
+    assert!(h.process(&refresh_ix(0, other)).raw_result.is_err());
+    assert!(h.anchor::<RegistryEntry>(&entry_pda(0)).ready);
+}
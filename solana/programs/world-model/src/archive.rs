@@ -0,0 +1,262 @@
+//! Compressed-NFT archival for replays.
+//!
+//! mint_replay_nft keeps a rent-exempt record and the replay checkpoint
+//! alive forever. The archive path trades them for a Bubblegum leaf: the
+//! replay is summarized (players, final frame, final-state commitment, a
+//! hash of the checkpoint data), the summary hash goes into the leaf's
+//! metadata, and the checkpoint is closed. The leaf's data hash commits to
+//! the metadata, so the tree root commits to the summary.
+//!
+//! Archive trees are created through create_archive_tree with this
+//! program's ARCHIVE_AUTHORITY_SEED PDA as tree creator; only that PDA can
+//! mint into them, so a leaf in such a tree is a replay this program
+//! checked.
+
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const COMPRESSION_PROGRAM_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// Leaf name and symbol of archived replays
+pub const ARCHIVE_NAME: &str = "AWM Replay";
+pub const ARCHIVE_SYMBOL: &str = "AWMR";
+/// Leaf URI prefix; the session and summary hash (hex) follow
+pub const ARCHIVE_URI_PREFIX: &str = "awm://replay/";
+
+/// What an archived replay commits to. Its SHA-256 (over the Borsh bytes)
+/// is the summary hash in the leaf's URI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct ReplaySummary {
+    pub session: Pubkey,
+    pub model: Pubkey,
+    pub player1: Pubkey,
+    pub player2: Pubkey,
+    pub final_frame: u32,
+    /// SessionStateAccount::final_state_hash at the end of the match
+    pub final_state_hash: [u8; 32],
+    /// SHA-256 of the replay checkpoint's data before it was closed
+    pub replay_hash: [u8; 32],
+}
+
+impl ReplaySummary {
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        let mut buf = Vec::new();
+        self.serialize(&mut buf)?;
+        Ok(hash(&buf).to_bytes())
+    }
+}
+
+// ── Bubblegum wire types (mpl-bubblegum MetadataArgs, Borsh) ─────────────────
+
+#[derive(AnchorSerialize)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(AnchorSerialize)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize)]
+pub struct Uses {
+    pub use_method: u8,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+pub const TOKEN_STANDARD_NON_FUNGIBLE: u8 = 0;
+pub const TOKEN_PROGRAM_VERSION_ORIGINAL: u8 = 0;
+
+#[derive(AnchorSerialize)]
+pub struct MetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    pub collection: Option<Collection>,
+    pub uses: Option<Uses>,
+    pub token_program_version: u8,
+    pub creators: Vec<Creator>,
+}
+
+/// The immutable leaf metadata of an archived replay.
+pub fn leaf_metadata(summary: &ReplaySummary) -> Result<MetadataArgs> {
+    let digest: String = summary
+        .hash()?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(MetadataArgs {
+        name: ARCHIVE_NAME.to_string(),
+        symbol: ARCHIVE_SYMBOL.to_string(),
+        uri: format!("{}{}/{}", ARCHIVE_URI_PREFIX, summary.session, digest),
+        seller_fee_basis_points: 0,
+        primary_sale_happened: false,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(TOKEN_STANDARD_NON_FUNGIBLE),
+        collection: None,
+        uses: None,
+        token_program_version: TOKEN_PROGRAM_VERSION_ORIGINAL,
+        creators: Vec::new(),
+    })
+}
+
+/// Bubblegum's tree config PDA for `merkle_tree`.
+pub fn tree_config(merkle_tree: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+fn discriminator(name: &str) -> Vec<u8> {
+    hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].to_vec()
+}
+
+/// Bubblegum accounts shared by create_tree and mint_v1.
+pub struct TreeAccounts<'a, 'info> {
+    pub tree_config: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    /// The ARCHIVE_AUTHORITY_SEED PDA
+    pub authority: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub bubblegum_program: &'a AccountInfo<'info>,
+}
+
+/// Create a private Bubblegum tree in the pre-allocated `merkle_tree`, with
+/// the archive authority as its creator.
+pub fn create_tree(
+    accounts: TreeAccounts,
+    max_depth: u32,
+    max_buffer_size: u32,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let mut data = discriminator("create_tree");
+    (max_depth, max_buffer_size, Some(false)).serialize(&mut data)?;
+    let ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(accounts.tree_config.key(), false),
+            AccountMeta::new(accounts.merkle_tree.key(), false),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new_readonly(accounts.log_wrapper.key(), false),
+            AccountMeta::new_readonly(accounts.compression_program.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.tree_config.clone(),
+            accounts.merkle_tree.clone(),
+            accounts.payer.clone(),
+            accounts.authority.clone(),
+            accounts.log_wrapper.clone(),
+            accounts.compression_program.clone(),
+            accounts.system_program.clone(),
+            accounts.bubblegum_program.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+    Ok(())
+}
+
+/// Mint a leaf with `metadata` to `leaf_owner` (also its delegate).
+pub fn mint_leaf<'info>(
+    accounts: TreeAccounts<'_, 'info>,
+    leaf_owner: &AccountInfo<'info>,
+    metadata: &MetadataArgs,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let mut data = discriminator("mint_v1");
+    metadata.serialize(&mut data)?;
+    let ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(accounts.tree_config.key(), false),
+            AccountMeta::new_readonly(leaf_owner.key(), false),
+            AccountMeta::new_readonly(leaf_owner.key(), false),
+            AccountMeta::new(accounts.merkle_tree.key(), false),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new_readonly(accounts.log_wrapper.key(), false),
+            AccountMeta::new_readonly(accounts.compression_program.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.tree_config.clone(),
+            leaf_owner.clone(),
+            accounts.merkle_tree.clone(),
+            accounts.payer.clone(),
+            accounts.authority.clone(),
+            accounts.log_wrapper.clone(),
+            accounts.compression_program.clone(),
+            accounts.system_program.clone(),
+            accounts.bubblegum_program.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> ReplaySummary {
+        ReplaySummary {
+            session: Pubkey::new_unique(),
+            model: Pubkey::new_unique(),
+            player1: Pubkey::new_unique(),
+            player2: Pubkey::new_unique(),
+            final_frame: 3600,
+            final_state_hash: [1; 32],
+            replay_hash: [2; 32],
+        }
+    }
+
+    #[test]
+    fn test_leaf_uri_commits_to_summary() {
+        let s = summary();
+        let uri = leaf_metadata(&s).unwrap().uri;
+        assert!(uri.starts_with(&format!("{}{}/", ARCHIVE_URI_PREFIX, s.session)));
+        assert!(uri.len() <= 200, "Bubblegum caps URIs at 200 bytes");
+
+        let mut changed = s;
+        changed.final_state_hash[0] ^= 1;
+        assert_ne!(leaf_metadata(&changed).unwrap().uri, uri);
+    }
+
+    #[test]
+    fn test_metadata_borsh_layout() {
+        let m = leaf_metadata(&summary()).unwrap();
+        let mut bytes = Vec::new();
+        m.serialize(&mut bytes).unwrap();
+        // name, symbol, uri (u32 length-prefixed), then fee..creators
+        let fixed = 2 + 1 + 1 + 1 + 2 + 1 + 1 + 1 + 4;
+        assert_eq!(
+            bytes.len(),
+            12 + m.name.len() + m.symbol.len() + m.uri.len() + fixed
+        );
+        assert_eq!(&bytes[..4], &(ARCHIVE_NAME.len() as u32).to_le_bytes());
+    }
+}
@@ -0,0 +1,139 @@
+//! Scheduler registration for cranking a session on the ephemeral rollup.
+//!
+//! The rollup's magic program runs scheduled tasks: a list of instructions
+//! it replays every `execution_interval_millis`, signed and paid for by the
+//! account that scheduled them. A session's task is a frame_advance over
+//! its own bundle, so a delegated world keeps ticking at 60fps without a
+//! bespoke off-chain cranker. frame_advance skips (rather than fails) a
+//! tick whose inputs aren't in yet, so an early tick costs nothing but the
+//! fee.
+//!
+//! Each session has one task, whose id is derived from the session key, so
+//! cancelling it needs nothing stored on-chain.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::InstructionData;
+use magicblock_magic_program_api::args::ScheduleTaskArgs;
+use magicblock_magic_program_api::instruction::MagicBlockInstruction;
+
+use crate::delegation::MAGIC_PROGRAM_ID;
+use crate::state::SESSION_BUNDLE_LEN;
+
+/// Scheduler task id of a session's crank: the session key's first eight
+/// bytes, kept non-negative.
+pub fn crank_task_id(session: &Pubkey) -> i64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&session.as_ref()[..8]);
+    i64::from_le_bytes(bytes) & i64::MAX
+}
+
+/// The frame_advance a session's task replays: `bundle` (session, hidden
+/// state, input buffer, meter), after `shards` (directory, then shards —
+/// empty without mamba2-inference), with `cranker` as the signer.
+pub fn frame_advance_payload(
+    manifest: Pubkey,
+    weights: Pubkey,
+    cranker: Pubkey,
+    shards: &[Pubkey],
+    bundle: [Pubkey; SESSION_BUNDLE_LEN],
+) -> Instruction {
+    let mut accounts = crate::accounts::FrameAdvance {
+        manifest,
+        weights,
+        cranker,
+    }
+    .to_account_metas(None);
+    accounts.extend(shards.iter().map(|k| AccountMeta::new_readonly(*k, false)));
+    accounts.extend(bundle.iter().map(|k| AccountMeta::new(*k, false)));
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::FrameAdvance {}.data(),
+    }
+}
+
+/// Schedule `payload` every `interval_ms`, `iterations` times, paid for by
+/// `payer`. `accounts` are every account the payload names besides `payer`.
+pub fn schedule<'info>(
+    payer: &AccountInfo<'info>,
+    magic_program: &AccountInfo<'info>,
+    task_id: i64,
+    interval_ms: u32,
+    iterations: u32,
+    payload: Instruction,
+    accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mut metas = vec![AccountMeta::new(payer.key(), true)];
+    metas.extend(accounts.iter().map(|a| AccountMeta::new_readonly(a.key(), false)));
+    let ix = Instruction::new_with_bincode(
+        MAGIC_PROGRAM_ID,
+        &MagicBlockInstruction::ScheduleTask(ScheduleTaskArgs {
+            task_id: task_id as u64,
+            execution_interval_millis: interval_ms as u64,
+            iterations: iterations as u64,
+            instructions: vec![payload],
+        }),
+        metas,
+    );
+
+    let mut infos = Vec::with_capacity(accounts.len() + 2);
+    infos.push(payer.clone());
+    infos.extend_from_slice(accounts);
+    infos.push(magic_program.clone());
+    invoke(&ix, &infos)?;
+    Ok(())
+}
+
+/// Cancel `payer`'s task `task_id`.
+pub fn cancel<'info>(
+    payer: &AccountInfo<'info>,
+    magic_program: &AccountInfo<'info>,
+    task_id: i64,
+) -> Result<()> {
+    let ix = Instruction::new_with_bincode(
+        MAGIC_PROGRAM_ID,
+        &MagicBlockInstruction::CancelTask {
+            task_id: task_id as u64,
+        },
+        vec![AccountMeta::new(payer.key(), true)],
+    );
+    invoke(&ix, &[payer.clone(), magic_program.clone()])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn test_crank_task_id_is_stable_and_non_negative() {
+        let session = Pubkey::new_from_array([0xff; 32]);
+        assert_eq!(crank_task_id(&session), i64::MAX);
+        assert_eq!(crank_task_id(&session), crank_task_id(&session));
+        assert_ne!(
+            crank_task_id(&Pubkey::new_unique()),
+            crank_task_id(&Pubkey::new_unique())
+        );
+    }
+
+    #[test]
+    fn test_frame_advance_payload_layout() {
+        let keys: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+        let ix = frame_advance_payload(
+            keys[0],
+            keys[1],
+            keys[2],
+            &[keys[3]],
+            [keys[4], keys[5], keys[6], keys[7]],
+        );
+        assert_eq!(ix.program_id, crate::ID);
+        assert_eq!(&ix.data[..8], crate::instruction::FrameAdvance::DISCRIMINATOR);
+        let order: Vec<Pubkey> = ix.accounts.iter().map(|m| m.pubkey).collect();
+        assert_eq!(order, keys);
+        assert!(ix.accounts[2].is_signer);
+        assert!(ix.accounts[4..].iter().all(|m| m.is_writable));
+    }
+}
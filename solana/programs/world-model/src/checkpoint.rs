@@ -0,0 +1,132 @@
+//! Hidden state checkpoint compression.
+//!
+//! A finished session's hidden state (up to ~200KB) is expensive to commit
+//! raw. Checkpoints store it with zero-run-length encoding, which is lossless
+//! and cheap on BPF (one pass, no allocation, no tables).
+//!
+//! Encoding (byte stream):
+//!   - non-zero byte b      → b
+//!   - run of n zero bytes  → 0x00, n   (1 <= n <= 255; longer runs split)
+//!
+//! Freshly reset or lightly used states compress to almost nothing; a fully
+//! dense state costs at most 2× for isolated zeros (see `rle_bound`).
+
+/// Worst-case compressed size for `raw_len` input bytes.
+pub fn rle_bound(raw_len: usize) -> usize {
+    raw_len * 2
+}
+
+/// Compress `input` into `output`. Returns the compressed length, or None
+/// if `output` is too small.
+pub fn rle_compress(input: &[u8], output: &mut [u8]) -> Option<usize> {
+    let mut out = 0;
+    let mut i = 0;
+
+    while i < input.len() {
+        if input[i] != 0 {
+            *output.get_mut(out)? = input[i];
+            out += 1;
+            i += 1;
+            continue;
+        }
+
+        let mut run = 1;
+        while run < 255 && i + run < input.len() && input[i + run] == 0 {
+            run += 1;
+        }
+        *output.get_mut(out)? = 0;
+        *output.get_mut(out + 1)? = run as u8;
+        out += 2;
+        i += run;
+    }
+
+    Some(out)
+}
+
+/// Decompress `input` into `output`. Returns the decompressed length, or None
+/// if the stream is malformed or `output` is too small.
+pub fn rle_decompress(input: &[u8], output: &mut [u8]) -> Option<usize> {
+    let mut out = 0;
+    let mut i = 0;
+
+    while i < input.len() {
+        if input[i] != 0 {
+            *output.get_mut(out)? = input[i];
+            out += 1;
+            i += 1;
+            continue;
+        }
+
+        let run = *input.get(i + 1)? as usize;
+        if run == 0 {
+            return None;
+        }
+        output.get_mut(out..out + run)?.fill(0);
+        out += run;
+        i += 2;
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(raw: &[u8]) -> usize {
+        let mut compressed = vec![0u8; rle_bound(raw.len())];
+        let n = rle_compress(raw, &mut compressed).unwrap();
+
+        let mut restored = vec![0xAAu8; raw.len()];
+        let m = rle_decompress(&compressed[..n], &mut restored).unwrap();
+
+        assert_eq!(m, raw.len());
+        assert_eq!(restored, raw);
+        n
+    }
+
+    #[test]
+    fn test_rle_all_zero() {
+        // 1000 zeros → 4 runs of ≤255 → 8 bytes
+        let n = roundtrip(&[0u8; 1000]);
+        assert_eq!(n, 8);
+    }
+
+    #[test]
+    fn test_rle_dense() {
+        let raw: Vec<u8> = (0..512).map(|i| (i % 255 + 1) as u8).collect();
+        let n = roundtrip(&raw);
+        assert_eq!(n, raw.len());
+    }
+
+    #[test]
+    fn test_rle_mixed() {
+        let raw: Vec<u8> = (0..2048)
+            .map(|i| if i % 7 == 0 || (300..900).contains(&i) { 0 } else { i as u8 | 1 })
+            .collect();
+        roundtrip(&raw);
+    }
+
+    #[test]
+    fn test_rle_worst_case_fits_bound() {
+        let raw: Vec<u8> = (0..256).map(|i| if i % 2 == 0 { 0 } else { 5 }).collect();
+        let n = roundtrip(&raw);
+        assert!(n <= rle_bound(raw.len()));
+    }
+
+    #[test]
+    fn test_rle_output_too_small() {
+        let mut out = [0u8; 3];
+        assert_eq!(rle_compress(&[1, 2, 3, 4], &mut out), None);
+        assert_eq!(rle_decompress(&[0, 10], &mut out), None);
+    }
+
+    #[test]
+    fn test_rle_malformed() {
+        let mut out = [0u8; 16];
+        // Truncated run
+        assert_eq!(rle_decompress(&[1, 0], &mut out), None);
+        // Zero-length run
+        assert_eq!(rle_decompress(&[0, 0], &mut out), None);
+    }
+}
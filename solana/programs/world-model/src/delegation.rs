@@ -0,0 +1,152 @@
+//! Ephemeral rollup handoff for session accounts.
+//!
+//! Wraps the MagicBlock delegation program CPIs so a session's trip to the
+//! rollup happens on-chain instead of in client scripts: its bundle
+//! accounts (state, hidden state, input buffer, meter) are delegated to a
+//! rollup validator, committed back while the match runs, and committed
+//! and undelegated once it ends.
+//!
+//! The meter is a PDA and is delegated with its seeds. The other three
+//! are keypair accounts, so they sign their own delegation.
+
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::cpi::{delegate_account, DelegateAccounts, DelegateConfig};
+use ephemeral_rollups_sdk::ephem::{commit_accounts, commit_and_undelegate_accounts};
+
+use crate::state::{DelegationConfigAccount, SESSION_BUNDLE_LEN};
+
+pub use ephemeral_rollups_sdk::consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID};
+use ephemeral_rollups_sdk::pda::{
+    DELEGATE_BUFFER_TAG, DELEGATION_METADATA_TAG, DELEGATION_RECORD_TAG,
+};
+
+/// The MagicBlock delegation program (the SDK's consts carry it as an
+/// `Address`)
+pub const DELEGATION_PROGRAM_ID: Pubkey = ephemeral_rollups_sdk::id();
+
+// The delegation program's PDAs, as the SDK's `pda` helpers derive them
+// (those take the SDK's own Pubkey type)
+
+pub fn delegation_record_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[DELEGATION_RECORD_TAG, delegated_account.as_ref()],
+        &DELEGATION_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn delegation_metadata_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[DELEGATION_METADATA_TAG, delegated_account.as_ref()],
+        &DELEGATION_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn delegate_buffer_pda_from_delegated_account_and_owner_program(
+    delegated_account: &Pubkey,
+    owner_program: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[DELEGATE_BUFFER_TAG, delegated_account.as_ref()],
+        owner_program,
+    )
+    .0
+}
+
+/// Delegation program accounts needed to delegate one account.
+pub struct DelegationAccounts<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    pub owner_program: &'a AccountInfo<'info>,
+    pub buffer: &'a AccountInfo<'info>,
+    pub delegation_record: &'a AccountInfo<'info>,
+    pub delegation_metadata: &'a AccountInfo<'info>,
+    pub delegation_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+/// Delegate `account` to the config's validator. `seeds` are its PDA seeds
+/// without the bump; empty for a keypair account signing for itself.
+pub fn delegate<'a, 'info>(
+    account: &'a AccountInfo<'info>,
+    seeds: &[&[u8]],
+    accounts: DelegationAccounts<'a, 'info>,
+    config: &DelegationConfigAccount,
+) -> Result<()> {
+    let mut delegate_config = DelegateConfig {
+        validator: (config.validator != Pubkey::default()).then_some(config.validator),
+        ..Default::default()
+    };
+    if config.commit_frequency_ms != 0 {
+        delegate_config.commit_frequency_ms = config.commit_frequency_ms;
+    }
+
+    delegate_account(
+        DelegateAccounts {
+            payer: accounts.payer,
+            pda: account,
+            owner_program: accounts.owner_program,
+            buffer: accounts.buffer,
+            delegation_record: accounts.delegation_record,
+            delegation_metadata: accounts.delegation_metadata,
+            delegation_program: accounts.delegation_program,
+            system_program: accounts.system_program,
+        },
+        seeds,
+        delegate_config,
+    )?;
+    Ok(())
+}
+
+/// The bundle accounts whose ER_ACCOUNT_* bit is set in `mask`, in bundle
+/// order.
+pub fn masked<T>(bundle: [T; SESSION_BUNDLE_LEN], mask: u8) -> Vec<T> {
+    bundle
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| mask & (1 << i) != 0)
+        .map(|(_, a)| a)
+        .collect()
+}
+
+/// Schedule a commit of `accounts` to the base layer; they stay delegated.
+pub fn commit<'a, 'info>(
+    payer: &'a AccountInfo<'info>,
+    accounts: Vec<&'a AccountInfo<'info>>,
+    magic_context: &'a AccountInfo<'info>,
+    magic_program: &'a AccountInfo<'info>,
+) -> Result<()> {
+    commit_accounts(payer, accounts, magic_context, magic_program)?;
+    Ok(())
+}
+
+/// Schedule a final commit of `accounts`, handing them back to this
+/// program on the base layer.
+pub fn commit_and_undelegate<'a, 'info>(
+    payer: &'a AccountInfo<'info>,
+    accounts: Vec<&'a AccountInfo<'info>>,
+    magic_context: &'a AccountInfo<'info>,
+    magic_program: &'a AccountInfo<'info>,
+) -> Result<()> {
+    commit_and_undelegate_accounts(payer, accounts, magic_context, magic_program)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        COMMIT_ALL, DEFAULT_COMMIT_MASK, ER_ACCOUNT_HIDDEN_STATE, ER_ACCOUNT_METER,
+    };
+
+    #[test]
+    fn test_masked_keeps_bundle_order() {
+        let bundle = ["session", "hidden", "input", "meter"];
+        assert_eq!(masked(bundle, COMMIT_ALL), bundle.to_vec());
+        assert_eq!(masked(bundle, DEFAULT_COMMIT_MASK), ["session", "input", "meter"]);
+        assert_eq!(
+            masked(bundle, (1 << ER_ACCOUNT_HIDDEN_STATE) | (1 << ER_ACCOUNT_METER)),
+            ["hidden", "meter"]
+        );
+    }
+}
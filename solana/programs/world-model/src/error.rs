@@ -7,6 +7,12 @@ pub enum WorldModelError {
     InvalidStateTransition,
     #[msg("Cannot join your own session")]
     CannotJoinOwnSession,
+    #[msg("Tick rate must be 30, 60 or 120 fps")]
+    InvalidTickRate,
+    #[msg("Operation not allowed while the session is active")]
+    SessionActive,
+    #[msg("Unknown crank mode, or a designated mode without a cranker")]
+    InvalidCrankMode,
 
     // ── Input errors ─────────────────────────────────────────────────────
     #[msg("Session is not active")]
@@ -15,10 +21,22 @@ pub enum WorldModelError {
     UnauthorizedPlayer,
     #[msg("Both players must submit inputs before inference")]
     InputsNotReady,
+    #[msg("frame_advance needs whole session bundles (session, hidden state, input buffer, meter)")]
+    SessionBundleMismatch,
+    #[msg("Session has not been idle long enough to reap")]
+    SessionNotIdle,
+    #[msg("Frame is not the session's next frame (already advanced or out of order)")]
+    FrameMismatch,
+    #[msg("Signer may not crank this session under its crank mode")]
+    CrankerNotPermitted,
 
     // ── Weight upload errors ─────────────────────────────────────────────
     #[msg("Unauthorized — signer does not match authority")]
     Unauthorized,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Session fee is below the treasury's rent-exempt minimum")]
+    FeeBelowRentExempt,
     #[msg("Weight account is already finalized")]
     AlreadyFinalized,
     #[msg("Chunk would write past end of data region")]
@@ -29,12 +47,211 @@ pub enum WorldModelError {
     IncompleteUpload,
     #[msg("SHA-256 hash does not match expected")]
     HashMismatch,
+    #[msg("Shard's named-tensor directory is malformed")]
+    InvalidTensorDirectory,
+    #[msg("Weight shard is not finalized")]
+    ShardNotFinalized,
+    #[msg("Shard index out of range, or every shard is already registered")]
+    InvalidShardIndex,
+    #[msg("Registered shard sizes overrun or fall short of total_weight_bytes")]
+    ShardSizeMismatch,
+    #[msg("Tensor table entry is invalid or out of range")]
+    InvalidTensorRef,
+    #[msg("Shard accounts do not match the manifest's shard directory")]
+    ShardAccountsMismatch,
+    #[msg("Weights account is not a finalized shard of the session's model")]
+    WeightsMismatch,
+    #[msg("sol_matmul_i8 syscall unavailable (build with er-syscall on an ER validator)")]
+    SyscallUnavailable,
+    #[msg("Syscall matmul output differs from the BPF kernel")]
+    KernelMismatch,
+    #[msg("Encoder spec has an invalid field descriptor or count")]
+    InvalidEncoderSpec,
+    #[msg("SiLU LUT is not ~0 at the origin")]
+    InvalidSiluLut,
+    #[msg("Softplus LUT is not monotonically increasing")]
+    InvalidSoftplusLut,
+    #[msg("rsqrt LUT is not monotonically decreasing")]
+    InvalidRsqrtLut,
+    #[msg("exp_neg LUT is not monotonically decreasing")]
+    InvalidExpNegLut,
+    #[msg("GELU LUT is not ~0 at the origin")]
+    InvalidGeluLut,
+    #[msg("tanh LUT is not monotonically increasing")]
+    InvalidTanhLut,
+    #[msg("LUT block size matches no LUT version")]
+    UnknownLutVersion,
+    #[msg("Expert count must be between 2 and MAX_EXPERTS")]
+    InvalidExpertCount,
+    #[msg("Expert table account does not match the manifest")]
+    ExpertTableMismatch,
+    #[msg("Attention layer mask names layers past num_layers")]
+    InvalidAttentionLayers,
+    #[msg("Attention window is zero or its KV buffer exceeds a hidden-state slot")]
+    InvalidAttentionWindow,
+    #[msg("Unknown requantization mode")]
+    InvalidRequantMode,
+    #[msg("Head groups don't split the heads or their B/C rows don't fit")]
+    InvalidHeadGroups,
+    #[msg("Character heads don't fit their player block or the model output")]
+    InvalidCharacterHeads,
+    #[msg("Action embedding or logit head doesn't match the action-state field")]
+    InvalidActionTensors,
+    #[msg("Layer scales must be non-zero for each layer and zero past num_layers")]
+    InvalidLayerScales,
+    #[msg("Output smoothing may only weight decoded LINEAR player fields")]
+    InvalidOutputSmoothing,
 
     // ── Inference errors ─────────────────────────────────────────────────
     #[msg("Account data too small for specified dimensions")]
     InsufficientData,
     #[msg("Model manifest is not ready (shards not finalized)")]
     ModelNotReady,
+    #[msg("Model requires a cartridge NFT token account")]
+    CartridgeRequired,
+    #[msg("Token account does not hold or delegate the cartridge NFT to the creator")]
+    CartridgeNotHeld,
+    #[msg("Model requires a gate token account")]
+    TokenGateRequired,
+    #[msg("Creator does not hold enough of the model's gate token")]
+    TokenGateBalanceTooLow,
+    #[msg("Gated models cannot be forked")]
+    CartridgeGated,
+    #[msg("Model is deprecated — pass allow_deprecated to play it anyway")]
+    ModelDeprecated,
     #[msg("Hidden state dimensions do not match manifest")]
     HiddenStateMismatch,
+    #[msg("Hidden state account is not a fresh, large enough program account")]
+    HiddenStateInUse,
+    #[msg("Unknown hidden state dtype")]
+    InvalidHiddenDtype,
+    #[msg("Manifest does not match the session's model")]
+    ModelMismatch,
+    #[msg("Requested hidden state range is out of bounds or too large")]
+    HiddenRangeOutOfBounds,
+
+    // ── Checkpoint errors ────────────────────────────────────────────────
+    #[msg("Checkpoint account too small for compressed hidden state")]
+    CheckpointTooLarge,
+    #[msg("Checkpoint data is corrupt or does not match the hidden state")]
+    CheckpointCorrupt,
+    #[msg("Checkpoint account already belongs to another session")]
+    CheckpointInUse,
+    #[msg("Replay has already been verified")]
+    ReplayAlreadyVerified,
+    #[msg("Replay verification would run past the replay's final frame")]
+    ReplayFrameOutOfRange,
+    #[msg("Replay already has a minted NFT and can't be archived")]
+    ReplayAlreadyMinted,
+    #[msg("Re-executed replay diverges from a committed keyframe")]
+    ReplayMismatch,
+    #[msg("Session's input buffer has no room for a frame log, so it can't be replayed")]
+    ReplayNotLogged,
+
+    // ── Matmul accumulator errors ───────────────────────────────────────
+    #[msg("Accumulator account too small for the tensor's rows")]
+    AccumulatorTooSmall,
+    #[msg("Accumulator is still in use by another cranker for this frame")]
+    AccumulatorInUse,
+    #[msg("Accumulator does not match this session, frame, segment, cranker or input")]
+    AccumulatorMismatch,
+    #[msg("Row range is not chunk-aligned or out of bounds")]
+    InvalidAccumulatorRange,
+    #[msg("Accumulator is missing row chunks")]
+    AccumulatorIncomplete,
+
+    // ── Wager errors ─────────────────────────────────────────────────────
+    #[msg("Amount must be non-zero")]
+    InvalidAmount,
+    #[msg("Player 2 has already deposited")]
+    WagerAlreadyAccepted,
+    #[msg("Session has not ended")]
+    SessionNotEnded,
+    #[msg("Wager timeout has not elapsed")]
+    WagerTimeoutNotReached,
+    #[msg("Wager is denominated in a different currency than this instruction")]
+    WagerMintMismatch,
+    #[msg("Token wager payout requires the player's token account")]
+    PayoutTokenAccountRequired,
+
+    // ── Rent pool errors ─────────────────────────────────────────────────
+    #[msg("Rent pool has insufficient free lamports")]
+    RentPoolInsufficient,
+    #[msg("Withdrawal exceeds the funder's deposit")]
+    WithdrawExceedsDeposit,
+    #[msg("Account does not belong to this session")]
+    SessionAccountMismatch,
+    #[msg("Settle session fees before closing the session")]
+    FeesUnsettled,
+    #[msg("Settle or refund the session's wager first")]
+    WagerUnsettled,
+    #[msg("The session's tips must be claimed first")]
+    TipsUnclaimed,
+
+    // ── Tournament errors ────────────────────────────────────────────────
+    #[msg("Bracket size must be 2, 4, or 8")]
+    InvalidBracketSize,
+    #[msg("Tournament registration is closed or full")]
+    RegistrationClosed,
+    #[msg("Tournament has not started or is already decided")]
+    TournamentNotRunning,
+    #[msg("Bracket node is not a playable match")]
+    InvalidBracketNode,
+    #[msg("Session is not a decided match between this node's players")]
+    SessionNotBracketMatch,
+    #[msg("Tournament is not complete")]
+    TournamentIncomplete,
+    #[msg("Payout account does not match the final standings")]
+    PayoutMismatch,
+    #[msg("Refund accounts must be the entrants, in join order")]
+    RefundMismatch,
+
+    // ── Tip errors ───────────────────────────────────────────────────────
+    #[msg("Player slot must be 0 or 1 and occupied")]
+    InvalidPlayerSlot,
+    #[msg("No tips to claim")]
+    NothingToClaim,
+
+    // ── Fee errors ───────────────────────────────────────────────────────
+    #[msg("Fee split must sum to 10000 basis points")]
+    InvalidFeeSplit,
+    #[msg("Cranker account is not the session's top cranker")]
+    NotTopCranker,
+    #[msg("Model charges its session fee in an SPL mint; pass the fee token accounts")]
+    FeeTokenAccountsRequired,
+    #[msg("Fee token account does not match the model's fee mint or treasury")]
+    FeeTokenMismatch,
+
+    // ── Settlement errors ────────────────────────────────────────────────
+    #[msg("Model has no settlement verifier for off-chain transitions")]
+    SettlementDisabled,
+    #[msg("Transition does not extend the session's current frame")]
+    StaleTransition,
+
+    // ── Delegation errors ────────────────────────────────────────────────
+    #[msg("Commit mask must name at least one session account and no others")]
+    InvalidCommitMask,
+    #[msg("Keypair session accounts must sign their own delegation")]
+    DelegationSignerMissing,
+
+    // ── Automation errors ────────────────────────────────────────────────
+    #[msg("Crank interval is shorter than the session's tick")]
+    CrankIntervalTooShort,
+    #[msg("Crank must run at least one iteration")]
+    CrankIterationsZero,
+}
+
+impl From<crate::lut::LutError> for WorldModelError {
+    fn from(e: crate::lut::LutError) -> Self {
+        use crate::lut::LutError;
+        match e {
+            LutError::SiluNotZeroAtOrigin => WorldModelError::InvalidSiluLut,
+            LutError::SoftplusNotMonotone => WorldModelError::InvalidSoftplusLut,
+            LutError::RsqrtNotDecreasing => WorldModelError::InvalidRsqrtLut,
+            LutError::ExpNegNotMonotone => WorldModelError::InvalidExpNegLut,
+            LutError::GeluNotZeroAtOrigin => WorldModelError::InvalidGeluLut,
+            LutError::TanhNotMonotone => WorldModelError::InvalidTanhLut,
+            LutError::UnknownVersion => WorldModelError::UnknownLutVersion,
+        }
+    }
 }
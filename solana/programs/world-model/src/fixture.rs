@@ -0,0 +1,666 @@
+//! Synthetic model fixture — a tiny but structurally complete model for
+//! tests, so unit tests, Mollusk tests and the simulator share one model
+//! instead of each fabricating byte blobs.
+//!
+//! `SyntheticModel::tiny()` is d_model=32, d_inner=64, d_state=4, 2 layers,
+//! INT8 hidden state, one weight shard per layer. Weights follow a fixed
+//! pattern, the LUTs pass `validate_luts`, every layer has scales and a
+//! tensor table entry, and the manifest it builds has no consistency
+//! failures. The melee_v2 encoder spec is wider than d_model=32, so the
+//! fixture carries a compact spec of its own.
+//!
+//! Compiled under `cfg(test)` or the `test-support` feature.
+
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hash;
+
+use crate::inference::{Mamba2Config, Rounding};
+use crate::lut::{
+    EXP_NEG_OFFSET, GELU_OFFSET, LUT_TOTAL_SIZE, LUT_VERSION_CURRENT, RSQRT_OFFSET, SILU_OFFSET,
+    SOFTPLUS_OFFSET, TANH_OFFSET,
+};
+use crate::state::*;
+
+/// in_proj requantization scale (65536 = 1.0). With `pattern` weights these
+/// keep activations well inside INT8 while each layer still moves the
+/// residual stream.
+pub const FIXTURE_IN_SCALE: u16 = 4096;
+/// out_proj requantization scale (65536 = 1.0)
+pub const FIXTURE_OUT_SCALE: u16 = 8192;
+/// Action states, more than a one-hot field can hold (as in Melee)
+pub const FIXTURE_ACTION_STATES: u16 = 400;
+
+/// Activation tables shaped like the real ones (SiLU and GELU ~0 at the
+/// origin, monotone softplus / rsqrt / exp_neg / tanh), so they pass
+/// `validate_luts` as a v2 block and as its v1 prefix.
+pub fn test_luts() -> [u8; LUT_TOTAL_SIZE] {
+    let mut luts = [0u8; LUT_TOTAL_SIZE];
+    for i in 0u16..256 {
+        let x = (i as i8) as f64 / 16.0;
+        luts[SILU_OFFSET + i as usize] =
+            (x / (1.0 + (-x).exp()) * 16.0 + 0.5).clamp(-128.0, 127.0) as i8 as u8;
+        luts[SOFTPLUS_OFFSET + i as usize] =
+            ((1.0 + x.exp()).ln() * 32.0).clamp(-128.0, 127.0) as i8 as u8;
+        luts[RSQRT_OFFSET + i as usize] =
+            (32.0 / ((i.max(1) as f64) / 32.0).sqrt()).min(255.0) as u8;
+        luts[EXP_NEG_OFFSET + i as usize] = ((-(i as f64) / 32.0).exp() * 255.0) as u8;
+        luts[GELU_OFFSET + i as usize] =
+            (x * 0.5 * (1.0 + (x * 0.7978846 * (1.0 + 0.044715 * x * x)).tanh()) * 16.0)
+                .round()
+                .clamp(-128.0, 127.0) as i8 as u8;
+        luts[TANH_OFFSET + i as usize] = ((x / 2.0).tanh() * 127.0).round() as i8 as u8;
+    }
+    luts
+}
+
+/// `n` INT8 values cycling through [-15, 15], stepping by `k`.
+pub fn pattern(n: usize, k: usize) -> Vec<u8> {
+    (0..n).map(|i| (((i * k) % 31) as i8 - 15) as u8).collect()
+}
+
+/// Ten fields per player plus the stage: 21 slots, fits d_model=32.
+pub fn compact_encoder_spec() -> EncoderSpec {
+    let fields = [
+        FieldSpec::linear(FIELD_X, 256),
+        FieldSpec::linear(FIELD_Y, 256),
+        FieldSpec::linear(FIELD_PERCENT, 4),
+        FieldSpec::linear(FIELD_STOCKS, 1),
+        FieldSpec::binary(FIELD_FACING),
+        FieldSpec::binary(FIELD_ON_GROUND),
+        FieldSpec::linear(FIELD_ACTION_STATE, 1),
+        FieldSpec::linear(FIELD_STICK_X, 1),
+        FieldSpec::linear(FIELD_STICK_Y, 1),
+        FieldSpec::linear(FIELD_BUTTONS, 1),
+    ];
+
+    let mut spec = EncoderSpec {
+        num_player_fields: fields.len() as u8,
+        ..EncoderSpec::default()
+    };
+    for (slot, field) in spec.player_fields.iter_mut().zip(fields) {
+        *slot = field;
+    }
+    spec.num_global_fields = 1;
+    spec.global_fields[0] = FieldSpec::linear(FIELD_STAGE, 1);
+    spec
+}
+
+/// A complete model: config, LUTs, encoder spec, weight shards and the
+/// tensor table that locates each layer's tensors in them.
+pub struct SyntheticModel {
+    pub config: Mamba2Config,
+    pub hidden_dtype: u8,
+    pub luts: [u8; LUT_TOTAL_SIZE],
+    pub encoder_spec: EncoderSpec,
+    /// Weight data regions in shard index order (one per layer)
+    pub shards: Vec<Vec<u8>>,
+    pub tensor_table: Vec<[TensorRef; TENSORS_PER_LAYER]>,
+}
+
+impl SyntheticModel {
+    /// d_model=32, d_inner=64, d_state=4, 2 layers, INT8 hidden state.
+    pub fn tiny() -> Self {
+        Self::new(32, 64, 4, 2, HIDDEN_DTYPE_I8)
+    }
+
+    /// A model of the given shape. Weights differ per layer and tensor but
+    /// are the same on every call.
+    pub fn new(
+        d_model: usize,
+        d_inner: usize,
+        d_state: usize,
+        num_layers: usize,
+        hidden_dtype: u8,
+    ) -> Self {
+        assert!(num_layers <= MAX_LAYERS, "at most {MAX_LAYERS} layers");
+        let shapes = [
+            (TENSOR_IN_PROJ, 2 * d_inner, d_model),
+            (TENSOR_OUT_PROJ, d_model, d_inner),
+            (TENSOR_NORM, d_model, 1),
+            (TENSOR_A_LOG, d_inner, 1),
+            (TENSOR_DT_BIAS, d_inner, 1),
+            (TENSOR_DT_PROJ, 1, d_model),
+        ];
+
+        let mut shards = Vec::with_capacity(num_layers);
+        let mut tensor_table = Vec::with_capacity(num_layers);
+        for layer in 0..num_layers {
+            let mut shard = Vec::new();
+            let mut tensors = [TensorRef::default(); TENSORS_PER_LAYER];
+            for (slot, rows, cols) in shapes {
+                tensors[slot] = TensorRef {
+                    shard: layer as u8,
+                    dtype: TENSOR_DTYPE_I8,
+                    offset: shard.len() as u32,
+                    rows: rows as u16,
+                    cols: cols as u16,
+                };
+                if slot == TENSOR_NORM {
+                    // RMSNorm gains near 1.0, so activations stay in range
+                    shard.extend(std::iter::repeat_n(64, rows));
+                } else {
+                    let step = 2 * (layer * TENSORS_PER_LAYER + slot) + 3;
+                    shard.extend(pattern(rows * cols, step));
+                }
+            }
+            shards.push(shard);
+            tensor_table.push(tensors);
+        }
+
+        SyntheticModel {
+            config: Mamba2Config {
+                d_model,
+                d_inner,
+                d_state,
+                num_layers,
+                num_heads: 1,
+                ngroups: 0,
+                dt_shift: 0,
+                rounding: Rounding::Truncate,
+            },
+            hidden_dtype,
+            luts: test_luts(),
+            encoder_spec: compact_encoder_spec(),
+            shards,
+            tensor_table,
+        }
+    }
+
+    pub fn total_weight_bytes(&self) -> u32 {
+        self.shards.iter().map(|s| s.len() as u32).sum()
+    }
+
+    /// Shard data regions as forward_pass takes them.
+    pub fn shard_slices(&self) -> Vec<&[u8]> {
+        self.shards.iter().map(|s| s.as_slice()).collect()
+    }
+
+    /// Per-layer input projection scales, broadcast like run_inference does.
+    pub fn in_scales(&self) -> Vec<Vec<u16>> {
+        vec![vec![FIXTURE_IN_SCALE; 2 * self.config.d_inner]; self.config.num_layers]
+    }
+
+    /// Per-layer output projection scales, broadcast like run_inference does.
+    pub fn out_scales(&self) -> Vec<Vec<u16>> {
+        vec![vec![FIXTURE_OUT_SCALE; self.config.d_model]; self.config.num_layers]
+    }
+
+    /// A deterministic d_model input vector.
+    pub fn input(&self) -> Vec<i8> {
+        pattern(self.config.d_model, 7).into_iter().map(|b| b as i8).collect()
+    }
+
+    /// The manifest as it stands once every shard is registered: ready,
+    /// tensor table, scales, LUTs and encoder spec filled in. The shard
+    /// directory and authority are left default.
+    pub fn manifest(&self) -> ModelManifestAccount {
+        let zeros = vec![0u8; 8 + core::mem::size_of::<ModelManifestAccount>()];
+        let mut m = ModelManifestAccount::try_deserialize_unchecked(&mut &zeros[..])
+            .expect("zeroed manifest decodes");
+
+        m.name[..9].copy_from_slice(b"synthetic");
+        m.version = 1;
+        m.d_model = self.config.d_model as u16;
+        m.d_inner = self.config.d_inner as u16;
+        m.d_state = self.config.d_state as u16;
+        m.num_layers = self.config.num_layers as u8;
+        m.num_heads = self.config.num_heads as u8;
+        m.hidden_dtype = self.hidden_dtype;
+        m.expected_shards = self.shards.len() as u8;
+        m.num_shards = self.shards.len() as u8;
+        m.registered_weight_bytes = self.total_weight_bytes() as u64;
+        m.tensor_table[..self.tensor_table.len()].copy_from_slice(&self.tensor_table);
+        m.layer_input_scales[..self.config.num_layers].fill(FIXTURE_IN_SCALE);
+        m.layer_output_scales[..self.config.num_layers].fill(FIXTURE_OUT_SCALE);
+        m.luts = self.luts;
+        m.lut_version = LUT_VERSION_CURRENT;
+        m.encoder_spec = self.encoder_spec;
+        m.input_size = self.encoder_spec.input_width() as u16;
+        m.num_continuous = self.encoder_spec.slots_of_kind(FIELD_KIND_LINEAR) as u8;
+        m.num_binary = self.encoder_spec.slots_of_kind(FIELD_KIND_BINARY) as u8;
+        m.num_action_states = FIXTURE_ACTION_STATES;
+        m.total_params = self.total_weight_bytes();
+        m.total_weight_bytes = self.total_weight_bytes();
+        m.ready = true;
+        m
+    }
+
+    /// init_manifest arguments for this model. init_manifest installs the
+    /// melee_v2 encoder spec and the v1 LUTs; follow with
+    /// set_encoder_spec(`encoder_spec`), set_tensor_table per layer and
+    /// set_extended_luts(`extended_luts_args`).
+    pub fn init_manifest_args(&self) -> crate::instruction::InitManifest {
+        let m = self.manifest();
+        crate::instruction::InitManifest {
+            name: m.name,
+            version: m.version,
+            d_model: m.d_model,
+            d_inner: m.d_inner,
+            d_state: m.d_state,
+            num_layers: m.num_layers,
+            num_heads: m.num_heads,
+            hidden_dtype: m.hidden_dtype,
+            expected_shards: m.expected_shards,
+            luts: m.luts[..LUT_V1_SIZE].try_into().unwrap(),
+            num_continuous: m.num_continuous,
+            num_action_states: m.num_action_states,
+            num_binary: m.num_binary,
+            input_size: m.input_size,
+            total_params: m.total_params,
+            total_weight_bytes: m.total_weight_bytes,
+        }
+    }
+
+    /// set_extended_luts arguments: the tables past the v1 block.
+    pub fn extended_luts_args(&self) -> crate::instruction::SetExtendedLuts {
+        crate::instruction::SetExtendedLuts {
+            luts: self.luts[LUT_V1_SIZE..].try_into().unwrap(),
+        }
+    }
+
+    /// SHA-256 of a shard's data region, as finalize_weights checks it.
+    pub fn shard_hash(&self, shard: usize) -> [u8; 32] {
+        hash(&self.shards[shard]).to_bytes()
+    }
+
+    /// Raw WeightAccount data for `shard`: header plus data region. An
+    /// unfinalized account has an empty (zeroed) data region, ready for
+    /// upload_weights; a finalized one holds the shard and its hash.
+    pub fn weight_account_data(&self, shard: usize, authority: Pubkey, finalized: bool) -> Vec<u8> {
+        let data = &self.shards[shard];
+        let header = WeightAccount {
+            shard_index: shard as u8,
+            data_size: data.len() as u32,
+            authority,
+            finalized,
+            data_hash: if finalized { self.shard_hash(shard) } else { [0; 32] },
+            bytes_written: if finalized { data.len() as u32 } else { 0 },
+        };
+        let mut account = Vec::with_capacity(WEIGHT_HEADER_SIZE + data.len());
+        header.try_serialize(&mut account).expect("weight header serializes");
+        debug_assert_eq!(account.len(), WEIGHT_HEADER_SIZE);
+        if finalized {
+            account.extend_from_slice(data);
+        } else {
+            account.resize(WEIGHT_HEADER_SIZE + data.len(), 0);
+        }
+        account
+    }
+
+    /// Size of the hidden state data region in bytes.
+    pub fn hidden_data_size(&self) -> u32 {
+        hidden_data_size(
+            self.config.num_layers as u8,
+            self.config.d_inner as u16,
+            self.config.d_state as u16,
+            self.hidden_dtype,
+        )
+    }
+
+    /// Raw hidden state account data as create_session leaves it: header
+    /// at frame 0, not yet initialized, zeroed state.
+    pub fn hidden_account_data(&self) -> Vec<u8> {
+        let mut data = vec![0u8; HIDDEN_HEADER_SIZE + self.hidden_data_size() as usize];
+        write_hidden_header(
+            &mut data,
+            &HiddenHeader {
+                num_layers: self.config.num_layers as u8,
+                d_inner: self.config.d_inner as u16,
+                d_state: self.config.d_state as u16,
+                data_size: self.hidden_data_size(),
+                frame: 0,
+                initialized: false,
+                dtype: self.hidden_dtype,
+            },
+        );
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::{forward_pass, HiddenStateMut};
+
+    #[test]
+    fn test_tiny_manifest_is_consistent() {
+        let model = SyntheticModel::tiny();
+        let manifest = model.manifest();
+        assert_eq!(manifest.consistency_failures(), 0);
+        assert!(manifest.encoder_spec.is_valid());
+        // Eight LINEAR and two BINARY slots a player, plus the stage
+        assert_eq!((manifest.num_continuous, manifest.num_binary), (17, 4));
+        assert!(crate::lut::validate_luts(manifest.lut_block()).is_ok());
+
+        let header = read_hidden_header(&model.hidden_account_data());
+        assert!(header.matches_manifest(&manifest));
+    }
+
+    #[test]
+    fn test_tiny_forward_pass_is_deterministic() {
+        let model = SyntheticModel::tiny();
+        let in_scales = model.in_scales();
+        let out_scales = model.out_scales();
+        let in_refs: Vec<&[u16]> = in_scales.iter().map(|s| s.as_slice()).collect();
+        let out_refs: Vec<&[u16]> = out_scales.iter().map(|s| s.as_slice()).collect();
+
+        let run = || {
+            let mut hidden = vec![0i8; model.hidden_data_size() as usize];
+            let output = forward_pass(
+                &model.input(),
+                HiddenStateMut::I8(&mut hidden),
+                &model.shard_slices(),
+                &model.tensor_table,
+                None,
+                &[],
+                &model.luts,
+                &model.config,
+                &in_refs,
+                &out_refs,
+                &mut [],
+                &mut [],
+            )
+            .expect("tensor table resolves");
+            (output, hidden)
+        };
+
+        let (output, hidden) = run();
+        assert_eq!(output.len(), model.config.d_model);
+        assert_ne!(output, model.input(), "layers should change the residual stream");
+        assert!(hidden.iter().any(|&h| h != 0), "scan should write the hidden state");
+        assert_eq!(run(), (output, hidden));
+    }
+
+    #[test]
+    fn test_forward_pass_resolves_tensors_by_name() {
+        use crate::tensor_dir::{directory_size, layer_tensor_hash, write_directory, TensorEntry};
+
+        let model = SyntheticModel::tiny();
+        let in_scales = model.in_scales();
+        let out_scales = model.out_scales();
+        let in_refs: Vec<&[u16]> = in_scales.iter().map(|s| s.as_slice()).collect();
+        let out_refs: Vec<&[u16]> = out_scales.iter().map(|s| s.as_slice()).collect();
+
+        // Prefix every shard with a directory naming its layer's tensors
+        let named: Vec<Vec<u8>> = model
+            .shards
+            .iter()
+            .zip(&model.tensor_table)
+            .enumerate()
+            .map(|(layer, (shard, tensors))| {
+                let dir_size = directory_size(TENSORS_PER_LAYER);
+                let mut entries: Vec<TensorEntry> = tensors
+                    .iter()
+                    .zip(TENSOR_NAMES)
+                    .map(|(t, name)| TensorEntry {
+                        name_hash: layer_tensor_hash(layer, name),
+                        offset: t.offset + dir_size as u32,
+                        rows: t.rows,
+                        cols: t.cols,
+                        dtype: t.dtype,
+                    })
+                    .collect();
+                let mut out = vec![0u8; dir_size];
+                write_directory(&mut entries, &mut out).unwrap();
+                out.extend_from_slice(shard);
+                out
+            })
+            .collect();
+        let named_refs: Vec<&[u8]> = named.iter().map(|s| s.as_slice()).collect();
+
+        let run = |shards: &[&[u8]], table: &[[TensorRef; TENSORS_PER_LAYER]]| {
+            let mut hidden = vec![0i8; model.hidden_data_size() as usize];
+            forward_pass(
+                &model.input(),
+                HiddenStateMut::I8(&mut hidden),
+                shards,
+                table,
+                None,
+                &[],
+                &model.luts,
+                &model.config,
+                &in_refs,
+                &out_refs,
+                &mut [],
+                &mut [],
+            )
+        };
+
+        // The directories alone locate every tensor: the table is blank
+        let blank = vec![[TensorRef::default(); TENSORS_PER_LAYER]; model.config.num_layers];
+        let expected = run(&model.shard_slices(), &model.tensor_table).unwrap();
+        assert_eq!(run(&named_refs, &blank), Some(expected));
+        assert_eq!(run(&model.shard_slices(), &blank), None);
+    }
+
+    /// A calibrating run widens one range per layer, frame over frame, and
+    /// every observed layer gets a fitted scale back.
+    #[test]
+    fn test_calibration_records_layer_ranges() {
+        use crate::inference::ActivationRange;
+
+        let model = SyntheticModel::tiny();
+        let layers = model.config.num_layers;
+        let in_scales = model.in_scales();
+        let out_scales = model.out_scales();
+        let in_refs: Vec<&[u16]> = in_scales.iter().map(|s| s.as_slice()).collect();
+        let out_refs: Vec<&[u16]> = out_scales.iter().map(|s| s.as_slice()).collect();
+
+        let mut calibration = CalibrationAccount::default();
+        calibration.reset();
+        let mut hidden = vec![0i8; model.hidden_data_size() as usize];
+        let mut seen = vec![ActivationRange::default(); layers];
+        for slot in 1..=3 {
+            let mut ranges = vec![ActivationRange::default(); layers];
+            forward_pass(
+                &model.input(),
+                HiddenStateMut::I8(&mut hidden),
+                &model.shard_slices(),
+                &model.tensor_table,
+                None,
+                &[],
+                &model.luts,
+                &model.config,
+                &in_refs,
+                &out_refs,
+                &mut [],
+                &mut ranges,
+            )
+            .expect("tensor table resolves");
+            calibration.record(&ranges, slot);
+            for (total, frame) in seen.iter_mut().zip(&ranges) {
+                total.merge(frame);
+            }
+        }
+
+        assert_eq!((calibration.frames, calibration.last_slot), (3, 3));
+        for (layer, expected) in seen.iter().enumerate() {
+            let range = calibration.range(layer);
+            assert_eq!(range, *expected);
+            assert!(range.in_scale().is_some() && range.out_scale().is_some());
+        }
+        assert_eq!(calibration.range(layers), ActivationRange::default());
+    }
+
+    #[test]
+    fn test_tied_action_head_reads_the_embedding_transposed() {
+        use crate::inference::{decode_output, encode_input, ActionTensors};
+
+        // Action state becomes a 4-slot field over 8 classes, after six
+        // one-slot fields
+        let (width, classes) = (4, 8);
+        let mut spec = compact_encoder_spec();
+        spec.player_fields[6] = FieldSpec::categorical(FIELD_ACTION_STATE, width as u8);
+        let embedding = pattern(width * classes, 7);
+        let head: Vec<u8> = (0..classes * width)
+            .map(|k| embedding[(k % width) * classes + k / width])
+            .collect();
+        let tied = ActionTensors { embedding: &embedding, head: None, width, classes };
+        let untied = ActionTensors { head: Some(&head[..]), ..tied };
+
+        let mut players = [PlayerState::default(); 2];
+        players[0].action_state = 5;
+        players[1].action_state = 2;
+        let inputs = [ControllerInput::default(); 2];
+        let mut input = vec![0i8; 32];
+        encode_input(&spec, Some(&tied), &players, &inputs, 0, &mut input, 32);
+        let column: Vec<i8> = (0..width).map(|r| embedding[r * classes + 5] as i8).collect();
+        assert_eq!(&input[6..6 + width], &column[..]);
+
+        // Decoding the encoded vector, the tied head matches the explicit
+        // transposed copy
+        let offsets = [0, spec.player_width()];
+        let luts = test_luts();
+        let a = decode_output(&spec, Some(&tied), &luts, &input, offsets);
+        let b = decode_output(&spec, Some(&untied), &luts, &input, offsets);
+        for (a, b) in a.iter().zip(&b) {
+            assert!((a.action_state as usize) < classes);
+            assert_eq!(a.action_state, b.action_state);
+            assert_eq!(a.action_state_prob, b.action_state_prob);
+        }
+    }
+
+    /// A hashed action field tells every action state apart up to a full
+    /// slot collision, and round-trips each state to one with its slots.
+    #[test]
+    fn test_hashed_action_field_round_trips() {
+        use crate::inference::{decode_output, encode_input, hashed_slots};
+
+        // Action state becomes a 16-slot field after six one-slot fields
+        let width = 16;
+        let mut spec = compact_encoder_spec();
+        spec.player_fields[6] = FieldSpec::hashed(FIELD_ACTION_STATE, width, FIXTURE_ACTION_STATES);
+        assert!(spec.is_valid() && spec.covers_action_states(FIXTURE_ACTION_STATES));
+        assert!(!spec.covers_action_states(FIXTURE_ACTION_STATES - 1));
+        assert_eq!(spec.player_width(), 10 + width as usize - 1);
+
+        let luts = test_luts();
+        let inputs = [ControllerInput::default(); 2];
+        let offsets = [0, spec.player_width()];
+        let d_model = 2 * spec.player_width() + 1;
+        let mut input = vec![0i8; d_model];
+        for state in (0..FIXTURE_ACTION_STATES).step_by(7) {
+            let mut players = [PlayerState::default(); 2];
+            players[0].action_state = state;
+            players[1].action_state = FIXTURE_ACTION_STATES - 1 - state;
+            encode_input(&spec, None, &players, &inputs, 0, &mut input, d_model);
+
+            let hot = &input[6..6 + width as usize];
+            assert_eq!(hot.iter().filter(|&&v| v == 64).count(), HASHED_PROBES);
+            assert_eq!(hot.iter().filter(|&&v| v == -64).count(), width as usize - HASHED_PROBES);
+
+            let decoded = decode_output(&spec, None, &luts, &input, offsets);
+            for (p, d) in players.iter().zip(&decoded) {
+                let slots = |s: u16| {
+                    let mut k = hashed_slots(s as i32, width as usize);
+                    k.sort();
+                    k
+                };
+                assert!(d.action_state < FIXTURE_ACTION_STATES);
+                assert_eq!(slots(d.action_state), slots(p.action_state));
+            }
+        }
+    }
+
+    /// Smoothing moves each weighted field a fixed share of the way to
+    /// its decoded value, leaves the rest raw and hands the raw values back.
+    #[test]
+    fn test_output_smoothing_is_a_fixed_point_ema() {
+        use crate::inference::smooth_players;
+
+        let spec = compact_encoder_spec();
+        let mut smoothing = [0u8; MAX_PLAYER_FIELDS];
+        // x keeps 3/4 of its last value, percent half
+        smoothing[0] = 192;
+        smoothing[2] = 128;
+        assert!(spec.accepts_smoothing(&smoothing));
+
+        let mut previous = [PlayerState::default(); 2];
+        previous[0].x = 1000;
+        previous[1].x = -1000;
+        previous[0].percent = 40;
+        let mut players = previous;
+        players[0].x = 2000;
+        players[1].x = -1001;
+        players[0].percent = 50;
+        players[0].y = 77;
+
+        let raw = smooth_players(&spec, &smoothing, &previous, &mut players);
+        assert_eq!(raw, vec![2000, 50, -1001, 0]);
+        assert_eq!(players[0].x, 1250);
+        assert_eq!(players[0].percent, 45);
+        assert_eq!(players[0].y, 77);
+        // The shift floors, so a pull under one unit is dropped
+        assert_eq!(players[1].x, -1001);
+
+        let off = [0; MAX_PLAYER_FIELDS];
+        assert!(smooth_players(&spec, &off, &previous, &mut players).is_empty());
+
+        // Binary, action-state, controller and unused slots stay raw
+        for field in [4, 6, 7, 10] {
+            let mut bad = [0u8; MAX_PLAYER_FIELDS];
+            bad[field] = 1;
+            assert!(!spec.accepts_smoothing(&bad));
+        }
+    }
+
+    /// The stub walks players off the stage's ledges and takes a stock at
+    /// its blast zones; the encoder's stage fields read the same geometry.
+    #[test]
+    fn test_stage_geometry_drives_stub_and_encoder() {
+        use crate::inference::{encode_input, enforce_blast_zones, step_players};
+        use crate::stages::{stage_or_default, RESPAWN_Y, STAGE_BATTLEFIELD};
+
+        let bf = stage_or_default(STAGE_BATTLEFIELD);
+        let still = [ControllerInput::default(); 2];
+
+        // Past the ledge there's no ground to land on
+        let mut players = PlayerState::spawns([2, 20], 0, STAGE_BATTLEFIELD);
+        players[0].x = -bf.half_width - 256;
+        step_players(&mut players, &still, STAGE_BATTLEFIELD);
+        assert_eq!(players[0].on_ground, 0);
+        assert!(players[0].y < 0);
+        assert_eq!((players[1].on_ground, players[1].y), (1, 0));
+
+        // Falling through the bottom blast zone costs a stock
+        players[0].y = bf.blast.bottom + 1;
+        step_players(&mut players, &still, STAGE_BATTLEFIELD);
+        assert_eq!(players[0].stocks, 3);
+        assert_eq!((players[0].x, players[0].y), (0, RESPAWN_Y));
+        assert_eq!(players[0].character, 2);
+
+        // A model that decoded the KO itself isn't charged twice
+        players[1].x = bf.blast.right + 1;
+        players[1].stocks = 3;
+        assert_eq!(enforce_blast_zones(&mut players, [3, 4], bf), [false, true]);
+        assert_eq!(players[1].stocks, 3);
+
+        let mut spec = compact_encoder_spec();
+        spec.num_global_fields = 3;
+        spec.global_fields[1] = FieldSpec::linear(FIELD_STAGE_HALF_WIDTH, 256);
+        spec.global_fields[2] = FieldSpec::linear(FIELD_STAGE_BLAST_BOTTOM, 256);
+        assert!(spec.is_valid());
+        let global = 2 * spec.player_width();
+        let mut input = vec![0i8; global + 3];
+        encode_input(&spec, None, &players, &still, STAGE_BATTLEFIELD, &mut input, global + 3);
+        assert_eq!(input[global], STAGE_BATTLEFIELD as i8);
+        assert_eq!(input[global + 1], (bf.half_width / 256) as i8);
+        assert_eq!(input[global + 2], (bf.blast.bottom / 256) as i8);
+    }
+
+    #[test]
+    fn test_weight_account_data() {
+        let model = SyntheticModel::tiny();
+        let authority = Pubkey::new_unique();
+
+        let finalized = model.weight_account_data(1, authority, true);
+        let header = WeightAccount::try_deserialize(&mut &finalized[..]).unwrap();
+        assert_eq!(header.shard_index, 1);
+        assert_eq!(header.data_hash, model.shard_hash(1));
+        assert_eq!(&finalized[WEIGHT_HEADER_SIZE..], &model.shards[1][..]);
+
+        let empty = model.weight_account_data(0, authority, false);
+        assert_eq!(empty.len(), WEIGHT_HEADER_SIZE + model.shards[0].len());
+        assert!(!WeightAccount::try_deserialize(&mut &empty[..]).unwrap().finalized);
+    }
+}
@@ -0,0 +1,450 @@
+//! Golden-vector tests against the PyTorch reference.
+//!
+//! nojohns-training exports one fixture per case (see
+//! quantization/export_golden.py) into `tests/golden/*.awmg` next to this
+//! crate's Cargo.toml. Each fixture carries chain-ready weights, LUTs, one
+//! input step and the reference's quantized outputs; the tests run it
+//! through `forward_pass` (and `mamba2_layer_step` for single-layer cases)
+//! and fail if any element drifts more than the fixture's tolerance.
+//! `tests/golden/two-layer-i16.awmg` pins the kernel's own two-layer INT16
+//! output, so the suite always has a case to run even before a reference
+//! export lands.
+//!
+//! Fixture layout (little-endian):
+//!   0   magic "AWMG"
+//!   4   version: u8 (= 1)
+//!   5   hidden_dtype: u8         (HIDDEN_DTYPE_*)
+//!   6   tolerance: u8            (max |Δ| per element, INT8/INT16 units)
+//!   7   num_layers: u8
+//!   8   d_model: u16
+//!   10  d_inner: u16
+//!   12  d_state: u16
+//!   14  reserved: u16
+//!   16  luts: [u8; LUT_V1_SIZE]    (the forward pass reads only v1 tables)
+//!   per layer:
+//!       in_proj   i8[2*d_inner × d_model]
+//!       out_proj  i8[d_model × d_inner]
+//!       norm      i8[d_model]
+//!       a_log     i8[d_inner]
+//!       dt_bias   i8[d_inner]
+//!       in_scales  u16[2*d_inner]
+//!       out_scales u16[d_model]
+//!   input            i8[d_model]
+//!   hidden           [num_layers × d_inner × d_state] elements
+//!   expected_output  i8[d_model]
+//!   expected_hidden  [num_layers × d_inner × d_state] elements
+
+use crate::inference::{self, HiddenStateMut, Mamba2Config, Rounding, ScratchArena};
+use crate::lut::LUT_V1_SIZE;
+use crate::state::{
+    hidden_elem_size, TensorRef, HIDDEN_DTYPE_I16, TENSORS_PER_LAYER, TENSOR_A_LOG,
+    TENSOR_DTYPE_I8, TENSOR_DT_BIAS, TENSOR_IN_PROJ, TENSOR_NORM, TENSOR_OUT_PROJ,
+};
+
+pub const GOLDEN_MAGIC: &[u8; 4] = b"AWMG";
+pub const GOLDEN_VERSION: u8 = 1;
+
+/// One layer's chain-ready weights.
+pub struct GoldenLayer {
+    pub in_proj: Vec<u8>,
+    pub out_proj: Vec<u8>,
+    pub norm: Vec<u8>,
+    pub a_log: Vec<u8>,
+    pub dt_bias: Vec<u8>,
+    pub in_scales: Vec<u16>,
+    pub out_scales: Vec<u16>,
+}
+
+/// A parsed golden fixture.
+pub struct GoldenCase {
+    pub config: Mamba2Config,
+    pub hidden_dtype: u8,
+    pub tolerance: u8,
+    pub luts: Vec<u8>,
+    pub layers: Vec<GoldenLayer>,
+    pub input: Vec<i8>,
+    /// Raw hidden state bytes in hidden_dtype
+    pub hidden: Vec<u8>,
+    pub expected_output: Vec<i8>,
+    pub expected_hidden: Vec<u8>,
+}
+
+/// Sequential little-endian reader over a fixture.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let out = self.data.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(out)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let b = self.bytes(2)?;
+        Some(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u16s(&mut self, n: usize) -> Option<Vec<u16>> {
+        (0..n).map(|_| self.u16()).collect()
+    }
+
+    fn i8s(&mut self, n: usize) -> Option<Vec<i8>> {
+        Some(self.bytes(n)?.iter().map(|&b| b as i8).collect())
+    }
+}
+
+impl GoldenCase {
+    /// Parse a fixture. Returns None on a bad magic/version or truncation.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut r = Reader { data, pos: 0 };
+        if r.bytes(4)? != GOLDEN_MAGIC || r.u8()? != GOLDEN_VERSION {
+            return None;
+        }
+        let hidden_dtype = r.u8()?;
+        let tolerance = r.u8()?;
+        let num_layers = r.u8()? as usize;
+        let d_model = r.u16()? as usize;
+        let d_inner = r.u16()? as usize;
+        let d_state = r.u16()? as usize;
+        r.u16()?;
+
+        let luts = r.bytes(LUT_V1_SIZE)?.to_vec();
+        let mut layers = Vec::with_capacity(num_layers);
+        for _ in 0..num_layers {
+            layers.push(GoldenLayer {
+                in_proj: r.bytes(2 * d_inner * d_model)?.to_vec(),
+                out_proj: r.bytes(d_model * d_inner)?.to_vec(),
+                norm: r.bytes(d_model)?.to_vec(),
+                a_log: r.bytes(d_inner)?.to_vec(),
+                dt_bias: r.bytes(d_inner)?.to_vec(),
+                in_scales: r.u16s(2 * d_inner)?,
+                out_scales: r.u16s(d_model)?,
+            });
+        }
+
+        let hidden_len = num_layers * d_inner * d_state * hidden_elem_size(hidden_dtype);
+        let input = r.i8s(d_model)?;
+        let hidden = r.bytes(hidden_len)?.to_vec();
+        let expected_output = r.i8s(d_model)?;
+        let expected_hidden = r.bytes(hidden_len)?.to_vec();
+        if r.pos != data.len() {
+            return None;
+        }
+
+        Some(Self {
+            config: Mamba2Config {
+                d_model,
+                d_inner,
+                d_state,
+                num_layers,
+                num_heads: 1,
+                ngroups: 0,
+                dt_shift: 0,
+                rounding: Rounding::Truncate,
+            },
+            hidden_dtype,
+            tolerance,
+            luts,
+            layers,
+            input,
+            hidden,
+            expected_output,
+            expected_hidden,
+        })
+    }
+
+    /// Serialize back to the fixture layout.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let c = &self.config;
+        let mut out = Vec::new();
+        out.extend_from_slice(GOLDEN_MAGIC);
+        out.push(GOLDEN_VERSION);
+        out.push(self.hidden_dtype);
+        out.push(self.tolerance);
+        out.push(c.num_layers as u8);
+        out.extend_from_slice(&(c.d_model as u16).to_le_bytes());
+        out.extend_from_slice(&(c.d_inner as u16).to_le_bytes());
+        out.extend_from_slice(&(c.d_state as u16).to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&self.luts);
+        for l in &self.layers {
+            for t in [&l.in_proj, &l.out_proj, &l.norm, &l.a_log, &l.dt_bias] {
+                out.extend_from_slice(t);
+            }
+            for s in l.in_scales.iter().chain(&l.out_scales) {
+                out.extend_from_slice(&s.to_le_bytes());
+            }
+        }
+        out.extend(self.input.iter().map(|&v| v as u8));
+        out.extend_from_slice(&self.hidden);
+        out.extend(self.expected_output.iter().map(|&v| v as u8));
+        out.extend_from_slice(&self.expected_hidden);
+        out
+    }
+
+    /// Pack the layers into one shard plus the matching tensor table, the
+    /// way finalize/register lays weights out on-chain.
+    fn shard_and_table(&self) -> (Vec<u8>, Vec<[TensorRef; TENSORS_PER_LAYER]>) {
+        let (d_model, d_inner) = (self.config.d_model as u16, self.config.d_inner as u16);
+        let mut shard = Vec::new();
+        let mut table = Vec::new();
+        for l in &self.layers {
+            let mut refs = [TensorRef::default(); TENSORS_PER_LAYER];
+            let slots = [
+                (TENSOR_IN_PROJ, &l.in_proj, 2 * d_inner, d_model),
+                (TENSOR_OUT_PROJ, &l.out_proj, d_model, d_inner),
+                (TENSOR_NORM, &l.norm, d_model, 1),
+                (TENSOR_A_LOG, &l.a_log, d_inner, 1),
+                (TENSOR_DT_BIAS, &l.dt_bias, d_inner, 1),
+            ];
+            for (slot, bytes, rows, cols) in slots {
+                refs[slot] = TensorRef {
+                    shard: 0,
+                    dtype: TENSOR_DTYPE_I8,
+                    offset: shard.len() as u32,
+                    rows,
+                    cols,
+                };
+                shard.extend_from_slice(bytes);
+            }
+            table.push(refs);
+        }
+        (shard, table)
+    }
+
+    /// Run the full forward pass. Returns (output, hidden state bytes).
+    pub fn run_forward(&self) -> Option<(Vec<i8>, Vec<u8>)> {
+        let (shard, table) = self.shard_and_table();
+        let in_scales: Vec<&[u16]> = self.layers.iter().map(|l| &l.in_scales[..]).collect();
+        let out_scales: Vec<&[u16]> = self.layers.iter().map(|l| &l.out_scales[..]).collect();
+
+        let mut hidden = HiddenBuf::from_bytes(self.hidden_dtype, &self.hidden);
+        let output = inference::forward_pass(
+            &self.input,
+            hidden.as_mut(),
+            &[&shard[..]],
+            &table,
+            None,
+            &[],
+            &self.luts,
+            &self.config,
+            &in_scales,
+            &out_scales,
+            &mut [],
+            &mut [],
+        )?;
+        Some((output, hidden.to_bytes()))
+    }
+
+    /// Run layer 0 alone through `mamba2_layer_step`.
+    pub fn run_layer_step(&self) -> Option<(Vec<i8>, Vec<u8>)> {
+        let (shard, table) = self.shard_and_table();
+        let layer = self.layers.first()?;
+        let weights = inference::layer_weights_from_table(
+            &[&shard[..]],
+            table.first()?,
+            &layer.in_scales,
+            &layer.out_scales,
+            &self.config,
+            inference::LayerKind::Ssm,
+        )?;
+
+        let mut x = self.input.clone();
+        let mut hidden = HiddenBuf::from_bytes(self.hidden_dtype, &self.hidden);
+        let mut arena = ScratchArena::new(self.config.d_model, self.config.d_inner);
+        inference::mamba2_layer_step(
+            &mut x,
+            hidden.as_mut(),
+            &weights,
+            &self.luts,
+            &self.config,
+            &mut arena.buffers(),
+        );
+        Some((x, hidden.to_bytes()))
+    }
+
+    /// Largest per-element difference of (output, hidden) from the
+    /// expected values.
+    pub fn max_drift(&self, output: &[i8], hidden: &[u8]) -> (u32, u32) {
+        let out = output
+            .iter()
+            .zip(&self.expected_output)
+            .map(|(&a, &b)| (a as i32 - b as i32).unsigned_abs())
+            .max()
+            .unwrap_or(0);
+        let got = HiddenBuf::from_bytes(self.hidden_dtype, hidden);
+        let want = HiddenBuf::from_bytes(self.hidden_dtype, &self.expected_hidden);
+        (out, got.max_diff(&want))
+    }
+}
+
+/// Owned hidden state in its element type.
+enum HiddenBuf {
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+}
+
+impl HiddenBuf {
+    fn from_bytes(dtype: u8, bytes: &[u8]) -> Self {
+        if dtype == HIDDEN_DTYPE_I16 {
+            HiddenBuf::I16(
+                bytes
+                    .chunks_exact(2)
+                    .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                    .collect(),
+            )
+        } else {
+            HiddenBuf::I8(bytes.iter().map(|&b| b as i8).collect())
+        }
+    }
+
+    fn as_mut(&mut self) -> HiddenStateMut<'_> {
+        match self {
+            HiddenBuf::I8(h) => HiddenStateMut::I8(h),
+            HiddenBuf::I16(h) => HiddenStateMut::I16(h),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            HiddenBuf::I8(h) => h.iter().map(|&v| v as u8).collect(),
+            HiddenBuf::I16(h) => h.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        }
+    }
+
+    fn max_diff(&self, other: &HiddenBuf) -> u32 {
+        match (self, other) {
+            (HiddenBuf::I8(a), HiddenBuf::I8(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(&x, &y)| (x as i32 - y as i32).unsigned_abs())
+                .max()
+                .unwrap_or(0),
+            (HiddenBuf::I16(a), HiddenBuf::I16(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(&x, &y)| (x as i32 - y as i32).unsigned_abs())
+                .max()
+                .unwrap_or(0),
+            _ => u32::MAX,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{pattern, test_luts};
+    use crate::state::HIDDEN_DTYPE_I8;
+    use std::path::PathBuf;
+
+    /// Small deterministic single-layer case whose expected values are the
+    /// kernel's own output — exercises the harness without a fixture.
+    fn synthetic_case() -> GoldenCase {
+        let (d_model, d_inner, d_state) = (8usize, 16usize, 4usize);
+        let mut case = GoldenCase {
+            config: Mamba2Config {
+                d_model,
+                d_inner,
+                d_state,
+                num_layers: 1,
+                num_heads: 1,
+                ngroups: 0,
+                dt_shift: 0,
+                rounding: Rounding::Truncate,
+            },
+            hidden_dtype: HIDDEN_DTYPE_I8,
+            tolerance: 0,
+            luts: test_luts()[..LUT_V1_SIZE].to_vec(),
+            layers: vec![GoldenLayer {
+                in_proj: pattern(2 * d_inner * d_model, 7),
+                out_proj: pattern(d_model * d_inner, 11),
+                norm: vec![64; d_model],
+                a_log: pattern(d_inner, 3),
+                dt_bias: pattern(d_inner, 5),
+                in_scales: vec![256; 2 * d_inner],
+                out_scales: vec![256; d_model],
+            }],
+            input: (0..d_model as i8).map(|i| i * 9 - 30).collect(),
+            hidden: vec![0; d_inner * d_state],
+            expected_output: vec![],
+            expected_hidden: vec![],
+        };
+        let (out, hidden) = case.run_forward().unwrap();
+        case.expected_output = out;
+        case.expected_hidden = hidden;
+        case
+    }
+
+    fn fixture_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+    }
+
+    #[test]
+    fn test_golden_format_roundtrip() {
+        let case = synthetic_case();
+        let bytes = case.to_bytes();
+        let parsed = GoldenCase::parse(&bytes).unwrap();
+        assert_eq!(parsed.to_bytes(), bytes);
+
+        // Truncated or trailing bytes are rejected
+        assert!(GoldenCase::parse(&bytes[..bytes.len() - 1]).is_none());
+        let mut long = bytes.clone();
+        long.push(0);
+        assert!(GoldenCase::parse(&long).is_none());
+    }
+
+    #[test]
+    fn test_golden_layer_step_matches_forward_pass() {
+        let case = synthetic_case();
+        let (out, hidden) = case.run_layer_step().unwrap();
+        assert_eq!(case.max_drift(&out, &hidden), (0, 0));
+    }
+
+    #[test]
+    fn test_golden_fixtures() {
+        let entries = std::fs::read_dir(fixture_dir())
+            .unwrap_or_else(|e| panic!("{}: {}", fixture_dir().display(), e));
+
+        let mut checked = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("awmg") {
+                continue;
+            }
+            let data = std::fs::read(&path).unwrap();
+            let case = GoldenCase::parse(&data)
+                .unwrap_or_else(|| panic!("{}: malformed fixture", path.display()));
+            let tol = case.tolerance as u32;
+
+            let (out, hidden) = case
+                .run_forward()
+                .unwrap_or_else(|| panic!("{}: tensor shapes don't fit config", path.display()));
+            let (out_drift, h_drift) = case.max_drift(&out, &hidden);
+            assert!(
+                out_drift <= tol && h_drift <= tol,
+                "{}: forward_pass drift output={} hidden={} > tolerance {}",
+                path.display(), out_drift, h_drift, tol
+            );
+
+            if case.config.num_layers == 1 {
+                let (out, hidden) = case.run_layer_step().unwrap();
+                let (out_drift, h_drift) = case.max_drift(&out, &hidden);
+                assert!(
+                    out_drift <= tol && h_drift <= tol,
+                    "{}: layer step drift output={} hidden={} > tolerance {}",
+                    path.display(), out_drift, h_drift, tol
+                );
+            }
+            checked += 1;
+        }
+        assert!(checked > 0, "no .awmg fixtures in {}", fixture_dir().display());
+    }
+}
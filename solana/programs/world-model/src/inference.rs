@@ -1,202 +1,434 @@
-/// Mamba2 INT8 inference kernel for onchain execution.
-///
-/// Implements a single-step (autoregressive) Mamba2 forward pass:
-///   (input_state, controller_inputs, hidden_state) → (output_state, new_hidden_state)
-///
-/// Architecture (per layer):
-///   1. RMSNorm(x)
-///   2. in_proj: x → [z, x_ssm, B, C, dt]    (INT8 matmul)
-///   3. Selective scan step:
-///      dt = softplus(dt)                       (LUT)
-///      A_bar = exp(-dt * A)                    (LUT)
-///      h_new = A_bar * h + dt * B * x_ssm     (INT8/INT32 MAC)
-///      y = C * h_new                           (INT8 dot product)
-///   4. Gate: y = y * SiLU(z)                  (LUT + multiply)
-///   5. out_proj: y → residual                 (INT8 matmul)
-///   6. Residual add                           (INT32 add, requantize)
-///
-/// Per-layer CU estimate (d_model=512, d_inner=1024, d_state=16):
-///   in_proj:  ~3.1M CU
-///   SSM step: ~147K CU
-///   gate:     ~5K CU
-///   out_proj: ~1.6M CU
-///   total:    ~4.9M CU per layer, ~59M CU for 12 layers
-
-use crate::lut;
-use crate::matmul;
-use crate::ssm;
-
-/// Configuration for a Mamba2 model, matching ModelManifest fields.
-pub struct Mamba2Config {
-    pub d_model: usize,
-    pub d_inner: usize,
-    pub d_state: usize,
-    pub num_layers: usize,
-    pub num_heads: usize,
+//! Model-specific glue around the shared awm-kernel Mamba2 layers.
+//!
+//! The layer math (RMSNorm → in_proj → selective scan → gate → out_proj)
+//! lives in `awm_kernel::mamba2`. This module binds it to the world-model
+//! manifest: resolving layer weights by name through the shards' tensor
+//! directories or the manifest tensor table, and encoding/decoding game
+//! state with the manifest's encoder spec.
+
+use crate::state::{
+    ControllerInput, EncoderSpec, ExpertTableAccount, FieldSpec, ModelManifestAccount,
+    PlayerState, TensorRef, EXPERT_IN_PROJ, EXPERT_OUT_PROJ,
+    FIELD_ACTION_STATE, FIELD_BUTTONS, FIELD_CHARACTER, FIELD_C_STICK_X, FIELD_C_STICK_Y,
+    FIELD_FACING, FIELD_HITLAG, FIELD_JUMPS_LEFT, FIELD_KIND_BINARY, FIELD_KIND_CATEGORICAL,
+    FIELD_KIND_HASHED, FIELD_ON_GROUND, FIELD_PERCENT, FIELD_SHIELD, FIELD_SPEED_AIR_X,
+    FIELD_SPEED_ATTACK_X, FIELD_SPEED_ATTACK_Y,
+    FIELD_SPEED_GROUND_X, FIELD_SPEED_Y, FIELD_STAGE, FIELD_STAGE_BLAST_BOTTOM,
+    FIELD_STAGE_BLAST_TOP, FIELD_STAGE_BLAST_X, FIELD_STAGE_HALF_WIDTH, FIELD_STATE_AGE,
+    FIELD_STICK_X,
+    FIELD_STICK_Y, FIELD_STOCKS, FIELD_TRIGGER_L, FIELD_TRIGGER_R, FIELD_X, FIELD_Y,
+    HASHED_PROBES, HIDDEN_DTYPE_I16, MAX_PLAYER_FIELDS, TENSORS_PER_LAYER, TENSOR_A_LOG,
+    TENSOR_DTYPE_TIED,
+    TENSOR_DT_BIAS, TENSOR_DT_PROJ, TENSOR_IN_PROJ, TENSOR_NAMES, TENSOR_NORM, TENSOR_OUT_PROJ,
+};
+use crate::stages::{self, Stage, RESPAWN_Y};
+use awm_kernel::{lut, matmul};
+use awm_kernel::tensor_dir::{self, TensorDirectory};
+pub use awm_kernel::matmul::Rounding;
+pub use awm_kernel::mamba2::{
+    dt_shift_for, forward_layers, mamba2_layer_step, ActivationRange, ExpertWeights,
+    HiddenStateMut, LayerKind, LayerWeights, Mamba2Config, MoeWeights, ScratchArena,
+    ScratchBuffers, CU_PER_MAC, CU_PER_SCAN_ELEM, MAX_EXPERTS,
+};
+
+/// Look up one layer's tensors through the manifest tensor table,
+/// checking each against the shape the config expects for a layer of
+/// `kind`. Attention layers' in_proj is [q, k, v] (3*d_inner rows) and
+/// their A_log/dt_bias/dt_proj slots are unused. An SSM layer whose dt_proj
+/// slot is unset (rows = 0) derives dt from x_ssm. Masked projections
+/// (TENSOR_DTYPE_I8_MASKED) bring their row sparsity masks along.
+pub fn layer_weights_from_table<'a>(
+    shards: &[&'a [u8]],
+    tensors: &[TensorRef; TENSORS_PER_LAYER],
+    in_proj_scales: &'a [u16],
+    out_proj_scales: &'a [u16],
+    config: &Mamba2Config,
+    kind: LayerKind,
+) -> Option<LayerWeights<'a>> {
+    let d_model = config.d_model;
+    let d_inner = config.d_inner;
+    let get = |slot: usize, rows, cols| resolve_shaped(shards, &tensors[slot], rows, cols);
+
+    let (in_proj, a_log, dt_bias, dt_proj) = match kind {
+        LayerKind::Ssm => (
+            get(TENSOR_IN_PROJ, config.in_proj_rows(), d_model)?,
+            get(TENSOR_A_LOG, d_inner, 1)?,
+            get(TENSOR_DT_BIAS, d_inner, 1)?,
+            if tensors[TENSOR_DT_PROJ].rows == 0 {
+                &[][..]
+            } else {
+                get(TENSOR_DT_PROJ, config.dt_heads(), d_model)?
+            },
+        ),
+        LayerKind::Attention { .. } => (
+            get(TENSOR_IN_PROJ, 3 * d_inner, d_model)?,
+            &[][..],
+            &[][..],
+            &[][..],
+        ),
+    };
+    // dt_proj is exported as the dt rows of the trained in_proj, so it
+    // requantizes at the in_proj scale
+    let dt_proj_scales = if dt_proj.is_empty() {
+        &[][..]
+    } else {
+        in_proj_scales.get(..config.dt_heads())?
+    };
+    Some(LayerWeights {
+        in_proj,
+        out_proj: get(TENSOR_OUT_PROJ, d_model, d_inner)?,
+        in_proj_mask: tensors[TENSOR_IN_PROJ].resolve_mask(shards)?,
+        out_proj_mask: tensors[TENSOR_OUT_PROJ].resolve_mask(shards)?,
+        norm: get(TENSOR_NORM, d_model, 1)?,
+        a_log,
+        dt_bias,
+        dt_proj,
+        dt_proj_scales,
+        in_proj_scales,
+        out_proj_scales,
+        moe: None,
+        kind,
+    })
 }
 
-/// Weight layout offsets within a shard.
-/// These are computed from the manifest and used to index into weight account data.
-pub struct LayerWeights<'a> {
-    /// in_proj weight: (2*d_inner, d_model) — maps input to [z, x_ssm]
-    pub in_proj: &'a [u8],
-    /// out_proj weight: (d_model, d_inner) — maps gated output back to residual
-    pub out_proj: &'a [u8],
-    /// RMSNorm weight: (d_model,)
-    pub norm: &'a [u8],
-    /// A_log diagonal: (d_inner,) — log of SSM decay matrix
-    pub a_log: &'a [u8],
-    /// dt bias: (d_inner,) — timestep bias
-    pub dt_bias: &'a [u8],
-    /// Per-channel requantization scales for in_proj output
-    pub in_proj_scales: &'a [u16],
-    /// Per-channel requantization scales for out_proj output
-    pub out_proj_scales: &'a [u16],
+/// One layer's expert routing from the expert table, checking the router
+/// and every expert against the shapes the config expects. Some(None) for
+/// a layer the table leaves dense (router rows = 0); None if a ref doesn't
+/// resolve.
+pub fn moe_weights_from_table<'a>(
+    shards: &[&'a [u8]],
+    table: &ExpertTableAccount,
+    layer: usize,
+    config: &Mamba2Config,
+) -> Option<Option<MoeWeights<'a>>> {
+    let d_model = config.d_model;
+    let d_inner = config.d_inner;
+    let router = table.routers.get(layer)?;
+    if router.rows == 0 {
+        return Some(None);
+    }
+
+    let refs = table.layer_experts(layer)?;
+    if refs.len() > MAX_EXPERTS {
+        return None;
+    }
+    let mut experts = [ExpertWeights::default(); MAX_EXPERTS];
+    for (slot, r) in experts.iter_mut().zip(refs) {
+        *slot = ExpertWeights {
+            in_proj: resolve_shaped(shards, &r[EXPERT_IN_PROJ], config.in_proj_rows(), d_model)?,
+            out_proj: resolve_shaped(shards, &r[EXPERT_OUT_PROJ], d_model, d_inner)?,
+        };
+    }
+
+    Some(Some(MoeWeights {
+        router: resolve_shaped(shards, router, refs.len(), d_model)?,
+        experts,
+        num_experts: refs.len(),
+    }))
 }
 
-/// Scratch buffers for intermediate computations within a layer.
-/// Allocated once and reused across layers to avoid per-layer allocation.
-pub struct ScratchBuffers {
-    /// Normalized input: (d_model,)
-    pub x_norm: Vec<i8>,
-    /// in_proj output before split: (2*d_inner,) as INT32
-    pub proj_i32: Vec<i32>,
-    /// z (gate input): (d_inner,)
-    pub z: Vec<i8>,
-    /// x_ssm (SSM input): (d_inner,)
-    pub x_ssm: Vec<i8>,
-    /// dt after softplus: (d_inner,)
-    pub dt: Vec<i8>,
-    /// SSM output: (d_inner,)
-    pub y_ssm: Vec<i8>,
-    /// Gate output (SiLU(z)): (d_inner,)
-    pub gate: Vec<i8>,
-    /// Gated output: (d_inner,)
-    pub y_gated: Vec<i8>,
-    /// out_proj output as INT32: (d_model,)
-    pub out_i32: Vec<i32>,
-    /// Layer output: (d_model,)
-    pub y_out: Vec<i8>,
+/// Parsed named-tensor directory of each shard: None for plain shards and
+/// for directories that don't parse (finalize_weights rejects those).
+pub fn shard_directories<'a>(shards: &[&'a [u8]]) -> Vec<Option<TensorDirectory<'a>>> {
+    shards.iter().map(|s| TensorDirectory::parse(s).ok().flatten()).collect()
 }
 
-impl ScratchBuffers {
-    pub fn new(d_model: usize, d_inner: usize) -> Self {
-        Self {
-            x_norm: vec![0i8; d_model],
-            proj_i32: vec![0i32; 2 * d_inner],
-            z: vec![0i8; d_inner],
-            x_ssm: vec![0i8; d_inner],
-            dt: vec![0i8; d_inner],
-            y_ssm: vec![0i8; d_inner],
-            gate: vec![0i8; d_inner],
-            y_gated: vec![0i8; d_inner],
-            out_i32: vec![0i32; d_model],
-            y_out: vec![0i8; d_model],
-        }
+/// One layer's tensor refs, looked up by name hash in the shard directories
+/// (`layers.{layer}.{TENSOR_NAMES[slot]}`). A slot no directory names keeps
+/// its manifest tensor table entry, so plain shards resolve as before.
+pub fn named_layer_tensors(
+    directories: &[Option<TensorDirectory>],
+    layer: usize,
+    table: &[TensorRef; TENSORS_PER_LAYER],
+) -> [TensorRef; TENSORS_PER_LAYER] {
+    core::array::from_fn(|slot| {
+        let key = tensor_dir::layer_tensor_hash(layer, TENSOR_NAMES[slot]);
+        directories
+            .iter()
+            .enumerate()
+            .find_map(|(shard, dir)| {
+                let e = dir.as_ref()?.find(key)?;
+                Some(TensorRef {
+                    shard: shard as u8,
+                    dtype: e.dtype,
+                    offset: e.offset,
+                    rows: e.rows,
+                    cols: e.cols,
+                })
+            })
+            .unwrap_or(table[slot])
+    })
+}
+
+/// A tensor's bytes, if it has shape (rows, cols) and lies within the shards.
+fn resolve_shaped<'a>(
+    shards: &[&'a [u8]],
+    t: &TensorRef,
+    rows: usize,
+    cols: usize,
+) -> Option<&'a [u8]> {
+    if t.rows as usize != rows || t.cols as usize != cols {
+        return None;
     }
+    t.resolve(shards)
 }
 
-/// Execute one Mamba2 layer (single timestep, single layer).
-///
-/// This is the core inner loop called num_layers times per frame.
-pub fn mamba2_layer_step(
-    x: &mut [i8],
-    h: &mut [i8],
-    weights: &LayerWeights,
-    lut_data: &[u8],
-    config: &Mamba2Config,
-    scratch: &mut ScratchBuffers,
-) {
-    let d_model = config.d_model;
-    let d_inner = config.d_inner;
+/// Head logits are products of two INT8 factors; shifting by 7 brings
+/// their gaps back to the slot scale the one-hot decode's softmax sees.
+const ACTION_LOGIT_SHIFT: u32 = 7;
+
+/// A model's action-state embedding and logit head, standing in for the
+/// CATEGORICAL action field's one-hot slots.
+#[derive(Clone, Copy, Debug)]
+pub struct ActionTensors<'a> {
+    /// [width × classes]: column c is the input slots of class c
+    pub embedding: &'a [u8],
+    /// [classes × width], or None when tied to `embedding`
+    pub head: Option<&'a [u8]>,
+    /// Slots of the action field
+    pub width: usize,
+    pub classes: usize,
+}
 
-    // ── Step 1: RMSNorm ─────────────────────────────────────────────────
-    lut::rmsnorm_int8(
-        lut_data,
-        x,
-        // Reinterpret norm weights as i8
-        unsafe { core::slice::from_raw_parts(weights.norm.as_ptr() as *const i8, d_model) },
-        &mut scratch.x_norm,
-        256, // weight_scale
-    );
+impl ActionTensors<'_> {
+    /// Write class `class`'s embedding column to `slots` (zeros for a class
+    /// past the table).
+    fn embed(&self, class: i32, slots: &mut [i8]) {
+        let class = usize::try_from(class).ok().filter(|&c| c < self.classes);
+        for (r, slot) in slots.iter_mut().take(self.width).enumerate() {
+            *slot = class.map_or(0, |c| self.embedding[r * self.classes + c] as i8);
+        }
+    }
 
-    // ── Step 2: in_proj matmul ──────────────────────────────────────────
-    matmul::matmul_i8(
-        weights.in_proj,
-        &scratch.x_norm,
-        &mut scratch.proj_i32,
-        2 * d_inner,
-        d_model,
-    );
+    /// Every class's logit for the action field's output `slots` (`width`
+    /// long): head × slots, or embeddingᵀ × slots when tied.
+    fn logits(&self, slots: &[i8]) -> Vec<i32> {
+        let mut logits = vec![0i32; self.classes];
+        match self.head {
+            Some(head) => matmul::matmul_i8(head, slots, &mut logits, self.classes, self.width),
+            None => matmul::matmul_i8_transposed(
+                self.embedding,
+                slots,
+                &mut logits,
+                self.width,
+                self.classes,
+            ),
+        }
+        logits
+    }
+}
 
-    // Requantize and split into z and x_ssm
-    let mut proj_i8 = vec![0i8; 2 * d_inner];
-    matmul::requantize_per_channel(
-        &scratch.proj_i32,
-        weights.in_proj_scales,
-        &mut proj_i8,
-        2 * d_inner,
-    );
+/// Resolve the manifest's action embedding and logit head against the
+/// shards. Some(None) for a one-hot model (embedding rows = 0); None if a
+/// ref doesn't resolve or the spec has no CATEGORICAL action field.
+pub fn action_tensors_from_manifest<'a>(
+    manifest: &ModelManifestAccount,
+    shards: &[&'a [u8]],
+) -> Option<Option<ActionTensors<'a>>> {
+    let embedding = &manifest.action_embedding;
+    if embedding.rows == 0 {
+        return Some(None);
+    }
+    let width = manifest.encoder_spec.action_field()?.width as usize;
+    let classes = embedding.cols as usize;
+    let head = match manifest.action_head.dtype {
+        TENSOR_DTYPE_TIED => None,
+        _ => Some(resolve_shaped(shards, &manifest.action_head, classes, width)?),
+    };
+    Some(Some(ActionTensors {
+        embedding: resolve_shaped(shards, embedding, width, classes)?,
+        head,
+        width,
+        classes,
+    }))
+}
 
-    scratch.z.copy_from_slice(&proj_i8[..d_inner]);
-    scratch.x_ssm.copy_from_slice(&proj_i8[d_inner..2 * d_inner]);
+/// Raw (unquantized) value of a per-player encoder source.
+/// Returns None for global sources.
+fn player_field_value(
+    p: &crate::state::PlayerState,
+    c: &crate::state::ControllerInput,
+    source: u8,
+) -> Option<i32> {
+    let v = match source {
+        FIELD_X => p.x,
+        FIELD_Y => p.y,
+        FIELD_PERCENT => p.percent as i32,
+        FIELD_SHIELD => p.shield_strength as i32,
+        FIELD_SPEED_AIR_X => p.speed_air_x as i32,
+        FIELD_SPEED_Y => p.speed_y as i32,
+        FIELD_SPEED_GROUND_X => p.speed_ground_x as i32,
+        FIELD_SPEED_ATTACK_X => p.speed_attack_x as i32,
+        FIELD_SPEED_ATTACK_Y => p.speed_attack_y as i32,
+        FIELD_STATE_AGE => p.state_age as i32,
+        FIELD_HITLAG => p.hitlag as i32,
+        FIELD_STOCKS => p.stocks as i32,
+        FIELD_FACING => p.facing as i32,
+        FIELD_ON_GROUND => p.on_ground as i32,
+        FIELD_ACTION_STATE => p.action_state as i32,
+        FIELD_JUMPS_LEFT => p.jumps_left as i32,
+        FIELD_CHARACTER => p.character as i32,
+        FIELD_STICK_X => c.stick_x as i32,
+        FIELD_STICK_Y => c.stick_y as i32,
+        FIELD_C_STICK_X => c.c_stick_x as i32,
+        FIELD_C_STICK_Y => c.c_stick_y as i32,
+        FIELD_TRIGGER_L => c.trigger_l as i32,
+        FIELD_TRIGGER_R => c.trigger_r as i32,
+        FIELD_BUTTONS => c.buttons as i32,
+        _ => return None,
+    };
+    Some(v)
+}
 
-    // ── Step 3: Selective scan step ─────────────────────────────────────
-    // dt = softplus(x_ssm + dt_bias)
-    for i in 0..d_inner {
-        let dt_raw = (scratch.x_ssm[i] as i16 + weights.dt_bias[i] as i8 as i16)
-            .clamp(-128, 127) as i8;
-        scratch.dt[i] = lut::softplus_lut(lut_data, dt_raw);
+/// Store `v` into the decodable player field named by `source`, clamped
+/// to its type. Other sources are ignored.
+fn set_player_field(p: &mut PlayerState, source: u8, v: i32) {
+    let u8v = v.clamp(0, u8::MAX as i32) as u8;
+    let u16v = v.clamp(0, u16::MAX as i32) as u16;
+    let i16v = v.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    match source {
+        FIELD_X => p.x = v,
+        FIELD_Y => p.y = v,
+        FIELD_PERCENT => p.percent = u16v,
+        FIELD_SHIELD => p.shield_strength = u16v,
+        FIELD_SPEED_AIR_X => p.speed_air_x = i16v,
+        FIELD_SPEED_Y => p.speed_y = i16v,
+        FIELD_SPEED_GROUND_X => p.speed_ground_x = i16v,
+        FIELD_SPEED_ATTACK_X => p.speed_attack_x = i16v,
+        FIELD_SPEED_ATTACK_Y => p.speed_attack_y = i16v,
+        FIELD_STATE_AGE => p.state_age = u16v,
+        FIELD_HITLAG => p.hitlag = u8v,
+        FIELD_STOCKS => p.stocks = u8v,
+        FIELD_FACING => p.facing = u8v,
+        FIELD_ON_GROUND => p.on_ground = u8v,
+        FIELD_JUMPS_LEFT => p.jumps_left = u8v,
+        _ => {}
     }
+}
 
-    ssm::selective_scan_step(
-        &scratch.x_ssm,
-        &scratch.dt,
-        h,
-        weights.a_log,
-        lut_data,
-        &mut scratch.y_ssm,
-        config.d_inner,
-        config.d_state,
-    );
+/// Output smoothing, between decode and the session write: each player
+/// field with a non-zero weight in `smoothing` (indexed like the spec's
+/// player fields, see `ModelManifestAccount::output_smoothing`) moves from
+/// its `previous` value toward the decoded one, keeping weight/256 of the
+/// previous value — a fixed-point EMA, floored so every node agrees.
+/// Returns the raw decoded values of the smoothed fields, player 1's then
+/// player 2's in spec order (empty when nothing is smoothed).
+pub fn smooth_players(
+    spec: &EncoderSpec,
+    smoothing: &[u8; MAX_PLAYER_FIELDS],
+    previous: &[PlayerState; 2],
+    players: &mut [PlayerState; 2],
+) -> Vec<i32> {
+    let num_player = (spec.num_player_fields as usize).min(MAX_PLAYER_FIELDS);
+    let none = ControllerInput::default();
+    let mut raw = Vec::new();
+    for (p, prev) in players.iter_mut().zip(previous) {
+        for (field, &weight) in spec.player_fields[..num_player].iter().zip(smoothing) {
+            if weight == 0 {
+                continue;
+            }
+            let (Some(value), Some(last)) = (
+                player_field_value(p, &none, field.source),
+                player_field_value(prev, &none, field.source),
+            ) else {
+                continue;
+            };
+            raw.push(value);
+            let delta = (last as i64 - value as i64) * weight as i64;
+            set_player_field(p, field.source, value + (delta >> 8) as i32);
+        }
+    }
+    raw
+}
 
-    // ── Step 4: Gate ────────────────────────────────────────────────────
-    scratch.gate.copy_from_slice(&scratch.z);
-    lut::silu_slice(lut_data, &mut scratch.gate);
+/// Quantize one raw value to INT8 according to its field spec.
+fn quantize_field(field: &FieldSpec, value: i32) -> i8 {
+    match field.kind {
+        FIELD_KIND_BINARY => if value != 0 { 64 } else { -64 },
+        _ => (value / field.divisor.max(1) as i32).clamp(-128, 127) as i8,
+    }
+}
 
-    matmul::elementwise_mul_i8(
-        &scratch.y_ssm,
-        &scratch.gate,
-        &mut scratch.y_gated,
-        d_inner,
-        7, // shift: INT8 * INT8 has ~14 bits, shift 7 to center
-    );
+/// Dequantize one INT8 model output according to its field spec.
+fn dequantize_field(field: &FieldSpec, raw: i8) -> i32 {
+    match field.kind {
+        FIELD_KIND_BINARY => (raw > 0) as i32,
+        _ => raw as i32 * field.divisor as i32,
+    }
+}
 
-    // ── Step 5: out_proj matmul ─────────────────────────────────────────
-    matmul::matmul_i8(
-        weights.out_proj,
-        &scratch.y_gated,
-        &mut scratch.out_i32,
-        d_model,
-        d_inner,
-    );
+/// The HASHED_PROBES distinct slots of a `width`-slot HASHED field that
+/// `class` sets: a multiplicative hash picks the first and a step to the
+/// second. Exporters encoding training data must match it.
+pub fn hashed_slots(class: i32, width: usize) -> [usize; HASHED_PROBES] {
+    let h = (class as u32).wrapping_mul(0x9E37_79B9);
+    let first = (h >> 16) as usize % width;
+    let step = 1 + (h & 0xFFFF) as usize % (width - 1);
+    [first, (first + step) % width]
+}
 
-    matmul::requantize_per_channel(
-        &scratch.out_i32,
-        weights.out_proj_scales,
-        &mut scratch.y_out,
-        d_model,
-    );
+/// Write one field's slots at `offset`, dropping slots past d_model.
+/// CATEGORICAL fields are one-hot over their width, or the class's
+/// embedding for the action field of a model with `action` tensors;
+/// HASHED fields are multi-hot over theirs; the rest take one slot.
+fn encode_field(
+    field: &FieldSpec,
+    value: i32,
+    action: Option<&ActionTensors>,
+    output: &mut [i8],
+    offset: usize,
+    d_model: usize,
+) {
+    if field.kind == FIELD_KIND_CATEGORICAL {
+        let end = (offset + field.width as usize).min(d_model);
+        let slots = output.get_mut(offset..end).unwrap_or_default();
+        match action.filter(|_| field.source == FIELD_ACTION_STATE) {
+            Some(action) => action.embed(value, slots),
+            None => {
+                for (k, slot) in slots.iter_mut().enumerate() {
+                    *slot = if value == k as i32 { 64 } else { -64 };
+                }
+            }
+        }
+    } else if field.kind == FIELD_KIND_HASHED {
+        let width = field.width as usize;
+        let hot = hashed_slots(value, width);
+        let end = (offset + width).min(d_model);
+        for (k, slot) in output.get_mut(offset..end).unwrap_or_default().iter_mut().enumerate() {
+            *slot = if hot.contains(&k) { 64 } else { -64 };
+        }
+    } else if offset < d_model {
+        output[offset] = quantize_field(field, value);
+    }
+}
 
-    // ── Step 6: Residual add ────────────────────────────────────────────
-    matmul::add_i8(x, &scratch.y_out, x, d_model);
+/// Decode a CATEGORICAL field's logits: the most likely class and its Q15
+/// softmax probability, `shift` setting the softmax temperature.
+fn decode_categorical(lut_data: &[u8], logits: &[i32], shift: u32) -> (i32, u16) {
+    let mut probs = vec![0u16; logits.len()];
+    lut::softmax_i32(lut_data, logits, shift, &mut probs);
+    let class = lut::argmax_i32(logits);
+    (class as i32, probs[class])
+}
+
+/// Decode a HASHED field's slots: the class whose slots sum highest, and
+/// its Q15 softmax probability among the field's `classes`.
+fn decode_hashed(lut_data: &[u8], slots: &[i8], classes: usize) -> (i32, u16) {
+    let scores: Vec<i32> = (0..classes)
+        .map(|c| hashed_slots(c as i32, slots.len()).iter().map(|&k| slots[k] as i32).sum())
+        .collect();
+    decode_categorical(lut_data, &scores, 0)
 }
 
 /// Encode game state + controller inputs into model input vector.
 ///
-/// Maps the structured game state plus controller inputs into a flat INT8 vector.
-/// Encoding matches the v2 encoding from nojohns-training.
+/// Maps the structured game state plus controller inputs into a flat INT8
+/// vector laid out by the manifest's encoder spec: player 1 fields, player 2
+/// fields, then global fields. Slots past d_model are dropped. A model with
+/// `action` tensors encodes the action field as its class's embedding.
 pub fn encode_input(
+    spec: &EncoderSpec,
+    action: Option<&ActionTensors>,
     players: &[crate::state::PlayerState; 2],
     controller_inputs: &[crate::state::ControllerInput; 2],
     stage: u8,
@@ -208,71 +440,32 @@ pub fn encode_input(
         *v = 0;
     }
 
+    let d_model = d_model.min(output.len());
+    let player_fields = &spec.player_fields[..spec.num_player_fields as usize];
+    let global_fields = &spec.global_fields[..spec.num_global_fields as usize];
+
     let mut offset = 0;
     for p_idx in 0..2 {
-        let p = &players[p_idx];
-        let c = &controller_inputs[p_idx];
-
-        // Continuous fields (quantized to INT8)
-        if offset < d_model { output[offset] = (p.x / 256).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = (p.y / 256).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = (p.percent as i32 / 4).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = p.shield_strength as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = (p.speed_air_x as i32 / 2).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = (p.speed_y as i32 / 2).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = (p.speed_ground_x as i32 / 2).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = (p.speed_attack_x as i32 / 2).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = (p.speed_attack_y as i32 / 2).clamp(-128, 127) as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = p.state_age as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = p.hitlag as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = p.stocks as i8; }
-        offset += 1;
-
-        // Binary fields
-        if offset < d_model { output[offset] = if p.facing != 0 { 64 } else { -64 }; }
-        offset += 1;
-        if offset < d_model { output[offset] = if p.on_ground != 0 { 64 } else { -64 }; }
-        offset += 1;
-
-        // Categorical
-        if offset < d_model { output[offset] = p.action_state as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = p.jumps_left as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = p.character as i8; }
-        offset += 1;
-
-        // Controller inputs
-        if offset < d_model { output[offset] = c.stick_x; }
-        offset += 1;
-        if offset < d_model { output[offset] = c.stick_y; }
-        offset += 1;
-        if offset < d_model { output[offset] = c.c_stick_x; }
-        offset += 1;
-        if offset < d_model { output[offset] = c.c_stick_y; }
-        offset += 1;
-        if offset < d_model { output[offset] = c.trigger_l as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = c.trigger_r as i8; }
-        offset += 1;
-        if offset < d_model { output[offset] = c.buttons as i8; }
-        offset += 1;
+        for field in player_fields {
+            let value = player_field_value(&players[p_idx], &controller_inputs[p_idx], field.source)
+                .unwrap_or(0);
+            encode_field(field, value, action, output, offset, d_model);
+            offset += field.width as usize;
+        }
     }
 
-    // Stage
-    if offset < d_model {
-        output[offset] = stage as i8;
+    let geometry = stages::stage_or_default(stage);
+    for field in global_fields {
+        let value = match field.source {
+            FIELD_STAGE => stage as i32,
+            FIELD_STAGE_HALF_WIDTH => geometry.half_width,
+            FIELD_STAGE_BLAST_X => geometry.blast.right,
+            FIELD_STAGE_BLAST_TOP => geometry.blast.top,
+            FIELD_STAGE_BLAST_BOTTOM => geometry.blast.bottom,
+            _ => 0,
+        };
+        encode_field(field, value, None, output, offset, d_model);
+        offset += field.width as usize;
     }
 }
 
@@ -293,14 +486,57 @@ pub struct DecodedPlayerState {
     pub facing: u8,
     pub on_ground: u8,
     pub action_state: u16,
+    /// Q15 probability of `action_state` when it decodes as CATEGORICAL
+    /// (`lut::PROB_ONE` otherwise) — for confidence display
+    pub action_state_prob: u16,
     pub jumps_left: u8,
     pub character: u8,
 }
 
+impl DecodedPlayerState {
+    /// Store a dequantized value into the field named by `source`.
+    /// Input-only sources (controller, stage) are ignored.
+    fn set_field(&mut self, source: u8, v: i32) {
+        let u8v = v.clamp(0, u8::MAX as i32) as u8;
+        let u16v = v.clamp(0, u16::MAX as i32) as u16;
+        let i16v = v.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        match source {
+            FIELD_X => self.x = v,
+            FIELD_Y => self.y = v,
+            FIELD_PERCENT => self.percent = u16v,
+            FIELD_SHIELD => self.shield_strength = u16v,
+            FIELD_SPEED_AIR_X => self.speed_air_x = i16v,
+            FIELD_SPEED_Y => self.speed_y = i16v,
+            FIELD_SPEED_GROUND_X => self.speed_ground_x = i16v,
+            FIELD_SPEED_ATTACK_X => self.speed_attack_x = i16v,
+            FIELD_SPEED_ATTACK_Y => self.speed_attack_y = i16v,
+            FIELD_STATE_AGE => self.state_age = u16v,
+            FIELD_HITLAG => self.hitlag = u8v,
+            FIELD_STOCKS => self.stocks = u8v,
+            FIELD_FACING => self.facing = u8v,
+            FIELD_ON_GROUND => self.on_ground = u8v,
+            FIELD_ACTION_STATE => self.action_state = u16v,
+            FIELD_JUMPS_LEFT => self.jumps_left = u8v,
+            FIELD_CHARACTER => self.character = u8v,
+            _ => {}
+        }
+    }
+}
+
 /// Decode model output vector into structured game state.
+///
+/// Walks the same player layout as encode_input from each player's
+/// `offsets` entry (`ModelManifestAccount::output_offsets`, which picks a
+/// multi-headed model's per-character head); controller slots are skipped.
+/// CATEGORICAL and HASHED fields decode to their most likely class via the
+/// exp_neg softmax in `lut_data`; with `action` tensors the action field's
+/// slots go through the logit head first.
 pub fn decode_output(
+    spec: &EncoderSpec,
+    action: Option<&ActionTensors>,
+    lut_data: &[u8],
     model_output: &[i8],
-    _d_model: usize,
+    offsets: [usize; 2],
 ) -> [DecodedPlayerState; 2] {
     let mut players = [
         DecodedPlayerState {
@@ -308,132 +544,316 @@ pub fn decode_output(
             speed_air_x: 0, speed_y: 0, speed_ground_x: 0,
             speed_attack_x: 0, speed_attack_y: 0,
             state_age: 0, hitlag: 0, stocks: 4,
-            facing: 1, on_ground: 1, action_state: 0, jumps_left: 2, character: 0,
+            facing: 1, on_ground: 1, action_state: 0, action_state_prob: lut::PROB_ONE,
+            jumps_left: 2, character: 0,
         },
         DecodedPlayerState {
             x: 0, y: 0, percent: 0, shield_strength: 0,
             speed_air_x: 0, speed_y: 0, speed_ground_x: 0,
             speed_attack_x: 0, speed_attack_y: 0,
             state_age: 0, hitlag: 0, stocks: 4,
-            facing: 0, on_ground: 1, action_state: 0, jumps_left: 2, character: 0,
+            facing: 0, on_ground: 1, action_state: 0, action_state_prob: lut::PROB_ONE,
+            jumps_left: 2, character: 0,
         },
     ];
 
-    let mut offset = 0;
-    for p_idx in 0..2 {
-        let p = &mut players[p_idx];
-
-        // Continuous fields (dequantize from INT8)
-        if offset < model_output.len() { p.x = model_output[offset] as i32 * 256; }
-        offset += 1;
-        if offset < model_output.len() { p.y = model_output[offset] as i32 * 256; }
-        offset += 1;
-        if offset < model_output.len() { p.percent = (model_output[offset] as i16 * 4).max(0) as u16; }
-        offset += 1;
-        if offset < model_output.len() { p.shield_strength = model_output[offset] as u16; }
-        offset += 1;
-        if offset < model_output.len() { p.speed_air_x = model_output[offset] as i16 * 2; }
-        offset += 1;
-        if offset < model_output.len() { p.speed_y = model_output[offset] as i16 * 2; }
-        offset += 1;
-        if offset < model_output.len() { p.speed_ground_x = model_output[offset] as i16 * 2; }
-        offset += 1;
-        if offset < model_output.len() { p.speed_attack_x = model_output[offset] as i16 * 2; }
-        offset += 1;
-        if offset < model_output.len() { p.speed_attack_y = model_output[offset] as i16 * 2; }
-        offset += 1;
-        if offset < model_output.len() { p.state_age = model_output[offset] as u16; }
-        offset += 1;
-        if offset < model_output.len() { p.hitlag = model_output[offset].max(0) as u8; }
-        offset += 1;
-        if offset < model_output.len() { p.stocks = model_output[offset].max(0) as u8; }
-        offset += 1;
-
-        // Binary fields (threshold at 0)
-        if offset < model_output.len() { p.facing = if model_output[offset] > 0 { 1 } else { 0 }; }
-        offset += 1;
-        if offset < model_output.len() { p.on_ground = if model_output[offset] > 0 { 1 } else { 0 }; }
-        offset += 1;
-
-        // Categorical
-        if offset < model_output.len() { p.action_state = model_output[offset].max(0) as u16; }
-        offset += 1;
-        if offset < model_output.len() { p.jumps_left = model_output[offset].max(0) as u8; }
-        offset += 1;
-        if offset < model_output.len() { p.character = model_output[offset].max(0) as u8; }
-        offset += 1;
-
-        // Skip controller input positions in output
-        offset += 7;
+    let player_fields = &spec.player_fields[..spec.num_player_fields as usize];
+
+    for (p, mut offset) in players.iter_mut().zip(offsets) {
+        for field in player_fields {
+            let width = field.width as usize;
+            if field.kind == FIELD_KIND_CATEGORICAL || field.kind == FIELD_KIND_HASHED {
+                let slots = model_output.get(offset..offset + width).unwrap_or(&[]);
+                if !slots.is_empty() {
+                    let (class, prob) = if field.kind == FIELD_KIND_HASHED {
+                        decode_hashed(lut_data, slots, field.divisor as usize)
+                    } else {
+                        let (logits, shift) =
+                            match action.filter(|_| field.source == FIELD_ACTION_STATE) {
+                                Some(action) => (action.logits(slots), ACTION_LOGIT_SHIFT),
+                                None => (slots.iter().map(|&l| l as i32).collect(), 0),
+                            };
+                        decode_categorical(lut_data, &logits, shift)
+                    };
+                    p.set_field(field.source, class);
+                    if field.source == FIELD_ACTION_STATE {
+                        p.action_state_prob = prob;
+                    }
+                }
+            } else if offset < model_output.len() {
+                p.set_field(field.source, dequantize_field(field, model_output[offset]));
+            }
+            offset += width;
+        }
     }
 
     players
 }
 
-/// Execute the full Mamba2 forward pass: all layers, encode → layers → decode.
+/// Execute the full Mamba2 forward pass over every layer.
 ///
 /// This is the top-level function called by run_inference for each frame.
+/// `weight_data` holds one data region per shard directory entry, in shard
+/// index order — any number of shards. Layer tensors are found by name in
+/// the shards' tensor directories, falling back to the manifest tensor
+/// table for shards without one, and a mixture-of-experts model's routers
+/// and experts through its `expert_table`; returns None if any entry points
+/// outside the shards or has the wrong shape for the config.
+/// `layer_kinds` marks a hybrid model's attention layers (layers past its
+/// end are SSM, so pure Mamba2 callers pass `&[]`). Each layer's selected
+/// expert goes to `routes`, and its accumulator extremes widen `ranges`
+/// (see `forward_layers`).
+#[allow(clippy::too_many_arguments)]
 pub fn forward_pass(
     input: &[i8],
-    hidden_state: &mut [i8],
+    hidden_state: HiddenStateMut,
     weight_data: &[&[u8]],
+    tensor_table: &[[TensorRef; TENSORS_PER_LAYER]],
+    expert_table: Option<&ExpertTableAccount>,
+    layer_kinds: &[LayerKind],
     lut_data: &[u8],
     config: &Mamba2Config,
     layer_in_scales: &[&[u16]],
     layer_out_scales: &[&[u16]],
-    norm_weights: &[&[u8]],
-    a_logs: &[&[u8]],
-    dt_biases: &[&[u8]],
-) -> Vec<i8> {
-    let d_model = config.d_model;
-    let d_inner = config.d_inner;
-    let d_state = config.d_state;
-    let h_per_layer = d_inner * d_state;
-
-    let mut x = input.to_vec();
-    let mut scratch = ScratchBuffers::new(d_model, d_inner);
-
-    for layer_idx in 0..config.num_layers {
-        let h_offset = layer_idx * h_per_layer;
-        let h_slice = &mut hidden_state[h_offset..h_offset + h_per_layer];
-
-        // Compute weight offsets for this layer
-        let in_proj_size = 2 * d_inner * d_model;
-        let out_proj_size = d_model * d_inner;
-        let layer_weight_offset = layer_idx * (in_proj_size + out_proj_size);
-
-        // Determine which shard this layer's weights are in
-        let shard_idx = if layer_weight_offset < weight_data[0].len() { 0 } else { 1 };
-        let shard = weight_data[shard_idx.min(weight_data.len() - 1)];
-        let offset_in_shard = if shard_idx == 0 {
-            layer_weight_offset
-        } else {
-            layer_weight_offset - weight_data[0].len()
-        };
+    routes: &mut [u8],
+    ranges: &mut [ActivationRange],
+) -> Option<Vec<i8>> {
+    let directories = shard_directories(weight_data);
+    forward_layers(input, hidden_state, lut_data, config, routes, ranges, |layer_idx| {
+        let kind = layer_kinds.get(layer_idx).copied().unwrap_or_default();
+        let tensors = named_layer_tensors(&directories, layer_idx, tensor_table.get(layer_idx)?);
+        let mut weights = layer_weights_from_table(
+            weight_data,
+            &tensors,
+            layer_in_scales.get(layer_idx)?,
+            layer_out_scales.get(layer_idx)?,
+            config,
+            kind,
+        )?;
+        if let (Some(table), LayerKind::Ssm) = (expert_table, kind) {
+            weights.moe = moe_weights_from_table(weight_data, table, layer_idx, config)?;
+        }
+        Some(weights)
+    })
+}
+
+/// Stub dynamics used by run_inference until the forward pass is wired in:
+/// simple physics-like rules that demonstrate the pipeline, on `stage`'s
+/// geometry. Deterministic, so the fraud-proof program can re-execute any
+/// frame from its inputs.
+pub fn step_players(
+    players: &mut [crate::state::PlayerState; 2],
+    inputs: &[crate::state::ControllerInput; 2],
+    stage: u8,
+) {
+    let geometry = stages::stage_or_default(stage);
+    let stocks_before = players.map(|p| p.stocks);
+    for (p, input) in players.iter_mut().zip(inputs.iter()) {
+        // Apply stick input as velocity (simplified physics)
+        let stick_x = input.stick_x as i32;
+        let stick_y = input.stick_y as i32;
+
+        p.x += stick_x * 2;
+        p.y += stick_y * 2;
+
+        // Walking off a ledge or platform edge
+        if p.on_ground != 0 && !geometry.supports(p.x, p.y) {
+            p.on_ground = 0;
+        }
 
-        let in_proj_end = (offset_in_shard + in_proj_size).min(shard.len());
-        let out_proj_start = in_proj_end;
-        let out_proj_end = (out_proj_start + out_proj_size).min(shard.len());
-
-        let weights = LayerWeights {
-            in_proj: &shard[offset_in_shard..in_proj_end],
-            out_proj: &shard[out_proj_start..out_proj_end],
-            norm: norm_weights.get(layer_idx).copied().unwrap_or(&[]),
-            a_log: a_logs.get(layer_idx).copied().unwrap_or(&[]),
-            dt_bias: dt_biases.get(layer_idx).copied().unwrap_or(&[]),
-            in_proj_scales: layer_in_scales.get(layer_idx).copied().unwrap_or(&[]),
-            out_proj_scales: layer_out_scales.get(layer_idx).copied().unwrap_or(&[]),
+        // Gravity if airborne
+        if p.on_ground == 0 {
+            let prev_y = p.y;
+            p.speed_y -= 4;
+            p.y += p.speed_y as i32;
+
+            if let Some(surface) = geometry.landing(p.x, prev_y, p.y) {
+                p.y = surface;
+                p.speed_y = 0;
+                p.on_ground = 1;
+            }
+        }
+
+        // Jump (button A = bit 0)
+        if input.buttons & 0x01 != 0 && p.jumps_left > 0 {
+            p.speed_y = 40;
+            p.on_ground = 0;
+            p.jumps_left = p.jumps_left.saturating_sub(1);
+        }
+
+        // Facing direction
+        if stick_x > 10 {
+            p.facing = 1;
+        } else if stick_x < -10 {
+            p.facing = 0;
+        }
+
+        p.speed_ground_x = (stick_x * 2).clamp(-32767, 32767) as i16;
+        p.state_age = p.state_age.saturating_add(1);
+    }
+    enforce_blast_zones(players, stocks_before, geometry);
+}
+
+/// Take a stock from each player past one of `stage`'s blast zones and
+/// respawn them above the stage. A player whose stocks already dropped
+/// this frame (below `stocks_before`, e.g. a model that decodes its own
+/// KOs) keeps them and only respawns. Returns which players were sent
+/// back.
+pub fn enforce_blast_zones(
+    players: &mut [PlayerState; 2],
+    stocks_before: [u8; 2],
+    stage: &Stage,
+) -> [bool; 2] {
+    let mut koed = [false; 2];
+    for (slot, p) in players.iter_mut().enumerate() {
+        if p.stocks == 0 || !stage.outside_blast_zone(p.x, p.y) {
+            continue;
+        }
+        if p.stocks >= stocks_before[slot] {
+            p.stocks = stocks_before[slot].saturating_sub(1);
+        }
+        *p = PlayerState {
+            x: 0,
+            y: RESPAWN_Y,
+            facing: p.facing,
+            jumps_left: 2,
+            shield_strength: 60 * 256,
+            stocks: p.stocks,
+            character: p.character,
+            ..Default::default()
         };
+        koed[slot] = true;
+    }
+    koed
+}
 
-        mamba2_layer_step(
-            &mut x,
-            h_slice,
-            &weights,
-            lut_data,
-            config,
-            &mut scratch,
-        );
+/// Each layer's kind under the manifest's attention_layers.
+pub fn manifest_layer_kinds(manifest: &ModelManifestAccount) -> Vec<LayerKind> {
+    (0..manifest.num_layers as usize).map(|l| manifest.layer_kind(l)).collect()
+}
+
+/// Kernel config for the manifest's architecture, at the native tick rate.
+pub fn manifest_config(manifest: &ModelManifestAccount) -> Mamba2Config {
+    Mamba2Config {
+        d_model: manifest.d_model as usize,
+        d_inner: manifest.d_inner as usize,
+        d_state: manifest.d_state as usize,
+        num_layers: manifest.num_layers as usize,
+        num_heads: manifest.num_heads as usize,
+        ngroups: manifest.ngroups as usize,
+        dt_shift: 0,
+        rounding: Rounding::Truncate,
     }
+}
+
+/// View a hidden state data region (past the header) in its stored element
+/// type. None if an INT16 region is misaligned or has an odd length.
+pub fn hidden_state_mut(dtype: u8, data: &mut [u8]) -> Option<HiddenStateMut<'_>> {
+    if dtype == HIDDEN_DTYPE_I16 {
+        bytemuck::try_cast_slice_mut(data).ok().map(HiddenStateMut::I16)
+    } else {
+        Some(HiddenStateMut::I8(bytemuck::cast_slice_mut(data)))
+    }
+}
 
-    x
+/// Real dynamics used by run_inference under the `mamba2-inference`
+/// feature: encode both players with the manifest's encoder spec, run every
+/// layer, and decode the output back into player state. Characters carry
+/// over, since no model output can change them.
+///
+/// `shards` are the shard data regions in directory order. The manifest
+/// stores one requantization scale per layer, broadcast across channels.
+/// The scan's dt is stretched to `tick_rate` (frames per second).
+/// Hybrid models run their attention layers (`manifest.layer_kind`) over
+/// KV buffers in the hidden state, and mixture-of-experts models route
+/// through `expert_table`. Stochastic requantization (`requant_mode`) draws
+/// from the frame's RNG stream `frame_seed`. Models with an action
+/// embedding and logit head encode and decode the action field through
+/// them. Each layer's accumulator extremes widen `ranges` (`&mut []` when
+/// not calibrating). Returns each layer's selected expert, or None if the
+/// tensor or expert table doesn't resolve against the shards.
+#[allow(clippy::too_many_arguments)]
+pub fn step_players_mamba2(
+    manifest: &ModelManifestAccount,
+    players: &mut [PlayerState; 2],
+    inputs: &[ControllerInput; 2],
+    stage: u8,
+    tick_rate: u32,
+    hidden_state: HiddenStateMut,
+    shards: &[&[u8]],
+    expert_table: Option<&ExpertTableAccount>,
+    frame_seed: u64,
+    ranges: &mut [ActivationRange],
+) -> Option<Vec<u8>> {
+    let config = Mamba2Config {
+        dt_shift: dt_shift_for(tick_rate),
+        rounding: manifest.rounding(frame_seed),
+        ..manifest_config(manifest)
+    };
+    let layers = config.num_layers;
+
+    let action = action_tensors_from_manifest(manifest, shards)?;
+    let mut input = vec![0i8; config.d_model];
+    encode_input(
+        &manifest.encoder_spec,
+        action.as_ref(),
+        players,
+        inputs,
+        stage,
+        &mut input,
+        config.d_model,
+    );
+
+    let in_scales: Vec<Vec<u16>> = manifest.layer_input_scales[..layers]
+        .iter()
+        .map(|&s| vec![s; 3 * config.d_inner])
+        .collect();
+    let out_scales: Vec<Vec<u16>> = manifest.layer_output_scales[..layers]
+        .iter()
+        .map(|&s| vec![s; config.d_model])
+        .collect();
+    let in_refs: Vec<&[u16]> = in_scales.iter().map(|s| &s[..]).collect();
+    let out_refs: Vec<&[u16]> = out_scales.iter().map(|s| &s[..]).collect();
+
+    let mut routes = vec![0u8; layers];
+    let output = forward_pass(
+        &input,
+        hidden_state,
+        shards,
+        &manifest.tensor_table[..layers],
+        expert_table,
+        &manifest_layer_kinds(manifest),
+        &manifest.luts,
+        &config,
+        &in_refs,
+        &out_refs,
+        &mut routes,
+        ranges,
+    )?;
+
+    let offsets = manifest.output_offsets([players[0].character, players[1].character]);
+    let decoded =
+        decode_output(&manifest.encoder_spec, action.as_ref(), &manifest.luts, &output, offsets);
+    for (p, d) in players.iter_mut().zip(decoded) {
+        *p = PlayerState {
+            x: d.x,
+            y: d.y,
+            percent: d.percent,
+            shield_strength: d.shield_strength,
+            speed_air_x: d.speed_air_x,
+            speed_y: d.speed_y,
+            speed_ground_x: d.speed_ground_x,
+            speed_attack_x: d.speed_attack_x,
+            speed_attack_y: d.speed_attack_y,
+            state_age: d.state_age,
+            hitlag: d.hitlag,
+            stocks: d.stocks,
+            facing: d.facing,
+            on_ground: d.on_ground,
+            action_state: d.action_state,
+            jumps_left: d.jumps_left,
+            character: p.character,
+        };
+    }
+    Some(routes)
 }
@@ -1,17 +1,41 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount as SplTokenAccount};
+use anchor_spl::token_interface::{
+    self, Mint as InterfaceMint, TokenAccount, TokenInterface, TransferChecked,
+};
+use ephemeral_rollups_sdk::anchor::ephemeral;
 
+pub mod archive;
+pub mod automation;
+pub mod checkpoint;
+pub mod delegation;
 pub mod error;
+#[cfg(any(test, feature = "test-support"))]
+pub mod fixture;
+#[cfg(test)]
+mod golden;
 pub mod inference;
-pub mod lut;
-pub mod matmul;
-pub mod ssm;
+pub mod stages;
 pub mod state;
 
+pub use awm_events as events;
+pub use awm_kernel::{attention, lut, matmul, rng, ssm, tensor_dir};
+
+#[cfg(not(any(feature = "stub-inference", feature = "mamba2-inference")))]
+compile_error!("enable `stub-inference` or `mamba2-inference` to pick run_inference's dynamics");
+
 use error::WorldModelError;
+use events::*;
 use state::*;
 
-declare_id!("WrLd1111111111111111111111111111111111111111");
+declare_id!("BC9hw928r1rvxedMwTSDSvRxFSHMgY4maWnYa9TV71nZ");
 
+#[ephemeral]
 #[program]
 pub mod world_model {
     use super::*;
@@ -29,7 +53,9 @@ pub mod world_model {
         d_state: u16,
         num_layers: u8,
         num_heads: u8,
-        luts: [u8; LUT_TOTAL_SIZE],
+        hidden_dtype: u8,
+        expected_shards: u8,
+        luts: [u8; LUT_V1_SIZE],
         num_continuous: u8,
         num_action_states: u16,
         num_binary: u8,
@@ -37,6 +63,16 @@ pub mod world_model {
         total_params: u32,
         total_weight_bytes: u32,
     ) -> Result<()> {
+        require!(
+            hidden_dtype == HIDDEN_DTYPE_I8 || hidden_dtype == HIDDEN_DTYPE_I16,
+            WorldModelError::InvalidHiddenDtype
+        );
+        require!(
+            expected_shards >= 1 && expected_shards as usize <= MAX_SHARDS,
+            WorldModelError::InvalidShardIndex
+        );
+        lut::validate_luts(&luts).map_err(WorldModelError::from)?;
+
         let manifest = &mut ctx.accounts.manifest;
 
         manifest.name = name;
@@ -46,7 +82,12 @@ pub mod world_model {
         manifest.d_state = d_state;
         manifest.num_layers = num_layers;
         manifest.num_heads = num_heads;
-        manifest.luts = luts;
+        manifest.ngroups = 0;
+        manifest.hidden_dtype = hidden_dtype;
+        manifest.luts = [0; LUT_TOTAL_SIZE];
+        manifest.luts[..LUT_V1_SIZE].copy_from_slice(&luts);
+        manifest.lut_version = lut::LUT_VERSION_1;
+        manifest.encoder_spec = EncoderSpec::melee_v2();
         manifest.num_continuous = num_continuous;
         manifest.num_action_states = num_action_states;
         manifest.num_binary = num_binary;
@@ -54,449 +95,5670 @@ pub mod world_model {
         manifest.total_params = total_params;
         manifest.total_weight_bytes = total_weight_bytes;
         manifest.authority = ctx.accounts.authority.key();
+        manifest.pending_authority = Pubkey::default();
         manifest.ready = false;
+        manifest.deprecated = false;
+        manifest.superseded_by = Pubkey::default();
+        manifest.forked_from = Pubkey::default();
+        manifest.session_fee = 0;
+        manifest.fee_mint = Pubkey::default();
+        manifest.cartridge_mint = Pubkey::default();
+        manifest.gate_mint = Pubkey::default();
+        manifest.gate_min_amount = 0;
+        manifest.settlement_verifier = Pubkey::default();
+        manifest.expected_shards = expected_shards;
         manifest.num_shards = 0;
+        manifest.shard_directory = Pubkey::default();
+        manifest.registered_weight_bytes = 0;
+        manifest.num_experts = 0;
+        manifest.expert_table = Pubkey::default();
+        manifest.attention_layers = 0;
+        manifest.attn_window = 0;
+        manifest.requant_mode = REQUANT_TRUNCATE;
+        manifest.head_stride = 0;
+        manifest.head_offsets = [0; MAX_CHARACTER_HEADS];
+        manifest.output_smoothing = [0; MAX_PLAYER_FIELDS];
+        manifest.action_embedding = TensorRef::default();
+        manifest.action_head = TensorRef::default();
 
         msg!("Manifest initialized: d_model={}, d_inner={}, layers={}",
              d_model, d_inner, num_layers);
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // 2. upload_weights — chunked weight upload with finalization
-    // ═══════════════════════════════════════════════════════════════════════
-
-    pub fn upload_weights(
-        ctx: Context<UploadWeights>,
-        offset: u32,
-        data: Vec<u8>,
-    ) -> Result<()> {
-        let weight = &mut ctx.accounts.weight_account;
+    /// Set the royalty charged on each create_session, in the manifest's
+    /// fee_mint. Authority only.
+    ///
+    /// A non-zero lamport fee must cover the treasury PDA's rent-exempt
+    /// minimum so the first payment can fund the account. Token fees go to
+    /// the treasury's token account instead and have no minimum.
+    pub fn set_session_fee(ctx: Context<SetSessionFee>, session_fee: u64) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
 
         require!(
-            ctx.accounts.authority.key() == weight.authority,
+            manifest.is_authority(&ctx.accounts.authority.key()),
             WorldModelError::Unauthorized
         );
-        require!(!weight.finalized, WorldModelError::AlreadyFinalized);
-        require!(data.len() <= MAX_CHUNK_SIZE, WorldModelError::ChunkTooLarge);
+        require!(
+            manifest.fee_mint != Pubkey::default()
+                || session_fee == 0
+                || session_fee >= Rent::get()?.minimum_balance(0),
+            WorldModelError::FeeBelowRentExempt
+        );
+
+        manifest.session_fee = session_fee;
+
+        msg!("Session fee set: {} (mint {})", session_fee, manifest.fee_mint);
+        Ok(())
+    }
+
+    /// Denominate the royalty in an SPL mint (default key = lamports).
+    /// Authority only. Amounts don't carry across currencies, so this
+    /// resets session_fee to 0; follow with set_session_fee.
+    pub fn set_fee_mint(ctx: Context<SetFeeMint>, fee_mint: Pubkey) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
 
-        let offset = offset as usize;
-        let end = offset + data.len();
         require!(
-            end <= weight.data_size as usize,
-            WorldModelError::ChunkOutOfBounds
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
         );
 
-        // Write to raw account data past the header
-        let weight_data = &ctx.accounts.weight_data;
-        let mut account_data = weight_data.try_borrow_mut_data()?;
-        let dest = &mut account_data[WEIGHT_HEADER_SIZE + offset..WEIGHT_HEADER_SIZE + end];
-        dest.copy_from_slice(&data);
+        manifest.fee_mint = fee_mint;
+        manifest.session_fee = 0;
 
-        // Track high-water mark
-        let new_written = end as u32;
-        if new_written > weight.bytes_written {
-            weight.bytes_written = new_written;
-        }
+        msg!("Fee mint set: {}", fee_mint);
+        Ok(())
+    }
+
+    /// Gate create_session on a cartridge NFT (default key = ungated).
+    /// Authority only.
+    pub fn set_cartridge_mint(ctx: Context<SetCartridgeMint>, cartridge_mint: Pubkey) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+
+        manifest.cartridge_mint = cartridge_mint;
 
+        msg!("Cartridge mint set: {}", cartridge_mint);
         Ok(())
     }
 
-    pub fn finalize_weights(
-        ctx: Context<FinalizeWeights>,
-        expected_hash: [u8; 32],
+    /// Gate create_session on holding at least `min_amount` of an SPL mint
+    /// (default key = ungated). Authority only.
+    pub fn set_token_gate(
+        ctx: Context<SetTokenGate>,
+        gate_mint: Pubkey,
+        min_amount: u64,
     ) -> Result<()> {
-        let weight = &mut ctx.accounts.weight_account;
+        let manifest = &mut ctx.accounts.manifest;
 
         require!(
-            ctx.accounts.authority.key() == weight.authority,
+            manifest.is_authority(&ctx.accounts.authority.key()),
             WorldModelError::Unauthorized
         );
-        require!(!weight.finalized, WorldModelError::AlreadyFinalized);
         require!(
-            weight.bytes_written >= weight.data_size,
-            WorldModelError::IncompleteUpload
+            gate_mint == Pubkey::default() || min_amount > 0,
+            WorldModelError::InvalidAmount
         );
 
-        // Verify hash of data region
-        let weight_data = &ctx.accounts.weight_data;
-        let account_data = weight_data.try_borrow_data()?;
-        let data_region = &account_data[WEIGHT_HEADER_SIZE..WEIGHT_HEADER_SIZE + weight.data_size as usize];
-        let hash = solana_program::hash::hash(data_region);
+        manifest.gate_mint = gate_mint;
+        manifest.gate_min_amount = min_amount;
+
+        msg!("Token gate set: mint={}, min_amount={}", gate_mint, min_amount);
+        Ok(())
+    }
+
+    /// Accept off-chain transitions proven to `verifier` (default key =
+    /// on-chain inference only). Authority only.
+    pub fn set_settlement_verifier(
+        ctx: Context<SetSettlementVerifier>,
+        verifier: Pubkey,
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
 
         require!(
-            hash.to_bytes() == expected_hash,
-            WorldModelError::HashMismatch
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
         );
 
-        weight.finalized = true;
-        weight.data_hash = expected_hash;
+        manifest.settlement_verifier = verifier;
 
-        msg!("Weight shard {} finalized ({} bytes, hash verified)",
-             weight.shard_index, weight.data_size);
+        msg!("Settlement verifier set: {}", verifier);
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // 3. create_session — insert cartridge, allocate session accounts
-    // ═══════════════════════════════════════════════════════════════════════
+    /// Sweep the manifest's royalty treasury to the authority. Authority only.
+    pub fn withdraw_royalties(ctx: Context<WithdrawRoyalties>) -> Result<()> {
+        require!(
+            ctx.accounts.manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
 
-    pub fn create_session(
-        ctx: Context<CreateSession>,
-        stage: u8,
-        character: u8,
-        max_frames: u32,
-        seed: u64,
-    ) -> Result<()> {
-        let session = &mut ctx.accounts.session;
-        let manifest = &ctx.accounts.manifest;
+        let amount = ctx.accounts.treasury.lamports();
+        let manifest_key = ctx.accounts.manifest.key();
+        let bump = [ctx.bumps.treasury];
+        let seeds: &[&[u8]] = &[TREASURY_SEED, manifest_key.as_ref(), &bump];
 
-        // Initialize session state
-        session.status = STATUS_WAITING_PLAYERS;
-        session.frame = 0;
-        session.max_frames = max_frames;
-        session.player1 = ctx.accounts.player1.key();
-        session.player2 = Pubkey::default();
-        session.stage = stage;
-        session.model = manifest.key();
-        session.seed = seed;
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
 
-        // Set player 1 defaults
-        session.players[0] = PlayerState::default();
-        session.players[0].character = character;
-        session.players[0].stocks = 4;
+        msg!("Royalties withdrawn: {} lamports", amount);
+        Ok(())
+    }
 
-        // Initialize hidden state header (raw AccountInfo)
-        let hidden = &ctx.accounts.hidden_state;
-        let mut h_data = hidden.try_borrow_mut_data()?;
-        let d_inner = manifest.d_inner;
-        let d_state = manifest.d_state;
-        let num_layers = manifest.num_layers;
-        let data_size = (num_layers as u32) * (d_inner as u32) * (d_state as u32);
-        write_hidden_header(
-            &mut h_data,
-            num_layers,
-            d_inner,
-            d_state,
-            data_size,
-            0,     // frame
-            false, // initialized
+    /// Sweep the treasury's token account for an SPL fee mint to a token
+    /// account of the authority's choosing. Authority only.
+    pub fn withdraw_token_royalties(ctx: Context<WithdrawTokenRoyalties>) -> Result<()> {
+        require!(
+            ctx.accounts.manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
         );
 
-        // Initialize input buffer
-        let input_buf = &mut ctx.accounts.input_buffer;
-        input_buf.frame = 0;
-        input_buf.p1_ready = false;
-        input_buf.p2_ready = false;
+        let amount = ctx.accounts.treasury_token.amount;
+        let manifest_key = ctx.accounts.manifest.key();
+        let bump = [ctx.bumps.treasury];
+        let seeds: &[&[u8]] = &[TREASURY_SEED, manifest_key.as_ref(), &bump];
 
-        msg!("Session created: player1={}, stage={}", ctx.accounts.player1.key(), stage);
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_token.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &[seeds],
+            amount,
+        )?;
+
+        msg!("Royalties withdrawn: {} of mint {}", amount, ctx.accounts.mint.key());
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // 4. join_session — plug in controller, activate game
-    // ═══════════════════════════════════════════════════════════════════════
-
-    pub fn join_session(
-        ctx: Context<JoinSession>,
-        character: u8,
-    ) -> Result<()> {
-        let session = &mut ctx.accounts.session;
-
-        require!(
-            session.status == STATUS_WAITING_PLAYERS,
-            WorldModelError::InvalidStateTransition
-        );
+    /// Fork a ready manifest into a new cartridge owned by the signer.
+    ///
+    /// Copies architecture params, shard references, tensor table, scales,
+    /// LUTs, encoder spec, and model card. The fork points at the same
+    /// finalized weight shards, so it is ready immediately — no re-upload.
+    pub fn fork_manifest(ctx: Context<ForkManifest>) -> Result<()> {
+        let source = &ctx.accounts.source;
+        require!(source.ready, WorldModelError::ModelNotReady);
+        // Forking would hand out an ungated copy of the author's cartridge
         require!(
-            ctx.accounts.player2.key() != session.player1,
-            WorldModelError::CannotJoinOwnSession
+            source.cartridge_mint == Pubkey::default() && source.gate_mint == Pubkey::default(),
+            WorldModelError::CartridgeGated
         );
 
-        // Set player 2
-        session.player2 = ctx.accounts.player2.key();
-        session.players[1] = PlayerState::default();
-        session.players[1].character = character;
-        session.players[1].stocks = 4;
-
-        // Set initial positions (FD defaults)
-        session.players[0].x = -30 * 256;
-        session.players[0].y = 0;
-        session.players[0].facing = 1;
-        session.players[0].on_ground = 1;
-        session.players[0].jumps_left = 2;
-        session.players[0].shield_strength = 60 * 256;
-
-        session.players[1].x = 30 * 256;
-        session.players[1].y = 0;
-        session.players[1].facing = 0;
-        session.players[1].on_ground = 1;
-        session.players[1].jumps_left = 2;
-        session.players[1].shield_strength = 60 * 256;
+        let fork = &mut ctx.accounts.manifest;
 
-        session.status = STATUS_ACTIVE;
+        fork.name = source.name;
+        fork.version = source.version;
+        fork.d_model = source.d_model;
+        fork.d_inner = source.d_inner;
+        fork.d_state = source.d_state;
+        fork.num_layers = source.num_layers;
+        fork.num_heads = source.num_heads;
+        fork.ngroups = source.ngroups;
+        fork.hidden_dtype = source.hidden_dtype;
+        fork.expected_shards = source.expected_shards;
+        fork.num_shards = source.num_shards;
+        fork.shard_directory = source.shard_directory;
+        fork.registered_weight_bytes = source.registered_weight_bytes;
+        fork.tensor_table = source.tensor_table;
+        fork.action_embedding = source.action_embedding;
+        fork.action_head = source.action_head;
+        fork.num_experts = source.num_experts;
+        fork.expert_table = source.expert_table;
+        fork.attention_layers = source.attention_layers;
+        fork.attn_window = source.attn_window;
+        fork.layer_input_scales = source.layer_input_scales;
+        fork.layer_output_scales = source.layer_output_scales;
+        fork.requant_mode = source.requant_mode;
+        fork.luts = source.luts;
+        fork.lut_version = source.lut_version;
+        fork.encoder_spec = source.encoder_spec;
+        fork.num_continuous = source.num_continuous;
+        fork.num_action_states = source.num_action_states;
+        fork.num_binary = source.num_binary;
+        fork.input_size = source.input_size;
+        fork.head_stride = source.head_stride;
+        fork.head_offsets = source.head_offsets;
+        fork.output_smoothing = source.output_smoothing;
+        fork.total_params = source.total_params;
+        fork.total_weight_bytes = source.total_weight_bytes;
+        fork.metadata_uri = source.metadata_uri;
+        fork.dataset_hash = source.dataset_hash;
+        fork.training_commit = source.training_commit;
 
-        msg!("Player 2 joined: character={}. Session ACTIVE!", character);
+        fork.authority = ctx.accounts.authority.key();
+        fork.pending_authority = Pubkey::default();
+        fork.ready = true;
+        fork.deprecated = false;
+        fork.superseded_by = Pubkey::default();
+        fork.forked_from = source.key();
+        fork.session_fee = 0;
+        fork.fee_mint = Pubkey::default();
+        fork.cartridge_mint = Pubkey::default();
+        fork.gate_mint = Pubkey::default();
+        fork.gate_min_amount = 0;
+        fork.settlement_verifier = Pubkey::default();
+
+        msg!("Manifest forked from {}", source.key());
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // 5. close_session — power off, end game
-    // ═══════════════════════════════════════════════════════════════════════
+    /// Cross-check manifest arithmetic and emit a ManifestValidated event.
+    /// Permissionless and read-only — a failed check does not fail the
+    /// instruction, so frontends can always read the result.
+    pub fn validate_manifest(ctx: Context<ValidateManifest>) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        let failures = manifest.consistency_failures();
 
-    pub fn close_session(
-        ctx: Context<CloseSession>,
+        emit!(ManifestValidated {
+            manifest: manifest.key(),
+            passed: failures == 0,
+            failures,
+        });
+
+        msg!("Manifest validation: failures={:#06b}", failures);
+        Ok(())
+    }
+
+    /// Set the model card pointer and training provenance. Authority only.
+    pub fn set_model_card(
+        ctx: Context<SetModelCard>,
+        metadata_uri: [u8; METADATA_URI_LEN],
+        dataset_hash: [u8; 32],
+        training_commit: [u8; 20],
     ) -> Result<()> {
-        let session = &mut ctx.accounts.session;
+        let manifest = &mut ctx.accounts.manifest;
 
         require!(
-            session.status == STATUS_ACTIVE || session.status == STATUS_WAITING_PLAYERS,
-            WorldModelError::InvalidStateTransition
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
         );
 
-        // Verify the closer is a participant
-        let player_key = ctx.accounts.player.key();
-        require!(
-            player_key == session.player1 || player_key == session.player2,
-            WorldModelError::UnauthorizedPlayer
-        );
+        manifest.metadata_uri = metadata_uri;
+        manifest.dataset_hash = dataset_hash;
+        manifest.training_commit = training_commit;
 
-        session.status = STATUS_ENDED;
-        msg!("Session ended at frame {}", session.frame);
+        msg!("Model card updated");
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // 6. submit_input — receive controller input from a player
-    // ═══════════════════════════════════════════════════════════════════════
-
-    pub fn submit_input(
-        ctx: Context<SubmitInput>,
-        stick_x: i8,
-        stick_y: i8,
-        c_stick_x: i8,
-        c_stick_y: i8,
-        trigger_l: u8,
-        trigger_r: u8,
-        buttons: u8,
-        buttons_ext: u8,
+    /// Step 1 of an authority transfer: the current authority nominates a
+    /// successor (Pubkey::default() cancels a pending transfer).
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        new_authority: Pubkey,
     ) -> Result<()> {
-        let session = &ctx.accounts.session;
-        let input_buf = &mut ctx.accounts.input_buffer;
-        let player_key = ctx.accounts.player.key();
+        let manifest = &mut ctx.accounts.manifest;
 
         require!(
-            session.status == STATUS_ACTIVE,
-            WorldModelError::SessionNotActive
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
         );
 
-        let is_p1 = player_key == session.player1;
-        let is_p2 = player_key == session.player2;
+        manifest.pending_authority = new_authority;
+
+        msg!("Authority transfer proposed: {}", new_authority);
+        Ok(())
+    }
+
+    /// Step 2 of an authority transfer: the nominee signs to take over.
+    /// Requiring the new authority's signature guards against typos and
+    /// proves a PDA authority's program can actually sign for it.
+    pub fn accept_authority(
+        ctx: Context<AcceptAuthority>,
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+        let new_authority = ctx.accounts.new_authority.key();
+
         require!(
-            is_p1 || is_p2,
-            WorldModelError::UnauthorizedPlayer
+            manifest.pending_authority != Pubkey::default()
+                && manifest.pending_authority == new_authority,
+            WorldModelError::NotPendingAuthority
         );
 
-        let controller = ControllerInput {
-            stick_x,
-            stick_y,
-            c_stick_x,
-            c_stick_y,
-            trigger_l,
-            trigger_r,
-            buttons,
-            buttons_ext,
-        };
+        manifest.authority = new_authority;
+        manifest.pending_authority = Pubkey::default();
 
-        if is_p1 {
-            input_buf.player1 = controller;
-            input_buf.p1_ready = true;
-        } else {
-            input_buf.player2 = controller;
-            input_buf.p2_ready = true;
-        }
+        msg!("Manifest authority transferred to {}", new_authority);
+        Ok(())
+    }
 
-        // Reset other player's ready flag on new frame
-        let expected_frame = session.frame + 1;
-        if input_buf.frame != expected_frame {
-            input_buf.frame = expected_frame;
-            if is_p1 {
-                input_buf.p2_ready = false;
-            } else {
-                input_buf.p1_ready = false;
-            }
-        }
+    /// Mark a model as deprecated, optionally pointing at its replacement.
+    pub fn deprecate_model(
+        ctx: Context<DeprecateModel>,
+        superseded_by: Pubkey,
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
 
+        manifest.deprecated = true;
+        manifest.superseded_by = superseded_by;
+
+        msg!("Model deprecated, superseded_by={}", superseded_by);
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // 7. run_inference — the heart of the autonomous world
-    // ═══════════════════════════════════════════════════════════════════════
-
-    pub fn run_inference(
-        ctx: Context<RunInference>,
+    /// Set one layer's named-tensor table entries. Authority only, and only
+    /// before the model is ready — the table is part of the cartridge.
+    pub fn set_tensor_table(
+        ctx: Context<SetTensorTable>,
+        layer: u8,
+        tensors: [TensorRef; TENSORS_PER_LAYER],
     ) -> Result<()> {
-        let session = &mut ctx.accounts.session;
-        let input_buf = &ctx.accounts.input_buffer;
+        let manifest = &mut ctx.accounts.manifest;
 
         require!(
-            session.status == STATUS_ACTIVE,
-            WorldModelError::SessionNotActive
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
         );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
         require!(
-            input_buf.p1_ready && input_buf.p2_ready,
-            WorldModelError::InputsNotReady
+            layer < manifest.num_layers && (layer as usize) < MAX_LAYERS,
+            WorldModelError::InvalidTensorRef
         );
+        for (slot, t) in tensors.iter().enumerate() {
+            let masked = t.dtype == TENSOR_DTYPE_I8_MASKED
+                && (slot == TENSOR_IN_PROJ || slot == TENSOR_OUT_PROJ);
+            require!(
+                (t.dtype == TENSOR_DTYPE_I8 || masked) && t.shard < manifest.expected_shards,
+                WorldModelError::InvalidTensorRef
+            );
+        }
 
-        // ── STUB INFERENCE ──────────────────────────────────────────────
-        // Phase 4 will replace this with real Mamba2 forward pass.
-        // For now: apply simple physics-like rules to demonstrate the pipeline.
+        manifest.tensor_table[layer as usize] = tensors;
 
-        let frame = session.frame + 1;
+        msg!("Tensor table set for layer {}", layer);
+        Ok(())
+    }
 
-        for player_idx in 0..2 {
-            let input = if player_idx == 0 {
-                &input_buf.player1
-            } else {
-                &input_buf.player2
-            };
+    /// Set the action-state embedding ([width × classes], one column per
+    /// class) and logit head ([classes × width]) standing in for the
+    /// CATEGORICAL action field's one-hot slots. A TENSOR_DTYPE_TIED head
+    /// (shard and offset 0) shares the embedding's bytes, read transposed;
+    /// an embedding with rows = 0 (and a default head) restores one-hot.
+    /// Authority only, and only before the model is ready.
+    pub fn set_action_tensors(
+        ctx: Context<SetActionTensors>,
+        embedding: TensorRef,
+        head: TensorRef,
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
 
-            let p = &mut session.players[player_idx];
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        let valid = if embedding.rows == 0 {
+            embedding == TensorRef::default() && head == TensorRef::default()
+        } else {
+            let width = manifest.encoder_spec.action_field().map(|f| f.width as u16);
+            let transposed = head.rows == embedding.cols && head.cols == embedding.rows;
+            let head_ok = match head.dtype {
+                TENSOR_DTYPE_TIED => head.shard == 0 && head.offset == 0,
+                TENSOR_DTYPE_I8 => head.shard < manifest.expected_shards,
+                _ => false,
+            };
+            width == Some(embedding.rows)
+                && embedding.cols >= 2
+                && embedding.dtype == TENSOR_DTYPE_I8
+                && embedding.shard < manifest.expected_shards
+                && transposed
+                && head_ok
+        };
+        require!(valid, WorldModelError::InvalidActionTensors);
 
-            // Apply stick input as velocity (simplified physics)
-            let stick_x = input.stick_x as i32;
-            let stick_y = input.stick_y as i32;
+        manifest.action_embedding = embedding;
+        manifest.action_head = head;
 
-            p.x += stick_x * 2;
-            p.y += stick_y * 2;
+        msg!(
+            "Action tensors set: {} classes, tied={}",
+            embedding.cols,
+            head.dtype == TENSOR_DTYPE_TIED
+        );
+        Ok(())
+    }
 
-            // Gravity if airborne
-            if p.on_ground == 0 {
-                p.speed_y -= 4;
-                p.y += p.speed_y as i32;
+    /// Set how many head groups share B and C. The layers' in_proj then
+    /// carries [B, C] rows, ngroups × d_state each, after [z, x_ssm]; 0
+    /// keeps B and C derived from x_ssm. Authority only, and only before
+    /// the model is ready.
+    pub fn set_head_groups(ctx: Context<SetHeadGroups>, ngroups: u8) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
 
-                if p.y <= 0 {
-                    p.y = 0;
-                    p.speed_y = 0;
-                    p.on_ground = 1;
-                }
-            }
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        let config = inference::Mamba2Config {
+            ngroups: ngroups as usize,
+            ..inference::manifest_config(manifest)
+        };
+        require!(config.groups_fit(), WorldModelError::InvalidHeadGroups);
 
-            // Jump (button A = bit 0)
-            if input.buttons & 0x01 != 0 && p.jumps_left > 0 {
-                p.speed_y = 40;
-                p.on_ground = 0;
-                p.jumps_left = p.jumps_left.saturating_sub(1);
-            }
+        manifest.ngroups = ngroups;
 
-            // Facing direction
-            if stick_x > 10 {
-                p.facing = 1;
-            } else if stick_x < -10 {
-                p.facing = 0;
+        msg!("Head groups set: {}", ngroups);
+        Ok(())
+    }
+
+    /// Choose how requantization rounds: REQUANT_TRUNCATE, or
+    /// REQUANT_STOCHASTIC to round from each frame's RNG stream (seeded by
+    /// the session seed and frame, so frames still replay exactly).
+    /// Authority only, and only before the model is ready.
+    pub fn set_requant_mode(ctx: Context<SetRequantMode>, requant_mode: u8) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        require!(
+            matches!(requant_mode, REQUANT_TRUNCATE | REQUANT_STOCHASTIC),
+            WorldModelError::InvalidRequantMode
+        );
+
+        manifest.requant_mode = requant_mode;
+
+        msg!("Requantization mode set: {}", requant_mode);
+        Ok(())
+    }
+
+    /// Give a multi-headed checkpoint one decoder head per character. Each
+    /// player's block of the model output is `head_stride` slots wide, and
+    /// a player decodes from its block at its character's `head_offsets`
+    /// entry; a head must fit its block and both blocks the output. A
+    /// stride of 0 (all offsets 0) restores the single shared head.
+    /// Authority only, and only before the model is ready.
+    pub fn set_character_heads(
+        ctx: Context<SetCharacterHeads>,
+        head_stride: u16,
+        head_offsets: [u16; MAX_CHARACTER_HEADS],
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        let stride = head_stride as usize;
+        let width = manifest.encoder_spec.player_width();
+        let heads_fit = if stride == 0 {
+            head_offsets.iter().all(|&o| o == 0)
+        } else {
+            NUM_PLAYERS * stride <= manifest.d_model as usize
+                && head_offsets.iter().all(|&o| o as usize + width <= stride)
+        };
+        require!(heads_fit, WorldModelError::InvalidCharacterHeads);
+
+        manifest.head_stride = head_stride;
+        manifest.head_offsets = head_offsets;
+
+        msg!("Character heads set: stride={}", head_stride);
+        Ok(())
+    }
+
+    /// Mark layers of a hybrid checkpoint as sliding-window attention
+    /// (bit i = layer i) attending over `attn_window` frames. Their
+    /// tensor_table in_proj maps to [q, k, v] (3*d_inner rows) and their KV
+    /// buffers take over their hidden-state slots, so the window must fit
+    /// one (attention::max_window). Authority only, and only before the
+    /// model is ready.
+    pub fn set_attention_layers(
+        ctx: Context<SetAttentionLayers>,
+        attention_layers: u16,
+        attn_window: u8,
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        require!(
+            attention_layers.checked_shr(manifest.num_layers as u32).unwrap_or(0) == 0,
+            WorldModelError::InvalidAttentionLayers
+        );
+        let max_window =
+            attention::max_window(manifest.d_inner as usize, manifest.d_state as usize);
+        require!(
+            attention_layers == 0 || (attn_window > 0 && attn_window as usize <= max_window),
+            WorldModelError::InvalidAttentionWindow
+        );
+
+        manifest.attention_layers = attention_layers;
+        manifest.attn_window = attn_window;
+
+        msg!("Attention layers set: mask={:#06x}, window={}", attention_layers, attn_window);
+        Ok(())
+    }
+
+    /// Create the expert table of a mixture-of-experts model with
+    /// `num_experts` experts per MoE layer. Authority only, and only before
+    /// the model is ready. Every layer starts dense until set_expert_layer.
+    pub fn init_expert_table(ctx: Context<InitExpertTable>, num_experts: u8) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        require!(
+            num_experts >= 2 && num_experts as usize <= MAX_EXPERTS,
+            WorldModelError::InvalidExpertCount
+        );
+
+        let layers = manifest.num_layers as usize;
+        let table = &mut ctx.accounts.expert_table;
+        table.manifest = manifest.key();
+        table.num_experts = num_experts;
+        table.routers = vec![TensorRef::default(); layers];
+        table.experts =
+            vec![[TensorRef::default(); TENSORS_PER_EXPERT]; layers * num_experts as usize];
+        manifest.num_experts = num_experts;
+        manifest.expert_table = table.key();
+
+        msg!("Expert table created: {} experts x {} layers", num_experts, layers);
+        Ok(())
+    }
+
+    /// Make one layer mixture-of-experts: its router, a (num_experts,
+    /// d_model) tensor, and every expert's in_proj/out_proj. SSM layers
+    /// only. Authority only, and only before the model is ready.
+    pub fn set_expert_layer(
+        ctx: Context<SetExpertLayer>,
+        layer: u8,
+        router: TensorRef,
+        experts: Vec<[TensorRef; TENSORS_PER_EXPERT]>,
+    ) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        let table = &mut ctx.accounts.expert_table;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        require!(
+            (layer as usize) < table.routers.len()
+                && manifest.layer_kind(layer as usize) == inference::LayerKind::Ssm
+                && experts.len() == table.num_experts as usize
+                && router.rows as usize == experts.len()
+                && router.cols == manifest.d_model,
+            WorldModelError::InvalidTensorRef
+        );
+        for t in experts.iter().flatten().chain([&router]) {
+            require!(
+                t.dtype == TENSOR_DTYPE_I8 && t.shard < manifest.expected_shards,
+                WorldModelError::InvalidTensorRef
+            );
+        }
+
+        let start = layer as usize * experts.len();
+        table.routers[layer as usize] = router;
+        table.experts[start..start + experts.len()].copy_from_slice(&experts);
+
+        msg!("Experts set for layer {}", layer);
+        Ok(())
+    }
+
+    /// Replace the v1 activation LUTs (e.g. after re-quantizing), keeping
+    /// any extended tables. Authority only, and only before the model is
+    /// ready. Validated like init_manifest.
+    pub fn update_luts(ctx: Context<UpdateLuts>, luts: [u8; LUT_V1_SIZE]) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        lut::validate_luts(&luts).map_err(WorldModelError::from)?;
+
+        manifest.luts[..LUT_V1_SIZE].copy_from_slice(&luts);
+
+        msg!("LUTs updated");
+        Ok(())
+    }
+
+    /// Add or replace the tables a v2 LUT block appends (GELU, tanh) and
+    /// bump the manifest to LUT_VERSION_2. Authority only, and only before
+    /// the model is ready. The whole block is validated.
+    pub fn set_extended_luts(ctx: Context<UpdateLuts>, luts: [u8; LUT_EXT_SIZE]) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+
+        let mut block = manifest.luts;
+        block[LUT_V1_SIZE..].copy_from_slice(&luts);
+        lut::validate_luts(&block).map_err(WorldModelError::from)?;
+
+        manifest.luts = block;
+        manifest.lut_version = lut::LUT_VERSION_2;
+
+        msg!("Extended LUTs set (version {})", lut::LUT_VERSION_2);
+        Ok(())
+    }
+
+    /// Replace the input/output encoder spec (defaults to Melee v2 at init).
+    /// The spec is the whole encoding — field order and per-field divisors
+    /// — so a retrained encoding only needs a new spec, not a new program.
+    /// input_size, num_continuous and num_binary are derived from it. A
+    /// HASHED action-state field must encode num_action_states classes.
+    /// Authority only, and only before the model is ready.
+    pub fn set_encoder_spec(ctx: Context<SetEncoderSpec>, spec: EncoderSpec) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        require!(
+            spec.is_valid()
+                && spec.input_width() <= manifest.d_model as usize
+                && spec.covers_action_states(manifest.num_action_states),
+            WorldModelError::InvalidEncoderSpec
+        );
+
+        manifest.encoder_spec = spec;
+        manifest.input_size = spec.input_width() as u16;
+        manifest.num_continuous = spec.slots_of_kind(FIELD_KIND_LINEAR) as u8;
+        manifest.num_binary = spec.slots_of_kind(FIELD_KIND_BINARY) as u8;
+        // Smoothing weights are per field of the old layout
+        manifest.output_smoothing = [0; MAX_PLAYER_FIELDS];
+
+        msg!("Encoder spec set: {} player fields, {} global fields, {} slots",
+             spec.num_player_fields, spec.num_global_fields, manifest.input_size);
+        Ok(())
+    }
+
+    /// Set each layer's in_proj and out_proj requantization scales, e.g.
+    /// ones refitted from a calibration account. Every layer needs a
+    /// non-zero scale; entries past num_layers must be zero. Authority
+    /// only, and only before the model is ready — a live model gets new
+    /// scales through a fork.
+    pub fn set_layer_scales(
+        ctx: Context<SetLayerScales>,
+        input_scales: [u16; MAX_LAYERS],
+        output_scales: [u16; MAX_LAYERS],
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        let layers = manifest.num_layers as usize;
+        require!(
+            [input_scales, output_scales].iter().all(|scales| {
+                let (live, unused) = scales.split_at(layers.min(MAX_LAYERS));
+                live.iter().all(|&s| s != 0) && unused.iter().all(|&s| s == 0)
+            }),
+            WorldModelError::InvalidLayerScales
+        );
+
+        manifest.layer_input_scales = input_scales;
+        manifest.layer_output_scales = output_scales;
+
+        msg!("Layer scales set for {} layers", layers);
+        Ok(())
+    }
+
+    /// Set the output smoothing weights, indexed like the encoder spec's
+    /// player fields: each non-zero weight has that field keep weight/256
+    /// of its previous value every frame, damping jitter in regressed
+    /// positions and speeds. Only decoded LINEAR fields may be smoothed.
+    /// Set after the encoder spec (set_encoder_spec clears them), by the
+    /// authority, before the model is ready.
+    pub fn set_output_smoothing(
+        ctx: Context<SetOutputSmoothing>,
+        smoothing: [u8; MAX_PLAYER_FIELDS],
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+        require!(!manifest.ready, WorldModelError::AlreadyFinalized);
+        require!(
+            manifest.encoder_spec.accepts_smoothing(&smoothing),
+            WorldModelError::InvalidOutputSmoothing
+        );
+
+        manifest.output_smoothing = smoothing;
+
+        msg!("Output smoothing set for {} fields",
+             smoothing.iter().filter(|&&w| w != 0).count());
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 2. upload_weights — chunked weight upload with finalization
+    // ═══════════════════════════════════════════════════════════════════════
+
+    pub fn upload_weights(
+        ctx: Context<UploadWeights>,
+        offset: u32,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let weight = &mut ctx.accounts.weight_account;
+
+        require!(
+            ctx.accounts.authority.key() == weight.authority,
+            WorldModelError::Unauthorized
+        );
+        require!(!weight.finalized, WorldModelError::AlreadyFinalized);
+        require!(data.len() <= MAX_CHUNK_SIZE, WorldModelError::ChunkTooLarge);
+
+        let offset = offset as usize;
+        let end = offset + data.len();
+        require!(
+            end <= weight.data_size as usize,
+            WorldModelError::ChunkOutOfBounds
+        );
+
+        // Write to raw account data past the header
+        let weight_data = &ctx.accounts.weight_data;
+        let mut account_data = weight_data.try_borrow_mut_data()?;
+        let dest = &mut account_data[WEIGHT_HEADER_SIZE + offset..WEIGHT_HEADER_SIZE + end];
+        dest.copy_from_slice(&data);
+
+        // Track high-water mark
+        let new_written = end as u32;
+        if new_written > weight.bytes_written {
+            weight.bytes_written = new_written;
+        }
+
+        Ok(())
+    }
+
+    pub fn finalize_weights(
+        ctx: Context<FinalizeWeights>,
+        expected_hash: [u8; 32],
+    ) -> Result<()> {
+        let weight = &mut ctx.accounts.weight_account;
+
+        require!(
+            ctx.accounts.authority.key() == weight.authority,
+            WorldModelError::Unauthorized
+        );
+        require!(!weight.finalized, WorldModelError::AlreadyFinalized);
+        require!(
+            weight.bytes_written >= weight.data_size,
+            WorldModelError::IncompleteUpload
+        );
+
+        // Verify hash of data region
+        let weight_data = &ctx.accounts.weight_data;
+        let account_data = weight_data.try_borrow_data()?;
+        let data_region = &account_data[WEIGHT_HEADER_SIZE..WEIGHT_HEADER_SIZE + weight.data_size as usize];
+        let hash = solana_sha256_hasher::hash(data_region);
+
+        require!(
+            hash.to_bytes() == expected_hash,
+            WorldModelError::HashMismatch
+        );
+        // A shard that opens with a named-tensor directory must parse, so
+        // inference can trust it
+        require!(
+            tensor_dir::TensorDirectory::parse(data_region).is_ok(),
+            WorldModelError::InvalidTensorDirectory
+        );
+
+        weight.finalized = true;
+        weight.data_hash = expected_hash;
+
+        msg!("Weight shard {} finalized ({} bytes, hash verified)",
+             weight.shard_index, weight.data_size);
+        Ok(())
+    }
+
+    /// Create the manifest's shard directory with one empty slot per
+    /// expected shard. Authority only; required before register_shard.
+    pub fn init_shard_directory(ctx: Context<InitShardDirectory>) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+
+        let directory = &mut ctx.accounts.shard_directory;
+        directory.manifest = manifest.key();
+        directory.entries = vec![ShardEntry::default(); manifest.expected_shards as usize];
+        manifest.shard_directory = directory.key();
+
+        msg!("Shard directory created: {} slots", manifest.expected_shards);
+        Ok(())
+    }
+
+    /// Record a finalized weight shard in the manifest. Once every expected
+    /// shard is registered the manifest flips to ready. Until then a slot
+    /// can be registered again to swap in a corrected shard, and no shard
+    /// may push the registered bytes past total_weight_bytes.
+    pub fn register_shard(
+        ctx: Context<RegisterShard>,
+        expected_hash: [u8; 32],
+    ) -> Result<()> {
+        let manifest = &mut ctx.accounts.manifest;
+        let weight = &ctx.accounts.weight_account;
+
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key())
+                && manifest.is_authority(&weight.authority),
+            WorldModelError::Unauthorized
+        );
+        require!(weight.finalized, WorldModelError::ShardNotFinalized);
+        require!(
+            weight.data_hash == expected_hash,
+            WorldModelError::HashMismatch
+        );
+
+        let directory = &mut ctx.accounts.shard_directory;
+        let idx = weight.shard_index as usize;
+        require!(
+            idx < directory.entries.len() && !manifest.ready,
+            WorldModelError::InvalidShardIndex
+        );
+
+        let previous = directory.entries[idx];
+        let registered = manifest.registered_weight_bytes - previous.size as u64
+            + weight.data_size as u64;
+        require!(
+            registered <= manifest.total_weight_bytes as u64,
+            WorldModelError::ShardSizeMismatch
+        );
+
+        directory.entries[idx] = ShardEntry {
+            key: weight.key(),
+            size: weight.data_size,
+        };
+        if previous.key == Pubkey::default() {
+            manifest.num_shards += 1;
+        }
+        manifest.registered_weight_bytes = registered;
+
+        if manifest.num_shards == manifest.expected_shards {
+            require!(
+                manifest.registered_weight_bytes == manifest.total_weight_bytes as u64,
+                WorldModelError::ShardSizeMismatch
+            );
+            manifest.ready = true;
+        }
+
+        msg!("Shard {} registered ({}/{}), ready={}",
+             idx, manifest.num_shards, manifest.expected_shards, manifest.ready);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 3. create_session — insert cartridge, allocate session accounts
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Start a session on a ready model. Needs the fee schedule
+    /// (init_fee_schedule) for its protocol fee.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        stage: u8,
+        character: u8,
+        max_frames: u32,
+        seed: u64,
+        allow_deprecated: bool,
+        tick_rate: u8,
+    ) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        require!(
+            matches!(tick_rate, TICK_RATE_30 | TICK_RATE_60 | TICK_RATE_120),
+            WorldModelError::InvalidTickRate
+        );
+
+        // Don't let players unknowingly start an old world
+        if manifest.deprecated {
+            require!(allow_deprecated, WorldModelError::ModelDeprecated);
+            msg!("WARNING: model is deprecated, superseded_by={}", manifest.superseded_by);
+        }
+
+        // Cartridge gate: the creator must own the NFT, or be its SPL
+        // delegate (a rental the owner can revoke)
+        if manifest.cartridge_mint != Pubkey::default() {
+            let token = ctx
+                .accounts
+                .cartridge_token
+                .as_ref()
+                .ok_or(WorldModelError::CartridgeRequired)?;
+            let creator = ctx.accounts.player1.key();
+            let owns = token.owner == creator && token.amount >= 1;
+            let rents = token.delegate == Some(creator).into() && token.delegated_amount >= 1;
+            require!(
+                token.mint == manifest.cartridge_mint && (owns || rents),
+                WorldModelError::CartridgeNotHeld
+            );
+        }
+
+        // Members-only gate: the creator must hold a minimum balance of the
+        // community mint (an NFT collection mint works with min_amount = 1)
+        if manifest.gate_mint != Pubkey::default() {
+            let token = ctx
+                .accounts
+                .gate_token
+                .as_ref()
+                .ok_or(WorldModelError::TokenGateRequired)?;
+            require!(
+                token.mint == manifest.gate_mint
+                    && token.owner == ctx.accounts.player1.key()
+                    && token.amount >= manifest.gate_min_amount,
+                WorldModelError::TokenGateBalanceTooLow
+            );
+        }
+
+        // Pay the model author's royalty, in its fee mint if it has one
+        if manifest.session_fee > 0 && manifest.fee_mint != Pubkey::default() {
+            let (Some(mint), Some(from), Some(to), Some(token_program)) = (
+                ctx.accounts.fee_mint.as_ref(),
+                ctx.accounts.fee_token.as_ref(),
+                ctx.accounts.treasury_fee_token.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return err!(WorldModelError::FeeTokenAccountsRequired);
+            };
+            require!(
+                mint.key() == manifest.fee_mint
+                    && to.mint == manifest.fee_mint
+                    && to.owner == ctx.accounts.treasury.key(),
+                WorldModelError::FeeTokenMismatch
+            );
+            transfer_tokens(
+                token_program,
+                mint,
+                from.to_account_info(),
+                to.to_account_info(),
+                ctx.accounts.player1.to_account_info(),
+                &[],
+                manifest.session_fee,
+            )?;
+        } else if manifest.session_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.player1.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                manifest.session_fee,
+            )?;
+        }
+
+        // Protocol session fee, held in the session account until settlement
+        let protocol_fee = ctx.accounts.fee_schedule.session_fee;
+        if protocol_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.player1.to_account_info(),
+                        to: ctx.accounts.session.to_account_info(),
+                    },
+                ),
+                protocol_fee,
+            )?;
+        }
+
+        let meter = &mut ctx.accounts.meter;
+        meter.session = ctx.accounts.session.key();
+        meter.frames = 0;
+        meter.estimated_cu = 0;
+        meter.last_slot = 0;
+        meter.bump = ctx.bumps.meter;
+
+        let session = &mut ctx.accounts.session.load_init()?;
+
+        // Initialize session state
+        session.status = STATUS_WAITING_PLAYERS;
+        session.frame = 0;
+        session.max_frames = max_frames;
+        session.player1 = ctx.accounts.player1.key();
+        session.player2 = Pubkey::default();
+        session.stage = stage;
+        session.tick_rate = tick_rate;
+        session.model = manifest.key();
+        session.created_at = Clock::get()?.unix_timestamp;
+        session.hidden_state = ctx.accounts.hidden_state.key();
+        session.input_buffer = ctx.accounts.input_buffer.key();
+        session.seed = seed;
+        session.fees_collected = protocol_fee;
+        session.crankers = Default::default();
+        session.crank_mode = CRANK_MODE_PERMISSIONLESS;
+        session.crank_authority = Pubkey::default();
+        session.state_commitment = [0; 32];
+        session.hidden_state_hash = [0; 32];
+
+        // Set player 1 defaults
+        session.players[0] = PlayerState::default();
+        session.players[0].character = character;
+        session.players[0].stocks = 4;
+
+        // Initialize hidden state header (raw AccountInfo)
+        bind_hidden_state(&ctx.accounts.hidden_state, manifest)?;
+
+        // Initialize input buffer
+        let input_buf = &mut ctx.accounts.input_buffer.load_init()?;
+        input_buf.frame = 0;
+        input_buf.p1_ready = 0;
+        input_buf.p2_ready = 0;
+
+        emit!(SessionCreated {
+            session: ctx.accounts.session.key(),
+            model: manifest.key(),
+            player1: ctx.accounts.player1.key(),
+            stage,
+            character,
+            max_frames,
+            tick_rate,
+        });
+        msg!("Session created: player1={}, stage={}", ctx.accounts.player1.key(), stage);
+        Ok(())
+    }
+
+    /// Choose who may advance the session's frames (CRANK_MODE_*):
+    /// anyone, only `cranker`, or only the two players. Player 1 only,
+    /// while waiting for player 2 — who accepts the mode by joining.
+    /// `cranker` is ignored outside CRANK_MODE_DESIGNATED.
+    pub fn set_crank_mode(
+        ctx: Context<SetCrankMode>,
+        mode: u8,
+        cranker: Pubkey,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session.load_mut()?;
+
+        require!(
+            ctx.accounts.player1.key() == session.player1,
+            WorldModelError::UnauthorizedPlayer
+        );
+        require!(
+            session.status == STATUS_WAITING_PLAYERS,
+            WorldModelError::InvalidStateTransition
+        );
+        let crank_authority = match mode {
+            CRANK_MODE_PERMISSIONLESS | CRANK_MODE_PLAYERS => Pubkey::default(),
+            CRANK_MODE_DESIGNATED if cranker != Pubkey::default() => cranker,
+            _ => return err!(WorldModelError::InvalidCrankMode),
+        };
+
+        session.crank_mode = mode;
+        session.crank_authority = crank_authority;
+
+        msg!("Crank mode set: mode={}, cranker={}", mode, crank_authority);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 4. join_session — plug in controller, activate game
+    // ═══════════════════════════════════════════════════════════════════════
+
+    pub fn join_session(
+        ctx: Context<JoinSession>,
+        character: u8,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session.load_mut()?;
+
+        require!(
+            session.status == STATUS_WAITING_PLAYERS,
+            WorldModelError::InvalidStateTransition
+        );
+        require!(
+            ctx.accounts.player2.key() != session.player1,
+            WorldModelError::CannotJoinOwnSession
+        );
+
+        // Set player 2
+        session.player2 = ctx.accounts.player2.key();
+
+        // Initial positions, facing and sides come from the seed and stage,
+        // so a replay re-derives the same frame 0
+        session.players = PlayerState::spawns(
+            [session.players[0].character, character],
+            session.seed,
+            session.stage,
+        );
+
+        session.status = STATUS_ACTIVE;
+
+        emit!(SessionJoined {
+            session: ctx.accounts.session.key(),
+            player2: session.player2,
+            character,
+        });
+        msg!("Player 2 joined: character={}. Session ACTIVE!", character);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 5. close_session / reap_session — power off, end game
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Either player ends the session. Closing a match in progress before
+    /// it finishes forfeits it to the other player.
+    pub fn close_session(
+        ctx: Context<CloseSession>,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session.load_mut()?;
+
+        require!(
+            session.status == STATUS_ACTIVE || session.status == STATUS_WAITING_PLAYERS,
+            WorldModelError::InvalidStateTransition
+        );
+
+        // Verify the closer is a participant
+        let player_key = ctx.accounts.player.key();
+        require!(
+            player_key == session.player1 || player_key == session.player2,
+            WorldModelError::UnauthorizedPlayer
+        );
+
+        // Walking out of a match in progress concedes it
+        if session.status == STATUS_ACTIVE && session.frame > 0 && !session.finished() {
+            session.forfeited_by = if player_key == session.player1 { 1 } else { 2 };
+        }
+        session.status = STATUS_ENDED;
+        session.last_update = Clock::get()?.unix_timestamp;
+
+        emit_session_end(ctx.accounts.session.key(), session, player_key);
+        msg!("Session ended at frame {}", session.frame);
+        Ok(())
+    }
+
+    /// End a session nobody has advanced for its idle timeout
+    /// (SESSION_IDLE_TIMEOUT_SECS at 60fps) and settle its fee.
+    /// Permissionless: the reaper takes REAPER_BPS of the collected fee and
+    /// the rest splits as in settle_session_fees. The session's wager and
+    /// rent loan then settle like any ended session's.
+    pub fn reap_session(ctx: Context<ReapSession>) -> Result<()> {
+        let session_info = ctx.accounts.session.to_account_info();
+        let session = &mut ctx.accounts.session.load_mut()?;
+
+        require!(
+            session.status == STATUS_ACTIVE || session.status == STATUS_WAITING_PLAYERS,
+            WorldModelError::InvalidStateTransition
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let idle_secs = now - session.last_activity();
+        require!(
+            idle_secs >= session.idle_timeout_secs(),
+            WorldModelError::SessionNotIdle
+        );
+
+        let reaper = ctx.accounts.reaper.key();
+        session.status = STATUS_ENDED;
+        session.last_update = now;
+        emit_session_end(ctx.accounts.session.key(), session, reaper);
+
+        let bounty = session.fees_collected * REAPER_BPS as u64 / BPS_DENOMINATOR as u64;
+        pay_from_escrow(&session_info, &ctx.accounts.reaper.to_account_info(), bounty)?;
+        session.fees_collected -= bounty;
+        let (author_share, cranker_share, protocol_share) = split_session_fees(
+            session,
+            &session_info,
+            &ctx.accounts.fee_schedule,
+            &ctx.accounts.author_treasury.to_account_info(),
+            &ctx.accounts.cranker,
+            &ctx.accounts.protocol_treasury,
+        )?;
+
+        emit!(SessionReaped {
+            session: ctx.accounts.session.key(),
+            reaper,
+            idle_secs,
+            bounty,
+        });
+        msg!("Session reaped after {}s idle: reaper={} author={} cranker={} protocol={}",
+             idle_secs, bounty, author_share, cranker_share, protocol_share);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 6. submit_input — receive controller input from a player
+    // ═══════════════════════════════════════════════════════════════════════
+
+    pub fn submit_input(
+        ctx: Context<SubmitInput>,
+        stick_x: i8,
+        stick_y: i8,
+        c_stick_x: i8,
+        c_stick_y: i8,
+        trigger_l: u8,
+        trigger_r: u8,
+        buttons: u8,
+        buttons_ext: u8,
+    ) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let input_buf = &mut ctx.accounts.input_buffer.load_mut()?;
+        let player_key = ctx.accounts.player.key();
+
+        require!(
+            session.status == STATUS_ACTIVE,
+            WorldModelError::SessionNotActive
+        );
+
+        let is_p1 = player_key == session.player1;
+        let is_p2 = player_key == session.player2;
+        require!(
+            is_p1 || is_p2,
+            WorldModelError::UnauthorizedPlayer
+        );
+
+        let controller = ControllerInput {
+            stick_x,
+            stick_y,
+            c_stick_x,
+            c_stick_y,
+            trigger_l,
+            trigger_r,
+            buttons,
+            buttons_ext,
+        };
+
+        if is_p1 {
+            input_buf.player1 = controller;
+            input_buf.p1_ready = 1;
+        } else {
+            input_buf.player2 = controller;
+            input_buf.p2_ready = 1;
+        }
+
+        // Reset other player's ready flag on new frame
+        let expected_frame = session.frame + 1;
+        if input_buf.frame != expected_frame {
+            input_buf.frame = expected_frame;
+            if is_p1 {
+                input_buf.p2_ready = 0;
+            } else {
+                input_buf.p1_ready = 0;
+            }
+        }
+
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 7. run_inference — the heart of the autonomous world
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Advance the session to `frame`, which must be its next frame: a
+    /// retried or racing crank for a frame that already ran fails instead
+    /// of advancing twice, and the inputs must have been submitted for it.
+    pub fn run_inference(
+        ctx: Context<RunInference>,
+        frame: u32,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session.load_mut()?;
+        require!(
+            session.status == STATUS_ACTIVE,
+            WorldModelError::SessionNotActive
+        );
+        require!(frame == session.frame + 1, WorldModelError::FrameMismatch);
+        let inputs = {
+            let input_buf = ctx.accounts.input_buffer.load()?;
+            require!(
+                input_buf.p1_ready != 0 && input_buf.p2_ready != 0 && input_buf.frame == frame,
+                WorldModelError::InputsNotReady
+            );
+            [input_buf.player1, input_buf.player2]
+        };
+
+        // Validate hidden state against the manifest before any scan writes
+        let manifest = &ctx.accounts.manifest;
+        let mut h_data = ctx.accounts.hidden_state.try_borrow_mut_data()?;
+        let header = check_hidden_state(session, manifest, &h_data)?;
+
+        // Remaining accounts (mamba2-inference): the shard directory, the
+        // expert table for mixture-of-experts models, then the shards in
+        // directory order.
+        #[cfg(feature = "mamba2-inference")]
+        let (shard_infos, experts, _) = split_shards(manifest, ctx.remaining_accounts)?;
+        #[cfg(not(feature = "mamba2-inference"))]
+        let (shard_infos, experts): (&[AccountInfo], Option<ExpertTableAccount>) = (&[], None);
+        #[cfg(feature = "mamba2-inference")]
+        require!(
+            shard_infos.iter().any(|s| s.key() == ctx.accounts.weights.key()),
+            WorldModelError::WeightsMismatch
+        );
+        let shard_data = shard_infos
+            .iter()
+            .map(|a| a.try_borrow_data())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let regions = shard_data
+            .iter()
+            .map(|d| d.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[]))
+            .collect::<Vec<&[u8]>>();
+
+        step_session(
+            ctx.accounts.session.key(),
+            session,
+            &mut h_data,
+            header,
+            &mut ctx.accounts.input_buffer.as_ref().try_borrow_mut_data()?,
+            inputs,
+            manifest,
+            &regions,
+            experts.as_ref(),
+            ctx.accounts.cranker.key(),
+            &mut ctx.accounts.meter,
+            ctx.accounts.calibration.as_deref_mut(),
+        )
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 7b. frame_advance — one crank for many sessions
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Advance every ready session of one model in a single instruction, for
+    /// an automated cranker serving many sessions.
+    ///
+    /// Remaining accounts: (mamba2-inference) the shard directory, expert
+    /// table and shards as for run_inference, then SESSION_BUNDLE_LEN
+    /// writable accounts per session — session, hidden state, input buffer,
+    /// meter. A session that isn't active, doesn't let this cranker advance
+    /// it (crank_mode), is still waiting on an input for its next frame, or
+    /// whose bundle doesn't check out is skipped rather than failing the
+    /// batch, so one stalled player can't hold up the rest.
+    ///
+    /// Returns one ADVANCE_* code per bundle, in order; advanced sessions
+    /// emit FrameAdvanced as run_inference does, skipped ones FrameSkipped.
+    pub fn frame_advance<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FrameAdvance<'info>>,
+    ) -> Result<Vec<u8>> {
+        let manifest = &ctx.accounts.manifest;
+        #[cfg(feature = "mamba2-inference")]
+        let (shard_infos, experts, bundles) = split_shards(manifest, ctx.remaining_accounts)?;
+        #[cfg(not(feature = "mamba2-inference"))]
+        let (shard_infos, experts, bundles): (&[AccountInfo], Option<ExpertTableAccount>, _) =
+            (&[], None, ctx.remaining_accounts);
+        require!(
+            !bundles.is_empty() && bundles.len() % SESSION_BUNDLE_LEN == 0,
+            WorldModelError::SessionBundleMismatch
+        );
+
+        let shard_data = shard_infos
+            .iter()
+            .map(|a| a.try_borrow_data())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let regions = shard_data
+            .iter()
+            .map(|d| d.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[]))
+            .collect::<Vec<&[u8]>>();
+
+        let cranker = ctx.accounts.cranker.key();
+        let mut codes = Vec::with_capacity(bundles.len() / SESSION_BUNDLE_LEN);
+        for bundle in bundles.chunks_exact(SESSION_BUNDLE_LEN) {
+            let code = advance_bundle(bundle, manifest, &regions, experts.as_ref(), cranker)?;
+            if code != ADVANCE_ADVANCED {
+                emit!(FrameSkipped {
+                    session: bundle[0].key(),
+                    frame: session_frame(&bundle[0]),
+                    reason: code,
+                });
+            }
+            codes.push(code);
+        }
+
+        let advanced = codes.iter().filter(|&&c| c == ADVANCE_ADVANCED).count();
+        msg!("frame_advance: {}/{} sessions advanced", advanced, codes.len());
+        Ok(codes)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 8. reset_hidden_state — wipe the world's memory between matches
+    // ═══════════════════════════════════════════════════════════════════════
+
+    pub fn reset_hidden_state(
+        ctx: Context<ResetHiddenState>,
+    ) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let manifest = &ctx.accounts.manifest;
+
+        require!(
+            session.status != STATUS_ACTIVE,
+            WorldModelError::SessionActive
+        );
+        require!(
+            session.model == manifest.key(),
+            WorldModelError::ModelMismatch
+        );
+
+        // Either participant or the model authority may reset
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == session.player1
+                || signer == session.player2
+                || manifest.is_authority(&signer),
+            WorldModelError::Unauthorized
+        );
+
+        let hidden = &ctx.accounts.hidden_state;
+        let mut h_data = hidden.try_borrow_mut_data()?;
+        require!(
+            h_data.len() >= HIDDEN_HEADER_SIZE,
+            WorldModelError::InsufficientData
+        );
+
+        let mut header = read_hidden_header(&h_data);
+        let data_end = HIDDEN_HEADER_SIZE + header.data_size as usize;
+        require!(
+            h_data.len() >= data_end,
+            WorldModelError::InsufficientData
+        );
+
+        h_data[HIDDEN_HEADER_SIZE..data_end].fill(0);
+        header.frame = 0;
+        header.initialized = false;
+        write_hidden_header(&mut h_data, &header);
+
+        msg!("Hidden state reset ({} bytes zeroed)", header.data_size);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 9. read_hidden_state — debug view of what the world "remembers"
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Return rows [row_start, row_start + row_count) of one layer's h matrix
+    /// as raw bytes (INT8, or INT16 LE per the header dtype) via return data.
+    /// Read-only — simulate the transaction to fetch the result.
+    pub fn read_hidden_state(
+        ctx: Context<ReadHiddenState>,
+        layer: u8,
+        row_start: u16,
+        row_count: u16,
+    ) -> Result<Vec<u8>> {
+        let h_data = ctx.accounts.hidden_state.try_borrow_data()?;
+        require!(
+            h_data.len() >= HIDDEN_HEADER_SIZE,
+            WorldModelError::InsufficientData
+        );
+
+        let header = read_hidden_header(&h_data);
+        let range = hidden_layer_range(&header, layer, row_start, row_count)
+            .ok_or(WorldModelError::HiddenRangeOutOfBounds)?;
+        require!(
+            range.len() <= MAX_RETURN_DATA,
+            WorldModelError::HiddenRangeOutOfBounds
+        );
+        require!(
+            h_data.len() >= range.end,
+            WorldModelError::InsufficientData
+        );
+
+        Ok(h_data[range].to_vec())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 10. warm_start_session — carry the world's memory into a new match
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Initialize a freshly created session's hidden state by copying it from
+    /// an ended session of the same model ("continue the saga").
+    pub fn warm_start_session(
+        ctx: Context<WarmStartSession>,
+    ) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let source_session = &ctx.accounts.source_session.load()?;
+        let manifest = &ctx.accounts.manifest;
+
+        require!(
+            session.status == STATUS_WAITING_PLAYERS && session.frame == 0,
+            WorldModelError::InvalidStateTransition
+        );
+        require!(
+            source_session.status == STATUS_ENDED,
+            WorldModelError::InvalidStateTransition
+        );
+        require!(
+            ctx.accounts.player1.key() == session.player1,
+            WorldModelError::UnauthorizedPlayer
+        );
+        require!(
+            session.model == manifest.key() && source_session.model == manifest.key(),
+            WorldModelError::ModelMismatch
+        );
+
+        let src_data = ctx.accounts.source_hidden_state.try_borrow_data()?;
+        let mut dst_data = ctx.accounts.hidden_state.try_borrow_mut_data()?;
+        require!(
+            src_data.len() >= HIDDEN_HEADER_SIZE && dst_data.len() >= HIDDEN_HEADER_SIZE,
+            WorldModelError::InsufficientData
+        );
+
+        let src_header = read_hidden_header(&src_data);
+        let mut dst_header = read_hidden_header(&dst_data);
+        require!(
+            src_header.matches_manifest(manifest) && dst_header.matches_manifest(manifest),
+            WorldModelError::HiddenStateMismatch
+        );
+
+        let data_end = HIDDEN_HEADER_SIZE + dst_header.data_size as usize;
+        require!(
+            src_data.len() >= data_end && dst_data.len() >= data_end,
+            WorldModelError::InsufficientData
+        );
+
+        dst_data[HIDDEN_HEADER_SIZE..data_end]
+            .copy_from_slice(&src_data[HIDDEN_HEADER_SIZE..data_end]);
+        dst_header.initialized = true;
+        write_hidden_header(&mut dst_data, &dst_header);
+
+        msg!("Hidden state warm-started from session {} (frame {})",
+             ctx.accounts.source_session.key(), src_header.frame);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 11. checkpoint_hidden_state — compact record of an ended session
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Compress an ended session's hidden state into its checkpoint PDA.
+    /// The checkpoint is what gets committed to mainnet instead of the raw
+    /// ~200KB state, and can seed later sessions via restore_checkpoint.
+    ///
+    /// An instruction can add at most MAX_PERMITTED_DATA_INCREASE bytes to
+    /// an account, so a state that doesn't compress into the account yet
+    /// grows it (the player pays the rent) and returns; call again until
+    /// the header is written. A written checkpoint is never overwritten.
+    pub fn checkpoint_hidden_state(
+        ctx: Context<CheckpointHiddenState>,
+    ) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+
+        require!(
+            session.status == STATUS_ENDED,
+            WorldModelError::InvalidStateTransition
+        );
+        let player_key = ctx.accounts.player.key();
+        require!(
+            player_key == session.player1 || player_key == session.player2,
+            WorldModelError::UnauthorizedPlayer
+        );
+
+        let h_data = ctx.accounts.hidden_state.try_borrow_data()?;
+        require!(
+            h_data.len() >= HIDDEN_HEADER_SIZE,
+            WorldModelError::InsufficientData
+        );
+        let h_header = read_hidden_header(&h_data);
+        let data_end = HIDDEN_HEADER_SIZE + h_header.data_size as usize;
+        require!(
+            h_data.len() >= data_end,
+            WorldModelError::InsufficientData
+        );
+        let bound = CHECKPOINT_HEADER_SIZE + checkpoint::rle_bound(h_header.data_size as usize);
+
+        let created = ctx.accounts.checkpoint.owner == &crate::ID;
+        if !created {
+            grow_checkpoint(ctx.accounts, ctx.bumps.checkpoint, bound)?;
+        } else {
+            require!(
+                read_checkpoint_header(&ctx.accounts.checkpoint.try_borrow_data()?).session
+                    == Pubkey::default(),
+                WorldModelError::CheckpointInUse
+            );
+        }
+
+        let mut c_data = ctx.accounts.checkpoint.try_borrow_mut_data()?;
+        require!(
+            c_data.len() >= CHECKPOINT_HEADER_SIZE,
+            WorldModelError::CheckpointTooLarge
+        );
+        let Some(compressed_size) = checkpoint::rle_compress(
+            &h_data[HIDDEN_HEADER_SIZE..data_end],
+            &mut c_data[CHECKPOINT_HEADER_SIZE..],
+        ) else {
+            // Not written: clear what the attempt left behind
+            c_data.fill(0);
+            let len = c_data.len();
+            require!(len < bound, WorldModelError::CheckpointTooLarge);
+            drop(c_data);
+            if created {
+                grow_checkpoint(ctx.accounts, ctx.bumps.checkpoint, bound)?;
+            }
+            msg!("Checkpoint grown to {} of up to {} bytes; call again",
+                 ctx.accounts.checkpoint.data_len(), bound);
+            return Ok(());
+        };
+
+        write_checkpoint_header(
+            &mut c_data,
+            &CheckpointHeader {
+                session: ctx.accounts.session.key(),
+                model: session.model,
+                frame: h_header.frame,
+                num_layers: h_header.num_layers,
+                dtype: h_header.dtype,
+                d_inner: h_header.d_inner,
+                d_state: h_header.d_state,
+                raw_size: h_header.data_size,
+                compressed_size: compressed_size as u32,
+                state_commitment: session.state_commitment,
+            },
+        );
+
+        msg!("Hidden state checkpointed: {} → {} bytes",
+             h_header.data_size, compressed_size);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 12. restore_checkpoint — warm-start a session from a checkpoint
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Seed a waiting session's hidden state from the checkpoint of an
+    /// earlier session player 1 played in.
+    pub fn restore_checkpoint(
+        ctx: Context<RestoreCheckpoint>,
+    ) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let source_session = &ctx.accounts.source_session.load()?;
+        let manifest = &ctx.accounts.manifest;
+
+        require!(
+            session.status == STATUS_WAITING_PLAYERS && session.frame == 0,
+            WorldModelError::InvalidStateTransition
+        );
+        let player1 = ctx.accounts.player1.key();
+        require!(
+            player1 == session.player1
+                && (player1 == source_session.player1 || player1 == source_session.player2),
+            WorldModelError::UnauthorizedPlayer
+        );
+
+        let c_data = ctx.accounts.checkpoint.try_borrow_data()?;
+        let mut h_data = ctx.accounts.hidden_state.try_borrow_mut_data()?;
+        require!(
+            c_data.len() >= CHECKPOINT_HEADER_SIZE && h_data.len() >= HIDDEN_HEADER_SIZE,
+            WorldModelError::InsufficientData
+        );
+
+        let c_header = read_checkpoint_header(&c_data);
+        require!(
+            c_header.session == ctx.accounts.source_session.key(),
+            WorldModelError::SessionAccountMismatch
+        );
+        require!(
+            session.model == manifest.key() && c_header.model == manifest.key(),
+            WorldModelError::ModelMismatch
+        );
+
+        let mut h_header = read_hidden_header(&h_data);
+        require!(
+            h_header.matches_manifest(manifest)
+                && c_header.num_layers == h_header.num_layers
+                && c_header.d_inner == h_header.d_inner
+                && c_header.d_state == h_header.d_state
+                && c_header.dtype == h_header.dtype
+                && c_header.raw_size == h_header.data_size,
+            WorldModelError::HiddenStateMismatch
+        );
+
+        let stream_end = CHECKPOINT_HEADER_SIZE + c_header.compressed_size as usize;
+        let data_end = HIDDEN_HEADER_SIZE + h_header.data_size as usize;
+        require!(
+            c_data.len() >= stream_end && h_data.len() >= data_end,
+            WorldModelError::InsufficientData
+        );
+
+        let restored = checkpoint::rle_decompress(
+            &c_data[CHECKPOINT_HEADER_SIZE..stream_end],
+            &mut h_data[HIDDEN_HEADER_SIZE..data_end],
+        )
+        .ok_or(WorldModelError::CheckpointCorrupt)?;
+        require!(
+            restored == h_header.data_size as usize,
+            WorldModelError::CheckpointCorrupt
+        );
+
+        h_header.initialized = true;
+        write_hidden_header(&mut h_data, &h_header);
+
+        msg!("Hidden state restored from checkpoint of session {} (frame {})",
+             c_header.session, c_header.frame);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 13. Wagers — escrowed stakes paid out to the winner
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Player 1 opens a wager on a waiting session and deposits `amount`.
+    pub fn create_wager(ctx: Context<CreateWager>, amount: u64) -> Result<()> {
+        check_new_wager(&*ctx.accounts.session.load()?, &ctx.accounts.player1.key(), amount)?;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.player1.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.session = ctx.accounts.session.key();
+        escrow.mint = Pubkey::default();
+        escrow.amount = amount;
+        escrow.p1_deposited = true;
+        escrow.p2_deposited = false;
+        escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+
+        msg!("Wager created: {} lamports", amount);
+        Ok(())
+    }
+
+    /// Player 2 matches the wager. Call in the same transaction as
+    /// join_session — it requires the player 2 seat and frame 0.
+    pub fn accept_wager(ctx: Context<AcceptWager>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        check_wager_accept(&*ctx.accounts.session.load()?, &ctx.accounts.player2.key(), escrow)?;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.player2.to_account_info(),
+                    to: escrow.to_account_info(),
+                },
+            ),
+            escrow.amount,
+        )?;
+        escrow.p2_deposited = true;
+
+        msg!("Wager accepted: pot={} lamports", escrow.amount * 2);
+        Ok(())
+    }
+
+    /// Pay out an ended session's wager. Permissionless.
+    ///
+    /// Winner takes the pot; draws split it; unplayed sessions (or a wager
+    /// player 2 never matched) refund each deposit. The escrow's rent goes
+    /// back to player 1.
+    pub fn settle_wager(ctx: Context<SettleWager>) -> Result<()> {
+        let (p1_payout, p2_payout) =
+            wager_payouts(&*ctx.accounts.session.load()?, &ctx.accounts.escrow)?;
+
+        pay_from_escrow(&ctx.accounts.escrow.to_account_info(), &ctx.accounts.player1, p1_payout)?;
+        pay_from_escrow(&ctx.accounts.escrow.to_account_info(), &ctx.accounts.player2, p2_payout)?;
+
+        msg!("Wager settled: p1={} p2={}", p1_payout, p2_payout);
+        Ok(())
+    }
+
+    /// Refund both deposits if the session hasn't ended within
+    /// WAGER_TIMEOUT_SECS of the wager opening. Permissionless. Ended
+    /// sessions must settle instead, so a loser can't wait out the timeout.
+    pub fn refund_wager(ctx: Context<SettleWager>) -> Result<()> {
+        let (p1_refund, p2_refund) =
+            wager_refunds(&*ctx.accounts.session.load()?, &ctx.accounts.escrow)?;
+
+        pay_from_escrow(&ctx.accounts.escrow.to_account_info(), &ctx.accounts.player1, p1_refund)?;
+        pay_from_escrow(&ctx.accounts.escrow.to_account_info(), &ctx.accounts.player2, p2_refund)?;
+
+        msg!("Wager refunded after timeout: p1={} p2={}", p1_refund, p2_refund);
+        Ok(())
+    }
+
+    /// Player 1 opens a wager staked in an SPL mint and deposits `amount`
+    /// into the escrow's associated token account, created here.
+    pub fn create_token_wager(ctx: Context<CreateTokenWager>, amount: u64) -> Result<()> {
+        check_new_wager(&*ctx.accounts.session.load()?, &ctx.accounts.player1.key(), amount)?;
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.player1_token.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.player1.to_account_info(),
+            &[],
+            amount,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.session = ctx.accounts.session.key();
+        escrow.mint = ctx.accounts.mint.key();
+        escrow.amount = amount;
+        escrow.p1_deposited = true;
+        escrow.p2_deposited = false;
+        escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+
+        msg!("Wager created: {} of mint {}", amount, escrow.mint);
+        Ok(())
+    }
+
+    /// Player 2 matches an SPL wager. Same timing as accept_wager.
+    pub fn accept_token_wager(ctx: Context<AcceptTokenWager>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        check_wager_accept(&*ctx.accounts.session.load()?, &ctx.accounts.player2.key(), escrow)?;
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.player2_token.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.player2.to_account_info(),
+            &[],
+            escrow.amount,
+        )?;
+        escrow.p2_deposited = true;
+
+        msg!("Wager accepted: pot={} of mint {}", escrow.amount * 2, escrow.mint);
+        Ok(())
+    }
+
+    /// settle_wager for an SPL wager. The escrow's token account is closed
+    /// and both rents go back to player 1.
+    pub fn settle_token_wager(ctx: Context<SettleTokenWager>) -> Result<()> {
+        let (p1_payout, p2_payout) =
+            wager_payouts(&*ctx.accounts.session.load()?, &ctx.accounts.escrow)?;
+        pay_token_wager(ctx.accounts, p1_payout, p2_payout)?;
+
+        msg!("Wager settled: p1={} p2={}", p1_payout, p2_payout);
+        Ok(())
+    }
+
+    /// refund_wager for an SPL wager.
+    pub fn refund_token_wager(ctx: Context<SettleTokenWager>) -> Result<()> {
+        let (p1_refund, p2_refund) =
+            wager_refunds(&*ctx.accounts.session.load()?, &ctx.accounts.escrow)?;
+        pay_token_wager(ctx.accounts, p1_refund, p2_refund)?;
+
+        msg!("Wager refunded after timeout: p1={} p2={}", p1_refund, p2_refund);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 14. Session fees — split between model author, cranker, and protocol
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Create the singleton fee schedule. Signer must be the program's
+    /// upgrade authority, and becomes the schedule's authority. Run once
+    /// after deploy: create_session reads the schedule.
+    pub fn init_fee_schedule(
+        ctx: Context<InitFeeSchedule>,
+        protocol_treasury: Pubkey,
+        session_fee: u64,
+        author_bps: u16,
+        cranker_bps: u16,
+        protocol_bps: u16,
+    ) -> Result<()> {
+        let schedule = &mut ctx.accounts.fee_schedule;
+        schedule.authority = ctx.accounts.authority.key();
+        schedule.bump = ctx.bumps.fee_schedule;
+        schedule.set(protocol_treasury, session_fee, author_bps, cranker_bps, protocol_bps)?;
+
+        msg!("Fee schedule initialized: fee={} split={}/{}/{}",
+             session_fee, author_bps, cranker_bps, protocol_bps);
+        Ok(())
+    }
+
+    /// Replace the fee schedule. Authority only; applies to new sessions.
+    pub fn update_fee_schedule(
+        ctx: Context<UpdateFeeSchedule>,
+        protocol_treasury: Pubkey,
+        session_fee: u64,
+        author_bps: u16,
+        cranker_bps: u16,
+        protocol_bps: u16,
+    ) -> Result<()> {
+        let schedule = &mut ctx.accounts.fee_schedule;
+        schedule.set(protocol_treasury, session_fee, author_bps, cranker_bps, protocol_bps)?;
+
+        msg!("Fee schedule updated: fee={} split={}/{}/{}",
+             session_fee, author_bps, cranker_bps, protocol_bps);
+        Ok(())
+    }
+
+    /// Split an ended session's collected fee. Permissionless.
+    ///
+    /// The author share goes to the manifest's royalty treasury, the cranker
+    /// share to the session's top cranker (or the protocol if nobody
+    /// cranked), and the rest — including rounding dust — to the protocol.
+    /// Uses the split in effect at settlement time.
+    pub fn settle_session_fees(ctx: Context<SettleSessionFees>) -> Result<()> {
+        let session = &mut ctx.accounts.session.load_mut()?;
+        require!(
+            session.status == STATUS_ENDED,
+            WorldModelError::SessionNotEnded
+        );
+
+        let (author_share, cranker_share, protocol_share) = split_session_fees(
+            session,
+            &ctx.accounts.session.to_account_info(),
+            &ctx.accounts.fee_schedule,
+            &ctx.accounts.author_treasury.to_account_info(),
+            &ctx.accounts.cranker,
+            &ctx.accounts.protocol_treasury,
+        )?;
+
+        msg!("Session fees settled: author={} cranker={} protocol={}",
+             author_share, cranker_share, protocol_share);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 15. Spectator tips
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Tip a player. Lamports go to the session stats account and are
+    /// credited to the player's claimable balance.
+    pub fn tip_player(ctx: Context<TipPlayer>, player_slot: u8, amount: u64) -> Result<()> {
+        tipped_player(&*ctx.accounts.session.load()?, player_slot)?;
+        require!(amount > 0, WorldModelError::InvalidAmount);
+        let slot = player_slot as usize;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.tipper.to_account_info(),
+                    to: ctx.accounts.stats.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stats = &mut ctx.accounts.stats;
+        stats.session = ctx.accounts.session.key();
+        stats.bump = ctx.bumps.stats;
+        stats.tips_total[slot] += amount;
+        stats.tips_claimable[slot] += amount;
+        stats.tip_count[slot] += 1;
+
+        msg!("Tip: player {} +{} lamports (total {})", slot, amount, stats.tips_total[slot]);
+        Ok(())
+    }
+
+    /// Withdraw a player's claimable tips. Signer must be that player.
+    pub fn claim_tips(ctx: Context<ClaimTips>, player_slot: u8) -> Result<()> {
+        let player = tipped_player(&*ctx.accounts.session.load()?, player_slot)?;
+        require!(
+            ctx.accounts.player.key() == player,
+            WorldModelError::UnauthorizedPlayer
+        );
+        let slot = player_slot as usize;
+
+        let amount = ctx.accounts.stats.tips_claimable[slot];
+        require!(amount > 0, WorldModelError::NothingToClaim);
+
+        pay_from_escrow(
+            &ctx.accounts.stats.to_account_info(),
+            &ctx.accounts.player.to_account_info(),
+            amount,
+        )?;
+        ctx.accounts.stats.tips_claimable[slot] = 0;
+
+        msg!("Tips claimed: player {} {} lamports", slot, amount);
+        Ok(())
+    }
+
+    /// tip_player in an SPL mint. The tokens go to the session's tip jar
+    /// for that mint, created here on the first tip.
+    pub fn tip_player_token(ctx: Context<TipPlayerToken>, player_slot: u8, amount: u64) -> Result<()> {
+        tipped_player(&*ctx.accounts.session.load()?, player_slot)?;
+        require!(amount > 0, WorldModelError::InvalidAmount);
+        let slot = player_slot as usize;
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.tipper_token.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.tipper.to_account_info(),
+            &[],
+            amount,
+        )?;
+
+        let jar = &mut ctx.accounts.jar;
+        jar.session = ctx.accounts.session.key();
+        jar.mint = ctx.accounts.mint.key();
+        jar.bump = ctx.bumps.jar;
+        jar.tips_total[slot] += amount;
+        jar.tips_claimable[slot] += amount;
+        jar.tip_count[slot] += 1;
+
+        msg!("Tip: player {} +{} of mint {} (total {})", slot, amount, jar.mint, jar.tips_total[slot]);
+        Ok(())
+    }
+
+    /// claim_tips for one SPL mint's tip jar, into a token account the
+    /// player owns.
+    pub fn claim_token_tips(ctx: Context<ClaimTokenTips>, player_slot: u8) -> Result<()> {
+        let player = tipped_player(&*ctx.accounts.session.load()?, player_slot)?;
+        require!(
+            ctx.accounts.player.key() == player,
+            WorldModelError::UnauthorizedPlayer
+        );
+        let slot = player_slot as usize;
+
+        let jar = &ctx.accounts.jar;
+        let amount = jar.tips_claimable[slot];
+        require!(amount > 0, WorldModelError::NothingToClaim);
+
+        let (session_key, mint_key) = (jar.session, jar.mint);
+        let bump = [jar.bump];
+        let seeds: &[&[u8]] = &[TIP_JAR_SEED, session_key.as_ref(), mint_key.as_ref(), &bump];
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.player_token.to_account_info(),
+            jar.to_account_info(),
+            &[seeds],
+            amount,
+        )?;
+        ctx.accounts.jar.tips_claimable[slot] = 0;
+
+        msg!("Tips claimed: player {} {} of mint {}", slot, amount, mint_key);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 16. Rent pool — front session account rent, recoup on close
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Create the singleton rent pool.
+    pub fn init_rent_pool(ctx: Context<InitRentPool>) -> Result<()> {
+        let pool = &mut ctx.accounts.rent_pool;
+        pool.total_deposited = 0;
+        pool.total_outstanding = 0;
+        pool.bump = ctx.bumps.rent_pool;
+
+        msg!("Rent pool initialized");
+        Ok(())
+    }
+
+    /// Deposit lamports into the rent pool.
+    pub fn deposit_rent_pool(ctx: Context<DepositRentPool>, amount: u64) -> Result<()> {
+        require!(amount > 0, WorldModelError::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.rent_pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let position = &mut ctx.accounts.position;
+        position.funder = ctx.accounts.funder.key();
+        position.deposited += amount;
+        position.bump = ctx.bumps.position;
+        ctx.accounts.rent_pool.total_deposited += amount;
+
+        msg!("Rent pool deposit: {} lamports (position {})", amount, position.deposited);
+        Ok(())
+    }
+
+    /// Withdraw up to the funder's deposit, limited to lamports not
+    /// currently lent out.
+    pub fn withdraw_rent_pool(ctx: Context<WithdrawRentPool>, amount: u64) -> Result<()> {
+        require!(
+            amount <= ctx.accounts.position.deposited,
+            WorldModelError::WithdrawExceedsDeposit
+        );
+        require!(
+            amount <= rent_pool_free(&ctx.accounts.rent_pool)?,
+            WorldModelError::RentPoolInsufficient
+        );
+
+        pay_from_escrow(
+            &ctx.accounts.rent_pool.to_account_info(),
+            &ctx.accounts.funder.to_account_info(),
+            amount,
+        )?;
+        ctx.accounts.position.deposited -= amount;
+        ctx.accounts.rent_pool.total_deposited -= amount;
+
+        msg!("Rent pool withdrawal: {} lamports", amount);
+        Ok(())
+    }
+
+    /// Reimburse player 1 for the rent of a new session's hidden state and
+    /// input buffer. The session record itself stays player 1's: wagers,
+    /// tips, tournaments and replays read it after the match.
+    pub fn borrow_session_rent(ctx: Context<BorrowSessionRent>) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        require!(
+            session.status == STATUS_WAITING_PLAYERS,
+            WorldModelError::InvalidStateTransition
+        );
+        require!(
+            session.hidden_state == ctx.accounts.hidden_state.key()
+                && session.input_buffer == ctx.accounts.input_buffer.key(),
+            WorldModelError::SessionAccountMismatch
+        );
+
+        let rent = Rent::get()?;
+        let amount = rent.minimum_balance(ctx.accounts.hidden_state.data_len())
+            + rent.minimum_balance(ctx.accounts.input_buffer.data_len());
+        require!(
+            amount <= rent_pool_free(&ctx.accounts.rent_pool)?,
+            WorldModelError::RentPoolInsufficient
+        );
+
+        pay_from_escrow(
+            &ctx.accounts.rent_pool.to_account_info(),
+            &ctx.accounts.player1.to_account_info(),
+            amount,
+        )?;
+        ctx.accounts.rent_pool.total_outstanding += amount;
+
+        let loan = &mut ctx.accounts.loan;
+        loan.session = ctx.accounts.session.key();
+        loan.borrower = ctx.accounts.player1.key();
+        loan.amount = amount;
+        loan.bump = ctx.bumps.loan;
+
+        msg!("Session rent fronted: {} lamports", amount);
+        Ok(())
+    }
+
+    /// Close an ended session's hidden state and input buffer and repay its
+    /// rent loan. Permissionless once the session's wager is settled and
+    /// its tips claimed. The loan amount returns to the pool; anything above
+    /// it (plus the loan account's rent) goes back to the borrower. The
+    /// session record stays open.
+    pub fn repay_session_rent(ctx: Context<RepaySessionRent>) -> Result<()> {
+        let session = ctx.accounts.session.load()?;
+        require!(
+            session.status == STATUS_ENDED,
+            WorldModelError::SessionNotEnded
+        );
+        require!(session.fees_collected == 0, WorldModelError::FeesUnsettled);
+        require!(
+            session.hidden_state == ctx.accounts.hidden_state.key()
+                && session.input_buffer == ctx.accounts.input_buffer.key(),
+            WorldModelError::SessionAccountMismatch
+        );
+        // Closed escrows and stats go back to the system program
+        require!(
+            ctx.accounts.wager.owner != &crate::ID,
+            WorldModelError::WagerUnsettled
+        );
+        if ctx.accounts.stats.owner == &crate::ID {
+            let stats = SessionStatsAccount::try_deserialize(
+                &mut &ctx.accounts.stats.try_borrow_data()?[..],
+            )?;
+            require!(
+                stats.tips_claimable.iter().all(|&t| t == 0),
+                WorldModelError::TipsUnclaimed
+            );
+        }
+
+        let pool_info = ctx.accounts.rent_pool.to_account_info();
+        let borrower = ctx.accounts.borrower.to_account_info();
+        let mut recovered = 0u64;
+        for account in [
+            ctx.accounts.hidden_state.to_account_info(),
+            ctx.accounts.input_buffer.to_account_info(),
+        ] {
+            let lamports = account.lamports();
+            recovered += lamports;
+            pay_from_escrow(&account, &pool_info, lamports)?;
+            account.assign(&system_program::ID);
+            account.resize(0)?;
+        }
+
+        let loan = ctx.accounts.loan.amount;
+        pay_from_escrow(&pool_info, &borrower, recovered.saturating_sub(loan))?;
+        let pool = &mut ctx.accounts.rent_pool;
+        pool.total_outstanding = pool.total_outstanding.saturating_sub(loan);
+
+        msg!("Session rent repaid: {} lamports ({} recovered)", loan, recovered);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 17. Tournaments — prize pool escrow with bracket-driven payouts
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Open a tournament for registration.
+    pub fn create_tournament(
+        ctx: Context<CreateTournament>,
+        id: u64,
+        max_entrants: u8,
+        entry_fee: u64,
+        payout_bps: [u16; 3],
+    ) -> Result<()> {
+        require!(
+            matches!(max_entrants, 2 | 4 | 8),
+            WorldModelError::InvalidBracketSize
+        );
+        require!(
+            payout_bps.iter().map(|&b| b as u32).sum::<u32>() == BPS_DENOMINATOR as u32,
+            WorldModelError::InvalidFeeSplit
+        );
+
+        let t = &mut ctx.accounts.tournament;
+        t.organizer = ctx.accounts.organizer.key();
+        t.id = id;
+        t.entry_fee = entry_fee;
+        t.payout_bps = payout_bps;
+        t.max_entrants = max_entrants;
+        t.num_entrants = 0;
+        t.entrants = [Pubkey::default(); MAX_ENTRANTS];
+        t.bracket = [Pubkey::default(); BRACKET_NODES];
+        t.matches = [Pubkey::default(); MAX_ENTRANTS - 1];
+        t.started_at = 0;
+        t.bump = ctx.bumps.tournament;
+
+        msg!("Tournament {} created: {} entrants, fee={}", id, max_entrants, entry_fee);
+        Ok(())
+    }
+
+    /// Register and pay the entry fee into the prize pool.
+    pub fn join_tournament(ctx: Context<JoinTournament>) -> Result<()> {
+        let entrant = ctx.accounts.entrant.key();
+        {
+            let t = &ctx.accounts.tournament;
+            require!(
+                t.started_at == 0 && t.num_entrants < t.max_entrants,
+                WorldModelError::RegistrationClosed
+            );
+            require!(
+                !t.entrants[..t.num_entrants as usize].contains(&entrant),
+                WorldModelError::RegistrationClosed
+            );
+        }
+
+        let entry_fee = ctx.accounts.tournament.entry_fee;
+        if entry_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.entrant.to_account_info(),
+                        to: ctx.accounts.tournament.to_account_info(),
+                    },
+                ),
+                entry_fee,
+            )?;
+        }
+
+        let t = &mut ctx.accounts.tournament;
+        let slot = t.num_entrants as usize;
+        t.entrants[slot] = entrant;
+        t.num_entrants += 1;
+
+        msg!("Entrant {} joined ({}/{})", entrant, t.num_entrants, t.max_entrants);
+        Ok(())
+    }
+
+    /// Add a sponsor deposit to the prize pool. Anyone, any time before payout.
+    pub fn sponsor_tournament(ctx: Context<SponsorTournament>, amount: u64) -> Result<()> {
+        require!(amount > 0, WorldModelError::InvalidAmount);
+        require!(
+            ctx.accounts.tournament.bracket[0] == Pubkey::default(),
+            WorldModelError::TournamentNotRunning
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sponsor.to_account_info(),
+                    to: ctx.accounts.tournament.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        msg!("Tournament sponsored: +{} lamports", amount);
+        Ok(())
+    }
+
+    /// Close registration and seed the bracket in join order. Organizer only;
+    /// the bracket must be full.
+    pub fn start_tournament(ctx: Context<StartTournament>) -> Result<()> {
+        let t = &mut ctx.accounts.tournament;
+        require!(
+            t.started_at == 0 && t.num_entrants == t.max_entrants,
+            WorldModelError::RegistrationClosed
+        );
+
+        let first_leaf = t.first_leaf();
+        for i in 0..t.max_entrants as usize {
+            t.bracket[first_leaf + i] = t.entrants[i];
+        }
+        t.started_at = Clock::get()?.unix_timestamp;
+
+        msg!("Tournament {} started", t.id);
+        Ok(())
+    }
+
+    /// Schedule `session` as the match for bracket node `node`. Organizer
+    /// only; its two players must be the node's children. A node may be
+    /// rescheduled (e.g. onto a fresh session after a stalled one) until it
+    /// is decided.
+    pub fn schedule_match(ctx: Context<ScheduleMatch>, node: u8) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let t = &mut ctx.accounts.tournament;
+        let node = node as usize;
+
+        require!(
+            t.started_at != 0 && t.bracket[0] == Pubkey::default(),
+            WorldModelError::TournamentNotRunning
+        );
+        require!(
+            t.is_match_node(node) && t.bracket[node] == Pubkey::default(),
+            WorldModelError::InvalidBracketNode
+        );
+
+        let (a, b) = (t.bracket[2 * node + 1], t.bracket[2 * node + 2]);
+        require!(
+            a != Pubkey::default() && b != Pubkey::default(),
+            WorldModelError::InvalidBracketNode
+        );
+        let players_match = (session.player1 == a && session.player2 == b)
+            || (session.player1 == b && session.player2 == a);
+        require!(
+            players_match && session.created_at >= t.started_at,
+            WorldModelError::SessionNotBracketMatch
+        );
+        t.matches[node] = ctx.accounts.session.key();
+
+        msg!("Bracket node {} scheduled: {}", node, t.matches[node]);
+        Ok(())
+    }
+
+    /// Advance the winner of node `node`'s scheduled session into the
+    /// bracket. Permissionless once that session has ended with a winner.
+    pub fn record_match(ctx: Context<RecordMatch>, node: u8) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        let t = &mut ctx.accounts.tournament;
+        let node = node as usize;
+
+        require!(
+            t.started_at != 0 && t.bracket[0] == Pubkey::default(),
+            WorldModelError::TournamentNotRunning
+        );
+        require!(
+            t.is_match_node(node) && t.bracket[node] == Pubkey::default(),
+            WorldModelError::InvalidBracketNode
+        );
+        require!(
+            t.matches[node] == ctx.accounts.session.key() && session.status == STATUS_ENDED,
+            WorldModelError::SessionNotBracketMatch
+        );
+
+        let winner = match session.outcome() {
+            SessionOutcome::Winner(0) => session.player1,
+            SessionOutcome::Winner(_) => session.player2,
+            _ => return err!(WorldModelError::SessionNotBracketMatch),
+        };
+        t.bracket[node] = winner;
+
+        msg!("Bracket node {} won by {}", node, winner);
+        Ok(())
+    }
+
+    /// Pay out the prize pool once the final is decided. Permissionless;
+    /// payout accounts are pinned to the standings. The organizer gets the
+    /// tournament account's rent back.
+    pub fn distribute_prizes(ctx: Context<DistributePrizes>) -> Result<()> {
+        let t = &ctx.accounts.tournament;
+        require!(t.bracket[0] != Pubkey::default(), WorldModelError::TournamentIncomplete);
+
+        let first = t.bracket[0];
+        let second = t.loser(0);
+        require!(
+            ctx.accounts.first.key() == first && ctx.accounts.second.key() == second,
+            WorldModelError::PayoutMismatch
+        );
+
+        let info = t.to_account_info();
+        let reserve = Rent::get()?.minimum_balance(info.data_len());
+        let pot = info.lamports().saturating_sub(reserve);
+        let bps = |i: usize| pot * t.payout_bps[i] as u64 / BPS_DENOMINATOR as u64;
+
+        let second_prize = bps(1);
+        let mut third_prizes = [0u64; 2];
+        if t.max_entrants >= 4 {
+            require!(
+                ctx.accounts.third_a.key() == t.loser(1)
+                    && ctx.accounts.third_b.key() == t.loser(2),
+                WorldModelError::PayoutMismatch
+            );
+            third_prizes = [bps(2) / 2, bps(2) / 2];
+        }
+        // 1st takes its share plus rounding dust (and 3rd in a 2-player bracket)
+        let first_prize = pot - second_prize - third_prizes[0] - third_prizes[1];
+
+        pay_from_escrow(&info, &ctx.accounts.first, first_prize)?;
+        pay_from_escrow(&info, &ctx.accounts.second, second_prize)?;
+        pay_from_escrow(&info, &ctx.accounts.third_a, third_prizes[0])?;
+        pay_from_escrow(&info, &ctx.accounts.third_b, third_prizes[1])?;
+
+        msg!("Prizes paid: 1st={} 2nd={} 3rd={}+{}",
+             first_prize, second_prize, third_prizes[0], third_prizes[1]);
+        Ok(())
+    }
+
+    /// Call off a tournament whose final is undecided. Organizer only. The
+    /// whole pot (entry fees and sponsor deposits) is split evenly among the
+    /// entrants, passed as remaining accounts in join order; the organizer
+    /// gets the rounding dust and the account's rent back.
+    pub fn cancel_tournament(ctx: Context<CancelTournament>) -> Result<()> {
+        let t = &ctx.accounts.tournament;
+        require!(t.bracket[0] == Pubkey::default(), WorldModelError::TournamentNotRunning);
+
+        let entrants = &t.entrants[..t.num_entrants as usize];
+        require!(
+            ctx.remaining_accounts.len() == entrants.len()
+                && ctx.remaining_accounts.iter().zip(entrants).all(|(a, e)| a.key == e),
+            WorldModelError::RefundMismatch
+        );
+
+        let info = t.to_account_info();
+        let reserve = Rent::get()?.minimum_balance(info.data_len());
+        let pot = info.lamports().saturating_sub(reserve);
+        let share = pot.checked_div(entrants.len() as u64).unwrap_or(0);
+        for entrant in ctx.remaining_accounts {
+            pay_from_escrow(&info, entrant, share)?;
+        }
+
+        msg!("Tournament {} cancelled: {} entrants refunded {} each", t.id, entrants.len(), share);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 18. mint_replay_nft — collectible for a committed match
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Mint the session's one-of-one replay NFT to a participant.
+    ///
+    /// The session must be ended and its replay committed (a checkpoint
+    /// account written by checkpoint_hidden_state for this session). The
+    /// record PDA stores the replay account and final-state hash; the mint
+    /// authority is revoked after minting so supply stays at 1.
+    pub fn mint_replay_nft(ctx: Context<MintReplayNft>) -> Result<()> {
+        let session = &ctx.accounts.session.load()?;
+        require!(
+            session.status == STATUS_ENDED,
+            WorldModelError::SessionNotEnded
+        );
+        let minter = ctx.accounts.minter.key();
+        require!(
+            minter == session.player1 || minter == session.player2,
+            WorldModelError::UnauthorizedPlayer
+        );
+        {
+            let c_data = ctx.accounts.replay.try_borrow_data()?;
+            require!(
+                c_data.len() >= CHECKPOINT_HEADER_SIZE
+                    && read_checkpoint_header(&c_data).session == ctx.accounts.session.key(),
+                WorldModelError::SessionAccountMismatch
+            );
+        }
+
+        let session_key = ctx.accounts.session.key();
+        let bump = [ctx.bumps.mint];
+        let seeds: &[&[u8]] = &[REPLAY_MINT_SEED, session_key.as_ref(), &bump];
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.minter_token.to_account_info(),
+                    authority: ctx.accounts.mint.to_account_info(),
+                },
+                &[seeds],
+            ),
+            1,
+        )?;
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::SetAuthority {
+                    current_authority: ctx.accounts.mint.to_account_info(),
+                    account_or_mint: ctx.accounts.mint.to_account_info(),
+                },
+                &[seeds],
+            ),
+            token::spl_token::instruction::AuthorityType::MintTokens,
+            None,
+        )?;
+
+        let record = &mut ctx.accounts.record;
+        record.session = session_key;
+        record.replay = ctx.accounts.replay.key();
+        record.mint = ctx.accounts.mint.key();
+        record.final_state_hash = session.final_state_hash()?;
+        record.final_frame = session.frame;
+        record.minted_by = minter;
+        record.minted_at = Clock::get()?.unix_timestamp;
+        record.verified = false;
+        record.bump = ctx.bumps.record;
+
+        msg!("Replay NFT minted: session={} mint={}", session_key, record.mint);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 19. verify_kernels — prove the ER syscall matches the BPF kernel
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Run rows [row_start, row_start + row_count) of one layer's in_proj or
+    /// out_proj through both the BPF matmul kernel and the native
+    /// sol_matmul_i8 syscall, and require byte-identical INT32 results.
+    ///
+    /// Rollup operators run this on their validator (program built with
+    /// `er-syscall`) to show the fast path hasn't diverged; a full layer is
+    /// covered by splitting its rows across transactions. Shards are passed
+    /// as remaining accounts in directory order.
+    pub fn verify_kernels(
+        ctx: Context<VerifyKernels>,
+        layer: u8,
+        tensor: u8,
+        row_start: u16,
+        row_count: u16,
+        input: Vec<i8>,
+    ) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        require!(manifest.ready, WorldModelError::ModelNotReady);
+        require!(
+            ctx.accounts.shard_directory.matches_shards(ctx.remaining_accounts),
+            WorldModelError::ShardAccountsMismatch
+        );
+        require!(
+            (layer as usize) < manifest.num_layers as usize
+                && (tensor as usize == TENSOR_IN_PROJ || tensor as usize == TENSOR_OUT_PROJ),
+            WorldModelError::InvalidTensorRef
+        );
+
+        let t = manifest.tensor_table[layer as usize][tensor as usize];
+        let cols = t.cols as usize;
+        let rows = row_count as usize;
+        let row_end = row_start as usize + rows;
+        require!(
+            rows > 0 && row_end <= t.rows as usize && input.len() == cols,
+            WorldModelError::InvalidTensorRef
+        );
+
+        let shard_data = ctx
+            .remaining_accounts
+            .iter()
+            .map(|a| a.try_borrow_data())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let regions = shard_data
+            .iter()
+            .map(|d| d.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[]))
+            .collect::<Vec<&[u8]>>();
+        let weights = t
+            .resolve(&regions)
+            .and_then(|w| w.get(row_start as usize * cols..row_end * cols))
+            .ok_or(WorldModelError::InvalidTensorRef)?;
+
+        let mut bpf_out = vec![0i32; rows];
+        let mut native_out = vec![0i32; rows];
+        matmul::matmul_i8(weights, &input, &mut bpf_out, rows, cols);
+        require!(
+            matmul::matmul_i8_native(weights, &input, &mut native_out, rows, cols),
+            WorldModelError::SyscallUnavailable
+        );
+
+        let matches = bpf_out == native_out;
+        emit!(KernelsVerified {
+            manifest: manifest.key(),
+            layer,
+            tensor,
+            row_start,
+            row_count,
+            matches,
+        });
+        require!(matches, WorldModelError::KernelMismatch);
+
+        msg!("Kernels match: layer={}, tensor={}, rows {}..{}",
+             layer, tensor, row_start, row_end);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 20. replay verification — re-run a minted match from its inputs
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Start re-executing a replay from frame 0. `hidden_state` is a freshly
+    /// allocated account sized for the model, as for create_session, that
+    /// stands in for the session's recurrent memory; the session's input
+    /// buffer must hold a full frame log. The frame-0 players are the
+    /// session's seed-derived spawns, as join_session set them up.
+    pub fn begin_replay_verification(ctx: Context<BeginReplayVerification>) -> Result<()> {
+        require!(
+            !ctx.accounts.record.verified,
+            WorldModelError::ReplayAlreadyVerified
+        );
+        let session = &ctx.accounts.session.load()?;
+        require!(
+            has_frame_log(&ctx.accounts.input_buffer.try_borrow_data()?, session.max_frames),
+            WorldModelError::ReplayNotLogged
+        );
+        bind_hidden_state(&ctx.accounts.hidden_state, &ctx.accounts.manifest)?;
+
+        let run = &mut ctx.accounts.verification;
+        run.record = ctx.accounts.record.key();
+        run.verifier = ctx.accounts.verifier.key();
+        run.hidden_state = ctx.accounts.hidden_state.key();
+        run.frame = 0;
+        run.commitment = [0; 32];
+        run.players = PlayerState::spawns(
+            session.players.map(|p| p.character),
+            session.seed,
+            session.stage,
+        );
+        run.bump = ctx.bumps.verification;
+
+        msg!("Replay verification started: record={}", run.record);
+        Ok(())
+    }
+
+    /// Re-execute the next `frames` frames from the session's frame log,
+    /// with the dynamics run_inference applied (under mamba2-inference, the
+    /// shard directory, expert table and shards follow as remaining
+    /// accounts, as for run_inference). The rolling commitment must match
+    /// every logged keyframe and the checkpoint's as it passes them; on the
+    /// final frame it must match the session's commitment and the record's
+    /// final-state hash, which marks the replay verified.
+    pub fn verify_replay_frames<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifyReplayFrames<'info>>,
+        frames: u32,
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.record;
+        let run = &mut ctx.accounts.verification;
+        require!(!record.verified, WorldModelError::ReplayAlreadyVerified);
+        require!(
+            frames > 0 && run.frame as u64 + frames as u64 <= record.final_frame as u64,
+            WorldModelError::ReplayFrameOutOfRange
+        );
+
+        let session = ctx.accounts.session.load()?;
+        let manifest = &ctx.accounts.manifest;
+        let log = ctx.accounts.input_buffer.try_borrow_data()?;
+        require!(
+            has_frame_log(&log, session.max_frames),
+            WorldModelError::ReplayNotLogged
+        );
+        let checkpoint = read_checkpoint_header(&ctx.accounts.replay.try_borrow_data()?);
+        let mut h_data = ctx.accounts.hidden_state.try_borrow_mut_data()?;
+        let mut header = check_hidden_state(&session, manifest, &h_data)?;
+
+        #[cfg(feature = "mamba2-inference")]
+        let (shard_infos, experts, _) = split_shards(manifest, ctx.remaining_accounts)?;
+        #[cfg(not(feature = "mamba2-inference"))]
+        let (shard_infos, experts): (&[AccountInfo], Option<ExpertTableAccount>) = (&[], None);
+        let shard_data = shard_infos
+            .iter()
+            .map(|a| a.try_borrow_data())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let regions = shard_data
+            .iter()
+            .map(|d| d.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[]))
+            .collect::<Vec<&[u8]>>();
+
+        for _ in 0..frames {
+            let frame = run.frame + 1;
+            let inputs = logged_inputs(&log, frame);
+            step_dynamics(
+                &mut run.players,
+                &inputs,
+                session.stage,
+                session.tick_rate(),
+                session.seed,
+                frame,
+                &mut h_data,
+                &mut header,
+                manifest,
+                &regions,
+                experts.as_ref(),
+                &mut [],
+            )?;
+            run.frame = frame;
+            run.commitment = frame_commitment(&run.commitment, frame, &run.players, &inputs)?;
+
+            if let Some(keyframe) = logged_keyframe(&log, session.max_frames, frame) {
+                require!(run.commitment == keyframe, WorldModelError::ReplayMismatch);
+            }
+            if frame == checkpoint.frame {
+                require!(
+                    run.commitment == checkpoint.state_commitment,
+                    WorldModelError::ReplayMismatch
+                );
+            }
+        }
+        header.frame = run.frame;
+        write_hidden_header(&mut h_data, &header);
+
+        if run.frame == record.final_frame {
+            require!(
+                run.commitment == session.state_commitment
+                    && state_hash(run.frame, &run.players)? == record.final_state_hash,
+                WorldModelError::ReplayMismatch
+            );
+            record.verified = true;
+            msg!("Replay verified: {} frames", run.frame);
+        } else {
+            msg!("Replay verified through frame {} / {}", run.frame, record.final_frame);
+        }
+        Ok(())
+    }
+
+    /// Close a verification run (finished or abandoned) and its hidden
+    /// state, reclaiming both rents.
+    pub fn close_replay_verification(ctx: Context<CloseReplayVerification>) -> Result<()> {
+        let hidden = ctx.accounts.hidden_state.to_account_info();
+        let verifier = ctx.accounts.verifier.to_account_info();
+        pay_from_escrow(&hidden, &verifier, hidden.lamports())?;
+        hidden.assign(&system_program::ID);
+        hidden.resize(0)?;
+        msg!("Replay verification closed");
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 21. settle_transition — accept a proven off-chain state transition
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Advance a session to a state computed off-chain, for worlds too big
+    /// to run on-chain. The manifest's settlement verifier is CPI'd with the
+    /// transition's public inputs and `proof`; the new players, frame,
+    /// commitment and hidden state hash are accepted only if it succeeds.
+    /// Remaining accounts are forwarded to the verifier.
+    pub fn settle_transition<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleTransition<'info>>,
+        frame: u32,
+        players: [PlayerState; NUM_PLAYERS],
+        commitment: [u8; 32],
+        hidden_state_hash: [u8; 32],
+        proof: Vec<u8>,
+    ) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        require!(
+            manifest.settlement_verifier != Pubkey::default(),
+            WorldModelError::SettlementDisabled
+        );
+        let public_inputs = {
+            let session = ctx.accounts.session.load()?;
+            require!(
+                session.status == STATUS_ACTIVE,
+                WorldModelError::SessionNotActive
+            );
+            require!(
+                frame > session.frame && frame <= session.max_frames,
+                WorldModelError::StaleTransition
+            );
+
+            TransitionPublicInputs {
+                session: ctx.accounts.session.key(),
+                model: manifest.key(),
+                prev_frame: session.frame,
+                prev_commitment: session.state_commitment,
+                frame,
+                commitment,
+                players_hash: state_hash(frame, &players)?,
+                hidden_state_hash,
+            }
+        };
+
+        let mut data =
+            solana_sha256_hasher::hash(VERIFY_TRANSITION_IX.as_bytes()).to_bytes()[..8].to_vec();
+        public_inputs.serialize(&mut data)?;
+        proof.serialize(&mut data)?;
+
+        let ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: manifest.settlement_verifier,
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|a| {
+                    if a.is_writable {
+                        AccountMeta::new(*a.key, a.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(*a.key, a.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        let mut infos = ctx.remaining_accounts.to_vec();
+        infos.push(ctx.accounts.verifier_program.to_account_info());
+        anchor_lang::solana_program::program::invoke(&ix, &infos)?;
+
+        let session = &mut ctx.accounts.session.load_mut()?;
+        session.players = players;
+        session.frame = frame;
+        session.state_commitment = commitment;
+        session.hidden_state_hash = hidden_state_hash;
+        session.last_update = Clock::get()?.unix_timestamp;
+
+        msg!("Off-chain transition settled: frame {}", frame);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 22. Ephemeral rollup — delegate, commit and undelegate a session
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Set where and how a session runs on the ephemeral rollup. Player 1
+    /// only, while the session is still on the base layer. `commit_mask`
+    /// picks the ER_ACCOUNT_* accounts commit_session writes back. Also
+    /// creates the session's empty settlement summary, delegated with it.
+    pub fn configure_delegation(
+        ctx: Context<ConfigureDelegation>,
+        validator: Pubkey,
+        commit_frequency_ms: u32,
+        commit_mask: u8,
+    ) -> Result<()> {
+        require!(
+            commit_mask != 0 && commit_mask & !COMMIT_ALL == 0,
+            WorldModelError::InvalidCommitMask
+        );
+        let session = ctx.accounts.session.load()?;
+        let config = &mut ctx.accounts.config;
+
+        config.session = ctx.accounts.session.key();
+        config.hidden_state = session.hidden_state;
+        config.input_buffer = session.input_buffer;
+        config.authority = ctx.accounts.player1.key();
+        config.validator = validator;
+        config.commit_frequency_ms = commit_frequency_ms;
+        config.commit_mask = commit_mask;
+        config.bump = ctx.bumps.config;
+
+        let summary = &mut ctx.accounts.summary;
+        summary.session = config.session;
+        summary.bump = ctx.bumps.summary;
+
+        msg!("Delegation configured: commit mask {:#06b}", commit_mask);
+        Ok(())
+    }
+
+    /// Delegate one of a session's ER_ACCOUNT_* accounts to the configured
+    /// validator. Send one per bundle account; the keypair accounts
+    /// (session, hidden state, input buffer) must co-sign.
+    pub fn delegate_session_account(
+        ctx: Context<DelegateSessionAccount>,
+        account: u8,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let target = &ctx.accounts.target;
+        let meter_seeds: &[&[u8]] = &[METER_SEED, config.session.as_ref()];
+        let summary_seeds: &[&[u8]] = &[SETTLEMENT_SEED, config.session.as_ref()];
+
+        let (expected, seeds) = match account {
+            ER_ACCOUNT_SESSION => (config.session, &[][..]),
+            ER_ACCOUNT_HIDDEN_STATE => (config.hidden_state, &[][..]),
+            ER_ACCOUNT_INPUT_BUFFER => (config.input_buffer, &[][..]),
+            ER_ACCOUNT_METER => (
+                Pubkey::find_program_address(meter_seeds, &crate::ID).0,
+                meter_seeds,
+            ),
+            ER_ACCOUNT_SUMMARY => (
+                Pubkey::find_program_address(summary_seeds, &crate::ID).0,
+                summary_seeds,
+            ),
+            _ => return err!(WorldModelError::SessionAccountMismatch),
+        };
+        require_keys_eq!(
+            target.key(),
+            expected,
+            WorldModelError::SessionAccountMismatch
+        );
+        require!(
+            !seeds.is_empty() || target.is_signer,
+            WorldModelError::DelegationSignerMissing
+        );
+
+        delegation::delegate(
+            target,
+            seeds,
+            delegation::DelegationAccounts {
+                payer: &ctx.accounts.authority.to_account_info(),
+                owner_program: &ctx.accounts.owner_program,
+                buffer: &ctx.accounts.buffer,
+                delegation_record: &ctx.accounts.delegation_record,
+                delegation_metadata: &ctx.accounts.delegation_metadata,
+                delegation_program: &ctx.accounts.delegation_program,
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            config,
+        )?;
+
+        msg!("Session account {} delegated", account);
+        Ok(())
+    }
+
+    /// Commit the session's masked accounts from the rollup to the base
+    /// layer mid-match, leaving them delegated. Permissionless.
+    pub fn commit_session(ctx: Context<CommitSession>) -> Result<()> {
+        let a = &ctx.accounts;
+        let session = a.session.to_account_info();
+        let accounts = delegation::masked(
+            [&session, &a.hidden_state, &a.input_buffer, &a.meter],
+            a.config.commit_mask,
+        );
+        let committed = accounts.len();
+
+        delegation::commit(
+            &a.payer.to_account_info(),
+            accounts,
+            &a.magic_context,
+            &a.magic_program,
+        )?;
+
+        msg!("Session commit scheduled: {} accounts", committed);
+        Ok(())
+    }
+
+    /// Settle an ended session back to the base layer, where fees, wagers
+    /// and rent settle. Permissionless. Fills in the settlement summary
+    /// (result, stats, final hashes, replay pointer), records the final
+    /// hidden state's hash on the session, cuts the hidden state down to
+    /// its header, then commits and undelegates the bundle and summary.
+    /// Checkpoint the hidden state first to keep it; pass the checkpoint
+    /// as `replay` to point the summary at it.
+    pub fn undelegate_session(ctx: Context<UndelegateSession>) -> Result<()> {
+        let a = &ctx.accounts;
+        let session_key = a.session.key();
+
+        let hidden_state_hash = {
+            let h_data = a.hidden_state.try_borrow_data()?;
+            require!(
+                h_data.len() >= HIDDEN_HEADER_SIZE,
+                WorldModelError::InsufficientData
+            );
+            let data_end = (HIDDEN_HEADER_SIZE + read_hidden_header(&h_data).data_size as usize)
+                .min(h_data.len());
+            solana_sha256_hasher::hash(&h_data[HIDDEN_HEADER_SIZE..data_end]).to_bytes()
+        };
+        let replay = match &a.replay {
+            Some(replay) => {
+                let c_data = replay.try_borrow_data()?;
+                require!(
+                    c_data.len() >= CHECKPOINT_HEADER_SIZE
+                        && read_checkpoint_header(&c_data).session == session_key,
+                    WorldModelError::SessionAccountMismatch
+                );
+                replay.key()
+            }
+            None => Pubkey::default(),
+        };
+        let metered_frames = {
+            let m_data = a.meter.try_borrow_data()?;
+            InferenceMeterAccount::try_deserialize(&mut &m_data[..])?.frames
+        };
+
+        let summary = {
+            let mut session = a.session.load_mut()?;
+            require!(
+                session.status == STATUS_ENDED,
+                WorldModelError::SessionNotEnded
+            );
+            session.hidden_state_hash = hidden_state_hash;
+            let (outcome, winner) = outcome_and_winner(&session);
+            SettlementSummaryAccount {
+                session: session_key,
+                model: session.model,
+                outcome,
+                winner,
+                final_frame: session.frame,
+                stocks: [session.players[0].stocks, session.players[1].stocks],
+                percents: [session.players[0].percent, session.players[1].percent],
+                metered_frames,
+                fees_collected: session.fees_collected,
+                final_state_hash: session.final_state_hash()?,
+                state_commitment: session.state_commitment,
+                hidden_state_hash,
+                replay,
+                settled_at: Clock::get()?.unix_timestamp,
+                bump: a.summary.bump,
+            }
+        };
+        a.hidden_state.resize(HIDDEN_HEADER_SIZE)?;
+
+        emit!(SessionSettled {
+            session: session_key,
+            final_frame: summary.final_frame,
+            outcome: summary.outcome,
+            winner: summary.winner,
+            final_state_hash: summary.final_state_hash,
+            state_commitment: summary.state_commitment,
+            hidden_state_hash,
+            replay,
+        });
+        ctx.accounts.summary.set_inner(summary);
+        ctx.accounts.summary.exit(&crate::ID)?;
+
+        let a = &ctx.accounts;
+        let session = a.session.to_account_info();
+        let summary = a.summary.to_account_info();
+        delegation::commit_and_undelegate(
+            &a.payer.to_account_info(),
+            vec![&session, &a.hidden_state, &a.input_buffer, &a.meter, &summary],
+            &a.magic_context,
+            &a.magic_program,
+        )?;
+
+        msg!("Session settlement scheduled: frame {}", a.summary.final_frame);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 23. Automation — crank a delegated session from the rollup scheduler
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Schedule the session's frame_advance on the rollup's task scheduler:
+    /// every `interval_ms` (no shorter than the session's tick, e.g.
+    /// CRANK_INTERVAL_MS at 60fps), `iterations` times. `payer` funds and
+    /// signs every tick and is credited as its cranker. Player 1 only, on
+    /// the rollup. With mamba2-inference, the shard directory, expert table
+    /// (MoE models) and shards follow as remaining accounts.
+    pub fn register_crank<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterCrank<'info>>,
+        interval_ms: u32,
+        iterations: u32,
+    ) -> Result<()> {
+        require!(iterations > 0, WorldModelError::CrankIterationsZero);
+        let a = &ctx.accounts;
+        {
+            let session = a.session.load()?;
+            require!(
+                session.status == STATUS_ACTIVE,
+                WorldModelError::SessionNotActive
+            );
+            require!(
+                interval_ms >= session.tick_interval_ms(),
+                WorldModelError::CrankIntervalTooShort
+            );
+            // Every tick is signed by the payer; frame_advance would skip
+            // a session that doesn't let it crank
+            require!(
+                session.may_crank(&a.payer.key()),
+                WorldModelError::CrankerNotPermitted
+            );
+            require!(
+                session.hidden_state == a.hidden_state.key()
+                    && session.input_buffer == a.input_buffer.key(),
+                WorldModelError::SessionAccountMismatch
+            );
+        }
+        #[cfg(feature = "mamba2-inference")]
+        require!(
+            split_shards(&a.manifest, ctx.remaining_accounts)?.2.is_empty(),
+            WorldModelError::ShardAccountsMismatch
+        );
+
+        let session_key = a.session.key();
+        let bundle = [
+            a.session.to_account_info(),
+            a.hidden_state.clone(),
+            a.input_buffer.clone(),
+            a.meter.to_account_info(),
+        ];
+        let shards: Vec<Pubkey> = ctx.remaining_accounts.iter().map(|s| s.key()).collect();
+        let payload = automation::frame_advance_payload(
+            a.manifest.key(),
+            a.weights.key(),
+            a.payer.key(),
+            &shards,
+            bundle.each_ref().map(|b| b.key()),
+        );
+
+        let mut accounts = vec![a.manifest.to_account_info(), a.weights.clone()];
+        accounts.extend_from_slice(ctx.remaining_accounts);
+        accounts.extend(bundle);
+        let task_id = automation::crank_task_id(&session_key);
+        automation::schedule(
+            &a.payer.to_account_info(),
+            &a.magic_program,
+            task_id,
+            interval_ms,
+            iterations,
+            payload,
+            &accounts,
+        )?;
+
+        emit!(CrankRegistered {
+            session: session_key,
+            payer: a.payer.key(),
+            task_id,
+            interval_ms,
+            iterations,
+        });
+        msg!("Crank registered: every {}ms, {} ticks", interval_ms, iterations);
+        Ok(())
+    }
+
+    /// Cancel the session's scheduled crank. Signed by the payer that
+    /// registered it, whose task it is.
+    pub fn unregister_crank(ctx: Context<UnregisterCrank>) -> Result<()> {
+        let session = ctx.accounts.session.key();
+        let task_id = automation::crank_task_id(&session);
+        automation::cancel(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.magic_program,
+            task_id,
+        )?;
+
+        emit!(CrankUnregistered {
+            session,
+            payer: ctx.accounts.payer.key(),
+            task_id,
+        });
+        msg!("Crank unregistered");
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 24. Replay archive — compressed NFT instead of rent-exempt accounts
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Create a Bubblegum tree for replay archives in `merkle_tree` (allocated
+    /// beforehand for the compression program at the chosen depth and
+    /// buffer size). The archive authority PDA is its creator.
+    /// Permissionless.
+    pub fn create_archive_tree(
+        ctx: Context<CreateArchiveTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let a = &ctx.accounts;
+        let bump = [ctx.bumps.archive_authority];
+        let seeds: &[&[u8]] = &[ARCHIVE_AUTHORITY_SEED, &bump];
+        archive::create_tree(
+            archive::TreeAccounts {
+                tree_config: &a.tree_config,
+                merkle_tree: &a.merkle_tree,
+                payer: &a.payer.to_account_info(),
+                authority: &a.archive_authority,
+                log_wrapper: &a.log_wrapper,
+                compression_program: &a.compression_program,
+                system_program: &a.system_program.to_account_info(),
+                bubblegum_program: &a.bubblegum_program,
+            },
+            max_depth,
+            max_buffer_size,
+            seeds,
+        )?;
+
+        msg!("Archive tree created: depth {}, buffer {}", max_depth, max_buffer_size);
+        Ok(())
+    }
+
+    /// Archive an ended session's replay as a compressed NFT to a
+    /// participant, then close the replay checkpoint, refunding its rent to
+    /// them. The leaf commits to the replay summary (see `archive`); the
+    /// ReplayArchived event carries it for indexers. Not for replays that
+    /// already have a mint_replay_nft record, which points at the
+    /// checkpoint.
+    pub fn archive_replay(ctx: Context<ArchiveReplay>) -> Result<()> {
+        let a = &ctx.accounts;
+        let session_key = a.session.key();
+        let summary = {
+            let session = a.session.load()?;
+            require!(
+                session.status == STATUS_ENDED,
+                WorldModelError::SessionNotEnded
+            );
+            let minter = a.minter.key();
+            require!(
+                minter == session.player1 || minter == session.player2,
+                WorldModelError::UnauthorizedPlayer
+            );
+            let c_data = a.replay.try_borrow_data()?;
+            require!(
+                c_data.len() >= CHECKPOINT_HEADER_SIZE
+                    && read_checkpoint_header(&c_data).session == session_key,
+                WorldModelError::SessionAccountMismatch
+            );
+            archive::ReplaySummary {
+                session: session_key,
+                model: session.model,
+                player1: session.player1,
+                player2: session.player2,
+                final_frame: session.frame,
+                final_state_hash: session.final_state_hash()?,
+                replay_hash: solana_sha256_hasher::hash(&c_data).to_bytes(),
             }
+        };
+
+        let bump = [ctx.bumps.archive_authority];
+        let seeds: &[&[u8]] = &[ARCHIVE_AUTHORITY_SEED, &bump];
+        let minter = a.minter.to_account_info();
+        archive::mint_leaf(
+            archive::TreeAccounts {
+                tree_config: &a.tree_config,
+                merkle_tree: &a.merkle_tree,
+                payer: &minter,
+                authority: &a.archive_authority,
+                log_wrapper: &a.log_wrapper,
+                compression_program: &a.compression_program,
+                system_program: &a.system_program.to_account_info(),
+                bubblegum_program: &a.bubblegum_program,
+            },
+            &minter,
+            &archive::leaf_metadata(&summary)?,
+            seeds,
+        )?;
+
+        let replay = &a.replay;
+        pay_from_escrow(replay, &minter, replay.lamports())?;
+        replay.assign(&system_program::ID);
+        replay.resize(0)?;
+
+        emit!(ReplayArchived {
+            session: session_key,
+            merkle_tree: a.merkle_tree.key(),
+            owner: minter.key(),
+            summary_hash: summary.hash()?,
+            final_frame: summary.final_frame,
+            final_state_hash: summary.final_state_hash,
+            replay_hash: summary.replay_hash,
+        });
+        msg!("Replay archived: session={} tree={}", session_key, a.merkle_tree.key());
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 25. Views — session state for other programs over CPI
+    // ═══════════════════════════════════════════════════════════════════════
+    //
+    // Read-only; each returns an awm-cpi type via set_return_data. Programs
+    // call them through awm_cpi, clients by simulating the transaction.
+
+    /// Status, players, model and progress of a session.
+    pub fn get_session_summary(ctx: Context<ViewSession>) -> Result<awm_cpi::SessionSummary> {
+        let session = ctx.accounts.session.load()?;
+        Ok(awm_cpi::SessionSummary {
+            status: session.status,
+            stage: session.stage,
+            model: session.model,
+            player1: session.player1,
+            player2: session.player2,
+            frame: session.frame,
+            max_frames: session.max_frames,
+            created_at: session.created_at,
+            last_update: session.last_update,
+            stocks: [session.players[0].stocks, session.players[1].stocks],
+            percents: [session.players[0].percent, session.players[1].percent],
+            tick_rate: session.tick_rate() as u8,
+        })
+    }
+
+    /// Standings of a session — final once `ended` is set. The same
+    /// outcome close_session logs as SessionResult.
+    pub fn get_result(ctx: Context<ViewSession>) -> Result<awm_cpi::MatchResult> {
+        let session = ctx.accounts.session.load()?;
+        let (outcome, winner) = outcome_and_winner(&session);
+        Ok(awm_cpi::MatchResult {
+            ended: session.status == STATUS_ENDED,
+            outcome,
+            winner,
+            frame: session.frame,
+            stocks: [session.players[0].stocks, session.players[1].stocks],
+            percents: [session.players[0].percent, session.players[1].percent],
+            final_state_hash: session.final_state_hash()?,
+        })
+    }
+
+    /// Both players at the session's latest frame, with its commitment.
+    pub fn get_frame(ctx: Context<ViewSession>) -> Result<awm_cpi::FrameView> {
+        let session = ctx.accounts.session.load()?;
+        Ok(awm_cpi::FrameView {
+            frame: session.frame,
+            state_commitment: session.state_commitment,
+            players: session.players.map(|p| awm_cpi::PlayerView {
+                x: p.x,
+                y: p.y,
+                percent: p.percent,
+                stocks: p.stocks,
+                facing: p.facing,
+                on_ground: p.on_ground,
+                action_state: p.action_state,
+                character: p.character,
+            }),
+        })
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 26. Split matmul — one layer projection across several transactions
+    // ═══════════════════════════════════════════════════════════════════════
+    //
+    // A large in_proj (in_proj_rows × d_model) can exceed a transaction's
+    // compute budget. The cranker claims an accumulator for (frame, layer),
+    // fills its INT32 sums in chunk-aligned row ranges, and requantizes once
+    // the completion bitmap is full. The INT8 output stays in the account.
+
+    /// Claim `accumulator` for one layer matmul of the session's next frame,
+    /// bound to the INT8 input vector by `input_hash`. Clears earlier sums.
+    pub fn begin_matmul_accumulator(
+        ctx: Context<BeginMatmulAccumulator>,
+        frame: u32,
+        layer: u8,
+        tensor: u8,
+        input_hash: [u8; 32],
+    ) -> Result<()> {
+        let session = ctx.accounts.session.load()?;
+        let manifest = &ctx.accounts.manifest;
+        require!(
+            session.status == STATUS_ACTIVE,
+            WorldModelError::InvalidStateTransition
+        );
+        require!(
+            session.model == manifest.key(),
+            WorldModelError::ModelMismatch
+        );
+        require!(frame == session.frame + 1, WorldModelError::AccumulatorMismatch);
+        require!(
+            (layer as usize) < manifest.num_layers as usize
+                && (tensor as usize == TENSOR_IN_PROJ || tensor as usize == TENSOR_OUT_PROJ),
+            WorldModelError::InvalidTensorRef
+        );
+
+        let t = manifest.tensor_table[layer as usize][tensor as usize];
+        let rows = t.rows as usize;
+        require!(
+            rows > 0 && rows <= ACCUM_MAX_ROWS,
+            WorldModelError::InvalidTensorRef
+        );
+
+        let mut a_data = ctx.accounts.accumulator.try_borrow_mut_data()?;
+        require!(
+            a_data.len() >= AccumulatorHeader::account_size(rows),
+            WorldModelError::AccumulatorTooSmall
+        );
+
+        // A claim lapses once its frame has run
+        let existing = read_accumulator_header(&a_data);
+        let cranker = ctx.accounts.cranker.key();
+        require!(
+            existing.session == Pubkey::default()
+                || existing.cranker == cranker
+                || existing.frame < frame,
+            WorldModelError::AccumulatorInUse
+        );
+
+        write_accumulator_header(
+            &mut a_data,
+            &AccumulatorHeader {
+                session: ctx.accounts.session.key(),
+                cranker,
+                input_hash,
+                frame,
+                layer,
+                tensor,
+                rows: t.rows,
+                cols: t.cols,
+                requantized: false,
+                bitmap: [0; ACCUM_BITMAP_SIZE],
+            },
+        );
+        a_data[ACCUM_HEADER_SIZE..AccumulatorHeader::account_size(rows)].fill(0);
+
+        msg!("Accumulator claimed: frame={}, layer={}, tensor={}, {} rows",
+             frame, layer, tensor, rows);
+        Ok(())
+    }
+
+    /// Accumulate rows [row_start, row_start + row_count) of the claimed
+    /// matmul. `row_start` is a multiple of ACCUM_CHUNK_ROWS, and so is
+    /// `row_count` unless the range ends at the last row. `frame` and
+    /// `segment` (accumulator_segment of the claimed layer and tensor) must
+    /// name the claim, so a delayed retry can't land in a reclaimed
+    /// accumulator. Shards are passed as remaining accounts in directory
+    /// order.
+    pub fn accumulate_matmul_rows(
+        ctx: Context<AccumulateMatmulRows>,
+        frame: u32,
+        segment: u16,
+        row_start: u16,
+        row_count: u16,
+        input: Vec<i8>,
+    ) -> Result<()> {
+        let session = ctx.accounts.session.load()?;
+        let manifest = &ctx.accounts.manifest;
+        require!(
+            session.model == manifest.key(),
+            WorldModelError::ModelMismatch
+        );
+        require!(
+            ctx.accounts.shard_directory.matches_shards(ctx.remaining_accounts),
+            WorldModelError::ShardAccountsMismatch
+        );
+
+        let mut a_data = ctx.accounts.accumulator.try_borrow_mut_data()?;
+        require!(
+            a_data.len() >= ACCUM_HEADER_SIZE,
+            WorldModelError::AccumulatorTooSmall
+        );
+        let mut header = read_accumulator_header(&a_data);
+        require!(
+            header.session == ctx.accounts.session.key()
+                && header.cranker == ctx.accounts.cranker.key()
+                && header.frame == session.frame + 1
+                && header.frame == frame
+                && header.segment() == segment
+                && !header.requantized
+                && header.input_hash == accumulator_input_hash(&input),
+            WorldModelError::AccumulatorMismatch
+        );
+
+        let cols = header.cols as usize;
+        let start = row_start as usize;
+        let rows = row_count as usize;
+        let row_end = start + rows;
+        require!(
+            rows > 0
+                && start.is_multiple_of(ACCUM_CHUNK_ROWS)
+                && (rows.is_multiple_of(ACCUM_CHUNK_ROWS) || row_end == header.rows as usize)
+                && row_end <= header.rows as usize,
+            WorldModelError::InvalidAccumulatorRange
+        );
+        require!(input.len() == cols, WorldModelError::AccumulatorMismatch);
+
+        let t = manifest.tensor_table[header.layer as usize][header.tensor as usize];
+        let shard_data = ctx
+            .remaining_accounts
+            .iter()
+            .map(|a| a.try_borrow_data())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let regions = shard_data
+            .iter()
+            .map(|d| d.get(WEIGHT_HEADER_SIZE..).unwrap_or(&[]))
+            .collect::<Vec<&[u8]>>();
+        let weights = t
+            .resolve(&regions)
+            .and_then(|w| w.get(start * cols..row_end * cols))
+            .ok_or(WorldModelError::InvalidTensorRef)?;
+
+        let mut sums = vec![0i32; rows];
+        matmul::matmul_i8(weights, &input, &mut sums, rows, cols);
+
+        let sums_at = ACCUM_HEADER_SIZE + start * 4;
+        for (dst, sum) in a_data[sums_at..sums_at + rows * 4].chunks_exact_mut(4).zip(&sums) {
+            dst.copy_from_slice(&sum.to_le_bytes());
+        }
+        header.mark_chunks(start / ACCUM_CHUNK_ROWS, rows.div_ceil(ACCUM_CHUNK_ROWS));
+        write_accumulator_header(&mut a_data, &header);
+
+        msg!("Accumulated layer={}, tensor={}, rows {}..{}",
+             header.layer, header.tensor, start, row_end);
+        Ok(())
+    }
+
+    /// Requantize a fully accumulated matmul to INT8 with the layer's scale
+    /// and the frame's rounding, as the single-transaction forward pass does.
+    /// `frame` and `segment` must name the claim, as for
+    /// accumulate_matmul_rows.
+    pub fn requantize_accumulator(
+        ctx: Context<RequantizeAccumulator>,
+        frame: u32,
+        segment: u16,
+    ) -> Result<()> {
+        let session = ctx.accounts.session.load()?;
+        let manifest = &ctx.accounts.manifest;
+        require!(
+            session.model == manifest.key(),
+            WorldModelError::ModelMismatch
+        );
+
+        let mut a_data = ctx.accounts.accumulator.try_borrow_mut_data()?;
+        require!(
+            a_data.len() >= ACCUM_HEADER_SIZE,
+            WorldModelError::AccumulatorTooSmall
+        );
+        let mut header = read_accumulator_header(&a_data);
+        require!(
+            header.session == ctx.accounts.session.key()
+                && header.cranker == ctx.accounts.cranker.key()
+                && header.frame == session.frame + 1
+                && header.frame == frame
+                && header.segment() == segment
+                && !header.requantized,
+            WorldModelError::AccumulatorMismatch
+        );
+        require!(header.is_complete(), WorldModelError::AccumulatorIncomplete);
+
+        let rows = header.rows as usize;
+        let layer = header.layer as usize;
+        let (scale, substream) = if header.tensor as usize == TENSOR_IN_PROJ {
+            (manifest.layer_input_scales[layer], 0)
+        } else {
+            (manifest.layer_output_scales[layer], 1)
+        };
+        let rounding = manifest
+            .rounding(rng::frame_seed(session.seed, header.frame))
+            .substream(layer as u64)
+            .substream(substream);
+
+        let sums_end = ACCUM_HEADER_SIZE + rows * 4;
+        let sums: Vec<i32> = a_data[ACCUM_HEADER_SIZE..sums_end]
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let mut out = vec![0i8; rows];
+        matmul::requantize_per_channel_rounded(&sums, &vec![scale; rows], &mut out, rows, rounding);
+        for (dst, &v) in a_data[sums_end..sums_end + rows].iter_mut().zip(&out) {
+            *dst = v as u8;
+        }
+
+        header.requantized = true;
+        write_accumulator_header(&mut a_data, &header);
+
+        msg!("Accumulator requantized: frame={}, layer={}, tensor={}",
+             header.frame, header.layer, header.tensor);
+        Ok(())
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // 27. Calibration — activation ranges from live play for re-quantization
+    // ═══════════════════════════════════════════════════════════════════════
+
+    /// Opt a model into activation-range telemetry by creating its
+    /// calibration account. Every run_inference that passes the account
+    /// then widens each layer's accumulator extremes (mamba2-inference;
+    /// frame_advance doesn't record). Authority only; ready models too,
+    /// since live play is what it measures.
+    pub fn init_calibration(ctx: Context<InitCalibration>) -> Result<()> {
+        let manifest = &ctx.accounts.manifest;
+        require!(
+            manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+
+        let calibration = &mut ctx.accounts.calibration;
+        calibration.manifest = manifest.key();
+        calibration.bump = ctx.bumps.calibration;
+        calibration.reset();
+
+        msg!("Calibration started for {} layers", manifest.num_layers);
+        Ok(())
+    }
+
+    /// Forget the ranges observed so far and start a new window. Authority
+    /// only.
+    pub fn reset_calibration(ctx: Context<ResetCalibration>) -> Result<()> {
+        require!(
+            ctx.accounts.manifest.is_authority(&ctx.accounts.authority.key()),
+            WorldModelError::Unauthorized
+        );
+
+        let calibration = &mut ctx.accounts.calibration;
+        msg!("Calibration reset after {} frames", calibration.frames);
+        calibration.reset();
+        Ok(())
+    }
+}
+
+/// Lendable rent pool lamports: the balance above the pool's own rent.
+/// Lent lamports have already left the account.
+fn rent_pool_free(pool: &Account<RentPoolAccount>) -> Result<u64> {
+    let info = pool.to_account_info();
+    let reserve = Rent::get()?.minimum_balance(info.data_len());
+    Ok(info.lamports().saturating_sub(reserve))
+}
+
+/// Move lamports out of a program-owned account (wager escrow, session fees,
+/// tips, rent pool, prize pool).
+fn pay_from_escrow(escrow: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    **escrow.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// Create the session's checkpoint PDA, or grow it, toward `target` bytes:
+/// by at most MAX_PERMITTED_DATA_INCREASE, the most one instruction may add
+/// to an account. The player tops up the rent. Creating through transfer,
+/// allocate and assign also works on a PDA someone pre-funded.
+fn grow_checkpoint(accounts: &CheckpointHiddenState, bump: u8, target: usize) -> Result<()> {
+    let checkpoint = accounts.checkpoint.to_account_info();
+    let created = checkpoint.owner == &crate::ID;
+    let len = if created { checkpoint.data_len() } else { 0 };
+    let new_len = target.min(len + MAX_PERMITTED_DATA_INCREASE);
+
+    let rent = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(checkpoint.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.player.to_account_info(),
+                    to: checkpoint.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+
+    if created {
+        return Ok(checkpoint.resize(new_len)?);
+    }
+    let session_key = accounts.session.key();
+    let bump = [bump];
+    let seeds: &[&[u8]] = &[CHECKPOINT_SEED, session_key.as_ref(), &bump];
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            accounts.system_program.to_account_info(),
+            system_program::Allocate {
+                account_to_allocate: checkpoint.clone(),
+            },
+            &[seeds],
+        ),
+        new_len as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            accounts.system_program.to_account_info(),
+            system_program::Assign {
+                account_to_assign: checkpoint,
+            },
+            &[seeds],
+        ),
+        &crate::ID,
+    )
+}
+
+/// The player a tip for `player_slot` goes to: slot 0 or 1, occupied.
+fn tipped_player(session: &SessionStateAccount, player_slot: u8) -> Result<Pubkey> {
+    let player = match player_slot {
+        0 => session.player1,
+        1 => session.player2,
+        _ => Pubkey::default(),
+    };
+    require!(player != Pubkey::default(), WorldModelError::InvalidPlayerSlot);
+    Ok(player)
+}
+
+/// transfer_checked `amount` of `mint` between token accounts. `signer`
+/// holds the seeds when `authority` is one of this program's PDAs.
+fn transfer_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, InterfaceMint>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from,
+                mint: mint.to_account_info(),
+                to,
+                authority,
+            },
+            signer,
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+/// Checks shared by create_wager and create_token_wager.
+fn check_new_wager(session: &SessionStateAccount, player1: &Pubkey, amount: u64) -> Result<()> {
+    require!(amount > 0, WorldModelError::InvalidAmount);
+    require!(
+        session.status == STATUS_WAITING_PLAYERS,
+        WorldModelError::InvalidStateTransition
+    );
+    require!(*player1 == session.player1, WorldModelError::UnauthorizedPlayer);
+    Ok(())
+}
+
+/// Checks shared by accept_wager and accept_token_wager.
+fn check_wager_accept(
+    session: &SessionStateAccount,
+    player2: &Pubkey,
+    escrow: &WagerEscrowAccount,
+) -> Result<()> {
+    require!(
+        session.status == STATUS_ACTIVE && session.frame == 0,
+        WorldModelError::InvalidStateTransition
+    );
+    require!(*player2 == session.player2, WorldModelError::UnauthorizedPlayer);
+    require!(!escrow.p2_deposited, WorldModelError::WagerAlreadyAccepted);
+    Ok(())
+}
+
+/// An ended session's wager payouts as (player 1, player 2).
+fn wager_payouts(session: &SessionStateAccount, escrow: &WagerEscrowAccount) -> Result<(u64, u64)> {
+    require!(
+        session.status == STATUS_ENDED,
+        WorldModelError::SessionNotEnded
+    );
+    if !escrow.p2_deposited {
+        return Ok((escrow.amount, 0));
+    }
+    Ok(match session.outcome() {
+        SessionOutcome::Winner(0) => (escrow.amount * 2, 0),
+        SessionOutcome::Winner(_) => (0, escrow.amount * 2),
+        SessionOutcome::Draw | SessionOutcome::Unplayed => (escrow.amount, escrow.amount),
+    })
+}
+
+/// A timed-out wager's refunds as (player 1, player 2). Ended sessions
+/// must settle instead.
+fn wager_refunds(session: &SessionStateAccount, escrow: &WagerEscrowAccount) -> Result<(u64, u64)> {
+    require!(
+        session.status != STATUS_ENDED,
+        WorldModelError::InvalidStateTransition
+    );
+    require!(
+        Clock::get()?.unix_timestamp >= escrow.created_at + WAGER_TIMEOUT_SECS,
+        WorldModelError::WagerTimeoutNotReached
+    );
+    let p1_refund = if escrow.p1_deposited { escrow.amount } else { 0 };
+    let p2_refund = if escrow.p2_deposited { escrow.amount } else { 0 };
+    Ok((p1_refund, p2_refund))
+}
+
+/// Pay an SPL wager out of its vault, signed by the escrow PDA, then close
+/// the emptied vault to player 1.
+fn pay_token_wager(accounts: &SettleTokenWager, p1_amount: u64, p2_amount: u64) -> Result<()> {
+    let session_key = accounts.session.key();
+    let bump = [accounts.escrow.bump];
+    let seeds: &[&[u8]] = &[WAGER_SEED, session_key.as_ref(), &bump];
+    let escrow = accounts.escrow.to_account_info();
+
+    transfer_tokens(
+        &accounts.token_program,
+        &accounts.mint,
+        accounts.vault.to_account_info(),
+        accounts.player1_token.to_account_info(),
+        escrow.clone(),
+        &[seeds],
+        p1_amount,
+    )?;
+    if p2_amount > 0 {
+        let player2_token = accounts
+            .player2_token
+            .as_ref()
+            .ok_or(WorldModelError::PayoutTokenAccountRequired)?;
+        transfer_tokens(
+            &accounts.token_program,
+            &accounts.mint,
+            accounts.vault.to_account_info(),
+            player2_token.to_account_info(),
+            escrow.clone(),
+            &[seeds],
+            p2_amount,
+        )?;
+    }
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        token_interface::CloseAccount {
+            account: accounts.vault.to_account_info(),
+            destination: accounts.player1.to_account_info(),
+            authority: escrow,
+        },
+        &[seeds],
+    ))
+}
+
+/// A session's standings as an OUTCOME_* code and the winning player
+/// (default unless there is one).
+fn outcome_and_winner(session: &SessionStateAccount) -> (u8, Pubkey) {
+    match session.outcome() {
+        SessionOutcome::Winner(0) => (OUTCOME_WINNER, session.player1),
+        SessionOutcome::Winner(_) => (OUTCOME_WINNER, session.player2),
+        SessionOutcome::Draw => (OUTCOME_DRAW, Pubkey::default()),
+        SessionOutcome::Unplayed => (OUTCOME_UNPLAYED, Pubkey::default()),
+    }
+}
+
+/// Log a session's end: `SessionEnded`, then its final standings as
+/// `SessionResult`.
+fn emit_session_end(session_key: Pubkey, session: &SessionStateAccount, closed_by: Pubkey) {
+    emit!(SessionEnded {
+        session: session_key,
+        frame: session.frame,
+        closed_by,
+    });
+    let (outcome, winner) = outcome_and_winner(session);
+    emit!(SessionResult {
+        session: session_key,
+        frame: session.frame,
+        outcome,
+        winner,
+        stocks: [session.players[0].stocks, session.players[1].stocks],
+        percents: [session.players[0].percent, session.players[1].percent],
+    });
+}
+
+/// Pay out a session's collected fee by the schedule's split and zero it.
+/// `cranker` must be the session's top cranker; when nobody cranked its
+/// share goes to the protocol. Returns (author, cranker, protocol) shares.
+fn split_session_fees(
+    session: &mut SessionStateAccount,
+    session_info: &AccountInfo,
+    schedule: &FeeScheduleAccount,
+    author_treasury: &AccountInfo,
+    cranker: &AccountInfo,
+    protocol_treasury: &AccountInfo,
+) -> Result<(u64, u64, u64)> {
+    let fees = session.fees_collected;
+    let author_share = fees * schedule.author_bps as u64 / BPS_DENOMINATOR as u64;
+    let mut cranker_share = fees * schedule.cranker_bps as u64 / BPS_DENOMINATOR as u64;
+
+    match session.top_cranker() {
+        Some(top) => require!(cranker.key() == top, WorldModelError::NotTopCranker),
+        None => cranker_share = 0,
+    }
+    let protocol_share = fees - author_share - cranker_share;
+
+    pay_from_escrow(session_info, author_treasury, author_share)?;
+    pay_from_escrow(session_info, cranker, cranker_share)?;
+    pay_from_escrow(session_info, protocol_treasury, protocol_share)?;
+    session.fees_collected = 0;
+    Ok((author_share, cranker_share, protocol_share))
+}
+
+/// Write a fresh hidden state header for `manifest`'s model. Only a
+/// freshly allocated account binds: program-owned, large enough and with
+/// an all-zero header (a written one means it belongs to a session).
+fn bind_hidden_state(hidden: &AccountInfo, manifest: &ModelManifestAccount) -> Result<()> {
+    let mut h_data = hidden.try_borrow_mut_data()?;
+    let data_size = hidden_data_size(
+        manifest.num_layers,
+        manifest.d_inner,
+        manifest.d_state,
+        manifest.hidden_dtype,
+    );
+    require!(
+        hidden.owner == &crate::ID
+            && h_data.len() >= HIDDEN_HEADER_SIZE + data_size as usize
+            && h_data[..HIDDEN_HEADER_SIZE].iter().all(|&b| b == 0),
+        WorldModelError::HiddenStateInUse
+    );
+    write_hidden_header(
+        &mut h_data,
+        &HiddenHeader {
+            num_layers: manifest.num_layers,
+            d_inner: manifest.d_inner,
+            d_state: manifest.d_state,
+            data_size,
+            frame: 0,
+            initialized: false,
+            dtype: manifest.hidden_dtype,
+        },
+    );
+    Ok(())
+}
+
+/// Check a session's hidden state against the model it runs, before any
+/// scan writes.
+fn check_hidden_state(
+    session: &SessionStateAccount,
+    manifest: &Account<ModelManifestAccount>,
+    h_data: &[u8],
+) -> Result<HiddenHeader> {
+    require!(
+        session.model == manifest.key(),
+        WorldModelError::ModelMismatch
+    );
+    require!(
+        h_data.len() >= HIDDEN_HEADER_SIZE,
+        WorldModelError::InsufficientData
+    );
+    let header = read_hidden_header(h_data);
+    require!(
+        header.matches_manifest(manifest),
+        WorldModelError::HiddenStateMismatch
+    );
+    require!(
+        h_data.len() >= HIDDEN_HEADER_SIZE + header.data_size as usize,
+        WorldModelError::InsufficientData
+    );
+    Ok(header)
+}
+
+/// The shard directory, the expert table (mixture-of-experts models only)
+/// and the shards at the front of `remaining`, checked against the
+/// manifest. Returns the shards, the expert table and the accounts after
+/// them.
+#[cfg(feature = "mamba2-inference")]
+fn split_shards<'a, 'info>(
+    manifest: &Account<ModelManifestAccount>,
+    remaining: &'a [AccountInfo<'info>],
+) -> Result<(&'a [AccountInfo<'info>], Option<ExpertTableAccount>, &'a [AccountInfo<'info>])> {
+    require!(manifest.ready, WorldModelError::ModelNotReady);
+    let (directory_info, rest) = remaining
+        .split_first()
+        .ok_or(WorldModelError::ShardAccountsMismatch)?;
+    require!(
+        directory_info.key() == manifest.shard_directory
+            && directory_info.owner == &crate::ID,
+        WorldModelError::ShardAccountsMismatch
+    );
+    let directory =
+        ShardDirectoryAccount::try_deserialize(&mut &directory_info.try_borrow_data()?[..])?;
+
+    let (experts, rest) = if manifest.num_experts > 0 {
+        let (table_info, rest) = rest
+            .split_first()
+            .ok_or(WorldModelError::ExpertTableMismatch)?;
+        require!(
+            table_info.key() == manifest.expert_table && table_info.owner == &crate::ID,
+            WorldModelError::ExpertTableMismatch
+        );
+        let table =
+            ExpertTableAccount::try_deserialize(&mut &table_info.try_borrow_data()?[..])?;
+        (Some(table), rest)
+    } else {
+        (None, rest)
+    };
+
+    require!(
+        rest.len() >= directory.entries.len(),
+        WorldModelError::ShardAccountsMismatch
+    );
+    let (shard_infos, rest) = rest.split_at(directory.entries.len());
+    require!(
+        directory.matches_shards(shard_infos),
+        WorldModelError::ShardAccountsMismatch
+    );
+    Ok((shard_infos, experts, rest))
+}
+
+/// Advance one validated session a frame: run the dynamics, fold the frame
+/// into the commitment and frame log, credit the cranker and meter the
+/// frame. Shared by run_inference and frame_advance, which check status
+/// and inputs first.
+fn step_session(
+    session_key: Pubkey,
+    session: &mut SessionStateAccount,
+    h_data: &mut [u8],
+    mut header: HiddenHeader,
+    input_data: &mut [u8],
+    inputs: [ControllerInput; NUM_PLAYERS],
+    manifest: &Account<ModelManifestAccount>,
+    regions: &[&[u8]],
+    experts: Option<&ExpertTableAccount>,
+    cranker: Pubkey,
+    meter: &mut InferenceMeterAccount,
+    calibration: Option<&mut CalibrationAccount>,
+) -> Result<()> {
+    let frame = session.frame + 1;
+    let stocks_before = session.players.map(|p| p.stocks);
+
+    // Only a calibrating run collects per-layer ranges
+    let layers = if calibration.is_some() { manifest.num_layers as usize } else { 0 };
+    let mut ranges = vec![inference::ActivationRange::default(); layers];
+    let (stage, tick_rate, seed) = (session.stage, session.tick_rate(), session.seed);
+    let (raw, routes) = step_dynamics(
+        &mut session.players,
+        &inputs,
+        stage,
+        tick_rate,
+        seed,
+        frame,
+        h_data,
+        &mut header,
+        manifest,
+        regions,
+        experts,
+        &mut ranges,
+    )?;
+    if let Some(calibration) = calibration {
+        calibration.record(&ranges, Clock::get()?.slot);
+    }
+    if !raw.is_empty() {
+        emit!(OutputSmoothed {
+            session: session_key,
+            frame,
+            raw,
+        });
+    }
+    if experts.is_some() {
+        emit!(ExpertsRouted {
+            session: session_key,
+            frame,
+            experts: routes,
+        });
+    }
+
+    // Update frame counters
+    session.frame = frame;
+    session.last_update = Clock::get()?.unix_timestamp;
+    session.commit_frame(&inputs)?;
+    session.record_crank(cranker);
+    log_frame(input_data, session.max_frames, frame, &inputs, &session.state_commitment);
+
+    for (slot, p) in session.players.iter().enumerate() {
+        if p.stocks < stocks_before[slot] {
+            emit!(PlayerKo {
+                session: session_key,
+                frame,
+                player: slot as u8,
+                stocks_left: p.stocks,
+            });
+        }
+    }
+    emit!(FrameAdvanced {
+        session: session_key,
+        frame,
+        state_commitment: session.state_commitment,
+        cranker,
+    });
+
+    // Meter usage for settlement
+    let config = inference::manifest_config(manifest);
+    meter.frames += 1;
+    meter.estimated_cu += config.estimate_frame_cu();
+    meter.last_slot = Clock::get()?.slot;
+
+    // Update hidden state frame counter
+    header.frame = frame;
+    write_hidden_header(h_data, &header);
+
+    Ok(())
+}
+
+/// The world's dynamics for one frame: advance `players` to `frame` on
+/// `inputs`. The one step run_inference, frame_advance and replay
+/// verification all apply, so a replay re-executes exactly what was
+/// cranked. Returns the pre-smoothing outputs and expert routes for the
+/// caller's events (both empty under stub-inference).
+fn step_dynamics(
+    players: &mut [PlayerState; NUM_PLAYERS],
+    inputs: &[ControllerInput; NUM_PLAYERS],
+    stage: u8,
+    tick_rate: u32,
+    seed: u64,
+    frame: u32,
+    h_data: &mut [u8],
+    header: &mut HiddenHeader,
+    manifest: &ModelManifestAccount,
+    regions: &[&[u8]],
+    experts: Option<&ExpertTableAccount>,
+    ranges: &mut [inference::ActivationRange],
+) -> Result<(Vec<i32>, Vec<u8>)> {
+    // First frame: zero the recurrent state so the scan never runs over
+    // stale account bytes. fill() lowers to sol_memset (~len/250 CU), so
+    // even a 200KB state fits comfortably in one transaction.
+    let data_end = HIDDEN_HEADER_SIZE + header.data_size as usize;
+    if !header.initialized {
+        h_data[HIDDEN_HEADER_SIZE..data_end].fill(0);
+        header.initialized = true;
+    }
+
+    // ── STUB INFERENCE (stub-inference) ─────────────────────────────────
+    // Simple physics-like rules that demonstrate the pipeline; the devnet
+    // default while the real kernel matures.
+    #[cfg(not(feature = "mamba2-inference"))]
+    {
+        let _ = (tick_rate, seed, frame, manifest, regions, experts, ranges);
+        inference::step_players(players, inputs, stage);
+        Ok((Vec::new(), Vec::new()))
+    }
+
+    // ── MAMBA2 INFERENCE (mamba2-inference) ─────────────────────────────
+    #[cfg(feature = "mamba2-inference")]
+    {
+        let stocks_before = players.map(|p| p.stocks);
+        let hidden_view =
+            inference::hidden_state_mut(header.dtype, &mut h_data[HIDDEN_HEADER_SIZE..data_end])
+                .ok_or(WorldModelError::HiddenStateMismatch)?;
+        let previous = *players;
+        let routes = inference::step_players_mamba2(
+            manifest,
+            players,
+            inputs,
+            stage,
+            tick_rate,
+            hidden_view,
+            regions,
+            experts,
+            rng::frame_seed(seed, frame),
+            ranges,
+        )
+        .ok_or(WorldModelError::InvalidTensorRef)?;
+        let raw = inference::smooth_players(
+            &manifest.encoder_spec,
+            &manifest.output_smoothing,
+            &previous,
+            players,
+        );
+        // The model decodes positions freely; the stage decides KOs
+        inference::enforce_blast_zones(players, stocks_before, stages::stage_or_default(stage));
+        Ok((raw, routes))
+    }
+}
+
+/// A session account's current frame, or 0 if it isn't one.
+fn session_frame<'info>(info: &'info AccountInfo<'info>) -> u32 {
+    let Ok(loader) = AccountLoader::<SessionStateAccount>::try_from(info) else {
+        return 0;
+    };
+    loader.load().map(|s| s.frame).unwrap_or(0)
+}
+
+/// One frame_advance bundle: advance it if it's ready and checks out,
+/// otherwise return why not. Errors only when advancing itself fails.
+fn advance_bundle<'info>(
+    bundle: &'info [AccountInfo<'info>],
+    manifest: &Account<ModelManifestAccount>,
+    regions: &[&[u8]],
+    experts: Option<&ExpertTableAccount>,
+    cranker: Pubkey,
+) -> Result<u8> {
+    let [session_info, hidden_info, input_info, meter_info] = bundle else {
+        return Ok(ADVANCE_INVALID_BUNDLE);
+    };
+    let (Ok(session_loader), Ok(input_loader), Ok(mut meter)) = (
+        AccountLoader::<SessionStateAccount>::try_from(session_info),
+        AccountLoader::<InputBufferAccount>::try_from(input_info),
+        Account::<InferenceMeterAccount>::try_from(meter_info),
+    ) else {
+        return Ok(ADVANCE_INVALID_BUNDLE);
+    };
+    if bundle.iter().any(|a| !a.is_writable) {
+        return Ok(ADVANCE_INVALID_BUNDLE);
+    }
+    let (Ok(mut session), Ok(inputs), Ok(mut h_data)) = (
+        session_loader.load_mut(),
+        input_loader.load().map(|b| (b.frame, b.p1_ready, b.p2_ready, [b.player1, b.player2])),
+        hidden_info.try_borrow_mut_data(),
+    ) else {
+        return Ok(ADVANCE_INVALID_BUNDLE);
+    };
+    let (input_frame, p1_ready, p2_ready, inputs) = inputs;
+    let Ok(mut input_data) = input_info.try_borrow_mut_data() else {
+        return Ok(ADVANCE_INVALID_BUNDLE);
+    };
+    if session.hidden_state != hidden_info.key()
+        || session.input_buffer != input_info.key()
+        || meter.session != session_info.key()
+    {
+        return Ok(ADVANCE_INVALID_BUNDLE);
+    }
+
+    if session.status != STATUS_ACTIVE {
+        return Ok(ADVANCE_NOT_ACTIVE);
+    }
+    if !session.may_crank(&cranker) {
+        return Ok(ADVANCE_NOT_PERMITTED);
+    }
+    // Both inputs, submitted for this session's next frame (not left over
+    // from the last one)
+    if p1_ready == 0 || p2_ready == 0 || input_frame != session.frame + 1 {
+        return Ok(ADVANCE_INPUTS_NOT_READY);
+    }
+    let Ok(header) = check_hidden_state(&session, manifest, &h_data) else {
+        return Ok(ADVANCE_INVALID_BUNDLE);
+    };
+
+    step_session(
+        session_info.key(),
+        &mut session,
+        &mut h_data,
+        header,
+        &mut input_data,
+        inputs,
+        manifest,
+        regions,
+        experts,
+        cranker,
+        &mut meter,
+        None,
+    )?;
+    meter.exit(&crate::ID)?;
+    Ok(ADVANCE_ADVANCED)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Account Contexts
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Accounts)]
+pub struct InitManifest<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ModelManifestAccount>()
+    )]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSessionFee<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeMint<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCartridgeMint<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTokenGate<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSettlementVerifier<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRoyalties<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(mut, seeds = [TREASURY_SEED, manifest.key().as_ref()], bump)]
+    pub treasury: SystemAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTokenRoyalties<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// Owner of the treasury token account; signs the transfer
+    #[account(seeds = [TREASURY_SEED, manifest.key().as_ref()], bump)]
+    pub treasury: SystemAccount<'info>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Both manifests are boxed — two ~2.6KB accounts overflow the BPF stack frame.
+#[derive(Accounts)]
+pub struct ForkManifest<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ModelManifestAccount>()
+    )]
+    pub manifest: Box<Account<'info, ModelManifestAccount>>,
+    pub source: Box<Account<'info, ModelManifestAccount>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateManifest<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetModelCard<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeprecateModel<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTensorTable<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetHeadGroups<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRequantMode<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetActionTensors<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCharacterHeads<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttentionLayers<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(num_experts: u8)]
+pub struct InitExpertTable<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = ExpertTableAccount::space(manifest.num_layers as usize, num_experts as usize),
+        seeds = [EXPERT_TABLE_SEED, manifest.key().as_ref()],
+        bump,
+    )]
+    pub expert_table: Account<'info, ExpertTableAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetExpertLayer<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(mut, address = manifest.expert_table)]
+    pub expert_table: Account<'info, ExpertTableAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLuts<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetEncoderSpec<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLayerScales<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOutputSmoothing<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UploadWeights<'info> {
+    #[account(mut)]
+    pub weight_account: Account<'info, WeightAccount>,
+    /// CHECK: Same underlying account as weight_account — raw data access for weight bytes.
+    #[account(mut)]
+    pub weight_data: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeWeights<'info> {
+    #[account(mut)]
+    pub weight_account: Account<'info, WeightAccount>,
+    /// CHECK: Same underlying account — raw data access for hash verification.
+    pub weight_data: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitShardDirectory<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = ShardDirectoryAccount::space(manifest.expected_shards as usize),
+        seeds = [SHARD_DIRECTORY_SEED, manifest.key().as_ref()],
+        bump,
+    )]
+    pub shard_directory: Account<'info, ShardDirectoryAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterShard<'info> {
+    #[account(mut)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(mut, address = manifest.shard_directory)]
+    pub shard_directory: Account<'info, ShardDirectoryAccount>,
+    pub weight_account: Account<'info, WeightAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSession<'info> {
+    #[account(zero)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Hidden state — too large for Borsh, accessed as raw data.
+    /// Must be program-owned, sized for the model and never bound (checked
+    /// in the handler).
+    #[account(mut)]
+    pub hidden_state: AccountInfo<'info>,
+    #[account(zero)]
+    pub input_buffer: AccountLoader<'info, InputBufferAccount>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(mut)]
+    pub player1: Signer<'info>,
+    /// Royalty treasury for this manifest
+    #[account(mut, seeds = [TREASURY_SEED, manifest.key().as_ref()], bump)]
+    pub treasury: SystemAccount<'info>,
+    #[account(seeds = [FEE_SCHEDULE_SEED], bump = fee_schedule.bump)]
+    pub fee_schedule: Account<'info, FeeScheduleAccount>,
+    #[account(
+        init,
+        payer = player1,
+        space = 8 + std::mem::size_of::<InferenceMeterAccount>(),
+        seeds = [METER_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub meter: Account<'info, InferenceMeterAccount>,
+    pub system_program: Program<'info, System>,
+    /// Creator's cartridge NFT token account — required only when the
+    /// manifest sets cartridge_mint
+    pub cartridge_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Creator's token account for the manifest's gate_mint — required only
+    /// when the manifest sets one
+    pub gate_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// The manifest's fee_mint, and the creator's and treasury's token
+    /// accounts for it — required only when the royalty is in an SPL mint
+    pub fee_mint: Option<InterfaceAccount<'info, InterfaceMint>>,
+    #[account(mut)]
+    pub fee_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub treasury_fee_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct SetCrankMode<'info> {
+    #[account(mut)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub player1: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct JoinSession<'info> {
+    #[account(mut)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub player2: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSession<'info> {
+    #[account(mut)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReapSession<'info> {
+    #[account(mut, has_one = model)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// The session's manifest — only used to derive its treasury
+    pub model: Account<'info, ModelManifestAccount>,
+    #[account(seeds = [FEE_SCHEDULE_SEED], bump = fee_schedule.bump, has_one = protocol_treasury)]
+    pub fee_schedule: Account<'info, FeeScheduleAccount>,
+    #[account(mut, seeds = [TREASURY_SEED, model.key().as_ref()], bump)]
+    pub author_treasury: SystemAccount<'info>,
+    /// CHECK: Must be the session's top cranker (checked in the handler);
+    /// any account when nobody cranked.
+    #[account(mut)]
+    pub cranker: AccountInfo<'info>,
+    /// CHECK: Pinned to fee_schedule.protocol_treasury via has_one.
+    #[account(mut)]
+    pub protocol_treasury: AccountInfo<'info>,
+    #[account(mut)]
+    pub reaper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitInput<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(mut)]
+    pub input_buffer: AccountLoader<'info, InputBufferAccount>,
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RunInference<'info> {
+    #[account(
+        mut,
+        has_one = hidden_state @ WorldModelError::SessionAccountMismatch,
+        has_one = input_buffer @ WorldModelError::SessionAccountMismatch,
+        constraint = session.load()?.model == manifest.key() @ WorldModelError::ModelMismatch,
+    )]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Hidden state — raw data access for Mamba2 recurrent state;
+    /// the one created with the session.
+    #[account(mut, owner = crate::ID @ WorldModelError::SessionAccountMismatch)]
+    pub hidden_state: AccountInfo<'info>,
+    #[account(mut)]
+    pub input_buffer: AccountLoader<'info, InputBufferAccount>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// A finalized weight shard; with mamba2-inference, one of the
+    /// manifest's shards
+    #[account(constraint = weights.finalized @ WorldModelError::WeightsMismatch)]
+    pub weights: Account<'info, WeightAccount>,
+    /// Whoever advances the frame, as the session's crank_mode permits;
+    /// tallied for the cranker fee share
+    #[account(
+        constraint = session.load()?.may_crank(&cranker.key())
+            @ WorldModelError::CrankerNotPermitted
+    )]
+    pub cranker: Signer<'info>,
+    #[account(mut, seeds = [METER_SEED, session.key().as_ref()], bump = meter.bump)]
+    pub meter: Account<'info, InferenceMeterAccount>,
+    /// The model's calibration account, to record this frame's activation
+    /// ranges (see init_calibration)
+    #[account(
+        mut,
+        seeds = [CALIBRATION_SEED, manifest.key().as_ref()],
+        bump = calibration.bump,
+    )]
+    pub calibration: Option<Account<'info, CalibrationAccount>>,
+}
+
+/// frame_advance: the shared model and cranker. Session bundles (and, with
+/// mamba2-inference, shards) follow as remaining accounts.
+#[derive(Accounts)]
+pub struct FrameAdvance<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// CHECK: Weight data — read-only raw access for INT8 weights.
+    pub weights: AccountInfo<'info>,
+    /// Whoever advances the frames; tallied in each session for the
+    /// cranker fee share
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetHiddenState<'info> {
+    #[account(has_one = hidden_state @ WorldModelError::SessionAccountMismatch)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Hidden state — raw data access for zeroing; the one created
+    /// with the session.
+    #[account(mut)]
+    pub hidden_state: AccountInfo<'info>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadHiddenState<'info> {
+    /// CHECK: Hidden state — read-only raw data access.
+    pub hidden_state: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WarmStartSession<'info> {
+    #[account(has_one = hidden_state @ WorldModelError::SessionAccountMismatch)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Destination hidden state — raw data access; the one created
+    /// with the session.
+    #[account(mut)]
+    pub hidden_state: AccountInfo<'info>,
+    #[account(
+        constraint = source_session.load()?.hidden_state == source_hidden_state.key()
+            @ WorldModelError::SessionAccountMismatch
+    )]
+    pub source_session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Source hidden state — read-only raw data access; the one
+    /// created with the source session.
+    pub source_hidden_state: AccountInfo<'info>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub player1: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CheckpointHiddenState<'info> {
+    #[account(has_one = hidden_state @ WorldModelError::SessionAccountMismatch)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Hidden state — read-only raw data access; the one created
+    /// with the session.
+    pub hidden_state: AccountInfo<'info>,
+    /// CHECK: Checkpoint — the session's raw checkpoint PDA, created and
+    /// grown by checkpoint_hidden_state (up to CHECKPOINT_HEADER_SIZE +
+    /// checkpoint::rle_bound(data_size)).
+    #[account(mut, seeds = [CHECKPOINT_SEED, session.key().as_ref()], bump)]
+    pub checkpoint: AccountInfo<'info>,
+    /// A participant; pays the checkpoint's rent
+    #[account(mut)]
+    pub player: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RestoreCheckpoint<'info> {
+    #[account(has_one = hidden_state @ WorldModelError::SessionAccountMismatch)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Destination hidden state — raw data access; the one created
+    /// with the session.
+    #[account(mut)]
+    pub hidden_state: AccountInfo<'info>,
+    /// The checkpointed session; player 1 must have played in it
+    pub source_session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Checkpoint — read-only raw data access; the source session's
+    /// checkpoint PDA, so its contents were produced by
+    /// checkpoint_hidden_state.
+    #[account(
+        owner = crate::ID,
+        seeds = [CHECKPOINT_SEED, source_session.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: AccountInfo<'info>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    pub player1: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateWager<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        init,
+        payer = player1,
+        space = 8 + std::mem::size_of::<WagerEscrowAccount>(),
+        seeds = [WAGER_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, WagerEscrowAccount>,
+    #[account(mut)]
+    pub player1: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptWager<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        mut,
+        seeds = [WAGER_SEED, session.key().as_ref()],
+        bump = escrow.bump,
+        has_one = session,
+        constraint = escrow.mint == Pubkey::default() @ WorldModelError::WagerMintMismatch,
+    )]
+    pub escrow: Account<'info, WagerEscrowAccount>,
+    #[account(mut)]
+    pub player2: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Shared by settle_wager and refund_wager. Closing the escrow returns its
+/// rent to player 1, who paid it.
+#[derive(Accounts)]
+pub struct SettleWager<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        mut,
+        seeds = [WAGER_SEED, session.key().as_ref()],
+        bump = escrow.bump,
+        has_one = session,
+        constraint = escrow.mint == Pubkey::default() @ WorldModelError::WagerMintMismatch,
+        close = player1,
+    )]
+    pub escrow: Account<'info, WagerEscrowAccount>,
+    /// CHECK: Payout destination, pinned to the session's player 1.
+    #[account(mut, address = session.load()?.player1)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Payout destination, pinned to the session's player 2. Not
+    /// required writable: unjoined sessions pass the default key (the
+    /// system program) and receive nothing.
+    #[account(address = session.load()?.player2)]
+    pub player2: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTokenWager<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        init,
+        payer = player1,
+        space = 8 + std::mem::size_of::<WagerEscrowAccount>(),
+        seeds = [WAGER_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, WagerEscrowAccount>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    /// The escrow's associated token account, holding both stakes
+    #[account(
+        init,
+        payer = player1,
+        associated_token::mint = mint,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub player1_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub player1: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptTokenWager<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        mut,
+        seeds = [WAGER_SEED, session.key().as_ref()],
+        bump = escrow.bump,
+        has_one = session,
+        has_one = mint @ WorldModelError::WagerMintMismatch,
+    )]
+    pub escrow: Account<'info, WagerEscrowAccount>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub player2_token: InterfaceAccount<'info, TokenAccount>,
+    pub player2: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Shared by settle_token_wager and refund_token_wager. Payouts go to
+/// token accounts the players own; player 2's is only needed when they
+/// receive something.
+#[derive(Accounts)]
+pub struct SettleTokenWager<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        mut,
+        seeds = [WAGER_SEED, session.key().as_ref()],
+        bump = escrow.bump,
+        has_one = session,
+        has_one = mint @ WorldModelError::WagerMintMismatch,
+        close = player1,
+    )]
+    pub escrow: Account<'info, WagerEscrowAccount>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Rent destination, pinned to the session's player 1.
+    #[account(mut, address = session.load()?.player1)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Pinned to the session's player 2; only owns player2_token.
+    #[account(address = session.load()?.player2)]
+    pub player2: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = player1,
+        token::token_program = token_program,
+    )]
+    pub player1_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = player2,
+        token::token_program = token_program,
+    )]
+    pub player2_token: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitFeeSchedule<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<FeeScheduleAccount>(),
+        seeds = [FEE_SCHEDULE_SEED],
+        bump,
+    )]
+    pub fee_schedule: Account<'info, FeeScheduleAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::WorldModel>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ WorldModelError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [FEE_SCHEDULE_SEED],
+        bump = fee_schedule.bump,
+        has_one = authority @ WorldModelError::Unauthorized,
+    )]
+    pub fee_schedule: Account<'info, FeeScheduleAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleSessionFees<'info> {
+    #[account(mut, has_one = model)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// The session's manifest — only used to derive its treasury
+    pub model: Account<'info, ModelManifestAccount>,
+    #[account(seeds = [FEE_SCHEDULE_SEED], bump = fee_schedule.bump, has_one = protocol_treasury)]
+    pub fee_schedule: Account<'info, FeeScheduleAccount>,
+    #[account(mut, seeds = [TREASURY_SEED, model.key().as_ref()], bump)]
+    pub author_treasury: SystemAccount<'info>,
+    /// CHECK: Must be the session's top cranker (checked in the handler);
+    /// any account when nobody cranked.
+    #[account(mut)]
+    pub cranker: AccountInfo<'info>,
+    /// CHECK: Pinned to fee_schedule.protocol_treasury via has_one.
+    #[account(mut)]
+    pub protocol_treasury: AccountInfo<'info>,
+}
 
-            p.speed_ground_x = (stick_x * 2).clamp(-32767, 32767) as i16;
-            p.state_age = p.state_age.saturating_add(1);
-        }
+#[derive(Accounts)]
+pub struct TipPlayer<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        init_if_needed,
+        payer = tipper,
+        space = 8 + std::mem::size_of::<SessionStatsAccount>(),
+        seeds = [SESSION_STATS_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, SessionStatsAccount>,
+    #[account(mut)]
+    pub tipper: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        // Update frame counters
-        session.frame = frame;
+#[derive(Accounts)]
+pub struct ClaimTips<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        mut,
+        seeds = [SESSION_STATS_SEED, session.key().as_ref()],
+        bump = stats.bump,
+        has_one = session,
+    )]
+    pub stats: Account<'info, SessionStatsAccount>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+}
 
-        // Update hidden state frame counter
-        let hidden = &ctx.accounts.hidden_state;
-        let mut h_data = hidden.try_borrow_mut_data()?;
-        if h_data.len() >= HIDDEN_HEADER_SIZE {
-            let frame_bytes = frame.to_le_bytes();
-            h_data[9..13].copy_from_slice(&frame_bytes);
-        }
+#[derive(Accounts)]
+pub struct TipPlayerToken<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        init_if_needed,
+        payer = tipper,
+        space = 8 + std::mem::size_of::<TokenTipJarAccount>(),
+        seeds = [TIP_JAR_SEED, session.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub jar: Account<'info, TokenTipJarAccount>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    /// The jar's associated token account, holding its unclaimed tips
+    #[account(
+        init_if_needed,
+        payer = tipper,
+        associated_token::mint = mint,
+        associated_token::authority = jar,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub tipper_token: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub tipper: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
 
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct ClaimTokenTips<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        mut,
+        seeds = [TIP_JAR_SEED, session.key().as_ref(), mint.key().as_ref()],
+        bump = jar.bump,
+        has_one = session,
+        has_one = mint,
+    )]
+    pub jar: Account<'info, TokenTipJarAccount>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = jar,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = player,
+        token::token_program = token_program,
+    )]
+    pub player_token: InterfaceAccount<'info, TokenAccount>,
+    pub player: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-// ═══════════════════════════════════════════════════════════════════════════
-// Account Contexts
-// ═══════════════════════════════════════════════════════════════════════════
+#[derive(Accounts)]
+pub struct InitRentPool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<RentPoolAccount>(),
+        seeds = [RENT_POOL_SEED],
+        bump,
+    )]
+    pub rent_pool: Account<'info, RentPoolAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
 #[derive(Accounts)]
-pub struct InitManifest<'info> {
+pub struct DepositRentPool<'info> {
+    #[account(mut, seeds = [RENT_POOL_SEED], bump = rent_pool.bump)]
+    pub rent_pool: Account<'info, RentPoolAccount>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = 8 + std::mem::size_of::<RentFunderAccount>(),
+        seeds = [RENT_FUNDER_SEED, funder.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, RentFunderAccount>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRentPool<'info> {
+    #[account(mut, seeds = [RENT_POOL_SEED], bump = rent_pool.bump)]
+    pub rent_pool: Account<'info, RentPoolAccount>,
+    #[account(
+        mut,
+        seeds = [RENT_FUNDER_SEED, funder.key().as_ref()],
+        bump = position.bump,
+        has_one = funder,
+    )]
+    pub position: Account<'info, RentFunderAccount>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BorrowSessionRent<'info> {
+    #[account(has_one = player1 @ WorldModelError::UnauthorizedPlayer)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Checked against session.hidden_state; only its size is read.
+    pub hidden_state: AccountInfo<'info>,
+    /// CHECK: Checked against session.input_buffer; only its size is read.
+    pub input_buffer: AccountInfo<'info>,
+    #[account(mut, seeds = [RENT_POOL_SEED], bump = rent_pool.bump)]
+    pub rent_pool: Account<'info, RentPoolAccount>,
     #[account(
         init,
-        payer = authority,
-        space = 8 + std::mem::size_of::<ModelManifestAccount>()
+        payer = player1,
+        space = 8 + std::mem::size_of::<RentLoanAccount>(),
+        seeds = [RENT_LOAN_SEED, session.key().as_ref()],
+        bump,
     )]
-    pub manifest: Account<'info, ModelManifestAccount>,
+    pub loan: Account<'info, RentLoanAccount>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub player1: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UploadWeights<'info> {
+pub struct RepaySessionRent<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Checked against session.hidden_state; closed here.
+    #[account(mut, owner = crate::ID)]
+    pub hidden_state: AccountInfo<'info>,
+    /// CHECK: Checked against session.input_buffer; closed here.
+    #[account(mut, owner = crate::ID)]
+    pub input_buffer: AccountInfo<'info>,
+    /// CHECK: The session's wager escrow PDA; must not exist (never opened,
+    /// or settled or refunded).
+    #[account(seeds = [WAGER_SEED, session.key().as_ref()], bump)]
+    pub wager: AccountInfo<'info>,
+    /// CHECK: The session's stats PDA; read for unclaimed tips if it exists.
+    #[account(seeds = [SESSION_STATS_SEED, session.key().as_ref()], bump)]
+    pub stats: AccountInfo<'info>,
+    #[account(mut, seeds = [RENT_POOL_SEED], bump = rent_pool.bump)]
+    pub rent_pool: Account<'info, RentPoolAccount>,
+    #[account(
+        mut,
+        seeds = [RENT_LOAN_SEED, session.key().as_ref()],
+        bump = loan.bump,
+        has_one = session,
+        has_one = borrower,
+        close = borrower,
+    )]
+    pub loan: Account<'info, RentLoanAccount>,
+    /// CHECK: Pinned to loan.borrower via has_one.
     #[account(mut)]
-    pub weight_account: Account<'info, WeightAccount>,
-    /// CHECK: Same underlying account as weight_account — raw data access for weight bytes.
+    pub borrower: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateTournament<'info> {
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + std::mem::size_of::<TournamentAccount>(),
+        seeds = [TOURNAMENT_SEED, organizer.key().as_ref(), &id.to_le_bytes()],
+        bump,
+    )]
+    pub tournament: Account<'info, TournamentAccount>,
     #[account(mut)]
-    pub weight_data: AccountInfo<'info>,
-    pub authority: Signer<'info>,
+    pub organizer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct FinalizeWeights<'info> {
+pub struct JoinTournament<'info> {
+    #[account(mut)]
+    pub tournament: Account<'info, TournamentAccount>,
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SponsorTournament<'info> {
+    #[account(mut)]
+    pub tournament: Account<'info, TournamentAccount>,
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StartTournament<'info> {
+    #[account(mut, has_one = organizer @ WorldModelError::Unauthorized)]
+    pub tournament: Account<'info, TournamentAccount>,
+    pub organizer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ScheduleMatch<'info> {
+    #[account(mut, has_one = organizer @ WorldModelError::Unauthorized)]
+    pub tournament: Account<'info, TournamentAccount>,
+    pub organizer: Signer<'info>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+}
+
+#[derive(Accounts)]
+pub struct RecordMatch<'info> {
+    #[account(mut)]
+    pub tournament: Account<'info, TournamentAccount>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+}
+
+#[derive(Accounts)]
+pub struct DistributePrizes<'info> {
+    #[account(
+        mut,
+        has_one = organizer,
+        close = organizer,
+    )]
+    pub tournament: Account<'info, TournamentAccount>,
+    /// CHECK: Receives the tournament account's rent on close.
+    #[account(mut)]
+    pub organizer: AccountInfo<'info>,
+    /// CHECK: Must be the champion (checked in the handler).
+    #[account(mut)]
+    pub first: AccountInfo<'info>,
+    /// CHECK: Must be the runner-up (checked in the handler).
+    #[account(mut)]
+    pub second: AccountInfo<'info>,
+    /// CHECK: Semifinal loser from node 1 (any account in a 2-player bracket).
+    #[account(mut)]
+    pub third_a: AccountInfo<'info>,
+    /// CHECK: Semifinal loser from node 2 (any account in a 2-player bracket).
+    #[account(mut)]
+    pub third_b: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTournament<'info> {
+    #[account(
+        mut,
+        has_one = organizer @ WorldModelError::Unauthorized,
+        close = organizer,
+    )]
+    pub tournament: Account<'info, TournamentAccount>,
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintReplayNft<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Committed replay — a program-owned checkpoint whose header
+    /// names this session (checked in the handler).
+    #[account(owner = crate::ID)]
+    pub replay: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = minter,
+        space = 8 + std::mem::size_of::<ReplayRecordAccount>(),
+        seeds = [REPLAY_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub record: Account<'info, ReplayRecordAccount>,
+    #[account(
+        init,
+        payer = minter,
+        mint::decimals = 0,
+        mint::authority = mint,
+        seeds = [REPLAY_MINT_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = minter,
+        associated_token::mint = mint,
+        associated_token::authority = minter,
+    )]
+    pub minter_token: Account<'info, SplTokenAccount>,
+    #[account(mut)]
+    pub minter: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyKernels<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(address = manifest.shard_directory)]
+    pub shard_directory: Account<'info, ShardDirectoryAccount>,
+}
+
+#[derive(Accounts)]
+pub struct BeginMatmulAccumulator<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// CHECK: Accumulator — program-owned raw account, sized by the caller
+    /// (AccumulatorHeader::account_size(rows)).
+    #[account(mut, owner = crate::ID)]
+    pub accumulator: AccountInfo<'info>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AccumulateMatmulRows<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(address = manifest.shard_directory)]
+    pub shard_directory: Account<'info, ShardDirectoryAccount>,
+    /// CHECK: Accumulator — header checked against the session and cranker.
+    #[account(mut, owner = crate::ID)]
+    pub accumulator: AccountInfo<'info>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequantizeAccumulator<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// CHECK: Accumulator — header checked against the session and cranker.
+    #[account(mut, owner = crate::ID)]
+    pub accumulator: AccountInfo<'info>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitCalibration<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<CalibrationAccount>(),
+        seeds = [CALIBRATION_SEED, manifest.key().as_ref()],
+        bump,
+    )]
+    pub calibration: Account<'info, CalibrationAccount>,
     #[account(mut)]
-    pub weight_account: Account<'info, WeightAccount>,
-    /// CHECK: Same underlying account — raw data access for hash verification.
-    pub weight_data: AccountInfo<'info>,
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateSession<'info> {
-    #[account(zero)]
-    pub session: Account<'info, SessionStateAccount>,
-    /// CHECK: Hidden state — too large for Borsh, accessed as raw data.
+pub struct ResetCalibration<'info> {
+    pub manifest: Account<'info, ModelManifestAccount>,
+    #[account(mut, has_one = manifest)]
+    pub calibration: Account<'info, CalibrationAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BeginReplayVerification<'info> {
+    #[account(
+        init,
+        payer = verifier,
+        space = 8 + std::mem::size_of::<ReplayVerificationAccount>(),
+        seeds = [REPLAY_VERIFY_SEED, record.key().as_ref(), verifier.key().as_ref()],
+        bump,
+    )]
+    pub verification: Account<'info, ReplayVerificationAccount>,
+    #[account(has_one = session)]
+    pub record: Account<'info, ReplayRecordAccount>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(address = session.load()?.model @ WorldModelError::ModelMismatch)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// CHECK: The session's input buffer, holding its frame log.
+    #[account(address = session.load()?.input_buffer @ WorldModelError::SessionAccountMismatch)]
+    pub input_buffer: AccountInfo<'info>,
+    /// CHECK: Scratch hidden state for the re-execution — program-owned,
+    /// sized for the model and never bound (checked in the handler).
     #[account(mut)]
     pub hidden_state: AccountInfo<'info>,
-    #[account(zero)]
-    pub input_buffer: Account<'info, InputBufferAccount>,
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyReplayFrames<'info> {
+    #[account(
+        mut,
+        seeds = [REPLAY_VERIFY_SEED, record.key().as_ref(), verifier.key().as_ref()],
+        bump = verification.bump,
+        has_one = hidden_state @ WorldModelError::SessionAccountMismatch,
+    )]
+    pub verification: Account<'info, ReplayVerificationAccount>,
+    #[account(mut, has_one = session, has_one = replay)]
+    pub record: Account<'info, ReplayRecordAccount>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(address = session.load()?.model @ WorldModelError::ModelMismatch)]
     pub manifest: Account<'info, ModelManifestAccount>,
+    /// CHECK: The session's input buffer, holding its frame log.
+    #[account(address = session.load()?.input_buffer @ WorldModelError::SessionAccountMismatch)]
+    pub input_buffer: AccountInfo<'info>,
+    /// CHECK: The run's scratch hidden state, bound by begin_replay_verification.
     #[account(mut)]
-    pub player1: Signer<'info>,
+    pub hidden_state: AccountInfo<'info>,
+    /// CHECK: The record's checkpoint — keyframe read from its raw header.
+    pub replay: AccountInfo<'info>,
+    pub verifier: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct JoinSession<'info> {
+pub struct CloseReplayVerification<'info> {
+    #[account(
+        mut,
+        seeds = [REPLAY_VERIFY_SEED, verification.record.as_ref(), verifier.key().as_ref()],
+        bump = verification.bump,
+        has_one = hidden_state @ WorldModelError::SessionAccountMismatch,
+        close = verifier,
+    )]
+    pub verification: Account<'info, ReplayVerificationAccount>,
+    /// CHECK: The run's scratch hidden state, closed with it.
     #[account(mut)]
-    pub session: Account<'info, SessionStateAccount>,
-    pub player2: Signer<'info>,
+    pub hidden_state: AccountInfo<'info>,
+    #[account(mut)]
+    pub verifier: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CloseSession<'info> {
+pub struct SettleTransition<'info> {
     #[account(mut)]
-    pub session: Account<'info, SessionStateAccount>,
-    pub player: Signer<'info>,
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(address = session.load()?.model)]
+    pub manifest: Account<'info, ModelManifestAccount>,
+    /// CHECK: The manifest's settlement verifier program.
+    #[account(executable, address = manifest.settlement_verifier)]
+    pub verifier_program: AccountInfo<'info>,
+    pub submitter: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct SubmitInput<'info> {
-    pub session: Account<'info, SessionStateAccount>,
+pub struct ConfigureDelegation<'info> {
+    #[account(has_one = player1)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(
+        init_if_needed,
+        payer = player1,
+        space = 8 + std::mem::size_of::<DelegationConfigAccount>(),
+        seeds = [DELEGATION_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub config: Account<'info, DelegationConfigAccount>,
+    #[account(
+        init_if_needed,
+        payer = player1,
+        space = 8 + std::mem::size_of::<SettlementSummaryAccount>(),
+        seeds = [SETTLEMENT_SEED, session.key().as_ref()],
+        bump,
+    )]
+    pub summary: Account<'info, SettlementSummaryAccount>,
     #[account(mut)]
-    pub input_buffer: Account<'info, InputBufferAccount>,
-    pub player: Signer<'info>,
+    pub player1: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RunInference<'info> {
+pub struct DelegateSessionAccount<'info> {
+    #[account(
+        seeds = [DELEGATION_SEED, config.session.as_ref()],
+        bump = config.bump,
+        has_one = authority,
+    )]
+    pub config: Account<'info, DelegationConfigAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: One of the config's session accounts (checked in the handler).
+    #[account(mut)]
+    pub target: AccountInfo<'info>,
+    /// CHECK: Delegation buffer of `target`, created by the delegation CPI.
+    #[account(mut)]
+    pub buffer: AccountInfo<'info>,
+    /// CHECK: Checked and created by the delegation program.
+    #[account(mut)]
+    pub delegation_record: AccountInfo<'info>,
+    /// CHECK: Checked and created by the delegation program.
+    #[account(mut)]
+    pub delegation_metadata: AccountInfo<'info>,
+    /// CHECK: This program, the delegated account's owner.
+    #[account(address = crate::ID)]
+    pub owner_program: AccountInfo<'info>,
+    /// CHECK: The MagicBlock delegation program.
+    #[account(address = delegation::DELEGATION_PROGRAM_ID)]
+    pub delegation_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSession<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [DELEGATION_SEED, session.key().as_ref()],
+        bump = config.bump,
+        has_one = session,
+        has_one = hidden_state,
+        has_one = input_buffer,
+    )]
+    pub config: Account<'info, DelegationConfigAccount>,
+    #[account(mut)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: The session's hidden state, committed as raw data.
+    #[account(mut)]
+    pub hidden_state: AccountInfo<'info>,
+    /// CHECK: The session's input buffer, committed as raw data.
+    #[account(mut)]
+    pub input_buffer: AccountInfo<'info>,
+    /// CHECK: The session's meter, committed as raw data.
+    #[account(mut, seeds = [METER_SEED, session.key().as_ref()], bump)]
+    pub meter: AccountInfo<'info>,
+    /// CHECK: The rollup's magic context.
+    #[account(mut, address = delegation::MAGIC_CONTEXT_ID)]
+    pub magic_context: AccountInfo<'info>,
+    /// CHECK: The rollup's magic program.
+    #[account(address = delegation::MAGIC_PROGRAM_ID)]
+    pub magic_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UndelegateSession<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [DELEGATION_SEED, session.key().as_ref()],
+        bump = config.bump,
+        has_one = session,
+        has_one = hidden_state,
+        has_one = input_buffer,
+    )]
+    pub config: Account<'info, DelegationConfigAccount>,
     #[account(mut)]
-    pub session: Account<'info, SessionStateAccount>,
-    /// CHECK: Hidden state — raw data access for Mamba2 recurrent state.
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: The session's hidden state; hashed, then cut to its header.
     #[account(mut)]
     pub hidden_state: AccountInfo<'info>,
+    /// CHECK: The session's input buffer, committed as raw data.
     #[account(mut)]
-    pub input_buffer: Account<'info, InputBufferAccount>,
+    pub input_buffer: AccountInfo<'info>,
+    /// CHECK: The session's meter, read and committed as raw data.
+    #[account(mut, seeds = [METER_SEED, session.key().as_ref()], bump)]
+    pub meter: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED, session.key().as_ref()],
+        bump = summary.bump,
+        has_one = session,
+    )]
+    pub summary: Account<'info, SettlementSummaryAccount>,
+    /// CHECK: Optional checkpoint of the session's final hidden state
+    /// (header checked in the handler); the summary's replay pointer.
+    pub replay: Option<AccountInfo<'info>>,
+    /// CHECK: The rollup's magic context.
+    #[account(mut, address = delegation::MAGIC_CONTEXT_ID)]
+    pub magic_context: AccountInfo<'info>,
+    /// CHECK: The rollup's magic program.
+    #[account(address = delegation::MAGIC_PROGRAM_ID)]
+    pub magic_program: AccountInfo<'info>,
+}
+
+/// register_crank: the frame_advance accounts the task replays, plus its
+/// payer. Shards (mamba2-inference) follow as remaining accounts.
+#[derive(Accounts)]
+pub struct RegisterCrank<'info> {
+    #[account(has_one = player1)]
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    pub player1: Signer<'info>,
+    /// CHECK: The session's hidden state (checked in the handler).
+    pub hidden_state: AccountInfo<'info>,
+    /// CHECK: The session's input buffer (checked in the handler).
+    pub input_buffer: AccountInfo<'info>,
+    #[account(seeds = [METER_SEED, session.key().as_ref()], bump = meter.bump)]
+    pub meter: Account<'info, InferenceMeterAccount>,
+    #[account(address = session.load()?.model)]
     pub manifest: Account<'info, ModelManifestAccount>,
-    /// CHECK: Weight data — read-only raw access for INT8 weights.
+    /// CHECK: Weight data, passed through to frame_advance.
     pub weights: AccountInfo<'info>,
+    /// Funds the scheduled ticks and signs them as cranker
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The rollup's magic program.
+    #[account(address = delegation::MAGIC_PROGRAM_ID)]
+    pub magic_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnregisterCrank<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The rollup's magic program.
+    #[account(address = delegation::MAGIC_PROGRAM_ID)]
+    pub magic_program: AccountInfo<'info>,
+}
+
+/// get_session_summary / get_result / get_frame: the session, read-only.
+#[derive(Accounts)]
+pub struct ViewSession<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CreateArchiveTree<'info> {
+    /// CHECK: Bubblegum tree config PDA of `merkle_tree`, created by Bubblegum.
+    #[account(mut)]
+    pub tree_config: AccountInfo<'info>,
+    /// CHECK: Pre-allocated concurrent merkle tree, initialized by Bubblegum.
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Signs as the tree creator.
+    #[account(seeds = [ARCHIVE_AUTHORITY_SEED], bump)]
+    pub archive_authority: AccountInfo<'info>,
+    /// CHECK: SPL noop program.
+    #[account(address = archive::NOOP_PROGRAM_ID)]
+    pub log_wrapper: AccountInfo<'info>,
+    /// CHECK: SPL account compression program.
+    #[account(address = archive::COMPRESSION_PROGRAM_ID)]
+    pub compression_program: AccountInfo<'info>,
+    /// CHECK: Metaplex Bubblegum.
+    #[account(address = archive::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveReplay<'info> {
+    pub session: AccountLoader<'info, SessionStateAccount>,
+    /// CHECK: Committed replay — a program-owned checkpoint whose header
+    /// names this session (checked in the handler). Closed to `minter`.
+    #[account(mut, owner = crate::ID)]
+    pub replay: AccountInfo<'info>,
+    /// CHECK: The session's replay NFT record; must not exist.
+    #[account(
+        seeds = [REPLAY_SEED, session.key().as_ref()],
+        bump,
+        constraint = record.data_is_empty() @ WorldModelError::ReplayAlreadyMinted,
+    )]
+    pub record: AccountInfo<'info>,
+    #[account(mut)]
+    pub minter: Signer<'info>,
+    /// CHECK: Signs as the archive tree's creator.
+    #[account(seeds = [ARCHIVE_AUTHORITY_SEED], bump)]
+    pub archive_authority: AccountInfo<'info>,
+    /// CHECK: Bubblegum tree config PDA of `merkle_tree`, checked by Bubblegum.
+    #[account(mut)]
+    pub tree_config: AccountInfo<'info>,
+    /// CHECK: An archive tree, checked by Bubblegum against `tree_config`.
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    /// CHECK: SPL noop program.
+    #[account(address = archive::NOOP_PROGRAM_ID)]
+    pub log_wrapper: AccountInfo<'info>,
+    /// CHECK: SPL account compression program.
+    #[account(address = archive::COMPRESSION_PROGRAM_ID)]
+    pub compression_program: AccountInfo<'info>,
+    /// CHECK: Metaplex Bubblegum.
+    #[account(address = archive::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
 }
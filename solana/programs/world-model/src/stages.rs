@@ -0,0 +1,268 @@
+//! Geometry of the legal stages, by stage ID.
+//!
+//! Sessions carry only a stage ID; these tables give it a shape for the
+//! stub dynamics' collision, blast-zone KOs and the encoder's stage
+//! fields. Coordinates share PlayerState's fixed-point (game units × 256).
+//! The main platform is solid, centered on x = 0 with its surface at
+//! y = 0, and its corners are the ledges. The other platforms are
+//! pass-through: players land on them only from above. The numbers match
+//! the ECS stage-geometry component.
+//!
+//! create_session doesn't check stage IDs, so stages outside the table
+//! play on Final Destination's geometry (`stage_or_default`).
+
+/// Legal stage IDs (matching the stage vocabulary the model trains on)
+pub const STAGE_FOUNTAIN_OF_DREAMS: u8 = 2;
+pub const STAGE_POKEMON_STADIUM: u8 = 3;
+pub const STAGE_YOSHIS_STORY: u8 = 8;
+pub const STAGE_DREAM_LAND: u8 = 28;
+pub const STAGE_BATTLEFIELD: u8 = 31;
+pub const STAGE_FINAL_DESTINATION: u8 = 32;
+
+/// Where a KO'd player comes back: above the stage center, airborne
+pub const RESPAWN_Y: i32 = 40 * 256;
+
+/// Sub-stream of the session seed that spawn layouts are drawn from (the
+/// ECS session-lifecycle's, so both programs derive the same frame 0)
+pub const SPAWN_STREAM: u64 = 0x5350_4157_4E00_0000;
+
+/// Spawn distance from stage center, as a share of the main platform's
+/// half width in 1/256
+pub const SPAWN_SPREAD_MIN: i32 = 64;
+pub const SPAWN_SPREAD_MAX: i32 = 160;
+
+/// A pass-through platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Platform {
+    pub left: i32,
+    pub right: i32,
+    /// Surface height
+    pub y: i32,
+}
+
+/// Crossing any of these costs a stock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlastZones {
+    pub left: i32,
+    pub right: i32,
+    pub top: i32,
+    pub bottom: i32,
+}
+
+/// One legal stage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stage {
+    pub id: u8,
+    /// Main platform half width (its ledges are at ±half_width)
+    pub half_width: i32,
+    pub platforms: &'static [Platform],
+    pub blast: BlastZones,
+}
+
+/// Game units in hundredths → fixed-point × 256
+const fn fx(centi: i32) -> i32 {
+    centi * 256 / 100
+}
+
+const fn platform(left: i32, right: i32, y: i32) -> Platform {
+    Platform {
+        left: fx(left),
+        right: fx(right),
+        y: fx(y),
+    }
+}
+
+const fn blast(left: i32, right: i32, top: i32, bottom: i32) -> BlastZones {
+    BlastZones {
+        left: fx(left),
+        right: fx(right),
+        top: fx(top),
+        bottom: fx(bottom),
+    }
+}
+
+pub const LEGAL_STAGES: [Stage; 6] = [
+    Stage {
+        id: STAGE_FINAL_DESTINATION,
+        half_width: fx(8557),
+        platforms: &[],
+        blast: blast(-24600, 24600, 18800, -14000),
+    },
+    Stage {
+        id: STAGE_BATTLEFIELD,
+        half_width: fx(6840),
+        platforms: &[
+            platform(-5760, -2000, 2720),
+            platform(2000, 5760, 2720),
+            platform(-1880, 1880, 5440),
+        ],
+        blast: blast(-22400, 22400, 20000, -10880),
+    },
+    Stage {
+        id: STAGE_POKEMON_STADIUM,
+        half_width: fx(8775),
+        platforms: &[platform(-5500, -2500, 2500), platform(2500, 5500, 2500)],
+        blast: blast(-23000, 23000, 20000, -11100),
+    },
+    Stage {
+        id: STAGE_YOSHIS_STORY,
+        half_width: fx(5600),
+        platforms: &[
+            platform(-6000, -2800, 2345),
+            platform(2800, 6000, 2345),
+            platform(-1575, 1575, 4200),
+        ],
+        blast: blast(-17570, 17360, 16800, -9100),
+    },
+    Stage {
+        id: STAGE_DREAM_LAND,
+        half_width: fx(7727),
+        platforms: &[
+            platform(-6139, -3173, 3014),
+            platform(3173, 6303, 3014),
+            platform(-1902, 1902, 5143),
+        ],
+        blast: blast(-25500, 25500, 25000, -12300),
+    },
+    Stage {
+        id: STAGE_FOUNTAIN_OF_DREAMS,
+        half_width: fx(6335),
+        platforms: &[
+            platform(-5050, -2050, 2720),
+            platform(2050, 5050, 2720),
+            platform(-1500, 1500, 4275),
+        ],
+        blast: blast(-19875, 19875, 20250, -14625),
+    },
+];
+
+/// The legal stage `id`, if it is one.
+pub fn stage(id: u8) -> Option<&'static Stage> {
+    LEGAL_STAGES.iter().find(|s| s.id == id)
+}
+
+/// The geometry a session on stage `id` plays on: its own, or Final
+/// Destination's for IDs outside the legal list.
+pub fn stage_or_default(id: u8) -> &'static Stage {
+    stage(id).unwrap_or(&LEGAL_STAGES[0])
+}
+
+impl Stage {
+    /// Whether (x, y) is past a blast zone.
+    pub fn outside_blast_zone(&self, x: i32, y: i32) -> bool {
+        x < self.blast.left || x > self.blast.right || y > self.blast.top || y < self.blast.bottom
+    }
+
+    /// Whether x is over the main platform.
+    pub fn over_ground(&self, x: i32) -> bool {
+        (-self.half_width..=self.half_width).contains(&x)
+    }
+
+    /// Surface a player moving from height `prev_y` to `y` at `x` lands
+    /// on: the main platform from anywhere at or below it, or the highest
+    /// platform they fell through from above.
+    pub fn landing(&self, x: i32, prev_y: i32, y: i32) -> Option<i32> {
+        if y <= 0 && self.over_ground(x) {
+            return Some(0);
+        }
+        self.platforms
+            .iter()
+            .filter(|p| (p.left..=p.right).contains(&x) && prev_y >= p.y && y <= p.y)
+            .map(|p| p.y)
+            .max()
+    }
+
+    /// Whether a player at (x, y) stands on a surface: over the main
+    /// platform at or below its top, or on a platform.
+    pub fn supports(&self, x: i32, y: i32) -> bool {
+        (y <= 0 && self.over_ground(x))
+            || self
+                .platforms
+                .iter()
+                .any(|p| p.y == y && (p.left..=p.right).contains(&x))
+    }
+}
+
+/// Spawn (x, y, facing) per port for a session seed on `stage`: both
+/// players on the main platform, mirrored about its center at a
+/// seed-drawn spread of its half width, facing each other. A seed bit
+/// picks which port starts on the left. Stages outside the table spawn
+/// on Final Destination's layout.
+pub fn spawn_positions(seed: u64, stage: u8) -> [(i32, i32, u8); 2] {
+    let half_width = stage_or_default(stage).half_width;
+    let roll = awm_kernel::rng::substream(seed, SPAWN_STREAM ^ stage as u64);
+    let range = (SPAWN_SPREAD_MAX - SPAWN_SPREAD_MIN + 1) as u64;
+    let spread = SPAWN_SPREAD_MIN + (roll % range) as i32;
+    let offset = half_width * spread / 256;
+
+    let left = (-offset, 0, 1);
+    let right = (offset, 0, 0);
+    if (roll >> 32) & 1 == 0 {
+        [left, right]
+    } else {
+        [right, left]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legal_stages_are_well_formed() {
+        for s in LEGAL_STAGES.iter() {
+            assert_eq!(stage(s.id), Some(s));
+            assert!(s.half_width > 0);
+            assert!(s.blast.left < -s.half_width && s.blast.right > s.half_width);
+            assert!(s.blast.bottom < 0);
+            for p in s.platforms {
+                assert!(p.left < p.right && p.y > 0 && p.y < s.blast.top);
+            }
+        }
+        assert_eq!(stage(0), None);
+        assert_eq!(stage_or_default(0).id, STAGE_FINAL_DESTINATION);
+    }
+
+    #[test]
+    fn test_landing_and_blast_zones() {
+        let bf = stage_or_default(STAGE_BATTLEFIELD);
+        let side = bf.platforms[0];
+        let mid = (side.left + side.right) / 2;
+
+        // The main platform is solid; past the ledge there's nothing
+        assert_eq!(bf.landing(0, 10 * 256, -256), Some(0));
+        assert_eq!(bf.landing(0, -512, -256), Some(0));
+        assert_eq!(bf.landing(bf.half_width + 1, 256, -256), None);
+
+        // Platforms only catch players falling through them
+        assert_eq!(bf.landing(mid, side.y + 256, side.y - 256), Some(side.y));
+        assert_eq!(bf.landing(mid, side.y - 512, side.y - 256), None);
+        assert!(bf.supports(mid, side.y) && bf.supports(0, 0));
+        assert!(!bf.supports(bf.half_width + 1, 0));
+
+        assert!(!bf.outside_blast_zone(0, 0));
+        assert!(bf.outside_blast_zone(bf.blast.right + 1, 0));
+        assert!(bf.outside_blast_zone(0, bf.blast.bottom - 1));
+        assert!(bf.outside_blast_zone(0, bf.blast.top + 1));
+    }
+
+    #[test]
+    fn test_spawns_face_off_on_the_main_platform() {
+        let mut sides = [0; 2];
+        for seed in 0..64 {
+            for s in LEGAL_STAGES.iter() {
+                let [p1, p2] = spawn_positions(seed, s.id);
+                assert_eq!((p1.0, p1.1), (-p2.0, 0));
+                assert!(p1.0 != 0 && p1.0.abs() < s.half_width);
+                // Each faces the other
+                assert_eq!(p1.2, (p1.0 < 0) as u8);
+                assert_eq!(p2.2, (p2.0 < 0) as u8);
+                sides[(p1.0 > 0) as usize] += 1;
+            }
+            let fd = stage_or_default(STAGE_FINAL_DESTINATION);
+            assert!(spawn_positions(seed, 0)[0].0.abs() < fd.half_width);
+        }
+        // The seed picks which port starts on the left
+        assert!(sides[0] > 0 && sides[1] > 0);
+    }
+}
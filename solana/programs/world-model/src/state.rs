@@ -1,26 +1,233 @@
 use anchor_lang::prelude::*;
+use awm_kernel::mamba2::{ActivationRange, LayerKind};
+use awm_kernel::matmul::Rounding;
+use bytemuck::{Pod, Zeroable};
 
 // ── Constants ────────────────────────────────────────────────────────────────
 
 pub const MAX_LAYERS: usize = 16;
-pub const MAX_SHARDS: usize = 4;
+/// Shard count limit — bounded by the accounts one run_inference transaction
+/// can load (the shard directory itself has no fixed capacity).
+pub const MAX_SHARDS: usize = 64;
 pub const LUT_TOTAL_SIZE: usize = crate::lut::LUT_TOTAL_SIZE;
+/// Bytes init_manifest / update_luts take: the v1 tables. A whole block
+/// doesn't fit in one transaction alongside init_manifest's other args.
+pub const LUT_V1_SIZE: usize = crate::lut::LUT_V1_SIZE;
+/// Bytes set_extended_luts takes: the tables a v2 block appends
+pub const LUT_EXT_SIZE: usize = LUT_TOTAL_SIZE - LUT_V1_SIZE;
 pub const NUM_PLAYERS: usize = 2;
 pub const MAX_CHUNK_SIZE: usize = 1000;
+/// Max bytes returned by read-only view instructions (Solana return-data limit)
+pub const MAX_RETURN_DATA: usize = 1024;
+pub const METADATA_URI_LEN: usize = 128;
+/// Seed prefix for a manifest's shard directory PDA: ["shards", manifest]
+pub const SHARD_DIRECTORY_SEED: &[u8] = b"shards";
+/// Seed prefix for a mixture-of-experts manifest's expert table PDA:
+/// ["experts", manifest]
+pub const EXPERT_TABLE_SEED: &[u8] = b"experts";
+/// Experts per MoE layer limit (the kernel routes over a fixed array)
+pub const MAX_EXPERTS: usize = awm_kernel::mamba2::MAX_EXPERTS;
+/// Attention window limit for hybrid models (see `attention::max_window`
+/// for the per-model bound)
+pub const MAX_ATTN_WINDOW: usize = awm_kernel::attention::MAX_ATTN_WINDOW;
+/// Seed prefix for a session's wager escrow PDA: ["wager", session]
+pub const WAGER_SEED: &[u8] = b"wager";
+/// Deposits can be reclaimed if a wagered session hasn't ended by then
+pub const WAGER_TIMEOUT_SECS: i64 = 24 * 60 * 60;
+/// Seed for the singleton protocol fee schedule PDA
+pub const FEE_SCHEDULE_SEED: &[u8] = b"fee_schedule";
+/// Basis-point denominator for fee splits
+pub const BPS_DENOMINATOR: u16 = 10_000;
+/// A session with no activity for this long can be reaped (at
+/// DEFAULT_TICK_RATE; see SessionStateAccount::idle_timeout_secs)
+pub const SESSION_IDLE_TIMEOUT_SECS: i64 = 10 * 60;
+/// Reaper's cut of a reaped session's collected fee, in basis points
+pub const REAPER_BPS: u16 = 1_000;
+/// Distinct crankers tallied per session
+pub const MAX_CRANKERS: usize = 4;
+/// Accounts per session in frame_advance: session, hidden state, input
+/// buffer, meter
+pub const SESSION_BUNDLE_LEN: usize = 4;
+/// Seed prefix for a session's stats PDA: ["stats", session]
+pub const SESSION_STATS_SEED: &[u8] = b"stats";
+/// Seed prefix for a session's SPL tip jar PDA: ["tip_jar", session, mint]
+pub const TIP_JAR_SEED: &[u8] = b"tip_jar";
+/// Seed for the singleton session rent pool PDA
+pub const RENT_POOL_SEED: &[u8] = b"rent_pool";
+/// Seed prefix for a funder's rent pool position: ["rent_funder", funder]
+pub const RENT_FUNDER_SEED: &[u8] = b"rent_funder";
+/// Seed prefix for a session's rent loan: ["rent_loan", session]
+pub const RENT_LOAN_SEED: &[u8] = b"rent_loan";
+/// Seed prefix for tournaments: ["tournament", organizer, id u64 LE]
+pub const TOURNAMENT_SEED: &[u8] = b"tournament";
+/// Largest single-elimination bracket
+pub const MAX_ENTRANTS: usize = 8;
+/// Bracket nodes (heap order: node i plays children 2i+1, 2i+2; root = final)
+pub const BRACKET_NODES: usize = 2 * MAX_ENTRANTS - 1;
+/// Seed prefix for a session's hidden-state checkpoint: ["checkpoint", session]
+pub const CHECKPOINT_SEED: &[u8] = b"checkpoint";
+/// Seed prefix for a session's replay record: ["replay", session]
+pub const REPLAY_SEED: &[u8] = b"replay";
+/// Seed prefix for a session's replay NFT mint: ["replay_mint", session]
+pub const REPLAY_MINT_SEED: &[u8] = b"replay_mint";
+/// Seed prefix for a replay verification run: ["replay_verify", record, verifier]
+pub const REPLAY_VERIFY_SEED: &[u8] = b"replay_verify";
+/// Seed for the PDA that creates and mints into replay archive trees
+pub const ARCHIVE_AUTHORITY_SEED: &[u8] = b"archive_authority";
+/// Seed prefix for a session's inference meter: ["meter", session]
+pub const METER_SEED: &[u8] = b"meter";
+/// Seed prefix for a model's activation calibration: ["calibration", manifest]
+pub const CALIBRATION_SEED: &[u8] = b"calibration";
+/// Seed prefix for a manifest's royalty treasury PDA: ["treasury", manifest]
+pub const TREASURY_SEED: &[u8] = b"treasury";
+/// Seed prefix for a session's ephemeral rollup config: ["delegation", session]
+pub const DELEGATION_SEED: &[u8] = b"delegation";
+/// Seed prefix for a session's settlement summary: ["settlement", session]
+pub const SETTLEMENT_SEED: &[u8] = b"settlement";
+/// Session accounts handed to the ephemeral rollup, in frame_advance's
+/// bundle order
+pub const ER_ACCOUNT_SESSION: u8 = 0;
+pub const ER_ACCOUNT_HIDDEN_STATE: u8 = 1;
+pub const ER_ACCOUNT_INPUT_BUFFER: u8 = 2;
+pub const ER_ACCOUNT_METER: u8 = 3;
+/// The settlement summary rides along outside the bundle: only
+/// undelegation writes and commits it
+pub const ER_ACCOUNT_SUMMARY: u8 = 4;
+/// commit_mask with every ER_ACCOUNT_* bundle bit set
+pub const COMMIT_ALL: u8 = (1 << SESSION_BUNDLE_LEN) - 1;
+/// Mid-match commits skip the hidden state: it is large, and undelegation
+/// commits only its hash
+pub const DEFAULT_COMMIT_MASK: u8 = COMMIT_ALL & !(1 << ER_ACCOUNT_HIDDEN_STATE);
+/// Scheduler cadence for one frame per tick at 60fps
+pub const CRANK_INTERVAL_MS: u32 = 16;
+/// Session tick rates (frames per second) create_session accepts
+pub const TICK_RATE_30: u8 = 30;
+pub const TICK_RATE_60: u8 = 60;
+pub const TICK_RATE_120: u8 = 120;
+/// Tick rate of sessions that store 0 (created before tick rates existed)
+pub const DEFAULT_TICK_RATE: u8 = TICK_RATE_60;
+
+/// Per-layer tensor slots in the manifest tensor table
+pub const TENSOR_IN_PROJ: usize = 0;
+pub const TENSOR_OUT_PROJ: usize = 1;
+pub const TENSOR_NORM: usize = 2;
+pub const TENSOR_A_LOG: usize = 3;
+pub const TENSOR_DT_BIAS: usize = 4;
+/// Optional (rows = 0 when unset): models without one derive dt from x_ssm
+pub const TENSOR_DT_PROJ: usize = 5;
+pub const TENSORS_PER_LAYER: usize = 6;
+/// Shard directory name of each slot: tensor_dir::layer_tensor_hash(layer,
+/// name) keys `layers.{layer}.{name}`
+pub const TENSOR_NAMES: [&[u8]; TENSORS_PER_LAYER] =
+    [b"in_proj", b"out_proj", b"norm", b"a_log", b"dt_bias", b"dt_proj"];
+
+/// Per-expert tensor slots in the expert table
+pub const EXPERT_IN_PROJ: usize = 0;
+pub const EXPERT_OUT_PROJ: usize = 1;
+pub const TENSORS_PER_EXPERT: usize = 2;
+
+/// Tensor element types
+pub const TENSOR_DTYPE_I8: u8 = 0;
+/// INT8 followed by per-row sparsity masks (matmul::mask_stride(cols) bytes
+/// a row); in_proj and out_proj slots only
+pub const TENSOR_DTYPE_I8_MASKED: u8 = 1;
+/// No bytes of its own: the tensor reads the manifest's action_embedding
+/// transposed (weight tying); action_head only
+pub const TENSOR_DTYPE_TIED: u8 = 2;
+
+/// Encoder spec capacity
+pub const MAX_PLAYER_FIELDS: usize = 32;
+pub const MAX_GLOBAL_FIELDS: usize = 8;
+
+/// Character ids a multi-headed model can give their own decoder head
+pub const MAX_CHARACTER_HEADS: usize = 32;
+
+/// Encoder field sources — per-player state
+pub const FIELD_X: u8 = 0;
+pub const FIELD_Y: u8 = 1;
+pub const FIELD_PERCENT: u8 = 2;
+pub const FIELD_SHIELD: u8 = 3;
+pub const FIELD_SPEED_AIR_X: u8 = 4;
+pub const FIELD_SPEED_Y: u8 = 5;
+pub const FIELD_SPEED_GROUND_X: u8 = 6;
+pub const FIELD_SPEED_ATTACK_X: u8 = 7;
+pub const FIELD_SPEED_ATTACK_Y: u8 = 8;
+pub const FIELD_STATE_AGE: u8 = 9;
+pub const FIELD_HITLAG: u8 = 10;
+pub const FIELD_STOCKS: u8 = 11;
+pub const FIELD_FACING: u8 = 12;
+pub const FIELD_ON_GROUND: u8 = 13;
+pub const FIELD_ACTION_STATE: u8 = 14;
+pub const FIELD_JUMPS_LEFT: u8 = 15;
+pub const FIELD_CHARACTER: u8 = 16;
+/// Encoder field sources — per-player controller (input only, skipped on decode)
+pub const FIELD_STICK_X: u8 = 32;
+pub const FIELD_STICK_Y: u8 = 33;
+pub const FIELD_C_STICK_X: u8 = 34;
+pub const FIELD_C_STICK_Y: u8 = 35;
+pub const FIELD_TRIGGER_L: u8 = 36;
+pub const FIELD_TRIGGER_R: u8 = 37;
+pub const FIELD_BUTTONS: u8 = 38;
+/// Encoder field sources — global (input only, skipped on decode)
+pub const FIELD_STAGE: u8 = 64;
+/// The session stage's geometry (crate::stages), fixed-point like positions:
+/// main platform half width, right blast zone, top and bottom blast zones
+pub const FIELD_STAGE_HALF_WIDTH: u8 = 65;
+pub const FIELD_STAGE_BLAST_X: u8 = 66;
+pub const FIELD_STAGE_BLAST_TOP: u8 = 67;
+pub const FIELD_STAGE_BLAST_BOTTOM: u8 = 68;
+
+/// Encoder field kinds
+/// LINEAR: encode clamp(v / divisor), decode raw * divisor
+pub const FIELD_KIND_LINEAR: u8 = 0;
+/// BINARY: encode ±64, decode raw > 0
+pub const FIELD_KIND_BINARY: u8 = 1;
+/// CATEGORICAL: one slot per class (width = class count); encode one-hot
+/// ±64, decode argmax of the softmax over the slots
+pub const FIELD_KIND_CATEGORICAL: u8 = 2;
+/// HASHED: multi-hot over `width` slots for fields with more classes than
+/// fit one-hot (`divisor` = class count, e.g. num_action_states); a class
+/// sets the HASHED_PROBES slots `inference::hashed_slots` picks to +64,
+/// the rest -64, and decodes as the class whose slots sum highest
+pub const FIELD_KIND_HASHED: u8 = 3;
+/// Slots a HASHED class sets
+pub const HASHED_PROBES: usize = 2;
+
+/// validate_manifest failure bits (ManifestValidated.failures)
+pub const CHECK_WEIGHT_BYTES: u8 = 1 << 0;
+pub const CHECK_DIMENSIONS: u8 = 1 << 1;
+pub const CHECK_INPUT_SIZE: u8 = 1 << 2;
+pub const CHECK_HIDDEN_SIZE: u8 = 1 << 3;
+
+/// Requantization rounding modes (selected per model via the manifest)
+pub const REQUANT_TRUNCATE: u8 = 0;
+/// Stochastic rounding from the session's per-frame RNG stream
+pub const REQUANT_STOCHASTIC: u8 = 1;
+
+/// Hidden state element types (selected per model via the manifest)
+pub const HIDDEN_DTYPE_I8: u8 = 0;
+pub const HIDDEN_DTYPE_I16: u8 = 1;
 
 /// Session status values
 pub const STATUS_WAITING_PLAYERS: u8 = 1;
 pub const STATUS_ACTIVE: u8 = 2;
 pub const STATUS_ENDED: u8 = 3;
 
+/// Who may advance a session's frames (SessionStateAccount.crank_mode)
+pub const CRANK_MODE_PERMISSIONLESS: u8 = 0;
+/// Only the session's crank_authority
+pub const CRANK_MODE_DESIGNATED: u8 = 1;
+/// Only the session's two players
+pub const CRANK_MODE_PLAYERS: u8 = 2;
+
 // ── ModelManifestAccount ─────────────────────────────────────────────────────
 
 /// Model manifest — the "cartridge label" of the autonomous world.
 ///
 /// Contains architecture params, weight shard references, quantization scales,
-/// and activation LUTs. Created once per model version. ~2KB.
+/// activation LUTs, and the input/output encoder spec. Created once per model
+/// version. ~2.6KB.
 #[account]
-#[derive(Default)]
 pub struct ModelManifestAccount {
     /// Human-readable model name (e.g., "melee-mamba2-v1")
     pub name: [u8; 32],
@@ -34,30 +241,508 @@ pub struct ModelManifestAccount {
     pub d_state: u16,
     pub num_layers: u8,
     pub num_heads: u8,
+    /// Head groups sharing one B and C (0 = B and C derived from x_ssm)
+    pub ngroups: u8,
+    /// Hidden state storage type (HIDDEN_DTYPE_I8 or HIDDEN_DTYPE_I16)
+    pub hidden_dtype: u8,
 
     // ── Weight shard references ──────────────────────────────────────────
+    /// Number of shards the model is split into (ready once all registered)
+    pub expected_shards: u8,
+    /// Number of shards registered so far
     pub num_shards: u8,
-    pub shard_keys: [Pubkey; MAX_SHARDS],
-    pub shard_sizes: [u32; MAX_SHARDS],
+    /// ShardDirectoryAccount listing the shard keys and sizes (shared by forks)
+    pub shard_directory: Pubkey,
+    /// Sum of registered shard sizes
+    pub registered_weight_bytes: u64,
+
+    // ── Named-tensor offset table ────────────────────────────────────────
+    /// Where each layer's tensors live: tensor_table[layer][TENSOR_*]
+    pub tensor_table: [[TensorRef; TENSORS_PER_LAYER]; MAX_LAYERS],
+    /// Action-state embedding, [width × classes]: column c is the input
+    /// slots of class c (rows = 0: the action field is one-hot)
+    pub action_embedding: TensorRef,
+    /// Action-state logit head, [classes × width], over the action field's
+    /// output slots; TENSOR_DTYPE_TIED shares action_embedding's bytes
+    pub action_head: TensorRef,
+    /// Experts per mixture-of-experts layer (0 = dense model)
+    pub num_experts: u8,
+    /// ExpertTableAccount with the routers and expert projections
+    /// (default = dense; shared by forks)
+    pub expert_table: Pubkey,
+    /// Hybrid models: bit i marks layer i as sliding-window attention
+    /// instead of SSM (0 = pure Mamba2)
+    pub attention_layers: u16,
+    /// Frames each attention layer attends over; its KV buffer lives in
+    /// the layer's hidden-state slot
+    pub attn_window: u8,
 
     // ── Per-layer quantization parameters ────────────────────────────────
     pub layer_input_scales: [u16; MAX_LAYERS],
     pub layer_output_scales: [u16; MAX_LAYERS],
+    /// How requantization rounds (REQUANT_TRUNCATE or REQUANT_STOCHASTIC)
+    pub requant_mode: u8,
 
-    // ── Activation LUTs (4 × 256 = 1024 bytes) ──────────────────────────
+    // ── Activation LUTs (6 × 256 = 1536 bytes) ──────────────────────────
+    /// Packed LUT block; tables past lut_version's block are zero
     pub luts: [u8; LUT_TOTAL_SIZE],
+    /// lut::LUT_VERSION_* of the tables luts carries
+    pub lut_version: u8,
 
     // ── Input/Output encoding ────────────────────────────────────────────
+    /// Field layout of the model's input/output vector: field order and
+    /// per-field divisors, so encode_input needs no constants of its own
+    pub encoder_spec: EncoderSpec,
+    /// LINEAR slots of the encoder spec (set_encoder_spec keeps this,
+    /// num_binary and input_size in step with the spec)
     pub num_continuous: u8,
     pub num_action_states: u16,
+    /// BINARY slots of the encoder spec
     pub num_binary: u8,
+    /// Slots the encoder spec fills
     pub input_size: u16,
+    /// Multi-headed models: output slots per player block, which holds one
+    /// decoder head per character (0 = single-headed, blocks are one
+    /// player layout wide)
+    pub head_stride: u16,
+    /// Where character c's head starts within its player's block, indexed
+    /// by PlayerState.character; characters past the table use offset 0
+    pub head_offsets: [u16; MAX_CHARACTER_HEADS],
+    /// Output smoothing per encoder player field: the share, in 1/256, of
+    /// the previous frame's value a decoded LINEAR field keeps (an EMA;
+    /// 0 = raw model output)
+    pub output_smoothing: [u8; MAX_PLAYER_FIELDS],
 
     // ── Metadata ─────────────────────────────────────────────────────────
+    /// Authority for all manifest-gated instructions. May be a PDA (e.g. a
+    /// multisig or DAO governance program) signing via invoke_signed.
     pub authority: Pubkey,
+    /// Proposed new authority awaiting accept_authority (default = none)
+    pub pending_authority: Pubkey,
     pub ready: bool,
+    /// Set by deprecate_model — new sessions must opt in to play this model
+    pub deprecated: bool,
+    /// Replacement model, if any (default = none)
+    pub superseded_by: Pubkey,
+    /// Manifest this one was forked from (default = original)
+    pub forked_from: Pubkey,
+    /// Royalty charged to player 1 on create_session, paid into the
+    /// ["treasury", manifest] PDA (0 = free)
+    pub session_fee: u64,
+    /// SPL mint session_fee is denominated in, paid into the treasury PDA's
+    /// associated token account (default = lamports)
+    pub fee_mint: Pubkey,
+    /// Cartridge NFT mint; create_session requires holding or renting
+    /// (SPL delegate) one (default = ungated)
+    pub cartridge_mint: Pubkey,
+    /// Members-only gate: create_session requires holding at least
+    /// gate_min_amount of this SPL mint (default = ungated)
+    pub gate_mint: Pubkey,
+    pub gate_min_amount: u64,
+    /// Program that verifies off-chain transition proofs for
+    /// settle_transition (Groth16 verifier, TEE attestation checker, ...);
+    /// default = on-chain inference only
+    pub settlement_verifier: Pubkey,
     pub total_params: u32,
     pub total_weight_bytes: u32,
+
+    // ── Model card / provenance ──────────────────────────────────────────
+    /// Off-chain model card pointer (Arweave/IPFS URI, zero-padded UTF-8)
+    pub metadata_uri: [u8; METADATA_URI_LEN],
+    /// SHA-256 of the training dataset manifest
+    pub dataset_hash: [u8; 32],
+    /// Git commit (SHA-1) of the training code that produced the weights
+    pub training_commit: [u8; 20],
+}
+
+impl ModelManifestAccount {
+    /// Whether `key` is the current manifest authority.
+    pub fn is_authority(&self, key: &Pubkey) -> bool {
+        *key == self.authority
+    }
+
+    /// Cross-check the manifest's arithmetic. Returns a CHECK_* bitmask of
+    /// failed checks (0 = consistent).
+    /// The packed LUT block at its version's size.
+    pub fn lut_block(&self) -> &[u8] {
+        let size = crate::lut::lut_block_size(self.lut_version).unwrap_or(LUT_TOTAL_SIZE);
+        &self.luts[..size]
+    }
+
+    /// Where each player's fields start in the model output, given the
+    /// players' characters: player p's block start plus its character's
+    /// head offset.
+    pub fn output_offsets(&self, characters: [u8; NUM_PLAYERS]) -> [usize; NUM_PLAYERS] {
+        let stride = match self.head_stride {
+            0 => self.encoder_spec.player_width(),
+            s => s as usize,
+        };
+        core::array::from_fn(|p| {
+            let head = self.head_offsets.get(characters[p] as usize).copied().unwrap_or(0);
+            p * stride + head as usize
+        })
+    }
+
+    /// Layer `layer`'s kind, from attention_layers.
+    pub fn layer_kind(&self, layer: usize) -> LayerKind {
+        if self.attention_layers.checked_shr(layer as u32).unwrap_or(0) & 1 != 0 {
+            LayerKind::Attention { window: self.attn_window as usize }
+        } else {
+            LayerKind::Ssm
+        }
+    }
+
+    /// Requantization rounding for a frame whose RNG stream is seeded
+    /// `frame_seed` (`rng::frame_seed(session.seed, frame)`).
+    pub fn rounding(&self, frame_seed: u64) -> Rounding {
+        match self.requant_mode {
+            REQUANT_STOCHASTIC => Rounding::Stochastic { seed: frame_seed },
+            _ => Rounding::Truncate,
+        }
+    }
+
+    pub fn consistency_failures(&self) -> u8 {
+        let mut failures = 0;
+
+        // Registered shard sizes never exceed the total, and match it once
+        // every expected shard is in.
+        let shard_bytes = self.registered_weight_bytes;
+        let total = self.total_weight_bytes as u64;
+        let complete = self.num_shards >= self.expected_shards;
+        if shard_bytes > total || (complete && shard_bytes != total) {
+            failures |= CHECK_WEIGHT_BYTES;
+        }
+
+        // Mamba2 expands d_model by an integer factor; heads split d_inner,
+        // and head groups split the heads.
+        let d_model = self.d_model as usize;
+        let d_inner = self.d_inner as usize;
+        if d_model == 0
+            || d_inner < d_model
+            || !d_inner.is_multiple_of(d_model)
+            || self.num_heads == 0
+            || !d_inner.is_multiple_of(self.num_heads as usize)
+            || !crate::inference::manifest_config(self).groups_fit()
+        {
+            failures |= CHECK_DIMENSIONS;
+        }
+
+        // The encoder spec must produce exactly input_size slots, within
+        // d_model, num_continuous and num_binary of them LINEAR and BINARY.
+        let spec = &self.encoder_spec;
+        let width = spec.input_width();
+        if width != self.input_size as usize
+            || width > d_model
+            || spec.slots_of_kind(FIELD_KIND_LINEAR) != self.num_continuous as usize
+            || spec.slots_of_kind(FIELD_KIND_BINARY) != self.num_binary as usize
+        {
+            failures |= CHECK_INPUT_SIZE;
+        }
+
+        let hidden_bytes = HIDDEN_HEADER_SIZE
+            + hidden_data_size(self.num_layers, self.d_inner, self.d_state, self.hidden_dtype)
+                as usize;
+        if hidden_bytes > MAX_HIDDEN_SIZE {
+            failures |= CHECK_HIDDEN_SIZE;
+        }
+        // Attention layers' KV buffers must fit their hidden-state slots.
+        let max_window = crate::attention::max_window(d_inner, self.d_state as usize);
+        if self.attention_layers != 0
+            && (self.attn_window == 0 || self.attn_window as usize > max_window)
+        {
+            failures |= CHECK_HIDDEN_SIZE;
+        }
+
+        failures
+    }
+}
+
+// ── ShardDirectoryAccount ────────────────────────────────────────────────────
+
+/// One registered weight shard.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ShardEntry {
+    /// WeightAccount key (default = slot not yet registered)
+    pub key: Pubkey,
+    pub size: u32,
+}
+
+/// Shard directory — the variable-length list of a model's weight shards.
+///
+/// Lives at ["shards", manifest] and is sized to the manifest's
+/// expected_shards, so models aren't capped by a fixed manifest array.
+/// Forks point their manifest at the original directory.
+#[account]
+pub struct ShardDirectoryAccount {
+    pub manifest: Pubkey,
+    /// One slot per shard, indexed by WeightAccount.shard_index
+    pub entries: Vec<ShardEntry>,
+}
+
+impl ShardDirectoryAccount {
+    /// Account size (including discriminator) for `num_shards` entries.
+    pub fn space(num_shards: usize) -> usize {
+        8 + 32 + 4 + num_shards * (32 + 4)
+    }
+
+    /// Whether `accounts` are exactly the registered shards, in index order.
+    /// run_inference callers pass shards as remaining accounts.
+    pub fn matches_shards(&self, accounts: &[AccountInfo]) -> bool {
+        accounts.len() == self.entries.len()
+            && self.entries.iter().zip(accounts).all(|(e, a)| e.key == *a.key)
+    }
+}
+
+// ── ExpertTableAccount ───────────────────────────────────────────────────────
+
+/// Expert table — where a mixture-of-experts model's routers and expert
+/// projections live.
+///
+/// Lives at ["experts", manifest] and is sized to num_layers × num_experts.
+/// In an MoE layer the router, a (num_experts, d_model) tensor, picks one
+/// expert per frame whose in_proj/out_proj replace the tensor_table ones;
+/// experts may sit in any shard. Layers whose router is unset (rows = 0)
+/// stay dense. Forks point their manifest at the original table.
+#[account]
+pub struct ExpertTableAccount {
+    pub manifest: Pubkey,
+    pub num_experts: u8,
+    /// One router per layer
+    pub routers: Vec<TensorRef>,
+    /// Layer-major: experts[layer * num_experts + e][EXPERT_*]
+    pub experts: Vec<[TensorRef; TENSORS_PER_EXPERT]>,
+}
+
+impl ExpertTableAccount {
+    /// Account size (including discriminator) for a model's layer and
+    /// expert counts. TensorRef is 10 bytes.
+    pub fn space(num_layers: usize, num_experts: usize) -> usize {
+        8 + 32 + 1 + 4 + num_layers * 10 + 4 + num_layers * num_experts * TENSORS_PER_EXPERT * 10
+    }
+
+    /// One layer's experts, in expert order.
+    pub fn layer_experts(&self, layer: usize) -> Option<&[[TensorRef; TENSORS_PER_EXPERT]]> {
+        let n = self.num_experts as usize;
+        self.experts.get(layer * n..(layer + 1) * n)
+    }
+}
+
+// ── TensorRef ────────────────────────────────────────────────────────────────
+
+/// Location and shape of one weight tensor inside the shard accounts (10 bytes).
+/// Exported alongside the weights, so inference never guesses offsets.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct TensorRef {
+    /// Index into the manifest's shard directory
+    pub shard: u8,
+    /// Element type (TENSOR_DTYPE_*)
+    pub dtype: u8,
+    /// Byte offset within the shard's data region
+    pub offset: u32,
+    /// Row-major shape; vectors use cols = 1
+    pub rows: u16,
+    pub cols: u16,
+}
+
+impl TensorRef {
+    /// Size of the tensor in bytes.
+    pub fn byte_len(&self) -> usize {
+        self.rows as usize * self.cols as usize
+    }
+
+    /// Resolve to the tensor's bytes within the given shard data regions.
+    /// Returns None if the shard or byte range doesn't exist.
+    pub fn resolve<'a>(&self, shards: &[&'a [u8]]) -> Option<&'a [u8]> {
+        let shard = shards.get(self.shard as usize)?;
+        let start = self.offset as usize;
+        shard.get(start..start.checked_add(self.byte_len())?)
+    }
+
+    /// Resolve to the row sparsity masks stored right after a masked
+    /// tensor's weights: Some(empty) for a dense tensor, None if the masks
+    /// don't fit in the shard.
+    pub fn resolve_mask<'a>(&self, shards: &[&'a [u8]]) -> Option<&'a [u8]> {
+        if self.dtype != TENSOR_DTYPE_I8_MASKED {
+            return Some(&[]);
+        }
+        let shard = shards.get(self.shard as usize)?;
+        let start = (self.offset as usize).checked_add(self.byte_len())?;
+        let len = self.rows as usize * crate::matmul::mask_stride(self.cols as usize);
+        shard.get(start..start.checked_add(len)?)
+    }
+}
+
+// ── EncoderSpec ──────────────────────────────────────────────────────────────
+
+/// One slot group in the model input/output vector (5 bytes).
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct FieldSpec {
+    /// Where the value comes from (FIELD_*)
+    pub source: u8,
+    /// How it is quantized (FIELD_KIND_*)
+    pub kind: u8,
+    /// Number of vector slots the field occupies
+    pub width: u8,
+    /// LINEAR quantization step (encoded = value / divisor); HASHED class
+    /// count
+    pub divisor: u16,
+}
+
+impl FieldSpec {
+    pub const fn linear(source: u8, divisor: u16) -> Self {
+        Self { source, kind: FIELD_KIND_LINEAR, width: 1, divisor }
+    }
+
+    pub const fn binary(source: u8) -> Self {
+        Self { source, kind: FIELD_KIND_BINARY, width: 1, divisor: 1 }
+    }
+
+    pub const fn categorical(source: u8, classes: u8) -> Self {
+        Self { source, kind: FIELD_KIND_CATEGORICAL, width: classes, divisor: 1 }
+    }
+
+    pub const fn hashed(source: u8, width: u8, classes: u16) -> Self {
+        Self { source, kind: FIELD_KIND_HASHED, width, divisor: classes }
+    }
+}
+
+/// Ordered field layout of the model's input/output vector.
+///
+/// The vector is: player_fields for player 1, player_fields for player 2,
+/// then global_fields. Decoding walks the same layout and skips input-only
+/// sources (controller, stage). Stored in the manifest so each game/model
+/// can define its own encoding.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct EncoderSpec {
+    pub num_player_fields: u8,
+    pub player_fields: [FieldSpec; MAX_PLAYER_FIELDS],
+    pub num_global_fields: u8,
+    pub global_fields: [FieldSpec; MAX_GLOBAL_FIELDS],
+}
+
+impl EncoderSpec {
+    /// The Melee v2 encoding from nojohns-training (24 slots per player + stage).
+    pub fn melee_v2() -> Self {
+        let fields = [
+            FieldSpec::linear(FIELD_X, 256),
+            FieldSpec::linear(FIELD_Y, 256),
+            FieldSpec::linear(FIELD_PERCENT, 4),
+            FieldSpec::linear(FIELD_SHIELD, 1),
+            FieldSpec::linear(FIELD_SPEED_AIR_X, 2),
+            FieldSpec::linear(FIELD_SPEED_Y, 2),
+            FieldSpec::linear(FIELD_SPEED_GROUND_X, 2),
+            FieldSpec::linear(FIELD_SPEED_ATTACK_X, 2),
+            FieldSpec::linear(FIELD_SPEED_ATTACK_Y, 2),
+            FieldSpec::linear(FIELD_STATE_AGE, 1),
+            FieldSpec::linear(FIELD_HITLAG, 1),
+            FieldSpec::linear(FIELD_STOCKS, 1),
+            FieldSpec::binary(FIELD_FACING),
+            FieldSpec::binary(FIELD_ON_GROUND),
+            FieldSpec::linear(FIELD_ACTION_STATE, 1),
+            FieldSpec::linear(FIELD_JUMPS_LEFT, 1),
+            FieldSpec::linear(FIELD_CHARACTER, 1),
+            FieldSpec::linear(FIELD_STICK_X, 1),
+            FieldSpec::linear(FIELD_STICK_Y, 1),
+            FieldSpec::linear(FIELD_C_STICK_X, 1),
+            FieldSpec::linear(FIELD_C_STICK_Y, 1),
+            FieldSpec::linear(FIELD_TRIGGER_L, 1),
+            FieldSpec::linear(FIELD_TRIGGER_R, 1),
+            FieldSpec::linear(FIELD_BUTTONS, 1),
+        ];
+
+        let mut spec = Self {
+            num_player_fields: fields.len() as u8,
+            ..Self::default()
+        };
+        for (slot, field) in spec.player_fields.iter_mut().zip(fields) {
+            *slot = field;
+        }
+        spec.num_global_fields = 1;
+        spec.global_fields[0] = FieldSpec::linear(FIELD_STAGE, 1);
+        spec
+    }
+
+    /// Vector slots of one player's fields.
+    pub fn player_width(&self) -> usize {
+        let num_player = (self.num_player_fields as usize).min(MAX_PLAYER_FIELDS);
+        self.player_fields[..num_player].iter().map(|f| f.width as usize).sum()
+    }
+
+    /// Total number of vector slots: both players' fields plus global fields.
+    pub fn input_width(&self) -> usize {
+        let num_global = (self.num_global_fields as usize).min(MAX_GLOBAL_FIELDS);
+        let global: usize = self.global_fields[..num_global].iter().map(|f| f.width as usize).sum();
+        NUM_PLAYERS * self.player_width() + global
+    }
+
+    /// Vector slots of fields of `kind` (FIELD_KIND_*): both players' plus
+    /// the global fields'.
+    pub fn slots_of_kind(&self, kind: u8) -> usize {
+        let num_player = (self.num_player_fields as usize).min(MAX_PLAYER_FIELDS);
+        let num_global = (self.num_global_fields as usize).min(MAX_GLOBAL_FIELDS);
+        let slots = |fields: &[FieldSpec]| -> usize {
+            fields.iter().filter(|f| f.kind == kind).map(|f| f.width as usize).sum()
+        };
+        NUM_PLAYERS * slots(&self.player_fields[..num_player])
+            + slots(&self.global_fields[..num_global])
+    }
+
+    /// The CATEGORICAL action-state player field an action embedding and
+    /// logit head stand in for, if the spec has one.
+    pub fn action_field(&self) -> Option<FieldSpec> {
+        let num_player = (self.num_player_fields as usize).min(MAX_PLAYER_FIELDS);
+        self.player_fields[..num_player]
+            .iter()
+            .find(|f| f.source == FIELD_ACTION_STATE && f.kind == FIELD_KIND_CATEGORICAL)
+            .copied()
+    }
+
+    /// Whether every HASHED action-state field encodes exactly
+    /// `num_action_states` classes (any count while that is unset).
+    pub fn covers_action_states(&self, num_action_states: u16) -> bool {
+        let num_player = (self.num_player_fields as usize).min(MAX_PLAYER_FIELDS);
+        num_action_states == 0
+            || self.player_fields[..num_player]
+                .iter()
+                .filter(|f| f.source == FIELD_ACTION_STATE && f.kind == FIELD_KIND_HASHED)
+                .all(|f| f.divisor == num_action_states)
+    }
+
+    /// Whether `smoothing` only weights LINEAR player fields the model
+    /// decodes — not controller inputs, action-state or character ids, or
+    /// slots past num_player_fields.
+    pub fn accepts_smoothing(&self, smoothing: &[u8; MAX_PLAYER_FIELDS]) -> bool {
+        let num_player = (self.num_player_fields as usize).min(MAX_PLAYER_FIELDS);
+        smoothing.iter().enumerate().all(|(i, &weight)| {
+            weight == 0
+                || (i < num_player && {
+                    let f = &self.player_fields[i];
+                    f.kind == FIELD_KIND_LINEAR
+                        && f.source < FIELD_STICK_X
+                        && f.source != FIELD_ACTION_STATE
+                        && f.source != FIELD_CHARACTER
+                })
+        })
+    }
+
+    /// Whether every descriptor is well-formed and counts are in range.
+    pub fn is_valid(&self) -> bool {
+        let valid = |f: &FieldSpec| {
+            let width_ok = match f.kind {
+                FIELD_KIND_LINEAR | FIELD_KIND_BINARY => f.width == 1,
+                FIELD_KIND_CATEGORICAL => f.width >= 2,
+                // Enough slots for the probes to land apart, and more than
+                // one class
+                FIELD_KIND_HASHED => f.width as usize > HASHED_PROBES && f.divisor >= 2,
+                _ => false,
+            };
+            width_ok && f.divisor >= 1
+        };
+        (self.num_player_fields as usize) <= MAX_PLAYER_FIELDS
+            && (self.num_global_fields as usize) <= MAX_GLOBAL_FIELDS
+            && self.player_fields[..self.num_player_fields as usize].iter().all(valid)
+            && self.global_fields[..self.num_global_fields as usize].iter().all(valid)
+    }
 }
 
 // ── WeightAccount ────────────────────────────────────────────────────────────
@@ -82,7 +767,8 @@ pub const WEIGHT_HEADER_SIZE: usize = 82;
 
 /// Per-player state output from the world model.
 /// Matches the v2 encoding from nojohns-training.
-#[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+#[repr(C)]
+#[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize, Pod, Zeroable)]
 pub struct PlayerState {
     // ── Continuous (regression heads) ────────────────────────────────────
     pub x: i32,                 // Fixed-point: actual = x / 256.0
@@ -108,30 +794,220 @@ pub struct PlayerState {
     pub character: u8,
 }
 
+const _: () = assert!(core::mem::size_of::<PlayerState>() == 32);
+
+impl PlayerState {
+    /// Frame-0 state for both players, as set up when the second player
+    /// joins: standing at the spawns the session's seed draws on its stage
+    /// (stages::spawn_positions).
+    pub fn spawns(characters: [u8; NUM_PLAYERS], seed: u64, stage: u8) -> [Self; NUM_PLAYERS] {
+        let positions = crate::stages::spawn_positions(seed, stage);
+        core::array::from_fn(|slot| {
+            let (x, y, facing) = positions[slot];
+            PlayerState {
+                x,
+                y,
+                facing,
+                on_ground: 1,
+                jumps_left: 2,
+                shield_strength: 60 * 256,
+                stocks: 4,
+                character: characters[slot],
+                ..Default::default()
+            }
+        })
+    }
+}
+
 // ── SessionStateAccount ──────────────────────────────────────────────────────
 
 /// Session state — the current frame of the autonomous world.
 /// Updated every frame by run_inference.
-#[account]
-#[derive(Default)]
+///
+/// Zero-copy: instructions touch fields in place instead of Borsh-decoding
+/// and re-encoding the whole account each frame. Fields are ordered so the
+/// repr(C) layout has no implicit padding; `status` stays first so clients
+/// can memcmp-filter on byte 8.
+#[account(zero_copy)]
 pub struct SessionStateAccount {
     pub status: u8,
-    pub frame: u32,
-    pub max_frames: u32,
-    pub player1: Pubkey,
-    pub player2: Pubkey,
     pub stage: u8,
-    pub players: [PlayerState; NUM_PLAYERS],
-    pub model: Pubkey,
+    /// TICK_RATE_* (0 = DEFAULT_TICK_RATE); see tick_rate()
+    pub tick_rate: u8,
+    /// CRANK_MODE_*: who may run_inference / frame_advance this session
+    pub crank_mode: u8,
+    /// 1 + slot of the player who closed the match before its natural end
+    /// (0 = nobody); see outcome()
+    pub forfeited_by: u8,
+    pub _padding: [u8; 3],
     pub created_at: i64,
     pub last_update: i64,
     pub seed: u64,
+    /// Protocol session fee held in this account until settle_session_fees
+    pub fees_collected: u64,
+    pub player1: Pubkey,
+    pub player2: Pubkey,
+    pub model: Pubkey,
+    /// Hidden state and input buffer created with this session
+    pub hidden_state: Pubkey,
+    pub input_buffer: Pubkey,
+    pub players: [PlayerState; NUM_PLAYERS],
+    /// Frames advanced per cranker (first MAX_CRANKERS distinct signers)
+    pub crankers: [CrankerTally; MAX_CRANKERS],
+    /// Rolling per-frame commitment (see commit_frame); zero before frame 1
+    pub state_commitment: [u8; 32],
+    /// Hidden state hash accepted by the last settle_transition (off-chain
+    /// worlds keep their recurrent state with the prover), or of the final
+    /// hidden state once undelegate_session summarized it
+    pub hidden_state_hash: [u8; 32],
+    pub frame: u32,
+    pub max_frames: u32,
+    /// The only cranker under CRANK_MODE_DESIGNATED (default otherwise)
+    pub crank_authority: Pubkey,
+}
+
+// Exact layout (after the 8-byte discriminator) — clients read these offsets.
+const _: () = assert!(core::mem::size_of::<SessionStateAccount>() == 512);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, status) == 0);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, crank_mode) == 3);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, forfeited_by) == 4);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, created_at) == 8);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, player1) == 40);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, players) == 200);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, crankers) == 264);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, state_commitment) == 408);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, frame) == 472);
+const _: () = assert!(core::mem::offset_of!(SessionStateAccount, crank_authority) == 480);
+
+/// Frames one cranker advanced in a session (36 bytes).
+#[repr(C)]
+#[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize, Pod, Zeroable)]
+pub struct CrankerTally {
+    pub cranker: Pubkey,
+    pub frames: u32,
+}
+
+const _: () = assert!(core::mem::size_of::<CrankerTally>() == 36);
+
+impl SessionStateAccount {
+    /// Credit one frame to `cranker`. Signers beyond the first MAX_CRANKERS
+    /// distinct crankers aren't tallied.
+    pub fn record_crank(&mut self, cranker: Pubkey) {
+        if let Some(t) = self.crankers.iter_mut().find(|t| t.cranker == cranker) {
+            t.frames = t.frames.saturating_add(1);
+        } else if let Some(t) = self.crankers.iter_mut().find(|t| t.cranker == Pubkey::default()) {
+            t.cranker = cranker;
+            t.frames = 1;
+        }
+    }
+
+    /// Whether `cranker` may advance this session under its crank_mode.
+    pub fn may_crank(&self, cranker: &Pubkey) -> bool {
+        match self.crank_mode {
+            CRANK_MODE_DESIGNATED => *cranker == self.crank_authority,
+            CRANK_MODE_PLAYERS => *cranker == self.player1 || *cranker == self.player2,
+            _ => true,
+        }
+    }
+
+    /// Fold the current frame into the rolling state commitment
+    /// (see frame_commitment).
+    pub fn commit_frame(&mut self, inputs: &[ControllerInput; NUM_PLAYERS]) -> Result<()> {
+        self.state_commitment =
+            frame_commitment(&self.state_commitment, self.frame, &self.players, inputs)?;
+        Ok(())
+    }
+
+    /// When the session last did anything: its latest frame, or its
+    /// creation if no frame has run.
+    pub fn last_activity(&self) -> i64 {
+        self.last_update.max(self.created_at)
+    }
+
+    /// Frames per second the session runs at.
+    pub fn tick_rate(&self) -> u32 {
+        match self.tick_rate {
+            0 => DEFAULT_TICK_RATE as u32,
+            r => r as u32,
+        }
+    }
+
+    /// Wall-clock milliseconds per frame (16 at 60fps): the fastest crank
+    /// cadence that still plays the match in real time.
+    pub fn tick_interval_ms(&self) -> u32 {
+        1000 / self.tick_rate()
+    }
+
+    /// How long the session must sit idle before it can be reaped: the
+    /// frames SESSION_IDLE_TIMEOUT_SECS spans at DEFAULT_TICK_RATE, at this
+    /// session's rate.
+    pub fn idle_timeout_secs(&self) -> i64 {
+        SESSION_IDLE_TIMEOUT_SECS * DEFAULT_TICK_RATE as i64 / self.tick_rate() as i64
+    }
+
+    /// The cranker that advanced the most frames, if any.
+    pub fn top_cranker(&self) -> Option<Pubkey> {
+        self.crankers
+            .iter()
+            .filter(|t| t.frames > 0)
+            .max_by_key(|t| t.frames)
+            .map(|t| t.cranker)
+    }
+}
+
+/// One link of a session's rolling state commitment:
+///   c_n = SHA-256(c_{n-1} || frame LE || players || p1 input || p2 input)
+/// where `players` is the state after frame n. Anyone replaying the logged
+/// inputs can recompute the chain and compare it against the session or a
+/// checkpoint.
+pub fn frame_commitment(
+    prev: &[u8; 32],
+    frame: u32,
+    players: &[PlayerState; NUM_PLAYERS],
+    inputs: &[ControllerInput; NUM_PLAYERS],
+) -> Result<[u8; 32]> {
+    let mut buf = Vec::with_capacity(NUM_PLAYERS * (32 + 8));
+    for p in players.iter() {
+        p.serialize(&mut buf)?;
+    }
+    for input in inputs.iter() {
+        input.serialize(&mut buf)?;
+    }
+    Ok(solana_sha256_hasher::hashv(&[
+        prev.as_ref(),
+        frame.to_le_bytes().as_ref(),
+        buf.as_ref(),
+    ])
+    .to_bytes())
+}
+
+/// Public inputs of an off-chain state transition. settle_transition passes
+/// them, Borsh-encoded, to the manifest's settlement verifier, which must
+/// check the proof against exactly these values.
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct TransitionPublicInputs {
+    pub session: Pubkey,
+    pub model: Pubkey,
+    pub prev_frame: u32,
+    pub prev_commitment: [u8; 32],
+    pub frame: u32,
+    pub commitment: [u8; 32],
+    /// state_hash(frame, players) of the claimed post-state
+    pub players_hash: [u8; 32],
+    pub hidden_state_hash: [u8; 32],
 }
 
+/// Anchor instruction name a settlement verifier must implement:
+///   verify_transition(public_inputs: TransitionPublicInputs, proof: Vec<u8>)
+/// and fail unless the proof verifies. Extra accounts (verifying key,
+/// attestation report, instructions sysvar) are forwarded unchanged.
+pub const VERIFY_TRANSITION_IX: &str = "global:verify_transition";
+
 // ── ControllerInput ──────────────────────────────────────────────────────────
 
 /// Melee controller input for one player (8 bytes).
-#[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+#[repr(C)]
+#[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize, Pod, Zeroable)]
 pub struct ControllerInput {
     pub stick_x: i8,
     pub stick_y: i8,
@@ -143,24 +1019,106 @@ pub struct ControllerInput {
     pub buttons_ext: u8,
 }
 
+const _: () = assert!(core::mem::size_of::<ControllerInput>() == 8);
+
 // ── InputBufferAccount ───────────────────────────────────────────────────────
 
 /// Input buffer — controller inputs for the current frame.
 /// Both players submit inputs, then inference reads this buffer.
-#[account]
-#[derive(Default)]
+#[account(zero_copy)]
 pub struct InputBufferAccount {
     pub frame: u32,
     pub player1: ControllerInput,
     pub player2: ControllerInput,
-    pub p1_ready: bool,
-    pub p2_ready: bool,
+    /// 1 once the player has submitted for `frame` (bool isn't Pod)
+    pub p1_ready: u8,
+    pub p2_ready: u8,
+    pub _padding: [u8; 2],
+}
+
+const _: () = assert!(core::mem::size_of::<InputBufferAccount>() == 24);
+const _: () = assert!(core::mem::offset_of!(InputBufferAccount, player1) == 4);
+const _: () = assert!(core::mem::offset_of!(InputBufferAccount, p1_ready) == 20);
+
+// ── Frame log ────────────────────────────────────────────────────────────────
+
+/// Frames between keyframes in a session's frame log
+pub const KEYFRAME_INTERVAL: u32 = 60;
+/// One frame log entry: both players' inputs for the frame
+pub const FRAME_LOG_ENTRY_SIZE: usize = NUM_PLAYERS * core::mem::size_of::<ControllerInput>();
+/// Where the frame log starts in the input buffer account
+pub const FRAME_LOG_OFFSET: usize = 8 + core::mem::size_of::<InputBufferAccount>();
+
+/// The frame log is the input buffer account's trailing space, written by
+/// every run_inference / frame_advance so a replay can be re-executed:
+///   [inputs for frames 1..=max_frames (FRAME_LOG_ENTRY_SIZE each)]
+///   [commitment after every KEYFRAME_INTERVAL-th frame (32 bytes each)]
+/// Clients opt in by allocating the input buffer at input_buffer_size;
+/// a buffer without room for the log is never written past its header.
+pub fn frame_log_size(max_frames: u32) -> usize {
+    max_frames as usize * FRAME_LOG_ENTRY_SIZE + (max_frames / KEYFRAME_INTERVAL) as usize * 32
+}
+
+/// Input buffer account size with room for a full frame log.
+pub fn input_buffer_size(max_frames: u32) -> usize {
+    FRAME_LOG_OFFSET + frame_log_size(max_frames)
+}
+
+/// Whether `data` (an input buffer account) holds a full frame log.
+pub fn has_frame_log(data: &[u8], max_frames: u32) -> bool {
+    data.len() >= input_buffer_size(max_frames)
+}
+
+fn log_entry_offset(frame: u32) -> usize {
+    FRAME_LOG_OFFSET + (frame as usize - 1) * FRAME_LOG_ENTRY_SIZE
+}
+
+fn keyframe_offset(max_frames: u32, frame: u32) -> usize {
+    FRAME_LOG_OFFSET
+        + max_frames as usize * FRAME_LOG_ENTRY_SIZE
+        + (frame / KEYFRAME_INTERVAL - 1) as usize * 32
+}
+
+/// Log frame `frame`'s inputs, and on a keyframe the commitment after it.
+/// No-op when the buffer has no frame log.
+pub fn log_frame(
+    data: &mut [u8],
+    max_frames: u32,
+    frame: u32,
+    inputs: &[ControllerInput; NUM_PLAYERS],
+    commitment: &[u8; 32],
+) {
+    if frame == 0 || frame > max_frames || !has_frame_log(data, max_frames) {
+        return;
+    }
+    let at = log_entry_offset(frame);
+    data[at..at + FRAME_LOG_ENTRY_SIZE].copy_from_slice(bytemuck::bytes_of(inputs));
+    if frame.is_multiple_of(KEYFRAME_INTERVAL) {
+        let at = keyframe_offset(max_frames, frame);
+        data[at..at + 32].copy_from_slice(commitment);
+    }
+}
+
+/// Frame `frame`'s logged inputs (1 ≤ frame ≤ max_frames, full log).
+pub fn logged_inputs(data: &[u8], frame: u32) -> [ControllerInput; NUM_PLAYERS] {
+    let at = log_entry_offset(frame);
+    bytemuck::pod_read_unaligned(&data[at..at + FRAME_LOG_ENTRY_SIZE])
+}
+
+/// The commitment logged after keyframe `frame` (a multiple of
+/// KEYFRAME_INTERVAL), or None between keyframes.
+pub fn logged_keyframe(data: &[u8], max_frames: u32, frame: u32) -> Option<[u8; 32]> {
+    if frame == 0 || !frame.is_multiple_of(KEYFRAME_INTERVAL) {
+        return None;
+    }
+    let at = keyframe_offset(max_frames, frame);
+    data.get(at..at + 32).map(|k| k.try_into().unwrap())
 }
 
 // ── Hidden state constants ───────────────────────────────────────────────────
 
 /// Hidden state is accessed via raw AccountInfo (too large for Borsh).
-/// Layout: [header (16 bytes)] [h_data (num_layers * d_inner * d_state bytes)]
+/// Layout: [header (16 bytes)] [h_data (num_layers * d_inner * d_state * elem_size bytes)]
 ///
 /// Header:
 ///   - num_layers: u8     (offset 0)
@@ -169,36 +1127,699 @@ pub struct InputBufferAccount {
 ///   - data_size: u32 LE  (offset 5)
 ///   - frame: u32 LE      (offset 9)
 ///   - initialized: u8    (offset 13)
-///   - padding: [u8; 2]   (offset 14)
+///   - dtype: u8          (offset 14) — HIDDEN_DTYPE_I8 or HIDDEN_DTYPE_I16
+///   - padding: u8        (offset 15)
 pub const HIDDEN_HEADER_SIZE: usize = 16;
+/// Largest hidden state account (header + data) a model may require
+pub const MAX_HIDDEN_SIZE: usize = 200_000;
+
+/// Parsed hidden state header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HiddenHeader {
+    pub num_layers: u8,
+    pub d_inner: u16,
+    pub d_state: u16,
+    pub data_size: u32,
+    pub frame: u32,
+    pub initialized: bool,
+    pub dtype: u8,
+}
+
+impl HiddenHeader {
+    /// Whether this header's dimensions and dtype match the manifest's model.
+    pub fn matches_manifest(&self, manifest: &ModelManifestAccount) -> bool {
+        self.num_layers == manifest.num_layers
+            && self.d_inner == manifest.d_inner
+            && self.d_state == manifest.d_state
+            && self.dtype == manifest.hidden_dtype
+            && self.data_size
+                == hidden_data_size(
+                    manifest.num_layers,
+                    manifest.d_inner,
+                    manifest.d_state,
+                    manifest.hidden_dtype,
+                )
+    }
+}
+
+/// Bytes per hidden state element for the given dtype.
+pub fn hidden_elem_size(dtype: u8) -> usize {
+    if dtype == HIDDEN_DTYPE_I16 { 2 } else { 1 }
+}
+
+/// Size in bytes of the hidden state data region for the given dimensions.
+pub fn hidden_data_size(num_layers: u8, d_inner: u16, d_state: u16, dtype: u8) -> u32 {
+    (num_layers as u32) * (d_inner as u32) * (d_state as u32) * hidden_elem_size(dtype) as u32
+}
+
+/// Byte range of rows [row_start, row_start + row_count) of one layer's h matrix
+/// (d_inner × d_state, row-major) within raw hidden state account data.
+/// Returns None if the layer or rows fall outside the header's dimensions.
+pub fn hidden_layer_range(
+    header: &HiddenHeader,
+    layer: u8,
+    row_start: u16,
+    row_count: u16,
+) -> Option<core::ops::Range<usize>> {
+    if layer >= header.num_layers
+        || row_start as usize + row_count as usize > header.d_inner as usize
+    {
+        return None;
+    }
+    let row_bytes = header.d_state as usize * hidden_elem_size(header.dtype);
+    let layer_bytes = header.d_inner as usize * row_bytes;
+    let start = HIDDEN_HEADER_SIZE + layer as usize * layer_bytes + row_start as usize * row_bytes;
+    Some(start..start + row_count as usize * row_bytes)
+}
 
 /// Read hidden state header fields from raw account data.
-pub fn read_hidden_header(data: &[u8]) -> (u8, u16, u16, u32, u32, bool) {
-    let num_layers = data[0];
-    let d_inner = u16::from_le_bytes([data[1], data[2]]);
-    let d_state = u16::from_le_bytes([data[3], data[4]]);
-    let data_size = u32::from_le_bytes([data[5], data[6], data[7], data[8]]);
-    let frame = u32::from_le_bytes([data[9], data[10], data[11], data[12]]);
-    let initialized = data[13] != 0;
-    (num_layers, d_inner, d_state, data_size, frame, initialized)
+pub fn read_hidden_header(data: &[u8]) -> HiddenHeader {
+    HiddenHeader {
+        num_layers: data[0],
+        d_inner: u16::from_le_bytes([data[1], data[2]]),
+        d_state: u16::from_le_bytes([data[3], data[4]]),
+        data_size: u32::from_le_bytes([data[5], data[6], data[7], data[8]]),
+        frame: u32::from_le_bytes([data[9], data[10], data[11], data[12]]),
+        initialized: data[13] != 0,
+        dtype: data[14],
+    }
 }
 
 /// Write hidden state header fields to raw account data.
-pub fn write_hidden_header(
-    data: &mut [u8],
-    num_layers: u8,
-    d_inner: u16,
-    d_state: u16,
-    data_size: u32,
-    frame: u32,
-    initialized: bool,
-) {
-    data[0] = num_layers;
-    data[1..3].copy_from_slice(&d_inner.to_le_bytes());
-    data[3..5].copy_from_slice(&d_state.to_le_bytes());
-    data[5..9].copy_from_slice(&data_size.to_le_bytes());
-    data[9..13].copy_from_slice(&frame.to_le_bytes());
-    data[13] = initialized as u8;
-    data[14] = 0;
+pub fn write_hidden_header(data: &mut [u8], header: &HiddenHeader) {
+    data[0] = header.num_layers;
+    data[1..3].copy_from_slice(&header.d_inner.to_le_bytes());
+    data[3..5].copy_from_slice(&header.d_state.to_le_bytes());
+    data[5..9].copy_from_slice(&header.data_size.to_le_bytes());
+    data[9..13].copy_from_slice(&header.frame.to_le_bytes());
+    data[13] = header.initialized as u8;
+    data[14] = header.dtype;
     data[15] = 0;
 }
+
+// ── Hidden state checkpoint ──────────────────────────────────────────────────
+
+/// Compressed hidden state checkpoint, written on session end (raw AccountInfo
+/// at the session's CHECKPOINT_SEED PDA, written once).
+/// Layout: [header (84 bytes)] [RLE stream (compressed_size bytes)]
+///
+/// Header:
+///   - session: Pubkey        (offset 0)
+///   - model: Pubkey          (offset 32)
+///   - frame: u32 LE          (offset 64)
+///   - num_layers: u8         (offset 68)
+///   - dtype: u8              (offset 69)
+///   - d_inner: u16 LE        (offset 70)
+///   - d_state: u16 LE        (offset 72)
+///   - raw_size: u32 LE       (offset 74)
+///   - compressed_size: u32 LE (offset 78)
+///   - padding: [u8; 2]       (offset 82)
+///   - state_commitment: [u8; 32] (offset 84) — session commitment at `frame`
+pub const CHECKPOINT_HEADER_SIZE: usize = 116;
+
+/// Parsed checkpoint header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CheckpointHeader {
+    pub session: Pubkey,
+    pub model: Pubkey,
+    pub frame: u32,
+    pub num_layers: u8,
+    pub dtype: u8,
+    pub d_inner: u16,
+    pub d_state: u16,
+    pub raw_size: u32,
+    pub compressed_size: u32,
+    pub state_commitment: [u8; 32],
+}
+
+/// Read checkpoint header fields from raw account data.
+pub fn read_checkpoint_header(data: &[u8]) -> CheckpointHeader {
+    CheckpointHeader {
+        session: Pubkey::new_from_array(data[0..32].try_into().unwrap()),
+        model: Pubkey::new_from_array(data[32..64].try_into().unwrap()),
+        frame: u32::from_le_bytes([data[64], data[65], data[66], data[67]]),
+        num_layers: data[68],
+        dtype: data[69],
+        d_inner: u16::from_le_bytes([data[70], data[71]]),
+        d_state: u16::from_le_bytes([data[72], data[73]]),
+        raw_size: u32::from_le_bytes([data[74], data[75], data[76], data[77]]),
+        compressed_size: u32::from_le_bytes([data[78], data[79], data[80], data[81]]),
+        state_commitment: data[84..116].try_into().unwrap(),
+    }
+}
+
+/// Write checkpoint header fields to raw account data.
+pub fn write_checkpoint_header(data: &mut [u8], header: &CheckpointHeader) {
+    data[0..32].copy_from_slice(header.session.as_ref());
+    data[32..64].copy_from_slice(header.model.as_ref());
+    data[64..68].copy_from_slice(&header.frame.to_le_bytes());
+    data[68] = header.num_layers;
+    data[69] = header.dtype;
+    data[70..72].copy_from_slice(&header.d_inner.to_le_bytes());
+    data[72..74].copy_from_slice(&header.d_state.to_le_bytes());
+    data[74..78].copy_from_slice(&header.raw_size.to_le_bytes());
+    data[78..82].copy_from_slice(&header.compressed_size.to_le_bytes());
+    data[82] = 0;
+    data[83] = 0;
+    data[84..116].copy_from_slice(&header.state_commitment);
+}
+
+// ── Matmul accumulator ───────────────────────────────────────────────────────
+
+/// Rows covered by one completion bit of a matmul accumulator. Row ranges
+/// passed to accumulate_matmul_rows start on a chunk boundary.
+pub const ACCUM_CHUNK_ROWS: usize = 32;
+
+/// Completion bitmap size in bytes.
+pub const ACCUM_BITMAP_SIZE: usize = 16;
+
+/// Largest tensor (in rows) one accumulator can track.
+pub const ACCUM_MAX_ROWS: usize = ACCUM_BITMAP_SIZE * 8 * ACCUM_CHUNK_ROWS;
+
+/// Scratch account for one layer matmul split across transactions by row
+/// range (raw AccountInfo, program-owned, sized by the caller).
+/// Layout: [header (124 bytes)] [INT32 sums: rows × 4] [INT8 output: rows]
+///
+/// Header:
+///   - session: Pubkey        (offset 0)
+///   - cranker: Pubkey        (offset 32) — only signer that may fill it
+///   - input_hash: [u8; 32]   (offset 64) — sha256 of the INT8 input vector
+///   - frame: u32 LE          (offset 96)
+///   - layer: u8              (offset 100)
+///   - tensor: u8             (offset 101) — TENSOR_IN_PROJ or TENSOR_OUT_PROJ
+///   - rows: u16 LE           (offset 102)
+///   - cols: u16 LE           (offset 104)
+///   - requantized: u8        (offset 106)
+///   - padding: u8            (offset 107)
+///   - bitmap: [u8; 16]       (offset 108) — one bit per ACCUM_CHUNK_ROWS rows
+pub const ACCUM_HEADER_SIZE: usize = 124;
+
+/// Parsed matmul accumulator header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccumulatorHeader {
+    pub session: Pubkey,
+    pub cranker: Pubkey,
+    pub input_hash: [u8; 32],
+    pub frame: u32,
+    pub layer: u8,
+    pub tensor: u8,
+    pub rows: u16,
+    pub cols: u16,
+    pub requantized: bool,
+    pub bitmap: [u8; ACCUM_BITMAP_SIZE],
+}
+
+impl AccumulatorHeader {
+    /// Account size needed for a tensor with `rows` output rows.
+    pub fn account_size(rows: usize) -> usize {
+        ACCUM_HEADER_SIZE + rows * 5
+    }
+
+    /// Completion bits needed to cover every row.
+    pub fn chunks(&self) -> usize {
+        (self.rows as usize).div_ceil(ACCUM_CHUNK_ROWS)
+    }
+
+    /// Mark chunks [first, first + count) as accumulated.
+    pub fn mark_chunks(&mut self, first: usize, count: usize) {
+        for c in first..first + count {
+            self.bitmap[c / 8] |= 1 << (c % 8);
+        }
+    }
+
+    /// Whether every row has been accumulated.
+    pub fn is_complete(&self) -> bool {
+        (0..self.chunks()).all(|c| self.bitmap[c / 8] & (1 << (c % 8)) != 0)
+    }
+
+    /// The pipeline segment this accumulator is claimed for.
+    pub fn segment(&self) -> u16 {
+        accumulator_segment(self.layer, self.tensor)
+    }
+}
+
+/// Pipeline segment id of one layer matmul: the layer in the high byte,
+/// the TENSOR_* index in the low byte.
+pub fn accumulator_segment(layer: u8, tensor: u8) -> u16 {
+    ((layer as u16) << 8) | tensor as u16
+}
+
+/// Hash binding an accumulator to its INT8 input vector.
+pub fn accumulator_input_hash(input: &[i8]) -> [u8; 32] {
+    let bytes: Vec<u8> = input.iter().map(|&v| v as u8).collect();
+    solana_sha256_hasher::hash(&bytes).to_bytes()
+}
+
+/// Read matmul accumulator header fields from raw account data.
+pub fn read_accumulator_header(data: &[u8]) -> AccumulatorHeader {
+    AccumulatorHeader {
+        session: Pubkey::new_from_array(data[0..32].try_into().unwrap()),
+        cranker: Pubkey::new_from_array(data[32..64].try_into().unwrap()),
+        input_hash: data[64..96].try_into().unwrap(),
+        frame: u32::from_le_bytes([data[96], data[97], data[98], data[99]]),
+        layer: data[100],
+        tensor: data[101],
+        rows: u16::from_le_bytes([data[102], data[103]]),
+        cols: u16::from_le_bytes([data[104], data[105]]),
+        requantized: data[106] != 0,
+        bitmap: data[108..124].try_into().unwrap(),
+    }
+}
+
+/// Write matmul accumulator header fields to raw account data.
+pub fn write_accumulator_header(data: &mut [u8], header: &AccumulatorHeader) {
+    data[0..32].copy_from_slice(header.session.as_ref());
+    data[32..64].copy_from_slice(header.cranker.as_ref());
+    data[64..96].copy_from_slice(&header.input_hash);
+    data[96..100].copy_from_slice(&header.frame.to_le_bytes());
+    data[100] = header.layer;
+    data[101] = header.tensor;
+    data[102..104].copy_from_slice(&header.rows.to_le_bytes());
+    data[104..106].copy_from_slice(&header.cols.to_le_bytes());
+    data[106] = header.requantized as u8;
+    data[107] = 0;
+    data[108..124].copy_from_slice(&header.bitmap);
+}
+
+// ── Wager escrow ─────────────────────────────────────────────────────────────
+
+/// Outcome of an ended session for wager settlement.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SessionOutcome {
+    /// Index of the winning player (0 or 1)
+    Winner(usize),
+    /// Same stocks and percent — pot is split
+    Draw,
+    /// Player 2 never joined or no frame ran — deposits are refunded
+    Unplayed,
+}
+
+impl SessionStateAccount {
+    /// Whether the match ran its course: a player is out of stocks, or
+    /// max_frames ran.
+    pub fn finished(&self) -> bool {
+        self.frame >= self.max_frames || self.players.iter().any(|p| p.stocks == 0)
+    }
+
+    /// Decide the session outcome: a player who closed the match early
+    /// forfeits; otherwise most stocks wins, then lowest percent.
+    pub fn outcome(&self) -> SessionOutcome {
+        if self.player2 == Pubkey::default() || self.frame == 0 {
+            return SessionOutcome::Unplayed;
+        }
+        if let 1..=2 = self.forfeited_by {
+            return SessionOutcome::Winner(2 - self.forfeited_by as usize);
+        }
+        let (p1, p2) = (&self.players[0], &self.players[1]);
+        match p1.stocks.cmp(&p2.stocks).then(p2.percent.cmp(&p1.percent)) {
+            core::cmp::Ordering::Greater => SessionOutcome::Winner(0),
+            core::cmp::Ordering::Less => SessionOutcome::Winner(1),
+            core::cmp::Ordering::Equal => SessionOutcome::Draw,
+        }
+    }
+}
+
+/// Session-scoped wager escrow at ["wager", session]. Holds both deposits
+/// as lamports on top of its own rent, or for an SPL wager in its
+/// associated token account for `mint`; closed on settlement or refund.
+#[account]
+#[derive(Default)]
+pub struct WagerEscrowAccount {
+    pub session: Pubkey,
+    /// SPL mint the stakes are in (default = lamports)
+    pub mint: Pubkey,
+    /// Lamports (or base units of `mint`) each player puts in
+    pub amount: u64,
+    pub p1_deposited: bool,
+    pub p2_deposited: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+// ── FeeScheduleAccount ───────────────────────────────────────────────────────
+
+/// Protocol fee schedule at ["fee_schedule"]. Every create_session pays
+/// session_fee into the session account; settle_session_fees splits it
+/// between the model author, the top cranker, and the protocol treasury.
+#[account]
+#[derive(Default)]
+pub struct FeeScheduleAccount {
+    pub authority: Pubkey,
+    pub protocol_treasury: Pubkey,
+    /// Lamports charged per session (0 = free)
+    pub session_fee: u64,
+    /// Split in basis points; the three shares sum to BPS_DENOMINATOR
+    pub author_bps: u16,
+    pub cranker_bps: u16,
+    pub protocol_bps: u16,
+    pub bump: u8,
+}
+
+impl FeeScheduleAccount {
+    /// Overwrite the schedule after checking the split sums to 100%.
+    pub fn set(
+        &mut self,
+        protocol_treasury: Pubkey,
+        session_fee: u64,
+        author_bps: u16,
+        cranker_bps: u16,
+        protocol_bps: u16,
+    ) -> Result<()> {
+        require!(
+            author_bps as u32 + cranker_bps as u32 + protocol_bps as u32
+                == BPS_DENOMINATOR as u32,
+            crate::error::WorldModelError::InvalidFeeSplit
+        );
+        self.protocol_treasury = protocol_treasury;
+        self.session_fee = session_fee;
+        self.author_bps = author_bps;
+        self.cranker_bps = cranker_bps;
+        self.protocol_bps = protocol_bps;
+        Ok(())
+    }
+}
+
+// ── SessionStatsAccount ──────────────────────────────────────────────────────
+
+/// Per-session spectator stats at ["stats", session], created by the first
+/// tip. Tipped lamports sit in this account until the player claims them.
+#[account]
+#[derive(Default)]
+pub struct SessionStatsAccount {
+    pub session: Pubkey,
+    /// Lifetime tips per player slot (for overlays)
+    pub tips_total: [u64; NUM_PLAYERS],
+    /// Tips not yet claimed per player slot
+    pub tips_claimable: [u64; NUM_PLAYERS],
+    pub tip_count: [u32; NUM_PLAYERS],
+    pub bump: u8,
+}
+
+/// A session's tips in one SPL mint at ["tip_jar", session, mint], created
+/// by the first tip in that mint. The tokens sit in the jar's associated
+/// token account until the player claims them.
+#[account]
+#[derive(Default)]
+pub struct TokenTipJarAccount {
+    pub session: Pubkey,
+    pub mint: Pubkey,
+    /// Lifetime tips per player slot, in base units of `mint`
+    pub tips_total: [u64; NUM_PLAYERS],
+    /// Tips not yet claimed per player slot
+    pub tips_claimable: [u64; NUM_PLAYERS],
+    pub tip_count: [u32; NUM_PLAYERS],
+    pub bump: u8,
+}
+
+// ── Rent pool ────────────────────────────────────────────────────────────────
+
+/// Shared pool at ["rent_pool"] that fronts rent for session accounts.
+/// Lamports above the pool's own rent are lendable.
+#[account]
+#[derive(Default)]
+pub struct RentPoolAccount {
+    /// Sum of live funder deposits
+    pub total_deposited: u64,
+    /// Lamports currently lent to open sessions
+    pub total_outstanding: u64,
+    pub bump: u8,
+}
+
+/// One funder's stake in the rent pool at ["rent_funder", funder].
+#[account]
+#[derive(Default)]
+pub struct RentFunderAccount {
+    pub funder: Pubkey,
+    /// Lamports deposited and not yet withdrawn
+    pub deposited: u64,
+    pub bump: u8,
+}
+
+/// Rent fronted to one session at ["rent_loan", session]; repaid by
+/// closing the session's hidden state and input buffer back into the pool.
+#[account]
+#[derive(Default)]
+pub struct RentLoanAccount {
+    pub session: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+// ── Tournament ───────────────────────────────────────────────────────────────
+
+/// Single-elimination tournament with a prize pool escrow.
+///
+/// Entry fees and sponsor deposits accumulate as lamports on this account.
+/// The bracket is a heap: leaves hold entrants, and each internal node is
+/// filled with the winner of the session the organizer scheduled for it.
+/// Once the final (node 0) is decided the pot pays out by `payout_bps`:
+/// 1st, 2nd, and 3rd (shared by the two semifinal losers), and the account
+/// closes.
+#[account]
+pub struct TournamentAccount {
+    pub organizer: Pubkey,
+    pub id: u64,
+    pub entry_fee: u64,
+    /// Prize split for 1st / 2nd / 3rd in basis points (sums to 10000)
+    pub payout_bps: [u16; 3],
+    /// Bracket size: 2, 4, or 8
+    pub max_entrants: u8,
+    pub num_entrants: u8,
+    pub entrants: [Pubkey; MAX_ENTRANTS],
+    pub bracket: [Pubkey; BRACKET_NODES],
+    /// Session scheduled for each match node (default = not yet scheduled)
+    pub matches: [Pubkey; MAX_ENTRANTS - 1],
+    /// Unix time the bracket was seeded (0 = registration open); only
+    /// sessions created after this count
+    pub started_at: i64,
+    pub bump: u8,
+}
+
+impl TournamentAccount {
+    /// Heap index of the first leaf.
+    pub fn first_leaf(&self) -> usize {
+        self.max_entrants as usize - 1
+    }
+
+    /// Whether `node` is an internal (match) node of this bracket.
+    pub fn is_match_node(&self, node: usize) -> bool {
+        node < self.first_leaf()
+    }
+
+    /// The loser of a decided match node.
+    pub fn loser(&self, node: usize) -> Pubkey {
+        let (a, b) = (self.bracket[2 * node + 1], self.bracket[2 * node + 2]);
+        if self.bracket[node] == a { b } else { a }
+    }
+}
+
+// ── ReplayRecordAccount ──────────────────────────────────────────────────────
+
+/// Provenance for a session's replay NFT at ["replay", session]. The mint
+/// at ["replay_mint", session] has supply 1; this record ties it to the
+/// committed replay data and the final game state.
+#[account]
+#[derive(Default)]
+pub struct ReplayRecordAccount {
+    pub session: Pubkey,
+    /// Committed replay data (the session's hidden-state checkpoint)
+    pub replay: Pubkey,
+    pub mint: Pubkey,
+    /// SHA-256 of the final frame number and both players' state
+    pub final_state_hash: [u8; 32],
+    pub final_frame: u32,
+    pub minted_by: Pubkey,
+    pub minted_at: i64,
+    /// Set once a replay verification re-ran the match from frame 0 and
+    /// matched every keyframe
+    pub verified: bool,
+    pub bump: u8,
+}
+
+/// In-progress re-execution of a replay at ["replay_verify", record, verifier].
+/// Advanced by verify_replay_frames, one batch of frame-log frames per tx.
+#[account]
+#[derive(Default)]
+pub struct ReplayVerificationAccount {
+    pub record: Pubkey,
+    pub verifier: Pubkey,
+    /// Scratch hidden state the re-execution runs its recurrent memory in
+    pub hidden_state: Pubkey,
+    /// Last frame re-executed
+    pub frame: u32,
+    /// Rolling state commitment through `frame`
+    pub commitment: [u8; 32],
+    /// Player state after `frame`
+    pub players: [PlayerState; NUM_PLAYERS],
+    pub bump: u8,
+}
+
+impl SessionStateAccount {
+    /// SHA-256 over the frame number and both players' serialized state.
+    pub fn final_state_hash(&self) -> Result<[u8; 32]> {
+        state_hash(self.frame, &self.players)
+    }
+}
+
+/// SHA-256 over a frame number and both players' serialized state.
+pub fn state_hash(frame: u32, players: &[PlayerState; NUM_PLAYERS]) -> Result<[u8; 32]> {
+    let mut buf = Vec::with_capacity(4 + NUM_PLAYERS * 32);
+    buf.extend_from_slice(&frame.to_le_bytes());
+    for p in players.iter() {
+        p.serialize(&mut buf)?;
+    }
+    Ok(solana_sha256_hasher::hash(&buf).to_bytes())
+}
+
+// ── InferenceMeterAccount ────────────────────────────────────────────────────
+
+/// Usage meter at ["meter", session], created with the session and bumped
+/// by every run_inference. Ephemeral-rollup operators convert it to fees
+/// when the session is undelegated.
+#[account]
+#[derive(Default)]
+pub struct InferenceMeterAccount {
+    pub session: Pubkey,
+    /// Frames advanced by run_inference
+    pub frames: u64,
+    /// Sum of per-frame compute estimates (Mamba2Config::estimate_frame_cu)
+    pub estimated_cu: u64,
+    /// Slot of the last metered frame
+    pub last_slot: u64,
+    pub bump: u8,
+}
+
+// ── CalibrationAccount ───────────────────────────────────────────────────────
+
+/// Activation-range telemetry of one model at ["calibration", manifest].
+/// The authority opts in by creating it; every run_inference that passes it
+/// widens each layer's INT32 accumulator extremes, so the authority can fit
+/// new layer scales to live play (ActivationRange::in_scale / out_scale)
+/// and set them on a fork.
+#[account]
+#[derive(Default)]
+pub struct CalibrationAccount {
+    pub manifest: Pubkey,
+    /// Frames observed
+    pub frames: u64,
+    /// Slot of the last observed frame
+    pub last_slot: u64,
+    /// Per-layer in_proj accumulator extremes (min above max until a frame
+    /// is observed)
+    pub in_min: [i32; MAX_LAYERS],
+    pub in_max: [i32; MAX_LAYERS],
+    /// Per-layer out_proj accumulator extremes
+    pub out_min: [i32; MAX_LAYERS],
+    pub out_max: [i32; MAX_LAYERS],
+    pub bump: u8,
+}
+
+impl CalibrationAccount {
+    /// Forget everything observed.
+    pub fn reset(&mut self) {
+        self.frames = 0;
+        self.last_slot = 0;
+        for layer in 0..MAX_LAYERS {
+            self.set_range(layer, &ActivationRange::default());
+        }
+    }
+
+    /// One layer's extremes so far.
+    pub fn range(&self, layer: usize) -> ActivationRange {
+        ActivationRange {
+            in_min: self.in_min[layer],
+            in_max: self.in_max[layer],
+            out_min: self.out_min[layer],
+            out_max: self.out_max[layer],
+        }
+    }
+
+    /// Widen the stored extremes by one frame's per-layer ranges.
+    pub fn record(&mut self, ranges: &[ActivationRange], slot: u64) {
+        for (layer, observed) in ranges.iter().enumerate().take(MAX_LAYERS) {
+            let mut range = self.range(layer);
+            range.merge(observed);
+            self.set_range(layer, &range);
+        }
+        self.frames += 1;
+        self.last_slot = slot;
+    }
+
+    fn set_range(&mut self, layer: usize, range: &ActivationRange) {
+        self.in_min[layer] = range.in_min;
+        self.in_max[layer] = range.in_max;
+        self.out_min[layer] = range.out_min;
+        self.out_max[layer] = range.out_max;
+    }
+}
+
+// ── DelegationConfigAccount ──────────────────────────────────────────────────
+
+/// Ephemeral rollup settings of a session at ["delegation", session], set by
+/// player 1 before the session's accounts are delegated. It stays on the
+/// base layer; the rollup reads a clone.
+///
+/// The commit mask is the session's state diff: which accounts a mid-match
+/// commit_session writes back to the base layer. Undelegation commits all
+/// of them, with the hidden state cut down to its header, plus the
+/// settlement summary.
+#[account]
+#[derive(Default)]
+pub struct DelegationConfigAccount {
+    pub session: Pubkey,
+    pub hidden_state: Pubkey,
+    pub input_buffer: Pubkey,
+    /// Player 1, who delegates the accounts
+    pub authority: Pubkey,
+    /// Validator the accounts are delegated to (default = any)
+    pub validator: Pubkey,
+    /// How often the rollup commits on its own (0 = the delegation
+    /// program's default)
+    pub commit_frequency_ms: u32,
+    /// ER_ACCOUNT_* bits commit_session writes back
+    pub commit_mask: u8,
+    pub bump: u8,
+}
+
+impl DelegationConfigAccount {
+    /// Whether commit_session writes back the ER_ACCOUNT_* `account`.
+    pub fn commits(&self, account: u8) -> bool {
+        self.commit_mask & (1 << account) != 0
+    }
+}
+
+// ── SettlementSummaryAccount ─────────────────────────────────────────────────
+
+/// What an ended session settles to mainnet, at ["settlement", session].
+/// Created with the delegation config and delegated with the session;
+/// undelegate_session fills it in on the rollup and commits it instead of
+/// the full hidden state. The hashes are what a later fraud proof
+/// re-executes against: the replay's inputs from frame 0 must reproduce
+/// `state_commitment`, `final_state_hash` and `hidden_state_hash`.
+#[account]
+#[derive(Default)]
+pub struct SettlementSummaryAccount {
+    pub session: Pubkey,
+    pub model: Pubkey,
+    /// OUTCOME_* of the match, and its winner (default unless a win)
+    pub outcome: u8,
+    pub winner: Pubkey,
+    pub final_frame: u32,
+    pub stocks: [u8; NUM_PLAYERS],
+    pub percents: [u16; NUM_PLAYERS],
+    /// Frames run_inference metered and fees collected for them
+    pub metered_frames: u64,
+    pub fees_collected: u64,
+    /// SessionStateAccount::final_state_hash
+    pub final_state_hash: [u8; 32],
+    /// Rolling per-frame commitment at the final frame
+    pub state_commitment: [u8; 32],
+    /// SHA-256 of the final hidden state's data
+    pub hidden_state_hash: [u8; 32],
+    /// Checkpoint holding the compressed final hidden state (default = none)
+    pub replay: Pubkey,
+    /// Unix time of undelegation (0 until then)
+    pub settled_at: i64,
+    pub bump: u8,
+}
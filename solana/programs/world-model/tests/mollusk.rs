@@ -0,0 +1,3132 @@
+/// Mollusk end-to-end tests — drive the world-model program through a whole
+/// match: init_manifest → upload/finalize/register the synthetic fixture's shards →
+/// create/join a session → submit inputs → run_inference over several frames.
+///
+/// Prerequisites: `cargo build-sbf --manifest-path programs/world-model/Cargo.toml`
+/// (the default `stub-inference` build; the .so must exist at
+/// programs/world-model/target/deploy/world_model.so). Tests that mint go
+/// through the real SPL programs, dumped next to it:
+/// `solana program dump -um TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA target/deploy/spl_token.so`
+/// and likewise ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL to spl_associated_token_account.so.
+/// Settlement stands in SPL Noop (noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV,
+/// spl_noop.so) for a verifier that accepts every proof.
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::{associated_token, token};
+use mollusk_svm::result::InstructionResult;
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use solana_instruction::error::InstructionError;
+use world_model::error::WorldModelError;
+use world_model::fixture::SyntheticModel;
+use world_model::state::*;
+use world_model::{accounts, archive, instruction, matmul};
+
+const LAMPORTS: u64 = 10_000_000_000;
+
+fn svm_key(key: &Pubkey) -> solana_pubkey::Pubkey {
+    solana_pubkey::Pubkey::new_from_array(key.to_bytes())
+}
+
+/// The program uses Anchor's Solana types; Mollusk uses its own.
+fn svm_instruction(ix: &Instruction) -> solana_instruction::Instruction {
+    solana_instruction::Instruction {
+        program_id: svm_key(&ix.program_id),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|m| solana_instruction::AccountMeta {
+                pubkey: svm_key(&m.pubkey),
+                is_signer: m.is_signer,
+                is_writable: m.is_writable,
+            })
+            .collect(),
+        data: ix.data.clone(),
+    }
+}
+
+fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: world_model::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &world_model::ID).0
+}
+
+fn error_code(e: WorldModelError) -> InstructionError {
+    InstructionError::Custom(e.into())
+}
+
+/// Mollusk plus an account store that successful instructions write back
+/// to, so instructions chain like transactions on a validator.
+struct Harness {
+    mollusk: Mollusk,
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        // world-model is excluded from the workspace, so its .so lives in its own target dir.
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        std::env::set_var(
+            "SBF_OUT_DIR",
+            std::path::Path::new(manifest_dir).join("target/deploy"),
+        );
+
+        let mut mollusk = Mollusk::default();
+        mollusk.add_program_with_loader(
+            &svm_key(&world_model::ID),
+            "world_model",
+            &mollusk_svm::program::loader_keys::LOADER_V3,
+        );
+
+        let mut accounts = HashMap::new();
+        let (_, system) = mollusk_svm::program::keyed_account_for_system_program();
+        accounts.insert(system_program::ID, system);
+        // Placeholder for omitted optional accounts
+        accounts.insert(
+            world_model::ID,
+            mollusk_svm::program::create_program_account_loader_v3(&svm_key(&world_model::ID)),
+        );
+        Harness { mollusk, accounts }
+    }
+
+    /// A system-owned wallet with lamports to pay rent and fees.
+    fn wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.accounts.insert(
+            key,
+            Account {
+                lamports: LAMPORTS,
+                owner: svm_key(&system_program::ID),
+                ..Account::default()
+            },
+        );
+        key
+    }
+
+    /// Make `authority` the program's upgrade authority, as deploying does.
+    fn set_upgrade_authority(&mut self, authority: Pubkey) {
+        // UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address }
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+        self.accounts.insert(
+            program_data(),
+            Account {
+                lamports: LAMPORTS,
+                data,
+                owner: mollusk_svm::program::loader_keys::LOADER_V3,
+                ..Account::default()
+            },
+        );
+    }
+
+    /// Load a dumped program's .so from SBF_OUT_DIR under its mainnet ID.
+    fn load_program(&mut self, program: Pubkey, name: &str) {
+        let elf = mollusk_svm::file::load_program_elf(name);
+        self.mollusk.add_program_with_loader_and_elf(
+            &svm_key(&program),
+            &mollusk_svm::program::loader_keys::LOADER_V2,
+            &elf,
+        );
+        self.accounts
+            .insert(program, mollusk_svm::program::create_program_account_loader_v2(&elf));
+    }
+
+    /// SPL Token and Associated Token Account, for tests whose instructions
+    /// create mints and token accounts.
+    fn add_token_programs(&mut self) {
+        self.load_program(token::ID, "spl_token");
+        self.load_program(associated_token::ID, "spl_associated_token_account");
+    }
+
+    /// A zeroed, rent-exempt account owned by the program, as clients
+    /// allocate with create_account before create_session.
+    fn allocate(&mut self, data: Vec<u8>) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.accounts.insert(
+            key,
+            Account {
+                lamports: self.mollusk.sysvars.rent.minimum_balance(data.len()),
+                data,
+                owner: svm_key(&world_model::ID),
+                ..Account::default()
+            },
+        );
+        key
+    }
+
+    fn process(&mut self, ix: &Instruction) -> InstructionResult {
+        let mut keys: Vec<Pubkey> = Vec::new();
+        for meta in &ix.accounts {
+            if !keys.contains(&meta.pubkey) {
+                keys.push(meta.pubkey);
+            }
+        }
+        let accounts: Vec<_> = keys
+            .iter()
+            .map(|k| {
+                (
+                    svm_key(k),
+                    self.accounts.get(k).cloned().unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        let result = self
+            .mollusk
+            .process_instruction(&svm_instruction(ix), &accounts);
+        if result.raw_result.is_ok() {
+            for (key, account) in keys.iter().zip(&result.resulting_accounts) {
+                self.accounts.insert(*key, account.1.clone());
+            }
+        }
+        result
+    }
+
+    fn ok(&mut self, ix: &Instruction) {
+        let result = self.process(ix);
+        assert!(
+            result.raw_result.is_ok(),
+            "instruction failed: {:?}",
+            result.raw_result
+        );
+    }
+
+    fn fails_with(&mut self, ix: &Instruction, err: WorldModelError) {
+        let expected = error_code(err);
+        let result = self.process(ix);
+        assert_eq!(result.raw_result, Err(expected));
+    }
+
+    fn data(&self, key: &Pubkey) -> &[u8] {
+        &self.accounts[key].data
+    }
+
+    fn anchor<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        T::try_deserialize(&mut self.data(key)).unwrap()
+    }
+
+    fn session(&self, key: &Pubkey) -> SessionStateAccount {
+        let size = core::mem::size_of::<SessionStateAccount>();
+        bytemuck::pod_read_unaligned(&self.data(key)[8..8 + size])
+    }
+}
+
+fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[world_model::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+fn init_fee_schedule_ix(authority: Pubkey) -> Instruction {
+    build(
+        accounts::InitFeeSchedule {
+            fee_schedule: pda(&[FEE_SCHEDULE_SEED]),
+            authority,
+            program: world_model::ID,
+            program_data: program_data(),
+            system_program: system_program::ID,
+        },
+        instruction::InitFeeSchedule {
+            protocol_treasury: authority,
+            session_fee: 0,
+            author_bps: 5_000,
+            cranker_bps: 3_000,
+            protocol_bps: 2_000,
+        },
+    )
+}
+
+/// A ready model and the accounts that produced it.
+struct Model {
+    manifest: Pubkey,
+    /// Weight accounts in shard index order
+    weights: Vec<Pubkey>,
+}
+
+/// init_fee_schedule (create_session reads it), then the fixture's
+/// manifest: init_manifest, its encoder spec, extended LUTs and tensor
+/// table, and init_shard_directory.
+fn init_model(h: &mut Harness, fixture: &SyntheticModel) -> (Pubkey, Pubkey) {
+    let authority = h.wallet();
+    h.set_upgrade_authority(authority);
+    h.ok(&init_fee_schedule_ix(authority));
+
+    let manifest = Pubkey::new_unique();
+    h.ok(&build(
+        accounts::InitManifest {
+            manifest,
+            authority,
+            system_program: system_program::ID,
+        },
+        fixture.init_manifest_args(),
+    ));
+    h.ok(&build(
+        accounts::SetEncoderSpec {
+            manifest,
+            authority,
+        },
+        instruction::SetEncoderSpec {
+            spec: fixture.encoder_spec,
+        },
+    ));
+    h.ok(&build(
+        accounts::UpdateLuts {
+            manifest,
+            authority,
+        },
+        fixture.extended_luts_args(),
+    ));
+    for (layer, tensors) in fixture.tensor_table.iter().enumerate() {
+        h.ok(&build(
+            accounts::SetTensorTable {
+                manifest,
+                authority,
+            },
+            instruction::SetTensorTable {
+                layer: layer as u8,
+                tensors: *tensors,
+            },
+        ));
+    }
+    h.ok(&build(
+        accounts::InitShardDirectory {
+            manifest,
+            shard_directory: pda(&[SHARD_DIRECTORY_SEED, manifest.as_ref()]),
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitShardDirectory {},
+    ));
+    (authority, manifest)
+}
+
+/// A fresh weight account for `shard`. The program has no instruction that
+/// allocates one, so the test writes the header directly.
+fn create_weight_account(
+    h: &mut Harness,
+    fixture: &SyntheticModel,
+    shard: usize,
+    authority: Pubkey,
+) -> Pubkey {
+    h.allocate(fixture.weight_account_data(shard, authority, false))
+}
+
+fn upload_ix(weight: Pubkey, authority: Pubkey, offset: u32, data: Vec<u8>) -> Instruction {
+    build(
+        accounts::UploadWeights {
+            weight_account: weight,
+            weight_data: weight,
+            authority,
+        },
+        instruction::UploadWeights { offset, data },
+    )
+}
+
+fn upload_all(h: &mut Harness, weight: Pubkey, authority: Pubkey, bytes: &[u8]) {
+    for (i, chunk) in bytes.chunks(MAX_CHUNK_SIZE).enumerate() {
+        h.ok(&upload_ix(
+            weight,
+            authority,
+            (i * MAX_CHUNK_SIZE) as u32,
+            chunk.to_vec(),
+        ));
+    }
+}
+
+fn finalize_ix(weight: Pubkey, authority: Pubkey, expected_hash: [u8; 32]) -> Instruction {
+    build(
+        accounts::FinalizeWeights {
+            weight_account: weight,
+            weight_data: weight,
+            authority,
+        },
+        instruction::FinalizeWeights { expected_hash },
+    )
+}
+
+fn register_ix(
+    manifest: Pubkey,
+    weight: Pubkey,
+    authority: Pubkey,
+    expected_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::RegisterShard {
+            manifest,
+            shard_directory: pda(&[SHARD_DIRECTORY_SEED, manifest.as_ref()]),
+            weight_account: weight,
+            authority,
+        },
+        instruction::RegisterShard { expected_hash },
+    )
+}
+
+fn ready_model(h: &mut Harness, fixture: &SyntheticModel) -> Model {
+    let (authority, manifest) = init_model(h, fixture);
+    let mut weights = Vec::new();
+    for (shard, bytes) in fixture.shards.iter().enumerate() {
+        let weight = create_weight_account(h, fixture, shard, authority);
+        upload_all(h, weight, authority, bytes);
+        let digest = fixture.shard_hash(shard);
+        h.ok(&finalize_ix(weight, authority, digest));
+        h.ok(&register_ix(manifest, weight, authority, digest));
+        weights.push(weight);
+    }
+    Model { manifest, weights }
+}
+
+/// The accounts of one session plus its two players.
+struct Session {
+    session: Pubkey,
+    hidden_state: Pubkey,
+    input_buffer: Pubkey,
+    player1: Pubkey,
+    player2: Pubkey,
+}
+
+fn create_session(h: &mut Harness, fixture: &SyntheticModel, model: &Model) -> Session {
+    create_session_at(h, fixture, model, TICK_RATE_60)
+}
+
+fn create_session_at(
+    h: &mut Harness,
+    fixture: &SyntheticModel,
+    model: &Model,
+    tick_rate: u8,
+) -> Session {
+    let (session, ix) = create_session_ix(h, fixture, model, tick_rate);
+    h.ok(&ix);
+    session
+}
+
+/// Allocate a session's accounts and build its create_session.
+fn create_session_ix(
+    h: &mut Harness,
+    fixture: &SyntheticModel,
+    model: &Model,
+    tick_rate: u8,
+) -> (Session, Instruction) {
+    let player1 = h.wallet();
+    let player2 = h.wallet();
+    let session = h.allocate(vec![0; 8 + core::mem::size_of::<SessionStateAccount>()]);
+    // Room for the frame log, so every session's replay can be verified
+    let input_buffer = h.allocate(vec![0; input_buffer_size(3600)]);
+    // A fresh header over stale bytes the first frame must clear
+    let mut hidden = vec![0xAB; fixture.hidden_account_data().len()];
+    hidden[..HIDDEN_HEADER_SIZE].fill(0);
+    let hidden_state = h.allocate(hidden);
+
+    let ix = build(
+        accounts::CreateSession {
+            session,
+            hidden_state,
+            input_buffer,
+            manifest: model.manifest,
+            player1,
+            treasury: pda(&[TREASURY_SEED, model.manifest.as_ref()]),
+            fee_schedule: pda(&[FEE_SCHEDULE_SEED]),
+            meter: pda(&[METER_SEED, session.as_ref()]),
+            system_program: system_program::ID,
+            cartridge_token: None,
+            gate_token: None,
+            fee_mint: None,
+            fee_token: None,
+            treasury_fee_token: None,
+            token_program: None,
+        },
+        instruction::CreateSession {
+            stage: 31,
+            character: 2,
+            max_frames: 3600,
+            seed: 7,
+            allow_deprecated: false,
+            tick_rate,
+        },
+    );
+    let session = Session {
+        session,
+        hidden_state,
+        input_buffer,
+        player1,
+        player2,
+    };
+    (session, ix)
+}
+
+fn join_ix(s: &Session, player2: Pubkey) -> Instruction {
+    build(
+        accounts::JoinSession {
+            session: s.session,
+            player2,
+        },
+        instruction::JoinSession { character: 9 },
+    )
+}
+
+fn submit_ix(s: &Session, player: Pubkey, stick_x: i8, buttons: u8) -> Instruction {
+    build(
+        accounts::SubmitInput {
+            session: s.session,
+            input_buffer: s.input_buffer,
+            player,
+        },
+        instruction::SubmitInput {
+            stick_x,
+            stick_y: 0,
+            c_stick_x: 0,
+            c_stick_y: 0,
+            trigger_l: 0,
+            trigger_r: 0,
+            buttons,
+            buttons_ext: 0,
+        },
+    )
+}
+
+/// run_inference for the session's next frame.
+fn inference_ix(h: &Harness, s: &Session, model: &Model, cranker: Pubkey) -> Instruction {
+    inference_ix_at(s, model, cranker, h.session(&s.session).frame + 1)
+}
+
+fn inference_ix_at(s: &Session, model: &Model, cranker: Pubkey, frame: u32) -> Instruction {
+    build(
+        accounts::RunInference {
+            session: s.session,
+            hidden_state: s.hidden_state,
+            input_buffer: s.input_buffer,
+            manifest: model.manifest,
+            weights: model.weights[0],
+            cranker,
+            meter: pda(&[METER_SEED, s.session.as_ref()]),
+            calibration: None,
+        },
+        instruction::RunInference { frame },
+    )
+}
+
+fn close_ix(s: &Session, player: Pubkey) -> Instruction {
+    build(
+        accounts::CloseSession {
+            session: s.session,
+            player,
+        },
+        instruction::CloseSession {},
+    )
+}
+
+#[test]
+fn weight_pipeline_makes_model_ready() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+
+    // Everything but the keys matches the fixture's own manifest
+    let manifest: ModelManifestAccount = h.anchor(&model.manifest);
+    let expected = fixture.manifest();
+    assert!(manifest.ready);
+    assert_eq!(manifest.num_shards, expected.num_shards);
+    assert_eq!(
+        manifest.registered_weight_bytes,
+        expected.registered_weight_bytes
+    );
+    assert_eq!(manifest.tensor_table, expected.tensor_table);
+    assert_eq!(manifest.encoder_spec, expected.encoder_spec);
+    assert_eq!(manifest.luts, expected.luts);
+    assert_eq!(manifest.lut_version, expected.lut_version);
+    assert_eq!(manifest.consistency_failures(), 0);
+
+    let directory: ShardDirectoryAccount =
+        h.anchor(&pda(&[SHARD_DIRECTORY_SEED, model.manifest.as_ref()]));
+    for (shard, weight) in model.weights.iter().enumerate() {
+        assert_eq!(directory.entries[shard].key, *weight);
+        assert_eq!(
+            &h.data(weight)[WEIGHT_HEADER_SIZE..],
+            &fixture.shards[shard][..]
+        );
+    }
+}
+
+#[test]
+fn weight_pipeline_error_paths() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, _) = init_model(&mut h, &fixture);
+    let weight = create_weight_account(&mut h, &fixture, 0, authority);
+    let bytes = &fixture.shards[0];
+    let digest = fixture.shard_hash(0);
+
+    // Past the end of the data region
+    h.fails_with(
+        &upload_ix(weight, authority, bytes.len() as u32 - 10, vec![0; 20]),
+        WorldModelError::ChunkOutOfBounds,
+    );
+    // Only someone else's authority
+    let stranger = h.wallet();
+    h.fails_with(
+        &upload_ix(weight, stranger, 0, vec![1; 10]),
+        WorldModelError::Unauthorized,
+    );
+
+    // Finalizing needs every byte written, and then the right hash
+    h.ok(&upload_ix(
+        weight,
+        authority,
+        0,
+        bytes[..MAX_CHUNK_SIZE].to_vec(),
+    ));
+    h.fails_with(
+        &finalize_ix(weight, authority, digest),
+        WorldModelError::IncompleteUpload,
+    );
+    for (i, chunk) in bytes.chunks(MAX_CHUNK_SIZE).enumerate().skip(1) {
+        h.ok(&upload_ix(
+            weight,
+            authority,
+            (i * MAX_CHUNK_SIZE) as u32,
+            chunk.to_vec(),
+        ));
+    }
+    h.fails_with(
+        &finalize_ix(weight, authority, [0; 32]),
+        WorldModelError::HashMismatch,
+    );
+    h.ok(&finalize_ix(weight, authority, digest));
+    h.fails_with(
+        &upload_ix(weight, authority, 0, vec![0; 10]),
+        WorldModelError::AlreadyFinalized,
+    );
+}
+
+#[test]
+fn register_shard_checks_the_running_total() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+
+    // A finalized shard 0 with `extra` bytes past (or short of) the real one
+    let resized = |extra: isize| {
+        let mut model = SyntheticModel::tiny();
+        let len = (model.shards[0].len() as isize + extra) as usize;
+        model.shards[0].resize(len, 7);
+        (model.weight_account_data(0, authority, true), model.shard_hash(0))
+    };
+
+    // Overrunning total_weight_bytes is caught on arrival, not at the end
+    let slack = fixture.shards[1].len() as isize;
+    let (data, digest) = resized(slack + 1);
+    let long = h.allocate(data);
+    h.fails_with(
+        &register_ix(manifest, long, authority, digest),
+        WorldModelError::ShardSizeMismatch,
+    );
+
+    // A short shard fits, then blocks the last one, but can be replaced
+    let (data, digest) = resized(-16);
+    let short = h.allocate(data);
+    h.ok(&register_ix(manifest, short, authority, digest));
+    let last = h.allocate(fixture.weight_account_data(1, authority, true));
+    h.fails_with(
+        &register_ix(manifest, last, authority, fixture.shard_hash(1)),
+        WorldModelError::ShardSizeMismatch,
+    );
+    let first = h.allocate(fixture.weight_account_data(0, authority, true));
+    h.ok(&register_ix(manifest, first, authority, fixture.shard_hash(0)));
+    h.ok(&register_ix(manifest, last, authority, fixture.shard_hash(1)));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert!(m.ready);
+    assert_eq!(m.num_shards, 2);
+    assert_eq!(
+        m.registered_weight_bytes,
+        fixture.manifest().registered_weight_bytes
+    );
+
+    // Once ready the directory is frozen
+    h.fails_with(
+        &register_ix(manifest, short, authority, digest),
+        WorldModelError::InvalidShardIndex,
+    );
+}
+
+fn set_expert_layer_ix(
+    manifest: Pubkey,
+    authority: Pubkey,
+    layer: u8,
+    router: TensorRef,
+    experts: Vec<[TensorRef; TENSORS_PER_EXPERT]>,
+) -> Instruction {
+    build(
+        accounts::SetExpertLayer {
+            manifest,
+            expert_table: pda(&[EXPERT_TABLE_SEED, manifest.as_ref()]),
+            authority,
+        },
+        instruction::SetExpertLayer {
+            layer,
+            router,
+            experts,
+        },
+    )
+}
+
+#[test]
+fn expert_table_checks_router_and_experts() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let expert_table = pda(&[EXPERT_TABLE_SEED, manifest.as_ref()]);
+    let init_ix = |num_experts| {
+        build(
+            accounts::InitExpertTable {
+                manifest,
+                expert_table,
+                authority,
+                system_program: system_program::ID,
+            },
+            instruction::InitExpertTable { num_experts },
+        )
+    };
+
+    // One expert is just a dense layer
+    h.fails_with(&init_ix(1), WorldModelError::InvalidExpertCount);
+    h.ok(&init_ix(2));
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!((m.num_experts, m.expert_table), (2, expert_table));
+
+    // Both experts reuse layer 0's projections; the router scores 2 experts
+    let dense = fixture.tensor_table[0];
+    let expert = [dense[TENSOR_IN_PROJ], dense[TENSOR_OUT_PROJ]];
+    let router = TensorRef {
+        dtype: TENSOR_DTYPE_I8,
+        rows: 2,
+        cols: m.d_model,
+        ..dense[TENSOR_IN_PROJ]
+    };
+    h.fails_with(
+        &set_expert_layer_ix(manifest, authority, 0, router, vec![expert]),
+        WorldModelError::InvalidTensorRef,
+    );
+    h.fails_with(
+        &set_expert_layer_ix(
+            manifest,
+            authority,
+            0,
+            TensorRef { rows: 3, ..router },
+            vec![expert; 2],
+        ),
+        WorldModelError::InvalidTensorRef,
+    );
+    h.ok(&set_expert_layer_ix(
+        manifest,
+        authority,
+        0,
+        router,
+        vec![expert; 2],
+    ));
+
+    let table: ExpertTableAccount = h.anchor(&expert_table);
+    assert_eq!(table.routers[0], router);
+    assert_eq!(table.experts[..2], [expert; 2]);
+}
+
+#[test]
+fn attention_layers_must_fit_hidden_state() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |attention_layers, attn_window| {
+        build(
+            accounts::SetAttentionLayers {
+                manifest,
+                authority,
+            },
+            instruction::SetAttentionLayers {
+                attention_layers,
+                attn_window,
+            },
+        )
+    };
+
+    // The fixture has 2 layers, and room for a one-frame KV buffer
+    h.fails_with(&set_ix(0b100, 1), WorldModelError::InvalidAttentionLayers);
+    h.fails_with(&set_ix(0b10, 0), WorldModelError::InvalidAttentionWindow);
+    h.fails_with(&set_ix(0b10, 2), WorldModelError::InvalidAttentionWindow);
+    h.ok(&set_ix(0b10, 1));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!(m.layer_kind(0), world_model::inference::LayerKind::Ssm);
+    assert_eq!(
+        m.layer_kind(1),
+        world_model::inference::LayerKind::Attention { window: 1 }
+    );
+}
+
+#[test]
+fn encoder_spec_sets_the_encoding_summary() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |spec| {
+        build(
+            accounts::SetEncoderSpec {
+                manifest,
+                authority,
+            },
+            instruction::SetEncoderSpec { spec },
+        )
+    };
+
+    // init_model's set_encoder_spec derived the counts from the compact spec
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    let expected = fixture.manifest();
+    assert_eq!(
+        (m.input_size, m.num_continuous, m.num_binary),
+        (expected.input_size, expected.num_continuous, expected.num_binary)
+    );
+
+    // melee_v2 is 49 slots, past d_model 32
+    h.fails_with(&set_ix(EncoderSpec::melee_v2()), WorldModelError::InvalidEncoderSpec);
+
+    // A retrained encoding: the stick becomes one binary slot
+    let mut spec = fixture.encoder_spec;
+    spec.player_fields[7] = FieldSpec::binary(FIELD_STICK_X);
+    h.ok(&set_ix(spec));
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!((m.input_size, m.num_continuous, m.num_binary), (21, 15, 6));
+    assert_eq!(m.consistency_failures() & CHECK_INPUT_SIZE, 0);
+}
+
+#[test]
+fn hashed_action_field_must_cover_the_action_states() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |width, classes| {
+        let mut spec = fixture.encoder_spec;
+        spec.player_fields[6] = FieldSpec::hashed(FIELD_ACTION_STATE, width, classes);
+        build(
+            accounts::SetEncoderSpec {
+                manifest,
+                authority,
+            },
+            instruction::SetEncoderSpec { spec },
+        )
+    };
+    let states = fixture.manifest().num_action_states;
+
+    // Two slots are all a class's probes; a count other than the
+    // manifest's leaves states out or invents them
+    h.fails_with(&set_ix(2, states), WorldModelError::InvalidEncoderSpec);
+    h.fails_with(&set_ix(6, states - 1), WorldModelError::InvalidEncoderSpec);
+    // Six slots a player keeps the vector within d_model 32
+    h.fails_with(&set_ix(7, states), WorldModelError::InvalidEncoderSpec);
+    h.ok(&set_ix(6, states));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!((m.input_size, m.num_continuous), (31, 15));
+}
+
+#[test]
+fn output_smoothing_only_weights_decoded_linear_fields() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |field: usize| {
+        let mut smoothing = [0u8; MAX_PLAYER_FIELDS];
+        smoothing[field] = 192;
+        build(
+            accounts::SetOutputSmoothing {
+                manifest,
+                authority,
+            },
+            instruction::SetOutputSmoothing { smoothing },
+        )
+    };
+
+    // Facing is binary, field 7 the stick, and the fixture has ten fields
+    h.fails_with(&set_ix(4), WorldModelError::InvalidOutputSmoothing);
+    h.fails_with(&set_ix(7), WorldModelError::InvalidOutputSmoothing);
+    h.fails_with(&set_ix(10), WorldModelError::InvalidOutputSmoothing);
+    h.ok(&set_ix(0));
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!(m.output_smoothing[0], 192);
+
+    // A new encoder spec clears weights laid out for the old one
+    h.ok(&build(
+        accounts::SetEncoderSpec {
+            manifest,
+            authority,
+        },
+        instruction::SetEncoderSpec { spec: fixture.encoder_spec },
+    ));
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!(m.output_smoothing, [0; MAX_PLAYER_FIELDS]);
+}
+
+#[test]
+fn head_groups_must_split_heads() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |ngroups| {
+        build(
+            accounts::SetHeadGroups {
+                manifest,
+                authority,
+            },
+            instruction::SetHeadGroups { ngroups },
+        )
+    };
+
+    // The fixture has one head
+    h.fails_with(&set_ix(2), WorldModelError::InvalidHeadGroups);
+    h.ok(&set_ix(1));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!(m.ngroups, 1);
+    assert_eq!(m.consistency_failures() & CHECK_DIMENSIONS, 0);
+}
+
+#[test]
+fn requant_mode_is_validated() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |requant_mode| {
+        build(
+            accounts::SetRequantMode {
+                manifest,
+                authority,
+            },
+            instruction::SetRequantMode { requant_mode },
+        )
+    };
+
+    h.fails_with(&set_ix(2), WorldModelError::InvalidRequantMode);
+    h.ok(&set_ix(REQUANT_STOCHASTIC));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!(m.requant_mode, REQUANT_STOCHASTIC);
+    assert_eq!(
+        m.rounding(7),
+        world_model::inference::Rounding::Stochastic { seed: 7 }
+    );
+}
+
+#[test]
+fn character_heads_must_fit_player_blocks() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |head_stride, head_offsets| {
+        build(
+            accounts::SetCharacterHeads {
+                manifest,
+                authority,
+            },
+            instruction::SetCharacterHeads {
+                head_stride,
+                head_offsets,
+            },
+        )
+    };
+    // The compact spec is 10 slots a player, d_model 32
+    let mut offsets = [0u16; MAX_CHARACTER_HEADS];
+    offsets[2] = 6;
+
+    h.fails_with(&set_ix(0, offsets), WorldModelError::InvalidCharacterHeads);
+    h.fails_with(&set_ix(17, offsets), WorldModelError::InvalidCharacterHeads);
+    offsets[2] = 7;
+    h.fails_with(&set_ix(16, offsets), WorldModelError::InvalidCharacterHeads);
+    offsets[2] = 6;
+    h.ok(&set_ix(16, offsets));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!(m.head_stride, 16);
+    assert_eq!(m.output_offsets([2, 9]), [6, 16]);
+    assert_eq!(m.output_offsets([9, 2]), [0, 22]);
+}
+
+#[test]
+fn action_tensors_must_match_the_action_field() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |embedding, head| {
+        build(
+            accounts::SetActionTensors {
+                manifest,
+                authority,
+            },
+            instruction::SetActionTensors { embedding, head },
+        )
+    };
+    let embedding = TensorRef { shard: 1, dtype: TENSOR_DTYPE_I8, offset: 0, rows: 4, cols: 8 };
+    let tied = TensorRef { dtype: TENSOR_DTYPE_TIED, rows: 8, cols: 4, ..TensorRef::default() };
+
+    // The compact spec's action state is a linear field
+    h.fails_with(&set_ix(embedding, tied), WorldModelError::InvalidActionTensors);
+
+    let mut spec = fixture.encoder_spec;
+    spec.player_fields[6] = FieldSpec::categorical(FIELD_ACTION_STATE, 4);
+    h.ok(&build(
+        accounts::SetEncoderSpec {
+            manifest,
+            authority,
+        },
+        instruction::SetEncoderSpec { spec },
+    ));
+
+    // The embedding is one row per slot of the field, the head its
+    // transpose, and a tied head has no location of its own
+    let narrow = TensorRef { rows: 3, ..embedding };
+    h.fails_with(
+        &set_ix(narrow, TensorRef { cols: 3, ..tied }),
+        WorldModelError::InvalidActionTensors,
+    );
+    h.fails_with(
+        &set_ix(embedding, TensorRef { cols: 8, ..tied }),
+        WorldModelError::InvalidActionTensors,
+    );
+    h.fails_with(
+        &set_ix(embedding, TensorRef { offset: 64, ..tied }),
+        WorldModelError::InvalidActionTensors,
+    );
+    let head = TensorRef { shard: 0, offset: 32, rows: 8, cols: 4, ..embedding };
+    h.ok(&set_ix(embedding, head));
+    h.ok(&set_ix(embedding, tied));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!((m.action_embedding, m.action_head), (embedding, tied));
+
+    // Clearing the embedding restores one-hot decoding
+    h.fails_with(&set_ix(TensorRef::default(), tied), WorldModelError::InvalidActionTensors);
+    h.ok(&set_ix(TensorRef::default(), TensorRef::default()));
+}
+
+#[test]
+fn layer_scales_must_cover_every_layer() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    let set_ix = |input_scales, output_scales| {
+        build(
+            accounts::SetLayerScales {
+                manifest,
+                authority,
+            },
+            instruction::SetLayerScales {
+                input_scales,
+                output_scales,
+            },
+        )
+    };
+    // The fixture has two layers
+    let mut scales = [0u16; MAX_LAYERS];
+    scales[..2].copy_from_slice(&[4096, 8192]);
+
+    let mut missing = scales;
+    missing[1] = 0;
+    h.fails_with(&set_ix(missing, scales), WorldModelError::InvalidLayerScales);
+    let mut extra = scales;
+    extra[2] = 4096;
+    h.fails_with(&set_ix(scales, extra), WorldModelError::InvalidLayerScales);
+    h.ok(&set_ix(scales, scales));
+
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!((m.layer_input_scales, m.layer_output_scales), (scales, scales));
+}
+
+fn propose_authority_ix(manifest: Pubkey, authority: Pubkey, new_authority: Pubkey) -> Instruction {
+    build(
+        accounts::ProposeAuthority { manifest, authority },
+        instruction::ProposeAuthority { new_authority },
+    )
+}
+
+fn accept_authority_ix(manifest: Pubkey, new_authority: Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAuthority {
+            manifest,
+            new_authority,
+        },
+        instruction::AcceptAuthority {},
+    )
+}
+
+#[test]
+fn authority_transfer_needs_the_nominee_to_accept() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let (authority, manifest) = init_model(&mut h, &fixture);
+    // A multisig vault PDA signs like any other nominee
+    let (successor, stranger) = (Pubkey::new_unique(), h.wallet());
+
+    h.fails_with(&propose_authority_ix(manifest, stranger, stranger), WorldModelError::Unauthorized);
+    h.fails_with(&accept_authority_ix(manifest, successor), WorldModelError::NotPendingAuthority);
+    h.ok(&propose_authority_ix(manifest, authority, successor));
+    assert_eq!(h.anchor::<ModelManifestAccount>(&manifest).pending_authority, successor);
+    h.fails_with(&accept_authority_ix(manifest, stranger), WorldModelError::NotPendingAuthority);
+
+    // Nominating the default key cancels
+    h.ok(&propose_authority_ix(manifest, authority, Pubkey::default()));
+    h.fails_with(&accept_authority_ix(manifest, successor), WorldModelError::NotPendingAuthority);
+
+    h.ok(&propose_authority_ix(manifest, authority, successor));
+    h.ok(&accept_authority_ix(manifest, successor));
+    let m: ModelManifestAccount = h.anchor(&manifest);
+    assert_eq!((m.authority, m.pending_authority), (successor, Pubkey::default()));
+    h.fails_with(&propose_authority_ix(manifest, authority, authority), WorldModelError::Unauthorized);
+}
+
+#[test]
+fn deprecated_models_need_an_explicit_opt_in() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let authority = h.anchor::<ModelManifestAccount>(&model.manifest).authority;
+    let successor = Pubkey::new_unique();
+    let deprecate = |authority| {
+        build(
+            accounts::DeprecateModel {
+                manifest: model.manifest,
+                authority,
+            },
+            instruction::DeprecateModel {
+                superseded_by: successor,
+            },
+        )
+    };
+
+    let stranger = h.wallet();
+    h.fails_with(&deprecate(stranger), WorldModelError::Unauthorized);
+    h.ok(&deprecate(authority));
+    let m: ModelManifestAccount = h.anchor(&model.manifest);
+    assert!(m.deprecated);
+    assert_eq!(m.superseded_by, successor);
+
+    let (_, mut ix) = create_session_ix(&mut h, &fixture, &model, TICK_RATE_60);
+    h.fails_with(&ix, WorldModelError::ModelDeprecated);
+    // allow_deprecated sits just before tick_rate
+    let at = ix.data.len() - 2;
+    ix.data[at] = 1;
+    h.ok(&ix);
+}
+
+fn fork_ix(source: Pubkey, authority: Pubkey) -> (Pubkey, Instruction) {
+    let manifest = Pubkey::new_unique();
+    let ix = build(
+        accounts::ForkManifest {
+            manifest,
+            source,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::ForkManifest {},
+    );
+    (manifest, ix)
+}
+
+#[test]
+fn fork_clones_a_ready_cartridge_for_the_forker() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let forker = h.wallet();
+
+    let (_, unready) = init_model(&mut h, &fixture);
+    h.fails_with(&fork_ix(unready, forker).1, WorldModelError::ModelNotReady);
+
+    let model = ready_model(&mut h, &fixture);
+    let (fork, ix) = fork_ix(model.manifest, forker);
+    h.ok(&ix);
+    let source: ModelManifestAccount = h.anchor(&model.manifest);
+    let m: ModelManifestAccount = h.anchor(&fork);
+    assert_eq!((m.authority, m.forked_from), (forker, model.manifest));
+    assert!(m.ready && !m.deprecated);
+    assert_eq!(m.session_fee, 0);
+    assert_eq!(m.shard_directory, source.shard_directory);
+    assert_eq!(m.tensor_table, source.tensor_table);
+}
+
+#[test]
+fn validate_manifest_reports_without_failing() {
+    use anchor_lang::AccountSerialize;
+
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let validate = |manifest| {
+        build(
+            accounts::ValidateManifest { manifest },
+            instruction::ValidateManifest {},
+        )
+    };
+    h.ok(&validate(model.manifest));
+
+    // An inconsistent manifest still validates (the event carries the
+    // failures), and validation leaves it untouched
+    let mut m: ModelManifestAccount = h.anchor(&model.manifest);
+    m.input_size += 1;
+    assert_eq!(m.consistency_failures(), CHECK_INPUT_SIZE);
+    let mut data = Vec::new();
+    m.try_serialize(&mut data).unwrap();
+    h.accounts.get_mut(&model.manifest).unwrap().data[..data.len()].copy_from_slice(&data);
+    let before = h.data(&model.manifest).to_vec();
+    h.ok(&validate(model.manifest));
+    assert_eq!(h.data(&model.manifest), &before[..]);
+
+    // Only manifests validate
+    assert!(h.process(&validate(model.weights[0])).raw_result.is_err());
+}
+
+fn accumulate_ix(
+    s: &Session,
+    model: &Model,
+    accumulator: Pubkey,
+    cranker: Pubkey,
+    row_start: u16,
+    row_count: u16,
+    input: &[i8],
+) -> Instruction {
+    let mut ix = build(
+        accounts::AccumulateMatmulRows {
+            session: s.session,
+            manifest: model.manifest,
+            shard_directory: pda(&[SHARD_DIRECTORY_SEED, model.manifest.as_ref()]),
+            accumulator,
+            cranker,
+        },
+        instruction::AccumulateMatmulRows {
+            frame: 1,
+            segment: accumulator_segment(0, TENSOR_IN_PROJ as u8),
+            row_start,
+            row_count,
+            input: input.to_vec(),
+        },
+    );
+    ix.accounts.extend(
+        model
+            .weights
+            .iter()
+            .map(|&w| anchor_lang::solana_program::instruction::AccountMeta::new_readonly(w, false)),
+    );
+    ix
+}
+
+#[test]
+fn split_matmul_accumulates_then_requantizes() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+    h.ok(&join_ix(&s, s.player2));
+    let cranker = h.wallet();
+
+    let t = fixture.tensor_table[0][TENSOR_IN_PROJ];
+    let (rows, cols) = (t.rows as usize, t.cols as usize);
+    let accumulator = h.allocate(vec![0; AccumulatorHeader::account_size(rows)]);
+    let input = fixture.input();
+    let begin = |frame: u32, cranker: Pubkey| {
+        build(
+            accounts::BeginMatmulAccumulator {
+                session: s.session,
+                manifest: model.manifest,
+                accumulator,
+                cranker,
+            },
+            instruction::BeginMatmulAccumulator {
+                frame,
+                layer: 0,
+                tensor: TENSOR_IN_PROJ as u8,
+                input_hash: accumulator_input_hash(&input),
+            },
+        )
+    };
+    let requantize_at = |frame: u32, segment: u16| {
+        build(
+            accounts::RequantizeAccumulator {
+                session: s.session,
+                manifest: model.manifest,
+                accumulator,
+                cranker,
+            },
+            instruction::RequantizeAccumulator { frame, segment },
+        )
+    };
+    let segment = accumulator_segment(0, TENSOR_IN_PROJ as u8);
+    let requantize_ix = requantize_at(1, segment);
+
+    // Only the session's next frame can be claimed, and only by one cranker
+    h.fails_with(&begin(2, cranker), WorldModelError::AccumulatorMismatch);
+    h.ok(&begin(1, cranker));
+    let rival = h.wallet();
+    h.fails_with(&begin(1, rival), WorldModelError::AccumulatorInUse);
+
+    let chunk = ACCUM_CHUNK_ROWS as u16;
+    h.fails_with(
+        &accumulate_ix(&s, &model, accumulator, cranker, 1, chunk, &input),
+        WorldModelError::InvalidAccumulatorRange,
+    );
+    h.fails_with(
+        &accumulate_ix(&s, &model, accumulator, cranker, 0, chunk, &vec![1; cols]),
+        WorldModelError::AccumulatorMismatch,
+    );
+    h.ok(&accumulate_ix(&s, &model, accumulator, cranker, 0, chunk, &input));
+    h.fails_with(&requantize_ix, WorldModelError::AccumulatorIncomplete);
+
+    let rest = rows as u16 - chunk;
+    h.ok(&accumulate_ix(&s, &model, accumulator, cranker, chunk, rest, &input));
+    // Only the claimed frame and segment requantize
+    h.fails_with(&requantize_at(2, segment), WorldModelError::AccumulatorMismatch);
+    let out_proj = accumulator_segment(0, TENSOR_OUT_PROJ as u8);
+    h.fails_with(&requantize_at(1, out_proj), WorldModelError::AccumulatorMismatch);
+    h.ok(&requantize_ix);
+
+    // Same INT8 projection as the single-transaction matmul
+    let weights = t.resolve(&fixture.shard_slices()).unwrap();
+    let mut sums = vec![0i32; rows];
+    matmul::matmul_i8(weights, &input, &mut sums, rows, cols);
+    let mut expected = vec![0i8; rows];
+    matmul::requantize_per_channel(&sums, &fixture.in_scales()[0], &mut expected, rows);
+
+    let data = h.data(&accumulator);
+    let header = read_accumulator_header(data);
+    assert!(header.requantized && header.is_complete());
+    let out = &data[ACCUM_HEADER_SIZE + rows * 4..AccumulatorHeader::account_size(rows)];
+    assert_eq!(out, expected.iter().map(|&v| v as u8).collect::<Vec<_>>());
+}
+
+#[test]
+fn session_runs_frames() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+    let cranker = h.wallet();
+
+    let state = h.session(&s.session);
+    assert_eq!(state.status, STATUS_WAITING_PLAYERS);
+    assert_eq!(state.model, model.manifest);
+
+    h.ok(&join_ix(&s, s.player2));
+    let state = h.session(&s.session);
+    assert_eq!(state.status, STATUS_ACTIVE);
+    assert_eq!(state.players[1].character, 9);
+    let start_x = [state.players[0].x, state.players[1].x];
+
+    const FRAMES: u32 = 5;
+    let mut commitments = Vec::new();
+    for frame in 1..=FRAMES {
+        h.ok(&submit_ix(&s, s.player1, 10, 0));
+        h.ok(&submit_ix(&s, s.player2, -20, 0));
+        h.ok(&inference_ix(&h, &s, &model, cranker));
+
+        let state = h.session(&s.session);
+        assert_eq!(state.frame, frame);
+        // Stub dynamics: stick_x * 2 per frame
+        assert_eq!(state.players[0].x, start_x[0] + 20 * frame as i32);
+        assert_eq!(state.players[1].x, start_x[1] - 40 * frame as i32);
+        assert_eq!(state.players[1].facing, 0);
+        commitments.push(state.state_commitment);
+    }
+    commitments.dedup();
+    assert_eq!(
+        commitments.len(),
+        FRAMES as usize,
+        "commitment must roll every frame"
+    );
+
+    // The first frame zeroed the stale hidden state and the header tracks frames
+    let header = read_hidden_header(h.data(&s.hidden_state));
+    assert!(header.initialized);
+    assert_eq!(header.frame, FRAMES);
+    assert!(h.data(&s.hidden_state)[HIDDEN_HEADER_SIZE..]
+        .iter()
+        .all(|&b| b == 0));
+
+    let meter: InferenceMeterAccount = h.anchor(&pda(&[METER_SEED, s.session.as_ref()]));
+    assert_eq!(meter.frames, FRAMES as u64);
+
+    h.ok(&close_ix(&s, s.player2));
+    assert_eq!(h.session(&s.session).status, STATUS_ENDED);
+}
+
+#[test]
+fn calibration_is_opt_in_and_authority_only() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let m: ModelManifestAccount = h.anchor(&model.manifest);
+    let calibration = pda(&[CALIBRATION_SEED, model.manifest.as_ref()]);
+    let init_ix = |authority| {
+        build(
+            accounts::InitCalibration {
+                manifest: model.manifest,
+                calibration,
+                authority,
+                system_program: system_program::ID,
+            },
+            instruction::InitCalibration {},
+        )
+    };
+
+    let stranger = h.wallet();
+    h.fails_with(&init_ix(stranger), WorldModelError::Unauthorized);
+    h.ok(&init_ix(m.authority));
+    let c: CalibrationAccount = h.anchor(&calibration);
+    assert_eq!((c.manifest, c.frames), (model.manifest, 0));
+    assert!(c.range(0).in_scale().is_none());
+
+    // run_inference takes it in the optional slot
+    let s = create_session(&mut h, &fixture, &model);
+    let cranker = h.wallet();
+    h.ok(&join_ix(&s, s.player2));
+    h.ok(&submit_ix(&s, s.player1, 10, 0));
+    h.ok(&submit_ix(&s, s.player2, -20, 0));
+    let mut ix = inference_ix(&h, &s, &model, cranker);
+    let slot = ix.accounts.len() - 1;
+    ix.accounts[slot] = AccountMeta::new(calibration, false);
+    h.ok(&ix);
+    assert_eq!(h.session(&s.session).frame, 1);
+
+    let reset_ix = |authority| {
+        build(
+            accounts::ResetCalibration {
+                manifest: model.manifest,
+                calibration,
+                authority,
+            },
+            instruction::ResetCalibration {},
+        )
+    };
+    h.fails_with(&reset_ix(stranger), WorldModelError::Unauthorized);
+    h.ok(&reset_ix(m.authority));
+}
+
+#[test]
+fn run_inference_checks_session_accounts() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+    let s = create_session(&mut h, &fixture, &model);
+    let other = create_session(&mut h, &fixture, &model);
+    for s in [&s, &other] {
+        h.ok(&join_ix(s, s.player2));
+        h.ok(&submit_ix(s, s.player1, 10, 0));
+        h.ok(&submit_ix(s, s.player2, -10, 0));
+    }
+    let swap = |index: usize, key: Pubkey| {
+        let mut ix = inference_ix_at(&s, &model, cranker, 1);
+        ix.accounts[index].pubkey = key;
+        ix
+    };
+
+    // Another session's hidden state or input buffer
+    h.fails_with(&swap(1, other.hidden_state), WorldModelError::SessionAccountMismatch);
+    h.fails_with(&swap(2, other.input_buffer), WorldModelError::SessionAccountMismatch);
+
+    // A manifest other than the session's model
+    let stranger = Pubkey::new_unique();
+    h.ok(&build(
+        accounts::InitManifest {
+            manifest: stranger,
+            authority: cranker,
+            system_program: system_program::ID,
+        },
+        fixture.init_manifest_args(),
+    ));
+    h.fails_with(&swap(3, stranger), WorldModelError::ModelMismatch);
+
+    // A shard that was never finalized
+    let unfinalized = create_weight_account(&mut h, &fixture, 0, cranker);
+    h.fails_with(&swap(4, unfinalized), WorldModelError::WeightsMismatch);
+
+    h.ok(&inference_ix(&h, &s, &model, cranker));
+    assert_eq!(h.session(&s.session).frame, 1);
+}
+
+#[test]
+fn session_error_paths() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+    let cranker = h.wallet();
+
+    // Nothing runs before player 2 joins
+    h.fails_with(
+        &submit_ix(&s, s.player1, 0, 0),
+        WorldModelError::SessionNotActive,
+    );
+    h.fails_with(
+        &inference_ix(&h, &s, &model, cranker),
+        WorldModelError::SessionNotActive,
+    );
+    h.fails_with(
+        &join_ix(&s, s.player1),
+        WorldModelError::CannotJoinOwnSession,
+    );
+
+    h.ok(&join_ix(&s, s.player2));
+    h.fails_with(
+        &join_ix(&s, s.player2),
+        WorldModelError::InvalidStateTransition,
+    );
+
+    // Only the two players submit, and both must before a frame runs
+    let stranger = h.wallet();
+    h.fails_with(
+        &submit_ix(&s, stranger, 0, 0),
+        WorldModelError::UnauthorizedPlayer,
+    );
+    h.ok(&submit_ix(&s, s.player1, 0, 0));
+    h.fails_with(
+        &inference_ix(&h, &s, &model, cranker),
+        WorldModelError::InputsNotReady,
+    );
+    h.ok(&submit_ix(&s, s.player2, 0, 0));
+    h.fails_with(&inference_ix_at(&s, &model, cranker, 2), WorldModelError::FrameMismatch);
+    h.ok(&inference_ix(&h, &s, &model, cranker));
+    assert_eq!(h.session(&s.session).frame, 1);
+
+    // A retried or racing crank for frame 1 can't advance it again, and
+    // frame 2 waits for inputs submitted for it
+    let retry = h.wallet();
+    h.fails_with(&inference_ix_at(&s, &model, retry, 1), WorldModelError::FrameMismatch);
+    h.fails_with(&inference_ix(&h, &s, &model, cranker), WorldModelError::InputsNotReady);
+    assert_eq!(h.session(&s.session).frame, 1);
+
+    // Strangers can't end it; once ended, nothing advances
+    h.fails_with(&close_ix(&s, stranger), WorldModelError::UnauthorizedPlayer);
+    h.ok(&close_ix(&s, s.player1));
+    h.fails_with(
+        &submit_ix(&s, s.player1, 0, 0),
+        WorldModelError::SessionNotActive,
+    );
+    h.fails_with(
+        &inference_ix(&h, &s, &model, cranker),
+        WorldModelError::SessionNotActive,
+    );
+    h.fails_with(
+        &close_ix(&s, s.player1),
+        WorldModelError::InvalidStateTransition,
+    );
+}
+
+fn reset_hidden_ix(s: &Session, hidden_state: Pubkey, model: &Model, signer: Pubkey) -> Instruction {
+    build(
+        accounts::ResetHiddenState {
+            session: s.session,
+            hidden_state,
+            manifest: model.manifest,
+            signer,
+        },
+        instruction::ResetHiddenState {},
+    )
+}
+
+#[test]
+fn reset_hidden_state_only_zeroes_the_sessions_own_state() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+    let s = create_session(&mut h, &fixture, &model);
+    let other = create_session(&mut h, &fixture, &model);
+    for s in [&s, &other] {
+        h.ok(&join_ix(s, s.player2));
+        h.ok(&submit_ix(s, s.player1, 10, 0));
+        h.ok(&submit_ix(s, s.player2, -10, 0));
+        h.ok(&inference_ix(&h, s, &model, cranker));
+    }
+    h.ok(&close_ix(&s, s.player1));
+
+    // An ended session can't reach into a live session's memory
+    h.fails_with(
+        &reset_hidden_ix(&s, other.hidden_state, &model, s.player1),
+        WorldModelError::SessionAccountMismatch,
+    );
+    assert!(read_hidden_header(h.data(&other.hidden_state)).initialized);
+
+    h.ok(&reset_hidden_ix(&s, s.hidden_state, &model, s.player1));
+    let header = read_hidden_header(h.data(&s.hidden_state));
+    assert_eq!((header.frame, header.initialized), (0, false));
+}
+
+fn warm_start_ix(
+    s: &Session,
+    hidden_state: Pubkey,
+    source: &Session,
+    source_hidden_state: Pubkey,
+    model: &Model,
+) -> Instruction {
+    build(
+        accounts::WarmStartSession {
+            session: s.session,
+            hidden_state,
+            source_session: source.session,
+            source_hidden_state,
+            manifest: model.manifest,
+            player1: s.player1,
+        },
+        instruction::WarmStartSession {},
+    )
+}
+
+#[test]
+fn warm_start_copies_between_the_sessions_own_states() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+    let source = create_session(&mut h, &fixture, &model);
+    h.ok(&join_ix(&source, source.player2));
+    h.ok(&submit_ix(&source, source.player1, 10, 0));
+    h.ok(&submit_ix(&source, source.player2, -10, 0));
+    h.ok(&inference_ix(&h, &source, &model, cranker));
+    h.ok(&close_ix(&source, source.player1));
+    let s = create_session(&mut h, &fixture, &model);
+    let bystander = create_session(&mut h, &fixture, &model);
+
+    // Neither side can be swapped for another session's state
+    h.fails_with(
+        &warm_start_ix(&s, bystander.hidden_state, &source, source.hidden_state, &model),
+        WorldModelError::SessionAccountMismatch,
+    );
+    h.fails_with(
+        &warm_start_ix(&s, s.hidden_state, &source, bystander.hidden_state, &model),
+        WorldModelError::SessionAccountMismatch,
+    );
+
+    h.ok(&warm_start_ix(&s, s.hidden_state, &source, source.hidden_state, &model));
+    assert!(read_hidden_header(h.data(&s.hidden_state)).initialized);
+    assert_eq!(
+        h.data(&s.hidden_state)[HIDDEN_HEADER_SIZE..],
+        h.data(&source.hidden_state)[HIDDEN_HEADER_SIZE..]
+    );
+}
+
+fn checkpoint_ix(s: &Session, hidden_state: Pubkey, player: Pubkey) -> Instruction {
+    build(
+        accounts::CheckpointHiddenState {
+            session: s.session,
+            hidden_state,
+            checkpoint: pda(&[CHECKPOINT_SEED, s.session.as_ref()]),
+            player,
+            system_program: system_program::ID,
+        },
+        instruction::CheckpointHiddenState {},
+    )
+}
+
+fn restore_ix(s: &Session, source: &Session, model: &Model) -> Instruction {
+    build(
+        accounts::RestoreCheckpoint {
+            session: s.session,
+            hidden_state: s.hidden_state,
+            source_session: source.session,
+            checkpoint: pda(&[CHECKPOINT_SEED, source.session.as_ref()]),
+            manifest: model.manifest,
+            player1: s.player1,
+        },
+        instruction::RestoreCheckpoint {},
+    )
+}
+
+/// A waiting session created by `player1`.
+fn create_session_by(
+    h: &mut Harness,
+    fixture: &SyntheticModel,
+    model: &Model,
+    player1: Pubkey,
+) -> Session {
+    let (mut s, mut ix) = create_session_ix(h, fixture, model, TICK_RATE_60);
+    ix.accounts[4].pubkey = player1;
+    s.player1 = player1;
+    h.ok(&ix);
+    s
+}
+
+#[test]
+fn checkpoint_is_written_once_and_restores_for_its_players() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+    let s = create_session(&mut h, &fixture, &model);
+    let other = create_session(&mut h, &fixture, &model);
+    for s in [&s, &other] {
+        h.ok(&join_ix(s, s.player2));
+        h.ok(&submit_ix(s, s.player1, 10, 0));
+        h.ok(&submit_ix(s, s.player2, -10, 0));
+        h.ok(&inference_ix(&h, s, &model, cranker));
+        h.ok(&close_ix(s, s.player1));
+    }
+    let checkpoint = pda(&[CHECKPOINT_SEED, s.session.as_ref()]);
+
+    // Only the session's own hidden state, by one of its players
+    h.fails_with(
+        &checkpoint_ix(&s, other.hidden_state, s.player1),
+        WorldModelError::SessionAccountMismatch,
+    );
+    let stranger = h.wallet();
+    h.fails_with(
+        &checkpoint_ix(&s, s.hidden_state, stranger),
+        WorldModelError::UnauthorizedPlayer,
+    );
+
+    h.ok(&checkpoint_ix(&s, s.hidden_state, s.player2));
+    let header = read_checkpoint_header(h.data(&checkpoint));
+    assert_eq!((header.session, header.model), (s.session, model.manifest));
+    assert_eq!(header.frame, 1);
+    assert_eq!(header.state_commitment, h.session(&s.session).state_commitment);
+
+    // A written checkpoint is final
+    h.fails_with(
+        &checkpoint_ix(&s, s.hidden_state, s.player1),
+        WorldModelError::CheckpointInUse,
+    );
+
+    // Restoring takes a player of the checkpointed session
+    let outsider = create_session(&mut h, &fixture, &model);
+    h.fails_with(&restore_ix(&outsider, &s, &model), WorldModelError::UnauthorizedPlayer);
+    let next = create_session_by(&mut h, &fixture, &model, s.player2);
+    h.ok(&restore_ix(&next, &s, &model));
+    assert!(read_hidden_header(h.data(&next.hidden_state)).initialized);
+    assert_eq!(
+        h.data(&next.hidden_state)[HIDDEN_HEADER_SIZE..],
+        h.data(&s.hidden_state)[HIDDEN_HEADER_SIZE..]
+    );
+}
+
+fn frame_advance_ix(sessions: &[&Session], model: &Model, cranker: Pubkey) -> Instruction {
+    let mut ix = build(
+        accounts::FrameAdvance {
+            manifest: model.manifest,
+            weights: model.weights[0],
+            cranker,
+        },
+        instruction::FrameAdvance {},
+    );
+    for s in sessions {
+        let meter = pda(&[METER_SEED, s.session.as_ref()]);
+        for key in [s.session, s.hidden_state, s.input_buffer, meter] {
+            ix.accounts
+                .push(anchor_lang::solana_program::instruction::AccountMeta::new(key, false));
+        }
+    }
+    ix
+}
+
+#[test]
+fn frame_advance_skips_stalled_sessions() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+
+    // ready: both inputs in; stalled: player 2 hasn't submitted; waiting:
+    // nobody joined
+    let ready = create_session(&mut h, &fixture, &model);
+    let stalled = create_session(&mut h, &fixture, &model);
+    let waiting = create_session(&mut h, &fixture, &model);
+    for s in [&ready, &stalled] {
+        h.ok(&join_ix(s, s.player2));
+        h.ok(&submit_ix(s, s.player1, 10, 0));
+    }
+    h.ok(&submit_ix(&ready, ready.player2, -10, 0));
+
+    let ix = frame_advance_ix(&[&ready, &stalled, &waiting], &model, cranker);
+    let result = h.process(&ix);
+    assert!(result.raw_result.is_ok(), "{:?}", result.raw_result);
+    // Borsh Vec<u8>: length, then one ADVANCE_* code per bundle
+    assert_eq!(result.return_data, [3, 0, 0, 0, 0, 2, 1]);
+    assert_eq!(h.session(&ready.session).frame, 1);
+    assert_eq!(h.session(&stalled.session).frame, 0);
+    let meter: InferenceMeterAccount = h.anchor(&pda(&[METER_SEED, ready.session.as_ref()]));
+    assert_eq!(meter.frames, 1);
+
+    // The ready session's inputs were for frame 1; until both players
+    // submit again it's stalled too
+    let result = h.process(&ix);
+    assert_eq!(result.return_data, [3, 0, 0, 0, 2, 2, 1]);
+    assert_eq!(h.session(&ready.session).frame, 1);
+
+    // A bundle from the wrong session is skipped, not failed
+    let mut ix = frame_advance_ix(&[&ready], &model, cranker);
+    ix.accounts[4].pubkey = stalled.hidden_state;
+    let result = h.process(&ix);
+    assert_eq!(result.return_data, [1, 0, 0, 0, 3]);
+
+    h.fails_with(
+        &frame_advance_ix(&[], &model, cranker),
+        WorldModelError::SessionBundleMismatch,
+    );
+}
+
+fn crank_mode_ix(s: &Session, player1: Pubkey, mode: u8, cranker: Pubkey) -> Instruction {
+    build(
+        accounts::SetCrankMode {
+            session: s.session,
+            player1,
+        },
+        instruction::SetCrankMode { mode, cranker },
+    )
+}
+
+#[test]
+fn crank_mode_gates_who_advances_frames() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let bot = h.wallet();
+    let stranger = h.wallet();
+
+    let designated = create_session(&mut h, &fixture, &model);
+    let players_only = create_session(&mut h, &fixture, &model);
+    let s = &designated;
+
+    // Player 1 picks the mode, only while waiting for player 2
+    h.fails_with(
+        &crank_mode_ix(s, s.player2, CRANK_MODE_DESIGNATED, bot),
+        WorldModelError::UnauthorizedPlayer,
+    );
+    h.fails_with(
+        &crank_mode_ix(s, s.player1, CRANK_MODE_DESIGNATED, Pubkey::default()),
+        WorldModelError::InvalidCrankMode,
+    );
+    h.fails_with(&crank_mode_ix(s, s.player1, 3, bot), WorldModelError::InvalidCrankMode);
+    h.ok(&crank_mode_ix(s, s.player1, CRANK_MODE_DESIGNATED, bot));
+    let p = &players_only;
+    h.ok(&crank_mode_ix(p, p.player1, CRANK_MODE_PLAYERS, bot));
+    assert_eq!(h.session(&p.session).crank_authority, Pubkey::default());
+
+    for s in [&designated, &players_only] {
+        h.ok(&join_ix(s, s.player2));
+        h.ok(&submit_ix(s, s.player1, 10, 0));
+        h.ok(&submit_ix(s, s.player2, -10, 0));
+    }
+    h.fails_with(
+        &crank_mode_ix(s, s.player1, CRANK_MODE_PERMISSIONLESS, bot),
+        WorldModelError::InvalidStateTransition,
+    );
+
+    h.fails_with(&inference_ix(&h, s, &model, stranger), WorldModelError::CrankerNotPermitted);
+    h.fails_with(&inference_ix(&h, s, &model, s.player1), WorldModelError::CrankerNotPermitted);
+    h.fails_with(&inference_ix(&h, p, &model, bot), WorldModelError::CrankerNotPermitted);
+    h.ok(&inference_ix(&h, s, &model, bot));
+    h.ok(&inference_ix(&h, p, &model, p.player2));
+    assert_eq!(h.session(&s.session).frame, 1);
+    assert_eq!(h.session(&p.session).frame, 1);
+
+    // frame_advance skips sessions the cranker may not advance
+    for s in [&designated, &players_only] {
+        h.ok(&submit_ix(s, s.player1, 10, 0));
+        h.ok(&submit_ix(s, s.player2, -10, 0));
+    }
+    let result = h.process(&frame_advance_ix(&[&designated, &players_only], &model, bot));
+    assert!(result.raw_result.is_ok(), "{:?}", result.raw_result);
+    assert_eq!(result.return_data, [2, 0, 0, 0, 0, 4]);
+    assert_eq!(h.session(&designated.session).frame, 2);
+    assert_eq!(h.session(&players_only.session).frame, 1);
+}
+
+fn update_fee_ix(schedule: &FeeScheduleAccount, authority: Pubkey, session_fee: u64) -> Instruction {
+    build(
+        accounts::UpdateFeeSchedule {
+            fee_schedule: pda(&[FEE_SCHEDULE_SEED]),
+            authority,
+        },
+        instruction::UpdateFeeSchedule {
+            protocol_treasury: schedule.protocol_treasury,
+            session_fee,
+            author_bps: schedule.author_bps,
+            cranker_bps: schedule.cranker_bps,
+            protocol_bps: schedule.protocol_bps,
+        },
+    )
+}
+
+fn settle_fees_ix(s: &Session, model: &Model, cranker: Pubkey, protocol_treasury: Pubkey) -> Instruction {
+    build(
+        accounts::SettleSessionFees {
+            session: s.session,
+            model: model.manifest,
+            fee_schedule: pda(&[FEE_SCHEDULE_SEED]),
+            author_treasury: pda(&[TREASURY_SEED, model.manifest.as_ref()]),
+            cranker,
+            protocol_treasury,
+        },
+        instruction::SettleSessionFees {},
+    )
+}
+
+#[test]
+fn fee_schedule_is_set_up_by_the_upgrade_authority() {
+    let mut h = Harness::new();
+    let deployer = h.wallet();
+    h.set_upgrade_authority(deployer);
+    let stranger = h.wallet();
+
+    h.fails_with(&init_fee_schedule_ix(stranger), WorldModelError::Unauthorized);
+    h.ok(&init_fee_schedule_ix(deployer));
+    let schedule: FeeScheduleAccount = h.anchor(&pda(&[FEE_SCHEDULE_SEED]));
+    assert_eq!(schedule.authority, deployer);
+    assert!(h.process(&init_fee_schedule_ix(deployer)).raw_result.is_err());
+
+    h.fails_with(&update_fee_ix(&schedule, stranger, 1), WorldModelError::Unauthorized);
+    h.ok(&update_fee_ix(&schedule, deployer, 1));
+    let schedule: FeeScheduleAccount = h.anchor(&pda(&[FEE_SCHEDULE_SEED]));
+    assert_eq!(schedule.session_fee, 1);
+}
+
+#[test]
+fn session_fees_split_between_author_cranker_and_protocol() {
+    const FEE: u64 = 10_000_000;
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let schedule: FeeScheduleAccount = h.anchor(&pda(&[FEE_SCHEDULE_SEED]));
+    h.ok(&update_fee_ix(&schedule, schedule.authority, FEE));
+    let protocol = schedule.protocol_treasury;
+
+    // create_session collects the fee into the session account
+    let s = create_session(&mut h, &fixture, &model);
+    assert_eq!(h.session(&s.session).fees_collected, FEE);
+    h.ok(&join_ix(&s, s.player2));
+    let cranker = h.wallet();
+    h.ok(&submit_ix(&s, s.player1, 10, 0));
+    h.ok(&submit_ix(&s, s.player2, -10, 0));
+    h.ok(&inference_ix(&h, &s, &model, cranker));
+
+    h.fails_with(&settle_fees_ix(&s, &model, cranker, protocol), WorldModelError::SessionNotEnded);
+    h.ok(&close_ix(&s, s.player1));
+    let stranger = h.wallet();
+    h.fails_with(&settle_fees_ix(&s, &model, stranger, protocol), WorldModelError::NotTopCranker);
+
+    let treasury = pda(&[TREASURY_SEED, model.manifest.as_ref()]);
+    let before = balances(&h, [treasury, cranker, protocol, s.session]);
+    h.ok(&settle_fees_ix(&s, &model, cranker, protocol));
+    let bps = |b: u16| FEE * b as u64 / BPS_DENOMINATOR as u64;
+    assert_eq!(
+        balances(&h, [treasury, cranker, protocol, s.session]),
+        [
+            before[0] + bps(schedule.author_bps),
+            before[1] + bps(schedule.cranker_bps),
+            before[2] + bps(schedule.protocol_bps),
+            before[3] - FEE,
+        ]
+    );
+    assert_eq!(h.session(&s.session).fees_collected, 0);
+}
+
+fn reap_ix(s: &Session, model: &Model, protocol_treasury: Pubkey, reaper: Pubkey) -> Instruction {
+    build(
+        accounts::ReapSession {
+            session: s.session,
+            model: model.manifest,
+            fee_schedule: pda(&[FEE_SCHEDULE_SEED]),
+            author_treasury: pda(&[TREASURY_SEED, model.manifest.as_ref()]),
+            // Nobody cranked, so any account stands in for the top cranker
+            cranker: pda(&[METER_SEED, s.session.as_ref()]),
+            protocol_treasury,
+            reaper,
+        },
+        instruction::ReapSession {},
+    )
+}
+
+#[test]
+fn reap_session_ends_idle_sessions() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+
+    // Charge a protocol fee so there's a bounty to pay, large enough that
+    // the author share lifts the empty treasury past rent
+    const FEE: u64 = 10_000_000;
+    let schedule: FeeScheduleAccount = h.anchor(&pda(&[FEE_SCHEDULE_SEED]));
+    h.ok(&update_fee_ix(&schedule, schedule.authority, FEE));
+
+    let s = create_session(&mut h, &fixture, &model);
+    h.ok(&join_ix(&s, s.player2));
+    let reaper = h.wallet();
+    let reap = reap_ix(&s, &model, schedule.protocol_treasury, reaper);
+
+    h.fails_with(&reap, WorldModelError::SessionNotIdle);
+
+    h.mollusk.sysvars.clock.unix_timestamp += SESSION_IDLE_TIMEOUT_SECS;
+    let reaper_before = h.accounts[&reaper].lamports;
+    let session_before = h.accounts[&s.session].lamports;
+    h.ok(&reap);
+
+    let state = h.session(&s.session);
+    assert_eq!(state.status, STATUS_ENDED);
+    assert_eq!(state.fees_collected, 0);
+    let bounty = FEE * REAPER_BPS as u64 / BPS_DENOMINATOR as u64;
+    assert_eq!(h.accounts[&reaper].lamports - reaper_before, bounty);
+    // The whole fee left the session: bounty, author and protocol shares
+    assert_eq!(session_before - h.accounts[&s.session].lamports, FEE);
+
+    h.fails_with(&reap, WorldModelError::InvalidStateTransition);
+}
+
+fn configure_delegation_ix(s: &Session, player1: Pubkey, commit_mask: u8) -> Instruction {
+    build(
+        accounts::ConfigureDelegation {
+            session: s.session,
+            config: pda(&[DELEGATION_SEED, s.session.as_ref()]),
+            summary: pda(&[SETTLEMENT_SEED, s.session.as_ref()]),
+            player1,
+            system_program: system_program::ID,
+        },
+        instruction::ConfigureDelegation {
+            validator: Pubkey::default(),
+            commit_frequency_ms: 0,
+            commit_mask,
+        },
+    )
+}
+
+#[test]
+fn configure_delegation_records_session_accounts() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+
+    h.fails_with(
+        &configure_delegation_ix(&s, s.player1, 0),
+        WorldModelError::InvalidCommitMask,
+    );
+    h.fails_with(
+        &configure_delegation_ix(&s, s.player1, COMMIT_ALL + 1),
+        WorldModelError::InvalidCommitMask,
+    );
+    assert!(h
+        .process(&configure_delegation_ix(&s, s.player2, DEFAULT_COMMIT_MASK))
+        .raw_result
+        .is_err());
+
+    h.ok(&configure_delegation_ix(&s, s.player1, DEFAULT_COMMIT_MASK));
+    let config: DelegationConfigAccount =
+        h.anchor(&pda(&[DELEGATION_SEED, s.session.as_ref()]));
+    assert_eq!(config.session, s.session);
+    assert_eq!(config.hidden_state, s.hidden_state);
+    assert_eq!(config.input_buffer, s.input_buffer);
+    assert_eq!(config.authority, s.player1);
+    assert!(config.commits(ER_ACCOUNT_SESSION) && config.commits(ER_ACCOUNT_METER));
+    assert!(!config.commits(ER_ACCOUNT_HIDDEN_STATE));
+    // The settlement summary starts empty; undelegation fills it in
+    let summary: SettlementSummaryAccount =
+        h.anchor(&pda(&[SETTLEMENT_SEED, s.session.as_ref()]));
+    assert_eq!(summary.session, s.session);
+    assert_eq!((summary.final_frame, summary.settled_at), (0, 0));
+
+    // Reconfigurable until delegated
+    h.ok(&configure_delegation_ix(&s, s.player1, COMMIT_ALL));
+    let config: DelegationConfigAccount =
+        h.anchor(&pda(&[DELEGATION_SEED, s.session.as_ref()]));
+    assert!(config.commits(ER_ACCOUNT_HIDDEN_STATE));
+}
+
+fn register_crank_ix(
+    s: &Session,
+    model: &Model,
+    payer: Pubkey,
+    interval_ms: u32,
+    iterations: u32,
+) -> Instruction {
+    build(
+        accounts::RegisterCrank {
+            session: s.session,
+            player1: s.player1,
+            hidden_state: s.hidden_state,
+            input_buffer: s.input_buffer,
+            meter: pda(&[METER_SEED, s.session.as_ref()]),
+            manifest: model.manifest,
+            weights: model.weights[0],
+            payer,
+            magic_program: world_model::delegation::MAGIC_PROGRAM_ID,
+        },
+        instruction::RegisterCrank {
+            interval_ms,
+            iterations,
+        },
+    )
+}
+
+#[test]
+fn register_crank_checks_cadence_and_session() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+    let payer = h.wallet();
+
+    h.fails_with(
+        &register_crank_ix(&s, &model, payer, CRANK_INTERVAL_MS, 3600),
+        WorldModelError::SessionNotActive,
+    );
+
+    h.ok(&join_ix(&s, s.player2));
+    h.fails_with(
+        &register_crank_ix(&s, &model, payer, CRANK_INTERVAL_MS - 1, 3600),
+        WorldModelError::CrankIntervalTooShort,
+    );
+    h.fails_with(
+        &register_crank_ix(&s, &model, payer, CRANK_INTERVAL_MS, 0),
+        WorldModelError::CrankIterationsZero,
+    );
+
+    let mut swapped = register_crank_ix(&s, &model, payer, CRANK_INTERVAL_MS, 3600);
+    swapped.accounts[2].pubkey = s.input_buffer;
+    swapped.accounts[3].pubkey = s.hidden_state;
+    h.fails_with(&swapped, WorldModelError::SessionAccountMismatch);
+}
+
+#[test]
+fn tick_rate_sets_crank_cadence_and_idle_timeout() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let schedule: FeeScheduleAccount = h.anchor(&pda(&[FEE_SCHEDULE_SEED]));
+
+    let s = create_session_at(&mut h, &fixture, &model, TICK_RATE_30);
+    assert_eq!(h.session(&s.session).tick_interval_ms(), 33);
+    h.ok(&join_ix(&s, s.player2));
+    let payer = h.wallet();
+    h.fails_with(
+        &register_crank_ix(&s, &model, payer, CRANK_INTERVAL_MS, 3600),
+        WorldModelError::CrankIntervalTooShort,
+    );
+
+    // Twice the wall-clock idle time of a 60fps session
+    let reap = reap_ix(&s, &model, schedule.protocol_treasury, h.wallet());
+    h.mollusk.sysvars.clock.unix_timestamp += SESSION_IDLE_TIMEOUT_SECS;
+    h.fails_with(&reap, WorldModelError::SessionNotIdle);
+    h.mollusk.sysvars.clock.unix_timestamp += SESSION_IDLE_TIMEOUT_SECS;
+    h.ok(&reap);
+}
+
+#[test]
+fn create_session_rejects_unsupported_tick_rate() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let (_, ix) = create_session_ix(&mut h, &fixture, &model, 45);
+    h.fails_with(&ix, WorldModelError::InvalidTickRate);
+}
+
+#[test]
+fn create_session_binds_only_a_fresh_hidden_state() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let victim = create_session(&mut h, &fixture, &model);
+    let before = h.data(&victim.hidden_state).to_vec();
+
+    // Another session's hidden state already has a header
+    let (_, mut ix) = create_session_ix(&mut h, &fixture, &model, TICK_RATE_60);
+    ix.accounts[1].pubkey = victim.hidden_state;
+    h.fails_with(&ix, WorldModelError::HiddenStateInUse);
+    assert_eq!(h.data(&victim.hidden_state), &before[..]);
+
+    let size = fixture.hidden_account_data().len();
+    let (_, mut ix) = create_session_ix(&mut h, &fixture, &model, TICK_RATE_60);
+    ix.accounts[1].pubkey = h.allocate(vec![0; size - 1]);
+    h.fails_with(&ix, WorldModelError::HiddenStateInUse);
+}
+
+#[test]
+fn token_royalty_waives_rent_minimum_and_needs_fee_accounts() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let authority = h.anchor::<ModelManifestAccount>(&model.manifest).authority;
+    let set_fee = |session_fee| {
+        build(
+            accounts::SetSessionFee {
+                manifest: model.manifest,
+                authority,
+            },
+            instruction::SetSessionFee { session_fee },
+        )
+    };
+
+    // One lamport can't fund the treasury PDA; one token unit is fine
+    h.fails_with(&set_fee(1), WorldModelError::FeeBelowRentExempt);
+    let fee_mint = Pubkey::new_unique();
+    h.ok(&build(
+        accounts::SetFeeMint {
+            manifest: model.manifest,
+            authority,
+        },
+        instruction::SetFeeMint { fee_mint },
+    ));
+    h.ok(&set_fee(1));
+    let manifest: ModelManifestAccount = h.anchor(&model.manifest);
+    assert_eq!((manifest.fee_mint, manifest.session_fee), (fee_mint, 1));
+
+    let (_, ix) = create_session_ix(&mut h, &fixture, &model, TICK_RATE_60);
+    h.fails_with(&ix, WorldModelError::FeeTokenAccountsRequired);
+}
+
+fn archive_replay_ix(s: &Session, replay: Pubkey, minter: Pubkey) -> Instruction {
+    let merkle_tree = Pubkey::new_unique();
+    build(
+        accounts::ArchiveReplay {
+            session: s.session,
+            replay,
+            record: pda(&[REPLAY_SEED, s.session.as_ref()]),
+            minter,
+            archive_authority: pda(&[ARCHIVE_AUTHORITY_SEED]),
+            tree_config: archive::tree_config(&merkle_tree),
+            merkle_tree,
+            log_wrapper: archive::NOOP_PROGRAM_ID,
+            compression_program: archive::COMPRESSION_PROGRAM_ID,
+            bubblegum_program: archive::BUBBLEGUM_PROGRAM_ID,
+            system_program: system_program::ID,
+        },
+        instruction::ArchiveReplay {},
+    )
+}
+
+#[test]
+fn archive_replay_checks_before_minting() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+    // Never written by checkpoint_hidden_state: its header names no session
+    let replay = h.allocate(vec![0; CHECKPOINT_HEADER_SIZE + 64]);
+
+    h.ok(&join_ix(&s, s.player2));
+    h.fails_with(
+        &archive_replay_ix(&s, replay, s.player1),
+        WorldModelError::SessionNotEnded,
+    );
+
+    h.ok(&close_ix(&s, s.player2));
+    let stranger = h.wallet();
+    h.fails_with(
+        &archive_replay_ix(&s, replay, stranger),
+        WorldModelError::UnauthorizedPlayer,
+    );
+    h.fails_with(
+        &archive_replay_ix(&s, replay, s.player1),
+        WorldModelError::SessionAccountMismatch,
+    );
+}
+
+fn mint_replay_ix(s: &Session, replay: Pubkey, minter: Pubkey) -> Instruction {
+    let mint = pda(&[REPLAY_MINT_SEED, s.session.as_ref()]);
+    build(
+        accounts::MintReplayNft {
+            session: s.session,
+            replay,
+            record: pda(&[REPLAY_SEED, s.session.as_ref()]),
+            mint,
+            minter_token: associated_token::get_associated_token_address(&minter, &mint),
+            minter,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::MintReplayNft {},
+    )
+}
+
+#[test]
+fn replay_nft_mints_one_of_one_to_a_player() {
+    use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account as TokenState, Mint as MintState};
+
+    let mut h = Harness::new();
+    h.add_token_programs();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+    let s = create_session(&mut h, &fixture, &model);
+    let replay = pda(&[CHECKPOINT_SEED, s.session.as_ref()]);
+    // Never written by checkpoint_hidden_state: its header names no session
+    let blank = h.allocate(vec![0; CHECKPOINT_HEADER_SIZE + 64]);
+
+    h.ok(&join_ix(&s, s.player2));
+    h.ok(&submit_ix(&s, s.player1, 10, 0));
+    h.ok(&submit_ix(&s, s.player2, -10, 0));
+    h.ok(&inference_ix(&h, &s, &model, cranker));
+    h.fails_with(
+        &mint_replay_ix(&s, blank, s.player1),
+        WorldModelError::SessionNotEnded,
+    );
+
+    h.ok(&close_ix(&s, s.player1));
+    h.fails_with(
+        &mint_replay_ix(&s, blank, s.player1),
+        WorldModelError::SessionAccountMismatch,
+    );
+    h.ok(&checkpoint_ix(&s, s.hidden_state, s.player1));
+    let stranger = h.wallet();
+    h.fails_with(
+        &mint_replay_ix(&s, replay, stranger),
+        WorldModelError::UnauthorizedPlayer,
+    );
+
+    h.ok(&mint_replay_ix(&s, replay, s.player2));
+    let record: ReplayRecordAccount = h.anchor(&pda(&[REPLAY_SEED, s.session.as_ref()]));
+    let mint = pda(&[REPLAY_MINT_SEED, s.session.as_ref()]);
+    let ended = h.session(&s.session);
+    assert_eq!((record.session, record.replay, record.mint), (s.session, replay, mint));
+    assert_eq!((record.final_frame, record.minted_by), (ended.frame, s.player2));
+    assert_eq!(record.final_state_hash, ended.final_state_hash().unwrap());
+    assert!(!record.verified);
+
+    // Supply is fixed at the one token the minter holds
+    let mint_state = MintState::unpack(h.data(&mint)).unwrap();
+    assert_eq!(mint_state.supply, 1);
+    assert!(mint_state.mint_authority.is_none());
+    let held = associated_token::get_associated_token_address(&s.player2, &mint);
+    assert_eq!(TokenState::unpack(h.data(&held)).unwrap().amount, 1);
+
+    // The record PDA exists now, so a session mints once
+    assert!(h.process(&mint_replay_ix(&s, replay, s.player1)).raw_result.is_err());
+}
+
+fn begin_verification_ix(
+    s: &Session,
+    model: &Model,
+    hidden_state: Pubkey,
+    verifier: Pubkey,
+) -> Instruction {
+    let record = pda(&[REPLAY_SEED, s.session.as_ref()]);
+    build(
+        accounts::BeginReplayVerification {
+            verification: pda(&[REPLAY_VERIFY_SEED, record.as_ref(), verifier.as_ref()]),
+            record,
+            session: s.session,
+            manifest: model.manifest,
+            input_buffer: s.input_buffer,
+            hidden_state,
+            verifier,
+            system_program: system_program::ID,
+        },
+        instruction::BeginReplayVerification {},
+    )
+}
+
+fn verify_frames_ix(
+    s: &Session,
+    model: &Model,
+    hidden_state: Pubkey,
+    verifier: Pubkey,
+    frames: u32,
+) -> Instruction {
+    let record = pda(&[REPLAY_SEED, s.session.as_ref()]);
+    build(
+        accounts::VerifyReplayFrames {
+            verification: pda(&[REPLAY_VERIFY_SEED, record.as_ref(), verifier.as_ref()]),
+            record,
+            session: s.session,
+            manifest: model.manifest,
+            input_buffer: s.input_buffer,
+            hidden_state,
+            replay: pda(&[CHECKPOINT_SEED, s.session.as_ref()]),
+            verifier,
+        },
+        instruction::VerifyReplayFrames { frames },
+    )
+}
+
+/// A three-frame session, ended, checkpointed and minted by player 1.
+fn minted_replay(h: &mut Harness, fixture: &SyntheticModel, model: &Model) -> Session {
+    let cranker = h.wallet();
+    let s = create_session(h, fixture, model);
+    h.ok(&join_ix(&s, s.player2));
+    for (x1, x2) in [(10, -10), (127, 0), (-40, 64)] {
+        h.ok(&submit_ix(&s, s.player1, x1, 0));
+        h.ok(&submit_ix(&s, s.player2, x2, 0));
+        h.ok(&inference_ix(h, &s, model, cranker));
+    }
+    h.ok(&close_ix(&s, s.player1));
+    h.ok(&checkpoint_ix(&s, s.hidden_state, s.player1));
+    h.ok(&mint_replay_ix(&s, pda(&[CHECKPOINT_SEED, s.session.as_ref()]), s.player1));
+    s
+}
+
+#[test]
+fn replay_verifies_by_re_running_the_frame_log() {
+    let mut h = Harness::new();
+    h.add_token_programs();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = minted_replay(&mut h, &fixture, &model);
+    let record = pda(&[REPLAY_SEED, s.session.as_ref()]);
+
+    // The run's memory must be a fresh account, not the session's own
+    let verifier = h.wallet();
+    h.fails_with(
+        &begin_verification_ix(&s, &model, s.hidden_state, verifier),
+        WorldModelError::HiddenStateInUse,
+    );
+    let scratch = h.allocate(vec![0; fixture.hidden_account_data().len()]);
+    h.ok(&begin_verification_ix(&s, &model, scratch, verifier));
+
+    h.fails_with(
+        &verify_frames_ix(&s, &model, scratch, verifier, 4),
+        WorldModelError::ReplayFrameOutOfRange,
+    );
+    h.ok(&verify_frames_ix(&s, &model, scratch, verifier, 2));
+    assert!(!h.anchor::<ReplayRecordAccount>(&record).verified);
+    h.ok(&verify_frames_ix(&s, &model, scratch, verifier, 1));
+    assert!(h.anchor::<ReplayRecordAccount>(&record).verified);
+}
+
+#[test]
+fn doctored_frame_log_fails_replay_verification() {
+    let mut h = Harness::new();
+    h.add_token_programs();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = minted_replay(&mut h, &fixture, &model);
+
+    // Player 1's frame-1 stick_x, as if they had pushed it further
+    h.accounts.get_mut(&s.input_buffer).unwrap().data[FRAME_LOG_OFFSET] = 99;
+    let verifier = h.wallet();
+    let scratch = h.allocate(vec![0; fixture.hidden_account_data().len()]);
+    h.ok(&begin_verification_ix(&s, &model, scratch, verifier));
+    h.fails_with(
+        &verify_frames_ix(&s, &model, scratch, verifier, 3),
+        WorldModelError::ReplayMismatch,
+    );
+}
+
+fn set_verifier_ix(model: &Model, authority: Pubkey, verifier: Pubkey) -> Instruction {
+    build(
+        accounts::SetSettlementVerifier {
+            manifest: model.manifest,
+            authority,
+        },
+        instruction::SetSettlementVerifier { verifier },
+    )
+}
+
+fn settle_ix(
+    s: &Session,
+    model: &Model,
+    verifier_program: Pubkey,
+    frame: u32,
+    players: [PlayerState; NUM_PLAYERS],
+) -> Instruction {
+    build(
+        accounts::SettleTransition {
+            session: s.session,
+            manifest: model.manifest,
+            verifier_program,
+            submitter: s.player1,
+        },
+        instruction::SettleTransition {
+            frame,
+            players,
+            commitment: [5; 32],
+            hidden_state_hash: [6; 32],
+            proof: vec![1, 2, 3],
+        },
+    )
+}
+
+#[test]
+fn settlement_takes_the_transition_its_verifier_accepts() {
+    let mut h = Harness::new();
+    h.load_program(archive::NOOP_PROGRAM_ID, "spl_noop");
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let authority = h.anchor::<ModelManifestAccount>(&model.manifest).authority;
+    let s = create_session(&mut h, &fixture, &model);
+    h.ok(&join_ix(&s, s.player2));
+    let before = h.session(&s.session);
+    let mut players = before.players;
+    players[0].percent += 12;
+
+    h.fails_with(
+        &settle_ix(&s, &model, archive::NOOP_PROGRAM_ID, 1, players),
+        WorldModelError::SettlementDisabled,
+    );
+
+    // The system program stands in for a verifier that rejects the proof
+    h.ok(&set_verifier_ix(&model, authority, system_program::ID));
+    let rejected = h.process(&settle_ix(&s, &model, system_program::ID, 1, players));
+    assert!(rejected.raw_result.is_err());
+    assert_eq!(h.session(&s.session).frame, before.frame);
+
+    h.ok(&set_verifier_ix(&model, authority, archive::NOOP_PROGRAM_ID));
+    h.fails_with(
+        &settle_ix(&s, &model, archive::NOOP_PROGRAM_ID, before.frame, players),
+        WorldModelError::StaleTransition,
+    );
+    h.fails_with(
+        &settle_ix(&s, &model, archive::NOOP_PROGRAM_ID, before.max_frames + 1, players),
+        WorldModelError::StaleTransition,
+    );
+
+    h.ok(&settle_ix(&s, &model, archive::NOOP_PROGRAM_ID, before.frame + 1, players));
+    let settled = h.session(&s.session);
+    assert_eq!(settled.frame, before.frame + 1);
+    assert_eq!(settled.players[0].percent, players[0].percent);
+    assert_eq!((settled.state_commitment, settled.hidden_state_hash), ([5; 32], [6; 32]));
+}
+
+fn create_wager_ix(s: &Session, amount: u64) -> Instruction {
+    build(
+        accounts::CreateWager {
+            session: s.session,
+            escrow: pda(&[WAGER_SEED, s.session.as_ref()]),
+            player1: s.player1,
+            system_program: system_program::ID,
+        },
+        instruction::CreateWager { amount },
+    )
+}
+
+fn accept_wager_ix(s: &Session, player2: Pubkey) -> Instruction {
+    build(
+        accounts::AcceptWager {
+            session: s.session,
+            escrow: pda(&[WAGER_SEED, s.session.as_ref()]),
+            player2,
+            system_program: system_program::ID,
+        },
+        instruction::AcceptWager {},
+    )
+}
+
+fn settle_wager_accounts(s: &Session) -> accounts::SettleWager {
+    accounts::SettleWager {
+        session: s.session,
+        escrow: pda(&[WAGER_SEED, s.session.as_ref()]),
+        player1: s.player1,
+        player2: s.player2,
+    }
+}
+
+fn settle_wager_ix(s: &Session) -> Instruction {
+    build(settle_wager_accounts(s), instruction::SettleWager {})
+}
+
+fn refund_wager_ix(s: &Session) -> Instruction {
+    build(settle_wager_accounts(s), instruction::RefundWager {})
+}
+
+fn tip_ix(s: &Session, tipper: Pubkey, player_slot: u8, amount: u64) -> Instruction {
+    build(
+        accounts::TipPlayer {
+            session: s.session,
+            stats: pda(&[SESSION_STATS_SEED, s.session.as_ref()]),
+            tipper,
+            system_program: system_program::ID,
+        },
+        instruction::TipPlayer {
+            player_slot,
+            amount,
+        },
+    )
+}
+
+fn claim_tips_ix(s: &Session, player: Pubkey, player_slot: u8) -> Instruction {
+    build(
+        accounts::ClaimTips {
+            session: s.session,
+            stats: pda(&[SESSION_STATS_SEED, s.session.as_ref()]),
+            player,
+        },
+        instruction::ClaimTips { player_slot },
+    )
+}
+
+/// init_rent_pool plus one funder's deposit of `amount`.
+/// SPL token accounts for Mollusk, which can't run the token program: the
+/// program accounts pass Anchor's checks, so SPL tests cover everything
+/// up to the token CPI.
+fn token_fixture(h: &mut Harness) -> Pubkey {
+    use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::Mint as MintState;
+
+    for (program, name) in [(token::ID, "spl_token"), (associated_token::ID, "spl_ata")] {
+        let (_, account) =
+            mollusk_svm::program::create_keyed_account_for_builtin_program(&svm_key(&program), name);
+        h.accounts.insert(program, account);
+    }
+    let mint = Pubkey::new_unique();
+    let mut data = vec![0; MintState::LEN];
+    MintState {
+        decimals: 6,
+        is_initialized: true,
+        ..MintState::default()
+    }
+    .pack_into_slice(&mut data);
+    h.accounts.insert(mint, token_program_account(h, data));
+    mint
+}
+
+fn token_account(h: &mut Harness, key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) {
+    use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account as TokenState, AccountState};
+
+    let mut data = vec![0; TokenState::LEN];
+    TokenState {
+        mint,
+        owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenState::default()
+    }
+    .pack_into_slice(&mut data);
+    let account = token_program_account(h, data);
+    h.accounts.insert(key, account);
+}
+
+fn token_program_account(h: &Harness, data: Vec<u8>) -> Account {
+    Account {
+        lamports: h.mollusk.sysvars.rent.minimum_balance(data.len()),
+        data,
+        owner: svm_key(&token::ID),
+        ..Account::default()
+    }
+}
+
+fn tip_token_ix(s: &Session, mint: Pubkey, tipper: Pubkey, player_slot: u8, amount: u64) -> Instruction {
+    let jar = pda(&[TIP_JAR_SEED, s.session.as_ref(), mint.as_ref()]);
+    build(
+        accounts::TipPlayerToken {
+            session: s.session,
+            jar,
+            mint,
+            vault: associated_token::get_associated_token_address(&jar, &mint),
+            tipper_token: associated_token::get_associated_token_address(&tipper, &mint),
+            tipper,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::TipPlayerToken { player_slot, amount },
+    )
+}
+
+fn claim_token_tips_ix(s: &Session, mint: Pubkey, player: Pubkey, player_slot: u8) -> Instruction {
+    let jar = pda(&[TIP_JAR_SEED, s.session.as_ref(), mint.as_ref()]);
+    build(
+        accounts::ClaimTokenTips {
+            session: s.session,
+            jar,
+            mint,
+            vault: associated_token::get_associated_token_address(&jar, &mint),
+            player_token: associated_token::get_associated_token_address(&player, &mint),
+            player,
+            token_program: token::ID,
+        },
+        instruction::ClaimTokenTips { player_slot },
+    )
+}
+
+#[test]
+fn token_tips_check_the_player_before_moving_tokens() {
+    use anchor_lang::AccountSerialize;
+
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+    let mint = token_fixture(&mut h);
+    let jar = pda(&[TIP_JAR_SEED, s.session.as_ref(), mint.as_ref()]);
+    let vault = associated_token::get_associated_token_address(&jar, &mint);
+    token_account(&mut h, vault, mint, jar, 0);
+    let tipper = h.wallet();
+    let tipper_token = associated_token::get_associated_token_address(&tipper, &mint);
+    token_account(&mut h, tipper_token, mint, tipper, 1_000);
+
+    // Player 2 hasn't joined yet
+    h.fails_with(&tip_token_ix(&s, mint, tipper, 1, 100), WorldModelError::InvalidPlayerSlot);
+    h.fails_with(&tip_token_ix(&s, mint, tipper, 2, 100), WorldModelError::InvalidPlayerSlot);
+    h.fails_with(&tip_token_ix(&s, mint, tipper, 0, 0), WorldModelError::InvalidAmount);
+
+    // A jar holding tips for player 1, as tip_player_token leaves it
+    let mut data = Vec::new();
+    TokenTipJarAccount {
+        session: s.session,
+        mint,
+        tips_total: [100, 0],
+        tips_claimable: [100, 0],
+        tip_count: [1, 0],
+        bump: Pubkey::find_program_address(
+            &[TIP_JAR_SEED, s.session.as_ref(), mint.as_ref()],
+            &world_model::ID,
+        )
+        .1,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    let lamports = h.mollusk.sysvars.rent.minimum_balance(data.len());
+    h.accounts.insert(
+        jar,
+        Account {
+            lamports,
+            data,
+            owner: svm_key(&world_model::ID),
+            ..Account::default()
+        },
+    );
+    h.ok(&join_ix(&s, s.player2));
+    for player in [s.player1, s.player2] {
+        let key = associated_token::get_associated_token_address(&player, &mint);
+        token_account(&mut h, key, mint, player, 0);
+    }
+
+    h.fails_with(&claim_token_tips_ix(&s, mint, s.player2, 0), WorldModelError::UnauthorizedPlayer);
+    h.fails_with(&claim_token_tips_ix(&s, mint, s.player2, 1), WorldModelError::NothingToClaim);
+    let other_mint = token_fixture(&mut h);
+    let mut ix = claim_token_tips_ix(&s, mint, s.player1, 0);
+    ix.accounts[2].pubkey = other_mint;
+    assert!(h.process(&ix).raw_result.is_err(), "jar is per mint");
+}
+
+fn fund_rent_pool(h: &mut Harness, amount: u64) {
+    let funder = h.wallet();
+    h.ok(&build(
+        accounts::InitRentPool {
+            rent_pool: pda(&[RENT_POOL_SEED]),
+            payer: funder,
+            system_program: system_program::ID,
+        },
+        instruction::InitRentPool {},
+    ));
+    h.ok(&build(
+        accounts::DepositRentPool {
+            rent_pool: pda(&[RENT_POOL_SEED]),
+            position: pda(&[RENT_FUNDER_SEED, funder.as_ref()]),
+            funder,
+            system_program: system_program::ID,
+        },
+        instruction::DepositRentPool { amount },
+    ));
+}
+
+fn borrow_rent_ix(s: &Session) -> Instruction {
+    build(
+        accounts::BorrowSessionRent {
+            session: s.session,
+            hidden_state: s.hidden_state,
+            input_buffer: s.input_buffer,
+            rent_pool: pda(&[RENT_POOL_SEED]),
+            loan: pda(&[RENT_LOAN_SEED, s.session.as_ref()]),
+            player1: s.player1,
+            system_program: system_program::ID,
+        },
+        instruction::BorrowSessionRent {},
+    )
+}
+
+fn repay_rent_ix(s: &Session) -> Instruction {
+    build(
+        accounts::RepaySessionRent {
+            session: s.session,
+            hidden_state: s.hidden_state,
+            input_buffer: s.input_buffer,
+            wager: pda(&[WAGER_SEED, s.session.as_ref()]),
+            stats: pda(&[SESSION_STATS_SEED, s.session.as_ref()]),
+            rent_pool: pda(&[RENT_POOL_SEED]),
+            loan: pda(&[RENT_LOAN_SEED, s.session.as_ref()]),
+            borrower: s.player1,
+        },
+        instruction::RepaySessionRent {},
+    )
+}
+
+#[test]
+fn rent_loan_covers_the_session_state_and_repays_into_the_pool() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    fund_rent_pool(&mut h, LAMPORTS / 2);
+    let s = create_session(&mut h, &fixture, &model);
+
+    let before = h.accounts[&s.player1].lamports;
+    h.ok(&borrow_rent_ix(&s));
+    let rent = &h.mollusk.sysvars.rent;
+    let expected = rent.minimum_balance(h.data(&s.hidden_state).len())
+        + rent.minimum_balance(h.data(&s.input_buffer).len());
+    let loan: RentLoanAccount = h.anchor(&pda(&[RENT_LOAN_SEED, s.session.as_ref()]));
+    assert_eq!((loan.borrower, loan.amount), (s.player1, expected));
+    let loan_rent = h.accounts[&pda(&[RENT_LOAN_SEED, s.session.as_ref()])].lamports;
+    assert_eq!(h.accounts[&s.player1].lamports, before + expected - loan_rent);
+    let pool: RentPoolAccount = h.anchor(&pda(&[RENT_POOL_SEED]));
+    assert_eq!(pool.total_outstanding, expected);
+
+    // Nothing to repay until the session ends
+    h.fails_with(&repay_rent_ix(&s), WorldModelError::SessionNotEnded);
+    h.ok(&join_ix(&s, s.player2));
+    h.ok(&close_ix(&s, s.player1));
+    h.ok(&repay_rent_ix(&s));
+
+    // The loan-backed accounts are closed; the session record stays
+    for key in [s.hidden_state, s.input_buffer] {
+        assert_eq!(h.accounts[&key].lamports, 0);
+        assert!(h.data(&key).is_empty());
+    }
+    assert_eq!(h.session(&s.session).status, STATUS_ENDED);
+    let pool: RentPoolAccount = h.anchor(&pda(&[RENT_POOL_SEED]));
+    assert_eq!(pool.total_outstanding, 0);
+    assert_eq!(
+        h.accounts[&pda(&[RENT_LOAN_SEED, s.session.as_ref()])].lamports,
+        0
+    );
+}
+
+#[test]
+fn rent_repay_waits_for_the_wager_and_tips() {
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    fund_rent_pool(&mut h, LAMPORTS / 2);
+    let s = create_session(&mut h, &fixture, &model);
+    h.ok(&borrow_rent_ix(&s));
+    h.ok(&create_wager_ix(&s, 1_000_000));
+    h.ok(&join_ix(&s, s.player2));
+    let fan = h.wallet();
+    h.ok(&tip_ix(&s, fan, 1, 5_000));
+    h.ok(&close_ix(&s, s.player1));
+
+    // The wager and tips still read the session
+    h.fails_with(&repay_rent_ix(&s), WorldModelError::WagerUnsettled);
+    h.ok(&settle_wager_ix(&s));
+    h.fails_with(&repay_rent_ix(&s), WorldModelError::TipsUnclaimed);
+    h.ok(&claim_tips_ix(&s, s.player2, 1));
+    h.ok(&repay_rent_ix(&s));
+}
+
+/// Lamports of `keys`, to diff payouts against.
+fn balances<const N: usize>(h: &Harness, keys: [Pubkey; N]) -> [u64; N] {
+    keys.map(|k| h.accounts[&k].lamports)
+}
+
+#[test]
+fn wager_pays_the_winner_and_walking_out_forfeits() {
+    const STAKE: u64 = 1_000_000;
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+    let s = create_session(&mut h, &fixture, &model);
+    let escrow = pda(&[WAGER_SEED, s.session.as_ref()]);
+
+    h.fails_with(&create_wager_ix(&s, 0), WorldModelError::InvalidAmount);
+    h.ok(&create_wager_ix(&s, STAKE));
+    h.ok(&join_ix(&s, s.player2));
+    let stranger = h.wallet();
+    h.fails_with(&accept_wager_ix(&s, stranger), WorldModelError::UnauthorizedPlayer);
+    h.ok(&accept_wager_ix(&s, s.player2));
+    h.fails_with(&accept_wager_ix(&s, s.player2), WorldModelError::WagerAlreadyAccepted);
+    let w: WagerEscrowAccount = h.anchor(&escrow);
+    assert!(w.p1_deposited && w.p2_deposited);
+
+    // Level after a frame: a draw on the standings...
+    h.ok(&submit_ix(&s, s.player1, 10, 0));
+    h.ok(&submit_ix(&s, s.player2, -10, 0));
+    h.ok(&inference_ix(&h, &s, &model, cranker));
+    h.fails_with(&settle_wager_ix(&s), WorldModelError::SessionNotEnded);
+
+    // ...but closing the match before it finishes concedes it
+    h.ok(&close_ix(&s, s.player2));
+    let state = h.session(&s.session);
+    assert_eq!(state.forfeited_by, 2);
+    assert_eq!(state.outcome(), SessionOutcome::Winner(0));
+    h.fails_with(&refund_wager_ix(&s), WorldModelError::InvalidStateTransition);
+
+    let escrow_lamports = h.accounts[&escrow].lamports;
+    let before = balances(&h, [s.player1, s.player2]);
+    h.ok(&settle_wager_ix(&s));
+    assert_eq!(
+        balances(&h, [s.player1, s.player2]),
+        [before[0] + escrow_lamports, before[1]]
+    );
+    let escrow_size = 8 + core::mem::size_of::<WagerEscrowAccount>();
+    assert_eq!(
+        escrow_lamports - 2 * STAKE,
+        h.mollusk.sysvars.rent.minimum_balance(escrow_size)
+    );
+}
+
+#[test]
+fn wager_refunds_unplayed_and_stalled_sessions() {
+    const STAKE: u64 = 1_000_000;
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let cranker = h.wallet();
+
+    // Closed before a frame ran: nobody forfeits, both stakes come back
+    let s = create_session(&mut h, &fixture, &model);
+    h.ok(&create_wager_ix(&s, STAKE));
+    h.ok(&join_ix(&s, s.player2));
+    h.ok(&accept_wager_ix(&s, s.player2));
+    h.ok(&close_ix(&s, s.player1));
+    assert_eq!(h.session(&s.session).forfeited_by, 0);
+    let before = balances(&h, [s.player1, s.player2]);
+    let escrow_rent = h.accounts[&pda(&[WAGER_SEED, s.session.as_ref()])].lamports - 2 * STAKE;
+    h.ok(&settle_wager_ix(&s));
+    assert_eq!(
+        balances(&h, [s.player1, s.player2]),
+        [before[0] + STAKE + escrow_rent, before[1] + STAKE]
+    );
+
+    // A match nobody ends refunds once the wager times out
+    let s = create_session(&mut h, &fixture, &model);
+    h.ok(&create_wager_ix(&s, STAKE));
+    h.ok(&join_ix(&s, s.player2));
+    h.ok(&accept_wager_ix(&s, s.player2));
+    h.ok(&submit_ix(&s, s.player1, 10, 0));
+    h.ok(&submit_ix(&s, s.player2, -10, 0));
+    h.ok(&inference_ix(&h, &s, &model, cranker));
+    h.fails_with(&refund_wager_ix(&s), WorldModelError::WagerTimeoutNotReached);
+    h.mollusk.sysvars.clock.unix_timestamp += WAGER_TIMEOUT_SECS;
+    let before = balances(&h, [s.player1, s.player2]);
+    h.ok(&refund_wager_ix(&s));
+    let after = balances(&h, [s.player1, s.player2]);
+    assert_eq!(after[1], before[1] + STAKE);
+    assert!(after[0] > before[0] + STAKE, "stake plus the escrow's rent");
+}
+
+fn create_tournament_ix(organizer: Pubkey, max_entrants: u8, entry_fee: u64) -> (Pubkey, Instruction) {
+    let tournament = pda(&[TOURNAMENT_SEED, organizer.as_ref(), &1u64.to_le_bytes()]);
+    let ix = build(
+        accounts::CreateTournament {
+            tournament,
+            organizer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateTournament {
+            id: 1,
+            max_entrants,
+            entry_fee,
+            payout_bps: [7000, 3000, 0],
+        },
+    );
+    (tournament, ix)
+}
+
+fn join_tournament_ix(tournament: Pubkey, entrant: Pubkey) -> Instruction {
+    build(
+        accounts::JoinTournament {
+            tournament,
+            entrant,
+            system_program: system_program::ID,
+        },
+        instruction::JoinTournament {},
+    )
+}
+
+fn start_tournament_ix(tournament: Pubkey, organizer: Pubkey) -> Instruction {
+    build(
+        accounts::StartTournament { tournament, organizer },
+        instruction::StartTournament {},
+    )
+}
+
+fn schedule_match_ix(tournament: Pubkey, organizer: Pubkey, session: Pubkey, node: u8) -> Instruction {
+    build(
+        accounts::ScheduleMatch { tournament, organizer, session },
+        instruction::ScheduleMatch { node },
+    )
+}
+
+fn record_match_ix(tournament: Pubkey, session: Pubkey, node: u8) -> Instruction {
+    build(
+        accounts::RecordMatch { tournament, session },
+        instruction::RecordMatch { node },
+    )
+}
+
+fn distribute_prizes_ix(tournament: Pubkey, organizer: Pubkey, first: Pubkey, second: Pubkey) -> Instruction {
+    build(
+        accounts::DistributePrizes {
+            tournament,
+            organizer,
+            first,
+            second,
+            third_a: organizer,
+            third_b: organizer,
+        },
+        instruction::DistributePrizes {},
+    )
+}
+
+fn cancel_tournament_ix(tournament: Pubkey, organizer: Pubkey, entrants: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        accounts::CancelTournament { tournament, organizer },
+        instruction::CancelTournament {},
+    );
+    ix.accounts.extend(entrants.iter().map(|&e| AccountMeta::new(e, false)));
+    ix
+}
+
+/// A session between two entrants, one frame in, that `loser` walks out of.
+fn bracket_match(
+    h: &mut Harness,
+    fixture: &SyntheticModel,
+    model: &Model,
+    players: [Pubkey; 2],
+    loser: Pubkey,
+) -> Session {
+    let cranker = h.wallet();
+    let mut s = create_session_by(h, fixture, model, players[0]);
+    s.player2 = players[1];
+    h.ok(&join_ix(&s, s.player2));
+    h.ok(&submit_ix(&s, s.player1, 10, 0));
+    h.ok(&submit_ix(&s, s.player2, -10, 0));
+    h.ok(&inference_ix(h, &s, model, cranker));
+    h.ok(&close_ix(&s, loser));
+    s
+}
+
+#[test]
+fn tournament_pays_out_the_scheduled_bracket() {
+    const FEE: u64 = 1_000_000;
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let organizer = h.wallet();
+    let (tournament, ix) = create_tournament_ix(organizer, 2, FEE);
+    h.ok(&ix);
+
+    let [a, b] = [h.wallet(), h.wallet()];
+    h.fails_with(&start_tournament_ix(tournament, organizer), WorldModelError::RegistrationClosed);
+    h.ok(&join_tournament_ix(tournament, a));
+    h.fails_with(&join_tournament_ix(tournament, a), WorldModelError::RegistrationClosed);
+    h.ok(&join_tournament_ix(tournament, b));
+    let late = h.wallet();
+    h.fails_with(&join_tournament_ix(tournament, late), WorldModelError::RegistrationClosed);
+    h.fails_with(&start_tournament_ix(tournament, a), WorldModelError::Unauthorized);
+    h.ok(&start_tournament_ix(tournament, organizer));
+    let t: TournamentAccount = h.anchor(&tournament);
+    assert_eq!(t.bracket[1..3], [a, b]);
+
+    // The bracket only counts the session the organizer scheduled
+    let decoy = bracket_match(&mut h, &fixture, &model, [a, b], b);
+    let s = bracket_match(&mut h, &fixture, &model, [a, b], a);
+    h.fails_with(&record_match_ix(tournament, s.session, 0), WorldModelError::SessionNotBracketMatch);
+    h.fails_with(&schedule_match_ix(tournament, a, decoy.session, 0), WorldModelError::Unauthorized);
+    let outsider = bracket_match(&mut h, &fixture, &model, [a, late], late);
+    h.fails_with(
+        &schedule_match_ix(tournament, organizer, outsider.session, 0),
+        WorldModelError::SessionNotBracketMatch,
+    );
+    h.ok(&schedule_match_ix(tournament, organizer, s.session, 0));
+    h.fails_with(&record_match_ix(tournament, decoy.session, 0), WorldModelError::SessionNotBracketMatch);
+    h.fails_with(&distribute_prizes_ix(tournament, organizer, b, a), WorldModelError::TournamentIncomplete);
+    h.ok(&record_match_ix(tournament, s.session, 0));
+    h.fails_with(&record_match_ix(tournament, s.session, 0), WorldModelError::TournamentNotRunning);
+    h.fails_with(&cancel_tournament_ix(tournament, organizer, &[a, b]), WorldModelError::TournamentNotRunning);
+
+    h.fails_with(&distribute_prizes_ix(tournament, organizer, a, b), WorldModelError::PayoutMismatch);
+    let pot = h.accounts[&tournament].lamports - h.mollusk.sysvars.rent.minimum_balance(h.data(&tournament).len());
+    assert_eq!(pot, 2 * FEE);
+    let before = balances(&h, [a, b]);
+    h.ok(&distribute_prizes_ix(tournament, organizer, b, a));
+    assert_eq!(balances(&h, [a, b]), [before[0] + pot * 3 / 10, before[1] + pot * 7 / 10]);
+    assert_eq!(h.accounts[&tournament].lamports, 0);
+}
+
+#[test]
+fn cancelled_tournament_refunds_the_entrants() {
+    const FEE: u64 = 1_000_000;
+    let mut h = Harness::new();
+    let organizer = h.wallet();
+    let (tournament, ix) = create_tournament_ix(organizer, 4, FEE);
+    h.ok(&ix);
+    let entrants = [h.wallet(), h.wallet(), h.wallet()];
+    for &e in &entrants {
+        h.ok(&join_tournament_ix(tournament, e));
+    }
+
+    h.fails_with(&cancel_tournament_ix(tournament, entrants[0], &entrants), WorldModelError::Unauthorized);
+    h.fails_with(
+        &cancel_tournament_ix(tournament, organizer, &entrants[..2]),
+        WorldModelError::RefundMismatch,
+    );
+    h.fails_with(
+        &cancel_tournament_ix(tournament, organizer, &[entrants[1], entrants[0], entrants[2]]),
+        WorldModelError::RefundMismatch,
+    );
+
+    let before = balances(&h, entrants);
+    h.ok(&cancel_tournament_ix(tournament, organizer, &entrants));
+    assert_eq!(balances(&h, entrants), before.map(|b| b + FEE));
+    assert_eq!(h.accounts[&tournament].lamports, 0);
+}
+
+#[test]
+fn cpi_interface_matches_the_program() {
+    use anchor_lang::Discriminator;
+
+    assert_eq!(awm_cpi::WORLD_MODEL_ID, world_model::ID);
+    for (cpi, program) in [
+        (awm_cpi::instruction::GET_SESSION_SUMMARY, instruction::GetSessionSummary::DISCRIMINATOR),
+        (awm_cpi::instruction::GET_RESULT, instruction::GetResult::DISCRIMINATOR),
+        (awm_cpi::instruction::GET_FRAME, instruction::GetFrame::DISCRIMINATOR),
+    ] {
+        assert_eq!(&cpi[..], program);
+    }
+}
+
+#[test]
+fn view_instructions_return_session_state() {
+    use anchor_lang::AnchorDeserialize;
+
+    let mut h = Harness::new();
+    let fixture = SyntheticModel::tiny();
+    let model = ready_model(&mut h, &fixture);
+    let s = create_session(&mut h, &fixture, &model);
+    assert_eq!(awm_cpi::WORLD_MODEL_ID, world_model::ID);
+
+    fn view(h: &mut Harness, ix: Instruction) -> Vec<u8> {
+        let result = h.process(&ix);
+        assert!(result.raw_result.is_ok(), "{:?}", result.raw_result);
+        result.return_data
+    }
+
+    let data = view(&mut h, awm_cpi::instruction::get_session_summary(s.session));
+    let summary = awm_cpi::SessionSummary::try_from_slice(&data).unwrap();
+    assert_eq!(summary.status, awm_cpi::STATUS_WAITING_PLAYERS);
+    assert_eq!((summary.model, summary.player1), (model.manifest, s.player1));
+    assert_eq!(summary.max_frames, 3600);
+    assert_eq!(summary.tick_rate, TICK_RATE_60);
+
+    h.ok(&join_ix(&s, s.player2));
+    let data = view(&mut h, awm_cpi::instruction::get_frame(s.session));
+    let frame = awm_cpi::FrameView::try_from_slice(&data).unwrap();
+    let state = h.session(&s.session);
+    assert_eq!(frame.frame, state.frame);
+    assert_eq!(frame.players[1].x, state.players[1].x);
+    assert_eq!(frame.players[1].character, 9);
+
+    h.ok(&close_ix(&s, s.player1));
+    let data = view(&mut h, awm_cpi::instruction::get_result(s.session));
+    let result = awm_cpi::MatchResult::try_from_slice(&data).unwrap();
+    assert!(result.ended);
+    // No frame ran
+    assert_eq!(result.outcome, awm_cpi::OUTCOME_UNPLAYED);
+    assert_eq!(
+        result.final_state_hash,
+        h.session(&s.session).final_state_hash().unwrap()
+    );
+}
@@ -0,0 +1,193 @@
+/// Native kernel benchmarks — what a validator pays per sol_matmul_i8 call
+/// and per layer, at production dims (d_model=512, d_inner=1024, d_state=16).
+///
+/// Run: `cargo bench -p awm-syscall` (reports land in target/criterion/).
+/// Compare against a saved baseline with `-- --save-baseline main` and
+/// `-- --baseline main`.
+use awm_kernel::mamba2::{
+    mamba2_layer_step, HiddenStateMut, LayerKind, LayerWeights, Mamba2Config, ScratchBuffers,
+};
+use awm_kernel::matmul::Rounding;
+use awm_kernel::ssm::{selective_scan_step, selective_scan_step_i16};
+use awm_syscall::matmul::{matmul_i8, matmul_i8_simd};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const D_MODEL: usize = 512;
+const D_INNER: usize = 1024;
+const D_STATE: usize = 16;
+
+/// Deterministic pseudo-random bytes (xorshift32), so runs are comparable.
+fn bytes(len: usize, seed: u32) -> Vec<u8> {
+    let mut s = seed.max(1);
+    (0..len)
+        .map(|_| {
+            s ^= s << 13;
+            s ^= s >> 17;
+            s ^= s << 5;
+            s as u8
+        })
+        .collect()
+}
+
+fn as_i8(v: &[u8]) -> Vec<i8> {
+    v.iter().map(|&b| b as i8).collect()
+}
+
+/// in_proj and out_proj shapes of one layer.
+fn matmul_shapes() -> [(&'static str, usize, usize); 2] {
+    [
+        ("in_proj", 2 * D_INNER, D_MODEL),
+        ("out_proj", D_MODEL, D_INNER),
+    ]
+}
+
+fn bench_matmul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("matmul_i8");
+    for (name, rows, cols) in matmul_shapes() {
+        let w_bytes = bytes(rows * cols, 1);
+        let weights = as_i8(&w_bytes);
+        let input = as_i8(&bytes(cols, 2));
+        let mut output = vec![0i32; rows];
+        group.throughput(Throughput::Elements((rows * cols) as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("scalar", name),
+            &(rows, cols),
+            |b, &(r, c)| {
+                b.iter(|| matmul_i8(black_box(&weights), black_box(&input), &mut output, r, c))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("packed", name),
+            &(rows, cols),
+            |b, &(r, c)| {
+                b.iter(|| {
+                    awm_kernel::matmul::matmul_i8(
+                        black_box(&w_bytes),
+                        black_box(&input),
+                        &mut output,
+                        r,
+                        c,
+                    )
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("simd", name),
+            &(rows, cols),
+            |b, &(r, c)| {
+                b.iter(|| matmul_i8_simd(black_box(&weights), black_box(&input), &mut output, r, c))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_scan(c: &mut Criterion) {
+    let x_ssm = as_i8(&bytes(D_INNER, 3));
+    let dt = as_i8(&bytes(D_INNER, 4));
+    let a_log = bytes(D_INNER, 5);
+    let luts = bytes(1024, 6);
+    let mut y_ssm = vec![0i8; D_INNER];
+
+    let mut group = c.benchmark_group("selective_scan_step");
+    group.throughput(Throughput::Elements((D_INNER * D_STATE) as u64));
+
+    let mut h8 = as_i8(&bytes(D_INNER * D_STATE, 7));
+    group.bench_function("i8", |b| {
+        b.iter(|| {
+            selective_scan_step(
+                black_box(&x_ssm),
+                black_box(&dt),
+                &mut h8,
+                &a_log,
+                None,
+                &luts,
+                &mut y_ssm,
+                D_INNER,
+                D_STATE,
+            )
+        })
+    });
+
+    let mut h16 = vec![0i16; D_INNER * D_STATE];
+    group.bench_function("i16", |b| {
+        b.iter(|| {
+            selective_scan_step_i16(
+                black_box(&x_ssm),
+                black_box(&dt),
+                &mut h16,
+                &a_log,
+                None,
+                &luts,
+                &mut y_ssm,
+                D_INNER,
+                D_STATE,
+            )
+        })
+    });
+    group.finish();
+}
+
+fn bench_layer(c: &mut Criterion) {
+    let config = Mamba2Config {
+        d_model: D_MODEL,
+        d_inner: D_INNER,
+        d_state: D_STATE,
+        num_layers: 1,
+        num_heads: 1,
+        ngroups: 0,
+        dt_shift: 0,
+        rounding: Rounding::Truncate,
+    };
+    let in_proj = bytes(2 * D_INNER * D_MODEL, 8);
+    let out_proj = bytes(D_MODEL * D_INNER, 9);
+    let norm = bytes(D_MODEL, 10);
+    let a_log = bytes(D_INNER, 11);
+    let dt_bias = bytes(D_INNER, 12);
+    let in_proj_scales = vec![256u16; 2 * D_INNER];
+    let out_proj_scales = vec![256u16; D_MODEL];
+    let weights = LayerWeights {
+        in_proj: &in_proj,
+        out_proj: &out_proj,
+        in_proj_mask: &[],
+        out_proj_mask: &[],
+        norm: &norm,
+        a_log: &a_log,
+        dt_bias: &dt_bias,
+        dt_proj: &[],
+        dt_proj_scales: &[],
+        in_proj_scales: &in_proj_scales,
+        out_proj_scales: &out_proj_scales,
+        moe: None,
+        kind: LayerKind::Ssm,
+    };
+    let luts = bytes(1024, 13);
+    let input = as_i8(&bytes(D_MODEL, 14));
+
+    let mut i8s = vec![0i8; ScratchBuffers::i8_len(D_MODEL, D_INNER)];
+    let mut i32s = vec![0i32; ScratchBuffers::i32_len(D_MODEL, D_INNER)];
+    let mut scratch = ScratchBuffers::from_slices(&mut i8s, &mut i32s, D_MODEL, D_INNER).unwrap();
+    let mut h = vec![0i8; D_INNER * D_STATE];
+    let mut x = input.clone();
+
+    let mut group = c.benchmark_group("mamba2_layer_step");
+    group.bench_function("i8_hidden", |b| {
+        b.iter(|| {
+            // Reset the residual stream so every iteration sees the same input
+            x.copy_from_slice(&input);
+            mamba2_layer_step(
+                black_box(&mut x),
+                HiddenStateMut::I8(&mut h),
+                &weights,
+                &luts,
+                &config,
+                &mut scratch,
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_matmul, bench_scan, bench_layer);
+criterion_main!(benches);
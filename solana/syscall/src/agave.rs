@@ -0,0 +1,48 @@
+//! Agave registration: `SyscallMatmulI8` for
+//! `register_function("sol_matmul_i8", SyscallMatmulI8::vm)`.
+
+use solana_program_runtime::{
+    invoke_context::InvokeContext,
+    solana_sbpf::{
+        declare_builtin_function,
+        memory_region::{AccessType, MemoryMapping},
+    },
+};
+
+use crate::host::{matmul_i8_syscall, Access, SyscallHost};
+
+/// InvokeContext metering and MemoryMapping translation.
+impl SyscallHost for (&mut InvokeContext, &MemoryMapping) {
+    type Error = Box<dyn std::error::Error>;
+
+    fn consume(&mut self, cu: u64) -> Result<(), Self::Error> {
+        self.0.consume_checked(cu)
+    }
+
+    /// Converts StableResult -> Result for use with `?`.
+    fn translate(&mut self, access: Access, addr: u64, len: u64) -> Result<u64, Self::Error> {
+        let access = match access {
+            Access::Load => AccessType::Load,
+            Access::Store => AccessType::Store,
+        };
+        Result::from(self.1.map(access, addr, len)).map_err(|e| format!("{e:?}").into())
+    }
+}
+
+declare_builtin_function!(
+    /// Native INT8 matrix-vector multiply for MagicBlock ephemeral rollups.
+    /// See `host::matmul_i8_syscall` for the register mapping.
+    SyscallMatmulI8,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        weights_addr: u64,
+        input_addr: u64,
+        output_addr: u64,
+        rows: u64,
+        cols: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut host = (invoke_context, &*memory_mapping);
+        matmul_i8_syscall(&mut host, weights_addr, input_addr, output_addr, rows, cols)
+    }
+);
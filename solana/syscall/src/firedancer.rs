@@ -0,0 +1,97 @@
+//! Firedancer registration: a C-ABI entry point for an fd_vm syscall shim.
+//!
+//! fd_vm syscalls are C functions over `fd_vm_t`, whose metering and
+//! address translation are macros, so they can't be called from Rust
+//! directly. The shim registered as `sol_matmul_i8` fills in an
+//! `FdVmHost` with its vm pointer and two small callbacks wrapping
+//! `FD_VM_CU_UPDATE` and `FD_VM_MEM_HADDR_LD` / `_ST`, then calls
+//! `awm_matmul_i8_fd` with the argument registers.
+
+use core::ffi::c_void;
+
+use crate::host::{matmul_i8_syscall, Access, SyscallError, SyscallHost};
+
+/// Returned for failures the core detects; callbacks' own nonzero codes
+/// are passed through unchanged.
+pub const AWM_ERR_DIMENSIONS: i32 = 1;
+
+/// Callbacks into the Firedancer VM. Both return 0 on success or an fd_vm
+/// error code.
+#[repr(C)]
+pub struct FdVmHost {
+    /// The shim's `fd_vm_t *`, passed back to each callback
+    pub vm: *mut c_void,
+    /// Charge `cu` compute units
+    pub consume_cu: unsafe extern "C" fn(vm: *mut c_void, cu: u64) -> i32,
+    /// Write the host address of `len` bytes at `vaddr` to `haddr`;
+    /// `store` is nonzero for a writable region
+    pub haddr: unsafe extern "C" fn(
+        vm: *mut c_void,
+        vaddr: u64,
+        len: u64,
+        store: i32,
+        haddr: *mut u64,
+    ) -> i32,
+}
+
+/// The shim's callbacks as a SyscallHost.
+struct FdHost<'a>(&'a FdVmHost);
+
+/// A nonzero fd_vm (or AWM_ERR_*) code.
+struct FdError(i32);
+
+impl From<SyscallError> for FdError {
+    fn from(e: SyscallError) -> Self {
+        match e {
+            SyscallError::DimensionsOverflow => FdError(AWM_ERR_DIMENSIONS),
+        }
+    }
+}
+
+impl SyscallHost for FdHost<'_> {
+    type Error = FdError;
+
+    fn consume(&mut self, cu: u64) -> Result<(), FdError> {
+        // SAFETY: the shim passes callbacks valid for its vm.
+        match unsafe { (self.0.consume_cu)(self.0.vm, cu) } {
+            0 => Ok(()),
+            err => Err(FdError(err)),
+        }
+    }
+
+    fn translate(&mut self, access: Access, addr: u64, len: u64) -> Result<u64, FdError> {
+        let mut haddr = 0u64;
+        let store = (access == Access::Store) as i32;
+        // SAFETY: as above; `haddr` is a valid out-pointer.
+        match unsafe { (self.0.haddr)(self.0.vm, addr, len, store, &mut haddr) } {
+            0 => Ok(haddr),
+            err => Err(FdError(err)),
+        }
+    }
+}
+
+/// sol_matmul_i8 for Firedancer. Writes the syscall's r0 to `ret` and
+/// returns 0, or returns the first error.
+///
+/// # Safety
+/// `host` must point to an `FdVmHost` whose callbacks are valid for its
+/// vm, and `ret` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn awm_matmul_i8_fd(
+    host: *const FdVmHost,
+    weights_addr: u64,
+    input_addr: u64,
+    output_addr: u64,
+    rows: u64,
+    cols: u64,
+    ret: *mut u64,
+) -> i32 {
+    let mut host = FdHost(&*host);
+    match matmul_i8_syscall(&mut host, weights_addr, input_addr, output_addr, rows, cols) {
+        Ok(r0) => {
+            *ret = r0;
+            0
+        }
+        Err(FdError(err)) => err,
+    }
+}
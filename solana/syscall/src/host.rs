@@ -0,0 +1,104 @@
+//! Runtime-agnostic core of sol_matmul_i8.
+//!
+//! A validator runtime registers the syscall through a thin adapter that
+//! implements `SyscallHost` — compute metering and VM address translation —
+//! and forwards the five argument registers to `matmul_i8_syscall`. CU
+//! pricing, bounds and the math live here once, so every runtime charges
+//! and computes the same thing:
+//!   - `agave` (default): `SyscallMatmulI8`, a `declare_builtin_function`
+//!     over InvokeContext and MemoryMapping
+//!   - `firedancer`: `awm_matmul_i8_fd`, a C-ABI entry point for an
+//!     fd_vm syscall shim
+
+use crate::matmul;
+
+/// CU cost: base + 1 per MAC. Tunable by MagicBlock.
+pub const CU_BASE: u64 = 100;
+pub const CU_PER_MAC: u64 = 1;
+
+/// How the syscall touches a VM region.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Access {
+    Load,
+    Store,
+}
+
+/// Failures the core itself detects (runtimes surface their own metering
+/// and translation errors through `SyscallHost::Error`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SyscallError {
+    /// rows × cols (or a region length) overflows u64
+    DimensionsOverflow,
+}
+
+impl core::fmt::Display for SyscallError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SyscallError::DimensionsOverflow => f.write_str("matmul dimensions overflow"),
+        }
+    }
+}
+
+impl std::error::Error for SyscallError {}
+
+/// What a validator runtime provides to the syscall.
+pub trait SyscallHost {
+    type Error: From<SyscallError>;
+
+    /// Charge `cu` compute units, failing once the budget is exhausted.
+    fn consume(&mut self, cu: u64) -> Result<(), Self::Error>;
+
+    /// Host address of `len` bytes at VM address `addr`, checked for
+    /// `access`.
+    fn translate(&mut self, access: Access, addr: u64, len: u64) -> Result<u64, Self::Error>;
+}
+
+/// Compute units sol_matmul_i8 charges for a rows × cols product.
+pub fn matmul_cost(rows: u64, cols: u64) -> Result<u64, SyscallError> {
+    let macs = rows
+        .checked_mul(cols)
+        .ok_or(SyscallError::DimensionsOverflow)?;
+    Ok(CU_BASE.saturating_add(macs.saturating_mul(CU_PER_MAC)))
+}
+
+/// sol_matmul_i8 over any runtime. Register mapping (standard 5-register
+/// syscall convention):
+///   r1 (weights_addr): VM pointer to row-major i8 weight matrix [rows * cols]
+///   r2 (input_addr):   VM pointer to i8 input vector [cols]
+///   r3 (output_addr):  VM pointer to caller-allocated i32 output buffer [rows]
+///   r4 (rows):         Number of rows in weight matrix
+///   r5 (cols):         Number of columns in weight matrix
+pub fn matmul_i8_syscall<H: SyscallHost>(
+    host: &mut H,
+    weights_addr: u64,
+    input_addr: u64,
+    output_addr: u64,
+    rows: u64,
+    cols: u64,
+) -> Result<u64, H::Error> {
+    // Charge CU proportional to work before touching memory
+    host.consume(matmul_cost(rows, cols)?)?;
+
+    let rows_usize = rows as usize;
+    let cols_usize = cols as usize;
+    let weights_len = rows * cols; // checked by matmul_cost
+    let output_len = rows
+        .checked_mul(4) // i32 = 4 bytes
+        .ok_or(SyscallError::DimensionsOverflow)?;
+
+    let weights_host = host.translate(Access::Load, weights_addr, weights_len)?;
+    let input_host = host.translate(Access::Load, input_addr, cols)?;
+    let output_host = host.translate(Access::Store, output_addr, output_len)?;
+
+    // SAFETY: the host validated these regions are accessible and within
+    // VM memory bounds for the lengths above.
+    let weights = unsafe {
+        std::slice::from_raw_parts(weights_host as *const i8, rows_usize * cols_usize)
+    };
+    let input = unsafe { std::slice::from_raw_parts(input_host as *const i8, cols_usize) };
+    let output = unsafe { std::slice::from_raw_parts_mut(output_host as *mut i32, rows_usize) };
+
+    matmul::matmul_i8(weights, input, output, rows_usize, cols_usize);
+
+    Ok(0)
+}
@@ -22,3 +22,72 @@ pub fn matmul_i8(
         output[i] = acc;
     }
 }
+
+/// Same product as `matmul_i8`, with explicit SIMD where the host has it.
+///
+/// On x86_64 with AVX2 each row is reduced 16 columns at a time: sign-extend
+/// to i16, `madd` adjacent pairs into eight i32 lanes, then sum the lanes.
+/// Elsewhere it falls back to `matmul_i8` and relies on auto-vectorization
+/// (NEON on aarch64). Results are identical to `matmul_i8`.
+pub fn matmul_i8_simd(
+    weights: &[i8],
+    input: &[i8],
+    output: &mut [i32],
+    rows: usize,
+    cols: usize,
+) {
+    assert!(weights.len() >= rows * cols);
+    assert!(input.len() >= cols);
+    assert!(output.len() >= rows);
+
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 availability checked above; bounds checked by the asserts.
+        unsafe { matmul_i8_avx2(weights, input, output, rows, cols) };
+        return;
+    }
+
+    matmul_i8(weights, input, output, rows, cols);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn matmul_i8_avx2(
+    weights: &[i8],
+    input: &[i8],
+    output: &mut [i32],
+    rows: usize,
+    cols: usize,
+) {
+    use std::arch::x86_64::*;
+
+    let chunks = cols / 16;
+    let x_ptr = input.as_ptr();
+
+    for (i, out) in output.iter_mut().enumerate().take(rows) {
+        let row = &weights[i * cols..(i + 1) * cols];
+        let w_ptr = row.as_ptr();
+
+        // i8 x i8 products fit in i16 pairs summed to i32 without overflow
+        let mut acc = _mm256_setzero_si256();
+        for c in 0..chunks {
+            let w = _mm_loadu_si128(w_ptr.add(c * 16) as *const __m128i);
+            let x = _mm_loadu_si128(x_ptr.add(c * 16) as *const __m128i);
+            let prod = _mm256_madd_epi16(_mm256_cvtepi8_epi16(w), _mm256_cvtepi8_epi16(x));
+            acc = _mm256_add_epi32(acc, prod);
+        }
+
+        // Horizontal sum of the eight i32 lanes
+        let lo = _mm256_castsi256_si128(acc);
+        let s = _mm_add_epi32(lo, _mm256_extracti128_si256(acc, 1));
+        let s = _mm_add_epi32(s, _mm_shuffle_epi32(s, 0b01_00_11_10));
+        let s = _mm_add_epi32(s, _mm_shuffle_epi32(s, 0b10_11_00_01));
+        let tail = chunks * 16;
+        *out = _mm_cvtsi128_si32(s)
+            + row[tail..]
+                .iter()
+                .zip(&input[tail..cols])
+                .map(|(&w, &x)| w as i32 * x as i32)
+                .sum::<i32>();
+    }
+}
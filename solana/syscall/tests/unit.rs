@@ -1,4 +1,7 @@
-use awm_syscall::matmul::matmul_i8;
+use awm_syscall::host::{
+    matmul_cost, matmul_i8_syscall, Access, SyscallError, SyscallHost, CU_BASE,
+};
+use awm_syscall::matmul::{matmul_i8, matmul_i8_simd};
 
 #[test]
 fn identity_matrix() {
@@ -144,3 +147,145 @@ fn zero_weights() {
 
     assert_eq!(output, vec![0, 0, 0, 0]);
 }
+
+#[test]
+fn matches_bpf_kernel() {
+    // Programs fall back to awm-kernel's packed-load matmul off the ER;
+    // both paths must produce identical accumulators
+    for (rows, cols) in [(1, 1), (3, 5), (7, 17), (16, 64), (33, 130)] {
+        let weights: Vec<i8> = (0..rows * cols)
+            .map(|i| ((i * 37 + 11) % 256) as i8)
+            .collect();
+        let input: Vec<i8> = (0..cols).map(|i| ((i * 5 + 3) % 256) as i8).collect();
+        let weight_bytes: Vec<u8> = weights.iter().map(|&w| w as u8).collect();
+
+        let mut native = vec![0i32; rows];
+        matmul_i8(&weights, &input, &mut native, rows, cols);
+
+        let mut bpf = vec![0i32; rows];
+        awm_kernel::matmul::matmul_i8(&weight_bytes, &input, &mut bpf, rows, cols);
+
+        assert_eq!(native, bpf, "{}x{} mismatch", rows, cols);
+    }
+}
+
+#[test]
+fn simd_matches_scalar() {
+    // Covers widths below, at and across the 16-column SIMD chunk
+    for (rows, cols) in [(1, 1), (3, 15), (4, 16), (7, 17), (33, 130), (8, 1024)] {
+        let weights: Vec<i8> = (0..rows * cols)
+            .map(|i| ((i * 37 + 11) % 256) as i8)
+            .collect();
+        let input: Vec<i8> = (0..cols).map(|i| ((i * 5 + 3) % 256) as i8).collect();
+
+        let mut scalar = vec![0i32; rows];
+        matmul_i8(&weights, &input, &mut scalar, rows, cols);
+
+        let mut simd = vec![0i32; rows];
+        matmul_i8_simd(&weights, &input, &mut simd, rows, cols);
+
+        assert_eq!(scalar, simd, "{}x{} mismatch", rows, cols);
+    }
+}
+
+#[test]
+fn simd_extremes() {
+    // -128 * -128 at every column: the widest products the i16 pairing sees
+    let cols = 1024;
+    let weights = vec![-128i8; cols];
+    let input = vec![-128i8; cols];
+    let mut output = vec![0i32; 1];
+
+    matmul_i8_simd(&weights, &input, &mut output, 1, cols);
+
+    assert_eq!(output[0], 16384 * cols as i32);
+}
+
+/// A runtime with three fixed VM regions and a CU budget.
+struct MockHost {
+    budget: u64,
+    weights: Vec<i8>,
+    input: Vec<i8>,
+    output: Vec<i32>,
+}
+
+const WEIGHTS_ADDR: u64 = 0x1000;
+const INPUT_ADDR: u64 = 0x2000;
+const OUTPUT_ADDR: u64 = 0x3000;
+
+#[derive(Debug, PartialEq)]
+enum MockError {
+    Core(SyscallError),
+    OutOfCompute,
+    AccessViolation(u64),
+}
+
+impl From<SyscallError> for MockError {
+    fn from(e: SyscallError) -> Self {
+        MockError::Core(e)
+    }
+}
+
+impl SyscallHost for MockHost {
+    type Error = MockError;
+
+    fn consume(&mut self, cu: u64) -> Result<(), MockError> {
+        self.budget = self.budget.checked_sub(cu).ok_or(MockError::OutOfCompute)?;
+        Ok(())
+    }
+
+    fn translate(&mut self, access: Access, addr: u64, len: u64) -> Result<u64, MockError> {
+        let (ptr, size) = match (access, addr) {
+            (Access::Load, WEIGHTS_ADDR) => (self.weights.as_ptr() as u64, self.weights.len()),
+            (Access::Load, INPUT_ADDR) => (self.input.as_ptr() as u64, self.input.len()),
+            (Access::Store, OUTPUT_ADDR) => (self.output.as_mut_ptr() as u64, self.output.len() * 4),
+            _ => return Err(MockError::AccessViolation(addr)),
+        };
+        if len > size as u64 {
+            return Err(MockError::AccessViolation(addr));
+        }
+        Ok(ptr)
+    }
+}
+
+#[test]
+fn syscall_core_charges_and_computes() {
+    // [[1,2],[3,4]] x [5,6] = [17, 39]
+    let mut host = MockHost {
+        budget: 1_000,
+        weights: vec![1, 2, 3, 4],
+        input: vec![5, 6],
+        output: vec![0; 2],
+    };
+    let r0 = matmul_i8_syscall(&mut host, WEIGHTS_ADDR, INPUT_ADDR, OUTPUT_ADDR, 2, 2);
+    assert_eq!(r0, Ok(0));
+    assert_eq!(host.output, vec![17, 39]);
+    assert_eq!(host.budget, 1_000 - matmul_cost(2, 2).unwrap());
+    assert_eq!(matmul_cost(2, 2), Ok(CU_BASE + 4));
+}
+
+#[test]
+fn syscall_core_rejects_before_writing() {
+    let mut host = MockHost {
+        budget: CU_BASE + 3,
+        weights: vec![1, 2, 3, 4],
+        input: vec![5, 6],
+        output: vec![-1; 2],
+    };
+    assert_eq!(
+        matmul_i8_syscall(&mut host, WEIGHTS_ADDR, INPUT_ADDR, OUTPUT_ADDR, 2, 2),
+        Err(MockError::OutOfCompute)
+    );
+
+    host.budget = 1_000;
+    // Output region too small for three rows
+    assert_eq!(
+        matmul_i8_syscall(&mut host, WEIGHTS_ADDR, INPUT_ADDR, OUTPUT_ADDR, 3, 1),
+        Err(MockError::AccessViolation(OUTPUT_ADDR))
+    );
+    assert_eq!(
+        matmul_i8_syscall(&mut host, WEIGHTS_ADDR, INPUT_ADDR, OUTPUT_ADDR, u64::MAX, 2),
+        Err(MockError::Core(SyscallError::DimensionsOverflow))
+    );
+    assert_eq!(host.output, vec![-1; 2]);
+}
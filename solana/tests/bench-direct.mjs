@@ -59,8 +59,76 @@ async function createDataAccount(size) {
   return account;
 }
 
-// Run an instruction and return CU consumed
-async function runAndMeasure(label, ix, cuLimit = 1_400_000) {
+// Results account every bench writes into (see BenchResults in the program)
+let resultsAccount;
+
+async function createResultsAccount() {
+  const results = Keypair.generate();
+  const ix = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: results.publicKey, isSigner: true, isWritable: true },
+      { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: disc("init_results"),
+  });
+  await sendAndConfirmTransaction(conn, new Transaction().add(ix), [wallet, results]);
+  return results.publicKey;
+}
+
+// BenchResults: disc(8) authority(32) op(1) dims(3×u32) cu(u64) cu_syscall(u64) checksum(i64)
+// slot(u64) runs(u32)
+async function readResults() {
+  const info = await conn.getAccountInfo(resultsAccount, "confirmed");
+  const d = info.data;
+  return {
+    op: d.readUInt8(40),
+    dims: [d.readUInt32LE(41), d.readUInt32LE(45), d.readUInt32LE(49)],
+    kernelCu: Number(d.readBigUInt64LE(53)),
+    syscallCu: Number(d.readBigUInt64LE(61)),
+    checksum: d.readBigInt64LE(69),
+    runs: d.readUInt32LE(85),
+  };
+}
+
+// Sweep table bench_sweep appends to (see SweepResults in the program)
+const OP_SWEEP_MATMUL = 9;
+const OP_SWEEP_SSM = 10;
+
+async function createSweepAccount() {
+  const sweep = Keypair.generate();
+  const ix = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: sweep.publicKey, isSigner: true, isWritable: true },
+      { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: disc("init_sweep"),
+  });
+  await sendAndConfirmTransaction(conn, new Transaction().add(ix), [wallet, sweep]);
+  return sweep.publicKey;
+}
+
+// SweepResults: disc(8) authority(32) len(u8) entries(32 × op(u8) dims(2×u32) cu(u64) checksum(i64))
+async function readSweep(sweep) {
+  const d = (await conn.getAccountInfo(sweep, "confirmed")).data;
+  return Array.from({ length: d.readUInt8(40) }, (_, i) => {
+    const o = 41 + i * 25;
+    return {
+      op: d.readUInt8(o),
+      dims: [d.readUInt32LE(o + 1), d.readUInt32LE(o + 5)],
+      cu: Number(d.readBigUInt64LE(o + 9)),
+      checksum: d.readBigInt64LE(o + 17),
+    };
+  });
+}
+
+// Run an instruction and return CU consumed: the whole transaction's, and
+// the kernel loop's alone as recorded in the results account
+async function runAndMeasure(label, ix, cuLimit = 1_400_000, remaining = []) {
+  ix.keys.push({ pubkey: resultsAccount, isSigner: false, isWritable: true }, ...remaining);
   const tx = new Transaction();
   // Request higher CU limit
   tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: cuLimit }));
@@ -75,7 +143,8 @@ async function runAndMeasure(label, ix, cuLimit = 1_400_000) {
       maxSupportedTransactionVersion: 0,
     });
     const cu = info?.meta?.computeUnitsConsumed ?? 0;
-    return { label, cu, success: true };
+    const { kernelCu, syscallCu, checksum } = await readResults();
+    return { label, cu, kernelCu, syscallCu, checksum, success: true };
   } catch (e) {
     const msg = e.message || String(e);
     if (msg.includes("exceeded") || msg.includes("computational budget")) {
@@ -159,6 +228,84 @@ async function benchSsm(dInner, dState) {
   return runAndMeasure(`ssm_step ${dInner}x${dState}`, ix);
 }
 
+// Sweep one kernel over `shapes`, `perTx` shapes per transaction, into a
+// fresh sweep table. Returns the table rows.
+async function benchSweep(op, shapes, perTx = 4) {
+  const size = Math.max(...shapes.map(([a, b]) =>
+    op === OP_SWEEP_MATMUL ? a * b + b : 1024 + 3 * a + a * b));
+  const account = await createDataAccount(size);
+  const sweep = await createSweepAccount();
+
+  for (let i = 0; i < shapes.length; i += perTx) {
+    const batch = shapes.slice(i, i + perTx);
+    const ix = new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys: [
+        { pubkey: account.publicKey, isSigner: false, isWritable: false },
+        { pubkey: sweep, isSigner: false, isWritable: true },
+      ],
+      data: Buffer.concat([
+        disc("bench_sweep"),
+        u8(op),
+        u32le(batch.length),
+        ...batch.flatMap(([a, b]) => [u32le(a), u32le(b)]),
+      ]),
+    });
+    const tx = new Transaction()
+      .add(ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }))
+      .add(ix);
+    try {
+      await sendAndConfirmTransaction(conn, tx, [wallet]);
+    } catch (e) {
+      console.log(`  sweep batch ${batch.map(s => s.join("x")).join(", ")}: ${(e.message || String(e)).slice(0, 120)}`);
+    }
+  }
+  return readSweep(sweep);
+}
+
+async function benchRmsnorm(n, weightScale = 256) {
+  const account = await createDataAccount(1024 + 2 * n);
+  const ix = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [{ pubkey: account.publicKey, isSigner: false, isWritable: false }],
+    data: Buffer.concat([disc("bench_rmsnorm"), u32le(n), u32le(weightScale)]),
+  });
+  return runAndMeasure(`rmsnorm ${n}`, ix);
+}
+
+async function benchRequantize(n, perChannel) {
+  const account = await createDataAccount(6 * n);
+  const ix = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [{ pubkey: account.publicKey, isSigner: false, isWritable: false }],
+    data: Buffer.concat([disc("bench_requantize"), u32le(n), u8(perChannel ? 1 : 0)]),
+  });
+  return runAndMeasure(`requantize_${perChannel ? "per_channel" : "per_tensor"} ${n}`, ix);
+}
+
+async function benchConv1d(dInner, kernelSize) {
+  const account = await createDataAccount(dInner * (2 * kernelSize));
+  const ix = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [{ pubkey: account.publicKey, isSigner: false, isWritable: false }],
+    data: Buffer.concat([disc("bench_conv1d"), u32le(dInner), u8(kernelSize)]),
+  });
+  return runAndMeasure(`conv1d ${dInner} k${kernelSize}`, ix);
+}
+
+const ACCESS_PATTERNS = ["sequential", "strided", "packed"];
+
+async function benchMemoryAccess(len, pattern, stride = 0) {
+  const account = await createDataAccount(len);
+  const ix = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [{ pubkey: account.publicKey, isSigner: false, isWritable: false }],
+    data: Buffer.concat([disc("bench_memory_access"), u32le(len), u8(pattern), u32le(stride)]),
+  });
+  const label = `memory_${ACCESS_PATTERNS[pattern]} ${len}${stride ? ` stride ${stride}` : ""}`;
+  return runAndMeasure(label, ix);
+}
+
 async function benchFullLayer(dModel, dInner, dState) {
   const weightSize = Math.min(dInner * 2 * dModel, 1_000_000);
   const stateSize = dModel + dInner * dState;
@@ -184,6 +331,46 @@ async function benchFullLayer(dModel, dInner, dState) {
   return runAndMeasure(`full_layer ${dModel}/${dInner}/${dState}`, ix);
 }
 
+// Whole-frame forward pass over an uploaded world-model manifest. The
+// hidden scratch account must be owned by this program so it can write.
+async function benchForwardPass(manifest, shards, maxLayers) {
+  const m = (await conn.getAccountInfo(manifest, "confirmed")).data;
+  // ModelManifestAccount: disc(8) name(32) version(2) d_model(2) d_inner(2) d_state(2) num_layers num_heads hidden_dtype
+  const dInner = m.readUInt16LE(44);
+  const dState = m.readUInt16LE(46);
+  const numLayers = m.readUInt8(48);
+  const elem = m.readUInt8(50) === 1 ? 2 : 1;
+  const size = numLayers * dInner * dState * elem;
+
+  const hidden = Keypair.generate();
+  const rent = await conn.getMinimumBalanceForRentExemption(size);
+  await sendAndConfirmTransaction(conn, new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: wallet.publicKey,
+      newAccountPubkey: hidden.publicKey,
+      space: size,
+      lamports: rent,
+      programId: PROGRAM_ID,
+    })
+  ), [wallet, hidden]);
+
+  const ix = new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: manifest, isSigner: false, isWritable: false },
+      { pubkey: hidden.publicKey, isSigner: false, isWritable: true },
+      // results is pushed by runAndMeasure; shards follow it
+    ],
+    data: Buffer.concat([disc("bench_forward_pass"), u8(maxLayers)]),
+  });
+  return runAndMeasure(
+    `forward_pass ${maxLayers || numLayers} layers`,
+    ix,
+    1_400_000,
+    shards.map(pubkey => ({ pubkey, isSigner: false, isWritable: false })),
+  );
+}
+
 // ── Main ────────────────────────────────────────────────────────────────
 
 async function main() {
@@ -194,6 +381,9 @@ async function main() {
   const bal = await conn.getBalance(wallet.publicKey);
   console.log("Wallet:", wallet.publicKey.toBase58(), `(${bal / 1e9} SOL)\n`);
 
+  resultsAccount = await createResultsAccount();
+  console.log("Results account:", resultsAccount.toBase58(), "\n");
+
   const results = [];
 
   // Matmul
@@ -253,12 +443,89 @@ async function main() {
     console.log(`  ${res.label}: ${res.cu.toLocaleString()} CU${res.exceeded ? " [EXCEEDED]" : ""}${res.error ? ` [ERROR: ${res.error}]` : ""}`);
   }
 
+  // Normalization and requantization (d_model, 2*d_inner and d_model sized)
+  console.log("\n── RMSNorm / Requantize ──");
+  for (const n of [512, 1024, 2048]) {
+    for (const res of [await benchRmsnorm(n), await benchRequantize(n, true), await benchRequantize(n, false)]) {
+      results.push(res);
+      const perElem = res.cu > 0 ? (res.cu / n).toFixed(1) : "N/A";
+      console.log(`  ${res.label}: ${res.cu.toLocaleString()} CU (${perElem} CU/element)${res.error ? ` [ERROR: ${res.error}]` : ""}`);
+    }
+  }
+
+  // Depthwise causal conv1d step
+  console.log("\n── Conv1d (depthwise, one step) ──");
+  for (const k of [2, 3, 4]) {
+    for (const d of [512, 1024, 2048]) {
+      const res = await benchConv1d(d, k);
+      results.push(res);
+      const perChannel = res.cu > 0 ? (res.cu / d).toFixed(1) : "N/A";
+      console.log(`  ${res.label}: ${res.cu.toLocaleString()} CU (${perChannel} CU/channel)${res.error ? ` [ERROR: ${res.error}]` : ""}`);
+    }
+  }
+
+  // Access patterns over one 64KB region (a 128x512 weight block): row
+  // order, column order, and row order with u32 loads
+  console.log("\n── Memory Access Patterns ──");
+  const accessLen = 65_536;
+  for (const [pattern, stride] of [[0, 0], [1, 512], [1, 64], [2, 0]]) {
+    const res = await benchMemoryAccess(accessLen, pattern, stride);
+    results.push(res);
+    const perByte = res.cu > 0 ? (res.cu / accessLen).toFixed(2) : "N/A";
+    console.log(`  ${res.label}: ${res.cu.toLocaleString()} CU (${perByte} CU/byte)${res.exceeded ? " [EXCEEDED]" : ""}${res.error ? ` [ERROR: ${res.error}]` : ""}`);
+  }
+
   // Full layer
   console.log("\n── Full Mamba2 Layer ──");
   const fullRes = await benchFullLayer(512, 1024, 16);
   results.push(fullRes);
   console.log(`  ${fullRes.label}: ${fullRes.cu.toLocaleString()} CU${fullRes.exceeded ? " [EXCEEDED]" : ""}${fullRes.error ? ` [ERROR: ${fullRes.error}]` : ""}`);
 
+  // Whole frame of a real model: AWM_MANIFEST=<pubkey> AWM_SHARDS=<pk,pk,...>
+  // [AWM_LAYERS=n] node tests/bench-direct.mjs
+  if (process.env.AWM_MANIFEST) {
+    console.log("\n── Forward Pass (encode + all layers + decode) ──");
+    const manifest = new PublicKey(process.env.AWM_MANIFEST);
+    const shards = (process.env.AWM_SHARDS ?? "").split(",").filter(Boolean).map(k => new PublicKey(k));
+    const res = await benchForwardPass(manifest, shards, Number(process.env.AWM_LAYERS ?? 0));
+    results.push(res);
+    console.log(`  ${res.label}: ${res.cu.toLocaleString()} CU${res.exceeded ? " [EXCEEDED]" : ""}${res.error ? ` [ERROR: ${res.error}]` : ""}`);
+  }
+
+  // BPF kernel vs sol_matmul_i8 on the same matmul. Needs the program built
+  // with er-syscall and an ER validator: AWM_SYSCALL=1 node tests/bench-direct.mjs
+  if (process.env.AWM_SYSCALL) {
+    console.log("\n── Syscall vs BPF matmul ──");
+    for (const [r, c] of [[64, 64], [512, 512], [2048, 512]]) {
+      const res = await benchMatmulVariant(r, c, "bench_matmul_syscall");
+      if (res.success) {
+        const speedup = res.syscallCu > 0 ? (res.kernelCu / res.syscallCu).toFixed(1) : "N/A";
+        console.log(`  ${res.label}: bpf ${res.kernelCu.toLocaleString()} CU, syscall ${res.syscallCu.toLocaleString()} CU (${speedup}x)`);
+      } else {
+        console.log(`  ${res.label}: ${res.exceeded ? "[EXCEEDED]" : `[ERROR: ${res.error}]`}`);
+      }
+    }
+  }
+
+  // CU tables for choosing model dims
+  console.log("\n── Sweep: matmul_i8 (rows x cols) ──");
+  const matmulShapes = [128, 256, 512, 1024].flatMap(c => [[c, c], [2 * c, c]]);
+  for (const row of await benchSweep(OP_SWEEP_MATMUL, matmulShapes, 1)) {
+    const macs = row.dims[0] * row.dims[1];
+    console.log(`  ${row.dims.join("x")}: ${row.cu.toLocaleString()} CU (${(macs / row.cu).toFixed(2)} MACs/CU)`);
+  }
+  console.log("\n── Sweep: selective_scan_step (d_inner x d_state) ──");
+  const ssmShapes = [256, 512, 1024].flatMap(d => [[d, 8], [d, 16]]);
+  for (const row of await benchSweep(OP_SWEEP_SSM, ssmShapes, 2)) {
+    console.log(`  ${row.dims.join("x")}: ${row.cu.toLocaleString()} CU`);
+  }
+
+  // Kernel-only CU from the results account (excludes dispatch and logging)
+  console.log("\n── Kernel CU (results account) ──");
+  for (const r of results.filter(r => r.success)) {
+    console.log(`  ${r.label}: ${r.kernelCu.toLocaleString()} CU (checksum ${r.checksum})`);
+  }
+
   // Projections
   console.log("\n═══ Projections ═══");
   if (fullRes.cu > 0 && fullRes.success) {
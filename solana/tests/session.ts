@@ -16,6 +16,12 @@ import {
   HIDDEN_STATE_PROGRAM_ID,
   INPUT_BUFFER_PROGRAM_ID,
   FRAME_LOG_PROGRAM_ID,
+  SETTLE_SESSION_PROGRAM_ID,
+  SESSION_RESULT_PROGRAM_ID,
+  PLAYER_PROFILE_PROGRAM_ID,
+  AGGREGATE_STATS_PROGRAM_ID,
+  SESSION_STATS_PROGRAM_ID,
+  PLAYER_STATS_PROGRAM_ID,
   deserializeSessionState,
 } from "../client/src/session";
 import { SessionStatus } from "../client/src/state";
@@ -51,6 +57,7 @@ describe("BOLT ECS session lifecycle", () => {
   let hiddenStatePda: PublicKey;
   let inputBufferPda: PublicKey;
   let frameLogPda: PublicKey;
+  const profileEntities: PublicKey[] = [];
 
   const player1 = Keypair.generate();
   const player2 = Keypair.generate();
@@ -152,6 +159,10 @@ describe("BOLT ECS session lifecycle", () => {
         d_inner: 768,
         d_state: 64,
         num_layers: 4,
+        emit_frames: false,
+        tick_rate: 60,
+        kind: 0,
+        archived_frames: 0,
       },
     });
     await provider.sendAndConfirm(result.transaction, [player1]);
@@ -196,6 +207,10 @@ describe("BOLT ECS session lifecycle", () => {
         d_inner: 0,
         d_state: 0,
         num_layers: 0,
+        emit_frames: false,
+        tick_rate: 0,
+        kind: 0,
+        archived_frames: 0,
       },
     });
     await provider.sendAndConfirm(result.transaction, [player2]);
@@ -206,13 +221,14 @@ describe("BOLT ECS session lifecycle", () => {
     expect(session.status).to.equal(SessionStatus.Active);
     expect(session.player2).to.equal(player2.publicKey.toBase58());
 
-    // Starting positions (fixed-point ×256)
-    expect(session.players[0].x).to.equal(-30 * 256);
-    expect(session.players[1].x).to.equal(30 * 256);
+    // Starting positions (fixed-point ×256): seed-derived, mirrored about
+    // the stage center and facing each other
+    expect(session.players[0].x).to.equal(-session.players[1].x);
+    expect(session.players[0].x).to.not.equal(0);
     expect(session.players[0].y).to.equal(0);
     expect(session.players[1].y).to.equal(0);
-    expect(session.players[0].facing).to.equal(1);
-    expect(session.players[1].facing).to.equal(0);
+    expect(session.players[0].facing).to.equal(session.players[0].x < 0 ? 1 : 0);
+    expect(session.players[1].facing).to.equal(session.players[1].x < 0 ? 1 : 0);
     expect(session.players[0].onGround).to.equal(1);
     expect(session.players[1].onGround).to.equal(1);
     expect(session.players[0].jumpsLeft).to.equal(2);
@@ -310,6 +326,10 @@ describe("BOLT ECS session lifecycle", () => {
         d_inner: 0,
         d_state: 0,
         num_layers: 0,
+        emit_frames: false,
+        tick_rate: 0,
+        kind: 0,
+        archived_frames: 0,
       },
     });
     await provider.sendAndConfirm(result.transaction, [player1]);
@@ -318,4 +338,125 @@ describe("BOLT ECS session lifecycle", () => {
     const session = deserializeSessionState(account!.data as Buffer);
     expect(session.status).to.equal(SessionStatus.Ended);
   });
+
+  it("SETTLE: settle_session records the result and marks the session settled", async () => {
+    // The result lives on the session entity; each profile on its own entity
+    const initResult = await InitializeComponent({
+      payer: player1.publicKey,
+      entity: entityPda,
+      componentId: SESSION_RESULT_PROGRAM_ID,
+    });
+    await provider.sendAndConfirm(initResult.transaction, [player1]);
+    const sessionResultPda = initResult.componentPda;
+
+    for (const player of [player1, player2]) {
+      const addEntity = await AddEntity({
+        payer: player.publicKey,
+        world: worldPda,
+        connection: provider.connection,
+      });
+      await provider.sendAndConfirm(addEntity.transaction, [player]);
+      const initProfile = await InitializeComponent({
+        payer: player.publicKey,
+        entity: addEntity.entityPda,
+        componentId: PLAYER_PROFILE_PROGRAM_ID,
+      });
+      await provider.sendAndConfirm(initProfile.transaction, [player]);
+      profileEntities.push(addEntity.entityPda);
+    }
+
+    const result = await ApplySystem({
+      authority: player1.publicKey,
+      systemId: SETTLE_SESSION_PROGRAM_ID,
+      world: worldPda,
+      entities: [
+        {
+          entity: entityPda,
+          components: [
+            { componentId: SESSION_STATE_PROGRAM_ID },
+            { componentId: FRAME_LOG_PROGRAM_ID },
+            { componentId: SESSION_RESULT_PROGRAM_ID },
+          ],
+        },
+        ...profileEntities.map((entity) => ({
+          entity,
+          components: [{ componentId: PLAYER_PROFILE_PROGRAM_ID }],
+        })),
+      ],
+    });
+    await provider.sendAndConfirm(result.transaction, [player1]);
+
+    const account = await provider.connection.getAccountInfo(sessionStatePda, "confirmed");
+    const session = deserializeSessionState(account!.data as Buffer);
+    expect(session.status).to.equal(SessionStatus.Settled);
+
+    // SessionResult: disc(8) session(32) frame(u32) outcome(u8) ...
+    // Ended at frame 0, so the match was never played
+    const settled = (await provider.connection.getAccountInfo(sessionResultPda, "confirmed"))!.data;
+    expect(new PublicKey(settled.subarray(8, 40)).toBase58()).to.equal(sessionStatePda.toBase58());
+    expect(settled.readUInt32LE(40)).to.equal(0);
+    expect(settled.readUInt8(44)).to.equal(0); // OUTCOME_UNPLAYED
+  });
+
+  it("AGGREGATE: aggregate_stats folds a settled session once", async () => {
+    const initStats = await InitializeComponent({
+      payer: player1.publicKey,
+      entity: entityPda,
+      componentId: SESSION_STATS_PROGRAM_ID,
+    });
+    await provider.sendAndConfirm(initStats.transaction, [player1]);
+    const sessionStatsPda = initStats.componentPda;
+
+    const playerStatsPdas: PublicKey[] = [];
+    for (const [i, player] of [player1, player2].entries()) {
+      const initPlayerStats = await InitializeComponent({
+        payer: player.publicKey,
+        entity: profileEntities[i],
+        componentId: PLAYER_STATS_PROGRAM_ID,
+      });
+      await provider.sendAndConfirm(initPlayerStats.transaction, [player]);
+      playerStatsPdas.push(initPlayerStats.componentPda);
+    }
+
+    const aggregate = () =>
+      ApplySystem({
+        authority: player1.publicKey,
+        systemId: AGGREGATE_STATS_PROGRAM_ID,
+        world: worldPda,
+        entities: [
+          {
+            entity: entityPda,
+            components: [
+              { componentId: SESSION_STATE_PROGRAM_ID },
+              { componentId: SESSION_RESULT_PROGRAM_ID },
+              { componentId: SESSION_STATS_PROGRAM_ID },
+            ],
+          },
+          ...profileEntities.map((entity) => ({
+            entity,
+            components: [{ componentId: PLAYER_STATS_PROGRAM_ID }],
+          })),
+        ],
+      });
+    await provider.sendAndConfirm((await aggregate()).transaction, [player1]);
+
+    // SessionStats: disc(8) damage_dealt(2×u32) hits_landed(2×u32) kos(2) folded(u8)
+    const folded = (await provider.connection.getAccountInfo(sessionStatsPda, "confirmed"))!.data;
+    expect(folded.readUInt8(26)).to.equal(1);
+
+    // Unplayed, so neither wallet's stats were claimed
+    for (const pda of playerStatsPdas) {
+      const stats = (await provider.connection.getAccountInfo(pda, "confirmed"))!.data;
+      expect(new PublicKey(stats.subarray(8, 40)).equals(PublicKey.default)).to.be.true;
+    }
+
+    // A session only counts once
+    let failed = false;
+    try {
+      await provider.sendAndConfirm((await aggregate()).transaction, [player1]);
+    } catch {
+      failed = true;
+    }
+    expect(failed).to.be.true;
+  });
 });
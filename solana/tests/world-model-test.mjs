@@ -117,23 +117,29 @@ async function sendIx(label, ix, signers, cuLimit = 400_000) {
 // ── Account sizes (8-byte discriminator + struct fields) ─────────────────
 
 // ModelManifestAccount size (approximate — Anchor adds 8-byte discriminator)
-// Fields: 32 + 2 + 2*4 + 2*2 + 1 + 1 + 1 + 32*4 + 4*4 + 16*2 + 16*2 + 1024 + 1 + 2 + 1 + 2 + 32 + 1 + 4 + 4
-// = ~1350 bytes. Round up generously.
-const MANIFEST_SIZE = 1500;
+// Fields: 32 + 2 + 2*3 + 1 + 1 + 1 + 1 + 1 + 32*4 + 4*4 + 16*5*10 (tensor table) + 16*2 + 16*2 + 1024
+//         + 202 (encoder spec) + 1 + 2 + 1 + 2 + 32 + 32 + 1 + 1 + 32 + 32 + 8 + 32 + 32 + 8 + 32 + 4 + 4 + 128 + 32 + 20
+// = ~2720 bytes. Round up generously.
+const MANIFEST_SIZE = 3072;
 
 // WeightAccount header: 8 + 1 + 4 + 32 + 1 + 32 + 4 = 82
 const WEIGHT_HEADER = 82;
 
-// SessionStateAccount: 8 + 1 + 4 + 4 + 32 + 32 + 1 + (2 * PlayerState) + 32 + 8 + 8 + 8
-// PlayerState: 4 + 4 + 2 + 2 + 2*5 + 2 + 1 + 1 + 1 + 1 + 2 + 1 + 1 = 32 bytes
-const SESSION_SIZE = 300;
+// SessionStateAccount (zero-copy, repr(C) — see the layout asserts in state.rs):
+//   8 (disc) + 8 (status, stage, padding) + 4 * 8 (timestamps, seed, fees) + 5 * 32 (pubkeys)
+//   + 2 * 32 (PlayerState) + 4 * 36 (cranker tallies) + 2 * 32 (commitments) + 4 + 4 (frame, max_frames)
+// = 488 bytes
+const SESSION_SIZE = 488;
+const SESSION_FRAME_OFFSET = 8 + 472;
+const SESSION_PLAYERS_OFFSET = 8 + 200;
 
-// InputBufferAccount: 8 + 4 + 2*(8 bytes ControllerInput) + 1 + 1 = 30
-const INPUT_BUFFER_SIZE = 40;
+// InputBufferAccount (zero-copy): 8 + 4 + 2*(8 bytes ControllerInput) + 1 + 1 + 2 (padding) = 32
+const INPUT_BUFFER_SIZE = 32;
 
-// Hidden state: header (16) + data (num_layers * d_inner * d_state)
-// For test: 2 layers, d_inner=8, d_state=4 = 64 bytes of data
-const HIDDEN_STATE_SIZE = 16 + 64;
+// Hidden state: header (16) + data (num_layers * d_inner * d_state * elem_size)
+// For test: 2 layers, d_inner=128, d_state=4 = 1024 bytes of data
+// (must match the manifest — run_inference rejects mismatched hidden state)
+const HIDDEN_STATE_SIZE = 16 + 2 * 128 * 4;
 
 // ── Test ─────────────────────────────────────────────────────────────────
 
@@ -174,8 +180,17 @@ async function main() {
   // Build init_manifest instruction data
   const modelName = Buffer.alloc(32);
   modelName.write("test-mamba2-v1");
-  const lutData = Buffer.alloc(1024); // Dummy LUTs
-  for (let i = 0; i < 1024; i++) lutData[i] = i & 0xFF;
+  // LUTs: [silu | softplus | rsqrt | exp_neg], 256 entries each.
+  // init_manifest rejects tables that aren't shaped like the real functions.
+  const lutData = Buffer.alloc(1024);
+  const clampI8 = (v) => Math.max(-128, Math.min(127, Math.trunc(v)));
+  for (let i = 0; i < 256; i++) {
+    const xs = ((i << 24) >> 24) / 16; // signed index → [-8, 8)
+    lutData.writeInt8(clampI8((xs / (1 + Math.exp(-xs))) * 16 + 0.5), i);
+    lutData.writeInt8(clampI8(Math.log(1 + Math.exp(xs)) * 32), 256 + i);
+    lutData[512 + i] = Math.min(255, Math.trunc(32 / Math.sqrt(Math.max(i, 1) / 32)));
+    lutData[768 + i] = Math.trunc(Math.exp(-i / 32) * 255);
+  }
 
   const initData = Buffer.concat([
     disc("init_manifest"),
@@ -186,6 +201,8 @@ async function main() {
     u16le(4),           // d_state: u16
     u8buf(2),           // num_layers: u8
     u8buf(4),           // num_heads: u8
+    u8buf(0),           // hidden_dtype: u8 (0 = INT8)
+    u8buf(1),           // expected_shards: u8
     lutData,            // luts: [u8; 1024]
     u8buf(12),          // num_continuous: u8
     u16le(400),         // num_action_states: u16
@@ -280,8 +297,53 @@ async function main() {
     u8buf(0),            // character: u8 (Fox = 0)
     u32le(28800),        // max_frames: u32
     u64le(42),           // seed: u64
+    u8buf(0),            // allow_deprecated: bool
+    u8buf(60),           // tick_rate: u8 (fps)
   ]);
 
+  const [treasury] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury"), manifestKp.publicKey.toBuffer()],
+    PROGRAM_ID
+  );
+
+  // create_session requires the protocol fee schedule; create a free one
+  // on a fresh validator. Only the upgrade authority (the deployer, our
+  // local keypair) may.
+  const [feeSchedule] = PublicKey.findProgramAddressSync(
+    [Buffer.from("fee_schedule")],
+    PROGRAM_ID
+  );
+  const [programData] = PublicKey.findProgramAddressSync(
+    [PROGRAM_ID.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+  if (!(await conn.getAccountInfo(feeSchedule))) {
+    const initFeeIx = new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys: [
+        { pubkey: feeSchedule, isSigner: false, isWritable: true },
+        { pubkey: player1.publicKey, isSigner: true, isWritable: true },
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: programData, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.concat([
+        disc("init_fee_schedule"),
+        pubkeyBuf(player1.publicKey), // protocol_treasury: Pubkey
+        u64le(0),                     // session_fee: u64
+        u16le(5000),                  // author_bps: u16
+        u16le(2000),                  // cranker_bps: u16
+        u16le(3000),                  // protocol_bps: u16
+      ]),
+    });
+    await sendIx("init_fee_schedule", initFeeIx, [player1]);
+  }
+
+  const [meter] = PublicKey.findProgramAddressSync(
+    [Buffer.from("meter"), sessionKp.publicKey.toBuffer()],
+    PROGRAM_ID
+  );
+
   const createSessionIx = new TransactionInstruction({
     programId: PROGRAM_ID,
     keys: [
@@ -290,6 +352,16 @@ async function main() {
       { pubkey: inputBufKp.publicKey, isSigner: false, isWritable: true },
       { pubkey: manifestKp.publicKey, isSigner: false, isWritable: false },
       { pubkey: player1.publicKey, isSigner: true, isWritable: true },
+      { pubkey: treasury, isSigner: false, isWritable: true },
+      { pubkey: feeSchedule, isSigner: false, isWritable: false },
+      { pubkey: meter, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // cartridge_token: None (ungated)
+      { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // gate_token: None (ungated)
+      { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // fee_mint: None (lamport royalty)
+      { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // fee_token: None
+      { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // treasury_fee_token: None
+      { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // token_program: None
     ],
     data: createSessionData,
   });
@@ -382,6 +454,8 @@ async function main() {
         { pubkey: inputBufKp.publicKey, isSigner: false, isWritable: true },
         { pubkey: manifestKp.publicKey, isSigner: false, isWritable: false },
         { pubkey: weightKp.publicKey, isSigner: false, isWritable: false },
+        { pubkey: player1.publicKey, isSigner: true, isWritable: false }, // cranker
+        { pubkey: meter, isSigner: false, isWritable: true },
       ],
       data: inferData,
     });
@@ -397,15 +471,14 @@ async function main() {
     const data = sessionData.data;
     // Skip 8-byte discriminator
     const status = data[8];
-    const frame = data.readUInt32LE(9);
+    const frame = data.readUInt32LE(SESSION_FRAME_OFFSET);
     console.log(`  Status: ${status} (expected: ${STATUS_ACTIVE} = ACTIVE)`);
     console.log(`  Frame: ${frame} (expected: 3)`);
 
-    // Player 1 x position (offset: 8 + 1 + 4 + 4 + 32 + 32 + 1 = 82, then i32)
-    const p1_x = data.readInt32LE(82);
-    // Player 2 starts after player 1 state
-    // PlayerState is ~32 bytes via Borsh serialization
-    console.log(`  Player 1 x: ${p1_x} (fixed-point, should be > initial -7680)`);
+    // Player 1 x position (first field of players[0])
+    const p1_x = data.readInt32LE(SESSION_PLAYERS_OFFSET);
+    // Player 2 starts 32 bytes later (PlayerState is repr(C), no padding)
+    console.log(`  Player 1 x: ${p1_x} (fixed-point, moved from its seed-derived spawn)`);
 
     if (frame === 3) {
       console.log("\n  ✓ All 3 frames processed successfully!");